@@ -1,6 +1,7 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::io::stdout;
 use std::panic;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use anyhow::Result;
@@ -10,26 +11,39 @@ use crossterm::{
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
+use hyper_util::client::proxy::matcher::Matcher;
+use ratatui::layout::Rect;
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::Line;
 use ratatui::widgets::{Block, Borders};
 use ratatui::{backend::CrosstermBackend, Terminal};
 use reqwest::Client;
 use serde_json::Value;
-use tokio::sync::mpsc;
-use tui_textarea::{Input, TextArea};
+use tokio::sync::{mpsc, oneshot};
+use tui_textarea::{CursorMove, Input, TextArea};
 use uuid::Uuid;
 
 use crate::clipboard::ClipboardProvider;
-use crate::config::{self, Config};
+use crate::config::{self, Config, ConfigProvenance};
+use crate::encoding::Charset;
+use crate::input::{self, Action, InputContext, Overlay};
+use crate::notify::{Level, Notifications};
 use crate::perf;
 use crate::storage::{
-    self, CollectionStore, NodeKind, PostmanHeader, PostmanItem, PostmanRequest, ProjectInfo,
-    ProjectTree, TreeNode,
+    self, CollectionStore, CompressSetting, CursorPosition, HttpVersionSetting, ItemTag, LastRun,
+    ChangeEntry, ChangeKind, LockStatus, NodeKind,
+    PollSetting, PollUntil, PostmanHeader, PostmanItem, PostmanRequest, ProjectInfo, ProjectTree,
+    ProxySetting, RedirectSetting, RequestPosition, RequestSettings, SchemaSetting, SendStat,
+    TreeNode,
 };
-use crate::storage::environment::{self, Environment};
+use crate::storage::{archive, openapi, sample};
+use crate::storage::environment::{self, EnvScope, Environment};
 use crate::vim::{Transition, Vim, VimMode};
-use crate::{http, ui};
+use crate::chain::{self, ResponseStore};
+use crate::{audit, cors, runner, snippets};
+use crate::util::{codec, editor, format, pager};
+use crate::schema::{self, SchemaCheck};
+use crate::{diff, html, http, http1, json_path, raw_http, sendlog, ui};
 
 #[derive(Debug, Clone, Default)]
 pub enum ResponseStatus {
@@ -37,7 +51,7 @@ pub enum ResponseStatus {
     Empty,
     Loading,
     Success(ResponseData),
-    Error(String),
+    Error(http::RequestError),
     Cancelled,
 }
 
@@ -80,6 +94,33 @@ fn request_tab_from_str(value: &str) -> RequestTab {
     }
 }
 
+/// A path from request settings (TLS files, schemas): `~` expanded, and
+/// relative paths taken from the project root so they work for everyone
+/// the project is shared with.
+fn project_path(path: &str) -> std::path::PathBuf {
+    let path = config::expand_tilde(std::path::Path::new(path.trim()));
+    match storage::find_project_root() {
+        Some(root) if path.is_relative() => root.join(path),
+        _ => path,
+    }
+}
+
+/// `ui.default_request_tab`; validation has made sure it names a tab.
+fn default_request_tab(config: &Config) -> RequestTab {
+    match config.ui.default_request_tab.as_str() {
+        "auth" => RequestTab::Auth,
+        "body" => RequestTab::Body,
+        _ => RequestTab::Headers,
+    }
+}
+
+fn default_response_tab(config: &Config) -> ResponseTab {
+    match config.ui.default_response_tab.as_str() {
+        "headers" => ResponseTab::Headers,
+        _ => ResponseTab::Body,
+    }
+}
+
 fn request_tab_to_str(value: RequestTab) -> &'static str {
     match value {
         RequestTab::Headers => "Headers",
@@ -92,15 +133,48 @@ fn request_tab_to_str(value: RequestTab) -> &'static str {
 pub struct ResponseData {
     pub status: u16,
     pub status_text: String,
+    /// Every header received, duplicates included. Names are lowercase and
+    /// grouped in the order each first arrived; repeated values keep their
+    /// order.
     pub headers: Vec<(String, String)>,
     pub body: String,
     pub duration_ms: u64,
+    /// `Content-Encoding` as received, e.g. "gzip".
+    pub content_encoding: Option<String>,
+    /// Body size on the wire, before decompression.
+    pub raw_size: usize,
+    /// Body size after decompression.
+    pub decoded_size: usize,
+    /// Decoded body bytes, kept verbatim for saving to disk.
+    pub bytes: Vec<u8>,
+    /// Set when the body isn't displayable text; `body` is empty then.
+    pub is_binary: bool,
+    /// Redirects followed before the final response, in order.
+    pub redirects: Vec<http::RedirectHop>,
+    /// URL of the final response after any redirects.
+    pub final_url: String,
+    /// The download was cancelled; `bytes` holds only what arrived.
+    pub partial: bool,
+    /// What `body` was decoded from.
+    pub charset: Charset,
+    /// `body` has U+FFFD where bytes weren't valid in `charset`.
+    pub lossy: bool,
+    /// Set when the request body was sent gzipped.
+    pub compression: Option<http::BodyCompression>,
+    /// What was cleaned up in the request before it went out.
+    pub normalized: http::Normalized,
+    /// Set when the response has no body by definition; nothing was read.
+    pub no_body: Option<http::NoBody>,
+    /// The HTTP version the response came over, e.g. "HTTP/2"; unset for
+    /// saved examples.
+    pub version: Option<&'static str>,
+    /// A saved example shown in place of a response.
+    pub example: bool,
 }
 
 fn is_json_like(headers: &[(String, String)], body: &str) -> bool {
-    let has_json_content_type = headers.iter().any(|(k, v)| {
-        k.eq_ignore_ascii_case("content-type") && v.to_ascii_lowercase().contains("application/json")
-    });
+    let has_json_content_type = http::header_value(headers, "content-type")
+        .is_some_and(|v| v.to_ascii_lowercase().contains("application/json"));
     if has_json_content_type {
         return true;
     }
@@ -109,14 +183,15 @@ fn is_json_like(headers: &[(String, String)], body: &str) -> bool {
         || (trimmed.starts_with('[') && trimmed.ends_with(']'))
 }
 
-fn format_json_if_possible(headers: &[(String, String)], body: &str) -> String {
+fn format_json_if_possible(
+    headers: &[(String, String)],
+    body: &str,
+    style: &format::JsonStyle,
+) -> String {
     if !is_json_like(headers, body) {
         return body.to_string();
     }
-    match serde_json::from_str::<Value>(body) {
-        Ok(value) => serde_json::to_string_pretty(&value).unwrap_or_else(|_| body.to_string()),
-        Err(_) => body.to_string(),
-    }
+    format::pretty_json(body, style).unwrap_or_else(|| body.to_string())
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -269,7 +344,7 @@ pub enum ApiKeyLocation {
     QueryParam,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum AuthField {
     #[default]
     AuthType,
@@ -427,6 +502,28 @@ pub struct FocusState {
     pub kv_focus: KvFocus,
 }
 
+/// Scroll position and filter of the `?` cheat sheet.
+#[derive(Debug, Clone)]
+pub struct HelpOverlay {
+    pub scroll: u16,
+    /// Rows visible at the last draw, used as the PgUp/PgDn step.
+    pub page: u16,
+    pub filter: TextInput,
+    /// Keys go to the filter input instead of scrolling.
+    pub filtering: bool,
+}
+
+impl Default for HelpOverlay {
+    fn default() -> Self {
+        Self {
+            scroll: 0,
+            page: 10,
+            filter: TextInput::new(String::new()),
+            filtering: false,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TextInput {
     pub value: String,
@@ -472,3136 +569,10466 @@ impl TextInput {
             self.cursor += 1;
         }
     }
+
+    /// Deletes back to the start of the previous word, like Ctrl+W in a shell.
+    pub fn delete_word(&mut self) {
+        let before = self.value[..self.cursor].trim_end();
+        let start = before
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map(|(i, c)| i + c.len_utf8())
+            .unwrap_or(0);
+        self.value.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum SidebarPopup {
-    Add(TextInput),
+    Add {
+        input: TextInput,
+        /// Templates offered for the new request.
+        templates: Vec<(Uuid, String)>,
+        /// 0 is a blank request; otherwise an index into `templates` plus one.
+        template: usize,
+    },
     Rename(TextInput),
+    /// Rename typed over the selected row instead of in a popup.
+    RenameInline(TextInput),
     Search(TextInput),
     ProjectSwitch { index: usize },
+    /// Where to write the project archive of `project_id`; `notes` puts
+    /// the user's request notes in it too.
+    ArchiveExport { project_id: Uuid, input: TextInput, notes: bool },
+    /// Path of a project archive to import into this project root.
+    ArchiveImport(TextInput),
+    /// Starting a run of the requests under `target`, with an optional
+    /// data file; `export` writes the responses under `runs/` and
+    /// `parallel` is how many to send at once, when not in order.
+    RunSetup { target: Uuid, input: TextInput, export: bool, parallel: Option<usize> },
     Move { index: usize, candidates: Vec<Uuid> },
     DeleteConfirm,
+    /// Asks again before changing a locked item, or one holding locked
+    /// items.
+    LockedConfirm(LockedAction),
+    /// Picking a [`SidebarFilter`]; `index` is into [`SidebarFilter::choices`].
+    Filter { index: usize },
+    /// Editing the tags of `id`, as `name:color, …`.
+    Tags { id: Uuid, input: TextInput },
 }
 
-#[derive(Debug, Clone)]
-pub struct SidebarState {
-    pub selection_id: Option<Uuid>,
-    pub expanded: HashSet<Uuid>,
-    pub search_query: String,
-    pub popup: Option<SidebarPopup>,
+/// When `collection.json` was last written, if it can be told.
+fn collection_modified() -> Option<std::time::SystemTime> {
+    let path = storage::collection_path()?;
+    std::fs::metadata(path).and_then(|meta| meta.modified()).ok()
 }
 
-#[derive(Debug, Clone)]
-pub struct SidebarLine {
-    pub id: Uuid,
-    pub prefix: String,
-    pub marker: String,
-    pub label: String,
-    pub kind: NodeKind,
-    pub method: Option<Method>,
+/// The text of a visual selection from `start` up to and including the
+/// character at `end`, as `y` copies it. A selection ending past the last
+/// character of a line doesn't take its line break.
+fn inclusive_selection(lines: &[String], start: (usize, usize), end: (usize, usize)) -> String {
+    let mut text = String::new();
+    let rows = lines.iter().enumerate().take(end.0 + 1).skip(start.0);
+    for (row, line) in rows {
+        let from = if row == start.0 { start.1 } else { 0 };
+        let to = if row == end.0 { end.1 + 1 } else { usize::MAX };
+        if row > start.0 {
+            text.push('\n');
+        }
+        text.extend(line.chars().skip(from).take(to.saturating_sub(from)));
+    }
+    text
 }
 
-struct SidebarCache {
-    lines: Vec<SidebarLine>,
-    search_lines: Vec<SidebarLine>,
-    lines_dirty: bool,
-    search_dirty: bool,
-    search_query: String,
+/// Custom methods kept in [`App::recent_methods`].
+const RECENT_METHODS_MAX: usize = 5;
+
+/// Changelog entries the changelog popup lists.
+const CHANGELOG_SHOWN: usize = 200;
+
+/// Sends of each request kept for its timing history.
+const SEND_HISTORY_MAX: usize = 30;
+
+/// How long quitting may take before "Saving…" is shown.
+const SHUTDOWN_INDICATOR_DELAY: Duration = Duration::from_millis(100);
+
+/// State writes the panic hook still attempts: filled while a debounced
+/// write is held back and emptied once it is made.
+#[derive(Default)]
+struct PanicFlush {
+    session: Option<(String, storage::SessionState)>,
+    ui_state: Option<storage::UiState>,
 }
 
-impl SidebarCache {
-    fn new() -> Self {
+impl PanicFlush {
+    fn write(&mut self) {
+        if let Some((root_key, session)) = self.session.take() {
+            let _ = storage::save_session_for_root(&root_key, session);
+        }
+        if let Some(state) = self.ui_state.take() {
+            let _ = storage::save_ui_state(&state);
+        }
+    }
+}
+
+/// Draws "Saving…" over the status line unless `done` fires or hangs up
+/// within [`SHUTDOWN_INDICATOR_DELAY`], so quick exits don't flash it.
+fn saving_indicator(done: std::sync::mpsc::Receiver<()>) {
+    use crossterm::{cursor::MoveTo, style::Print};
+    if done.recv_timeout(SHUTDOWN_INDICATOR_DELAY)
+        != Err(std::sync::mpsc::RecvTimeoutError::Timeout)
+    {
+        return;
+    }
+    let Ok((_, rows)) = crossterm::terminal::size() else {
+        return;
+    };
+    let mut out = stdout();
+    let _ = out
+        .execute(MoveTo(0, rows.saturating_sub(1)))
+        .and_then(|out| out.execute(Print(" Saving\u{2026} ")));
+}
+
+/// Body line length, in characters, past which the wrap/format hint shows.
+const LONG_BODY_LINE: usize = 1000;
+
+/// Starts the line above each group of sorted response headers.
+pub const HEADER_GROUP_MARK: &str = "\u{2500}\u{2500}";
+
+/// The first label after `current`, wrapping around, that starts with `c`
+/// (ignoring case). Repeated presses cycle through the matches.
+fn next_starting_with(labels: &[&str], current: usize, c: char) -> Option<usize> {
+    let count = labels.len();
+    (1..=count).map(|step| (current + step) % count).find(|&i| {
+        labels[i].chars().next().is_some_and(|first| first.eq_ignore_ascii_case(&c))
+    })
+}
+
+/// A project's expanded folders and selected item.
+#[derive(Debug, Clone, Default)]
+struct ProjectSidebar {
+    expanded: HashSet<Uuid>,
+    selection_id: Option<Uuid>,
+}
+
+impl ProjectSidebar {
+    fn of(sidebar: &SidebarState) -> Self {
         Self {
-            lines: Vec::new(),
-            search_lines: Vec::new(),
-            lines_dirty: true,
-            search_dirty: true,
-            search_query: String::new(),
+            expanded: sidebar.expanded.clone(),
+            selection_id: sidebar.selection_id,
         }
     }
 
-    fn invalidate_all(&mut self) {
-        self.lines_dirty = true;
-        self.search_dirty = true;
+    fn from_session(state: &storage::ProjectSidebarState) -> Self {
+        Self {
+            expanded: state.expanded.iter().filter_map(|id| Uuid::parse_str(id).ok()).collect(),
+            selection_id: state.selection_id.as_deref().and_then(|id| Uuid::parse_str(id).ok()),
+        }
     }
 
-    fn invalidate_search(&mut self) {
-        self.search_dirty = true;
+    fn to_session(&self) -> storage::ProjectSidebarState {
+        let mut expanded: Vec<String> = self.expanded.iter().map(Uuid::to_string).collect();
+        expanded.sort();
+        storage::ProjectSidebarState {
+            expanded,
+            selection_id: self.selection_id.map(|id| id.to_string()),
+        }
     }
 }
 
-pub struct RequestState {
-    pub method: Method,
-    pub url_editor: TextArea<'static>,
-    pub headers_editor: TextArea<'static>,
-    pub body_editor: TextArea<'static>,
-    pub body_mode: BodyMode,
-    pub body_form_pairs: Vec<KvPair>,
-    pub body_multipart_fields: Vec<MultipartField>,
-    pub body_binary_path_editor: TextArea<'static>,
-    pub auth_type: AuthType,
-    pub api_key_location: ApiKeyLocation,
-    pub auth_token_editor: TextArea<'static>,
-    pub auth_username_editor: TextArea<'static>,
-    pub auth_password_editor: TextArea<'static>,
-    pub auth_key_name_editor: TextArea<'static>,
-    pub auth_key_value_editor: TextArea<'static>,
+/// Sidebar operations that ask for confirmation on locked items.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockedAction {
+    Delete,
+    Rename,
+    Move,
+    Outdent,
+    Indent,
 }
 
-#[derive(Clone, Copy)]
-enum YankTarget {
-    Request,
-    ResponseBody,
-    ResponseHeaders,
+impl LockedAction {
+    pub fn verb(self) -> &'static str {
+        match self {
+            LockedAction::Delete => "Delete",
+            LockedAction::Rename => "Rename",
+            LockedAction::Move | LockedAction::Outdent | LockedAction::Indent => "Move",
+        }
+    }
 }
 
-impl RequestState {
-    pub fn new() -> Self {
-        let mut url_editor = TextArea::default();
-        configure_editor(&mut url_editor, "Enter URL...");
+/// Resolves `{{name}}` placeholders: environment variables first, then
+/// references to the last response of another request in the project.
+struct Resolver<'a> {
+    variables: HashMap<String, String>,
+    app: &'a App,
+}
 
-        let mut headers_editor = TextArea::default();
-        configure_editor(&mut headers_editor, "Key: Value");
+impl Resolver<'_> {
+    fn substitute(&self, template: &str) -> (String, Vec<String>) {
+        environment::substitute_with(template, |name| self.resolve(name).ok().flatten())
+    }
 
-        let mut body_editor = TextArea::default();
-        configure_editor(&mut body_editor, "Request body...");
+    /// `Ok(None)` when the name is neither a variable nor a reference;
+    /// `Err` when it is a reference that can't be resolved.
+    fn resolve(&self, name: &str) -> Result<Option<String>, String> {
+        if let Some(value) = self.variables.get(name) {
+            return Ok(Some(value.clone()));
+        }
+        let Some(reference) = chain::parse_reference(name) else {
+            return Ok(None);
+        };
+        let mut matches = self.app.sidebar_tree.nodes.values().filter(|node| {
+            node.kind == NodeKind::Request && node.name == reference.request
+        });
+        let node = matches
+            .next()
+            .ok_or_else(|| format!("no request named \"{}\" in this project", reference.request))?;
+        if matches.next().is_some() {
+            return Err(format!("several requests are named \"{}\"", reference.request));
+        }
+        let response = self.app.response_store.get(node.id).ok_or_else(|| {
+            format!("run \"{}\" first, it has no response yet", reference.request)
+        })?;
+        chain::extract(&reference, response)
+            .map(Some)
+            .map_err(|err| format!("\"{}\": {}", reference.request, err))
+    }
+}
 
-        let mut body_binary_path_editor = TextArea::default();
-        configure_editor(&mut body_binary_path_editor, "File path...");
+/// Whether the in-memory collection matches `collection.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum SaveStatus {
+    #[default]
+    Saved,
+    /// A background retry is about to be written.
+    Saving { attempts: u32 },
+    Failed {
+        error: String,
+        attempts: u32,
+        retry_at: Instant,
+    },
+}
 
-        let mut auth_token_editor = TextArea::default();
-        configure_editor(&mut auth_token_editor, "Token");
+/// Where the codec popup's input came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodecSource {
+    /// The visual selection in a request editor, which can be replaced.
+    RequestSelection,
+    /// The visual selection in a read-only response editor.
+    ResponseSelection,
+    Clipboard,
+}
 
-        let mut auth_username_editor = TextArea::default();
-        configure_editor(&mut auth_username_editor, "Username");
+/// Encode/decode helpers (`Ctrl+U`).
+#[derive(Debug)]
+pub struct CodecPopup {
+    pub input: String,
+    pub source: CodecSource,
+    pub index: usize,
+    /// The selected transform applied to `input`.
+    pub result: Result<String, String>,
+}
 
-        let mut auth_password_editor = TextArea::default();
-        configure_editor(&mut auth_password_editor, "Password");
+impl CodecPopup {
+    fn new(input: String, source: CodecSource) -> Self {
+        let result = codec::Transform::ALL[0].apply(&input);
+        Self {
+            input,
+            source,
+            index: 0,
+            result,
+        }
+    }
 
-        let mut auth_key_name_editor = TextArea::default();
-        configure_editor(&mut auth_key_name_editor, "Key name");
+    pub fn transform(&self) -> codec::Transform {
+        codec::Transform::ALL[self.index]
+    }
 
-        let mut auth_key_value_editor = TextArea::default();
-        configure_editor(&mut auth_key_value_editor, "Key value");
+    pub fn can_replace(&self) -> bool {
+        self.source == CodecSource::RequestSelection
+            && self.transform().replaces_input()
+            && self.result.is_ok()
+    }
 
-        Self {
-            method: Method::default(),
-            url_editor,
-            headers_editor,
-            body_editor,
-            body_mode: BodyMode::Raw,
-            body_form_pairs: vec![KvPair::new_empty()],
-            body_multipart_fields: vec![MultipartField::new_empty()],
-            body_binary_path_editor,
-            auth_type: AuthType::NoAuth,
-            api_key_location: ApiKeyLocation::Header,
-            auth_token_editor,
-            auth_username_editor,
-            auth_password_editor,
-            auth_key_name_editor,
-            auth_key_value_editor,
-        }
+    fn select(&mut self, index: usize) {
+        self.index = index;
+        self.result = self.transform().apply(&self.input);
     }
+}
 
-    pub fn set_contents(&mut self, method: Method, url: String, headers: String, body: String) {
-        self.method = method;
-        let url_lines = if url.is_empty() { vec![String::new()] } else { vec![url] };
-        let header_lines = if headers.is_empty() {
-            vec![String::new()]
-        } else {
-            headers.lines().map(|l| l.to_string()).collect()
+/// A pinned response compared with the current one (`C`).
+#[derive(Debug)]
+pub struct CompareView {
+    /// Status, duration and size of the pinned and the current response.
+    pub pinned_summary: String,
+    pub current_summary: String,
+    pub headers: Vec<diff::HeaderChange>,
+    /// Unified diff of the bodies, JSON pretty-printed first.
+    pub body: Vec<diff::DiffLine>,
+    pub scroll: u16,
+}
+
+impl CompareView {
+    fn new(pinned: &ResponseData, current: &ResponseData, style: &format::JsonStyle) -> Self {
+        let summary = |data: &ResponseData| {
+            format!(
+                "{} {} \u{b7} {} ms \u{b7} {}",
+                data.status,
+                data.status_text,
+                data.duration_ms,
+                http::format_bytes(data.decoded_size)
+            )
         };
-        let body_lines = if body.is_empty() {
-            vec![String::new()]
-        } else {
-            body.lines().map(|l| l.to_string()).collect()
+        let body = |data: &ResponseData| {
+            if data.is_binary {
+                format!("<{} bytes of binary data>", data.bytes.len())
+            } else {
+                format_json_if_possible(&data.headers, &data.body, style)
+            }
         };
+        Self {
+            pinned_summary: summary(pinned),
+            current_summary: summary(current),
+            headers: diff::header_delta(&pinned.headers, &current.headers),
+            body: diff::diff_lines(&body(pinned), &body(current)),
+            scroll: 0,
+        }
+    }
+}
 
-        self.url_editor = TextArea::new(url_lines);
-        configure_editor(&mut self.url_editor, "Enter URL...");
-        self.headers_editor = TextArea::new(header_lines);
-        configure_editor(&mut self.headers_editor, "Key: Value");
-        self.body_editor = TextArea::new(body_lines);
-        configure_editor(&mut self.body_editor, "Request body...");
+/// Recent entries of the collection's changelog (`C` in the sidebar),
+/// newest first.
+#[derive(Debug, Clone)]
+pub struct ChangelogPopup {
+    pub entries: Vec<ChangeEntry>,
+    pub scroll: u16,
+}
 
-        // Reset body mode fields
-        self.body_mode = BodyMode::Raw;
-        self.body_form_pairs = vec![KvPair::new_empty()];
-        self.body_multipart_fields = vec![MultipartField::new_empty()];
-        self.body_binary_path_editor = TextArea::default();
-        configure_editor(&mut self.body_binary_path_editor, "File path...");
+/// The URL taken apart a line per part (`Ctrl+Q` on the URL field).
+#[derive(Debug, Clone)]
+pub struct UrlOutlinePopup {
+    pub url: String,
+    pub scroll: u16,
+}
 
-        self.reset_auth();
-    }
+/// The request as an HTTP/1.1 message (`V` on the request panel).
+#[derive(Debug)]
+pub struct RawView {
+    /// The resolved message as it would be sent, or why it can't be built.
+    pub text: String,
+    pub scroll: u16,
+    /// The unresolved message, while it is being edited.
+    pub editor: Option<TextArea<'static>>,
+    pub error: Option<raw_http::ParseError>,
+    /// Body text when editing began; if it comes back unchanged the body
+    /// mode is left alone.
+    original_body: String,
+}
 
-    pub fn reset_auth(&mut self) {
-        self.auth_type = AuthType::NoAuth;
-        self.api_key_location = ApiKeyLocation::Header;
-        self.auth_token_editor = TextArea::default();
-        configure_editor(&mut self.auth_token_editor, "Token");
-        self.auth_username_editor = TextArea::default();
-        configure_editor(&mut self.auth_username_editor, "Username");
-        self.auth_password_editor = TextArea::default();
-        configure_editor(&mut self.auth_password_editor, "Password");
-        self.auth_key_name_editor = TextArea::default();
-        configure_editor(&mut self.auth_key_name_editor, "Key name");
-        self.auth_key_value_editor = TextArea::default();
-        configure_editor(&mut self.auth_key_value_editor, "Key value");
-    }
+/// A one-off send with edits that never reach the request
+/// (Ctrl+Shift+Enter). The message is edited as in the raw view, and its
+/// placeholders resolve when it is sent.
+#[derive(Debug)]
+pub struct ScratchSend {
+    pub editor: TextArea<'static>,
+    pub error: Option<raw_http::ParseError>,
+    /// The request's body text when the scratch was filled. While the body
+    /// is left alone the request's own body goes out, form fields and
+    /// files included.
+    original_body: String,
+}
 
-    pub fn url_text(&self) -> String {
-        self.url_editor.lines().join("")
-    }
+/// The user's notes on a request (`n` on the response panel), typed
+/// straight into the editor and saved when the popup closes.
+#[derive(Debug)]
+pub struct NotesPopup {
+    request_id: Uuid,
+    pub editor: TextArea<'static>,
+}
 
-    pub fn headers_text(&self) -> String {
-        self.headers_editor.lines().join("\n")
+/// A parsed scratch message on its way out. It outlives the overlay so the
+/// send confirmations in between send it rather than the request.
+#[derive(Debug, Clone)]
+struct ScratchMessage {
+    message: raw_http::RawRequest,
+    body_edited: bool,
+    /// A CORS preflight, sent without the request's auth as a browser
+    /// sends it.
+    preflight: bool,
+}
+
+impl ScratchMessage {
+    fn headers_text(&self) -> String {
+        let lines: Vec<String> =
+            self.message.headers.iter().map(|(key, value)| format!("{}: {}", key, value)).collect();
+        lines.join("\n")
     }
+}
 
-    pub fn body_text(&self) -> String {
-        self.body_editor.lines().join("\n")
+/// The request fully resolved, shown without sending it (`P` on the
+/// request panel).
+#[derive(Debug)]
+pub struct SendPreviewPopup {
+    /// The request, or why it can't be built.
+    pub preview: Result<http::SendPreview, String>,
+    pub scroll: u16,
+}
+
+/// The CORS preflight check (`K` on the request panel): the origin being
+/// typed, then the preflight sent for the open request and its verdict.
+#[derive(Debug, Clone)]
+pub struct CorsPopup {
+    pub origin: TextInput,
+    /// Set once the preflight is sent.
+    pub preflight: Option<cors::Preflight>,
+    /// The verdict, or why no answer came; unset while waiting for one.
+    pub verdict: Option<Result<cors::Verdict, String>>,
+}
+
+/// Bytes of body the send preview shows before cutting it short.
+const PREVIEW_BODY_LIMIT: usize = 16 * 1024;
+
+fn truncate_preview_body(body: String) -> String {
+    if body.len() <= PREVIEW_BODY_LIMIT {
+        return body;
     }
+    let mut end = PREVIEW_BODY_LIMIT;
+    while !body.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}\n\n\u{2026} {} more bytes not shown", &body[..end], body.len() - end)
+}
 
-    pub fn body_binary_path_text(&self) -> String {
-        self.body_binary_path_editor.lines().join("")
+/// A send held back until the user confirms it, because the project isn't
+/// trusted yet.
+#[derive(Debug, Clone)]
+pub struct SendConfirm {
+    /// The resolved request, from [`http::request_preview`].
+    pub lines: Vec<String>,
+}
+
+/// An OpenAPI spec whose operations were imported before, waiting for the
+/// choice between updating those requests and a new project.
+#[derive(Debug, Clone)]
+pub struct SpecUpdatePrompt {
+    pub spec: openapi::OpenApiImport,
+    /// How many of its operations match existing requests.
+    pub matched: usize,
+}
+
+/// A send to a `[protect]` host or environment, held back until its method
+/// is typed.
+#[derive(Debug, Clone)]
+pub struct ProtectedConfirm {
+    pub method: String,
+    /// The URL after substitution.
+    pub url: String,
+    pub environment: Option<String>,
+    /// The host pattern or environment that matched.
+    pub reason: String,
+    pub input: TextInput,
+}
+
+/// A send held back because placeholders in it resolve to nothing.
+#[derive(Debug, Clone)]
+pub struct UnresolvedConfirm {
+    /// Each unresolved name with the fields it appears in.
+    pub names: Vec<(String, Vec<&'static str>)>,
+}
+
+/// A send held back because its method and body don't go together.
+#[derive(Debug, Clone)]
+pub struct BodyConfirm {
+    pub method: String,
+    pub mismatch: http::BodyMismatch,
+}
+
+impl BodyConfirm {
+    /// "GET with a body" or "POST with an empty body".
+    pub fn describe(&self) -> String {
+        match self.mismatch {
+            http::BodyMismatch::UnexpectedBody => format!("{} with a body", self.method),
+            http::BodyMismatch::MissingBody => format!("{} with an empty body", self.method),
+        }
     }
+}
 
-    pub fn build_body_content(&self) -> http::BodyContent {
-        match self.body_mode {
-            BodyMode::Raw => {
-                let text = self.body_text();
-                if text.trim().is_empty() {
-                    http::BodyContent::None
-                } else {
-                    http::BodyContent::Raw(text)
-                }
-            }
-            BodyMode::Json => {
-                let text = self.body_text();
-                if text.trim().is_empty() {
-                    http::BodyContent::None
-                } else {
-                    http::BodyContent::Json(text)
-                }
-            }
-            BodyMode::Xml => {
-                let text = self.body_text();
-                if text.trim().is_empty() {
-                    http::BodyContent::None
-                } else {
-                    http::BodyContent::Xml(text)
-                }
-            }
-            BodyMode::FormUrlEncoded => {
-                let pairs: Vec<(String, String)> = self
-                    .body_form_pairs
-                    .iter()
-                    .filter(|p| p.enabled && !(p.key.is_empty() && p.value.is_empty()))
-                    .map(|p| (p.key.clone(), p.value.clone()))
-                    .collect();
-                if pairs.is_empty() {
-                    http::BodyContent::None
-                } else {
-                    http::BodyContent::FormUrlEncoded(pairs)
-                }
-            }
-            BodyMode::Multipart => {
-                let parts: Vec<http::MultipartPart> = self
-                    .body_multipart_fields
-                    .iter()
-                    .filter(|f| f.enabled && !f.key.is_empty())
-                    .map(|f| http::MultipartPart {
-                        key: f.key.clone(),
-                        value: f.value.clone(),
-                        field_type: match f.field_type {
-                            MultipartFieldType::Text => http::MultipartPartType::Text,
-                            MultipartFieldType::File => http::MultipartPartType::File,
-                        },
-                    })
-                    .collect();
-                if parts.is_empty() {
-                    http::BodyContent::None
-                } else {
-                    http::BodyContent::Multipart(parts)
-                }
-            }
-            BodyMode::Binary => {
-                let path = self.body_binary_path_text();
-                if path.trim().is_empty() {
-                    http::BodyContent::None
-                } else {
-                    http::BodyContent::Binary(path)
-                }
-            }
-        }
+/// Saving the response as an example of its request, or browsing the open
+/// request's examples.
+#[derive(Debug, Clone)]
+pub enum ExamplePopup {
+    /// Name for the example being saved.
+    Name(TextInput),
+    /// Index into the open request's examples.
+    List { index: usize },
+}
+
+/// `"`: recent yanks to paste or copy again.
+#[derive(Debug, Clone, Copy)]
+pub struct ClipboardRingPopup {
+    pub index: usize,
+    /// Paste the pick into the focused request editor.
+    pub paste: bool,
+}
+
+/// Body snippets (`Ctrl+Space` in insert mode on the body): the filter
+/// typed and the pick among the snippets matching it.
+#[derive(Debug, Clone)]
+pub struct SnippetPopup {
+    pub snippets: Vec<snippets::Snippet>,
+    pub filter: TextInput,
+    pub index: usize,
+}
+
+impl SnippetPopup {
+    pub fn matches(&self) -> Vec<&snippets::Snippet> {
+        snippets::filter(&self.snippets, &self.filter.value)
     }
+}
 
-    pub fn auth_token_text(&self) -> String {
-        self.auth_token_editor.lines().join("")
+/// `!`: the command to pipe a body through, then what it printed.
+#[derive(Debug, Clone)]
+pub enum PipePopup {
+    Command(TextInput),
+    Output {
+        command: String,
+        output: pager::PipeOutput,
+        scroll: u16,
+    },
+}
+
+/// A draft left by a session that ended with unsaved edits.
+#[derive(Debug, Clone)]
+pub struct RecoveredDraft {
+    pub request_id: Uuid,
+    /// Sidebar path of the request.
+    pub path: String,
+    /// What the draft changes, from [`storage::draft_changes`].
+    pub changes: Vec<String>,
+    pub draft: storage::Draft,
+}
+
+/// Drafts offered on startup, one at a time.
+#[derive(Debug, Clone)]
+pub struct DraftPrompt {
+    pub drafts: Vec<RecoveredDraft>,
+}
+
+/// The first-run overlay offering a way to start out in a new project root.
+#[derive(Debug, Clone)]
+pub enum Onboarding {
+    Choose { index: usize },
+    /// Path of a Postman collection, OpenAPI spec or project archive to
+    /// import.
+    Import(TextInput),
+}
+
+/// The first-run choices, as `(label, explanation)`.
+pub const ONBOARDING_CHOICES: [(&str, &str); 3] = [
+    ("Start empty", "A blank request in a new project"),
+    ("Create a sample project", "Documented requests against httpbin.org"),
+    ("Import a Postman collection", "Or an OpenAPI spec or Perseus project archive"),
+];
+
+/// File actions started from the environment switcher.
+#[derive(Debug, Clone)]
+pub enum EnvFilePrompt {
+    /// Path of a Postman environment export to import, and where to keep it.
+    Import { input: TextInput, scope: EnvScope },
+    /// Where to write the named environment as a Postman export.
+    Export { name: String, input: TextInput },
+    /// An import whose name is taken: overwrite that environment or pick
+    /// another name, typed into `rename` once renaming starts.
+    Conflict {
+        env: Environment,
+        scope: EnvScope,
+        rename: Option<TextInput>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct ErrorPopup {
+    pub title: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsRow {
+    Proxy,
+    ProxyUrl,
+    Redirects,
+    Compress,
+    HttpVersion,
+    UserAgent,
+    Protect,
+    LineEndings,
+    ExpectsBody,
+    TlsVerify,
+    TlsCaCert,
+    TlsClientCert,
+    TlsClientKey,
+    Schema,
+    Poll,
+    PollInterval,
+    PollAttempts,
+    PollCodes,
+    PollPath,
+    PollValue,
+}
+
+/// A run of every request under a sidebar item (`R`), once per data row.
+#[derive(Debug)]
+pub struct FolderRun {
+    pub report: runner::RunReport,
+    /// In run order: each request after the requests it references.
+    requests: Vec<Uuid>,
+    /// How many providers each of `requests` sits under, for indenting.
+    depths: Vec<usize>,
+    /// Steps dispatched so far, counting iterations times requests.
+    next: usize,
+    /// The step in flight.
+    pub current: Option<RunStep>,
+    pub finished: bool,
+    /// Where the responses are being written, when exporting.
+    pub export: Option<runner::RunExport>,
+    /// Set when steps are sent several at a time rather than in order.
+    pub parallel: Option<ParallelSends>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RunStep {
+    /// Index into the report's iterations.
+    pub iteration: usize,
+    pub request_id: Uuid,
+    started: Instant,
+}
+
+impl RunStep {
+    fn is(&self, other: &RunStep) -> bool {
+        self.iteration == other.iteration && self.request_id == other.request_id
     }
+}
 
-    pub fn auth_username_text(&self) -> String {
-        self.auth_username_editor.lines().join("")
+type StepOutcome = (RunStep, Result<ResponseData, http::RequestError>);
+
+/// The sends of a parallel run: up to `limit` tasks at once, each handing
+/// its response back tagged with its step.
+#[derive(Debug)]
+pub struct ParallelSends {
+    pub limit: usize,
+    in_flight: Vec<InFlightStep>,
+    done_tx: mpsc::UnboundedSender<StepOutcome>,
+    done_rx: mpsc::UnboundedReceiver<StepOutcome>,
+}
+
+#[derive(Debug)]
+struct InFlightStep {
+    step: RunStep,
+    /// The request's schema when sent, checked once the response is in.
+    schema: Option<SchemaSetting>,
+    handle: tokio::task::AbortHandle,
+}
+
+impl ParallelSends {
+    fn new(limit: usize) -> Self {
+        let (done_tx, done_rx) = mpsc::unbounded_channel();
+        Self {
+            limit,
+            in_flight: Vec::new(),
+            done_tx,
+            done_rx,
+        }
     }
 
-    pub fn auth_password_text(&self) -> String {
-        self.auth_password_editor.lines().join("")
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.len()
     }
+}
 
-    pub fn auth_key_name_text(&self) -> String {
-        self.auth_key_name_editor.lines().join("")
+impl FolderRun {
+    pub fn total_steps(&self) -> usize {
+        self.requests.len() * self.report.iterations.len()
     }
 
-    pub fn auth_key_value_text(&self) -> String {
-        self.auth_key_value_editor.lines().join("")
+    /// Indent of the `index`th step of an iteration.
+    pub fn depth(&self, index: usize) -> usize {
+        self.depths.get(index).copied().unwrap_or(0)
     }
 
-    pub fn build_auth_config(&self) -> http::AuthConfig {
-        match self.auth_type {
-            AuthType::NoAuth => http::AuthConfig::NoAuth,
-            AuthType::Bearer => http::AuthConfig::Bearer {
-                token: self.auth_token_text(),
-            },
-            AuthType::Basic => http::AuthConfig::Basic {
-                username: self.auth_username_text(),
-                password: self.auth_password_text(),
-            },
-            AuthType::ApiKey => http::AuthConfig::ApiKey {
-                key: self.auth_key_name_text(),
-                value: self.auth_key_value_text(),
-                location: self.api_key_location,
-            },
+    /// Whether the next step can be sent, or the run wrapped up once the
+    /// last parallel send is back.
+    fn is_waiting(&self) -> bool {
+        if self.finished || self.current.is_some() {
+            return false;
         }
+        self.parallel.as_ref().is_none_or(|sends| {
+            sends.in_flight.len() < sends.limit
+                && (self.next < self.total_steps() || sends.in_flight.is_empty())
+        })
     }
 
-    pub fn active_editor(
-        &mut self,
-        field: RequestField,
-        body_field: BodyField,
-    ) -> Option<&mut TextArea<'static>> {
-        match field {
-            RequestField::Url => Some(&mut self.url_editor),
-            RequestField::Headers => Some(&mut self.headers_editor),
-            RequestField::Body => match body_field {
-                BodyField::TextEditor => Some(&mut self.body_editor),
-                BodyField::BinaryPath => Some(&mut self.body_binary_path_editor),
-                _ => None,
-            },
-            RequestField::Method | RequestField::Send | RequestField::Auth => None,
+    /// The steps sent and not yet back, in run order.
+    pub fn in_flight(&self) -> Vec<RunStep> {
+        let mut steps: Vec<RunStep> = self.current.into_iter().collect();
+        if let Some(sends) = &self.parallel {
+            steps.extend(sends.in_flight.iter().map(|sent| sent.step));
         }
+        steps.sort_by_key(|step| {
+            (step.iteration, run_rank(&self.requests, &step.request_id.to_string()))
+        });
+        steps
     }
-}
 
-fn configure_editor(editor: &mut TextArea<'static>, placeholder: &str) {
-    editor.set_cursor_line_style(Style::default());
-    editor.set_placeholder_text(placeholder);
+    /// The data row of the step in flight, which overrides the environment.
+    fn current_variables(&self) -> Option<&runner::DataRow> {
+        let step = self.current?;
+        Some(&self.report.iterations[step.iteration].variables)
+    }
 }
 
-pub(crate) struct WrapCache {
-    pub(crate) width: usize,
-    pub(crate) generation: u64,
-    pub(crate) cursor: Option<(usize, usize)>,
-    pub(crate) selection: Option<((usize, usize), (usize, usize))>,
-    pub(crate) wrapped_lines: Vec<Line<'static>>,
-    pub(crate) cursor_pos: Option<(usize, usize)>,
+/// Where a request comes in a run's order of `requests`.
+fn run_rank(requests: &[Uuid], request_id: &str) -> usize {
+    requests.iter().position(|id| id.to_string() == request_id).unwrap_or(requests.len())
 }
 
-impl WrapCache {
-    fn new() -> Self {
-        Self {
-            width: 0,
-            generation: 0,
-            cursor: None,
-            selection: None,
-            wrapped_lines: Vec::new(),
-            cursor_pos: None,
-        }
-    }
+/// Results of the folder run, and the path being typed to save them.
+#[derive(Debug, Clone, Default)]
+pub struct RunPopup {
+    pub scroll: u16,
+    pub save: Option<TextInput>,
 }
 
-pub(crate) struct ResponseBodyRenderCache {
-    pub(crate) dirty: bool,
-    pub(crate) generation: u64,
-    pub(crate) body_text: String,
-    pub(crate) is_json: bool,
-    pub(crate) lines: Vec<Line<'static>>,
-    pub(crate) wrap_cache: WrapCache,
+/// A request flagged by the URL audit.
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    pub request_id: Uuid,
+    /// Folder path and name, as shown in the sidebar.
+    pub path: String,
+    pub url: String,
+    pub findings: Vec<audit::Finding>,
 }
 
-impl ResponseBodyRenderCache {
-    fn new() -> Self {
-        Self {
-            dirty: true,
-            generation: 0,
-            body_text: String::new(),
-            is_json: false,
-            lines: Vec::new(),
-            wrap_cache: WrapCache::new(),
-        }
+impl AuditEntry {
+    fn literal_base(&self) -> Option<&str> {
+        self.findings.iter().find_map(|finding| match finding {
+            audit::Finding::LiteralHost(base) => Some(base.as_str()),
+            _ => None,
+        })
     }
 }
 
-pub(crate) struct ResponseHeadersRenderCache {
-    pub(crate) dirty: bool,
-    pub(crate) generation: u64,
-    pub(crate) lines: Vec<Line<'static>>,
-    pub(crate) wrap_cache: WrapCache,
+/// Results of the URL audit (`U`), and the variable typed for the fix
+/// that replaces the selected entry's literal host.
+#[derive(Debug, Clone)]
+pub struct UrlAuditPopup {
+    pub entries: Vec<AuditEntry>,
+    pub index: usize,
+    pub fix: Option<TextInput>,
 }
 
-impl ResponseHeadersRenderCache {
-    fn new() -> Self {
-        Self {
-            dirty: true,
-            generation: 0,
-            lines: Vec::new(),
-            wrap_cache: WrapCache::new(),
-        }
+impl UrlAuditPopup {
+    /// The literal host the fix would replace, and how many requests have it.
+    pub fn fix_target(&self) -> Option<(&str, usize)> {
+        let base = self.entries.get(self.index)?.literal_base()?;
+        let count = self.entries.iter().filter(|e| e.literal_base() == Some(base)).count();
+        Some((base, count))
     }
 }
 
-pub struct App {
-    running: bool,
-    dirty: bool,
-    pub config: Config,
-    pub request: RequestState,
-    pub focus: FocusState,
-    pub response: ResponseStatus,
-    pub response_tab: ResponseTab,
-    pub request_tab: RequestTab,
-    pub client: Client,
-    pub app_mode: AppMode,
-    pub vim: Vim,
-    pub response_scroll: u16,
-    pub loading_tick: u8,
-    pub show_help: bool,
-    pub show_method_popup: bool,
-    pub method_popup_index: usize,
-    pub method_popup_custom_mode: bool,
-    pub method_custom_input: String,
-    pub show_auth_type_popup: bool,
-    pub auth_type_popup_index: usize,
-    pub sidebar_visible: bool,
-    pub sidebar_width: u16,
-    pub collection: CollectionStore,
-    pub project_list: Vec<ProjectInfo>,
-    pub sidebar_tree: ProjectTree,
-    pub sidebar: SidebarState,
-    sidebar_cache: SidebarCache,
-    pub active_project_id: Uuid,
-    pub current_request_id: Option<Uuid>,
-    pub request_dirty: bool,
-    clipboard_toast: Option<(String, Instant)>,
-    request_handle: Option<tokio::task::AbortHandle>,
-    clipboard: ClipboardProvider,
-    last_yank_request: String,
-    last_yank_response: String,
-    last_yank_response_headers: String,
-    pub response_editor: TextArea<'static>,
-    pub response_headers_editor: TextArea<'static>,
-    pub(crate) response_body_cache: ResponseBodyRenderCache,
-    pub(crate) response_headers_cache: ResponseHeadersRenderCache,
-    pub environments: Vec<Environment>,
-    pub active_environment_name: Option<String>,
-    pub show_env_popup: bool,
-    pub env_popup_index: usize,
-    pub show_body_mode_popup: bool,
-    pub body_mode_popup_index: usize,
-    pub kv_edit_textarea: Option<TextArea<'static>>,
+/// A line of a saved request that uses the variable being looked up.
+#[derive(Debug, Clone)]
+pub struct UsageHit {
+    pub request_id: Uuid,
+    /// Folder path and name, as shown in the sidebar.
+    pub path: String,
+    /// `Url`, `Headers`, `Body` or `Auth`.
+    pub field: RequestField,
+    /// Counting from 1; form and auth fields count one line per entry.
+    pub line: usize,
 }
 
-impl App {
-    const CLIPBOARD_TOAST_DURATION: Duration = Duration::from_secs(2);
-    const SPINNER_TICK: Duration = Duration::from_millis(100);
+/// Find usages (`u` in the environment popup): the variable name being
+/// typed and the lines that use it.
+#[derive(Debug, Clone)]
+pub struct UsagesPopup {
+    pub name: TextInput,
+    pub hits: Vec<UsageHit>,
+    pub index: usize,
+    /// The new name being typed after Ctrl+R.
+    pub rename: Option<TextInput>,
+    pub preview: Option<RenamePreview>,
+}
 
-    pub fn new() -> Result<Self> {
-        let config = config::load_config().map_err(anyhow::Error::msg)?;
+/// The variables of an environment (`v` in the environment popup), the
+/// ones it inherits dimmed.
+#[derive(Debug, Clone)]
+pub struct EnvVariablesPopup {
+    pub name: String,
+    pub index: usize,
+}
 
-        let client = Self::build_client(&config)?;
+/// A variable rename waiting to be confirmed.
+#[derive(Debug, Clone)]
+pub struct RenamePreview {
+    pub from: String,
+    pub to: String,
+    /// Environment files that change.
+    pub env_files: Vec<String>,
+    /// Placeholders and keys rewritten, in requests and environment files.
+    pub occurrences: usize,
+    plan: environment::VariableRename,
+}
 
-        let mut collection = CollectionStore::load_or_init().map_err(anyhow::Error::msg)?;
-        if collection.collection.item.is_empty() {
-            let root_name = collection
-                .root
-                .file_name()
-                .and_then(|s| s.to_str())
-                .unwrap_or("Perseus")
-                .to_string();
-            let _ = collection
-                .add_project(root_name)
-                .map_err(anyhow::Error::msg)?;
-            collection.save().map_err(anyhow::Error::msg)?;
-        }
+/// A poll started by sending a request that has a poll setting.
+#[derive(Debug, Clone)]
+pub struct PollRun {
+    pub request_id: Uuid,
+    pub setting: PollSetting,
+    /// Attempts sent so far, including the one in flight.
+    pub attempt: u32,
+    /// When the next attempt is due; `None` while one is in flight.
+    pub next_at: Option<Instant>,
+}
 
-        let project_list = collection.list_projects();
-        if project_list.is_empty() {
-            return Err(anyhow::anyhow!("No projects found in collection"));
-        }
+/// A schema file as last read: its modification time then, and the
+/// parsed schema or why it couldn't be used.
+type CachedSchema = (Option<std::time::SystemTime>, Result<Value, String>);
 
-        let ui_state = storage::load_ui_state()
-            .map_err(anyhow::Error::msg)?
-            .unwrap_or_else(|| storage::UiState::new(project_list[0].id.to_string(), config.ui.sidebar_width));
+/// Per-request settings popup (Ctrl+O). Edits apply to `draft` and are
+/// written to the collection as soon as they are committed.
+#[derive(Debug, Clone)]
+pub struct SettingsPopup {
+    pub index: usize,
+    pub draft: RequestSettings,
+    pub editing: Option<TextInput>,
+    pub error: Option<String>,
+}
 
-        let root_key = storage::project_root_key();
-        let session_state = match root_key.as_deref() {
-            Some(key) => match storage::load_session_for_root(key) {
-                Ok(state) => state,
-                Err(err) => {
-                    eprintln!("Failed to load session: {}", err);
-                    None
+impl SettingsPopup {
+    pub fn rows(&self) -> Vec<SettingsRow> {
+        let mut rows = vec![SettingsRow::Proxy];
+        if matches!(self.draft.proxy, ProxySetting::Custom { .. }) {
+            rows.push(SettingsRow::ProxyUrl);
+        }
+        rows.push(SettingsRow::Redirects);
+        rows.push(SettingsRow::Compress);
+        rows.push(SettingsRow::HttpVersion);
+        rows.push(SettingsRow::UserAgent);
+        rows.push(SettingsRow::Protect);
+        rows.push(SettingsRow::LineEndings);
+        rows.push(SettingsRow::ExpectsBody);
+        rows.extend([
+            SettingsRow::TlsVerify,
+            SettingsRow::TlsCaCert,
+            SettingsRow::TlsClientCert,
+            SettingsRow::TlsClientKey,
+            SettingsRow::Schema,
+        ]);
+        rows.push(SettingsRow::Poll);
+        if let Some(poll) = &self.draft.poll {
+            rows.extend([SettingsRow::PollInterval, SettingsRow::PollAttempts]);
+            match poll.until {
+                PollUntil::Status { .. } => rows.push(SettingsRow::PollCodes),
+                PollUntil::Json { .. } => {
+                    rows.extend([SettingsRow::PollPath, SettingsRow::PollValue])
                 }
-            },
-            None => None,
-        };
-
-        let session_active_project = session_state
-            .as_ref()
-            .and_then(|state| Uuid::parse_str(&state.active_project_id).ok());
-        let ui_active_project = Uuid::parse_str(&ui_state.active_project_id).ok();
-        let mut active_project_id = session_active_project
-            .or(ui_active_project)
-            .unwrap_or(project_list[0].id);
-        if !project_list.iter().any(|p| p.id == active_project_id) {
-            active_project_id = project_list[0].id;
+            }
         }
+        rows
+    }
 
-        let mut created_request_id: Option<Uuid> = None;
-        if !collection_has_requests(&collection.collection.item) {
-            let req = PostmanRequest::new("GET".to_string(), String::new(), Vec::new(), None);
-            let new_id = collection
-                .add_request(active_project_id, "New Request".to_string(), req)
-                .map_err(anyhow::Error::msg)?;
-            collection.save().map_err(anyhow::Error::msg)?;
-            created_request_id = Some(new_id);
+    /// The text a row is edited as, for rows edited through `editing`.
+    pub fn row_text(&self, row: SettingsRow) -> Option<String> {
+        let poll = self.draft.poll.as_ref();
+        match (row, poll.map(|p| &p.until)) {
+            (SettingsRow::ProxyUrl, _) => match &self.draft.proxy {
+                ProxySetting::Custom { url } => Some(url.clone()),
+                _ => None,
+            },
+            (SettingsRow::UserAgent, _) => Some(self.draft.user_agent.clone().unwrap_or_default()),
+            (SettingsRow::TlsCaCert, _) => Some(self.draft.tls.ca_cert.clone().unwrap_or_default()),
+            (SettingsRow::TlsClientCert, _) => {
+                Some(self.draft.tls.client_cert.clone().unwrap_or_default())
+            }
+            (SettingsRow::TlsClientKey, _) => {
+                Some(self.draft.tls.client_key.clone().unwrap_or_default())
+            }
+            (SettingsRow::Schema, _) => Some(match &self.draft.schema {
+                Some(SchemaSetting::File { path }) => path.clone(),
+                Some(SchemaSetting::Inline { schema }) => schema.to_string(),
+                None => String::new(),
+            }),
+            (SettingsRow::PollInterval, _) => poll.map(|p| p.interval_secs.to_string()),
+            (SettingsRow::PollAttempts, _) => poll.map(|p| p.max_attempts.to_string()),
+            (SettingsRow::PollCodes, Some(PollUntil::Status { codes })) => {
+                Some(storage::format_status_codes(codes))
+            }
+            (SettingsRow::PollPath, Some(PollUntil::Json { path, .. })) => Some(path.clone()),
+            (SettingsRow::PollValue, Some(PollUntil::Json { value, .. })) => Some(value.clone()),
+            _ => None,
         }
+    }
 
-        let sidebar_width = clamp_sidebar_width(
-            session_state
-                .as_ref()
-                .map(|state| state.sidebar_width)
-                .unwrap_or(ui_state.sidebar_width),
-        );
-        let sidebar_visible = session_state
-            .as_ref()
-            .map(|state| state.sidebar_visible)
-            .unwrap_or(true);
-        let request_tab = session_state
-            .as_ref()
-            .map(|state| request_tab_from_str(&state.request_tab))
-            .unwrap_or_default();
-        let response_tab = session_state
-            .as_ref()
-            .map(|state| response_tab_from_str(&state.response_tab))
-            .unwrap_or_default();
-        let session_selection_id = session_state
-            .as_ref()
-            .and_then(|state| state.selection_id.as_ref())
-            .and_then(|id| Uuid::parse_str(id).ok());
-        let session_current_request_id = session_state
-            .as_ref()
-            .and_then(|state| state.current_request_id.as_ref())
-            .and_then(|id| Uuid::parse_str(id).ok());
-        let session_expanded_ids: Vec<Uuid> = session_state
-            .as_ref()
-            .map(|state| {
-                state
-                    .expanded
-                    .iter()
-                    .filter_map(|id| Uuid::parse_str(id).ok())
-                    .collect()
-            })
-            .unwrap_or_default();
-        let sidebar_tree = collection
-            .build_tree(active_project_id)
-            .map_err(anyhow::Error::msg)?;
-
-        let mut expanded = HashSet::new();
-        for id in session_expanded_ids {
-            if sidebar_tree.nodes.contains_key(&id) {
-                expanded.insert(id);
+    /// Applies edited text to the row's field in `draft`.
+    fn apply_row_text(&mut self, row: SettingsRow, text: &str) -> Result<(), String> {
+        let text = text.trim();
+        if row == SettingsRow::ProxyUrl {
+            if reqwest::Url::parse(text).is_err() {
+                return Err(format!("\"{}\" is not a valid proxy URL", text));
             }
+            self.draft.proxy = ProxySetting::Custom {
+                url: text.to_string(),
+            };
+            return Ok(());
         }
-        expanded.insert(active_project_id);
-        let session_selection_id =
-            session_selection_id.filter(|id| sidebar_tree.nodes.contains_key(id));
-        let selection_id = session_selection_id.unwrap_or(active_project_id);
-
-        let sidebar = SidebarState {
-            selection_id: Some(selection_id),
-            expanded,
-            search_query: String::new(),
-            popup: None,
+        if row == SettingsRow::UserAgent {
+            // Cleared, `http.user_agent` applies again
+            if !text.is_empty() && reqwest::header::HeaderValue::from_str(text).is_err() {
+                return Err(format!("\"{}\" can't be sent as a User-Agent", text));
+            }
+            self.draft.user_agent = (!text.is_empty()).then(|| text.to_string());
+            return Ok(());
+        }
+        let tls = &mut self.draft.tls;
+        let tls_field = match row {
+            SettingsRow::TlsCaCert => Some(&mut tls.ca_cert),
+            SettingsRow::TlsClientCert => Some(&mut tls.client_cert),
+            SettingsRow::TlsClientKey => Some(&mut tls.client_key),
+            _ => None,
         };
-
-        collection
-            .write_all_request_files()
-            .map_err(anyhow::Error::msg)?;
-
-        let environments = environment::load_all_environments().unwrap_or_default();
-
-        let mut app = Self {
-            running: true,
-            dirty: true,
-            config,
-            request: RequestState::new(),
-            focus: FocusState::default(),
-            response: ResponseStatus::Empty,
-            response_tab,
-            request_tab,
-            client,
-            app_mode: AppMode::Navigation,
-            vim: Vim::new(VimMode::Normal),
-            response_scroll: 0,
-            loading_tick: 0,
-            show_help: false,
-            show_method_popup: false,
-            method_popup_index: 0,
-            method_popup_custom_mode: false,
-            method_custom_input: String::new(),
-            show_auth_type_popup: false,
-            auth_type_popup_index: 0,
-            sidebar_visible,
-            sidebar_width,
-            collection,
-            project_list,
-            sidebar_tree,
-            sidebar,
-            sidebar_cache: SidebarCache::new(),
-            active_project_id,
-            current_request_id: None,
-            request_dirty: false,
-            clipboard_toast: None,
-            request_handle: None,
-            clipboard: ClipboardProvider::new(),
-            last_yank_request: String::new(),
-            last_yank_response: String::new(),
-            last_yank_response_headers: String::new(),
-            response_editor: {
-                let mut editor = TextArea::default();
-                editor.set_cursor_line_style(Style::default());
-                editor
-            },
-            response_headers_editor: {
-                let mut editor = TextArea::default();
-                editor.set_cursor_line_style(Style::default());
-                editor
-            },
-            response_body_cache: ResponseBodyRenderCache::new(),
-            response_headers_cache: ResponseHeadersRenderCache::new(),
-            environments,
-            active_environment_name: None,
-            show_env_popup: false,
-            env_popup_index: 0,
-            show_body_mode_popup: false,
-            body_mode_popup_index: 0,
-            kv_edit_textarea: None,
+        if let Some(field) = tls_field {
+            // Cleared, the configured path applies again
+            if text.is_empty() {
+                *field = None;
+                return Ok(());
+            }
+            let path = project_path(text);
+            if !path.is_file() {
+                return Err(format!("No file at {}", path.display()));
+            }
+            *field = Some(text.to_string());
+            return Ok(());
+        }
+        if row == SettingsRow::Schema {
+            // Text starting with `{` is the schema itself, anything else a path
+            self.draft.schema = if text.is_empty() {
+                None
+            } else if text.starts_with('{') {
+                let schema = schema::parse_schema(text)?;
+                Some(SchemaSetting::Inline { schema })
+            } else {
+                let path = project_path(text);
+                if !path.is_file() {
+                    return Err(format!("No file at {}", path.display()));
+                }
+                Some(SchemaSetting::File {
+                    path: text.to_string(),
+                })
+            };
+            return Ok(());
+        }
+        let Some(poll) = self.draft.poll.as_mut() else {
+            return Ok(());
         };
-
-        if let Some(request_id) = created_request_id {
-            app.sidebar.selection_id = Some(request_id);
-            app.expand_sidebar_ancestors(request_id);
-            app.open_request(request_id);
-        } else if let Some(request_id) = session_current_request_id {
-            if app.sidebar_tree.nodes.contains_key(&request_id) {
-                app.sidebar.selection_id = Some(request_id);
-                app.expand_sidebar_ancestors(request_id);
-                app.open_request(request_id);
+        let positive = |what: &str| format!("{} must be a whole number above 0", what);
+        match (row, &mut poll.until) {
+            (SettingsRow::PollInterval, _) => {
+                poll.interval_secs = text
+                    .parse()
+                    .ok()
+                    .filter(|secs| *secs > 0)
+                    .ok_or_else(|| positive("The interval"))?;
+            }
+            (SettingsRow::PollAttempts, _) => {
+                poll.max_attempts = text
+                    .parse()
+                    .ok()
+                    .filter(|n| *n > 0)
+                    .ok_or_else(|| positive("Attempts"))?;
+            }
+            (SettingsRow::PollCodes, PollUntil::Status { codes }) => {
+                *codes = storage::parse_status_codes(text)?;
+            }
+            (SettingsRow::PollPath, PollUntil::Json { path, .. }) => {
+                if json_path::parse_path(text).is_none() {
+                    return Err(format!("\"{}\" is not a JSON path like $.state", text));
+                }
+                *path = text.to_string();
             }
-        } else if let Some(selection_id) = session_selection_id {
-            if app.sidebar_tree.nodes.contains_key(&selection_id) {
-                app.sidebar.selection_id = Some(selection_id);
-                app.expand_sidebar_ancestors(selection_id);
+            (SettingsRow::PollValue, PollUntil::Json { value, .. }) => {
+                *value = text.to_string();
             }
+            _ => {}
         }
+        Ok(())
+    }
 
-        app.apply_editor_tab_size();
-        app.persist_ui_state();
-        Ok(app)
+    pub fn selected_row(&self) -> SettingsRow {
+        let rows = self.rows();
+        rows[self.index.min(rows.len() - 1)]
     }
+}
 
-    fn active_environment(&self) -> Option<&Environment> {
-        self.active_environment_name
-            .as_ref()
-            .and_then(|name| self.environments.iter().find(|e| e.name == *name))
+/// Quick sidebar filter (`f`), applied together with the search query.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum SidebarFilter {
+    #[default]
+    All,
+    Method(HttpMethod),
+    /// Requests with edits that haven't been saved.
+    Unsaved,
+    /// Requests whose last send this session failed or got an error status.
+    Failing,
+    /// Folders and requests carrying the named tag.
+    Tag(String),
+}
+
+impl SidebarFilter {
+    /// Every filter, in the order the picker lists them, with one per tag
+    /// in `tags`.
+    pub fn choices(tags: &[ItemTag]) -> Vec<SidebarFilter> {
+        let mut choices = vec![SidebarFilter::All];
+        choices.extend(HttpMethod::ALL.into_iter().map(SidebarFilter::Method));
+        choices.extend([SidebarFilter::Unsaved, SidebarFilter::Failing]);
+        choices.extend(tags.iter().map(|tag| SidebarFilter::Tag(tag.name.clone())));
+        choices
     }
 
-    fn apply_editor_tab_size(&mut self) {
-        let tab = self.config.editor.tab_size;
-        self.request.url_editor.set_tab_length(tab);
-        self.request.headers_editor.set_tab_length(tab);
-        self.request.body_editor.set_tab_length(tab);
-        self.request.auth_token_editor.set_tab_length(tab);
-        self.request.auth_username_editor.set_tab_length(tab);
-        self.request.auth_password_editor.set_tab_length(tab);
-        self.request.auth_key_name_editor.set_tab_length(tab);
-        self.request.auth_key_value_editor.set_tab_length(tab);
+    pub fn label(&self) -> &str {
+        match self {
+            SidebarFilter::All => "All",
+            SidebarFilter::Method(method) => method.as_str(),
+            SidebarFilter::Unsaved => "Unsaved",
+            SidebarFilter::Failing => "Failing",
+            SidebarFilter::Tag(name) => name,
+        }
     }
+}
 
-    fn build_client(config: &Config) -> Result<Client> {
-        use reqwest::redirect::Policy;
+#[derive(Debug, Clone, Default)]
+pub struct SidebarState {
+    pub selection_id: Option<Uuid>,
+    pub expanded: HashSet<Uuid>,
+    pub search_query: String,
+    pub filter: SidebarFilter,
+    pub popup: Option<SidebarPopup>,
+    /// First tree row shown; kept so the selection stays in view.
+    pub scroll: usize,
+    /// Scroll the selection to the middle on the next draw.
+    pub center_selection: bool,
+    /// A row highlighted until the deadline so it's easy to spot.
+    pub flash: Option<(Uuid, Instant)>,
+}
 
-        let mut builder = Client::builder();
+#[derive(Debug, Clone)]
+pub struct SidebarLine {
+    pub id: Uuid,
+    pub prefix: String,
+    pub marker: String,
+    pub label: String,
+    pub kind: NodeKind,
+    pub method: Option<Method>,
+    /// Shown with a lock glyph; set on the locked item itself, not on what
+    /// it holds.
+    pub locked: bool,
+    /// How the request's last send went, shown after its name.
+    pub last_run: Option<LastRun>,
+    pub tags: Vec<ItemTag>,
+}
 
-        // Timeout (0 = no timeout, so we simply don't set one)
-        if config.http.timeout > 0 {
-            builder = builder.timeout(Duration::from_secs(config.http.timeout));
+impl SidebarLine {
+    /// Text drawn before the label: indentation plus the method or marker.
+    pub fn lead(&self) -> String {
+        let tag = match self.kind {
+            NodeKind::Request => self.method.as_ref().map(|m| m.as_str().to_string()),
+            NodeKind::Folder | NodeKind::Project => {
+                (!self.marker.is_empty()).then(|| self.marker.clone())
+            }
+        };
+        match tag {
+            Some(tag) => format!("{}{} ", self.prefix, tag),
+            None => self.prefix.clone(),
         }
+    }
+}
 
-        // Redirect policy
-        if config.http.follow_redirects {
-            builder = builder.redirect(Policy::limited(config.http.max_redirects as usize));
-        } else {
-            builder = builder.redirect(Policy::none());
-        }
+struct SidebarCache {
+    lines: Vec<SidebarLine>,
+    search_lines: Vec<SidebarLine>,
+    lines_dirty: bool,
+    search_dirty: bool,
+    search_query: String,
+    search_filter: SidebarFilter,
+    /// The open request if it had unsaved edits when the lines were built.
+    search_unsaved: Option<Uuid>,
+}
 
-        // Proxy
-        if let Some(ref proxy_url) = config.proxy.url {
-            let mut proxy = reqwest::Proxy::all(proxy_url)
-                .map_err(|e| anyhow::anyhow!("invalid proxy configuration: {}", e))?;
-            if let Some(ref no_proxy) = config.proxy.no_proxy {
-                let np = reqwest::NoProxy::from_string(no_proxy);
-                proxy = proxy.no_proxy(np);
-            }
-            builder = builder.proxy(proxy);
-        }
+impl Default for SidebarCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        // SSL verification
-        if !config.ssl.verify {
-            builder = builder.danger_accept_invalid_certs(true);
+impl SidebarCache {
+    fn new() -> Self {
+        Self {
+            lines: Vec::new(),
+            search_lines: Vec::new(),
+            lines_dirty: true,
+            search_dirty: true,
+            search_query: String::new(),
+            search_filter: SidebarFilter::All,
+            search_unsaved: None,
         }
+    }
 
-        // Custom CA certificate
-        if let Some(ref ca_path) = config.ssl.ca_cert {
-            let pem = std::fs::read(ca_path)
-                .map_err(|e| anyhow::anyhow!("failed to read CA cert \"{}\": {}", ca_path.display(), e))?;
-            let cert = reqwest::Certificate::from_pem(&pem)
-                .map_err(|e| anyhow::anyhow!("invalid CA cert \"{}\": {}", ca_path.display(), e))?;
-            builder = builder.add_root_certificate(cert);
-        }
+    fn invalidate_all(&mut self) {
+        self.lines_dirty = true;
+        self.search_dirty = true;
+    }
 
-        // Client certificate + key (mutual TLS)
-        if let (Some(ref cert_path), Some(ref key_path)) =
-            (&config.ssl.client_cert, &config.ssl.client_key)
-        {
-            let cert_pem = std::fs::read(cert_path).map_err(|e| {
-                anyhow::anyhow!("failed to read client cert \"{}\": {}", cert_path.display(), e)
-            })?;
-            let key_pem = std::fs::read(key_path).map_err(|e| {
-                anyhow::anyhow!("failed to read client key \"{}\": {}", key_path.display(), e)
-            })?;
-            let identity = reqwest::Identity::from_pkcs8_pem(&cert_pem, &key_pem)
-                .map_err(|e| anyhow::anyhow!("invalid client identity: {}", e))?;
-            builder = builder.identity(identity);
-        }
+    fn invalidate_search(&mut self) {
+        self.search_dirty = true;
+    }
 
-        builder
-            .build()
-            .map_err(|e| anyhow::anyhow!("failed to build HTTP client: {}", e))
+    /// Updates the lines of one request in place, so a response doesn't
+    /// rebuild the whole list.
+    fn set_last_run(&mut self, id: Uuid, last_run: LastRun) {
+        for line in self.lines.iter_mut().chain(self.search_lines.iter_mut()) {
+            if line.id == id {
+                line.last_run = Some(last_run);
+            }
+        }
     }
+}
 
-    pub async fn run(&mut self) -> Result<()> {
-        self.install_panic_hook();
-        self.setup_terminal()?;
+pub struct RequestState {
+    pub method: Method,
+    pub url_editor: TextArea<'static>,
+    pub headers_editor: TextArea<'static>,
+    /// The headers as table rows, loaded from `headers_editor` and written
+    /// back to it after every table edit.
+    pub header_rows: Vec<KvPair>,
+    pub body_editor: TextArea<'static>,
+    pub body_mode: BodyMode,
+    pub body_form_pairs: Vec<KvPair>,
+    pub body_multipart_fields: Vec<MultipartField>,
+    pub body_binary_path_editor: TextArea<'static>,
+    /// The body is kept but left out of sends.
+    pub body_disabled: bool,
+    pub auth_type: AuthType,
+    pub api_key_location: ApiKeyLocation,
+    pub auth_token_editor: TextArea<'static>,
+    pub auth_username_editor: TextArea<'static>,
+    pub auth_password_editor: TextArea<'static>,
+    pub auth_key_name_editor: TextArea<'static>,
+    pub auth_key_value_editor: TextArea<'static>,
+    /// Auth is kept but left out of sends.
+    pub auth_disabled: bool,
+}
 
-        let result = self.event_loop().await;
+#[derive(Clone, Copy)]
+enum YankTarget {
+    Request,
+    ResponseBody,
+    ResponseHeaders,
+}
 
-        self.persist_session_state();
-        self.restore_terminal()?;
-        result
-    }
+/// Text that `Ctrl+G` hands to the external editor. Response text comes
+/// back read-only; request text replaces the editor contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExternalEdit {
+    RequestHeaders,
+    RequestBody,
+    ResponseBody,
+    ResponseHeaders,
+}
 
-    pub fn clipboard_toast_message(&self) -> Option<&str> {
-        match &self.clipboard_toast {
-            Some((msg, at)) if at.elapsed() <= Self::CLIPBOARD_TOAST_DURATION => Some(msg.as_str()),
-            _ => None,
-        }
+impl Default for RequestState {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    fn set_clipboard_toast(&mut self, msg: impl Into<String>) {
-        self.clipboard_toast = Some((msg.into(), Instant::now()));
-        self.dirty = true;
-    }
+impl RequestState {
+    pub fn new() -> Self {
+        let mut url_editor = TextArea::default();
+        configure_editor(&mut url_editor, "Enter URL...");
 
-    fn mark_sidebar_dirty(&mut self) {
-        self.sidebar_cache.invalidate_all();
-        self.dirty = true;
-    }
+        let mut headers_editor = TextArea::default();
+        configure_editor(&mut headers_editor, "Key: Value");
 
-    fn mark_sidebar_search_dirty(&mut self) {
-        self.sidebar_cache.invalidate_search();
-        self.dirty = true;
+        let mut body_editor = TextArea::default();
+        configure_editor(&mut body_editor, "Request body...");
+
+        let mut body_binary_path_editor = TextArea::default();
+        configure_editor(&mut body_binary_path_editor, "File path...");
+
+        let mut auth_token_editor = TextArea::default();
+        configure_editor(&mut auth_token_editor, "Token");
+
+        let mut auth_username_editor = TextArea::default();
+        configure_editor(&mut auth_username_editor, "Username");
+
+        let mut auth_password_editor = TextArea::default();
+        configure_editor(&mut auth_password_editor, "Password");
+
+        let mut auth_key_name_editor = TextArea::default();
+        configure_editor(&mut auth_key_name_editor, "Key name");
+
+        let mut auth_key_value_editor = TextArea::default();
+        configure_editor(&mut auth_key_value_editor, "Key value");
+
+        Self {
+            method: Method::default(),
+            url_editor,
+            headers_editor,
+            header_rows: vec![KvPair::new_empty()],
+            body_editor,
+            body_mode: BodyMode::Raw,
+            body_form_pairs: vec![KvPair::new_empty()],
+            body_multipart_fields: vec![MultipartField::new_empty()],
+            body_binary_path_editor,
+            body_disabled: false,
+            auth_type: AuthType::NoAuth,
+            api_key_location: ApiKeyLocation::Header,
+            auth_token_editor,
+            auth_username_editor,
+            auth_password_editor,
+            auth_key_name_editor,
+            auth_key_value_editor,
+            auth_disabled: false,
+        }
     }
 
-    fn persist_ui_state(&self) {
-        let state = storage::UiState::new(self.active_project_id.to_string(), self.sidebar_width);
-        if let Err(err) = storage::save_ui_state(&state) {
-            eprintln!("Failed to save UI state: {}", err);
+    /// Replaces method, URL, headers and, when given, the body text. Auth and
+    /// the other body modes' fields are kept.
+    pub fn set_message(
+        &mut self,
+        method: Method,
+        url: String,
+        headers: String,
+        body: Option<String>,
+    ) {
+        self.method = method;
+        self.url_editor = TextArea::new(vec![url]);
+        configure_editor(&mut self.url_editor, "Enter URL...");
+        let header_lines: Vec<String> = headers.lines().map(str::to_string).collect();
+        self.headers_editor = TextArea::new(header_lines);
+        configure_editor(&mut self.headers_editor, "Key: Value");
+        if let Some(body) = body {
+            self.body_editor = TextArea::new(body.split('\n').map(str::to_string).collect());
+            configure_editor(&mut self.body_editor, "Request body...");
         }
+        self.load_header_rows();
     }
 
-    fn persist_session_state(&self) {
-        let Some(root_key) = storage::project_root_key() else {
-            return;
+    pub fn set_contents(&mut self, method: Method, url: String, headers: String, body: String) {
+        self.method = method;
+        let url_lines = if url.is_empty() { vec![String::new()] } else { vec![url] };
+        let header_lines = if headers.is_empty() {
+            vec![String::new()]
+        } else {
+            headers.lines().map(|l| l.to_string()).collect()
         };
-        let mut expanded: Vec<String> = self.sidebar.expanded.iter().map(|id| id.to_string()).collect();
-        expanded.sort();
-        let session = storage::SessionState {
-            active_project_id: self.active_project_id.to_string(),
-            sidebar_width: self.sidebar_width,
-            sidebar_visible: self.sidebar_visible,
-            selection_id: self.sidebar.selection_id.map(|id| id.to_string()),
-            current_request_id: self.current_request_id.map(|id| id.to_string()),
-            expanded,
-            request_tab: request_tab_to_str(self.request_tab).to_string(),
-            response_tab: self.response_tab.label().to_string(),
+        let body_lines = if body.is_empty() {
+            vec![String::new()]
+        } else {
+            body.lines().map(|l| l.to_string()).collect()
         };
-        if let Err(err) = storage::save_session_for_root(&root_key, session) {
-            eprintln!("Failed to save session: {}", err);
-        }
+
+        self.url_editor = TextArea::new(url_lines);
+        configure_editor(&mut self.url_editor, "Enter URL...");
+        self.headers_editor = TextArea::new(header_lines);
+        configure_editor(&mut self.headers_editor, "Key: Value");
+        self.body_editor = TextArea::new(body_lines);
+        configure_editor(&mut self.body_editor, "Request body...");
+        self.load_header_rows();
+
+        // Reset body mode fields
+        self.body_mode = BodyMode::Raw;
+        self.body_form_pairs = vec![KvPair::new_empty()];
+        self.body_multipart_fields = vec![MultipartField::new_empty()];
+        self.body_binary_path_editor = TextArea::default();
+        configure_editor(&mut self.body_binary_path_editor, "File path...");
+        self.body_disabled = false;
+
+        self.reset_auth();
     }
 
-    fn rebuild_sidebar_tree(&mut self) {
-        if let Ok(tree) = self.collection.build_tree(self.active_project_id) {
-            self.sidebar_tree = tree;
-        }
-        self.sidebar
-            .expanded
-            .retain(|id| self.sidebar_tree.nodes.contains_key(id));
-        if !self.sidebar.expanded.contains(&self.active_project_id) {
-            self.sidebar.expanded.insert(self.active_project_id);
-        }
-        if let Some(selected) = self.sidebar.selection_id {
-            if !self.sidebar_tree.nodes.contains_key(&selected) {
-                self.sidebar.selection_id = Some(self.active_project_id);
-            }
-        } else {
-            self.sidebar.selection_id = Some(self.active_project_id);
-        }
-        self.mark_sidebar_dirty();
+    pub fn reset_auth(&mut self) {
+        self.auth_type = AuthType::NoAuth;
+        self.auth_disabled = false;
+        self.api_key_location = ApiKeyLocation::Header;
+        self.auth_token_editor = TextArea::default();
+        configure_editor(&mut self.auth_token_editor, "Token");
+        self.auth_username_editor = TextArea::default();
+        configure_editor(&mut self.auth_username_editor, "Username");
+        self.auth_password_editor = TextArea::default();
+        configure_editor(&mut self.auth_password_editor, "Password");
+        self.auth_key_name_editor = TextArea::default();
+        configure_editor(&mut self.auth_key_name_editor, "Key name");
+        self.auth_key_value_editor = TextArea::default();
+        configure_editor(&mut self.auth_key_value_editor, "Key value");
     }
 
-    fn expand_sidebar_ancestors(&mut self, id: Uuid) {
-        let mut current = Some(id);
-        while let Some(node_id) = current {
-            if let Some(node) = self.sidebar_tree.node(node_id) {
-                if matches!(node.kind, NodeKind::Folder | NodeKind::Project) {
-                    self.sidebar.expanded.insert(node_id);
-                }
-                current = node.parent_id;
-            } else {
-                break;
-            }
-        }
-        self.mark_sidebar_dirty();
+    pub fn url_text(&self) -> String {
+        self.url_editor.lines().join("")
     }
 
-    fn focus_sidebar(&mut self) {
-        if !self.sidebar_visible {
-            self.sidebar_visible = true;
-        }
-        if let Some(request_id) = self.current_request_id {
-            if self.sidebar_tree.nodes.contains_key(&request_id) {
-                self.sidebar.selection_id = Some(request_id);
-                self.expand_sidebar_ancestors(request_id);
-            } else {
-                self.sidebar.selection_id = Some(self.active_project_id);
-            }
+    pub fn headers_text(&self) -> String {
+        self.headers_editor.lines().join("\n")
+    }
+
+    /// Rebuilds `header_rows` from the headers text. Lines commented out
+    /// with `#` become disabled rows; an empty row is kept at the end.
+    pub fn load_header_rows(&mut self) {
+        self.header_rows = storage::parse_headers(&self.headers_text())
+            .into_iter()
+            .map(|header| KvPair {
+                enabled: !header.disabled.unwrap_or(false),
+                key: header.key,
+                value: header.value,
+            })
+            .collect();
+        self.header_rows.push(KvPair::new_empty());
+    }
+
+    /// Writes `header_rows` back as the headers text.
+    pub fn store_header_rows(&mut self) {
+        let headers: Vec<PostmanHeader> = self
+            .header_rows
+            .iter()
+            .map(|row| PostmanHeader {
+                key: row.key.trim().to_string(),
+                value: row.value.clone(),
+                disabled: (!row.enabled).then_some(true),
+            })
+            .collect();
+        let text = headers_to_text(&headers);
+        let lines = if text.is_empty() {
+            vec![String::new()]
         } else {
-            self.sidebar.selection_id = Some(self.active_project_id);
-        }
-        self.focus.panel = Panel::Sidebar;
-        self.app_mode = AppMode::Sidebar;
+            text.lines().map(str::to_string).collect()
+        };
+        let tab = self.headers_editor.tab_length();
+        self.headers_editor = TextArea::new(lines);
+        configure_editor(&mut self.headers_editor, "Key: Value");
+        self.headers_editor.set_tab_length(tab);
     }
 
-    pub fn sidebar_lines(&mut self) -> &[SidebarLine] {
-        let _guard = perf::scope("sidebar_lines");
-        if self.sidebar.search_query.is_empty() {
-            if self.sidebar_cache.lines_dirty {
-                let mut lines = Vec::new();
-                self.collect_sidebar_lines(
-                    self.sidebar_tree.root_id,
-                    &[],
-                    true,
-                    true,
-                    &mut lines,
-                );
-                self.sidebar_cache.lines = lines;
-                self.sidebar_cache.lines_dirty = false;
-            }
-            return &self.sidebar_cache.lines;
-        }
+    pub fn body_text(&self) -> String {
+        self.body_editor.lines().join("\n")
+    }
 
-        let query = self.sidebar.search_query.as_str();
-        if self.sidebar_cache.search_dirty || self.sidebar_cache.search_query != query {
-            self.sidebar_cache.search_lines = self.sidebar_search_lines_for(query);
-            self.sidebar_cache.search_query = query.to_string();
-            self.sidebar_cache.search_dirty = false;
-        }
-        &self.sidebar_cache.search_lines
+    pub fn body_binary_path_text(&self) -> String {
+        self.body_binary_path_editor.lines().join("")
     }
 
-    fn sidebar_search_lines_for(&self, query: &str) -> Vec<SidebarLine> {
-        let _guard = perf::scope("sidebar_search_lines");
-        let mut lines = Vec::new();
-        let query = query.to_lowercase();
-        for (id, node) in &self.sidebar_tree.nodes {
-            if node.kind == NodeKind::Project {
-                continue;
+    pub fn auth_token_text(&self) -> String {
+        self.auth_token_editor.lines().join("")
+    }
+
+    pub fn auth_username_text(&self) -> String {
+        self.auth_username_editor.lines().join("")
+    }
+
+    pub fn auth_password_text(&self) -> String {
+        self.auth_password_editor.lines().join("")
+    }
+
+    pub fn auth_key_name_text(&self) -> String {
+        self.auth_key_name_editor.lines().join("")
+    }
+
+    pub fn auth_key_value_text(&self) -> String {
+        self.auth_key_value_editor.lines().join("")
+    }
+
+    pub fn active_editor(
+        &mut self,
+        field: RequestField,
+        body_field: BodyField,
+    ) -> Option<&mut TextArea<'static>> {
+        match field {
+            RequestField::Url => Some(&mut self.url_editor),
+            RequestField::Headers => Some(&mut self.headers_editor),
+            RequestField::Body => match body_field {
+                BodyField::TextEditor => Some(&mut self.body_editor),
+                BodyField::BinaryPath => Some(&mut self.body_binary_path_editor),
+                _ => None,
+            },
+            RequestField::Method | RequestField::Send | RequestField::Auth => None,
+        }
+    }
+}
+
+fn configure_editor(editor: &mut TextArea<'static>, placeholder: &str) {
+    editor.set_cursor_line_style(Style::default());
+    editor.set_placeholder_text(placeholder);
+}
+
+pub(crate) struct WrapCache {
+    pub(crate) width: usize,
+    pub(crate) generation: u64,
+    pub(crate) cursor: Option<(usize, usize)>,
+    pub(crate) selection: Option<((usize, usize), (usize, usize))>,
+    pub(crate) wrapped_lines: Vec<Line<'static>>,
+    pub(crate) cursor_pos: Option<(usize, usize)>,
+}
+
+impl WrapCache {
+    fn new() -> Self {
+        Self {
+            width: 0,
+            generation: 0,
+            cursor: None,
+            selection: None,
+            wrapped_lines: Vec::new(),
+            cursor_pos: None,
+        }
+    }
+}
+
+pub(crate) struct ResponseBodyRenderCache {
+    pub(crate) dirty: bool,
+    pub(crate) generation: u64,
+    pub(crate) body_text: String,
+    pub(crate) is_json: bool,
+    pub(crate) lines: Vec<Line<'static>>,
+    /// Display width of the longest line.
+    pub(crate) max_line_width: usize,
+    pub(crate) wrap_cache: WrapCache,
+    /// First display row shown. Each tab keeps its own; drawing clamps it
+    /// to the content.
+    pub(crate) scroll: usize,
+}
+
+impl Default for ResponseBodyRenderCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResponseBodyRenderCache {
+    fn new() -> Self {
+        Self {
+            dirty: true,
+            generation: 0,
+            body_text: String::new(),
+            is_json: false,
+            lines: Vec::new(),
+            max_line_width: 0,
+            wrap_cache: WrapCache::new(),
+            scroll: 0,
+        }
+    }
+}
+
+pub(crate) struct ResponseHeadersRenderCache {
+    pub(crate) dirty: bool,
+    pub(crate) generation: u64,
+    pub(crate) lines: Vec<Line<'static>>,
+    /// Display width of the longest line.
+    pub(crate) max_line_width: usize,
+    pub(crate) wrap_cache: WrapCache,
+    /// First display row shown. Each tab keeps its own; drawing clamps it
+    /// to the content.
+    pub(crate) scroll: usize,
+}
+
+impl Default for ResponseHeadersRenderCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResponseHeadersRenderCache {
+    fn new() -> Self {
+        Self {
+            dirty: true,
+            generation: 0,
+            lines: Vec::new(),
+            max_line_width: 0,
+            wrap_cache: WrapCache::new(),
+            scroll: 0,
+        }
+    }
+}
+
+/// Lines of the request body drawn soft-wrapped, rebuilt when the text
+/// changes.
+pub(crate) struct BodyWrapCache {
+    /// Hash of the body text the lines were built from.
+    pub(crate) text_hash: u64,
+    pub(crate) lines: Vec<Line<'static>>,
+    pub(crate) wrap_cache: WrapCache,
+    /// First display row shown.
+    pub(crate) scroll: usize,
+}
+
+impl Default for BodyWrapCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BodyWrapCache {
+    fn new() -> Self {
+        Self {
+            text_hash: 0,
+            lines: Vec::new(),
+            wrap_cache: WrapCache::new(),
+            scroll: 0,
+        }
+    }
+}
+
+/// Panels whose content changed since the last draw. A clean panel reuses
+/// the lines it built for the previous frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct DirtyPanels {
+    pub(crate) sidebar: bool,
+    pub(crate) request: bool,
+    pub(crate) response: bool,
+    pub(crate) status: bool,
+}
+
+impl DirtyPanels {
+    pub(crate) const ALL: Self = Self {
+        sidebar: true,
+        request: true,
+        response: true,
+        status: true,
+    };
+    pub(crate) const SIDEBAR: Self = Self {
+        sidebar: true,
+        ..Self::NONE
+    };
+    pub(crate) const REQUEST: Self = Self {
+        request: true,
+        ..Self::NONE
+    };
+    pub(crate) const RESPONSE: Self = Self {
+        response: true,
+        ..Self::NONE
+    };
+    pub(crate) const STATUS: Self = Self {
+        status: true,
+        ..Self::NONE
+    };
+    const NONE: Self = Self {
+        sidebar: false,
+        request: false,
+        response: false,
+        status: false,
+    };
+}
+
+impl std::ops::BitOr for DirtyPanels {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self {
+            sidebar: self.sidebar || other.sidebar,
+            request: self.request || other.request,
+            response: self.response || other.response,
+            status: self.status || other.status,
+        }
+    }
+}
+
+/// Lines last built for a panel and the area they were laid out for.
+#[derive(Default)]
+pub(crate) struct PanelCache {
+    pub(crate) area: Rect,
+    pub(crate) lines: Vec<Line<'static>>,
+}
+
+impl PanelCache {
+    pub(crate) fn is_stale(&self, dirty: bool, area: Rect) -> bool {
+        dirty || self.area != area
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct PanelCaches {
+    pub(crate) sidebar: PanelCache,
+    pub(crate) request_tabs: PanelCache,
+    pub(crate) response_tabs: PanelCache,
+    pub(crate) status_bar: PanelCache,
+}
+
+#[cfg_attr(test, derive(Default))]
+pub struct App {
+    running: bool,
+    dirty: bool,
+    pub(crate) dirty_panels: DirtyPanels,
+    pub(crate) panel_caches: PanelCaches,
+    pub show_perf_overlay: bool,
+    pub perf_last_frame: Vec<perf::ScopeTiming>,
+    pub config: Config,
+    pub config_provenance: ConfigProvenance,
+    pub request: RequestState,
+    pub focus: FocusState,
+    pub response: ResponseStatus,
+    pub response_tab: ResponseTab,
+    pub request_tab: RequestTab,
+    pub client: Client,
+    pub app_mode: AppMode,
+    pub vim: Vim,
+    /// First column shown when the response panel doesn't wrap.
+    pub response_hscroll: u16,
+    /// Long response lines are soft-wrapped; off, they are clipped and
+    /// `h`/`l` scroll sideways.
+    pub response_wrap: bool,
+    /// Long request body lines are soft-wrapped, and `gj`/`gk` move by
+    /// screen line.
+    pub body_wrap: bool,
+    pub(crate) body_wrap_cache: BodyWrapCache,
+    /// The long-line hint was shown this session.
+    body_line_hinted: bool,
+    /// Repeated response header names are numbered (`set-cookie [1/2]`).
+    response_header_counts: bool,
+    /// Response headers by group and name instead of as received (`o`).
+    pub response_header_sort: bool,
+    /// Only response headers containing this are shown (`/`).
+    pub response_header_filter: String,
+    /// The filter while it's being typed.
+    pub header_filter_input: Option<TextInput>,
+    /// The headers tab was brought up for a response without a body; the
+    /// next response with one goes back to the body tab.
+    headers_for_no_body: bool,
+    /// Width of the response content area at the last draw, which bounds
+    /// horizontal scrolling.
+    pub(crate) response_view_width: u16,
+    /// Height of the response content area at the last draw, one page.
+    pub(crate) response_view_height: u16,
+    pub loading_tick: u8,
+    pub show_help: bool,
+    pub help: HelpOverlay,
+    /// Masks every secret field, overriding per-field reveals.
+    pub privacy_mode: bool,
+    /// Sends don't go out; a request's saved example answers instead.
+    pub offline: bool,
+    /// High contrast and reduced motion, from `[ui]` until toggled in the
+    /// config viewer.
+    pub theme: ui::Theme,
+    /// Layout of pretty-printed JSON, from `[json]` until sorting is
+    /// toggled in the config viewer.
+    pub json_style: format::JsonStyle,
+    revealed_secrets: HashSet<AuthField>,
+    pub show_method_popup: bool,
+    pub method_popup_index: usize,
+    pub method_popup_custom_mode: bool,
+    pub method_custom_input: String,
+    /// Custom methods used lately, most recent first, listed under
+    /// "Custom..." in the method popup. Saved with the UI state.
+    pub recent_methods: Vec<String>,
+    pub show_auth_type_popup: bool,
+    pub auth_type_popup_index: usize,
+    pub sidebar_visible: bool,
+    pub sidebar_width: u16,
+    pub collection: CollectionStore,
+    pub project_list: Vec<ProjectInfo>,
+    pub sidebar_tree: ProjectTree,
+    pub sidebar: SidebarState,
+    sidebar_cache: SidebarCache,
+    pub active_project_id: Uuid,
+    /// Expanded folders and selection of the projects not shown, restored
+    /// when switching back. Saved with the session.
+    project_sidebars: HashMap<Uuid, ProjectSidebar>,
+    pub current_request_id: Option<Uuid>,
+    /// Cursor and tab positions per request, saved with the session.
+    request_positions: HashMap<Uuid, RequestPosition>,
+    pub request_dirty: bool,
+    pub notifications: Notifications,
+    pub save_status: SaveStatus,
+    /// Pending writes of `.perseus/ui.json` and the session store.
+    ui_state_write: storage::WriteDebounce,
+    session_write: storage::WriteDebounce,
+    /// Changelog entries not yet appended to `.perseus/changelog.jsonl`.
+    changelog_pending: Vec<ChangeEntry>,
+    changelog_write: storage::WriteDebounce,
+    /// Held-back state writes, shared with the panic hook.
+    panic_flush: Arc<Mutex<PanicFlush>>,
+    pub changelog_popup: Option<ChangelogPopup>,
+    response_store: ResponseStore,
+    /// Requests whose last send this session failed, for the sidebar filter.
+    failing_requests: HashSet<Uuid>,
+    /// The last status of every request sent, saved with the session.
+    last_runs: HashMap<Uuid, LastRun>,
+    /// Recent sends of every request, oldest first, saved with the session.
+    send_history: HashMap<Uuid, Vec<SendStat>>,
+    pub show_send_history: bool,
+    /// The user's notes on each request, saved with the session rather
+    /// than the collection.
+    request_notes: HashMap<Uuid, String>,
+    pub notes_popup: Option<NotesPopup>,
+    /// The request the in-flight (or last) send belongs to.
+    sent_request_id: Option<Uuid>,
+    /// Execution log entry for the in-flight send, written once it ends.
+    pending_send_log: Option<sendlog::SendRecord>,
+    /// Last execution log failure, so a broken log warns once, not per send.
+    send_log_error: Option<String>,
+    /// Schema of the in-flight send, if its request has one.
+    sent_schema: Option<SchemaSetting>,
+    /// Parsed schema files by path, with the modification time they were
+    /// read at so edits are picked up.
+    schema_cache: HashMap<std::path::PathBuf, CachedSchema>,
+    /// Latest schema validation per request, this session.
+    schema_checks: HashMap<Uuid, SchemaCheck>,
+    pub show_schema_check: bool,
+    pub show_message_log: bool,
+    pub message_log_scroll: u16,
+    /// Request files that didn't parse on the last sync; they are left as
+    /// they are on disk until fixed.
+    pub request_file_errors: Vec<storage::RequestFileError>,
+    /// Selected row of the unreadable request files popup, while it's open.
+    pub request_file_popup: Option<usize>,
+    request_handle: Option<tokio::task::AbortHandle>,
+    /// Stops the in-flight body download, keeping what was received.
+    request_cancel: Option<oneshot::Sender<()>>,
+    pub loading_progress: Option<http::Progress>,
+    /// When the in-flight request was dispatched, for the elapsed timer.
+    pub loading_started: Option<Instant>,
+    pub poll: Option<PollRun>,
+    /// The folder run in progress, or the last one while its results show.
+    pub run: Option<FolderRun>,
+    pub run_popup: Option<RunPopup>,
+    pub url_audit: Option<UrlAuditPopup>,
+    pub usages_popup: Option<UsagesPopup>,
+    pub env_variables: Option<EnvVariablesPopup>,
+    /// Requests flagged by the last URL audit, marked in the sidebar.
+    pub url_audit_flags: HashSet<Uuid>,
+    /// Requests of the active project that reference another request's
+    /// response, marked in the sidebar.
+    pub chained_requests: HashSet<Uuid>,
+    /// Expands the raw error chain under a failed request (`D`).
+    pub show_error_detail: bool,
+    clipboard: ClipboardProvider,
+    last_yank_request: String,
+    last_yank_response: String,
+    last_yank_response_headers: String,
+    pub response_editor: TextArea<'static>,
+    pub response_headers_editor: TextArea<'static>,
+    pub(crate) response_body_cache: ResponseBodyRenderCache,
+    pub(crate) response_headers_cache: ResponseHeadersRenderCache,
+    pub environments: Vec<Environment>,
+    pub active_environment_name: Option<String>,
+    pub show_env_popup: bool,
+    pub env_popup_index: usize,
+    pub show_body_mode_popup: bool,
+    pub body_mode_popup_index: usize,
+    pub kv_edit_textarea: Option<TextArea<'static>>,
+    /// Show the Headers tab as a key/value table instead of text.
+    pub headers_table: bool,
+    url_error: Option<(String, http::UrlError)>,
+    /// `[hosts]` override applied to the last sent request, shown in the
+    /// response Headers tab.
+    sent_host_override: Option<String>,
+    pub request_settings: RequestSettings,
+    pub settings_popup: Option<SettingsPopup>,
+    /// Clients for requests whose settings deviate from the global config,
+    /// keyed by `client_cache_key`.
+    client_cache: HashMap<String, Client>,
+    /// Header-case preserving connectors by `client_cache_key`, the shared
+    /// config's under "".
+    connector_cache: HashMap<String, Option<http1::Connector>>,
+    pub show_config_viewer: bool,
+    pub config_viewer_scroll: u16,
+    pub config_viewer_notice: Option<String>,
+    pub error_popup: Option<ErrorPopup>,
+    pub response_hex_view: bool,
+    pub response_preview: bool,
+    pub save_response_popup: Option<TextInput>,
+    pub example_popup: Option<ExamplePopup>,
+    pub pipe_popup: Option<PipePopup>,
+    /// Last command run with `!`, offered again next time.
+    last_pipe_command: String,
+    /// Whether requests from this project root may be sent without review.
+    pub project_trusted: bool,
+    /// The project root, while the trust prompt is shown.
+    pub trust_prompt: Option<String>,
+    pub draft_prompt: Option<DraftPrompt>,
+    /// Shown on the first run in a project root until answered.
+    pub onboarding: Option<Onboarding>,
+    /// This instance's hold on the project; dropping it releases the lock.
+    project_lock: Option<storage::ProjectLock>,
+    /// Another instance owns the project: edits are refused, nothing is
+    /// written and the collection is re-read when the owner changes it.
+    pub read_only: bool,
+    /// The instance holding the lock, while this one is read-only.
+    pub lock_owner: Option<storage::LockOwner>,
+    /// Name of the project root when `--root` or `PERSEUS_ROOT` set it.
+    pub root_override: Option<String>,
+    /// Asks whether to take over a lock whose owner is gone.
+    pub lock_prompt: bool,
+    /// The prompt was answered with read-only; it isn't asked again.
+    stale_lock_declined: bool,
+    /// When `collection.json` was last read, to notice the owner's writes.
+    collection_modified: Option<std::time::SystemTime>,
+    pub spec_update: Option<SpecUpdatePrompt>,
+    /// The open request and its serialized contents as last written to
+    /// its draft, so unchanged edits aren't rewritten every tick.
+    draft_snapshot: Option<(Uuid, String)>,
+    pub send_confirm: Option<SendConfirm>,
+    pub protected_confirm: Option<ProtectedConfirm>,
+    pub unresolved_confirm: Option<UnresolvedConfirm>,
+    pub body_confirm: Option<BodyConfirm>,
+    pub env_file_prompt: Option<EnvFilePrompt>,
+    pub raw_view: Option<RawView>,
+    pub url_outline: Option<UrlOutlinePopup>,
+    pub scratch_send: Option<ScratchSend>,
+    /// Scratch sends put away with Esc, by request, for the session.
+    scratch_drafts: HashMap<Option<Uuid>, ScratchSend>,
+    scratch_message: Option<ScratchMessage>,
+    pub send_preview: Option<SendPreviewPopup>,
+    pub cors_popup: Option<CorsPopup>,
+    /// Set by `Ctrl+G`; the event loop suspends the TUI and runs the editor.
+    pending_external_edit: Option<ExternalEdit>,
+    /// Set by Enter on an unreadable request file; the event loop suspends
+    /// the TUI and runs the editor on the file.
+    pending_file_edit: Option<std::path::PathBuf>,
+    /// Set by `|`; the event loop suspends the TUI and runs the pager on it.
+    pending_pager: Option<Vec<u8>>,
+    /// Response kept for comparison (`p` on the response panel).
+    pub pinned_response: Option<ResponseData>,
+    pub compare_view: Option<CompareView>,
+    pub codec_popup: Option<CodecPopup>,
+    pub clipboard_ring: Option<ClipboardRingPopup>,
+    pub snippet_popup: Option<SnippetPopup>,
+    /// An untrusted project still confirms sends that read files, but only
+    /// the first of the others.
+    untrusted_send_confirmed: bool,
+    /// Set for the single send the confirm popup let through.
+    send_confirm_granted: bool,
+    /// Unresolved variables were accepted for the send in progress; cleared
+    /// once it is dispatched or cancelled.
+    unresolved_send_granted: bool,
+    /// The protected send was confirmed; taken by the next send.
+    protected_send_granted: bool,
+    /// The method and body were accepted for the send in progress; cleared
+    /// like `unresolved_send_granted`.
+    body_send_granted: bool,
+}
+
+impl App {
+    /// Bytes rendered by the hex view before it stops with a note.
+    const HEX_VIEW_LIMIT: usize = 1024 * 1024;
+    /// Columns left for the name below which rename uses the popup instead
+    /// of editing the sidebar row in place.
+    const INLINE_RENAME_MIN_WIDTH: usize = 12;
+    /// How long a revealed sidebar row stays highlighted.
+    const SIDEBAR_FLASH: Duration = Duration::from_secs(1);
+    /// Drawn in place of each character of a masked secret.
+    const SECRET_MASK_CHAR: char = '\u{2022}';
+    /// How often unsaved edits are written to the open request's draft.
+    const DRAFT_INTERVAL: Duration = Duration::from_secs(5);
+    /// How often a read-only instance looks for changes by the owner.
+    const LOCK_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+    /// How soon the responses of a parallel run are picked up.
+    const RUN_RESULTS_INTERVAL: Duration = Duration::from_millis(50);
+
+    pub fn new() -> Result<Self> {
+        let (config, config_provenance) =
+            config::load_config().map_err(anyhow::Error::msg)?;
+
+        let client = Self::build_client(&config, &RequestSettings::default())?;
+        let clipboard = ClipboardProvider::new(config.clipboard.osc52);
+        let theme = ui::Theme::from_config(&config.ui);
+        let json_style = config.json.style();
+
+        // Another instance holding the project lock makes this one read-only
+        let mut lock_error = None;
+        let lock = storage::ensure_storage_dir().and_then(|dir| storage::acquire_lock(&dir));
+        let (project_lock, lock_owner, stale_lock) = match lock {
+            Ok(LockStatus::Acquired(lock)) => (Some(lock), None, false),
+            Ok(LockStatus::Held(owner)) => (None, Some(owner), false),
+            Ok(LockStatus::Stale(owner)) => (None, owner, true),
+            Err(err) => {
+                lock_error = Some(err);
+                (None, None, false)
             }
-            if node.name_lower.contains(&query) {
-                let path = self.sidebar_tree.path_for(*id).join("/");
-                let method = if node.kind == NodeKind::Request {
-                    node.request_method
-                        .as_deref()
-                        .map(Method::from_str)
-                } else {
+        };
+        let read_only = project_lock.is_none() && lock_error.is_none();
+
+        // A project whose collection is created right now is the user's own.
+        let fresh_project = !storage::collection_path().is_some_and(|path| path.exists());
+        let loaded = if read_only {
+            CollectionStore::load_read_only()
+        } else {
+            CollectionStore::load_or_init()
+        };
+        let mut collection = match loaded {
+            Ok(collection) => collection,
+            Err(err) => match err.restorable_backup() {
+                Some((path, backup)) if !read_only && confirm_restore(&err, &backup) => {
+                    let corrupt =
+                        CollectionStore::restore_backup(path).map_err(anyhow::Error::msg)?;
+                    eprintln!("Restored from backup; the damaged file is at {}", corrupt.display());
+                    CollectionStore::load_or_init().map_err(|e| anyhow::anyhow!("{}", e))?
+                }
+                _ => return Err(anyhow::anyhow!("{}", err)),
+            },
+        };
+        if collection.collection.item.is_empty() {
+            let root_name = collection
+                .root
+                .file_name()
+                .and_then(|s| s.to_str())
+                .unwrap_or("Perseus")
+                .to_string();
+            let _ = collection
+                .add_project(root_name)
+                .map_err(anyhow::Error::msg)?;
+            if !read_only {
+                collection.save().map_err(anyhow::Error::msg)?;
+            }
+        }
+
+        let project_list = collection.list_projects();
+        if project_list.is_empty() {
+            return Err(anyhow::anyhow!("No projects found in collection"));
+        }
+
+        // A damaged state file is moved aside and reported once the UI is up
+        let mut state_errors = Vec::new();
+        let stored_ui_state = storage::load_ui_state().unwrap_or_else(|err| {
+            state_errors.push(err);
+            None
+        });
+        let first_open = stored_ui_state.is_none() && state_errors.is_empty();
+        let ui_state = stored_ui_state
+            .unwrap_or_else(|| storage::UiState::new(project_list[0].id.to_string(), config.ui.sidebar_width));
+
+        let root_key = storage::project_root_key();
+        let session_state = match root_key.as_deref() {
+            Some(key) => match storage::load_session_for_root(key) {
+                Ok(state) => state,
+                Err(err) => {
+                    state_errors.push(format!("Failed to load session: {}", err));
                     None
-                };
-                lines.push(SidebarLine {
-                    id: *id,
-                    prefix: String::new(),
-                    marker: String::new(),
-                    label: path,
-                    kind: node.kind,
-                    method,
-                });
+                }
+            },
+            None => None,
+        };
+        let stored_trust = match root_key.as_deref() {
+            Some(key) => storage::load_trust_for_root(key).unwrap_or_else(|err| {
+                state_errors.push(format!("Failed to load project trust: {}", err));
+                None
+            }),
+            None => None,
+        };
+
+        let session_active_project = session_state
+            .as_ref()
+            .and_then(|state| Uuid::parse_str(&state.active_project_id).ok());
+        let ui_active_project = Uuid::parse_str(&ui_state.active_project_id).ok();
+        let mut active_project_id = session_active_project
+            .or(ui_active_project)
+            .unwrap_or(project_list[0].id);
+        if !project_list.iter().any(|p| p.id == active_project_id) {
+            active_project_id = project_list[0].id;
+        }
+
+        let mut created_request_id: Option<Uuid> = None;
+        if !collection_has_requests(&collection.collection.item) {
+            let req = config.defaults.new_request(String::new());
+            let new_id = collection
+                .add_request(active_project_id, "New Request".to_string(), req)
+                .map_err(anyhow::Error::msg)?;
+            if !read_only {
+                collection.save().map_err(anyhow::Error::msg)?;
+            }
+            created_request_id = Some(new_id);
+        }
+
+        let sidebar_width = clamp_sidebar_width(
+            session_state
+                .as_ref()
+                .map(|state| state.sidebar_width)
+                .unwrap_or(ui_state.sidebar_width),
+        );
+        let sidebar_visible = session_state
+            .as_ref()
+            .map(|state| state.sidebar_visible)
+            .unwrap_or(true);
+        let request_tab = session_state
+            .as_ref()
+            .map(|state| request_tab_from_str(&state.request_tab))
+            .unwrap_or_else(|| default_request_tab(&config));
+        let response_tab = session_state
+            .as_ref()
+            .map(|state| response_tab_from_str(&state.response_tab))
+            .unwrap_or_else(|| default_response_tab(&config));
+        let headers_table = session_state
+            .as_ref()
+            .is_some_and(|state| state.headers_table);
+        let response_wrap = session_state
+            .as_ref()
+            .is_none_or(|state| state.response_wrap);
+        let body_wrap = session_state
+            .as_ref()
+            .is_some_and(|state| state.body_wrap);
+        let mut project_sidebars: HashMap<Uuid, ProjectSidebar> = session_state
+            .as_ref()
+            .map(|state| {
+                state
+                    .projects
+                    .iter()
+                    .filter_map(|(id, sidebar)| {
+                        Some((Uuid::parse_str(id).ok()?, ProjectSidebar::from_session(sidebar)))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let active_sidebar = project_sidebars.remove(&active_project_id).unwrap_or_default();
+        let session_selection_id = active_sidebar.selection_id;
+        let session_current_request_id = session_state
+            .as_ref()
+            .and_then(|state| state.current_request_id.as_ref())
+            .and_then(|id| Uuid::parse_str(id).ok());
+        let request_positions: HashMap<Uuid, RequestPosition> = session_state
+            .as_ref()
+            .map(|state| {
+                state
+                    .positions
+                    .iter()
+                    .filter_map(|(id, pos)| Some((Uuid::parse_str(id).ok()?, pos.clone())))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let last_runs: HashMap<Uuid, LastRun> = session_state
+            .as_ref()
+            .map(|state| {
+                state
+                    .last_runs
+                    .iter()
+                    .filter_map(|(id, run)| Some((Uuid::parse_str(id).ok()?, *run)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let send_history: HashMap<Uuid, Vec<SendStat>> = session_state
+            .as_ref()
+            .map(|state| {
+                state
+                    .send_history
+                    .iter()
+                    .filter_map(|(id, sends)| Some((Uuid::parse_str(id).ok()?, sends.clone())))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let request_notes: HashMap<Uuid, String> = session_state
+            .as_ref()
+            .map(|state| {
+                state
+                    .notes
+                    .iter()
+                    .filter_map(|(id, note)| Some((Uuid::parse_str(id).ok()?, note.clone())))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let sidebar_tree = collection
+            .build_tree(active_project_id)
+            .map_err(anyhow::Error::msg)?;
+
+        let mut expanded: HashSet<Uuid> = active_sidebar
+            .expanded
+            .into_iter()
+            .filter(|id| sidebar_tree.nodes.contains_key(id))
+            .collect();
+        expanded.insert(active_project_id);
+        let session_selection_id =
+            session_selection_id.filter(|id| sidebar_tree.nodes.contains_key(id));
+        let selection_id = session_selection_id.unwrap_or(active_project_id);
+
+        let sidebar = SidebarState {
+            selection_id: Some(selection_id),
+            expanded,
+            search_query: String::new(),
+            filter: SidebarFilter::All,
+            popup: None,
+            scroll: 0,
+            center_selection: true,
+            flash: None,
+        };
+
+        let request_files = collection
+            .write_all_request_files()
+            .map_err(anyhow::Error::msg)?;
+
+        let environments = environment::load_all_environments().unwrap_or_default();
+
+        let mut app = Self {
+            running: true,
+            dirty: true,
+            dirty_panels: DirtyPanels::ALL,
+            panel_caches: PanelCaches::default(),
+            show_perf_overlay: false,
+            perf_last_frame: Vec::new(),
+            config,
+            config_provenance,
+            request: RequestState::new(),
+            focus: FocusState::default(),
+            response: ResponseStatus::Empty,
+            response_tab,
+            request_tab,
+            client,
+            app_mode: AppMode::Navigation,
+            vim: Vim::new(VimMode::Normal),
+            response_hscroll: 0,
+            response_wrap,
+            body_wrap,
+            body_wrap_cache: BodyWrapCache::new(),
+            body_line_hinted: false,
+            response_header_counts: false,
+            response_header_sort: false,
+            response_header_filter: String::new(),
+            header_filter_input: None,
+            headers_for_no_body: false,
+            response_view_width: 0,
+            response_view_height: 0,
+            loading_tick: 0,
+            show_help: false,
+            help: HelpOverlay::default(),
+            privacy_mode: false,
+            offline: false,
+            theme,
+            json_style,
+            revealed_secrets: HashSet::new(),
+            show_method_popup: false,
+            method_popup_index: 0,
+            method_popup_custom_mode: false,
+            method_custom_input: String::new(),
+            recent_methods: ui_state.recent_methods.clone(),
+            show_auth_type_popup: false,
+            auth_type_popup_index: 0,
+            sidebar_visible,
+            sidebar_width,
+            collection,
+            project_list,
+            sidebar_tree,
+            sidebar,
+            sidebar_cache: SidebarCache::new(),
+            active_project_id,
+            project_sidebars,
+            current_request_id: None,
+            request_positions,
+            request_dirty: false,
+            notifications: Notifications::default(),
+            save_status: SaveStatus::Saved,
+            ui_state_write: storage::WriteDebounce::default(),
+            session_write: storage::WriteDebounce::default(),
+            changelog_pending: Vec::new(),
+            changelog_write: storage::WriteDebounce::default(),
+            panic_flush: Arc::default(),
+            changelog_popup: None,
+            response_store: ResponseStore::default(),
+            failing_requests: HashSet::new(),
+            last_runs,
+            send_history,
+            show_send_history: false,
+            request_notes,
+            notes_popup: None,
+            sent_request_id: None,
+            pending_send_log: None,
+            send_log_error: None,
+            sent_schema: None,
+            schema_cache: HashMap::new(),
+            schema_checks: HashMap::new(),
+            show_schema_check: false,
+            show_message_log: false,
+            message_log_scroll: 0,
+            request_file_errors: request_files.unreadable,
+            request_file_popup: None,
+            request_handle: None,
+            request_cancel: None,
+            loading_progress: None,
+            loading_started: None,
+            poll: None,
+            run: None,
+            run_popup: None,
+            url_audit: None,
+            usages_popup: None,
+            env_variables: None,
+            url_audit_flags: HashSet::new(),
+            chained_requests: HashSet::new(),
+            show_error_detail: false,
+            clipboard,
+            last_yank_request: String::new(),
+            last_yank_response: String::new(),
+            last_yank_response_headers: String::new(),
+            response_editor: {
+                let mut editor = TextArea::default();
+                editor.set_cursor_line_style(Style::default());
+                editor
+            },
+            response_headers_editor: {
+                let mut editor = TextArea::default();
+                editor.set_cursor_line_style(Style::default());
+                editor
+            },
+            response_body_cache: ResponseBodyRenderCache::new(),
+            response_headers_cache: ResponseHeadersRenderCache::new(),
+            environments,
+            active_environment_name: None,
+            show_env_popup: false,
+            env_popup_index: 0,
+            show_body_mode_popup: false,
+            body_mode_popup_index: 0,
+            kv_edit_textarea: None,
+            headers_table,
+            url_error: None,
+            sent_host_override: None,
+            request_settings: RequestSettings::default(),
+            settings_popup: None,
+            client_cache: HashMap::new(),
+            connector_cache: HashMap::new(),
+            show_config_viewer: false,
+            config_viewer_scroll: 0,
+            config_viewer_notice: None,
+            error_popup: None,
+            response_hex_view: false,
+            response_preview: false,
+            save_response_popup: None,
+            example_popup: None,
+            pipe_popup: None,
+            last_pipe_command: String::new(),
+            project_trusted: fresh_project || stored_trust == Some(true),
+            trust_prompt: None,
+            draft_prompt: None,
+            onboarding: None,
+            project_lock: None,
+            read_only: false,
+            lock_owner: None,
+            root_override: storage::overridden_root_name(),
+            lock_prompt: false,
+            stale_lock_declined: false,
+            collection_modified: None,
+            spec_update: None,
+            draft_snapshot: None,
+            send_confirm: None,
+            protected_confirm: None,
+            unresolved_confirm: None,
+            body_confirm: None,
+            env_file_prompt: None,
+            raw_view: None,
+            url_outline: None,
+            scratch_send: None,
+            scratch_drafts: HashMap::new(),
+            scratch_message: None,
+            send_preview: None,
+            cors_popup: None,
+            pending_external_edit: None,
+            pending_file_edit: None,
+            pending_pager: None,
+            pinned_response: None,
+            compare_view: None,
+            codec_popup: None,
+            clipboard_ring: None,
+            snippet_popup: None,
+            untrusted_send_confirmed: false,
+            send_confirm_granted: false,
+            unresolved_send_granted: false,
+            protected_send_granted: false,
+            body_send_granted: false,
+        };
+
+        if let Some(request_id) = created_request_id {
+            app.sidebar.selection_id = Some(request_id);
+            app.expand_sidebar_ancestors(request_id);
+            app.open_request(request_id);
+        } else if let Some(request_id) = session_current_request_id {
+            if app.sidebar_tree.nodes.contains_key(&request_id) {
+                app.sidebar.selection_id = Some(request_id);
+                app.expand_sidebar_ancestors(request_id);
+                app.open_request(request_id);
+            }
+        } else if let Some(selection_id) = session_selection_id {
+            if app.sidebar_tree.nodes.contains_key(&selection_id) {
+                app.sidebar.selection_id = Some(selection_id);
+                app.expand_sidebar_ancestors(selection_id);
+            }
+        }
+
+        app.refresh_chained_requests();
+        for err in state_errors {
+            app.notify(Level::Warn, err);
+        }
+        if !app.request_file_errors.is_empty() {
+            app.notify(Level::Warn, app.request_file_notice());
+        }
+        if let Some(err) = lock_error {
+            app.notify(Level::Warn, format!("{}; other instances won't see this one", err));
+        }
+        app.project_lock = project_lock;
+        app.read_only = read_only;
+        app.lock_owner = lock_owner;
+        app.lock_prompt = stale_lock;
+        app.collection_modified = collection_modified();
+        if read_only && !stale_lock {
+            app.notify(Level::Warn, app.read_only_notice());
+        }
+        // First run: nothing of this root has been seen before and the
+        // collection only has the request just created for it
+        let first_run = first_open && session_state.is_none() && created_request_id.is_some();
+        if (first_run || ui_state.onboarding_pending) && !read_only {
+            app.onboarding = Some(Onboarding::Choose { index: 0 });
+        }
+        if fresh_project {
+            app.set_project_trust(true);
+        } else if stored_trust.is_none() {
+            app.open_trust_prompt();
+        }
+        // Drafts belong to the instance that owns the project
+        if !read_only {
+            app.recover_drafts();
+        }
+        app.apply_editor_tab_size();
+        app.persist_ui_state();
+        Ok(app)
+    }
+
+    /// The open request's description, shown until it's first sent.
+    pub fn current_request_description(&self) -> Option<&str> {
+        let id = self.current_request_id?;
+        self.collection.get_item(id)?.description_text()
+    }
+
+    fn active_environment(&self) -> Option<&Environment> {
+        self.active_environment_name
+            .as_ref()
+            .and_then(|name| self.environments.iter().find(|e| e.name == *name))
+    }
+
+    /// The active environment's enabled variables, inherited ones included.
+    /// Fails when what it extends is missing or extends it back.
+    fn active_variables(&self) -> Result<HashMap<String, String>, String> {
+        environment::resolve_variables(self.active_environment(), &self.environments)
+    }
+
+    fn apply_editor_tab_size(&mut self) {
+        let tab = self.config.editor.tab_size;
+        self.request.url_editor.set_tab_length(tab);
+        self.request.headers_editor.set_tab_length(tab);
+        self.request.body_editor.set_tab_length(tab);
+        self.request.auth_token_editor.set_tab_length(tab);
+        self.request.auth_username_editor.set_tab_length(tab);
+        self.request.auth_password_editor.set_tab_length(tab);
+        self.request.auth_key_name_editor.set_tab_length(tab);
+        self.request.auth_key_value_editor.set_tab_length(tab);
+    }
+
+    /// Rebuilds the shared HTTP client from the current config and drops
+    /// every settings-specific client derived from the old one.
+    fn rebuild_client(&mut self) -> Result<(), String> {
+        self.client = Self::build_client(&self.config, &RequestSettings::default())
+            .map_err(|e| e.to_string())?;
+        self.client_cache.clear();
+        self.connector_cache.clear();
+        Ok(())
+    }
+
+    /// Re-reads the config files and applies whatever changed. On failure
+    /// the running config is left untouched and the error is shown.
+    fn reload_config(&mut self) {
+        let (config, provenance) = match config::load_config() {
+            Ok(loaded) => loaded,
+            Err(err) => {
+                self.error_popup = Some(ErrorPopup {
+                    title: "Config reload failed".to_string(),
+                    message: err,
+                });
+                return;
+            }
+        };
+
+        let previous = std::mem::replace(&mut self.config, config);
+        if previous.client_settings_differ(&self.config) {
+            if let Err(err) = self.rebuild_client() {
+                self.config = previous;
+                self.error_popup = Some(ErrorPopup {
+                    title: "Config reload failed".to_string(),
+                    message: err,
+                });
+                return;
+            }
+        }
+        if previous.ui.sidebar_width != self.config.ui.sidebar_width {
+            self.sidebar_width = clamp_sidebar_width(self.config.ui.sidebar_width);
+            self.persist_ui_state();
+        }
+        if previous.ui.high_contrast != self.config.ui.high_contrast
+            || previous.ui.reduced_motion != self.config.ui.reduced_motion
+        {
+            self.theme = ui::Theme::from_config(&self.config.ui);
+            self.mark_dirty(DirtyPanels::ALL);
+        }
+        if previous.json != self.config.json {
+            self.json_style = self.config.json.style();
+            self.reformat_response();
+        }
+        self.apply_editor_tab_size();
+        self.clipboard.set_osc52(self.config.clipboard.osc52);
+        self.config_provenance = provenance;
+        self.config_viewer_notice = Some("Config reloaded".to_string());
+    }
+
+    fn build_client(config: &Config, settings: &RequestSettings) -> Result<Client> {
+        use reqwest::redirect::Policy;
+
+        let mut builder = Client::builder();
+
+        // Timeout (0 = no timeout, so we simply don't set one)
+        if config.http.timeout > 0 {
+            builder = builder.timeout(Duration::from_secs(config.http.timeout));
+        }
+
+        // Redirects are followed by `http::Sender` so hops can be recorded
+        builder = builder.redirect(Policy::none());
+
+        // Proxy
+        match &settings.proxy {
+            ProxySetting::Inherit => {
+                if let Some(ref proxy_url) = config.proxy.url {
+                    let mut proxy = reqwest::Proxy::all(proxy_url)
+                        .map_err(|e| anyhow::anyhow!("invalid proxy configuration: {}", e))?;
+                    if let Some(ref no_proxy) = config.proxy.no_proxy {
+                        let np = reqwest::NoProxy::from_string(no_proxy);
+                        proxy = proxy.no_proxy(np);
+                    }
+                    builder = builder.proxy(proxy);
+                }
+            }
+            ProxySetting::None => {
+                builder = builder.no_proxy();
+            }
+            ProxySetting::Custom { url } => {
+                let proxy = reqwest::Proxy::all(url)
+                    .map_err(|e| anyhow::anyhow!("invalid request proxy \"{}\": {}", url, e))?;
+                builder = builder.proxy(proxy);
+            }
+        }
+
+        match settings.http_version {
+            HttpVersionSetting::Auto => {}
+            HttpVersionSetting::Http1 => builder = builder.http1_only(),
+            HttpVersionSetting::Http2 => builder = builder.http2_prior_knowledge(),
+        }
+
+        // Host overrides. DNS has no notion of ports, so the port half of a
+        // target is applied to the URL at send time instead.
+        for (host, target) in config.host_targets() {
+            builder = builder.resolve(host, std::net::SocketAddr::new(target.ip, 0));
+        }
+
+        builder = builder.use_preconfigured_tls(Self::tls_connector(config, settings)?);
+
+        builder
+            .build()
+            .map_err(|e| anyhow::anyhow!("failed to build HTTP client: {}", e))
+    }
+
+    /// The TLS side of [`Self::build_client`], shared with header-case
+    /// preserving sends.
+    fn tls_connector(
+        config: &Config,
+        settings: &RequestSettings,
+    ) -> Result<native_tls::TlsConnector> {
+        let mut builder = native_tls::TlsConnector::builder();
+
+        // SSL verification, unless the request says otherwise
+        let tls = &settings.tls;
+        if !tls.verify.unwrap_or(config.ssl.verify) {
+            builder.danger_accept_invalid_certs(true);
+        }
+
+        // Custom CA certificate; the request's replaces the configured one
+        let ca_cert = match &tls.ca_cert {
+            Some(path) => Some(project_path(path)),
+            None => config.ssl.ca_cert.clone(),
+        };
+        if let Some(ref ca_path) = ca_cert {
+            let pem = std::fs::read(ca_path)
+                .map_err(|e| anyhow::anyhow!("failed to read CA cert \"{}\": {}", ca_path.display(), e))?;
+            let cert = native_tls::Certificate::from_pem(&pem)
+                .map_err(|e| anyhow::anyhow!("invalid CA cert \"{}\": {}", ca_path.display(), e))?;
+            builder.add_root_certificate(cert);
+        }
+
+        // Client certificate + key (mutual TLS), the request's pair or the
+        // configured one
+        let identity = match (&tls.client_cert, &tls.client_key) {
+            (Some(cert), Some(key)) => Some((project_path(cert), project_path(key))),
+            (None, None) => config.ssl.client_cert.clone().zip(config.ssl.client_key.clone()),
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "request TLS settings need both a client cert and a client key"
+                ))
+            }
+        };
+        if let Some((ref cert_path, ref key_path)) = identity {
+            let cert_pem = std::fs::read(cert_path).map_err(|e| {
+                anyhow::anyhow!("failed to read client cert \"{}\": {}", cert_path.display(), e)
+            })?;
+            let key_pem = std::fs::read(key_path).map_err(|e| {
+                anyhow::anyhow!("failed to read client key \"{}\": {}", key_path.display(), e)
+            })?;
+            let identity = native_tls::Identity::from_pkcs8(&cert_pem, &key_pem)
+                .map_err(|e| anyhow::anyhow!("invalid client identity: {}", e))?;
+            builder.identity(identity);
+        }
+
+        builder
+            .build()
+            .map_err(|e| anyhow::anyhow!("failed to build TLS connector: {}", e))
+    }
+
+    /// Where header-case preserving sends connect, or `None` when sends go
+    /// through reqwest: the option is off or HTTP/2 is forced.
+    fn build_header_case_connector(
+        config: &Config,
+        settings: &RequestSettings,
+    ) -> Result<Option<http1::Connector>> {
+        if !config.http.preserve_header_case || settings.http_version == HttpVersionSetting::Http2
+        {
+            return Ok(None);
+        }
+        let tls = Self::tls_connector(config, settings)?;
+        let resolve = config
+            .host_targets()
+            .map(|(host, target)| (host.to_string(), target.ip))
+            .collect();
+        let proxies = Self::proxy_matcher(config, settings);
+        Ok(Some(http1::Connector::new(tls, resolve, proxies)))
+    }
+
+    /// The proxies [`Self::build_client`] sets up, matched the way reqwest
+    /// matches them, so requests it would proxy aren't sent direct: the
+    /// configured proxy less `no_proxy`, or else the environment and OS
+    /// settings.
+    fn proxy_matcher(config: &Config, settings: &RequestSettings) -> Matcher {
+        match &settings.proxy {
+            ProxySetting::Inherit => match &config.proxy.url {
+                Some(url) => Matcher::builder()
+                    .all(url.clone())
+                    .no(config.proxy.no_proxy.clone().unwrap_or_default())
+                    .build(),
+                None => Matcher::from_system(),
+            },
+            ProxySetting::None => Matcher::builder().build(),
+            ProxySetting::Custom { url } => Matcher::builder().all(url.clone()).build(),
+        }
+    }
+
+    pub async fn run(&mut self) -> Result<()> {
+        self.install_panic_hook();
+        self.setup_terminal()?;
+
+        let result = self.event_loop().await;
+
+        self.shutdown();
+        // Let another instance have the project
+        self.project_lock = None;
+        self.restore_terminal()?;
+        if let SaveStatus::Failed { ref error, .. } = self.save_status {
+            eprintln!("warning: collection changes were not saved: {}", error);
+        }
+        result
+    }
+
+    /// Stops the sends in flight and makes the writes quitting would
+    /// otherwise lose, in [`storage::shutdown_writes`] order, before the
+    /// terminal is restored.
+    fn shutdown(&mut self) {
+        if let Some(handle) = self.request_handle.take() {
+            handle.abort();
+        }
+        if let Some(sends) = self.run.as_mut().and_then(|run| run.parallel.as_mut()) {
+            for sent in sends.in_flight.drain(..) {
+                sent.handle.abort();
+            }
+        }
+        self.persist_session_state();
+        if self.read_only {
+            self.flush_state_files(None);
+            return;
+        }
+        let writes = storage::shutdown_writes(
+            // Last chance for changes a failed save left only in memory
+            self.save_status != SaveStatus::Saved,
+            &self.session_write,
+            &self.ui_state_write,
+            &self.changelog_write,
+        );
+        let (done, waiting) = std::sync::mpsc::channel();
+        let indicator = std::thread::spawn(move || saving_indicator(waiting));
+        let now = Instant::now();
+        for write in writes {
+            match write {
+                storage::PendingWrite::Collection => self.save_collection(),
+                storage::PendingWrite::Session => {
+                    self.session_write.wrote(now);
+                    self.write_session_state();
+                }
+                storage::PendingWrite::UiState => {
+                    self.ui_state_write.wrote(now);
+                    self.write_ui_state();
+                }
+                storage::PendingWrite::Changelog => {
+                    self.changelog_write.wrote(now);
+                    self.write_changelog();
+                }
+            }
+        }
+        drop(done);
+        let _ = indicator.join();
+        self.stash_for_panic();
+    }
+
+    /// Writes the collection to disk. A failure doesn't undo the in-memory
+    /// change; it is retried from the event loop with backoff until a write
+    /// succeeds, and the status bar shows it as unsaved meanwhile.
+    fn save_collection(&mut self) {
+        if self.read_only {
+            return;
+        }
+        let previous_attempts = match self.save_status {
+            SaveStatus::Saved => 0,
+            SaveStatus::Saving { attempts } | SaveStatus::Failed { attempts, .. } => attempts,
+        };
+        match self.collection.save() {
+            Ok(()) => {
+                // Our own write isn't a change from outside
+                self.collection_modified = collection_modified();
+                if previous_attempts > 0 {
+                    self.notify(Level::Info, "Collection saved");
+                }
+                self.save_status = SaveStatus::Saved;
+            }
+            Err(error) => {
+                let attempts = previous_attempts + 1;
+                if attempts == 1 {
+                    self.notify(Level::Error, format!("{} (will retry)", error));
+                }
+                self.save_status = SaveStatus::Failed {
+                    error,
+                    attempts,
+                    retry_at: Instant::now() + save_retry_delay(attempts),
+                };
+            }
+        }
+        self.mark_dirty(DirtyPanels::STATUS);
+    }
+
+    fn notify(&mut self, level: Level, msg: impl Into<String>) {
+        self.notifications.push(level, msg);
+        self.mark_dirty(DirtyPanels::STATUS);
+    }
+
+    /// Schedules a redraw that rebuilds `panels`; other panels reuse the
+    /// lines from the previous frame.
+    fn mark_dirty(&mut self, panels: DirtyPanels) {
+        self.dirty_panels = self.dirty_panels | panels;
+        self.dirty = true;
+    }
+
+    fn mark_sidebar_dirty(&mut self) {
+        self.sidebar_cache.invalidate_all();
+        self.mark_dirty(DirtyPanels::SIDEBAR);
+    }
+
+    fn mark_sidebar_search_dirty(&mut self) {
+        self.sidebar_cache.invalidate_search();
+        self.mark_dirty(DirtyPanels::SIDEBAR);
+    }
+
+    /// What a key press in a request editor must leave unchanged for the
+    /// sidebar and response panel to be reused as they are.
+    fn request_typing_state(
+        &self,
+    ) -> Option<(std::mem::Discriminant<ResponseStatus>, ResponseTab, bool)> {
+        (self.app_mode == AppMode::Editing && self.focus.panel == Panel::Request).then(|| {
+            (std::mem::discriminant(&self.response), self.response_tab, self.request_dirty)
+        })
+    }
+
+    /// Schedules a write of the UI state; see [`App::flush_state_files`].
+    fn persist_ui_state(&mut self) {
+        self.ui_state_write.request();
+    }
+
+    /// Schedules a write of the session; see [`App::flush_state_files`].
+    fn persist_session_state(&mut self) {
+        self.session_write.request();
+    }
+
+    /// Writes the UI state and session files whose debounce interval is up,
+    /// or every pending one when `now` is `None`, as on quit.
+    fn flush_state_files(&mut self, now: Option<Instant>) {
+        // The owner's session and UI state win; this instance's are dropped
+        if self.read_only {
+            self.ui_state_write = storage::WriteDebounce::default();
+            self.session_write = storage::WriteDebounce::default();
+            self.changelog_write = storage::WriteDebounce::default();
+            self.changelog_pending.clear();
+            return;
+        }
+        let due = |write: &storage::WriteDebounce| match now {
+            Some(now) => write.is_due(now),
+            None => write.is_pending(),
+        };
+        let written_at = now.unwrap_or_else(Instant::now);
+        if due(&self.ui_state_write) {
+            self.ui_state_write.wrote(written_at);
+            self.write_ui_state();
+        }
+        if due(&self.session_write) {
+            self.session_write.wrote(written_at);
+            self.write_session_state();
+        }
+        if due(&self.changelog_write) {
+            self.changelog_write.wrote(written_at);
+            self.write_changelog();
+        }
+        self.stash_for_panic();
+    }
+
+    /// Keeps the state files still held back where the panic hook can
+    /// write them; each is taken once per held-back write.
+    fn stash_for_panic(&mut self) {
+        let panic_flush = Arc::clone(&self.panic_flush);
+        let Ok(mut flush) = panic_flush.lock() else {
+            return;
+        };
+        if !self.session_write.is_pending() {
+            flush.session = None;
+        } else if flush.session.is_none() {
+            flush.session =
+                storage::project_root_key().map(|key| (key, self.current_session_state()));
+        }
+        if !self.ui_state_write.is_pending() {
+            flush.ui_state = None;
+        } else if flush.ui_state.is_none() {
+            flush.ui_state = Some(self.ui_state());
+        }
+    }
+
+    /// Notes a structural change to the collection for the changelog; the
+    /// entry is written with the other state files.
+    fn record_change(&mut self, kind: ChangeKind, path: String, detail: Option<String>) {
+        self.changelog_pending.push(ChangeEntry {
+            timestamp: sendlog::timestamp(std::time::SystemTime::now()),
+            kind,
+            path,
+            detail,
+        });
+        self.changelog_write.request();
+    }
+
+    /// The path of `id` in the collection, `Project/Folder/Request`.
+    fn item_path(&self, id: Uuid) -> String {
+        storage::item_path(&self.collection.layout(), id)
+    }
+
+    fn write_changelog(&mut self) {
+        if self.changelog_pending.is_empty() {
+            return;
+        }
+        let entries = std::mem::take(&mut self.changelog_pending);
+        let Some(path) = storage::changelog_path() else {
+            return;
+        };
+        if let Err(err) = storage::append_changes(&path, &entries) {
+            self.notify(Level::Warn, format!("Failed to write the changelog: {}", err));
+        }
+    }
+
+    fn open_changelog(&mut self) {
+        self.write_changelog();
+        let entries = storage::changelog_path()
+            .map(|path| storage::read_changes(&path, CHANGELOG_SHOWN))
+            .unwrap_or_default();
+        if entries.is_empty() {
+            self.notify(Level::Info, "No structural changes recorded yet");
+            return;
+        }
+        self.changelog_popup = Some(ChangelogPopup { entries, scroll: 0 });
+    }
+
+    fn handle_changelog_popup(&mut self, key: KeyEvent) {
+        let Some(popup) = self.changelog_popup.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => popup.scroll = popup.scroll.saturating_add(1),
+            KeyCode::Up | KeyCode::Char('k') => popup.scroll = popup.scroll.saturating_sub(1),
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('C') => self.changelog_popup = None,
+            _ => {}
+        }
+    }
+
+    fn ui_state(&self) -> storage::UiState {
+        let mut state =
+            storage::UiState::new(self.active_project_id.to_string(), self.sidebar_width);
+        state.onboarding_pending = self.onboarding.is_some();
+        state.recent_methods = self.recent_methods.clone();
+        state
+    }
+
+    fn write_ui_state(&mut self) {
+        let state = self.ui_state();
+        if let Err(err) = storage::save_ui_state(&state) {
+            self.notify(Level::Warn, format!("Failed to save UI state: {}", err));
+        }
+    }
+
+    fn write_session_state(&mut self) {
+        let Some(root_key) = storage::project_root_key() else {
+            return;
+        };
+        let session = self.current_session_state();
+        if let Err(err) = storage::save_session_for_root(&root_key, session) {
+            self.notify(Level::Warn, format!("Failed to save session: {}", err));
+        }
+    }
+
+    fn current_session_state(&mut self) -> storage::SessionState {
+        self.remember_request_position();
+        let collection = &self.collection;
+        self.request_positions
+            .retain(|id, _| collection.get_item(*id).is_some());
+        let positions = self
+            .request_positions
+            .iter()
+            .map(|(id, pos)| (id.to_string(), pos.clone()))
+            .collect();
+        self.last_runs.retain(|id, _| collection.get_item(*id).is_some());
+        let last_runs = self.last_runs.iter().map(|(id, run)| (id.to_string(), *run)).collect();
+        self.send_history.retain(|id, _| collection.get_item(*id).is_some());
+        let send_history = self
+            .send_history
+            .iter()
+            .map(|(id, sends)| (id.to_string(), sends.clone()))
+            .collect();
+        self.request_notes.retain(|id, _| collection.get_item(*id).is_some());
+        let notes =
+            self.request_notes.iter().map(|(id, note)| (id.to_string(), note.clone())).collect();
+        let projects = self
+            .project_sidebars
+            .iter()
+            .filter(|(id, _)| collection.get_item(**id).is_some())
+            .chain([(&self.active_project_id, &ProjectSidebar::of(&self.sidebar))])
+            .map(|(id, sidebar)| (id.to_string(), sidebar.to_session()))
+            .collect();
+        storage::SessionState {
+            active_project_id: self.active_project_id.to_string(),
+            sidebar_width: self.sidebar_width,
+            sidebar_visible: self.sidebar_visible,
+            current_request_id: self.current_request_id.map(|id| id.to_string()),
+            projects,
+            legacy_selection_id: None,
+            legacy_expanded: Vec::new(),
+            request_tab: request_tab_to_str(self.request_tab).to_string(),
+            response_tab: self.response_tab.label().to_string(),
+            headers_table: self.headers_table,
+            response_wrap: self.response_wrap,
+            body_wrap: self.body_wrap,
+            positions,
+            last_runs,
+            send_history,
+            notes,
+        }
+    }
+
+    fn open_trust_prompt(&mut self) {
+        let root = storage::find_project_root()
+            .map(|root| root.display().to_string())
+            .unwrap_or_default();
+        self.trust_prompt = Some(root);
+    }
+
+    fn set_project_trust(&mut self, trusted: bool) {
+        self.project_trusted = trusted;
+        let Some(root_key) = storage::project_root_key() else {
+            return;
+        };
+        if let Err(err) = storage::save_trust_for_root(&root_key, trusted) {
+            self.notify(Level::Warn, format!("Failed to save project trust: {}", err));
+        }
+    }
+
+    fn handle_trust_prompt(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('y') => {
+                self.trust_prompt = None;
+                self.set_project_trust(true);
+                self.notify(Level::Info, "Project trusted");
+            }
+            KeyCode::Char('n') => {
+                self.trust_prompt = None;
+                self.set_project_trust(false);
+                self.notify(Level::Info, "Project not trusted; sends will be confirmed");
+            }
+            KeyCode::Esc | KeyCode::Char('q') => self.trust_prompt = None,
+            _ => {}
+        }
+    }
+
+    fn handle_send_confirm(&mut self, key: KeyEvent, tx: mpsc::Sender<http::RequestEvent>) {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Enter => {
+                self.send_confirm = None;
+                self.untrusted_send_confirmed = true;
+                self.send_confirm_granted = true;
+                self.send_request(tx);
+            }
+            KeyCode::Char('t') => {
+                self.send_confirm = None;
+                self.set_project_trust(true);
+                self.notify(Level::Info, "Project trusted");
+                self.send_request(tx);
+            }
+            KeyCode::Char('n') | KeyCode::Esc | KeyCode::Char('q') => {
+                self.send_confirm = None;
+                self.unresolved_send_granted = false;
+                self.body_send_granted = false;
+                self.scratch_message = None;
+                self.notify(Level::Info, "Send cancelled");
+            }
+            _ => {}
+        }
+    }
+
+    /// Sends once the typed text is the method; anything else keeps the
+    /// popup open.
+    fn handle_protected_confirm(&mut self, key: KeyEvent, tx: mpsc::Sender<http::RequestEvent>) {
+        let Some(mut confirm) = self.protected_confirm.take() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.unresolved_send_granted = false;
+                self.body_send_granted = false;
+                self.scratch_message = None;
+                self.notify(
+                    Level::Info,
+                    format!("Send cancelled: {} {}", confirm.method, confirm.url),
+                );
+            }
+            KeyCode::Enter if confirm.input.value.trim().eq_ignore_ascii_case(&confirm.method) => {
+                self.notify(
+                    Level::Warn,
+                    format!("Confirmed {} {} ({})", confirm.method, confirm.url, confirm.reason),
+                );
+                self.protected_send_granted = true;
+                self.send_request(tx);
+                self.protected_send_granted = false;
+            }
+            KeyCode::Enter => self.protected_confirm = Some(confirm),
+            _ => {
+                handle_text_input(&mut confirm.input, key);
+                self.protected_confirm = Some(confirm);
+            }
+        }
+    }
+
+    /// The confirmation a `method` send to `url` needs, if its host or the
+    /// active environment is protected and the method changes things.
+    fn protected_send(&self, method: &str, url: &str) -> Option<ProtectedConfirm> {
+        if method.eq_ignore_ascii_case("GET") || method.eq_ignore_ascii_case("HEAD") {
+            return None;
+        }
+        let host = reqwest::Url::parse(url).ok()?.host_str()?.to_string();
+        let environment = self.active_environment_name.clone();
+        let reason = self.config.protection(&host, environment.as_deref())?;
+        Some(ProtectedConfirm {
+            method: method.to_string(),
+            url: url.to_string(),
+            environment,
+            reason,
+            input: TextInput::new(String::new()),
+        })
+    }
+
+    fn handle_unresolved_confirm(&mut self, key: KeyEvent, tx: mpsc::Sender<http::RequestEvent>) {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Enter => {
+                self.unresolved_confirm = None;
+                self.unresolved_send_granted = true;
+                self.send_request(tx);
+            }
+            KeyCode::Char('e') => {
+                self.unresolved_confirm = None;
+                self.scratch_message = None;
+                if self.app_mode == AppMode::Editing {
+                    self.exit_editing();
+                }
+                self.app_mode = AppMode::Navigation;
+                self.show_env_popup = true;
+                self.env_popup_index = self
+                    .active_environment_name
+                    .as_ref()
+                    .and_then(|name| self.environments.iter().position(|e| e.name == *name))
+                    .map(|i| i + 1)
+                    .unwrap_or(0);
+            }
+            KeyCode::Char('n') | KeyCode::Esc | KeyCode::Char('q') => {
+                self.unresolved_confirm = None;
+                self.scratch_message = None;
+                self.notify(Level::Info, "Send cancelled");
+            }
+            _ => {}
+        }
+    }
+
+    /// `y` sends as is; `p` sends a request that shouldn't carry a body as
+    /// POST instead, changing the request's method.
+    fn handle_body_confirm(&mut self, key: KeyEvent, tx: mpsc::Sender<http::RequestEvent>) {
+        let Some(confirm) = self.body_confirm.take() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Enter => {
+                self.body_send_granted = true;
+                self.send_request(tx);
+            }
+            KeyCode::Char('p') if confirm.mismatch == http::BodyMismatch::UnexpectedBody => {
+                match self.scratch_message.as_mut() {
+                    Some(scratch) => scratch.message.method = "POST".to_string(),
+                    None => {
+                        self.request.method = Method::Standard(HttpMethod::Post);
+                        self.request_dirty = true;
+                        self.mark_dirty(DirtyPanels::REQUEST);
+                    }
+                }
+                self.notify(Level::Info, format!("Switched {} to POST", confirm.method));
+                self.send_request(tx);
+            }
+            KeyCode::Char('n') | KeyCode::Esc | KeyCode::Char('q') => {
+                self.unresolved_send_granted = false;
+                self.scratch_message = None;
+                self.notify(Level::Info, "Send cancelled");
+            }
+            _ => self.body_confirm = Some(confirm),
+        }
+    }
+
+    /// Whether a send from an untrusted project has to be shown first.
+    fn needs_send_confirm(&mut self, body: &http::BodyContent) -> bool {
+        if self.project_trusted || std::mem::take(&mut self.send_confirm_granted) {
+            return false;
+        }
+        !self.untrusted_send_confirmed || !body.files().is_empty()
+    }
+
+    fn rebuild_sidebar_tree(&mut self) {
+        if let Ok(tree) = self.collection.build_tree(self.active_project_id) {
+            self.sidebar_tree = tree;
+        }
+        self.sidebar
+            .expanded
+            .retain(|id| self.sidebar_tree.nodes.contains_key(id));
+        if !self.sidebar.expanded.contains(&self.active_project_id) {
+            self.sidebar.expanded.insert(self.active_project_id);
+        }
+        self.refresh_chained_requests();
+        if let Some(selected) = self.sidebar.selection_id {
+            if !self.sidebar_tree.nodes.contains_key(&selected) {
+                self.sidebar.selection_id = Some(self.active_project_id);
+            }
+        } else {
+            self.sidebar.selection_id = Some(self.active_project_id);
+        }
+        self.mark_sidebar_dirty();
+    }
+
+    fn expand_sidebar_ancestors(&mut self, id: Uuid) {
+        let mut current = Some(id);
+        while let Some(node_id) = current {
+            if let Some(node) = self.sidebar_tree.node(node_id) {
+                if matches!(node.kind, NodeKind::Folder | NodeKind::Project) {
+                    self.sidebar.expanded.insert(node_id);
+                }
+                current = node.parent_id;
+            } else {
+                break;
+            }
+        }
+        self.mark_sidebar_dirty();
+    }
+
+    /// Selects `id` in the full tree: drops any search, expands its
+    /// ancestors, centers it and briefly highlights the row.
+    fn reveal_in_sidebar(&mut self, id: Uuid) {
+        if !self.sidebar.search_query.is_empty() {
+            self.sidebar.search_query.clear();
+            self.mark_sidebar_search_dirty();
+        }
+        self.sidebar.selection_id = Some(id);
+        self.expand_sidebar_ancestors(id);
+        self.sidebar.center_selection = true;
+        self.sidebar.flash = Some((id, Instant::now() + Self::SIDEBAR_FLASH));
+    }
+
+    /// The first row to draw in a tree viewport `height` rows tall, moved
+    /// just enough to keep the selection visible (or to center it when a
+    /// reveal asked for that).
+    pub fn sidebar_scroll_for(&mut self, height: usize) -> usize {
+        let selected = self.sidebar.selection_id;
+        let lines = self.sidebar_lines();
+        let count = lines.len();
+        let index = lines.iter().position(|line| Some(line.id) == selected);
+        let center = std::mem::take(&mut self.sidebar.center_selection);
+        let mut scroll = self.sidebar.scroll;
+        if let Some(index) = index {
+            if center {
+                scroll = index.saturating_sub(height / 2);
+            } else if index < scroll {
+                scroll = index;
+            } else if height > 0 && index >= scroll + height {
+                scroll = index + 1 - height;
+            }
+        }
+        scroll = scroll.min(count.saturating_sub(height));
+        self.sidebar.scroll = scroll;
+        scroll
+    }
+
+    fn focus_sidebar(&mut self) {
+        if !self.sidebar_visible {
+            self.sidebar_visible = true;
+        }
+        if let Some(request_id) = self.current_request_id {
+            if self.sidebar_tree.nodes.contains_key(&request_id) {
+                self.sidebar.selection_id = Some(request_id);
+                self.expand_sidebar_ancestors(request_id);
+            } else {
+                self.sidebar.selection_id = Some(self.active_project_id);
+            }
+        } else {
+            self.sidebar.selection_id = Some(self.active_project_id);
+        }
+        self.focus.panel = Panel::Sidebar;
+        self.app_mode = AppMode::Sidebar;
+    }
+
+    pub fn sidebar_lines(&mut self) -> &[SidebarLine] {
+        let _guard = perf::scope("sidebar_lines");
+        if self.sidebar.search_query.is_empty() && self.sidebar.filter == SidebarFilter::All {
+            if self.sidebar_cache.lines_dirty {
+                let mut lines = Vec::new();
+                self.collect_sidebar_lines(
+                    self.sidebar_tree.root_id,
+                    &[],
+                    true,
+                    true,
+                    &mut lines,
+                );
+                self.sidebar_cache.lines = lines;
+                self.sidebar_cache.lines_dirty = false;
+            }
+            return &self.sidebar_cache.lines;
+        }
+
+        let query = self.sidebar.search_query.as_str();
+        let filter = &self.sidebar.filter;
+        let unsaved = self.current_request_id.filter(|_| self.request_dirty);
+        let cache = &self.sidebar_cache;
+        let unsaved_changed = *filter == SidebarFilter::Unsaved && cache.search_unsaved != unsaved;
+        if cache.search_dirty
+            || cache.search_query != query
+            || cache.search_filter != *filter
+            || unsaved_changed
+        {
+            self.sidebar_cache.search_lines = self.sidebar_search_lines_for(query, filter);
+            self.sidebar_cache.search_query = query.to_string();
+            self.sidebar_cache.search_filter = filter.clone();
+            self.sidebar_cache.search_unsaved = unsaved;
+            self.sidebar_cache.search_dirty = false;
+        }
+        &self.sidebar_cache.search_lines
+    }
+
+    fn sidebar_search_lines_for(&self, query: &str, filter: &SidebarFilter) -> Vec<SidebarLine> {
+        let _guard = perf::scope("sidebar_search_lines");
+        let mut lines = Vec::new();
+        let query = query.to_lowercase();
+        let unsaved = match filter {
+            SidebarFilter::Unsaved => self.unsaved_request_ids(),
+            _ => HashSet::new(),
+        };
+        for (id, node) in &self.sidebar_tree.nodes {
+            if node.kind == NodeKind::Project {
+                continue;
+            }
+            let passes = match filter {
+                SidebarFilter::All => true,
+                SidebarFilter::Tag(name) => node.tags.iter().any(|tag| tag.name == *name),
+                _ if node.kind != NodeKind::Request => false,
+                SidebarFilter::Method(method) => node
+                    .request_method
+                    .as_deref()
+                    .is_some_and(|m| m.eq_ignore_ascii_case(method.as_str())),
+                SidebarFilter::Unsaved => unsaved.contains(id),
+                SidebarFilter::Failing => self.failing_requests.contains(id),
+            };
+            if passes && node.name_lower.contains(&query) {
+                let path = self.sidebar_tree.path_for(*id).join("/");
+                let method = if node.kind == NodeKind::Request {
+                    node.request_method
+                        .as_deref()
+                        .map(Method::from_str)
+                } else {
+                    None
+                };
+                lines.push(SidebarLine {
+                    id: *id,
+                    prefix: String::new(),
+                    marker: String::new(),
+                    label: path,
+                    kind: node.kind,
+                    method,
+                    locked: node.locked,
+                    last_run: self.last_runs.get(id).copied(),
+                    tags: node.tags.clone(),
+                });
+            }
+        }
+        lines.sort_by_cached_key(|line| line.label.to_lowercase());
+        lines
+    }
+
+    /// Keeps what a send of `request_id` brought back: the response for
+    /// references, its schema result and the request's outcome. `elapsed_ms`
+    /// stands in for the duration when nothing came back.
+    fn note_response(
+        &mut self,
+        request_id: Uuid,
+        result: &Result<ResponseData, http::RequestError>,
+        elapsed_ms: u64,
+    ) {
+        if let Ok(data) = result {
+            if !data.partial {
+                self.response_store.insert(request_id, data);
+            }
+            self.check_schema(request_id, data);
+        }
+        let failed = match result {
+            Ok(data) => runner::check_response(data.status)
+                .iter()
+                .chain(self.schema_assertion(request_id).as_ref())
+                .any(|check| !check.passed),
+            Err(_) => true,
+        };
+        let status = result.as_ref().ok().map(|data| data.status);
+        let duration_ms = result.as_ref().map_or(elapsed_ms, |data| data.duration_ms);
+        self.record_outcome(request_id, status, duration_ms, failed);
+    }
+
+    fn record_outcome(
+        &mut self,
+        request_id: Uuid,
+        status: Option<u16>,
+        duration_ms: u64,
+        failed: bool,
+    ) {
+        let last_run = LastRun { status };
+        if self.last_runs.insert(request_id, last_run) != Some(last_run) {
+            self.sidebar_cache.set_last_run(request_id, last_run);
+            self.mark_dirty(DirtyPanels::SIDEBAR);
+        }
+        let at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        let sends = self.send_history.entry(request_id).or_default();
+        sends.push(SendStat {
+            at,
+            status,
+            duration_ms,
+        });
+        let excess = sends.len().saturating_sub(SEND_HISTORY_MAX);
+        sends.drain(..excess);
+        self.persist_session_state();
+        let changed = if failed {
+            self.failing_requests.insert(request_id)
+        } else {
+            self.failing_requests.remove(&request_id)
+        };
+        if changed && self.sidebar.filter == SidebarFilter::Failing {
+            self.mark_sidebar_search_dirty();
+        }
+    }
+
+    /// The user's notes on the open request, when there are any.
+    pub fn current_note(&self) -> Option<&str> {
+        self.request_notes.get(&self.current_request_id?).map(String::as_str)
+    }
+
+    fn open_notes(&mut self) {
+        let Some(request_id) = self.current_request_id else {
+            self.notify(Level::Info, "Open a request to take notes on it");
+            return;
+        };
+        let text = self.request_notes.get(&request_id).cloned().unwrap_or_default();
+        let mut editor = TextArea::new(text.split('\n').map(str::to_string).collect());
+        configure_editor(&mut editor, "What you found out about this request");
+        editor.move_cursor(CursorMove::Bottom);
+        editor.move_cursor(CursorMove::End);
+        self.notes_popup = Some(NotesPopup { request_id, editor });
+    }
+
+    /// Esc keeps what was typed; notes left blank are removed.
+    fn handle_notes_popup(&mut self, key: KeyEvent) {
+        let Some(popup) = self.notes_popup.as_mut() else {
+            return;
+        };
+        if key.code != KeyCode::Esc {
+            popup.editor.input(key);
+            return;
+        }
+        let note = popup.editor.lines().join("\n").trim_end().to_string();
+        let request_id = popup.request_id;
+        self.notes_popup = None;
+        let changed = if note.trim().is_empty() {
+            self.request_notes.remove(&request_id).is_some()
+        } else {
+            self.request_notes.insert(request_id, note.clone()) != Some(note)
+        };
+        if changed {
+            self.persist_session_state();
+        }
+        self.mark_dirty(DirtyPanels::ALL);
+    }
+
+    /// Recent sends of the open request, oldest first.
+    pub fn send_history(&self) -> &[SendStat] {
+        self.current_request_id
+            .and_then(|id| self.send_history.get(&id))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// The open request if it has unsaved edits, and requests whose
+    /// drafts were kept from an earlier session.
+    fn unsaved_request_ids(&self) -> HashSet<Uuid> {
+        let mut ids: HashSet<Uuid> = storage::load_drafts()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|draft| Uuid::parse_str(&draft.request_id).ok())
+            .collect();
+        if let Some(id) = self.current_request_id.filter(|_| self.request_dirty) {
+            ids.insert(id);
+        }
+        ids
+    }
+
+    fn collect_sidebar_lines(
+        &self,
+        id: Uuid,
+        ancestors_last: &[bool],
+        is_last: bool,
+        is_root: bool,
+        out: &mut Vec<SidebarLine>,
+    ) {
+        if let Some(node) = self.sidebar_tree.node(id) {
+            let is_expanded = self.sidebar.expanded.contains(&id);
+            let marker = match node.kind {
+                NodeKind::Project | NodeKind::Folder => {
+                    if is_expanded { "▾" } else { "▸" }
+                }
+                NodeKind::Request => "•",
+            };
+            let method = if node.kind == NodeKind::Request {
+                node.request_method
+                    .as_deref()
+                    .map(Method::from_str)
+            } else {
+                None
+            };
+            let prefix = if is_root {
+                String::new()
+            } else {
+                sidebar_tree_prefix(ancestors_last, is_last)
+            };
+            out.push(SidebarLine {
+                id,
+                prefix,
+                marker: marker.to_string(),
+                label: node.name.clone(),
+                kind: node.kind,
+                method,
+                locked: node.locked,
+                last_run: self.last_runs.get(&id).copied(),
+                tags: node.tags.clone(),
+            });
+            if matches!(node.kind, NodeKind::Project | NodeKind::Folder) && is_expanded {
+                let mut next_ancestors = ancestors_last.to_vec();
+                if !is_root {
+                    next_ancestors.push(is_last);
+                }
+                for (index, child) in node.children.iter().enumerate() {
+                    let child_is_last = index + 1 == node.children.len();
+                    self.collect_sidebar_lines(
+                        *child,
+                        &next_ancestors,
+                        child_is_last,
+                        false,
+                        out,
+                    );
+                }
+            }
+        }
+    }
+
+    fn sidebar_selected_index(selected: Option<Uuid>, lines: &[SidebarLine]) -> usize {
+        let Some(selected) = selected else {
+            return 0;
+        };
+        lines
+            .iter()
+            .position(|line| line.id == selected)
+            .unwrap_or(0)
+    }
+
+    fn sidebar_move_selection(&mut self, delta: i32) {
+        let selected = self.sidebar.selection_id;
+        let lines = self.sidebar_lines();
+        if lines.is_empty() {
+            return;
+        }
+        let mut index = Self::sidebar_selected_index(selected, lines) as i32;
+        index = (index + delta).clamp(0, (lines.len() - 1) as i32);
+        let next_id = lines[index as usize].id;
+        self.sidebar.selection_id = Some(next_id);
+    }
+
+    fn sidebar_selected_node(&self) -> Option<&TreeNode> {
+        self.sidebar
+            .selection_id
+            .and_then(|id| self.sidebar_tree.node(id))
+    }
+
+    pub fn sidebar_selected_id(&self) -> Option<Uuid> {
+        self.sidebar.selection_id
+    }
+
+    fn save_current_request_if_dirty(&mut self) {
+        if !self.request_dirty {
+            return;
+        }
+        let Some(request_id) = self.current_request_id else {
+            return;
+        };
+        if let Err(err) = self.save_request_by_id(request_id) {
+            self.notify(Level::Error, err);
+        } else {
+            self.request_dirty = false;
+        }
+    }
+
+    fn save_request_by_id(&mut self, request_id: Uuid) -> Result<(), String> {
+        let request = self.build_postman_request();
+        self.collection.update_request(request_id, request)?;
+        // An auto-named request follows its new method and path
+        let follows = self.auto_names_enabled()
+            && self.collection.get_item(request_id).is_some_and(|item| item.name_is_auto);
+        let renamed = follows && self.collection.apply_auto_name(request_id)?;
+        self.save_collection();
+        if renamed {
+            self.rebuild_sidebar_tree();
+        } else {
+            self.refresh_chained_requests();
+        }
+        self.mark_dirty(DirtyPanels::SIDEBAR);
+        if let Some(parent_id) = self
+            .sidebar_tree
+            .node(request_id)
+            .and_then(|node| node.parent_id)
+        {
+            self.collection
+                .save_request_file(request_id, parent_id, self.active_project_id)?;
+        }
+        self.draft_snapshot = None;
+        storage::delete_draft(&request_id.to_string())
+    }
+
+    fn write_request_files(&self, request_ids: &[Uuid]) -> Result<(), String> {
+        for request_id in request_ids {
+            let parent_id = self
+                .sidebar_tree
+                .node(*request_id)
+                .and_then(|node| node.parent_id)
+                .ok_or("Request parent not found")?;
+            self.collection
+                .save_request_file(*request_id, parent_id, self.active_project_id)?;
+        }
+        Ok(())
+    }
+
+    fn delete_request_files(&self, request_ids: &[Uuid]) -> Result<(), String> {
+        for request_id in request_ids {
+            self.collection.delete_request_file(*request_id)?;
+            storage::delete_draft(&request_id.to_string())?;
+        }
+        Ok(())
+    }
+
+    fn build_postman_request(&self) -> PostmanRequest {
+        let method = self.request.method.as_str().to_string();
+        let url = self.request.url_text();
+        let headers = storage::parse_headers(&self.request.headers_text());
+
+        let body = match self.request.body_mode {
+            BodyMode::Raw => {
+                let text = self.request.body_text();
+                if text.trim().is_empty() {
+                    None
+                } else {
+                    Some(storage::PostmanBody::raw(&text))
+                }
+            }
+            BodyMode::Json => {
+                let text = self.request.body_text();
+                if text.trim().is_empty() {
+                    None
+                } else {
+                    Some(storage::PostmanBody::json(&text))
+                }
+            }
+            BodyMode::Xml => {
+                let text = self.request.body_text();
+                if text.trim().is_empty() {
+                    None
+                } else {
+                    Some(storage::PostmanBody::xml(&text))
+                }
+            }
+            BodyMode::FormUrlEncoded => {
+                let pairs: Vec<storage::PostmanKvPair> = self
+                    .request
+                    .body_form_pairs
+                    .iter()
+                    .filter(|p| !(p.key.is_empty() && p.value.is_empty()))
+                    .map(|p| storage::PostmanKvPair {
+                        key: p.key.clone(),
+                        value: p.value.clone(),
+                        disabled: if p.enabled { None } else { Some(true) },
+                    })
+                    .collect();
+                if pairs.is_empty() {
+                    None
+                } else {
+                    Some(storage::PostmanBody::urlencoded(pairs))
+                }
+            }
+            BodyMode::Multipart => {
+                let params: Vec<storage::PostmanFormParam> = self
+                    .request
+                    .body_multipart_fields
+                    .iter()
+                    .filter(|f| !f.key.is_empty())
+                    .map(|f| storage::PostmanFormParam {
+                        key: f.key.clone(),
+                        value: if f.field_type == MultipartFieldType::Text {
+                            Some(f.value.clone())
+                        } else {
+                            None
+                        },
+                        src: if f.field_type == MultipartFieldType::File {
+                            Some(f.value.clone())
+                        } else {
+                            None
+                        },
+                        param_type: match f.field_type {
+                            MultipartFieldType::Text => "text".to_string(),
+                            MultipartFieldType::File => "file".to_string(),
+                        },
+                        disabled: if f.enabled { None } else { Some(true) },
+                    })
+                    .collect();
+                if params.is_empty() {
+                    None
+                } else {
+                    Some(storage::PostmanBody::formdata(params))
+                }
+            }
+            BodyMode::Binary => {
+                let path = self.request.body_binary_path_text();
+                if path.trim().is_empty() {
+                    None
+                } else {
+                    Some(storage::PostmanBody::file(&path))
+                }
+            }
+        };
+
+        let auth = match self.request.auth_type {
+            AuthType::NoAuth => None,
+            AuthType::Bearer => {
+                Some(storage::PostmanAuth::bearer(&self.request.auth_token_text()))
+            }
+            AuthType::Basic => Some(storage::PostmanAuth::basic(
+                &self.request.auth_username_text(),
+                &self.request.auth_password_text(),
+            )),
+            AuthType::ApiKey => Some(storage::PostmanAuth::apikey(
+                &self.request.auth_key_name_text(),
+                &self.request.auth_key_value_text(),
+                if self.request.api_key_location == ApiKeyLocation::Header {
+                    "header"
+                } else {
+                    "query"
+                },
+            )),
+        };
+
+        let mut req = PostmanRequest::new(method, url, headers, None);
+        req.body = body.map(|body| storage::PostmanBody {
+            disabled: self.request.body_disabled.then_some(true),
+            ..body
+        });
+        req.auth = auth.map(|auth| storage::PostmanAuth {
+            disabled: self.request.auth_disabled.then_some(true),
+            ..auth
+        });
+        req
+    }
+
+    fn open_request(&mut self, request_id: Uuid) {
+        self.save_current_request_if_dirty();
+        self.remember_request_position();
+        self.revealed_secrets.clear();
+        let request_data = self
+            .collection
+            .get_item(request_id)
+            .and_then(|item| item.request.clone());
+        if let Some(request) = request_data {
+            self.load_request_contents(&request);
+            self.request_settings = self
+                .collection
+                .get_item(request_id)
+                .map(|item| item.settings.clone())
+                .unwrap_or_default();
+            self.current_request_id = Some(request_id);
+            self.request_dirty = false;
+            self.kv_edit_textarea = None;
+            self.focus.panel = Panel::Request;
+            self.focus.request_field = RequestField::Url;
+            self.focus.body_field = BodyField::ModeSelector;
+            self.focus.kv_focus = KvFocus::default();
+            self.restore_request_position(request_id);
+            self.body_wrap_cache.scroll = 0;
+            self.hint_long_body_line();
+        }
+    }
+
+    /// Fills the editors from `request`.
+    fn load_request_contents(&mut self, request: &PostmanRequest) {
+        let method = Method::from_str(&request.method);
+        let url = extract_url(&request.url);
+        let headers = headers_to_text(&request.header);
+        let raw_body = request
+            .body
+            .as_ref()
+            .and_then(|b| b.raw.clone())
+            .unwrap_or_default();
+        self.request.set_contents(method, url, headers, raw_body);
+        self.load_body_mode_from_postman(request);
+        self.load_auth_from_postman(request);
+        self.apply_editor_tab_size();
+    }
+
+    /// Snapshots the open request's unsaved edits to its draft file.
+    fn write_draft(&mut self) {
+        let Some(request_id) = self.current_request_id.filter(|_| self.request_dirty) else {
+            return;
+        };
+        if self.read_only {
+            return;
+        }
+        let request = self.build_postman_request();
+        let contents = serde_json::to_string(&request).unwrap_or_default();
+        if self.draft_snapshot.as_ref() == Some(&(request_id, contents.clone())) {
+            return;
+        }
+        self.draft_snapshot = Some((request_id, contents));
+        let draft = storage::Draft::new(request_id.to_string(), request);
+        if let Err(err) = storage::save_draft(&draft) {
+            self.notify(Level::Warn, err);
+        }
+    }
+
+    /// Offers the drafts a previous session left behind. Drafts that are no
+    /// newer than their saved request, or change nothing in it, are dropped;
+    /// drafts of requests in other projects wait until that project is open.
+    fn recover_drafts(&mut self) {
+        let drafts = match storage::load_drafts() {
+            Ok(drafts) => drafts,
+            Err(err) => {
+                self.notify(Level::Warn, err);
+                return;
+            }
+        };
+        let mut recovered = Vec::new();
+        for draft in drafts {
+            let request_id = Uuid::parse_str(&draft.request_id).ok();
+            let saved = request_id
+                .and_then(|id| self.collection.get_item(id))
+                .and_then(|item| item.request.as_ref());
+            let changes = match saved {
+                Some(saved) if draft.is_newer_than_saved() => {
+                    storage::draft_changes(saved, &draft.request)
+                }
+                _ => Vec::new(),
+            };
+            match request_id {
+                Some(id) if !changes.is_empty() => {
+                    if self.sidebar_tree.nodes.contains_key(&id) {
+                        recovered.push(RecoveredDraft {
+                            request_id: id,
+                            path: self.sidebar_tree.path_for(id).join("/"),
+                            changes,
+                            draft,
+                        });
+                    }
+                }
+                _ => {
+                    let _ = storage::delete_draft(&draft.request_id);
+                }
+            }
+        }
+        if !recovered.is_empty() {
+            self.draft_prompt = Some(DraftPrompt { drafts: recovered });
+        }
+    }
+
+    fn handle_draft_prompt(&mut self, key: KeyEvent) {
+        let Some(prompt) = self.draft_prompt.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Char('r') | KeyCode::Enter => {
+                let recovered = prompt.drafts.remove(0);
+                self.restore_draft(recovered);
+            }
+            KeyCode::Char('d') => {
+                let recovered = prompt.drafts.remove(0);
+                if let Err(err) = storage::delete_draft(&recovered.draft.request_id) {
+                    self.notify(Level::Warn, err);
+                }
+                self.mark_sidebar_search_dirty();
+            }
+            // Kept on disk, so they are offered again next time
+            KeyCode::Esc | KeyCode::Char('q') => self.draft_prompt = None,
+            _ => {}
+        }
+        if self.draft_prompt.as_ref().is_some_and(|prompt| prompt.drafts.is_empty()) {
+            self.draft_prompt = None;
+        }
+    }
+
+    /// Opens the draft's request with the draft's contents, unsaved. The
+    /// draft file stays until the request is saved.
+    fn restore_draft(&mut self, recovered: RecoveredDraft) {
+        let request_id = recovered.request_id;
+        self.sidebar.selection_id = Some(request_id);
+        self.expand_sidebar_ancestors(request_id);
+        self.open_request(request_id);
+        if self.current_request_id != Some(request_id) {
+            return;
+        }
+        self.load_request_contents(&recovered.draft.request);
+        self.request_dirty = true;
+        self.mark_sidebar_dirty();
+        self.notify(Level::Info, format!("Restored the draft of {}", recovered.path));
+    }
+
+    /// Records the editor cursors, request tab and response scroll of the
+    /// open request so `open_request` can put them back.
+    fn remember_request_position(&mut self) {
+        let Some(request_id) = self.current_request_id else {
+            return;
+        };
+        let cursor = |editor: &TextArea<'static>| {
+            let (row, col) = editor.cursor();
+            CursorPosition { row, col }
+        };
+        let previous = self.request_positions.get(&request_id);
+        // The response panel shows the last response from any request; only
+        // its own response's scroll belongs to this request.
+        let response_scroll = if self.sent_request_id == Some(request_id) {
+            self.response_editor.cursor().0
+        } else {
+            previous.map(|pos| pos.response_scroll).unwrap_or(0)
+        };
+        let position = RequestPosition {
+            url: cursor(&self.request.url_editor),
+            headers: cursor(&self.request.headers_editor),
+            body: cursor(&self.request.body_editor),
+            request_tab: Some(request_tab_to_str(self.request_tab).to_string()),
+            response_tab: Some(self.response_tab.label().to_string()),
+            response_scroll,
+        };
+        self.request_positions.insert(request_id, position);
+    }
+
+    /// Puts back the cursors, tabs and response scroll of a request opened
+    /// before. Requests seen for the first time get the configured tabs.
+    fn restore_request_position(&mut self, request_id: Uuid) {
+        let position = self.request_positions.get(&request_id).cloned().unwrap_or_default();
+        self.request_tab = position
+            .request_tab
+            .as_deref()
+            .map(request_tab_from_str)
+            .unwrap_or_else(|| default_request_tab(&self.config));
+        self.response_tab = position
+            .response_tab
+            .as_deref()
+            .map(response_tab_from_str)
+            .unwrap_or_else(|| default_response_tab(&self.config));
+        self.sync_field_to_tab();
+        let jump = |editor: &mut TextArea<'static>, position: CursorPosition| {
+            let CursorPosition { row, col } = position.clamped(editor.lines());
+            editor.move_cursor(CursorMove::Jump(
+                row.min(u16::MAX as usize) as u16,
+                col.min(u16::MAX as usize) as u16,
+            ));
+        };
+        jump(&mut self.request.url_editor, position.url);
+        jump(&mut self.request.headers_editor, position.headers);
+        jump(&mut self.request.body_editor, position.body);
+        if self.sent_request_id == Some(request_id) {
+            let scroll = CursorPosition {
+                row: position.response_scroll,
+                col: 0,
+            };
+            jump(&mut self.response_editor, scroll);
+        }
+    }
+
+    fn load_body_mode_from_postman(&mut self, request: &PostmanRequest) {
+        self.request.body_disabled = request.body.as_ref().and_then(|b| b.disabled) == Some(true);
+        if let Some(body) = &request.body {
+            match body.mode.as_str() {
+                "raw" => {
+                    let language = body
+                        .options
+                        .as_ref()
+                        .and_then(|o| o.raw.as_ref())
+                        .map(|r| r.language.as_str());
+                    self.request.body_mode = match language {
+                        Some("json") => BodyMode::Json,
+                        Some("xml") => BodyMode::Xml,
+                        _ => BodyMode::Raw,
+                    };
+                    // Raw text is already loaded by set_contents above
+                }
+                "urlencoded" => {
+                    self.request.body_mode = BodyMode::FormUrlEncoded;
+                    if let Some(pairs) = &body.urlencoded {
+                        self.request.body_form_pairs = pairs
+                            .iter()
+                            .map(|p| KvPair {
+                                key: p.key.clone(),
+                                value: p.value.clone(),
+                                enabled: !p.disabled.unwrap_or(false),
+                            })
+                            .collect();
+                    }
+                    if self.request.body_form_pairs.is_empty()
+                        || self
+                            .request
+                            .body_form_pairs
+                            .last()
+                            .map(|p| !p.key.is_empty() || !p.value.is_empty())
+                            .unwrap_or(true)
+                    {
+                        self.request.body_form_pairs.push(KvPair::new_empty());
+                    }
+                }
+                "formdata" => {
+                    self.request.body_mode = BodyMode::Multipart;
+                    if let Some(params) = &body.formdata {
+                        self.request.body_multipart_fields = params
+                            .iter()
+                            .map(|p| MultipartField {
+                                key: p.key.clone(),
+                                value: match p.param_type.as_str() {
+                                    "file" => p.src.clone().unwrap_or_default(),
+                                    _ => p.value.clone().unwrap_or_default(),
+                                },
+                                field_type: match p.param_type.as_str() {
+                                    "file" => MultipartFieldType::File,
+                                    _ => MultipartFieldType::Text,
+                                },
+                                enabled: !p.disabled.unwrap_or(false),
+                            })
+                            .collect();
+                    }
+                    if self.request.body_multipart_fields.is_empty()
+                        || self
+                            .request
+                            .body_multipart_fields
+                            .last()
+                            .map(|f| !f.key.is_empty())
+                            .unwrap_or(true)
+                    {
+                        self.request
+                            .body_multipart_fields
+                            .push(MultipartField::new_empty());
+                    }
+                }
+                "file" => {
+                    self.request.body_mode = BodyMode::Binary;
+                    if let Some(file_ref) = &body.file {
+                        if let Some(src) = &file_ref.src {
+                            self.request.body_binary_path_editor =
+                                TextArea::new(vec![src.clone()]);
+                            configure_editor(
+                                &mut self.request.body_binary_path_editor,
+                                "File path...",
+                            );
+                        }
+                    }
+                }
+                _ => {
+                    self.request.body_mode = BodyMode::Raw;
+                }
+            }
+        } else {
+            self.request.body_mode = BodyMode::Raw;
+        }
+    }
+
+    fn load_auth_from_postman(&mut self, request: &PostmanRequest) {
+        self.request.auth_disabled = request.auth.as_ref().and_then(|a| a.disabled) == Some(true);
+        if let Some(auth) = &request.auth {
+            match auth.auth_type.as_str() {
+                "bearer" => {
+                    self.request.auth_type = AuthType::Bearer;
+                    if let Some(token) = auth.get_bearer_token() {
+                        self.request.auth_token_editor =
+                            TextArea::new(vec![token.to_string()]);
+                        configure_editor(&mut self.request.auth_token_editor, "Token");
+                    }
+                }
+                "basic" => {
+                    self.request.auth_type = AuthType::Basic;
+                    if let Some((username, password)) = auth.get_basic_credentials() {
+                        self.request.auth_username_editor =
+                            TextArea::new(vec![username.to_string()]);
+                        configure_editor(&mut self.request.auth_username_editor, "Username");
+                        self.request.auth_password_editor =
+                            TextArea::new(vec![password.to_string()]);
+                        configure_editor(&mut self.request.auth_password_editor, "Password");
+                    }
+                }
+                "apikey" => {
+                    self.request.auth_type = AuthType::ApiKey;
+                    if let Some((key, value, location)) = auth.get_apikey() {
+                        self.request.auth_key_name_editor =
+                            TextArea::new(vec![key.to_string()]);
+                        configure_editor(&mut self.request.auth_key_name_editor, "Key name");
+                        self.request.auth_key_value_editor =
+                            TextArea::new(vec![value.to_string()]);
+                        configure_editor(&mut self.request.auth_key_value_editor, "Key value");
+                        self.request.api_key_location = match location {
+                            "query" => ApiKeyLocation::QueryParam,
+                            _ => ApiKeyLocation::Header,
+                        };
+                    }
+                }
+                _ => {
+                    self.request.auth_type = AuthType::NoAuth;
+                }
+            }
+        } else {
+            self.request.auth_type = AuthType::NoAuth;
+        }
+    }
+
+    fn open_project_switcher(&mut self) {
+        let index = self
+            .project_list
+            .iter()
+            .position(|p| p.id == self.active_project_id)
+            .unwrap_or(0);
+        self.sidebar.popup = Some(SidebarPopup::ProjectSwitch { index });
+        self.focus.panel = Panel::Sidebar;
+    }
+
+    fn handle_sidebar_key(&mut self, key: KeyEvent) {
+        let edits = matches!(
+            key.code,
+            KeyCode::Char('a' | 'r' | 'd' | 'D' | 'm' | 'n' | 'N' | 'p' | 't' | 'T' | '[' | ']')
+        );
+        if edits && self.refuse_read_only() {
+            return;
+        }
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => self.sidebar_move_selection(1),
+            KeyCode::Char('k') | KeyCode::Up => self.sidebar_move_selection(-1),
+            KeyCode::Char('h') => self.sidebar_collapse_or_parent(),
+            KeyCode::Char('l') | KeyCode::Enter => self.sidebar_expand_or_open(),
+            KeyCode::Char('a') => self.open_add_popup(),
+            KeyCode::Char('r') => self.confirm_if_locked(LockedAction::Rename),
+            KeyCode::Char('d') => self.confirm_if_locked(LockedAction::Delete),
+            KeyCode::Char('D') => {
+                if let Err(err) = self.duplicate_selected() {
+                    self.notify(Level::Error, err);
+                }
+            }
+            KeyCode::Char('m') => self.confirm_if_locked(LockedAction::Move),
+            KeyCode::Char('p') => self.toggle_selected_lock(),
+            KeyCode::Char('n') => {
+                if let Err(err) = self.auto_name_selected() {
+                    self.notify(Level::Error, err);
+                }
+            }
+            KeyCode::Char('N') => {
+                if let Err(err) = self.toggle_auto_names() {
+                    self.notify(Level::Error, err);
+                }
+            }
+            KeyCode::Char('R') => self.open_run_setup(),
+            KeyCode::Char('U') => self.open_url_audit(),
+            KeyCode::Char('C') => self.open_changelog(),
+            KeyCode::Char('c') => self.copy_selected_path(),
+            KeyCode::Char('t') => {
+                if let Err(err) = self.save_selected_as_template() {
+                    self.notify(Level::Error, err);
+                }
+            }
+            KeyCode::Char('/') => {
+                let input = TextInput::new(self.sidebar.search_query.clone());
+                self.sidebar.popup = Some(SidebarPopup::Search(input));
+            }
+            KeyCode::Char('f') => {
+                let choices = SidebarFilter::choices(&self.sidebar_tree.tags());
+                let index = choices.iter().position(|f| *f == self.sidebar.filter).unwrap_or(0);
+                self.sidebar.popup = Some(SidebarPopup::Filter { index });
+            }
+            KeyCode::Char('T') => self.open_tags_popup(),
+            KeyCode::Char('[') => self.confirm_if_locked(LockedAction::Outdent),
+            KeyCode::Char(']') => self.confirm_if_locked(LockedAction::Indent),
+            KeyCode::Char('H') => self.collapse_all(),
+            KeyCode::Char('L') => self.expand_all(),
+            KeyCode::Char('?') => self.open_help(),
+            KeyCode::Char('q') => {
+                self.save_current_request_if_dirty();
+                self.persist_session_state();
+                self.running = false;
+            }
+            KeyCode::Esc
+                if !self.sidebar.search_query.is_empty()
+                    || self.sidebar.filter != SidebarFilter::All =>
+            {
+                self.sidebar.search_query.clear();
+                self.sidebar.filter = SidebarFilter::All;
+                self.mark_sidebar_search_dirty();
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_sidebar_popup(&mut self, key: KeyEvent) {
+        let mut popup = match self.sidebar.popup.take() {
+            Some(popup) => popup,
+            None => return,
+        };
+        let mut close = false;
+        let mut confirmed = None;
+
+        match &mut popup {
+            SidebarPopup::Add {
+                input,
+                templates,
+                template,
+            } => {
+                let choices = templates.len() + 1;
+                if key.code == KeyCode::Enter {
+                    let template_id = template.checked_sub(1).map(|i| templates[i].0);
+                    if let Err(err) = self.handle_add_input(&input.value, template_id) {
+                        self.notify(Level::Error, err);
+                    }
+                    close = true;
+                } else if matches!(key.code, KeyCode::Tab | KeyCode::Down) {
+                    *template = (*template + 1) % choices;
+                } else if matches!(key.code, KeyCode::BackTab | KeyCode::Up) {
+                    *template = (*template + choices - 1) % choices;
+                } else if key.code == KeyCode::Esc {
+                    close = true;
+                } else {
+                    handle_text_input(input, key);
+                }
+            }
+            SidebarPopup::Tags { id, input } => {
+                if key.code == KeyCode::Enter {
+                    // A bad list keeps the popup open to fix it
+                    match self.set_tags(*id, &input.value) {
+                        Ok(()) => close = true,
+                        Err(err) => self.notify(Level::Error, err),
+                    }
+                } else if key.code == KeyCode::Esc {
+                    close = true;
+                } else {
+                    handle_text_input(input, key);
+                }
+            }
+            SidebarPopup::Rename(input) => {
+                if key.code == KeyCode::Enter {
+                    if let Err(err) = self.rename_selected(input.value.clone()) {
+                        self.notify(Level::Error, err);
+                    }
+                    close = true;
+                } else if key.code == KeyCode::Esc {
+                    close = true;
+                } else {
+                    handle_text_input(input, key);
+                }
+            }
+            SidebarPopup::RenameInline(input) => {
+                if key.code == KeyCode::Enter {
+                    // An empty name keeps the row in edit mode to fix it
+                    match self.rename_selected(input.value.clone()) {
+                        Ok(()) => close = true,
+                        Err(err) => self.notify(Level::Error, err),
+                    }
+                } else if key.code == KeyCode::Esc {
+                    close = true;
+                } else {
+                    handle_text_input(input, key);
+                }
+            }
+            SidebarPopup::Search(input) => {
+                if key.code == KeyCode::Enter {
+                    self.sidebar.search_query = input.value.clone();
+                    self.mark_sidebar_search_dirty();
+                    close = true;
+                } else if key.code == KeyCode::Esc {
+                    self.sidebar.search_query.clear();
+                    self.mark_sidebar_search_dirty();
+                    close = true;
+                } else {
+                    handle_text_input(input, key);
+                    self.sidebar.search_query = input.value.clone();
+                    self.mark_sidebar_search_dirty();
+                }
+            }
+            SidebarPopup::Filter { index } => {
+                let choices = SidebarFilter::choices(&self.sidebar_tree.tags());
+                match key.code {
+                    KeyCode::Char('j') | KeyCode::Char('f') | KeyCode::Down => {
+                        *index = (*index + 1) % choices.len();
+                    }
+                    KeyCode::Char('k') | KeyCode::Up => {
+                        *index = (*index + choices.len() - 1) % choices.len();
+                    }
+                    KeyCode::Enter => {
+                        self.sidebar.filter = choices[*index].clone();
+                        self.mark_sidebar_search_dirty();
+                        close = true;
+                    }
+                    KeyCode::Esc => close = true,
+                    _ => {}
+                }
+            }
+            SidebarPopup::ProjectSwitch { index } => match key.code {
+                KeyCode::Char('j') | KeyCode::Down if !self.project_list.is_empty() => {
+                    *index = (*index + 1) % self.project_list.len();
+                }
+                KeyCode::Char('k') | KeyCode::Up if !self.project_list.is_empty() => {
+                    if *index == 0 {
+                        *index = self.project_list.len() - 1;
+                    } else {
+                        *index -= 1;
+                    }
+                }
+                KeyCode::Enter => {
+                    if let Some(project) = self.project_list.get(*index) {
+                        self.set_active_project(project.id);
+                    }
+                    close = true;
+                }
+                KeyCode::Char('x') => {
+                    if let Some(project) = self.project_list.get(*index) {
+                        let input = TextInput::new(archive::archive_file_name(&project.name));
+                        popup = SidebarPopup::ArchiveExport {
+                            project_id: project.id,
+                            input,
+                            notes: false,
+                        };
+                    }
+                }
+                KeyCode::Char('i') if !self.refuse_read_only() => {
+                    popup = SidebarPopup::ArchiveImport(TextInput::new(String::new()));
+                }
+                KeyCode::Esc => close = true,
+                _ => {}
+            },
+            SidebarPopup::ArchiveExport { project_id, input, notes } => match key.code {
+                KeyCode::Tab => *notes = !*notes,
+                KeyCode::Enter if !input.value.trim().is_empty() => {
+                    let path = config::expand_tilde(std::path::Path::new(input.value.trim()));
+                    match self.export_project_archive(*project_id, &path, *notes) {
+                        Ok(msg) => {
+                            self.notify(Level::Info, msg);
+                            close = true;
+                        }
+                        Err(err) => self.notify(Level::Error, err),
+                    }
+                }
+                KeyCode::Esc => close = true,
+                _ => handle_text_input(input, key),
+            },
+            SidebarPopup::RunSetup { target, input, export, parallel } => match key.code {
+                KeyCode::Tab => *export = !*export,
+                KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    *parallel = match parallel {
+                        Some(_) => None,
+                        None => Some(runner::DEFAULT_CONCURRENCY),
+                    };
+                }
+                KeyCode::Up => {
+                    if let Some(limit) = parallel {
+                        *limit = (*limit + 1).min(runner::MAX_CONCURRENCY);
+                    }
+                }
+                KeyCode::Down => {
+                    if let Some(limit) = parallel {
+                        *limit = limit.saturating_sub(1).max(2);
+                    }
+                }
+                KeyCode::Enter => {
+                    let data_path = input.value.trim();
+                    match self.start_run(*target, data_path, *export, *parallel) {
+                        Ok(()) => close = true,
+                        Err(err) => self.notify(Level::Error, err),
+                    }
+                }
+                KeyCode::Esc => close = true,
+                _ => handle_text_input(input, key),
+            },
+            SidebarPopup::ArchiveImport(input) => match key.code {
+                KeyCode::Enter if !input.value.trim().is_empty() => {
+                    let path = config::expand_tilde(std::path::Path::new(input.value.trim()));
+                    match self.import_project_archive(&path) {
+                        Ok(msg) => {
+                            if let Some(msg) = msg {
+                                self.notify(Level::Info, msg);
+                            }
+                            close = true;
+                        }
+                        Err(err) => self.notify(Level::Error, err),
+                    }
+                }
+                KeyCode::Esc => close = true,
+                _ => handle_text_input(input, key),
+            },
+            SidebarPopup::Move { index, candidates } => match key.code {
+                KeyCode::Char('j') | KeyCode::Down if !candidates.is_empty() => {
+                    *index = (*index + 1) % candidates.len();
+                }
+                KeyCode::Char('k') | KeyCode::Up if !candidates.is_empty() => {
+                    if *index == 0 {
+                        *index = candidates.len() - 1;
+                    } else {
+                        *index -= 1;
+                    }
+                }
+                KeyCode::Enter => {
+                    if let Some(dest_id) = candidates.get(*index).copied() {
+                        if let Err(err) = self.move_selected(dest_id) {
+                            self.notify(Level::Error, err);
+                        }
+                    }
+                    close = true;
+                }
+                KeyCode::Esc => close = true,
+                _ => {}
+            },
+            SidebarPopup::DeleteConfirm => match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => {
+                    if let Err(err) = self.delete_selected() {
+                        self.notify(Level::Error, err);
+                    }
+                    close = true;
+                }
+                KeyCode::Char('n') | KeyCode::Esc => close = true,
+                _ => {}
+            },
+            SidebarPopup::LockedConfirm(action) => match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => {
+                    confirmed = Some(*action);
+                    close = true;
+                }
+                KeyCode::Char('n') | KeyCode::Esc => close = true,
+                _ => {}
+            },
+        }
+
+        if close {
+            self.sidebar.popup = None;
+        } else {
+            self.sidebar.popup = Some(popup);
+        }
+        if let Some(action) = confirmed {
+            self.run_locked_action(action);
+        }
+    }
+
+    /// Runs `action` on the selected item, asking first if it is locked or
+    /// holds locked items.
+    fn confirm_if_locked(&mut self, action: LockedAction) {
+        let locked = self
+            .sidebar_selected_id()
+            .is_some_and(|id| self.sidebar_tree.contains_locked(id));
+        if locked {
+            self.sidebar.popup = Some(SidebarPopup::LockedConfirm(action));
+        } else {
+            self.run_locked_action(action);
+        }
+    }
+
+    fn run_locked_action(&mut self, action: LockedAction) {
+        match action {
+            LockedAction::Delete => self.sidebar.popup = Some(SidebarPopup::DeleteConfirm),
+            LockedAction::Rename => self.open_rename_popup(),
+            LockedAction::Move => self.open_move_popup(),
+            LockedAction::Outdent => self.outdent_selected(),
+            LockedAction::Indent => self.indent_selected(),
+        }
+    }
+
+    fn open_tags_popup(&mut self) {
+        let Some(id) = self.sidebar_selected_id() else {
+            return;
+        };
+        let tags = self.sidebar_tree.node(id).map(|node| node.tags.as_slice()).unwrap_or(&[]);
+        let input = TextInput::new(storage::format_tags(tags));
+        self.sidebar.popup = Some(SidebarPopup::Tags { id, input });
+    }
+
+    fn set_tags(&mut self, id: Uuid, text: &str) -> Result<(), String> {
+        let tags = storage::parse_tags(text)?;
+        self.collection.set_tags(id, tags)?;
+        self.save_collection();
+        self.refresh_after_collection_change();
+        Ok(())
+    }
+
+    fn toggle_selected_lock(&mut self) {
+        let Some(id) = self.sidebar_selected_id() else {
+            return;
+        };
+        let name = self.sidebar_tree.node(id).map(|node| node.name.clone()).unwrap_or_default();
+        match self.collection.toggle_locked(id) {
+            Ok(locked) => {
+                self.save_collection();
+                self.refresh_after_collection_change();
+                let msg = match (locked, self.sidebar_tree.locked_by(id)) {
+                    (true, _) => format!("Locked '{}'", name),
+                    (false, Some(by)) => {
+                        let by = self.sidebar_tree.path_for(by).join("/");
+                        format!("Unlocked '{}', still locked by '{}'", name, by)
+                    }
+                    (false, None) => format!("Unlocked '{}'", name),
+                };
+                self.notify(Level::Info, msg);
+            }
+            Err(err) => self.notify(Level::Error, err),
+        }
+    }
+
+    /// Whether the open request is locked, itself or through a folder.
+    fn request_locked(&self) -> bool {
+        self.current_request_id
+            .is_some_and(|id| self.sidebar_tree.locked_by(id).is_some())
+    }
+
+    /// Refuses to change a locked request, or any request while read-only,
+    /// saying why. Returns whether it refused.
+    fn refuse_locked_edit(&mut self) -> bool {
+        if self.refuse_read_only() {
+            return true;
+        }
+        if !self.request_locked() {
+            return false;
+        }
+        self.notify(Level::Warn, "Request is locked; p in the sidebar unlocks it");
+        true
+    }
+
+    fn export_project_archive(
+        &mut self,
+        project_id: Uuid,
+        path: &std::path::Path,
+        notes: bool,
+    ) -> Result<String, String> {
+        self.save_current_request_if_dirty();
+        let mut session = self.current_session_state();
+        if !notes {
+            session.notes.clear();
+        }
+        let requests =
+            archive::export_project_archive(&self.collection, project_id, Some(&session), path)?;
+        Ok(format!("Archived {} requests to {}", requests, path.display()))
+    }
+
+    /// Imports an archive, a Postman collection (`.json`) or an OpenAPI
+    /// spec as a new project and switches to it. A spec whose operations
+    /// were imported before opens the update prompt instead, with `None`
+    /// returned.
+    fn import_project_archive(
+        &mut self,
+        path: &std::path::Path,
+    ) -> Result<Option<String>, String> {
+        if openapi::is_spec_file(path) {
+            let spec = openapi::read_openapi_spec(path)?;
+            let matched = openapi::matching_requests(&self.collection, &spec);
+            if matched > 0 {
+                self.spec_update = Some(SpecUpdatePrompt { spec, matched });
+                return Ok(None);
+            }
+            return self.install_spec(spec).map(Some);
+        }
+        let project = if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json")) {
+            archive::read_postman_collection(path)?
+        } else {
+            archive::read_project_archive(path)?
+        };
+        let imported = archive::install_project_archive(&mut self.collection, project)?;
+        Ok(Some(self.show_imported_project(&imported)))
+    }
+
+    fn install_spec(&mut self, spec: openapi::OpenApiImport) -> Result<String, String> {
+        let summary = spec.skipped_summary();
+        let imported = archive::install_project_archive(&mut self.collection, spec.archive)?;
+        Ok(format!("{}{}", self.show_imported_project(&imported), summary))
+    }
+
+    fn handle_spec_update(&mut self, key: KeyEvent) {
+        let result = match key.code {
+            KeyCode::Char('u') | KeyCode::Enter => {
+                let Some(prompt) = self.spec_update.take() else {
+                    return;
+                };
+                self.update_from_spec(&prompt.spec)
+            }
+            KeyCode::Char('n') => {
+                let Some(prompt) = self.spec_update.take() else {
+                    return;
+                };
+                self.install_spec(prompt.spec)
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.spec_update = None;
+                Ok("Import cancelled".to_string())
+            }
+            _ => return,
+        };
+        match result {
+            Ok(msg) => self.notify(Level::Info, msg),
+            Err(err) => self.notify(Level::Error, err),
+        }
+    }
+
+    /// Updates the requests imported from an earlier version of the spec
+    /// and adds its new operations.
+    fn update_from_spec(&mut self, spec: &openapi::OpenApiImport) -> Result<String, String> {
+        self.save_current_request_if_dirty();
+        let update = openapi::update_from_spec(&mut self.collection, spec)?;
+        self.save_collection();
+        self.request_file_errors = self.collection.write_all_request_files()?.unreadable;
+        self.refresh_after_collection_change();
+        if let Some(current) = self.current_request_id {
+            self.open_request(current);
+        }
+        Ok(format!(
+            "Updated {} requests, added {}{}",
+            update.updated,
+            update.added,
+            spec.skipped_summary()
+        ))
+    }
+
+    /// Switches to a project just added to the collection, restoring the
+    /// folders it had open and where each request was left.
+    fn show_imported_project(&mut self, imported: &archive::ImportedProject) -> String {
+        let detail = format!("imported, {} requests", imported.requests);
+        self.record_change(ChangeKind::Added, imported.name.clone(), Some(detail));
+        self.refresh_after_collection_change();
+        if let Some(session) = &imported.session {
+            let project_id = imported.project_id.to_string();
+            if let Some(sidebar) = session.projects.get(&project_id) {
+                let sidebar = ProjectSidebar::from_session(sidebar);
+                self.project_sidebars.insert(imported.project_id, sidebar);
+            }
+            for (id, position) in &session.positions {
+                if let Ok(id) = Uuid::parse_str(id) {
+                    self.request_positions.insert(id, position.clone());
+                }
+            }
+            for (id, note) in &session.notes {
+                if let Ok(id) = Uuid::parse_str(id) {
+                    self.request_notes.insert(id, note.clone());
+                }
+            }
+        }
+        self.set_active_project(imported.project_id);
+        if !imported.environments.is_empty() {
+            match environment::load_all_environments() {
+                Ok(environments) => self.environments = environments,
+                Err(err) => self.notify(Level::Error, err),
+            }
+        }
+        let mut msg = format!(
+            "Imported '{}' ({} requests, {} environments)",
+            imported.name,
+            imported.requests,
+            imported.environments.len()
+        );
+        if !imported.skipped_environments.is_empty() {
+            msg.push_str(&format!(
+                "; kept existing {}",
+                imported.skipped_environments.join(", ")
+            ));
+        }
+        msg
+    }
+
+    fn handle_onboarding(&mut self, key: KeyEvent) {
+        let Some(onboarding) = self.onboarding.as_mut() else {
+            return;
+        };
+        let count = ONBOARDING_CHOICES.len();
+        let mut choice = None;
+        match onboarding {
+            Onboarding::Choose { index } => match key.code {
+                KeyCode::Char('j') | KeyCode::Down => *index = (*index + 1) % count,
+                KeyCode::Char('k') | KeyCode::Up => *index = (*index + count - 1) % count,
+                KeyCode::Char(c @ '1'..='3') => choice = Some(c as usize - '1' as usize),
+                KeyCode::Enter => choice = Some(*index),
+                KeyCode::Esc | KeyCode::Char('q') => choice = Some(0),
+                _ => {}
+            },
+            Onboarding::Import(input) => match key.code {
+                KeyCode::Enter if !input.value.trim().is_empty() => {
+                    let path = config::expand_tilde(std::path::Path::new(input.value.trim()));
+                    match self.import_project_archive(&path) {
+                        Ok(msg) => {
+                            if let Some(msg) = msg {
+                                self.notify(Level::Info, msg);
+                            }
+                            self.finish_onboarding();
+                        }
+                        Err(err) => self.notify(Level::Error, err),
+                    }
+                    return;
+                }
+                KeyCode::Esc => *onboarding = Onboarding::Choose { index: 2 },
+                _ => handle_text_input(input, key),
+            },
+        }
+        match choice {
+            Some(0) => self.finish_onboarding(),
+            Some(1) => match self.create_sample_project() {
+                Ok(msg) => {
+                    self.notify(Level::Info, msg);
+                    self.finish_onboarding();
+                }
+                Err(err) => self.notify(Level::Error, err),
+            },
+            Some(_) => self.onboarding = Some(Onboarding::Import(TextInput::new(String::new()))),
+            None => {}
+        }
+    }
+
+    /// Dismisses the first-run overlay for good.
+    fn finish_onboarding(&mut self) {
+        self.onboarding = None;
+        self.persist_ui_state();
+    }
+
+    /// Adds the httpbin sample project, switches to it with its environment
+    /// active and opens its first request.
+    fn create_sample_project(&mut self) -> Result<String, String> {
+        let imported = sample::create_sample_project(&mut self.collection)?;
+        let msg = self.show_imported_project(&imported);
+        if self.environments.iter().any(|env| env.name == sample::SAMPLE_ENVIRONMENT) {
+            self.active_environment_name = Some(sample::SAMPLE_ENVIRONMENT.to_string());
+        }
+        let first_request = self
+            .collection
+            .get_item(imported.project_id)
+            .and_then(|project| runner::run_order(project).first().map(|item| item.id.clone()))
+            .and_then(|id| Uuid::parse_str(&id).ok());
+        if let Some(request_id) = first_request {
+            self.sidebar.selection_id = Some(request_id);
+            self.expand_sidebar_ancestors(request_id);
+            self.open_request(request_id);
+        }
+        Ok(msg)
+    }
+
+    /// Every request of the active project whose URL has a literal host or
+    /// variables the active environment lacks, in sidebar order.
+    fn audit_urls(&self) -> Vec<AuditEntry> {
+        let Some(project) = self.collection.get_item(self.active_project_id) else {
+            return Vec::new();
+        };
+        let variables = self.active_variables().unwrap_or_default();
+        runner::run_order(project)
+            .into_iter()
+            .filter_map(|item| {
+                let request_id = Uuid::parse_str(&item.id).ok()?;
+                let url = extract_url(&item.request.as_ref()?.url);
+                let findings = audit::audit_url(&url, |name| variables.contains_key(name));
+                (!findings.is_empty()).then(|| AuditEntry {
+                    request_id,
+                    path: self.sidebar_tree.path_for(request_id).join("/"),
+                    url,
+                    findings,
+                })
+            })
+            .collect()
+    }
+
+    fn open_url_audit(&mut self) {
+        self.save_current_request_if_dirty();
+        let entries = self.audit_urls();
+        self.url_audit_flags = entries.iter().map(|entry| entry.request_id).collect();
+        self.mark_sidebar_dirty();
+        if entries.is_empty() {
+            self.url_audit = None;
+            self.notify(Level::Info, "Every request URL uses defined variables");
+            return;
+        }
+        self.url_audit = Some(UrlAuditPopup {
+            entries,
+            index: 0,
+            fix: None,
+        });
+    }
+
+    fn handle_url_audit(&mut self, key: KeyEvent) {
+        let Some(popup) = self.url_audit.as_mut() else {
+            return;
+        };
+        if let Some(input) = popup.fix.as_mut() {
+            match key.code {
+                KeyCode::Esc => popup.fix = None,
+                KeyCode::Enter => {
+                    let variable = input.value.trim().to_string();
+                    let base = popup.fix_target().map(|(base, _)| base.to_string());
+                    if let Some(base) = base {
+                        if let Err(err) = self.replace_literal_base(&base, &variable) {
+                            self.notify(Level::Error, err);
+                        }
+                    }
+                }
+                _ => handle_text_input(input, key),
+            }
+            return;
+        }
+        let count = popup.entries.len();
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => popup.index = (popup.index + 1) % count,
+            KeyCode::Up | KeyCode::Char('k') => popup.index = (popup.index + count - 1) % count,
+            KeyCode::Enter => {
+                let id = popup.entries[popup.index].request_id;
+                self.url_audit = None;
+                self.reveal_in_sidebar(id);
+                self.open_request(id);
+                self.app_mode = AppMode::Navigation;
+            }
+            KeyCode::Char('f') => {
+                if popup.fix_target().is_some() {
+                    popup.fix = Some(TextInput::new("base_url".to_string()));
+                } else {
+                    self.notify(Level::Warn, "This request has no literal host to replace");
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') => self.url_audit = None,
+            _ => {}
+        }
+    }
+
+    /// Every line of the active project's saved requests that uses
+    /// `{{name}}`, in sidebar order.
+    fn find_usages(&self, name: &str) -> Vec<UsageHit> {
+        let Some(project) = self.collection.get_item(self.active_project_id) else {
+            return Vec::new();
+        };
+        let mut hits = Vec::new();
+        for item in runner::run_order(project) {
+            let (Ok(request_id), Some(request)) = (Uuid::parse_str(&item.id), &item.request)
+            else {
+                continue;
+            };
+            let path = self.sidebar_tree.path_for(request_id).join("/");
+            for (field, text) in usage_fields(request) {
+                for line in environment::placeholder_lines(&text, name) {
+                    hits.push(UsageHit {
+                        request_id,
+                        path: path.clone(),
+                        field,
+                        line,
+                    });
+                }
+            }
+        }
+        hits
+    }
+
+    fn open_usages(&mut self) {
+        // Unsaved edits to the open request count too
+        self.save_current_request_if_dirty();
+        self.usages_popup = Some(UsagesPopup {
+            name: TextInput::new(String::new()),
+            hits: Vec::new(),
+            index: 0,
+            rename: None,
+            preview: None,
+        });
+    }
+
+    fn handle_usages(&mut self, key: KeyEvent) {
+        let Some(popup) = self.usages_popup.as_mut() else {
+            return;
+        };
+        if popup.preview.is_some() {
+            match key.code {
+                KeyCode::Enter | KeyCode::Char('y') => {
+                    if let Some(preview) = popup.preview.take() {
+                        if let Err(err) = self.rename_variable(preview) {
+                            self.notify(Level::Error, err);
+                        }
+                    }
+                }
+                KeyCode::Esc | KeyCode::Char('n') => popup.preview = None,
+                _ => {}
+            }
+            return;
+        }
+        if let Some(input) = popup.rename.as_mut() {
+            match key.code {
+                KeyCode::Esc => popup.rename = None,
+                KeyCode::Enter => {
+                    let from = popup.name.value.trim().to_string();
+                    let to = input.value.trim().to_string();
+                    match self.preview_variable_rename(&from, &to) {
+                        Ok(preview) => {
+                            if let Some(popup) = self.usages_popup.as_mut() {
+                                popup.rename = None;
+                                popup.preview = Some(preview);
+                            }
+                        }
+                        Err(err) => self.notify(Level::Warn, err),
+                    }
+                }
+                _ => handle_text_input(input, key),
+            }
+            return;
+        }
+        let count = popup.hits.len();
+        match key.code {
+            KeyCode::Esc => self.usages_popup = None,
+            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                let name = popup.name.value.trim().to_string();
+                if name.is_empty() {
+                    self.notify(Level::Warn, "Type the variable to rename first");
+                } else if !self.refuse_read_only() {
+                    if let Some(popup) = self.usages_popup.as_mut() {
+                        popup.rename = Some(TextInput::new(name));
+                    }
+                }
+            }
+            KeyCode::Down if count > 0 => popup.index = (popup.index + 1) % count,
+            KeyCode::Up if count > 0 => popup.index = (popup.index + count - 1) % count,
+            KeyCode::Enter => {
+                if let Some(hit) = popup.hits.get(popup.index).cloned() {
+                    self.open_usage(&hit);
+                }
+            }
+            _ => {
+                handle_text_input(&mut popup.name, key);
+                let name = popup.name.value.trim().to_string();
+                let hits = self.find_usages(&name);
+                if let Some(popup) = self.usages_popup.as_mut() {
+                    popup.hits = hits;
+                    popup.index = 0;
+                }
+            }
+        }
+    }
+
+    /// The saved requests of the active project, in sidebar order.
+    fn project_request_ids(&self) -> Vec<Uuid> {
+        let Some(project) = self.collection.get_item(self.active_project_id) else {
+            return Vec::new();
+        };
+        runner::run_order(project)
+            .into_iter()
+            .filter_map(|item| Uuid::parse_str(&item.id).ok())
+            .collect()
+    }
+
+    /// Works out renaming `from` to `to` in the environment files and the
+    /// project's requests, refusing a name that's already in use.
+    fn preview_variable_rename(&self, from: &str, to: &str) -> Result<RenamePreview, String> {
+        if !environment::is_safe_env_name(to) {
+            return Err(format!("'{}' isn't a valid variable name", to));
+        }
+        if to == from {
+            return Err(format!("The variable is already called '{}'", to));
+        }
+        let taken = self.find_usages(to).len();
+        if taken > 0 {
+            return Err(format!("{{{{{}}}}} is already used on {} lines", to, taken));
+        }
+        let plan = environment::plan_variable_rename(from, to)?;
+        let in_requests: usize = self
+            .project_request_ids()
+            .into_iter()
+            .filter_map(|id| self.collection.get_item(id)?.request.clone())
+            .map(|mut request| request.rename_placeholder(from, to))
+            .sum();
+        let occurrences = plan.occurrences + in_requests;
+        if occurrences == 0 {
+            return Err(format!("Nothing uses {{{{{}}}}}", from));
+        }
+        Ok(RenamePreview {
+            from: from.to_string(),
+            to: to.to_string(),
+            env_files: plan.paths().map(|path| path.display().to_string()).collect(),
+            occurrences,
+            plan,
+        })
+    }
+
+    /// Writes the environment files, then rewrites and saves the requests.
+    fn rename_variable(&mut self, preview: RenamePreview) -> Result<(), String> {
+        if self.refuse_read_only() {
+            return Ok(());
+        }
+        preview.plan.apply()?;
+        let mut changed = Vec::new();
+        for id in self.project_request_ids() {
+            let Some(request) = self.collection.get_item_mut(id).and_then(|i| i.request.as_mut())
+            else {
+                continue;
+            };
+            if request.rename_placeholder(&preview.from, &preview.to) > 0 {
+                changed.push(id);
+            }
+        }
+        self.save_collection();
+        let written = self.write_request_files(&changed);
+        if let Some(current) = self.current_request_id.filter(|id| changed.contains(id)) {
+            self.open_request(current);
+        }
+        match environment::load_all_environments() {
+            Ok(environments) => self.environments = environments,
+            Err(err) => self.notify(Level::Error, err),
+        }
+        let hits = self.find_usages(&preview.to);
+        if let Some(popup) = self.usages_popup.as_mut() {
+            popup.name = TextInput::new(preview.to.clone());
+            popup.hits = hits;
+            popup.index = 0;
+        }
+        self.mark_dirty(DirtyPanels::ALL);
+        let msg = format!(
+            "Renamed {{{{{}}}}} to {{{{{}}}}}: {} occurrences in {} requests, {} env files",
+            preview.from,
+            preview.to,
+            preview.occurrences,
+            changed.len(),
+            preview.env_files.len()
+        );
+        self.notify(Level::Info, msg);
+        written
+    }
+
+    /// Opens the request of `hit` with its field focused and, in the text
+    /// editors and form rows, the cursor on its line.
+    fn open_usage(&mut self, hit: &UsageHit) {
+        self.usages_popup = None;
+        self.show_env_popup = false;
+        self.reveal_in_sidebar(hit.request_id);
+        self.open_request(hit.request_id);
+        self.app_mode = AppMode::Navigation;
+        self.focus.panel = Panel::Request;
+        self.focus.request_field = hit.field;
+        let row = hit.line.saturating_sub(1);
+        let jump = CursorMove::Jump(row.min(u16::MAX as usize) as u16, 0);
+        match hit.field {
+            RequestField::Headers => {
+                self.request_tab = RequestTab::Headers;
+                self.request.headers_editor.move_cursor(jump);
+            }
+            RequestField::Auth => self.request_tab = RequestTab::Auth,
+            RequestField::Body => {
+                self.request_tab = RequestTab::Body;
+                let mode = self.request.body_mode;
+                if mode.is_text_mode() {
+                    self.focus.body_field = BodyField::TextEditor;
+                    self.request.body_editor.move_cursor(jump);
+                } else if mode == BodyMode::Binary {
+                    self.focus.body_field = BodyField::BinaryPath;
+                } else {
+                    self.focus.body_field = BodyField::KvRow;
+                    self.focus.kv_focus.row = row;
+                }
+            }
+            _ => {}
+        }
+        self.mark_dirty(DirtyPanels::ALL);
+    }
+
+    /// Rewrites every flagged URL starting with `base` to start with
+    /// `{{variable}}` instead, saves them and audits again.
+    fn replace_literal_base(&mut self, base: &str, variable: &str) -> Result<(), String> {
+        if !environment::is_safe_env_name(variable) {
+            return Err(format!("'{}' isn't a valid variable name", variable));
+        }
+        let targets: Vec<Uuid> = self
+            .url_audit
+            .iter()
+            .flat_map(|popup| popup.entries.iter())
+            .filter(|entry| entry.literal_base() == Some(base))
+            .map(|entry| entry.request_id)
+            .collect();
+        let mut changed = Vec::new();
+        for id in targets {
+            let Some(request) = self.collection.get_item_mut(id).and_then(|i| i.request.as_mut())
+            else {
+                continue;
+            };
+            if let Some(url) = audit::replace_base(&extract_url(&request.url), base, variable) {
+                request.url = Value::String(url);
+                changed.push(id);
+            }
+        }
+        self.save_collection();
+        self.write_request_files(&changed)?;
+        if let Some(current) = self.current_request_id.filter(|id| changed.contains(id)) {
+            self.open_request(current);
+        }
+
+        let mut msg = format!(
+            "Replaced {} with {{{{{}}}}} in {} requests",
+            base,
+            variable,
+            changed.len()
+        );
+        let defined = self.active_variables().unwrap_or_default();
+        if !defined.contains_key(variable) {
+            msg.push_str(&format!("; define {} in the active environment", variable));
+        }
+        self.notify(Level::Info, msg);
+
+        let entries = self.audit_urls();
+        self.url_audit_flags = entries.iter().map(|entry| entry.request_id).collect();
+        self.mark_sidebar_dirty();
+        self.url_audit = (!entries.is_empty()).then_some(UrlAuditPopup {
+            index: 0,
+            entries,
+            fix: None,
+        });
+        Ok(())
+    }
+
+    fn open_rename_popup(&mut self) {
+        let selected = self.sidebar.selection_id;
+        let lead = self
+            .sidebar_lines()
+            .iter()
+            .find(|line| Some(line.id) == selected)
+            .map(|line| line.lead().chars().count());
+        if let Some(node) = self.sidebar_selected_node() {
+            let input = TextInput::new(node.name.clone());
+            // Borders take two columns and the cursor one
+            let room = (self.sidebar_width as usize)
+                .saturating_sub(3)
+                .saturating_sub(lead.unwrap_or(usize::MAX));
+            self.sidebar.popup = Some(if room < Self::INLINE_RENAME_MIN_WIDTH {
+                SidebarPopup::Rename(input)
+            } else {
+                SidebarPopup::RenameInline(input)
+            });
+        }
+    }
+
+    fn open_add_popup(&mut self) {
+        self.sidebar.popup = Some(SidebarPopup::Add {
+            input: TextInput::new(String::new()),
+            templates: self.collection.list_templates(self.active_project_id),
+            template: 0,
+        });
+    }
+
+    fn save_selected_as_template(&mut self) -> Result<(), String> {
+        let Some(id) = self.sidebar_selected_id() else {
+            return Ok(());
+        };
+        if self.sidebar_tree.node(id).map(|n| n.kind) != Some(NodeKind::Request) {
+            return Err("Only requests can be saved as templates".to_string());
+        }
+        if self.current_request_id == Some(id) {
+            self.save_current_request_if_dirty();
+        }
+        let template_id = self.collection.save_as_template(self.active_project_id, id)?;
+        self.save_collection();
+        let path = self.item_path(template_id);
+        self.record_change(ChangeKind::Added, path, None);
+        let parent_id = self
+            .collection
+            .templates_folder(self.active_project_id)
+            .ok_or("Templates folder not found")?;
+        self.collection
+            .save_request_file(template_id, parent_id, self.active_project_id)?;
+        self.refresh_after_collection_change();
+        let name = self.sidebar_tree.node(id).map(|n| n.name.clone()).unwrap_or_default();
+        self.notify(
+            Level::Info,
+            format!("Saved \"{}\" to {}/", name, storage::TEMPLATES_FOLDER),
+        );
+        Ok(())
+    }
+
+    fn handle_add_input(&mut self, input: &str, template_id: Option<Uuid>) -> Result<(), String> {
+        let trimmed = input.trim();
+        if trimmed.is_empty() {
+            return Ok(());
+        }
+        // A pasted URL becomes a request named after its path
+        let pasted_url = http::looks_like_url(trimmed);
+        let (folders, request, url) = if pasted_url {
+            let name = http::request_name_from_url(trimmed);
+            (Vec::new(), Some(name), Some(trimmed.to_string()))
+        } else {
+            let (folders, request) = parse_add_path(trimmed);
+            (folders, request, None)
+        };
+        let mut parent_id = self.add_parent_id();
+        let mut new_folder = None;
+
+        for folder in folders {
+            if let Some(existing) = self.find_child_folder(parent_id, &folder) {
+                parent_id = existing;
+            } else {
+                parent_id = self.collection.add_folder(parent_id, folder)?;
+                new_folder = new_folder.or(Some(parent_id));
+            }
+        }
+
+        if let Some(request_name) = request {
+            let new_id = match template_id {
+                Some(template_id) => {
+                    let id = self.collection.add_request_from_template(
+                        parent_id,
+                        request_name,
+                        template_id,
+                    )?;
+                    let request = self.collection.get_item_mut(id).and_then(|i| i.request.as_mut());
+                    if let (Some(request), Some(url)) = (request, url) {
+                        request.url = Value::String(url);
+                    }
+                    id
+                }
+                None => {
+                    let req = self.config.defaults.new_request(url.unwrap_or_default());
+                    self.collection.add_request(parent_id, request_name, req)?
+                }
+            };
+            if pasted_url && self.auto_names_enabled() {
+                self.collection.apply_auto_name(new_id)?;
+            }
+            self.save_collection();
+            let path = self.item_path(new_id);
+            self.record_change(ChangeKind::Added, path, None);
+            self.collection
+                .save_request_file(new_id, parent_id, self.active_project_id)?;
+            self.refresh_after_collection_change();
+            self.sidebar.selection_id = Some(new_id);
+            self.open_request(new_id);
+        } else {
+            self.save_collection();
+            if let Some(folder) = new_folder {
+                let path = self.item_path(folder);
+                self.record_change(ChangeKind::Added, path, None);
+            }
+            self.refresh_after_collection_change();
+            self.sidebar.selection_id = Some(parent_id);
+        }
+        Ok(())
+    }
+
+    /// Whether the active project renames auto-named requests when their
+    /// method or path changes.
+    fn auto_names_enabled(&self) -> bool {
+        self.collection
+            .get_item(self.active_project_id)
+            .is_some_and(|project| project.auto_names)
+    }
+
+    /// Names every request in the selection after its method and path,
+    /// leaving locked ones alone.
+    fn auto_name_selected(&mut self) -> Result<(), String> {
+        let Some(id) = self.sidebar_selected_id() else {
+            return Ok(());
+        };
+        let mut request_ids = Vec::new();
+        if let Some(item) = self.collection.get_item(id) {
+            collect_request_ids(item, &mut request_ids);
+        }
+        let (locked, request_ids): (Vec<Uuid>, Vec<Uuid>) = request_ids
+            .into_iter()
+            .partition(|id| self.sidebar_tree.locked_by(*id).is_some());
+        if request_ids.is_empty() {
+            let msg = if locked.is_empty() { "No requests to name" } else { "Requests are locked" };
+            return Err(msg.to_string());
+        }
+        let mut renamed = 0;
+        for request_id in &request_ids {
+            let old_path = self.item_path(*request_id);
+            if self.collection.apply_auto_name(*request_id)? {
+                renamed += 1;
+                let path = self.item_path(*request_id);
+                self.record_change(ChangeKind::Renamed, path, Some(old_path));
+            }
+        }
+        self.save_collection();
+        self.refresh_after_collection_change();
+        self.write_request_files(&request_ids)?;
+        let mut msg = format!(
+            "Renamed {} of {} request(s) to method and path",
+            renamed,
+            request_ids.len()
+        );
+        if !locked.is_empty() {
+            msg.push_str(&format!(", skipped {} locked", locked.len()));
+        }
+        self.notify(Level::Info, msg);
+        Ok(())
+    }
+
+    fn toggle_auto_names(&mut self) -> Result<(), String> {
+        let enabled = self.collection.toggle_auto_names(self.active_project_id)?;
+        self.save_collection();
+        self.refresh_after_collection_change();
+        let msg = if enabled {
+            "Auto-named requests now follow method and path edits (n names the selection)"
+        } else {
+            "Auto-named requests keep their names"
+        };
+        self.notify(Level::Info, msg.to_string());
+        Ok(())
+    }
+
+    fn rename_selected(&mut self, name: String) -> Result<(), String> {
+        let Some(id) = self.sidebar_selected_id() else {
+            return Ok(());
+        };
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err("Name cannot be empty".to_string());
+        }
+        let is_request = self
+            .sidebar_tree
+            .node(id)
+            .map(|n| n.kind == NodeKind::Request)
+            .unwrap_or(false);
+        let old_path = self.item_path(id);
+        self.collection.rename_item(id, name)?;
+        self.save_collection();
+        let path = self.item_path(id);
+        self.record_change(ChangeKind::Renamed, path, Some(old_path));
+        self.refresh_after_collection_change();
+        if is_request {
+            self.write_request_files(&[id])?;
+        }
+        Ok(())
+    }
+
+    fn delete_selected(&mut self) -> Result<(), String> {
+        let Some(id) = self.sidebar_selected_id() else {
+            return Ok(());
+        };
+        let kind = self
+            .sidebar_tree
+            .node(id)
+            .map(|n| n.kind)
+            .unwrap_or(NodeKind::Folder);
+        let was_active_project = id == self.active_project_id;
+        let mut request_ids = Vec::new();
+        if let Some(item) = self.collection.get_item(id) {
+            collect_request_ids(item, &mut request_ids);
+        }
+        let path = self.item_path(id);
+        self.collection.delete_item(id)?;
+        self.save_collection();
+        self.record_change(ChangeKind::Deleted, path, None);
+        self.project_list = self.collection.list_projects();
+        if kind == NodeKind::Project && self.project_list.is_empty() {
+            let root_name = self
+                .collection
+                .root
+                .file_name()
+                .and_then(|s| s.to_str())
+                .unwrap_or("Perseus")
+                .to_string();
+            let new_id = self.collection.add_project(root_name)?;
+            self.save_collection();
+            self.project_list = self.collection.list_projects();
+            self.active_project_id = new_id;
+        } else if was_active_project {
+            if let Some(first) = self.project_list.first() {
+                self.active_project_id = first.id;
+            }
+        }
+        self.rebuild_sidebar_tree();
+        self.persist_ui_state();
+
+        if let Some(current) = self.current_request_id {
+            if current == id {
+                self.request = RequestState::new();
+                self.current_request_id = None;
+                self.request_dirty = false;
+            }
+        }
+
+        if !request_ids.is_empty() {
+            self.delete_request_files(&request_ids)?;
+        }
+
+        Ok(())
+    }
+
+    fn duplicate_selected(&mut self) -> Result<(), String> {
+        let Some(id) = self.sidebar_selected_id() else {
+            return Ok(());
+        };
+        let new_id = self.collection.duplicate_item(id)?;
+        let (path, original) = (self.item_path(new_id), self.item_path(id));
+        self.record_change(ChangeKind::Duplicated, path, Some(original));
+        let mut request_ids = Vec::new();
+        if let Some(item) = self.collection.get_item(new_id) {
+            collect_request_ids(item, &mut request_ids);
+        }
+        self.save_collection();
+        self.refresh_after_collection_change();
+        if !request_ids.is_empty() {
+            self.write_request_files(&request_ids)?;
+        }
+        self.sidebar.selection_id = Some(new_id);
+        Ok(())
+    }
+
+    fn move_selected(&mut self, dest_id: Uuid) -> Result<(), String> {
+        let Some(id) = self.sidebar_selected_id() else {
+            return Ok(());
+        };
+        if self.sidebar_tree.is_descendant(id, dest_id) {
+            return Err("Cannot move into a descendant".to_string());
+        }
+        let Some(node) = self.sidebar_tree.node(id) else {
+            return Ok(());
+        };
+        let is_request = node.kind == NodeKind::Request;
+        if node.kind == NodeKind::Project {
+            return Err("Projects cannot be moved".to_string());
+        }
+        let old_path = self.item_path(id);
+        self.collection.move_item(id, dest_id)?;
+        self.save_collection();
+        let path = self.item_path(id);
+        self.record_change(ChangeKind::Moved, path, Some(old_path));
+        self.refresh_after_collection_change();
+        if is_request {
+            self.write_request_files(&[id])?;
+        }
+        self.sidebar.selection_id = Some(id);
+        Ok(())
+    }
+
+    fn open_move_popup(&mut self) {
+        let Some(selected) = self.sidebar_selected_id() else {
+            return;
+        };
+        if let Some(node) = self.sidebar_tree.node(selected) {
+            if node.kind == NodeKind::Project {
+                return;
+            }
+        }
+        let mut candidates = Vec::new();
+        for (id, node) in &self.sidebar_tree.nodes {
+            if *id == selected {
+                continue;
+            }
+            if node.kind == NodeKind::Request {
+                continue;
+            }
+            if self.sidebar_tree.is_descendant(selected, *id) {
+                continue;
+            }
+            candidates.push(*id);
+        }
+        candidates.sort_by(|a, b| {
+            let ap = self.sidebar_tree.path_for(*a).join("/");
+            let bp = self.sidebar_tree.path_for(*b).join("/");
+            ap.to_lowercase().cmp(&bp.to_lowercase())
+        });
+        if candidates.is_empty() {
+            return;
+        }
+        self.sidebar.popup = Some(SidebarPopup::Move { index: 0, candidates });
+    }
+
+    fn copy_selected_path(&mut self) {
+        let Some(id) = self.sidebar_selected_id() else {
+            return;
+        };
+        let path = self.sidebar_tree.path_for(id).join("/");
+        self.set_clipboard(path);
+        self.notify(Level::Info, "Copied path");
+    }
+
+    /// Copies `text` to the system clipboard and toasts what was copied.
+    fn copy_to_clipboard(&mut self, what: &str, text: String) {
+        let size = http::format_bytes(text.len());
+        self.set_clipboard(text);
+        self.notify(Level::Info, format!("Copied {} ({})", what, size));
+    }
+
+    /// Copies `text` to the system clipboard. Without one the copy still
+    /// lands in the yank ring, and goes out over OSC 52 when enabled.
+    fn set_clipboard(&mut self, text: String) {
+        if self.clipboard.set_text(text).is_err() {
+            self.report_clipboard_failure();
+        }
+    }
+
+    /// Tells the user once that the system clipboard can't be used;
+    /// later failures stay quiet.
+    fn report_clipboard_failure(&mut self) {
+        if !self.clipboard.take_failure_notice() {
+            return;
+        }
+        let msg = if self.clipboard.osc52() {
+            "System clipboard unavailable; copying over OSC 52 and to the yank ring (\" lists it)"
+        } else {
+            "System clipboard unavailable; yanks go to the yank ring (\" lists it)"
+        };
+        self.notify(Level::Warn, msg);
+    }
+
+    /// Opens the `"` popup of recent yanks. With `paste`, picking one
+    /// pastes it into the focused request editor; otherwise it is copied
+    /// again.
+    fn open_clipboard_ring(&mut self, paste: bool) {
+        if self.clipboard.ring().next().is_none() {
+            self.notify(Level::Info, "Nothing yanked yet");
+            return;
+        }
+        self.clipboard_ring = Some(ClipboardRingPopup { index: 0, paste });
+    }
+
+    /// Recent yanks, newest first.
+    pub fn recent_yanks(&self) -> Vec<&str> {
+        self.clipboard.ring().collect()
+    }
+
+    fn handle_clipboard_ring(&mut self, key: KeyEvent) {
+        let Some(mut popup) = self.clipboard_ring.take() else {
+            return;
+        };
+        let count = self.clipboard.ring().count();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('"') => return,
+            KeyCode::Down | KeyCode::Char('j') => {
+                popup.index = (popup.index + 1).min(count.saturating_sub(1));
+            }
+            KeyCode::Up | KeyCode::Char('k') => popup.index = popup.index.saturating_sub(1),
+            KeyCode::Enter => {
+                let Some(text) = self.clipboard.ring().nth(popup.index).map(str::to_string) else {
+                    return;
+                };
+                if popup.paste {
+                    if let Some(textarea) = self.active_request_editor() {
+                        textarea.set_yank_text(text.clone());
+                        textarea.paste();
+                        self.request_dirty = true;
+                        self.update_last_yank(YankTarget::Request, text.clone());
+                    }
+                }
+                // Picking a yank makes it the newest, pasted or not
+                self.set_clipboard(text);
+                if !popup.paste {
+                    self.notify(Level::Info, "Copied");
+                }
+                return;
+            }
+            _ => {}
+        }
+        self.clipboard_ring = Some(popup);
+    }
+
+    /// Opens the snippet picker over the body being typed in.
+    fn open_snippet_popup(&mut self) {
+        let path = storage::storage_dir().map(|dir| dir.join(snippets::SNIPPETS_FILE_NAME));
+        let (snippets, err) = snippets::load_snippets(path.as_deref());
+        if let Some(err) = err {
+            self.notify(Level::Warn, format!("Only built-in snippets: {}", err));
+        }
+        self.snippet_popup = Some(SnippetPopup {
+            snippets,
+            filter: TextInput::new(String::new()),
+            index: 0,
+        });
+    }
+
+    fn handle_snippet_popup(&mut self, key: KeyEvent) {
+        let Some(mut popup) = self.snippet_popup.take() else {
+            return;
+        };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let count = popup.matches().len();
+        match key.code {
+            KeyCode::Esc => return,
+            KeyCode::Down | KeyCode::Tab => {
+                popup.index = (popup.index + 1).min(count.saturating_sub(1));
+            }
+            KeyCode::Char('n') if ctrl => {
+                popup.index = (popup.index + 1).min(count.saturating_sub(1));
+            }
+            KeyCode::Up | KeyCode::BackTab => popup.index = popup.index.saturating_sub(1),
+            KeyCode::Char('p') if ctrl => popup.index = popup.index.saturating_sub(1),
+            KeyCode::Enter => {
+                let Some(snippet) = popup.matches().get(popup.index).copied() else {
+                    return;
+                };
+                let (text, stops) = snippets::expand(&snippet.body);
+                self.vim.insert_snippet(&mut self.request.body_editor, &text, &stops);
+                self.request_dirty = true;
+                self.mark_dirty(DirtyPanels::REQUEST);
+                return;
+            }
+            _ => {
+                handle_text_input(&mut popup.filter, key);
+                popup.index = 0;
+            }
+        }
+        self.snippet_popup = Some(popup);
+    }
+
+    fn yank_response_body(&mut self) {
+        let ResponseStatus::Success(ref data) = self.response else {
+            return;
+        };
+        if data.is_binary {
+            self.notify(Level::Warn, "Binary body can't be copied; press S to save it");
+            return;
+        }
+        let body = data.body.clone();
+        self.copy_to_clipboard("response body", body);
+    }
+
+    /// Copies the headers the Headers tab shows, in the order it shows
+    /// them.
+    fn yank_response_headers(&mut self) {
+        let ResponseStatus::Success(ref data) = self.response else {
+            return;
+        };
+        let shown = self.shown_headers(data);
+        let what = if shown.len() < data.headers.len() {
+            format!("{} of {} response headers (A: all)", shown.len(), data.headers.len())
+        } else {
+            "response headers".to_string()
+        };
+        let headers = shown
+            .iter()
+            .map(|&i| format!("{}: {}", data.headers[i].0, data.headers[i].1))
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.copy_to_clipboard(&what, headers);
+    }
+
+    /// Copies every response header as received, whatever is filtered.
+    fn yank_all_response_headers(&mut self) {
+        let ResponseStatus::Success(ref data) = self.response else {
+            return;
+        };
+        let headers = data
+            .headers
+            .iter()
+            .map(|(k, v)| format!("{}: {}", k, v))
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.copy_to_clipboard("all response headers", headers);
+    }
+
+    /// Indices of the response headers the Headers tab shows.
+    fn shown_headers(&self, data: &ResponseData) -> Vec<usize> {
+        let filter = match &self.header_filter_input {
+            Some(input) => &input.value,
+            None => &self.response_header_filter,
+        };
+        http::arrange_headers(&data.headers, self.response_header_sort, filter)
+    }
+
+    /// `/` on the Headers tab: rows are filtered as the text is typed.
+    /// Enter keeps the filter, Esc clears it.
+    fn handle_header_filter(&mut self, key: KeyEvent) {
+        let Some(input) = self.header_filter_input.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Enter => {
+                self.response_header_filter = input.value.clone();
+                self.header_filter_input = None;
+            }
+            KeyCode::Esc => {
+                self.response_header_filter.clear();
+                self.header_filter_input = None;
+            }
+            _ => handle_text_input(input, key),
+        }
+        self.response_headers_cache.scroll = 0;
+        self.load_response_editors();
+        self.mark_dirty(DirtyPanels::RESPONSE);
+    }
+
+    fn yank_json_value_at_cursor(&mut self) {
+        let lines = self.response_editor.lines().to_vec();
+        let (row, col) = self.response_editor.cursor();
+        let text = lines.join("\n");
+        let offset = json_path::cursor_offset(&lines, row, col);
+        match json_path::value_at(&text, offset) {
+            Some(found) => {
+                let value = found.extract(&text);
+                let path = json_path::format_path(&found.path);
+                self.copy_to_clipboard(&path, value);
+            }
+            None => self.notify(Level::Warn, "No JSON value under the cursor"),
+        }
+    }
+
+    /// Replaces the request body with the visual selection in the response
+    /// body and moves to it, with the old body left to undo. A body mode
+    /// without a text editor becomes JSON, or raw text when the selection
+    /// isn't JSON. Returns whether the body was replaced.
+    fn selection_to_body(&mut self) -> bool {
+        if self.refuse_locked_edit() {
+            return false;
+        }
+        let editor = &mut self.response_editor;
+        let Some((start, end)) = editor.selection_range() else {
+            return false;
+        };
+        let text = inclusive_selection(editor.lines(), start, end);
+        editor.cancel_selection();
+        self.vim = Vim::new(VimMode::Normal);
+        if text.is_empty() {
+            return false;
+        }
+
+        // Form and file bodies keep their fields; the mode switch is said
+        let previous = self.request.body_mode;
+        if !previous.is_text_mode() {
+            self.request.body_mode = if serde_json::from_str::<Value>(&text).is_ok() {
+                BodyMode::Json
+            } else {
+                BodyMode::Raw
+            };
+        }
+        let body = &mut self.request.body_editor;
+        body.select_all();
+        body.insert_str(&text);
+        body.move_cursor(CursorMove::Jump(0, 0));
+        self.request_tab = RequestTab::Body;
+        self.focus.panel = Panel::Request;
+        self.focus.request_field = RequestField::Body;
+        self.focus.body_field = BodyField::TextEditor;
+        self.request_dirty = true;
+        self.update_terminal_cursor();
+        self.mark_dirty(DirtyPanels::ALL);
+        if previous == self.request.body_mode {
+            self.notify(Level::Info, "Selection is now the request body");
+        } else {
+            self.notify(
+                Level::Warn,
+                format!(
+                    "Selection is now the request body; body mode {} \u{2192} {}",
+                    previous.as_str(),
+                    self.request.body_mode.as_str()
+                ),
+            );
+        }
+        true
+    }
+
+    fn sidebar_expand_or_open(&mut self) {
+        let Some(node) = self.sidebar_selected_node() else {
+            return;
+        };
+        let node_id = node.id;
+        match node.kind {
+            NodeKind::Request => {
+                if !self.sidebar.search_query.is_empty() {
+                    self.reveal_in_sidebar(node_id);
+                }
+                self.open_request(node_id);
+                self.app_mode = AppMode::Navigation;
+            }
+            NodeKind::Folder | NodeKind::Project => {
+                if self.sidebar.expanded.contains(&node_id) {
+                    self.sidebar.expanded.remove(&node_id);
+                } else {
+                    self.sidebar.expanded.insert(node_id);
+                }
+                self.mark_sidebar_dirty();
+            }
+        }
+    }
+
+    fn sidebar_collapse_or_parent(&mut self) {
+        let Some(node) = self.sidebar_selected_node() else {
+            return;
+        };
+        let node_id = node.id;
+        let node_kind = node.kind;
+        let node_parent = node.parent_id;
+        let is_expanded = self.sidebar.expanded.contains(&node_id);
+        match node_kind {
+            NodeKind::Request => {
+                if let Some(parent) = node_parent {
+                    self.sidebar.selection_id = Some(parent);
+                }
+            }
+            NodeKind::Folder | NodeKind::Project => {
+                if is_expanded {
+                    self.sidebar.expanded.remove(&node_id);
+                    self.mark_sidebar_dirty();
+                } else if let Some(parent) = node_parent {
+                    self.sidebar.selection_id = Some(parent);
+                }
+            }
+        }
+    }
+
+    fn collapse_all(&mut self) {
+        self.sidebar.expanded.clear();
+        self.mark_sidebar_dirty();
+    }
+
+    fn expand_all(&mut self) {
+        self.sidebar.expanded = self
+            .sidebar_tree
+            .nodes
+            .iter()
+            .filter_map(|(id, node)| {
+                if node.kind == NodeKind::Request {
+                    None
+                } else {
+                    Some(*id)
+                }
+            })
+            .collect();
+        self.mark_sidebar_dirty();
+    }
+
+    fn indent_selected(&mut self) {
+        let Some(selected) = self.sidebar_selected_id() else {
+            return;
+        };
+        let Some(node) = self.sidebar_tree.node(selected) else {
+            return;
+        };
+        let Some(parent_id) = node.parent_id else {
+            return;
+        };
+        let Some(parent) = self.sidebar_tree.node(parent_id) else {
+            return;
+        };
+        let siblings = &parent.children;
+        let index = siblings.iter().position(|id| *id == selected).unwrap_or(0);
+        if index == 0 {
+            return;
+        }
+        let candidate_id = siblings[index - 1];
+        if let Some(candidate) = self.sidebar_tree.node(candidate_id) {
+            if candidate.kind == NodeKind::Folder || candidate.kind == NodeKind::Project {
+                if let Err(err) = self.move_selected(candidate_id) {
+                    self.notify(Level::Error, err);
+                }
+            }
+        }
+    }
+
+    fn outdent_selected(&mut self) {
+        let Some(selected) = self.sidebar_selected_id() else {
+            return;
+        };
+        let Some(node) = self.sidebar_tree.node(selected) else {
+            return;
+        };
+        let Some(parent_id) = node.parent_id else {
+            return;
+        };
+        let Some(parent) = self.sidebar_tree.node(parent_id) else {
+            return;
+        };
+        let Some(grand_parent_id) = parent.parent_id else {
+            return;
+        };
+        if let Err(err) = self.move_selected(grand_parent_id) {
+            self.notify(Level::Error, err);
+        }
+    }
+
+    fn set_active_project(&mut self, project_id: Uuid) {
+        if self.active_project_id == project_id {
+            return;
+        }
+        self.save_current_request_if_dirty();
+        let left = ProjectSidebar::of(&self.sidebar);
+        self.project_sidebars.insert(self.active_project_id, left);
+        let restored = self.project_sidebars.remove(&project_id).unwrap_or_default();
+        self.active_project_id = project_id;
+        self.sidebar.expanded = restored.expanded;
+        // Falls back to the project root if the item is gone
+        self.sidebar.selection_id = restored.selection_id;
+        self.rebuild_sidebar_tree();
+        self.sidebar.search_query.clear();
+        self.mark_sidebar_search_dirty();
+        self.persist_ui_state();
+    }
+
+    /// Finds the requests of the active project that reference another
+    /// one of its requests by name.
+    fn refresh_chained_requests(&mut self) {
+        let Some(project) = self.collection.get_item(self.active_project_id) else {
+            self.chained_requests.clear();
+            return;
+        };
+        let requests = runner::run_order(project);
+        let names: HashSet<&str> = requests.iter().map(|item| item.name.as_str()).collect();
+        self.chained_requests = requests
+            .iter()
+            .filter(|item| {
+                item.request.as_ref().is_some_and(|request| {
+                    referenced_requests(request)
+                        .iter()
+                        .any(|name| *name != item.name && names.contains(name.as_str()))
+                })
+            })
+            .filter_map(|item| Uuid::parse_str(&item.id).ok())
+            .collect();
+    }
+
+    fn refresh_after_collection_change(&mut self) {
+        self.project_list = self.collection.list_projects();
+        self.rebuild_sidebar_tree();
+        self.persist_ui_state();
+    }
+
+    /// Refuses a change to the collection while another instance owns the
+    /// project. Returns whether it refused.
+    fn refuse_read_only(&mut self) -> bool {
+        if self.read_only {
+            self.notify(Level::Warn, self.read_only_notice());
+        }
+        self.read_only
+    }
+
+    pub fn read_only_notice(&self) -> String {
+        match self.lock_owner {
+            Some(owner) => format!(
+                "Read-only: another perseus ({}) has this project open",
+                owner.describe()
+            ),
+            None => "Read-only: another perseus has this project open".to_string(),
+        }
+    }
+
+    /// In a read-only instance: takes the lock once the owner has let go,
+    /// and otherwise re-reads the collection whenever the owner writes it.
+    fn check_project_lock(&mut self) {
+        if self.lock_prompt {
+            return;
+        }
+        let Some(dir) = storage::storage_dir() else {
+            return;
+        };
+        match storage::acquire_lock(&dir) {
+            Ok(LockStatus::Acquired(lock)) => {
+                self.reload_collection();
+                self.project_lock = Some(lock);
+                self.read_only = false;
+                self.lock_owner = None;
+                self.notify(Level::Info, "The other perseus closed; this project can be edited");
+                self.mark_dirty(DirtyPanels::ALL);
+                return;
+            }
+            Ok(LockStatus::Stale(owner)) if !self.stale_lock_declined => {
+                self.lock_owner = owner;
+                self.lock_prompt = true;
+            }
+            Ok(LockStatus::Held(owner)) => self.lock_owner = Some(owner),
+            Ok(LockStatus::Stale(_)) | Err(_) => {}
+        }
+        let modified = collection_modified();
+        if modified != self.collection_modified {
+            self.reload_collection();
+        }
+    }
+
+    /// In the owning instance: picks up a `collection.json` changed by
+    /// something else, such as a `git pull`, unless a save of ours is
+    /// pending and would overwrite it anyway.
+    fn check_collection_on_disk(&mut self) {
+        if self.save_status != SaveStatus::Saved {
+            return;
+        }
+        if collection_modified() != self.collection_modified {
+            self.reload_collection();
+        }
+    }
+
+    /// Re-reads `collection.json`, refreshing the sidebar and the open
+    /// request when the owner, or something outside perseus, changed them.
+    fn reload_collection(&mut self) {
+        self.collection_modified = collection_modified();
+        match CollectionStore::load_read_only() {
+            Ok(collection) => {
+                let before = self.collection.layout();
+                self.collection = collection;
+                let summary = storage::summarize_layout_change(&before, &self.collection.layout());
+                if let Some(summary) = summary.filter(|_| !self.read_only) {
+                    self.notify(Level::Info, format!("Collection changed on disk: {}", summary));
+                    self.record_change(ChangeKind::External, String::new(), Some(summary));
+                }
+            }
+            Err(err) => {
+                self.notify(Level::Warn, format!("Failed to re-read the collection: {}", err));
+                return;
+            }
+        }
+        self.refresh_after_collection_change();
+        if let Some(first) = self.project_list.first().map(|project| project.id) {
+            if !self.project_list.iter().any(|project| project.id == self.active_project_id) {
+                self.set_active_project(first);
+            }
+        }
+        let saved = self.current_request_id.and_then(|id| self.collection.get_item(id));
+        if let Some(item) = saved.filter(|_| !self.request_dirty) {
+            let settings = item.settings.clone();
+            if let Some(request) = item.request.clone() {
+                let shown = serde_json::to_string(&self.build_postman_request()).ok();
+                if shown != serde_json::to_string(&request).ok() {
+                    self.load_request_contents(&request);
+                }
+            }
+            self.request_settings = settings;
+        }
+        self.mark_sidebar_dirty();
+        self.mark_dirty(DirtyPanels::ALL);
+    }
+
+    /// The stale lock prompt: `t` takes the project over, anything else
+    /// stays read-only.
+    fn handle_lock_prompt(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('t') => {
+                self.lock_prompt = false;
+                let taken =
+                    storage::ensure_storage_dir().and_then(|dir| storage::take_over_lock(&dir));
+                match taken {
+                    Ok(lock) => {
+                        self.reload_collection();
+                        self.project_lock = Some(lock);
+                        self.read_only = false;
+                        self.lock_owner = None;
+                        self.notify(Level::Info, "Took over the project");
+                        // What the instance that left the lock didn't save
+                        self.recover_drafts();
+                    }
+                    Err(err) => self.notify(Level::Error, err),
+                }
+            }
+            KeyCode::Char('r') | KeyCode::Esc | KeyCode::Char('q') => {
+                self.lock_prompt = false;
+                self.stale_lock_declined = true;
+                self.notify(Level::Warn, self.read_only_notice());
+            }
+            _ => {}
+        }
+    }
+
+    fn add_parent_id(&self) -> Uuid {
+        if let Some(selected) = self.sidebar_selected_node() {
+            match selected.kind {
+                NodeKind::Request => selected.parent_id.unwrap_or(self.active_project_id),
+                NodeKind::Folder | NodeKind::Project => selected.id,
+            }
+        } else {
+            self.active_project_id
+        }
+    }
+
+    fn find_child_folder(&self, parent_id: Uuid, name: &str) -> Option<Uuid> {
+        let parent = self.sidebar_tree.node(parent_id)?;
+        for child in &parent.children {
+            if let Some(node) = self.sidebar_tree.node(*child) {
+                if (node.kind == NodeKind::Folder || node.kind == NodeKind::Project)
+                    && node.name == name
+                {
+                    return Some(*child);
+                }
+            }
+        }
+        None
+    }
+
+    fn active_yank_target(&self) -> Option<YankTarget> {
+        match self.focus.panel {
+            Panel::Response => match self.response_tab {
+                ResponseTab::Body => Some(YankTarget::ResponseBody),
+                ResponseTab::Headers => Some(YankTarget::ResponseHeaders),
+            },
+            Panel::Request => match self.focus.request_field {
+                RequestField::Url | RequestField::Headers | RequestField::Body => {
+                    Some(YankTarget::Request)
+                }
+                RequestField::Auth if self.is_auth_text_field() => Some(YankTarget::Request),
+                _ => None,
+            },
+            Panel::Sidebar => None,
+        }
+    }
+
+    fn update_last_yank(&mut self, target: YankTarget, text: String) {
+        match target {
+            YankTarget::Request => self.last_yank_request = text,
+            YankTarget::ResponseBody => self.last_yank_response = text,
+            YankTarget::ResponseHeaders => self.last_yank_response_headers = text,
+        }
+    }
+
+    fn sync_clipboard_from_active_yank(&mut self) {
+        let mut new_yank: Option<String> = None;
+        match self.focus.panel {
+            Panel::Response => match self.response_tab {
+                ResponseTab::Body => {
+                    let yank = self.response_editor.yank_text();
+                    if self.last_yank_response != yank {
+                        self.last_yank_response = yank.clone();
+                        new_yank = Some(yank);
+                    }
+                }
+                ResponseTab::Headers => {
+                    let yank = self.response_headers_editor.yank_text();
+                    if self.last_yank_response_headers != yank {
+                        self.last_yank_response_headers = yank.clone();
+                        new_yank = Some(yank);
+                    }
+                }
+            },
+            Panel::Request => {
+                let yank = self.active_request_editor().map(|ta| ta.yank_text());
+                if let Some(yank) = yank {
+                    if self.last_yank_request != yank {
+                        self.last_yank_request = yank.clone();
+                        new_yank = Some(yank);
+                    }
+                }
+            }
+            Panel::Sidebar => {}
+        }
+
+        if let Some(yank) = new_yank {
+            self.set_clipboard(yank);
+        }
+    }
+
+    fn handle_clipboard_paste_shortcut(&mut self) {
+        let target = match self.active_yank_target() {
+            Some(target) => target,
+            None => return,
+        };
+
+        let clipboard_text = match self.clipboard.get_text() {
+            Ok(text) => Some(text),
+            Err(_) => {
+                self.report_clipboard_failure();
+                None
+            }
+        };
+
+        let mut last_yank_update: Option<(YankTarget, String)> = None;
+        let mut exit_to_normal = false;
+        let vim_mode = self.vim.mode;
+
+        match target {
+            YankTarget::Request => {
+                if let Some(textarea) = self.active_request_editor() {
+                    if let Some(text) = clipboard_text.as_ref() {
+                        textarea.set_yank_text(text.clone());
+                        if vim_mode == VimMode::Insert {
+                            textarea.insert_str(text.as_str());
+                        } else {
+                            textarea.paste();
+                            if matches!(vim_mode, VimMode::Visual | VimMode::Operator(_)) {
+                                exit_to_normal = true;
+                            }
+                        }
+                        last_yank_update = Some((target, text.clone()));
+                    } else if vim_mode == VimMode::Insert {
+                        let fallback = textarea.yank_text();
+                        if !fallback.is_empty() {
+                            textarea.insert_str(fallback);
+                        }
+                    } else {
+                        textarea.paste();
+                        if matches!(vim_mode, VimMode::Visual | VimMode::Operator(_)) {
+                            exit_to_normal = true;
+                        }
+                    }
+                }
+            }
+            YankTarget::ResponseBody => {
+                let textarea = &mut self.response_editor;
+                if let Some(text) = clipboard_text.as_ref() {
+                    textarea.set_yank_text(text.clone());
+                    if self.vim.mode == VimMode::Insert {
+                        textarea.insert_str(text.as_str());
+                    } else {
+                        textarea.paste();
+                        if matches!(self.vim.mode, VimMode::Visual | VimMode::Operator(_)) {
+                            exit_to_normal = true;
+                        }
+                    }
+                    last_yank_update = Some((target, text.clone()));
+                } else if self.vim.mode == VimMode::Insert {
+                    let fallback = textarea.yank_text();
+                    if !fallback.is_empty() {
+                        textarea.insert_str(fallback);
+                    }
+                } else {
+                    textarea.paste();
+                    if matches!(self.vim.mode, VimMode::Visual | VimMode::Operator(_)) {
+                        exit_to_normal = true;
+                    }
+                }
+            }
+            YankTarget::ResponseHeaders => {
+                let textarea = &mut self.response_headers_editor;
+                if let Some(text) = clipboard_text.as_ref() {
+                    textarea.set_yank_text(text.clone());
+                    if self.vim.mode == VimMode::Insert {
+                        textarea.insert_str(text.as_str());
+                    } else {
+                        textarea.paste();
+                        if matches!(self.vim.mode, VimMode::Visual | VimMode::Operator(_)) {
+                            exit_to_normal = true;
+                        }
+                    }
+                    last_yank_update = Some((target, text.clone()));
+                } else if self.vim.mode == VimMode::Insert {
+                    let fallback = textarea.yank_text();
+                    if !fallback.is_empty() {
+                        textarea.insert_str(fallback);
+                    }
+                } else {
+                    textarea.paste();
+                    if matches!(self.vim.mode, VimMode::Visual | VimMode::Operator(_)) {
+                        exit_to_normal = true;
+                    }
+                }
+            }
+        }
+
+        if let Some((target, text)) = last_yank_update {
+            self.update_last_yank(target, text);
+        }
+
+        if exit_to_normal {
+            self.vim = Vim::new(VimMode::Normal);
+            self.update_terminal_cursor();
+        }
+    }
+
+    fn handle_clipboard_copy_shortcut(&mut self) {
+        let target = match self.active_yank_target() {
+            Some(target) => target,
+            None => return,
+        };
+
+        let mut yank: Option<String> = None;
+        let mut exit_visual = false;
+        let vim_mode = self.vim.mode;
+
+        match target {
+            YankTarget::Request => {
+                if let Some(textarea) = self.active_request_editor() {
+                    if textarea.is_selecting() {
+                        textarea.copy();
+                        yank = Some(textarea.yank_text());
+                        if vim_mode == VimMode::Visual {
+                            exit_visual = true;
+                        }
+                    }
+                }
+            }
+            YankTarget::ResponseBody => {
+                let textarea = &mut self.response_editor;
+                if textarea.is_selecting() {
+                    textarea.copy();
+                    yank = Some(textarea.yank_text());
+                    if self.vim.mode == VimMode::Visual {
+                        exit_visual = true;
+                    }
+                }
+            }
+            YankTarget::ResponseHeaders => {
+                let textarea = &mut self.response_headers_editor;
+                if textarea.is_selecting() {
+                    textarea.copy();
+                    yank = Some(textarea.yank_text());
+                    if self.vim.mode == VimMode::Visual {
+                        exit_visual = true;
+                    }
+                }
+            }
+        }
+
+        if let Some(text) = yank {
+            self.update_last_yank(target, text.clone());
+            self.set_clipboard(text);
+        }
+
+        if exit_visual {
+            self.vim = Vim::new(VimMode::Normal);
+            self.update_terminal_cursor();
+        }
+    }
+
+    fn install_panic_hook(&self) {
+        let original_hook = panic::take_hook();
+        // Released only if it is ours by then, so a read-only instance that
+        // never got the lock leaves it alone
+        let lock_path = storage::storage_dir().map(|dir| dir.join(storage::LOCK_FILE_NAME));
+        let flush = Arc::clone(&self.panic_flush);
+        panic::set_hook(Box::new(move |panic_info| {
+            // Best effort; the lock may be held by the code that panicked
+            if let Ok(mut flush) = flush.try_lock() {
+                flush.write();
+            }
+            if let Some(path) = lock_path.as_deref() {
+                storage::release_lock_at(path);
+            }
+            let _ = disable_raw_mode();
+            let _ = stdout().execute(LeaveAlternateScreen);
+            original_hook(panic_info);
+        }));
+    }
+
+    fn setup_terminal(&self) -> Result<()> {
+        enable_raw_mode()?;
+        stdout().execute(EnterAlternateScreen)?;
+        Ok(())
+    }
+
+    fn restore_terminal(&self) -> Result<()> {
+        disable_raw_mode()?;
+        stdout().execute(LeaveAlternateScreen)?;
+        Ok(())
+    }
+
+    pub fn prepare_editors(&mut self) {
+        let is_editing = self.app_mode == AppMode::Editing;
+        let focused_field = self.focus.request_field;
+        let in_request = self.focus.panel == Panel::Request;
+
+        let url_focused = in_request && focused_field == RequestField::Url;
+        let headers_focused = in_request && focused_field == RequestField::Headers;
+        let body_focused = in_request && focused_field == RequestField::Body;
+
+        let mut url_block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(self.theme.border(url_focused, Color::White));
+        if url_focused && self.theme.high_contrast {
+            url_block = url_block.title(self.theme.title("URL", true));
+        }
+        self.request.url_editor.set_block(url_block);
+        self.request
+            .headers_editor
+            .set_block(Block::default().borders(Borders::NONE));
+        self.request
+            .body_editor
+            .set_block(Block::default().borders(Borders::NONE));
+
+        // Set cursor style based on mode
+        let cursor_style = if is_editing && url_focused {
+            self.vim_cursor_style()
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        self.request.url_editor.set_cursor_style(cursor_style);
+
+        let cursor_style = if is_editing && headers_focused {
+            self.vim_cursor_style()
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        self.request.headers_editor.set_cursor_style(cursor_style);
+
+        // Body editors — prepare based on body mode and sub-field
+        let body_text_focused = body_focused
+            && self.focus.body_field == BodyField::TextEditor
+            && self.request.body_mode.is_text_mode();
+        let body_binary_focused = body_focused
+            && self.focus.body_field == BodyField::BinaryPath
+            && self.request.body_mode == BodyMode::Binary;
+
+        self.request
+            .body_editor
+            .set_block(Block::default().borders(Borders::NONE));
+        let cursor_style = if is_editing && body_text_focused {
+            self.vim_cursor_style()
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        self.request.body_editor.set_cursor_style(cursor_style);
+
+        self.request
+            .body_binary_path_editor
+            .set_block(Block::default().borders(Borders::NONE));
+        let cursor_style = if is_editing && body_binary_focused {
+            self.vim_cursor_style()
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        self.request
+            .body_binary_path_editor
+            .set_cursor_style(cursor_style);
+
+        // KV cell edit textarea — update cursor style when active
+        let kv_focused = (body_focused && self.focus.body_field == BodyField::KvRow)
+            || (headers_focused && self.headers_table);
+        let kv_cursor_style = if is_editing && kv_focused {
+            self.vim_cursor_style()
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        if let Some(ref mut kv_textarea) = self.kv_edit_textarea {
+            kv_textarea.set_cursor_style(kv_cursor_style);
+        }
+
+        // Auth editors — prepare only the ones relevant to current auth type
+        self.prepare_auth_editors();
+
+        // Response editor block/cursor
+        let response_editing = is_editing && self.focus.panel == Panel::Response;
+        self.response_editor.set_block(Block::default().borders(Borders::NONE));
+        self.response_headers_editor
+            .set_block(Block::default().borders(Borders::NONE));
+        let response_cursor = if response_editing {
+            self.vim_cursor_style()
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        self.response_editor.set_cursor_style(response_cursor);
+        self.response_headers_editor
+            .set_cursor_style(response_cursor);
+    }
+
+    fn prepare_auth_editors(&mut self) {
+        let is_editing = self.app_mode == AppMode::Editing;
+        let in_auth = self.focus.panel == Panel::Request
+            && self.focus.request_field == RequestField::Auth;
+        let auth_field = self.focus.auth_field;
+        let hidden_cursor = Style::default().fg(Color::DarkGray);
+        let vim_style = self.vim_cursor_style();
+
+        let cursor_for = |field: AuthField| -> Style {
+            if is_editing && in_auth && auth_field == field {
+                vim_style
+            } else {
+                hidden_cursor
+            }
+        };
+
+        let auth_block = Block::default().borders(Borders::NONE);
+
+        let secret_editors = [
+            (AuthField::Token, &mut self.request.auth_token_editor),
+            (AuthField::Password, &mut self.request.auth_password_editor),
+            (AuthField::KeyValue, &mut self.request.auth_key_value_editor),
+        ];
+        for (field, editor) in secret_editors {
+            let masked = match field {
+                AuthField::Token => self.privacy_mode,
+                _ => self.privacy_mode || !self.revealed_secrets.contains(&field),
+            };
+            if masked {
+                editor.set_mask_char(Self::SECRET_MASK_CHAR);
+            } else {
+                editor.clear_mask_char();
+            }
+        }
+
+        match self.request.auth_type {
+            AuthType::Bearer => {
+                self.request.auth_token_editor.set_block(auth_block);
+                self.request.auth_token_editor.set_cursor_style(cursor_for(AuthField::Token));
+            }
+            AuthType::Basic => {
+                self.request.auth_username_editor.set_block(auth_block.clone());
+                self.request.auth_username_editor.set_cursor_style(cursor_for(AuthField::Username));
+                self.request.auth_password_editor.set_block(auth_block);
+                self.request.auth_password_editor.set_cursor_style(cursor_for(AuthField::Password));
+            }
+            AuthType::ApiKey => {
+                self.request.auth_key_name_editor.set_block(auth_block.clone());
+                self.request.auth_key_name_editor.set_cursor_style(cursor_for(AuthField::KeyName));
+                self.request.auth_key_value_editor.set_block(auth_block);
+                self.request.auth_key_value_editor.set_cursor_style(cursor_for(AuthField::KeyValue));
+            }
+            AuthType::NoAuth => {}
+        }
+    }
+
+    /// The focused auth field, when it is one whose value can be revealed.
+    fn focused_secret_field(&self) -> Option<AuthField> {
+        let auth_focused = self.focus.panel == Panel::Request
+            && self.focus.request_field == RequestField::Auth
+            && matches!(self.app_mode, AppMode::Navigation | AppMode::Editing);
+        let field = self.focus.auth_field;
+        let revealable = match self.request.auth_type {
+            AuthType::Basic => field == AuthField::Password,
+            AuthType::ApiKey => field == AuthField::KeyValue,
+            AuthType::Bearer | AuthType::NoAuth => false,
+        };
+        (auth_focused && revealable).then_some(field)
+    }
+
+    fn toggle_secret_reveal(&mut self, field: AuthField) {
+        if !self.revealed_secrets.remove(&field) {
+            self.revealed_secrets.insert(field);
+        }
+        if self.privacy_mode {
+            self.notify(Level::Info, "Privacy mode is on; secrets stay masked (Ctrl+X)");
+        }
+    }
+
+    fn toggle_privacy_mode(&mut self) {
+        self.privacy_mode = !self.privacy_mode;
+        let msg = if self.privacy_mode {
+            "Privacy mode on: secrets are masked"
+        } else {
+            "Privacy mode off"
+        };
+        self.notify(Level::Info, msg);
+    }
+
+    fn toggle_offline(&mut self) {
+        self.offline = !self.offline;
+        let msg = if self.offline {
+            // Nothing left to poll for
+            self.poll = None;
+            "Offline: sends show the request's saved example instead (O goes back online)"
+        } else {
+            "Back online"
+        };
+        self.notify(Level::Info, msg);
+        self.mark_dirty(DirtyPanels::STATUS);
+    }
+
+    /// What a send does offline: shows the open request's saved example,
+    /// or an error when it has none.
+    fn answer_offline(&mut self) {
+        let example = self
+            .current_request_id
+            .filter(|_| self.scratch_message.is_none())
+            .and_then(|id| self.collection.get_item(id))
+            .and_then(PostmanItem::offline_example);
+        match example {
+            Some(example) => self.show_example(&example),
+            None => {
+                self.response = ResponseStatus::Error(http::RequestError::new(
+                    http::ErrorKind::Offline,
+                    "Not sent: offline mode is on and this request has no saved example",
+                ));
+                self.mark_dirty(DirtyPanels::ALL);
             }
         }
-        lines.sort_by_cached_key(|line| line.label.to_lowercase());
-        lines
     }
 
-    fn collect_sidebar_lines(
-        &self,
-        id: Uuid,
-        ancestors_last: &[bool],
-        is_last: bool,
-        is_root: bool,
-        out: &mut Vec<SidebarLine>,
-    ) {
-        if let Some(node) = self.sidebar_tree.node(id) {
-            let is_expanded = self.sidebar.expanded.contains(&id);
-            let marker = match node.kind {
-                NodeKind::Project | NodeKind::Folder => {
-                    if is_expanded { "▾" } else { "▸" }
-                }
-                NodeKind::Request => "•",
-            };
-            let method = if node.kind == NodeKind::Request {
-                node.request_method
-                    .as_deref()
-                    .map(Method::from_str)
-            } else {
-                None
-            };
-            let prefix = if is_root {
-                String::new()
+    fn vim_cursor_style(&self) -> Style {
+        match self.vim.mode {
+            VimMode::Normal => Style::default()
+                .fg(Color::Reset)
+                .add_modifier(Modifier::REVERSED),
+            VimMode::Insert => Style::default()
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            VimMode::Visual => Style::default()
+                .fg(Color::LightYellow)
+                .add_modifier(Modifier::REVERSED),
+            VimMode::Operator(_) => Style::default()
+                .fg(Color::LightGreen)
+                .add_modifier(Modifier::REVERSED),
+        }
+    }
+
+    /// Fills the read-only response body/headers editors from the current
+    /// response. Binary bodies show a summary or, with the hex view toggled
+    /// on, a hex dump.
+    /// Lays the shown response out again after the JSON style changed.
+    fn reformat_response(&mut self) {
+        self.load_response_editors();
+        self.mark_dirty(DirtyPanels::RESPONSE);
+    }
+
+    fn load_response_editors(&mut self) {
+        let ResponseStatus::Success(ref data) = self.response else {
+            return;
+        };
+        let mut lines: Vec<String> = if data.no_body.is_some() {
+            Vec::new()
+        } else if data.is_binary {
+            if self.response_hex_view {
+                ui::hex_dump_lines(&data.bytes, Self::HEX_VIEW_LIMIT)
             } else {
-                sidebar_tree_prefix(ancestors_last, is_last)
-            };
-            out.push(SidebarLine {
-                id,
-                prefix,
-                marker: marker.to_string(),
-                label: node.name.clone(),
-                kind: node.kind,
-                method,
-            });
-            if matches!(node.kind, NodeKind::Project | NodeKind::Folder) && is_expanded {
-                let mut next_ancestors = ancestors_last.to_vec();
-                if !is_root {
-                    next_ancestors.push(is_last);
-                }
-                for (index, child) in node.children.iter().enumerate() {
-                    let child_is_last = index + 1 == node.children.len();
-                    self.collect_sidebar_lines(
-                        *child,
-                        &next_ancestors,
-                        child_is_last,
-                        false,
-                        out,
-                    );
-                }
+                binary_summary_lines(data)
+            }
+        } else if self.response_preview && html::is_html(&data.headers) {
+            html::html_to_text(&data.body)
+                .lines()
+                .map(String::from)
+                .collect()
+        } else {
+            format_json_if_possible(&data.headers, &data.body, &self.json_style)
+                .lines()
+                .map(String::from)
+                .collect()
+        };
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        self.response_editor = TextArea::new(lines);
+        self.response_editor.set_cursor_line_style(Style::default());
+        let mut header_lines: Vec<String> = Vec::new();
+        if let Some(ref host_override) = self.sent_host_override {
+            header_lines.push(format!("# [hosts] override: {}", host_override));
+            header_lines.push(String::new());
+        }
+        let chain = redirect_chain_lines(data);
+        if !chain.is_empty() {
+            header_lines.extend(chain);
+            header_lines.push(String::new());
+        }
+        let all = http::header_lines(&data.headers, self.response_header_counts);
+        let shown = self.shown_headers(data);
+        // Sorted groups get a dim separator, once there's more than one
+        let groups: HashSet<http::HeaderGroup> = shown
+            .iter()
+            .map(|&i| http::HeaderGroup::of(&data.headers[i].0))
+            .collect();
+        let mut group = None;
+        for index in shown {
+            let this = http::HeaderGroup::of(&data.headers[index].0);
+            if self.response_header_sort && groups.len() > 1 && group != Some(this) {
+                header_lines.push(format!("{} {}", HEADER_GROUP_MARK, this.label()));
+                group = Some(this);
             }
+            header_lines.push(all[index].clone());
+        }
+        if header_lines.is_empty() {
+            header_lines.push(String::new());
         }
+        self.response_headers_editor = TextArea::new(header_lines);
+        self.response_headers_editor
+            .set_cursor_line_style(Style::default());
+        self.last_yank_response = self.response_editor.yank_text();
+        self.last_yank_response_headers = self.response_headers_editor.yank_text();
+        self.response_body_cache.dirty = true;
+        self.response_headers_cache.dirty = true;
     }
 
-    fn sidebar_selected_index(selected: Option<Uuid>, lines: &[SidebarLine]) -> usize {
-        let Some(selected) = selected else {
-            return 0;
+    fn toggle_hex_view(&mut self) {
+        match &self.response {
+            ResponseStatus::Success(data) if data.is_binary => {
+                self.response_hex_view = !self.response_hex_view;
+                self.response_tab = ResponseTab::Body;
+                self.load_response_editors();
+            }
+            ResponseStatus::Success(_) => {
+                self.notify(Level::Warn, "Hex view is only available for binary responses")
+            }
+            _ => {}
+        }
+    }
+
+    /// Columns moved per `h`/`l` press when the response doesn't wrap.
+    const HSCROLL_STEP: u16 = 8;
+
+    fn toggle_response_wrap(&mut self) {
+        self.response_wrap = !self.response_wrap;
+        self.response_hscroll = 0;
+        self.mark_dirty(DirtyPanels::RESPONSE);
+        self.persist_session_state();
+    }
+
+    /// Leaves the body or auth of the shown tab out of sends, or puts it
+    /// back, keeping what was typed.
+    fn toggle_section_enabled(&mut self) {
+        if self.refuse_locked_edit() {
+            return;
+        }
+        let (disabled, section) = match self.request_tab {
+            RequestTab::Auth => (&mut self.request.auth_disabled, "Auth"),
+            RequestTab::Body => (&mut self.request.body_disabled, "Body"),
+            RequestTab::Headers => return,
         };
-        lines
-            .iter()
-            .position(|line| line.id == selected)
-            .unwrap_or(0)
+        *disabled = !*disabled;
+        let msg = if *disabled {
+            format!("{} is left out of sends (Ctrl+Space turns it back on)", section)
+        } else {
+            format!("{} is sent again", section)
+        };
+        self.request_dirty = true;
+        self.mark_dirty(DirtyPanels::REQUEST);
+        self.notify(Level::Info, msg);
     }
 
-    fn sidebar_move_selection(&mut self, delta: i32) {
-        let selected = self.sidebar.selection_id;
-        let lines = self.sidebar_lines();
-        if lines.is_empty() {
+    fn toggle_body_wrap(&mut self) {
+        self.body_wrap = !self.body_wrap;
+        self.body_wrap_cache.scroll = 0;
+        self.mark_dirty(DirtyPanels::REQUEST);
+        self.persist_session_state();
+    }
+
+    /// Suggests wrapping or formatting once per session when the body has
+    /// a line too long to read without scrolling sideways.
+    fn hint_long_body_line(&mut self) {
+        if self.body_wrap || self.body_line_hinted || !self.request.body_mode.is_text_mode() {
             return;
         }
-        let mut index = Self::sidebar_selected_index(selected, lines) as i32;
-        index = (index + delta).clamp(0, (lines.len() - 1) as i32);
-        let next_id = lines[index as usize].id;
-        self.sidebar.selection_id = Some(next_id);
+        let longest = self
+            .request
+            .body_editor
+            .lines()
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        if longest > LONG_BODY_LINE {
+            self.body_line_hinted = true;
+            self.notify(
+                Level::Info,
+                format!("Body has a {}-character line: w wraps it, F formats JSON", longest),
+            );
+        }
     }
 
-    fn sidebar_selected_node(&self) -> Option<&TreeNode> {
-        self.sidebar
-            .selection_id
-            .and_then(|id| self.sidebar_tree.node(id))
+    fn reset_response_scroll(&mut self) {
+        self.response_body_cache.scroll = 0;
+        self.response_headers_cache.scroll = 0;
+        self.response_hscroll = 0;
     }
 
-    fn sidebar_selected_id(&self) -> Option<Uuid> {
-        self.sidebar.selection_id
+    /// The shown tab's scroll offset and how many rows it has, wrapped as
+    /// at the last draw.
+    fn response_scroll_rows(&self) -> (usize, usize) {
+        let (scroll, lines, wrap_cache) = match self.response_tab {
+            ResponseTab::Body => {
+                let cache = &self.response_body_cache;
+                (cache.scroll, &cache.lines, &cache.wrap_cache)
+            }
+            ResponseTab::Headers => {
+                let cache = &self.response_headers_cache;
+                (cache.scroll, &cache.lines, &cache.wrap_cache)
+            }
+        };
+        let rows = if self.response_wrap { wrap_cache.wrapped_lines.len() } else { lines.len() };
+        (scroll, rows)
     }
 
-    fn save_current_request_if_dirty(&mut self) {
-        if !self.request_dirty {
-            return;
+    /// Scrolls the shown response tab by `pages`, stopping with the last
+    /// row at the bottom of the panel.
+    fn scroll_response_pages(&mut self, pages: f32) {
+        let height = self.response_view_height as usize;
+        let delta = (height as f32 * pages) as isize;
+        let (scroll, rows) = self.response_scroll_rows();
+        let next = scroll.saturating_add_signed(delta).min(rows.saturating_sub(height));
+        if next != scroll {
+            match self.response_tab {
+                ResponseTab::Body => self.response_body_cache.scroll = next,
+                ResponseTab::Headers => self.response_headers_cache.scroll = next,
+            }
+            self.mark_dirty(DirtyPanels::RESPONSE);
         }
-        let Some(request_id) = self.current_request_id else {
-            return;
+    }
+
+    /// How far down the shown response tab is scrolled, when it doesn't fit
+    /// the panel.
+    pub(crate) fn response_scroll_percent(&self) -> Option<usize> {
+        let (scroll, rows) = self.response_scroll_rows();
+        let max = rows.saturating_sub(self.response_view_height as usize);
+        (max > 0).then(|| scroll.min(max) * 100 / max)
+    }
+
+    /// Moves the unwrapped response `delta` columns, stopping once the end
+    /// of the longest line is in view.
+    fn scroll_response_horizontally(&mut self, delta: i32) {
+        let longest = match self.response_tab {
+            ResponseTab::Body => self.response_body_cache.max_line_width,
+            ResponseTab::Headers => self.response_headers_cache.max_line_width,
         };
-        if let Err(err) = self.save_request_by_id(request_id) {
-            self.response = ResponseStatus::Error(err);
+        let max = longest.saturating_sub(self.response_view_width as usize);
+        let max = max.min(u16::MAX as usize) as u16;
+        let column = (self.response_hscroll as i32 + delta).clamp(0, max as i32) as u16;
+        if column != self.response_hscroll {
+            self.response_hscroll = column;
+            self.mark_dirty(DirtyPanels::RESPONSE);
+        }
+    }
+
+    fn toggle_html_preview(&mut self) {
+        match &self.response {
+            ResponseStatus::Success(data) if !data.is_binary && html::is_html(&data.headers) => {
+                self.response_preview = !self.response_preview;
+                self.response_tab = ResponseTab::Body;
+                self.load_response_editors();
+            }
+            ResponseStatus::Success(_) => {
+                self.notify(Level::Warn, "Preview is only available for HTML responses")
+            }
+            _ => {}
+        }
+    }
+
+    /// Final URL of the current response when redirects were followed.
+    pub fn redirected_url(&self) -> Option<&str> {
+        match &self.response {
+            ResponseStatus::Success(data) if !data.redirects.is_empty() => {
+                Some(data.final_url.as_str())
+            }
+            _ => None,
+        }
+    }
+
+    /// Label for the alternate Body rendering currently in effect, if any.
+    pub fn response_body_mode(&self) -> Option<&'static str> {
+        let ResponseStatus::Success(ref data) = self.response else {
+            return None;
+        };
+        if data.is_binary && self.response_hex_view {
+            Some("hex")
+        } else if self.response_preview && !data.is_binary && html::is_html(&data.headers) {
+            Some("preview")
         } else {
-            self.request_dirty = false;
+            None
         }
     }
 
-    fn save_request_by_id(&mut self, request_id: Uuid) -> Result<(), String> {
-        let request = self.build_postman_request();
-        self.collection.update_request(request_id, request)?;
-        self.collection.save()?;
-        if let Some(parent_id) = self
-            .sidebar_tree
-            .node(request_id)
-            .and_then(|node| node.parent_id)
-        {
-            self.collection
-                .save_request_file(request_id, parent_id, self.active_project_id)?;
+    fn open_save_response_popup(&mut self) {
+        if let ResponseStatus::Success(ref data) = self.response {
+            let name = format!("response.{}", response_file_extension(data));
+            self.save_response_popup = Some(TextInput::new(name));
         }
-        Ok(())
     }
 
-    fn write_request_files(&self, request_ids: &[Uuid]) -> Result<(), String> {
-        for request_id in request_ids {
-            let parent_id = self
-                .sidebar_tree
-                .node(*request_id)
-                .and_then(|node| node.parent_id)
-                .ok_or("Request parent not found")?;
-            self.collection
-                .save_request_file(*request_id, parent_id, self.active_project_id)?;
+    fn open_example_name_popup(&mut self) {
+        match &self.response {
+            ResponseStatus::Success(data) if data.is_binary => {
+                self.notify(Level::Warn, "Binary responses can't be saved as examples")
+            }
+            ResponseStatus::Success(data) if self.sent_request_id.is_some() => {
+                let name = format!("{} {}", data.status, data.status_text).trim().to_string();
+                self.example_popup = Some(ExamplePopup::Name(TextInput::new(name)));
+            }
+            _ => {}
         }
-        Ok(())
     }
 
-    fn delete_request_files(&self, request_ids: &[Uuid]) -> Result<(), String> {
-        for request_id in request_ids {
-            self.collection.delete_request_file(*request_id)?;
+    fn open_example_list(&mut self) {
+        let count = self.current_examples().len();
+        if count == 0 {
+            self.notify(Level::Info, "This request has no saved examples (E saves one)");
+            return;
         }
-        Ok(())
+        self.example_popup = Some(ExamplePopup::List { index: 0 });
     }
 
-    fn build_postman_request(&self) -> PostmanRequest {
-        let method = self.request.method.as_str().to_string();
-        let url = self.request.url_text();
-        let headers = storage::parse_headers(&self.request.headers_text());
+    /// Examples saved on the open request, with their index in its
+    /// `response` list.
+    pub fn current_examples(&self) -> Vec<(usize, storage::PostmanExample)> {
+        self.current_request_id
+            .and_then(|id| self.collection.get_item(id))
+            .map(PostmanItem::examples)
+            .unwrap_or_default()
+    }
 
-        let body = match self.request.body_mode {
-            BodyMode::Raw => {
-                let text = self.request.body_text();
-                if text.trim().is_empty() {
-                    None
-                } else {
-                    Some(storage::PostmanBody::raw(&text))
+    fn handle_example_popup(&mut self, key: KeyEvent) {
+        let Some(popup) = self.example_popup.take() else {
+            return;
+        };
+        match popup {
+            ExamplePopup::Name(mut input) => match key.code {
+                KeyCode::Esc => {}
+                KeyCode::Enter => {
+                    let name = input.value.trim().to_string();
+                    if name.is_empty() {
+                        self.example_popup = Some(ExamplePopup::Name(input));
+                        return;
+                    }
+                    if let Err(err) = self.save_response_example(name) {
+                        self.notify(Level::Error, err);
+                    }
                 }
-            }
-            BodyMode::Json => {
-                let text = self.request.body_text();
-                if text.trim().is_empty() {
-                    None
-                } else {
-                    Some(storage::PostmanBody::json(&text))
+                _ => {
+                    handle_text_input(&mut input, key);
+                    self.example_popup = Some(ExamplePopup::Name(input));
+                }
+            },
+            ExamplePopup::List { mut index } => {
+                let examples = self.current_examples();
+                match key.code {
+                    KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('X') => return,
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        index = (index + 1).min(examples.len().saturating_sub(1));
+                    }
+                    KeyCode::Up | KeyCode::Char('k') => index = index.saturating_sub(1),
+                    KeyCode::Enter => {
+                        if let Some((_, example)) = examples.get(index) {
+                            self.show_example(example);
+                        }
+                        return;
+                    }
+                    KeyCode::Char('d') => {
+                        if let Some((position, example)) = examples.get(index) {
+                            match self.delete_example(*position) {
+                                Ok(()) => {
+                                    let msg = format!("Deleted example '{}'", example.name);
+                                    self.notify(Level::Info, msg);
+                                }
+                                Err(err) => self.notify(Level::Error, err),
+                            }
+                        }
+                        let remaining = examples.len().saturating_sub(1);
+                        if remaining == 0 {
+                            return;
+                        }
+                        index = index.min(remaining - 1);
+                    }
+                    _ => {}
                 }
+                self.example_popup = Some(ExamplePopup::List { index });
+            }
+        }
+    }
+
+    /// Appends the current response to its request's examples, as sent:
+    /// the request snapshot is the editor when it is still open, the saved
+    /// request otherwise.
+    fn save_response_example(&mut self, name: String) -> Result<(), String> {
+        let ResponseStatus::Success(ref data) = self.response else {
+            return Ok(());
+        };
+        let request_id = self.sent_request_id.ok_or("No request to attach the example to")?;
+        let original = if self.current_request_id == Some(request_id) {
+            self.build_postman_request()
+        } else {
+            self.collection
+                .get_item(request_id)
+                .and_then(|item| item.request.clone())
+                .ok_or("The request of this response no longer exists")?
+        };
+        let example = storage::PostmanExample::new(
+            name,
+            data.status,
+            data.status_text.clone(),
+            &data.headers,
+            &data.body,
+            original,
+        );
+        let value = serde_json::to_value(&example)
+            .map_err(|e| format!("Failed to serialize example: {}", e))?;
+        self.collection
+            .get_item_mut(request_id)
+            .ok_or("The request of this response no longer exists")?
+            .response
+            .push(value);
+        self.save_collection();
+        self.write_request_files(&[request_id])?;
+        let msg = if example.truncated {
+            format!(
+                "Saved example '{}' (body cut to {})",
+                example.name,
+                http::format_bytes(storage::PostmanExample::MAX_BODY)
+            )
+        } else {
+            format!("Saved example '{}'", example.name)
+        };
+        self.notify(Level::Info, msg);
+        Ok(())
+    }
+
+    fn delete_example(&mut self, position: usize) -> Result<(), String> {
+        let request_id = self.current_request_id.ok_or("No request is open")?;
+        let item = self.collection.get_item_mut(request_id).ok_or("Request not found")?;
+        if position < item.response.len() {
+            item.response.remove(position);
+        }
+        self.save_collection();
+        self.write_request_files(&[request_id])
+    }
+
+    /// Shows a saved example in the response panel as if it had just come
+    /// back.
+    fn show_example(&mut self, example: &storage::PostmanExample) {
+        let bytes = example.body.as_bytes().to_vec();
+        let headers: Vec<(String, String)> = example
+            .header
+            .iter()
+            .map(|header| (header.key.clone(), header.value.clone()))
+            .collect();
+        let no_body = http::NoBody::detect(false, example.code, &headers);
+        self.response = ResponseStatus::Success(ResponseData {
+            status: example.code,
+            status_text: example.status.clone(),
+            headers,
+            body: example.body.clone(),
+            duration_ms: 0,
+            content_encoding: None,
+            raw_size: bytes.len(),
+            decoded_size: bytes.len(),
+            bytes,
+            is_binary: false,
+            redirects: Vec::new(),
+            final_url: String::new(),
+            partial: false,
+            charset: Charset::Utf8,
+            lossy: false,
+            compression: None,
+            normalized: http::Normalized::default(),
+            no_body,
+            version: None,
+            example: true,
+        });
+        self.reset_response_scroll();
+        self.response_tab = ResponseTab::Body;
+        self.response_hex_view = false;
+        self.load_response_editors();
+        self.mark_dirty(DirtyPanels::ALL);
+        let msg = if example.truncated {
+            format!("Showing example '{}' (body was cut when saved)", example.name)
+        } else {
+            format!("Showing example '{}'", example.name)
+        };
+        self.notify(Level::Info, msg);
+    }
+
+    fn handle_env_popup(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                let count = self.environments.len() + 1; // +1 for "No Environment"
+                self.env_popup_index = (self.env_popup_index + 1) % count;
             }
-            BodyMode::Xml => {
-                let text = self.request.body_text();
-                if text.trim().is_empty() {
-                    None
-                } else {
-                    Some(storage::PostmanBody::xml(&text))
-                }
+            KeyCode::Char('k') | KeyCode::Up => {
+                let count = self.environments.len() + 1;
+                self.env_popup_index =
+                    (self.env_popup_index + count - 1) % count;
             }
-            BodyMode::FormUrlEncoded => {
-                let pairs: Vec<storage::PostmanKvPair> = self
-                    .request
-                    .body_form_pairs
-                    .iter()
-                    .filter(|p| !(p.key.is_empty() && p.value.is_empty()))
-                    .map(|p| storage::PostmanKvPair {
-                        key: p.key.clone(),
-                        value: p.value.clone(),
-                        disabled: if p.enabled { None } else { Some(true) },
-                    })
-                    .collect();
-                if pairs.is_empty() {
+            KeyCode::Enter => {
+                self.active_environment_name = if self.env_popup_index == 0 {
                     None
                 } else {
-                    Some(storage::PostmanBody::urlencoded(pairs))
+                    Some(self.environments[self.env_popup_index - 1].name.clone())
+                };
+                self.show_env_popup = false;
+                if let Err(err) = self.active_variables() {
+                    self.notify(Level::Warn, err);
                 }
             }
-            BodyMode::Multipart => {
-                let params: Vec<storage::PostmanFormParam> = self
-                    .request
-                    .body_multipart_fields
-                    .iter()
-                    .filter(|f| !f.key.is_empty())
-                    .map(|f| storage::PostmanFormParam {
-                        key: f.key.clone(),
-                        value: if f.field_type == MultipartFieldType::Text {
-                            Some(f.value.clone())
-                        } else {
-                            None
-                        },
-                        src: if f.field_type == MultipartFieldType::File {
-                            Some(f.value.clone())
-                        } else {
-                            None
-                        },
-                        param_type: match f.field_type {
-                            MultipartFieldType::Text => "text".to_string(),
-                            MultipartFieldType::File => "file".to_string(),
-                        },
-                        disabled: if f.enabled { None } else { Some(true) },
-                    })
-                    .collect();
-                if params.is_empty() {
-                    None
-                } else {
-                    Some(storage::PostmanBody::formdata(params))
-                }
+            KeyCode::Char('i') => {
+                self.env_file_prompt = Some(EnvFilePrompt::Import {
+                    input: TextInput::new(String::new()),
+                    scope: EnvScope::Project,
+                });
             }
-            BodyMode::Binary => {
-                let path = self.request.body_binary_path_text();
-                if path.trim().is_empty() {
-                    None
-                } else {
-                    Some(storage::PostmanBody::file(&path))
+            KeyCode::Char('u') => self.open_usages(),
+            KeyCode::Char('v') => match self.env_popup_index.checked_sub(1) {
+                Some(index) => {
+                    let name = self.environments[index].name.clone();
+                    self.env_variables = Some(EnvVariablesPopup { name, index: 0 });
                 }
+                None => self.notify(Level::Warn, "Select an environment to list its variables"),
+            },
+            KeyCode::Char('x') => match self.env_popup_index.checked_sub(1) {
+                Some(index) => {
+                    let name = self.environments[index].name.clone();
+                    let input = TextInput::new(format!("{}.postman_environment.json", name));
+                    self.env_file_prompt = Some(EnvFilePrompt::Export { name, input });
+                }
+                None => self.notify(Level::Warn, "Select an environment to export"),
+            },
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.show_env_popup = false;
             }
-        };
-
-        let auth = match self.request.auth_type {
-            AuthType::NoAuth => None,
-            AuthType::Bearer => {
-                Some(storage::PostmanAuth::bearer(&self.request.auth_token_text()))
-            }
-            AuthType::Basic => Some(storage::PostmanAuth::basic(
-                &self.request.auth_username_text(),
-                &self.request.auth_password_text(),
-            )),
-            AuthType::ApiKey => Some(storage::PostmanAuth::apikey(
-                &self.request.auth_key_name_text(),
-                &self.request.auth_key_value_text(),
-                if self.request.api_key_location == ApiKeyLocation::Header {
-                    "header"
-                } else {
-                    "query"
-                },
-            )),
-        };
-
-        let mut req = PostmanRequest::new(method, url, headers, None);
-        req.body = body;
-        req.auth = auth;
-        req
-    }
-
-    fn open_request(&mut self, request_id: Uuid) {
-        self.save_current_request_if_dirty();
-        let request_data = self
-            .collection
-            .get_item(request_id)
-            .and_then(|item| item.request.clone());
-        if let Some(request) = request_data {
-            let method = Method::from_str(&request.method);
-            let url = extract_url(&request.url);
-            let headers = headers_to_text(&request.header);
-            let raw_body = request
-                .body
-                .as_ref()
-                .and_then(|b| b.raw.clone())
-                .unwrap_or_default();
-            self.request.set_contents(method, url, headers, raw_body);
-            self.load_body_mode_from_postman(&request);
-            self.load_auth_from_postman(&request);
-            self.apply_editor_tab_size();
-            self.current_request_id = Some(request_id);
-            self.request_dirty = false;
-            self.kv_edit_textarea = None;
-            self.focus.panel = Panel::Request;
-            self.focus.request_field = RequestField::Url;
-            self.focus.body_field = BodyField::ModeSelector;
-            self.focus.kv_focus = KvFocus::default();
+            _ => {}
         }
     }
 
-    fn load_body_mode_from_postman(&mut self, request: &PostmanRequest) {
-        if let Some(body) = &request.body {
-            match body.mode.as_str() {
-                "raw" => {
-                    let language = body
-                        .options
-                        .as_ref()
-                        .and_then(|o| o.raw.as_ref())
-                        .map(|r| r.language.as_str());
-                    self.request.body_mode = match language {
-                        Some("json") => BodyMode::Json,
-                        Some("xml") => BodyMode::Xml,
-                        _ => BodyMode::Raw,
+    fn handle_env_file_prompt(&mut self, key: KeyEvent) {
+        let Some(prompt) = self.env_file_prompt.take() else {
+            return;
+        };
+        match prompt {
+            EnvFilePrompt::Import { mut input, scope } => match key.code {
+                KeyCode::Esc => {}
+                KeyCode::Tab => {
+                    let scope = match scope {
+                        EnvScope::User => EnvScope::Project,
+                        _ => EnvScope::User,
                     };
-                    // Raw text is already loaded by set_contents above
+                    self.env_file_prompt = Some(EnvFilePrompt::Import { input, scope });
                 }
-                "urlencoded" => {
-                    self.request.body_mode = BodyMode::FormUrlEncoded;
-                    if let Some(pairs) = &body.urlencoded {
-                        self.request.body_form_pairs = pairs
-                            .iter()
-                            .map(|p| KvPair {
-                                key: p.key.clone(),
-                                value: p.value.clone(),
-                                enabled: !p.disabled.unwrap_or(false),
-                            })
-                            .collect();
-                    }
-                    if self.request.body_form_pairs.is_empty()
-                        || self
-                            .request
-                            .body_form_pairs
-                            .last()
-                            .map(|p| !p.key.is_empty() || !p.value.is_empty())
-                            .unwrap_or(true)
-                    {
-                        self.request.body_form_pairs.push(KvPair::new_empty());
+                KeyCode::Enter if !input.value.trim().is_empty() => {
+                    let path = config::expand_tilde(std::path::Path::new(input.value.trim()));
+                    match environment::import_postman_environment(&path) {
+                        Ok(env) if environment::environment_exists(&env.name, scope) => {
+                            self.env_file_prompt =
+                                Some(EnvFilePrompt::Conflict { env, scope, rename: None });
+                        }
+                        Ok(env) => self.store_imported_environment(env, scope),
+                        Err(err) => {
+                            self.notify(Level::Error, err);
+                            self.env_file_prompt = Some(EnvFilePrompt::Import { input, scope });
+                        }
                     }
                 }
-                "formdata" => {
-                    self.request.body_mode = BodyMode::Multipart;
-                    if let Some(params) = &body.formdata {
-                        self.request.body_multipart_fields = params
-                            .iter()
-                            .map(|p| MultipartField {
-                                key: p.key.clone(),
-                                value: match p.param_type.as_str() {
-                                    "file" => p.src.clone().unwrap_or_default(),
-                                    _ => p.value.clone().unwrap_or_default(),
-                                },
-                                field_type: match p.param_type.as_str() {
-                                    "file" => MultipartFieldType::File,
-                                    _ => MultipartFieldType::Text,
-                                },
-                                enabled: !p.disabled.unwrap_or(false),
-                            })
-                            .collect();
-                    }
-                    if self.request.body_multipart_fields.is_empty()
-                        || self
-                            .request
-                            .body_multipart_fields
-                            .last()
-                            .map(|f| !f.key.is_empty())
-                            .unwrap_or(true)
-                    {
-                        self.request
-                            .body_multipart_fields
-                            .push(MultipartField::new_empty());
-                    }
+                _ => {
+                    handle_text_input(&mut input, key);
+                    self.env_file_prompt = Some(EnvFilePrompt::Import { input, scope });
                 }
-                "file" => {
-                    self.request.body_mode = BodyMode::Binary;
-                    if let Some(file_ref) = &body.file {
-                        if let Some(src) = &file_ref.src {
-                            self.request.body_binary_path_editor =
-                                TextArea::new(vec![src.clone()]);
-                            configure_editor(
-                                &mut self.request.body_binary_path_editor,
-                                "File path...",
-                            );
+            },
+            EnvFilePrompt::Export { name, mut input } => match key.code {
+                KeyCode::Esc => {}
+                KeyCode::Enter if !input.value.trim().is_empty() => {
+                    let path = config::expand_tilde(std::path::Path::new(input.value.trim()));
+                    let result = match self.environments.iter().find(|e| e.name == name) {
+                        Some(env) => {
+                            environment::export_postman_environment(env, &self.environments, &path)
+                        }
+                        None => Err(format!("Environment '{}' no longer exists", name)),
+                    };
+                    match result {
+                        Ok(()) => {
+                            let msg = format!("Exported '{}' to {}", name, path.display());
+                            self.notify(Level::Info, msg);
+                        }
+                        Err(err) => {
+                            self.notify(Level::Error, err);
+                            self.env_file_prompt = Some(EnvFilePrompt::Export { name, input });
                         }
                     }
                 }
                 _ => {
-                    self.request.body_mode = BodyMode::Raw;
+                    handle_text_input(&mut input, key);
+                    self.env_file_prompt = Some(EnvFilePrompt::Export { name, input });
                 }
-            }
-        } else {
-            self.request.body_mode = BodyMode::Raw;
+            },
+            EnvFilePrompt::Conflict {
+                env,
+                scope,
+                rename: None,
+            } => match key.code {
+                KeyCode::Char('o') => self.store_imported_environment(env, scope),
+                KeyCode::Char('r') => {
+                    let input = TextInput::new(free_environment_name(&env.name, scope));
+                    self.env_file_prompt = Some(EnvFilePrompt::Conflict {
+                        env,
+                        scope,
+                        rename: Some(input),
+                    });
+                }
+                KeyCode::Esc | KeyCode::Char('n') => {}
+                _ => {
+                    self.env_file_prompt = Some(EnvFilePrompt::Conflict {
+                        env,
+                        scope,
+                        rename: None,
+                    });
+                }
+            },
+            EnvFilePrompt::Conflict {
+                mut env,
+                scope,
+                rename: Some(mut input),
+            } => match key.code {
+                KeyCode::Esc => {
+                    self.env_file_prompt = Some(EnvFilePrompt::Conflict {
+                        env,
+                        scope,
+                        rename: None,
+                    });
+                }
+                KeyCode::Enter => {
+                    let name = input.value.trim().to_string();
+                    if environment::environment_exists(&name, scope) {
+                        self.notify(
+                            Level::Warn,
+                            format!("An environment named '{}' already exists", name),
+                        );
+                        self.env_file_prompt = Some(EnvFilePrompt::Conflict {
+                            env,
+                            scope,
+                            rename: Some(input),
+                        });
+                    } else {
+                        env.name = name;
+                        self.store_imported_environment(env, scope);
+                    }
+                }
+                _ => {
+                    handle_text_input(&mut input, key);
+                    self.env_file_prompt = Some(EnvFilePrompt::Conflict {
+                        env,
+                        scope,
+                        rename: Some(input),
+                    });
+                }
+            },
         }
     }
 
-    fn load_auth_from_postman(&mut self, request: &PostmanRequest) {
-        if let Some(auth) = &request.auth {
-            match auth.auth_type.as_str() {
-                "bearer" => {
-                    self.request.auth_type = AuthType::Bearer;
-                    if let Some(token) = auth.get_bearer_token() {
-                        self.request.auth_token_editor =
-                            TextArea::new(vec![token.to_string()]);
-                        configure_editor(&mut self.request.auth_token_editor, "Token");
-                    }
+    fn handle_env_variables(&mut self, key: KeyEvent) {
+        let Some(popup) = self.env_variables.as_mut() else {
+            return;
+        };
+        let Some(env) = self.environments.iter().find(|e| e.name == popup.name) else {
+            self.env_variables = None;
+            return;
+        };
+        let vars = environment::chain_variables(env, &self.environments).unwrap_or_default();
+        let count = vars.len().max(1);
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => popup.index = (popup.index + 1) % count,
+            KeyCode::Up | KeyCode::Char('k') => popup.index = (popup.index + count - 1) % count,
+            KeyCode::Char('o') => {
+                let Some(&(var, from)) = vars.get(popup.index) else {
+                    return;
+                };
+                if from.is_none() {
+                    let msg = format!("'{}' is already set in '{}'", var.key, env.name);
+                    self.notify(Level::Info, msg);
+                    return;
                 }
-                "basic" => {
-                    self.request.auth_type = AuthType::Basic;
-                    if let Some((username, password)) = auth.get_basic_credentials() {
-                        self.request.auth_username_editor =
-                            TextArea::new(vec![username.to_string()]);
-                        configure_editor(&mut self.request.auth_username_editor, "Username");
-                        self.request.auth_password_editor =
-                            TextArea::new(vec![password.to_string()]);
-                        configure_editor(&mut self.request.auth_password_editor, "Password");
-                    }
+                let (env, var) = (env.clone(), var.clone());
+                if self.refuse_read_only() {
+                    return;
                 }
-                "apikey" => {
-                    self.request.auth_type = AuthType::ApiKey;
-                    if let Some((key, value, location)) = auth.get_apikey() {
-                        self.request.auth_key_name_editor =
-                            TextArea::new(vec![key.to_string()]);
-                        configure_editor(&mut self.request.auth_key_name_editor, "Key name");
-                        self.request.auth_key_value_editor =
-                            TextArea::new(vec![value.to_string()]);
-                        configure_editor(&mut self.request.auth_key_value_editor, "Key value");
-                        self.request.api_key_location = match location {
-                            "query" => ApiKeyLocation::QueryParam,
-                            _ => ApiKeyLocation::Header,
-                        };
-                    }
+                if let Err(err) = environment::override_variable(&env, &var) {
+                    self.notify(Level::Error, err);
+                    return;
                 }
-                _ => {
-                    self.request.auth_type = AuthType::NoAuth;
+                match environment::load_all_environments() {
+                    Ok(environments) => self.environments = environments,
+                    Err(err) => self.notify(Level::Error, err),
                 }
+                let msg = format!(
+                    "Copied '{}' into '{}'; change it in its environment file",
+                    var.key, env.name
+                );
+                self.notify(Level::Info, msg);
             }
-        } else {
-            self.request.auth_type = AuthType::NoAuth;
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('v') => self.env_variables = None,
+            _ => {}
         }
     }
 
-    fn open_project_switcher(&mut self) {
-        let index = self
-            .project_list
-            .iter()
-            .position(|p| p.id == self.active_project_id)
-            .unwrap_or(0);
-        self.sidebar.popup = Some(SidebarPopup::ProjectSwitch { index });
-        self.focus.panel = Panel::Sidebar;
+    /// Saves an imported environment to `scope`, replacing any file with the
+    /// same name there, then reloads the layered list and selects it.
+    fn store_imported_environment(&mut self, env: Environment, scope: EnvScope) {
+        if let Err(err) = environment::save_environment_in(&env, scope) {
+            self.notify(Level::Error, err);
+            return;
+        }
+        match environment::load_all_environments() {
+            Ok(environments) => self.environments = environments,
+            Err(err) => self.notify(Level::Error, err),
+        }
+        if let Some(index) = self.environments.iter().position(|e| e.name == env.name) {
+            self.env_popup_index = index + 1;
+        }
+        let msg = format!(
+            "Imported '{}' ({} variables) as a {} environment",
+            env.name,
+            env.values.len(),
+            scope.label()
+        );
+        self.notify(Level::Info, msg);
     }
 
-    fn handle_sidebar_key(&mut self, key: KeyEvent) {
+    fn handle_save_response_popup(&mut self, key: KeyEvent) {
+        let Some(mut input) = self.save_response_popup.take() else {
+            return;
+        };
         match key.code {
-            KeyCode::Char('j') | KeyCode::Down => self.sidebar_move_selection(1),
-            KeyCode::Char('k') | KeyCode::Up => self.sidebar_move_selection(-1),
-            KeyCode::Char('h') => self.sidebar_collapse_or_parent(),
-            KeyCode::Char('l') | KeyCode::Enter => self.sidebar_expand_or_open(),
-            KeyCode::Char('a') => self.sidebar.popup = Some(SidebarPopup::Add(TextInput::new(String::new()))),
-            KeyCode::Char('r') => self.open_rename_popup(),
-            KeyCode::Char('d') => self.sidebar.popup = Some(SidebarPopup::DeleteConfirm),
-            KeyCode::Char('D') => {
-                if let Err(err) = self.duplicate_selected() {
-                    self.response = ResponseStatus::Error(err);
+            KeyCode::Esc => {}
+            KeyCode::Enter => {
+                let path = input.value.trim().to_string();
+                if path.is_empty() {
+                    self.save_response_popup = Some(input);
+                    return;
+                }
+                if let ResponseStatus::Success(ref data) = self.response {
+                    let target = config::expand_tilde(std::path::Path::new(&path));
+                    // Original bytes are written untouched, binary or not
+                    match std::fs::write(&target, &data.bytes) {
+                        Ok(()) => {
+                            let msg = format!(
+                                "Saved {} to {}",
+                                http::format_bytes(data.bytes.len()),
+                                target.display()
+                            );
+                            self.notify(Level::Info, msg);
+                        }
+                        Err(e) => {
+                            let msg = format!("Failed to write '{}': {}", target.display(), e);
+                            self.notify(Level::Error, msg);
+                        }
+                    }
                 }
             }
-            KeyCode::Char('m') => self.open_move_popup(),
-            KeyCode::Char('c') => self.copy_selected_path(),
-            KeyCode::Char('/') => {
-                let input = TextInput::new(self.sidebar.search_query.clone());
-                self.sidebar.popup = Some(SidebarPopup::Search(input));
-            }
-            KeyCode::Char('[') => self.outdent_selected(),
-            KeyCode::Char(']') => self.indent_selected(),
-            KeyCode::Char('H') => self.collapse_all(),
-            KeyCode::Char('L') => self.expand_all(),
-            KeyCode::Char('?') => self.show_help = !self.show_help,
-            KeyCode::Char('q') => {
-                self.save_current_request_if_dirty();
-                self.persist_session_state();
-                self.running = false;
+            _ => {
+                handle_text_input(&mut input, key);
+                self.save_response_popup = Some(input);
             }
-            KeyCode::Esc => {
-                if !self.sidebar.search_query.is_empty() {
-                    self.sidebar.search_query.clear();
-                    self.mark_sidebar_search_dirty();
+        }
+    }
+
+    /// Applies a message from the request in flight: progress goes to the
+    /// loading line, and the result becomes the response.
+    pub fn handle_request_event(&mut self, event: http::RequestEvent) {
+        let result = match event {
+            http::RequestEvent::Progress(progress) => {
+                if matches!(self.response, ResponseStatus::Loading) {
+                    self.loading_progress = Some(progress);
+                    self.mark_dirty(DirtyPanels::RESPONSE);
                 }
+                return;
             }
-            _ => {}
+            http::RequestEvent::Done(result) => result,
+        };
+        if matches!(self.response, ResponseStatus::Loading) {
+            if let Some(request_id) = self.sent_request_id {
+                let elapsed_ms = self
+                    .loading_started
+                    .map_or(0, |started| started.elapsed().as_millis() as u64);
+                self.note_response(request_id, &result, elapsed_ms);
+            }
+            self.finish_send_log(match &result {
+                Ok(data) => Ok((data.status, data.duration_ms, data.raw_size)),
+                Err(err) => Err(err.to_string()),
+            });
+            if result.as_ref().is_ok_and(|data| data.lossy) {
+                self.notify(
+                    Level::Warn,
+                    "Response body decoded with replacement characters",
+                );
+            }
+            self.response = match result {
+                Ok(data) => ResponseStatus::Success(data),
+                Err(e) => ResponseStatus::Error(e),
+            };
+            self.reset_response_scroll();
+            // The response tab stays as the request left it, except
+            // that a response without a body shows its headers
+            let no_body = matches!(
+                &self.response,
+                ResponseStatus::Success(data) if data.no_body.is_some()
+            );
+            if no_body && self.response_tab == ResponseTab::Body {
+                self.response_tab = ResponseTab::Headers;
+                self.headers_for_no_body = true;
+            } else if !no_body && std::mem::take(&mut self.headers_for_no_body) {
+                self.response_tab = ResponseTab::Body;
+            }
+            self.response_hex_view = false;
+            self.load_response_editors();
+            self.advance_poll();
+            self.finish_run_step();
+            self.finish_cors_check();
+            self.mark_dirty(DirtyPanels::ALL);
         }
+        self.request_handle = None;
+        self.request_cancel = None;
+        self.loading_progress = None;
+        self.loading_started = None;
     }
 
-    fn handle_sidebar_popup(&mut self, key: KeyEvent) {
-        let mut popup = match self.sidebar.popup.take() {
-            Some(popup) => popup,
-            None => return,
-        };
-        let mut close = false;
+    async fn event_loop(&mut self) -> Result<()> {
+        let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+        let (tx, mut rx) = mpsc::channel::<http::RequestEvent>(16);
+        let mut last_spinner_tick = Instant::now();
+        let mut last_draft = Instant::now();
+        let mut last_lock_check = Instant::now();
+        let mut was_loading = false;
 
-        match &mut popup {
-            SidebarPopup::Add(input) => {
-                if key.code == KeyCode::Enter {
-                    if let Err(err) = self.handle_add_input(&input.value) {
-                        self.response = ResponseStatus::Error(err);
-                    }
-                    close = true;
-                } else if key.code == KeyCode::Esc {
-                    close = true;
-                } else {
-                    handle_text_input(input, key);
+        while self.running {
+            let _loop_guard = perf::scope("event_loop_tick");
+            let is_loading = matches!(self.response, ResponseStatus::Loading);
+            if is_loading && !was_loading {
+                last_spinner_tick = Instant::now();
+            }
+            was_loading = is_loading;
+
+            while let Ok(event) = rx.try_recv() {
+                self.handle_request_event(event);
+            }
+
+            self.collect_run_results();
+            if self.run.as_ref().is_some_and(FolderRun::is_waiting)
+                && !matches!(self.response, ResponseStatus::Loading)
+            {
+                self.advance_run(tx.clone());
+                self.mark_dirty(DirtyPanels::ALL);
+            }
+            if let Some(due) = self.poll.as_ref().and_then(|run| run.next_at) {
+                if due <= Instant::now() {
+                    self.resend_poll(tx.clone());
+                    self.mark_dirty(DirtyPanels::ALL);
                 }
+                // Keeps the countdown current
+                self.mark_dirty(DirtyPanels::STATUS);
             }
-            SidebarPopup::Rename(input) => {
-                if key.code == KeyCode::Enter {
-                    if let Err(err) = self.rename_selected(input.value.clone()) {
-                        self.response = ResponseStatus::Error(err);
-                    }
-                    close = true;
-                } else if key.code == KeyCode::Esc {
-                    close = true;
+            if self.notifications.expire(Instant::now()) {
+                self.mark_dirty(DirtyPanels::STATUS);
+            }
+            if last_draft.elapsed() >= Self::DRAFT_INTERVAL {
+                self.write_draft();
+                last_draft = Instant::now();
+            }
+            if last_lock_check.elapsed() >= Self::LOCK_CHECK_INTERVAL {
+                if self.read_only {
+                    self.check_project_lock();
                 } else {
-                    handle_text_input(input, key);
+                    self.check_collection_on_disk();
                 }
+                last_lock_check = Instant::now();
             }
-            SidebarPopup::Search(input) => {
-                if key.code == KeyCode::Enter {
-                    self.sidebar.search_query = input.value.clone();
-                    self.mark_sidebar_search_dirty();
-                    close = true;
-                } else if key.code == KeyCode::Esc {
-                    self.sidebar.search_query.clear();
-                    self.mark_sidebar_search_dirty();
-                    close = true;
-                } else {
-                    handle_text_input(input, key);
-                    self.sidebar.search_query = input.value.clone();
-                    self.mark_sidebar_search_dirty();
+            if self.sidebar.flash.is_some_and(|(_, until)| until <= Instant::now()) {
+                self.sidebar.flash = None;
+                self.mark_dirty(DirtyPanels::SIDEBAR);
+            }
+            self.flush_state_files(Some(Instant::now()));
+
+            // Retries run one tick after being marked, so "saving" gets drawn
+            match self.save_status {
+                SaveStatus::Saving { .. } => self.save_collection(),
+                SaveStatus::Failed { attempts, retry_at, .. } if retry_at <= Instant::now() => {
+                    self.save_status = SaveStatus::Saving { attempts };
+                    self.mark_dirty(DirtyPanels::STATUS);
                 }
+                _ => {}
             }
-            SidebarPopup::ProjectSwitch { index } => match key.code {
-                KeyCode::Char('j') | KeyCode::Down => {
-                    if !self.project_list.is_empty() {
-                        *index = (*index + 1) % self.project_list.len();
-                    }
+
+            if is_loading && last_spinner_tick.elapsed() >= self.theme.loading_interval() {
+                self.loading_tick = self.loading_tick.wrapping_add(1);
+                last_spinner_tick = Instant::now();
+                self.mark_dirty(DirtyPanels::RESPONSE);
+            }
+
+            if self.dirty {
+                self.prepare_editors();
+                // Scopes from key handling aren't part of the frame
+                perf::take_frame();
+                {
+                    let _draw_guard = perf::scope("terminal.draw");
+                    terminal.draw(|frame| {
+                        let _render_guard = perf::scope("ui::render");
+                        ui::render(frame, self);
+                    })?;
+                }
+                if self.show_perf_overlay {
+                    self.perf_last_frame = perf::take_frame();
+                }
+                self.dirty = false;
+                self.dirty_panels = DirtyPanels::default();
+            }
+
+            let now = Instant::now();
+            let mut timeout = if is_loading {
+                let next_tick = last_spinner_tick + self.theme.loading_interval();
+                next_tick.saturating_duration_since(now)
+            } else {
+                Duration::from_millis(250)
+            };
+            let save_deadline = match self.save_status {
+                SaveStatus::Saved => None,
+                SaveStatus::Saving { .. } => Some(now),
+                SaveStatus::Failed { retry_at, .. } => Some(retry_at),
+            };
+            let deadlines = [
+                self.notifications.next_expiry(),
+                save_deadline,
+                self.ui_state_write.due_at(now),
+                self.session_write.due_at(now),
+                self.sidebar.flash.map(|(_, until)| until),
+                self.poll.as_ref().and_then(|run| run.next_at),
+            ];
+            if self.run.as_ref().is_some_and(|run| !run.finished && run.parallel.is_some()) {
+                timeout = timeout.min(Self::RUN_RESULTS_INTERVAL);
+            }
+            if let Some(deadline) = deadlines.into_iter().flatten().min() {
+                let until_deadline = deadline.saturating_duration_since(now);
+                if until_deadline < timeout {
+                    timeout = until_deadline;
                 }
-                KeyCode::Char('k') | KeyCode::Up => {
-                    if !self.project_list.is_empty() {
-                        if *index == 0 {
-                            *index = self.project_list.len() - 1;
+            }
+            if timeout.is_zero() {
+                timeout = Duration::from_millis(1);
+            }
+
+            if event::poll(timeout)? {
+                match event::read()? {
+                    Event::Key(key) if key.kind == KeyEventKind::Press => {
+                        let before = self.request_typing_state();
+                        self.handle_key(key, tx.clone());
+                        if let Some(target) = self.pending_external_edit.take() {
+                            self.edit_externally(&mut terminal, target)?;
+                        }
+                        if let Some(path) = self.pending_file_edit.take() {
+                            self.edit_request_file(&mut terminal, &path)?;
+                        }
+                        if let Some(input) = self.pending_pager.take() {
+                            self.page_externally(&mut terminal, &input)?;
+                        }
+                        if before.is_some() && before == self.request_typing_state() {
+                            self.mark_dirty(DirtyPanels::REQUEST | DirtyPanels::STATUS);
                         } else {
-                            *index -= 1;
+                            self.mark_dirty(DirtyPanels::ALL);
                         }
                     }
-                }
-                KeyCode::Enter => {
-                    if let Some(project) = self.project_list.get(*index) {
-                        self.set_active_project(project.id);
+                    Event::Resize(..) => {
+                        // Drop anything drawn for the old size
+                        terminal.clear()?;
+                        self.response_body_cache.wrap_cache = WrapCache::new();
+                        self.response_headers_cache.wrap_cache = WrapCache::new();
+                        self.mark_dirty(DirtyPanels::ALL);
                     }
-                    close = true;
+                    _ => {}
                 }
-                KeyCode::Esc => close = true,
-                _ => {}
-            },
-            SidebarPopup::Move { index, candidates } => match key.code {
-                KeyCode::Char('j') | KeyCode::Down => {
-                    if !candidates.is_empty() {
-                        *index = (*index + 1) % candidates.len();
-                    }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, tx: mpsc::Sender<http::RequestEvent>) {
+        let ctx = self.input_context();
+        if let Some(action) = input::dispatch(&ctx, key) {
+            self.apply_action(action, tx);
+        }
+    }
+
+    /// The state key dispatch depends on.
+    pub(crate) fn input_context(&self) -> InputContext {
+        InputContext {
+            mode: self.app_mode,
+            overlay: self.open_overlay(),
+            panel: self.focus.panel,
+            request_field: self.focus.request_field,
+            body_field: self.focus.body_field,
+            headers_table: self.headers_table,
+            secret_field: self.focused_secret_field().is_some(),
+            response_wrap: self.response_wrap,
+            response_hscrolled: self.response_hscroll > 0,
+            response_error: matches!(self.response, ResponseStatus::Error(_)),
+            response_headers: self.focus.panel == Panel::Response
+                && self.response_tab == ResponseTab::Headers,
+        }
+    }
+
+    /// The open overlay that gets keys first. Those opened from navigation
+    /// mode only count in that mode.
+    fn open_overlay(&self) -> Option<Overlay> {
+        let global = [
+            (self.onboarding.is_some(), Overlay::Onboarding),
+            // Trust popups can open from any mode, so they take keys first.
+            (self.lock_prompt, Overlay::LockPrompt),
+            (self.trust_prompt.is_some(), Overlay::TrustPrompt),
+            (self.draft_prompt.is_some(), Overlay::DraftPrompt),
+            (self.spec_update.is_some(), Overlay::SpecUpdate),
+            (self.send_confirm.is_some(), Overlay::SendConfirm),
+            (self.protected_confirm.is_some(), Overlay::ProtectedConfirm),
+            (self.unresolved_confirm.is_some(), Overlay::UnresolvedConfirm),
+            (self.body_confirm.is_some(), Overlay::BodyConfirm),
+            (self.codec_popup.is_some(), Overlay::Codec),
+            (self.clipboard_ring.is_some(), Overlay::ClipboardRing),
+            (self.snippet_popup.is_some(), Overlay::Snippets),
+            (self.run_popup.is_some(), Overlay::RunPopup),
+            (self.url_audit.is_some(), Overlay::UrlAudit),
+            (self.changelog_popup.is_some(), Overlay::Changelog),
+            (self.usages_popup.is_some(), Overlay::Usages),
+            (self.env_variables.is_some(), Overlay::EnvVariables),
+            (self.env_file_prompt.is_some(), Overlay::EnvFilePrompt),
+            (self.show_env_popup, Overlay::EnvPopup),
+            (self.scratch_send.is_some(), Overlay::ScratchSend),
+        ];
+        if let Some((_, overlay)) = global.into_iter().find(|(open, _)| *open) {
+            return Some(overlay);
+        }
+        if self.app_mode != AppMode::Navigation {
+            return None;
+        }
+        let navigation = [
+            (self.show_help, Overlay::Help),
+            (self.error_popup.is_some(), Overlay::ErrorPopup),
+            (self.show_config_viewer, Overlay::ConfigViewer),
+            (self.show_body_mode_popup, Overlay::BodyModePopup),
+            (self.show_auth_type_popup, Overlay::AuthTypePopup),
+            (self.show_method_popup, Overlay::MethodPopup),
+            (self.settings_popup.is_some(), Overlay::Settings),
+            (self.save_response_popup.is_some(), Overlay::SaveResponse),
+            (self.example_popup.is_some(), Overlay::Example),
+            (self.pipe_popup.is_some(), Overlay::Pipe),
+            (self.raw_view.is_some(), Overlay::RawView),
+            (self.url_outline.is_some(), Overlay::UrlOutline),
+            (self.send_preview.is_some(), Overlay::SendPreview),
+            (self.cors_popup.is_some(), Overlay::CorsCheck),
+            (self.compare_view.is_some(), Overlay::Compare),
+            (self.show_schema_check, Overlay::SchemaCheck),
+            (self.show_send_history, Overlay::SendHistory),
+            (self.notes_popup.is_some(), Overlay::Notes),
+            (self.show_message_log, Overlay::MessageLog),
+            (self.request_file_popup.is_some(), Overlay::RequestFiles),
+            (self.header_filter_input.is_some(), Overlay::HeaderFilter),
+            (self.sidebar.popup.is_some(), Overlay::SidebarPopup),
+        ];
+        navigation.into_iter().find(|(open, _)| *open).map(|(_, overlay)| overlay)
+    }
+
+    /// Carries out a dispatched key action.
+    pub(crate) fn apply_action(&mut self, action: Action, tx: mpsc::Sender<http::RequestEvent>) {
+        match action {
+            Action::Overlay(overlay, key) => self.handle_overlay_key(overlay, key, tx),
+            Action::Editing(key) => self.handle_editing_mode(key, tx),
+            Action::Sidebar(key) => self.handle_sidebar_mode(key),
+            Action::TogglePerfOverlay => {
+                self.show_perf_overlay = !self.show_perf_overlay;
+                perf::set_recording(self.show_perf_overlay);
+                self.perf_last_frame.clear();
+            }
+            Action::ToggleSecretReveal => {
+                if let Some(field) = self.focused_secret_field() {
+                    self.toggle_secret_reveal(field);
                 }
-                KeyCode::Char('k') | KeyCode::Up => {
-                    if !candidates.is_empty() {
-                        if *index == 0 {
-                            *index = candidates.len() - 1;
-                        } else {
-                            *index -= 1;
-                        }
+            }
+            Action::ToggleSidebar => {
+                self.sidebar_visible = !self.sidebar_visible;
+                if self.sidebar_visible {
+                    self.focus_sidebar();
+                } else {
+                    if self.focus.panel == Panel::Sidebar {
+                        self.focus.panel = Panel::Request;
+                        self.focus.request_field = RequestField::Url;
+                    }
+                    if matches!(self.app_mode, AppMode::Sidebar) {
+                        self.app_mode = AppMode::Navigation;
                     }
                 }
-                KeyCode::Enter => {
-                    if let Some(dest_id) = candidates.get(*index).copied() {
-                        if let Err(err) = self.move_selected(dest_id) {
-                            self.response = ResponseStatus::Error(err);
-                        }
+            }
+            Action::FocusSidebar => self.focus_sidebar(),
+            Action::RevealInSidebar => {
+                self.focus_sidebar();
+                if let Some(id) = self.current_request_id {
+                    if self.sidebar_tree.nodes.contains_key(&id) {
+                        self.reveal_in_sidebar(id);
                     }
-                    close = true;
                 }
-                KeyCode::Esc => close = true,
-                _ => {}
-            },
-            SidebarPopup::DeleteConfirm => match key.code {
-                KeyCode::Char('y') | KeyCode::Enter => {
-                    if let Err(err) = self.delete_selected() {
-                        self.response = ResponseStatus::Error(err);
+            }
+            Action::OpenProjectSwitcher => {
+                if self.sidebar_visible {
+                    self.open_project_switcher();
+                }
+            }
+            Action::NarrowSidebar => {
+                self.sidebar_width = clamp_sidebar_width(self.sidebar_width.saturating_sub(2));
+                self.persist_ui_state();
+            }
+            Action::WidenSidebar => {
+                self.sidebar_width = clamp_sidebar_width(self.sidebar_width.saturating_add(2));
+                self.persist_ui_state();
+            }
+            Action::SaveRequest => {
+                let current = self.current_request_id;
+                if let Some(request_id) = current.filter(|_| !self.refuse_read_only()) {
+                    if let Err(err) = self.save_request_by_id(request_id) {
+                        self.notify(Level::Error, err);
+                    } else {
+                        self.request_dirty = false;
                     }
-                    close = true;
                 }
-                KeyCode::Char('n') | KeyCode::Esc => close = true,
-                _ => {}
-            },
-        }
-
-        if close {
-            self.sidebar.popup = None;
-        } else {
-            self.sidebar.popup = Some(popup);
-        }
-    }
-
-    fn open_rename_popup(&mut self) {
-        if let Some(node) = self.sidebar_selected_node() {
-            let input = TextInput::new(node.name.clone());
-            self.sidebar.popup = Some(SidebarPopup::Rename(input));
-        }
-    }
-
-    fn handle_add_input(&mut self, input: &str) -> Result<(), String> {
-        let trimmed = input.trim();
-        if trimmed.is_empty() {
-            return Ok(());
-        }
-        let (folders, request) = parse_add_path(trimmed);
-        let mut parent_id = self.add_parent_id();
-
-        for folder in folders {
-            if let Some(existing) = self.find_child_folder(parent_id, &folder) {
-                parent_id = existing;
-            } else {
-                parent_id = self.collection.add_folder(parent_id, folder)?;
+            }
+            Action::SendOrCancel => {
+                if self.is_busy() {
+                    self.cancel_request();
+                } else {
+                    self.send_request(tx);
+                }
+            }
+            Action::ResendLast => self.resend_last(tx),
+            Action::ScratchSend => self.open_scratch_send(),
+            Action::OpenCodec => self.open_codec_popup(),
+            Action::ExternalEdit => self.request_external_edit(),
+            Action::OpenTrustPrompt => self.open_trust_prompt(),
+            Action::TogglePrivacy => self.toggle_privacy_mode(),
+            Action::ToggleOffline => self.toggle_offline(),
+            Action::OpenConfigViewer => {
+                self.show_config_viewer = true;
+                self.config_viewer_scroll = 0;
+                self.config_viewer_notice = None;
+            }
+            Action::OpenSettings => {
+                if self.current_request_id.is_some() && !self.refuse_locked_edit() {
+                    self.settings_popup = Some(SettingsPopup {
+                        index: 0,
+                        draft: self.request_settings.clone(),
+                        editing: None,
+                        error: None,
+                    });
+                }
+            }
+            Action::ToggleEnvPopup => {
+                self.show_method_popup = false;
+                self.show_auth_type_popup = false;
+                self.show_body_mode_popup = false;
+                self.show_env_popup = !self.show_env_popup;
+                if self.show_env_popup {
+                    self.env_popup_index = self
+                        .active_environment_name
+                        .as_ref()
+                        .and_then(|name| self.environments.iter().position(|e| e.name == *name))
+                        .map(|i| i + 1)
+                        .unwrap_or(0);
+                }
+                self.dirty = true;
+            }
+            Action::MoveLeft => self.prev_horizontal(),
+            Action::MoveRight => self.next_horizontal(),
+            Action::MoveUp => self.prev_vertical(),
+            Action::MoveDown => self.next_vertical(),
+            Action::ToggleHeadersTable => self.toggle_headers_table(),
+            Action::KvDown => {
+                if self.focus.kv_focus.row + 1 < self.kv_row_count() {
+                    self.focus.kv_focus.row += 1;
+                } else {
+                    self.next_vertical();
+                }
+            }
+            Action::KvUp => {
+                if self.focus.kv_focus.row > 0 {
+                    self.focus.kv_focus.row -= 1;
+                } else {
+                    self.prev_vertical();
+                }
+            }
+            Action::KvNextColumn => self.kv_next_column(),
+            Action::KvPrevColumn => self.kv_prev_column(),
+            Action::KvAddRow => self.kv_add_row(),
+            Action::KvDeleteRow => self.kv_delete_row(),
+            Action::KvToggleEnabled => self.kv_toggle_enabled(),
+            Action::KvEditCell => self.start_kv_cell_edit(),
+            Action::KvToggleMultipart => self.kv_toggle_multipart_type(),
+            Action::NextAuthField => self.next_auth_field(),
+            Action::PrevAuthField => self.prev_auth_field(),
+            Action::NextBodyField => self.next_body_field(),
+            Action::PrevBodyField => self.prev_body_field(),
+            Action::ScrollResponseLeft => {
+                self.scroll_response_horizontally(-(Self::HSCROLL_STEP as i32));
+            }
+            Action::ScrollResponseRight => {
+                self.scroll_response_horizontally(Self::HSCROLL_STEP as i32);
+            }
+            Action::ScrollResponsePages(pages) => self.scroll_response_pages(pages.count()),
+            Action::OpenHelp => self.open_help(),
+            Action::OpenClipboardRing => self.open_clipboard_ring(false),
+            // While request files are unreadable, `M` shows those first
+            Action::OpenMessageLog if !self.request_file_errors.is_empty() => {
+                self.request_file_popup = Some(0);
+            }
+            Action::OpenMessageLog => {
+                self.show_message_log = true;
+                self.message_log_scroll = 0;
+            }
+            Action::OpenRawView => self.open_raw_view(),
+            Action::OpenUrlOutline => {
+                let url = self.request.url_text();
+                if url.trim().is_empty() {
+                    self.notify(Level::Info, "The URL is empty");
+                } else {
+                    self.url_outline = Some(UrlOutlinePopup { url, scroll: 0 });
+                }
+            }
+            Action::OpenSendPreview => self.open_send_preview(),
+            Action::OpenCorsCheck => self.open_cors_check(),
+            Action::OpenSchemaCheck => {
+                if self.current_schema_check().is_some() {
+                    self.show_schema_check = true;
+                } else {
+                    self.notify(Level::Info, "No schema result for this response");
+                }
+            }
+            Action::OpenSendHistory => {
+                if self.send_history().is_empty() {
+                    self.notify(Level::Info, "No sends of this request yet");
+                } else {
+                    self.show_send_history = true;
+                }
+            }
+            Action::OpenNotes => self.open_notes(),
+            Action::TogglePin => self.toggle_pin(),
+            Action::OpenCompare => self.open_compare_view(),
+            Action::SaveResponse => self.open_save_response_popup(),
+            Action::SaveExample => {
+                if !self.refuse_read_only() {
+                    self.open_example_name_popup();
+                }
+            }
+            Action::ListExamples => self.open_example_list(),
+            Action::YankBody => self.yank_response_body(),
+            Action::YankHeaders => self.yank_response_headers(),
+            Action::ToggleHexView => self.toggle_hex_view(),
+            Action::ToggleHtmlPreview => self.toggle_html_preview(),
+            Action::ToggleWrap => self.toggle_response_wrap(),
+            Action::ToggleBodyWrap => self.toggle_body_wrap(),
+            Action::ToggleSectionEnabled => self.toggle_section_enabled(),
+            Action::FormatBody => self.format_body(),
+            Action::ToggleHeaderCounts => {
+                self.response_header_counts = !self.response_header_counts;
+                self.load_response_editors();
+                self.mark_dirty(DirtyPanels::RESPONSE);
+            }
+            Action::ToggleHeaderSort => {
+                self.response_header_sort = !self.response_header_sort;
+                self.load_response_editors();
+                self.mark_dirty(DirtyPanels::RESPONSE);
+            }
+            Action::OpenHeaderFilter => {
+                let filter = self.response_header_filter.clone();
+                self.header_filter_input = Some(TextInput::new(filter));
+            }
+            Action::YankAllHeaders => self.yank_all_response_headers(),
+            Action::OpenPager => self.open_pager(),
+            Action::OpenPipePrompt => self.open_pipe_prompt(),
+            Action::ToggleErrorDetail => self.show_error_detail = !self.show_error_detail,
+            Action::Activate => self.activate_focused(tx),
+            Action::Insert => {
+                let in_request = self.focus.panel == Panel::Request;
+                if in_request && self.refuse_locked_edit() {
+                    return;
+                }
+                if self.focus.panel == Panel::Sidebar {
+                    self.app_mode = AppMode::Sidebar;
+                } else if in_request && self.focus.request_field == RequestField::Body {
+                    self.handle_body_enter();
+                } else if in_request && self.is_editable_field() {
+                    self.enter_editing(VimMode::Insert);
+                } else if self.focus.panel == Panel::Response
+                    && matches!(self.response, ResponseStatus::Success(_))
+                {
+                    self.enter_editing(VimMode::Normal);
+                }
+            }
+            Action::Quit => {
+                self.save_current_request_if_dirty();
+                self.persist_session_state();
+                self.running = false;
             }
         }
-
-        if let Some(request_name) = request {
-            let req = PostmanRequest::new("GET".to_string(), String::new(), Vec::new(), None);
-            let new_id = self
-                .collection
-                .add_request(parent_id, request_name, req)?;
-            self.collection.save()?;
-            self.collection
-                .save_request_file(new_id, parent_id, self.active_project_id)?;
-            self.refresh_after_collection_change();
-            self.sidebar.selection_id = Some(new_id);
-            self.open_request(new_id);
-        } else {
-            self.collection.save()?;
-            self.refresh_after_collection_change();
-            self.sidebar.selection_id = Some(parent_id);
-        }
-        Ok(())
     }
 
-    fn rename_selected(&mut self, name: String) -> Result<(), String> {
-        let Some(id) = self.sidebar_selected_id() else {
-            return Ok(());
-        };
-        let is_request = self
-            .sidebar_tree
-            .node(id)
-            .map(|n| n.kind == NodeKind::Request)
-            .unwrap_or(false);
-        self.collection.rename_item(id, name)?;
-        self.collection.save()?;
-        self.refresh_after_collection_change();
-        if is_request {
-            self.write_request_files(&[id])?;
+    fn handle_overlay_key(
+        &mut self,
+        overlay: Overlay,
+        key: KeyEvent,
+        tx: mpsc::Sender<http::RequestEvent>,
+    ) {
+        match overlay {
+            Overlay::Onboarding => self.handle_onboarding(key),
+            Overlay::LockPrompt => self.handle_lock_prompt(key),
+            Overlay::TrustPrompt => self.handle_trust_prompt(key),
+            Overlay::DraftPrompt => self.handle_draft_prompt(key),
+            Overlay::SpecUpdate => self.handle_spec_update(key),
+            Overlay::SendConfirm => self.handle_send_confirm(key, tx),
+            Overlay::ProtectedConfirm => self.handle_protected_confirm(key, tx),
+            Overlay::UnresolvedConfirm => self.handle_unresolved_confirm(key, tx),
+            Overlay::BodyConfirm => self.handle_body_confirm(key, tx),
+            Overlay::Codec => self.handle_codec_popup(key),
+            Overlay::ClipboardRing => self.handle_clipboard_ring(key),
+            Overlay::Snippets => self.handle_snippet_popup(key),
+            Overlay::RunPopup => self.handle_run_popup(key),
+            Overlay::UrlAudit => self.handle_url_audit(key),
+            Overlay::Changelog => self.handle_changelog_popup(key),
+            Overlay::Usages => self.handle_usages(key),
+            Overlay::EnvVariables => self.handle_env_variables(key),
+            Overlay::EnvFilePrompt => self.handle_env_file_prompt(key),
+            Overlay::EnvPopup => self.handle_env_popup(key),
+            Overlay::ScratchSend => self.handle_scratch_send(key, tx),
+            Overlay::Help => self.handle_help_key(key),
+            Overlay::ErrorPopup => {
+                if matches!(key.code, KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q')) {
+                    self.error_popup = None;
+                }
+            }
+            Overlay::ConfigViewer => self.handle_config_viewer(key),
+            Overlay::BodyModePopup => self.handle_body_mode_popup(key),
+            Overlay::AuthTypePopup => self.handle_auth_type_popup(key),
+            Overlay::MethodPopup => self.handle_method_popup(key),
+            Overlay::Settings => self.handle_settings_popup(key),
+            Overlay::SaveResponse => self.handle_save_response_popup(key),
+            Overlay::Example => self.handle_example_popup(key),
+            Overlay::Pipe => self.handle_pipe_popup(key),
+            Overlay::RawView => self.handle_raw_view(key),
+            Overlay::UrlOutline => self.handle_url_outline(key),
+            Overlay::SendPreview => self.handle_send_preview(key),
+            Overlay::CorsCheck => self.handle_cors_popup(key, tx),
+            Overlay::Compare => self.handle_compare_view(key),
+            Overlay::SchemaCheck => {
+                if matches!(key.code, KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('V')) {
+                    self.show_schema_check = false;
+                }
+            }
+            Overlay::SendHistory => {
+                let ctrl_t = key.code == KeyCode::Char('t')
+                    && key.modifiers.contains(KeyModifiers::CONTROL);
+                if matches!(key.code, KeyCode::Esc | KeyCode::Char('q')) || ctrl_t {
+                    self.show_send_history = false;
+                }
+            }
+            Overlay::Notes => self.handle_notes_popup(key),
+            Overlay::MessageLog => match key.code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('M') => {
+                    self.show_message_log = false;
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    self.message_log_scroll = self.message_log_scroll.saturating_add(1);
+                }
+                KeyCode::Up | KeyCode::Char('k') => {
+                    self.message_log_scroll = self.message_log_scroll.saturating_sub(1);
+                }
+                _ => {}
+            },
+            Overlay::RequestFiles => self.handle_request_files_popup(key),
+            Overlay::HeaderFilter => self.handle_header_filter(key),
+            Overlay::SidebarPopup => self.handle_sidebar_popup(key),
         }
-        Ok(())
     }
 
-    fn delete_selected(&mut self) -> Result<(), String> {
-        let Some(id) = self.sidebar_selected_id() else {
-            return Ok(());
+    fn handle_request_files_popup(&mut self, key: KeyEvent) {
+        let Some(index) = self.request_file_popup else {
+            return;
         };
-        let kind = self
-            .sidebar_tree
-            .node(id)
-            .map(|n| n.kind)
-            .unwrap_or(NodeKind::Folder);
-        let was_active_project = id == self.active_project_id;
-        let mut request_ids = Vec::new();
-        if let Some(item) = self.collection.get_item(id) {
-            collect_request_ids(item, &mut request_ids);
-        }
-        self.collection.delete_item(id)?;
-        self.collection.save()?;
-        self.project_list = self.collection.list_projects();
-        if kind == NodeKind::Project && self.project_list.is_empty() {
-            let root_name = self
-                .collection
-                .root
-                .file_name()
-                .and_then(|s| s.to_str())
-                .unwrap_or("Perseus")
-                .to_string();
-            let new_id = self.collection.add_project(root_name)?;
-            self.collection.save()?;
-            self.project_list = self.collection.list_projects();
-            self.active_project_id = new_id;
-        } else if was_active_project {
-            if let Some(first) = self.project_list.first() {
-                self.active_project_id = first.id;
+        let count = self.request_file_errors.len();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.request_file_popup = None,
+            KeyCode::Char('M') => {
+                self.request_file_popup = None;
+                self.show_message_log = true;
+                self.message_log_scroll = 0;
             }
-        }
-        self.rebuild_sidebar_tree();
-        self.persist_ui_state();
-
-        if let Some(current) = self.current_request_id {
-            if current == id {
-                self.request = RequestState::new();
-                self.current_request_id = None;
-                self.request_dirty = false;
+            KeyCode::Down | KeyCode::Char('j') if index + 1 < count => {
+                self.request_file_popup = Some(index + 1);
             }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.request_file_popup = Some(index.saturating_sub(1));
+            }
+            KeyCode::Enter | KeyCode::Char('e') => {
+                if let Some(err) = self.request_file_errors.get(index) {
+                    self.pending_file_edit = Some(err.path.clone());
+                }
+            }
+            _ => {}
         }
+    }
 
-        if !request_ids.is_empty() {
-            self.delete_request_files(&request_ids)?;
-        }
-
-        Ok(())
+    /// "2 request files could not be parsed — press M for details".
+    fn request_file_notice(&self) -> String {
+        let count = self.request_file_errors.len();
+        format!(
+            "{} request file{} could not be parsed \u{2014} press M for details",
+            count,
+            if count == 1 { "" } else { "s" }
+        )
     }
 
-    fn duplicate_selected(&mut self) -> Result<(), String> {
-        let Some(id) = self.sidebar_selected_id() else {
+    /// Opens an unreadable request file in the editor, then syncs the
+    /// request files again to see whether it parses now.
+    fn edit_request_file(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+        path: &std::path::Path,
+    ) -> Result<()> {
+        if let Err(err) = self.suspended(terminal, || editor::edit_file(path))? {
+            self.notify(Level::Error, err);
             return Ok(());
-        };
-        let new_id = self.collection.duplicate_item(id)?;
-        let mut request_ids = Vec::new();
-        if let Some(item) = self.collection.get_item(new_id) {
-            collect_request_ids(item, &mut request_ids);
         }
-        self.collection.save()?;
-        self.refresh_after_collection_change();
-        if !request_ids.is_empty() {
-            self.write_request_files(&request_ids)?;
+        self.sync_request_files();
+        if self.request_file_errors.is_empty() {
+            self.request_file_popup = None;
+            self.notify(Level::Info, "All request files parse again");
+        } else {
+            let last = self.request_file_errors.len() - 1;
+            self.request_file_popup = self.request_file_popup.map(|index| index.min(last));
+            self.notify(Level::Warn, self.request_file_notice());
         }
-        self.sidebar.selection_id = Some(new_id);
         Ok(())
     }
 
-    fn move_selected(&mut self, dest_id: Uuid) -> Result<(), String> {
-        let Some(id) = self.sidebar_selected_id() else {
-            return Ok(());
-        };
-        if self.sidebar_tree.is_descendant(id, dest_id) {
-            return Err("Cannot move into a descendant".to_string());
-        }
-        let Some(node) = self.sidebar_tree.node(id) else {
-            return Ok(());
-        };
-        let is_request = node.kind == NodeKind::Request;
-        if node.kind == NodeKind::Project {
-            return Err("Projects cannot be moved".to_string());
+    /// Mirrors the collection into the request files, noting the ones
+    /// that don't parse.
+    fn sync_request_files(&mut self) {
+        match self.collection.write_all_request_files() {
+            Ok(report) => self.request_file_errors = report.unreadable,
+            Err(err) => self.notify(Level::Error, err),
         }
-        self.collection.move_item(id, dest_id)?;
-        self.collection.save()?;
-        self.refresh_after_collection_change();
-        if is_request {
-            self.write_request_files(&[id])?;
-        }
-        self.sidebar.selection_id = Some(id);
-        Ok(())
     }
 
-    fn open_move_popup(&mut self) {
-        let Some(selected) = self.sidebar_selected_id() else {
-            return;
-        };
-        if let Some(node) = self.sidebar_tree.node(selected) {
-            if node.kind == NodeKind::Project {
-                return;
-            }
-        }
-        let mut candidates = Vec::new();
-        for (id, node) in &self.sidebar_tree.nodes {
-            if *id == selected {
-                continue;
-            }
-            if node.kind == NodeKind::Request {
-                continue;
+    fn handle_config_viewer(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.config_viewer_scroll = self.config_viewer_scroll.saturating_add(1);
             }
-            if self.sidebar_tree.is_descendant(selected, *id) {
-                continue;
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.config_viewer_scroll = self.config_viewer_scroll.saturating_sub(1);
+            }
+            KeyCode::Char('r') => self.reload_config(),
+            KeyCode::Char('c') => {
+                self.theme.high_contrast = !self.theme.high_contrast;
+                self.config_viewer_notice = Some(format!(
+                    "High contrast {} for this session",
+                    if self.theme.high_contrast { "on" } else { "off" }
+                ));
+                self.mark_dirty(DirtyPanels::ALL);
+            }
+            KeyCode::Char('s') => {
+                self.json_style.sort_keys = !self.json_style.sort_keys;
+                self.reformat_response();
+                self.config_viewer_notice = Some(format!(
+                    "Sorted JSON keys {} for this session",
+                    if self.json_style.sort_keys { "on" } else { "off" }
+                ));
+            }
+            KeyCode::Char('m') => {
+                self.theme.reduced_motion = !self.theme.reduced_motion;
+                self.config_viewer_notice = Some(format!(
+                    "Reduced motion {} for this session",
+                    if self.theme.reduced_motion { "on" } else { "off" }
+                ));
+                self.mark_dirty(DirtyPanels::ALL);
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.show_config_viewer = false;
+            }
+            KeyCode::Char(',') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.show_config_viewer = false;
             }
-            candidates.push(*id);
-        }
-        candidates.sort_by(|a, b| {
-            let ap = self.sidebar_tree.path_for(*a).join("/");
-            let bp = self.sidebar_tree.path_for(*b).join("/");
-            ap.to_lowercase().cmp(&bp.to_lowercase())
-        });
-        if candidates.is_empty() {
-            return;
+            _ => {}
         }
-        self.sidebar.popup = Some(SidebarPopup::Move { index: 0, candidates });
     }
 
-    fn copy_selected_path(&mut self) {
-        let Some(id) = self.sidebar_selected_id() else {
+    fn handle_compare_view(&mut self, key: KeyEvent) {
+        let Some(view) = self.compare_view.as_mut() else {
             return;
         };
-        let path = self.sidebar_tree.path_for(id).join("/");
-        if let Err(_) = self.clipboard.set_text(path) {
-            self.set_clipboard_toast("Clipboard write failed");
-        } else {
-            self.set_clipboard_toast("Copied path");
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => view.scroll = view.scroll.saturating_add(1),
+            KeyCode::Up | KeyCode::Char('k') => view.scroll = view.scroll.saturating_sub(1),
+            KeyCode::Char('p') => self.toggle_pin(),
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('C') => self.compare_view = None,
+            _ => {}
         }
     }
 
-    fn sidebar_expand_or_open(&mut self) {
-        let Some(node) = self.sidebar_selected_node() else {
-            return;
-        };
-        let node_id = node.id;
-        match node.kind {
-            NodeKind::Request => {
-                self.open_request(node_id);
-                self.app_mode = AppMode::Navigation;
+    fn handle_method_popup(&mut self, key: KeyEvent) {
+        // Standard methods, "Custom...", then recent custom methods
+        let custom_index = HttpMethod::ALL.len();
+        let popup_item_count = custom_index + 1 + self.recent_methods.len();
+
+        if self.method_popup_custom_mode {
+            // Text input mode for custom method
+            match key.code {
+                KeyCode::Enter => {
+                    let input = self.method_custom_input.trim().to_string();
+                    if !input.is_empty()
+                        && input.is_ascii()
+                        && !input.contains(char::is_whitespace)
+                    {
+                        self.choose_custom_method(input.to_uppercase());
+                        self.method_popup_custom_mode = false;
+                    }
+                }
+                KeyCode::Esc => {
+                    self.method_custom_input.clear();
+                    self.method_popup_custom_mode = false;
+                    self.show_method_popup = false;
+                }
+                KeyCode::Backspace => {
+                    self.method_custom_input.pop();
+                }
+                KeyCode::Char(c)
+                    if c.is_ascii()
+                        && !c.is_whitespace()
+                        && self.method_custom_input.len() < 20 =>
+                {
+                    self.method_custom_input.push(c.to_ascii_uppercase());
+                }
+                _ => {}
             }
-            NodeKind::Folder | NodeKind::Project => {
-                if self.sidebar.expanded.contains(&node_id) {
-                    self.sidebar.expanded.remove(&node_id);
-                } else {
-                    self.sidebar.expanded.insert(node_id);
+        } else {
+            // Standard popup navigation mode
+            match key.code {
+                KeyCode::Down | KeyCode::Char('j') => {
+                    self.method_popup_index =
+                        (self.method_popup_index + 1) % popup_item_count;
                 }
-                self.mark_sidebar_dirty();
+                KeyCode::Up | KeyCode::Char('k') => {
+                    self.method_popup_index = if self.method_popup_index == 0 {
+                        popup_item_count - 1
+                    } else {
+                        self.method_popup_index - 1
+                    };
+                }
+                KeyCode::Enter => {
+                    let index = self.method_popup_index;
+                    if index < custom_index {
+                        self.request.method = Method::Standard(HttpMethod::from_index(index));
+                        self.show_method_popup = false;
+                        self.request_dirty = true;
+                    } else if index == custom_index {
+                        // "Custom..." selected — enter text input mode
+                        self.method_popup_custom_mode = true;
+                    } else if let Some(method) = self.recent_methods.get(index - custom_index - 1) {
+                        self.choose_custom_method(method.clone());
+                    }
+                }
+                KeyCode::Esc => {
+                    self.show_method_popup = false;
+                }
+                // Type-ahead: jump to the next method starting with the letter
+                KeyCode::Char(c) if c.is_ascii_alphabetic() => {
+                    let labels: Vec<&str> = HttpMethod::ALL
+                        .iter()
+                        .map(|m| m.as_str())
+                        .chain([""])
+                        .chain(self.recent_methods.iter().map(String::as_str))
+                        .collect();
+                    if let Some(index) = next_starting_with(&labels, self.method_popup_index, c) {
+                        self.method_popup_index = index;
+                    }
+                }
+                _ => {}
             }
         }
     }
 
-    fn sidebar_collapse_or_parent(&mut self) {
-        let Some(node) = self.sidebar_selected_node() else {
-            return;
-        };
-        let node_id = node.id;
-        let node_kind = node.kind;
-        let node_parent = node.parent_id;
-        let is_expanded = self.sidebar.expanded.contains(&node_id);
-        match node_kind {
-            NodeKind::Request => {
-                if let Some(parent) = node_parent {
-                    self.sidebar.selection_id = Some(parent);
+    /// Sets a custom method and moves it to the front of the recent ones.
+    fn choose_custom_method(&mut self, method: String) {
+        self.recent_methods.retain(|recent| *recent != method);
+        self.recent_methods.insert(0, method.clone());
+        self.recent_methods.truncate(RECENT_METHODS_MAX);
+        self.request.method = Method::Custom(method);
+        self.show_method_popup = false;
+        self.request_dirty = true;
+        self.persist_ui_state();
+    }
+
+    /// Enter in navigation mode: opens or edits whatever has focus.
+    fn activate_focused(&mut self, tx: mpsc::Sender<http::RequestEvent>) {
+        let in_request = self.focus.panel == Panel::Request;
+        let in_response = self.focus.panel == Panel::Response;
+        let in_sidebar = self.focus.panel == Panel::Sidebar;
+        if in_sidebar {
+            self.app_mode = AppMode::Sidebar;
+        } else if in_request {
+            match self.focus.request_field {
+                RequestField::Method if self.request_locked() => {
+                    self.refuse_locked_edit();
+                }
+                RequestField::Method => {
+                    match &self.request.method {
+                        Method::Standard(m) => {
+                            self.method_popup_index = m.index();
+                            self.method_custom_input.clear();
+                        }
+                        Method::Custom(s) => {
+                            let recent = self.recent_methods.iter().position(|m| m == s);
+                            self.method_popup_index =
+                                HttpMethod::ALL.len() + recent.map_or(0, |i| i + 1);
+                            self.method_custom_input = s.clone();
+                        }
+                    }
+                    self.method_popup_custom_mode = false;
+                    self.show_method_popup = true;
+                }
+                RequestField::Send => {
+                    if self.is_busy() {
+                        self.cancel_request();
+                    } else {
+                        self.send_request(tx);
+                    }
                 }
-            }
-            NodeKind::Folder | NodeKind::Project => {
-                if is_expanded {
-                    self.sidebar.expanded.remove(&node_id);
-                    self.mark_sidebar_dirty();
-                } else if let Some(parent) = node_parent {
-                    self.sidebar.selection_id = Some(parent);
+                RequestField::Url | RequestField::Headers => {
+                    self.enter_editing(VimMode::Normal);
+                }
+                RequestField::Body => {
+                    self.handle_body_enter();
+                }
+                RequestField::Auth => {
+                    self.handle_auth_enter();
                 }
             }
+        } else if in_response
+            && matches!(self.response, ResponseStatus::Success(_))
+        {
+            self.enter_editing(VimMode::Normal);
         }
     }
 
-    fn collapse_all(&mut self) {
-        self.sidebar.expanded.clear();
-        self.mark_sidebar_dirty();
+    fn open_help(&mut self) {
+        self.show_help = true;
+        self.help = HelpOverlay::default();
     }
 
-    fn expand_all(&mut self) {
-        self.sidebar.expanded = self
-            .sidebar_tree
-            .nodes
-            .iter()
-            .filter_map(|(id, node)| {
-                if node.kind == NodeKind::Request {
-                    None
-                } else {
-                    Some(*id)
+    fn handle_help_key(&mut self, key: KeyEvent) {
+        let help = &mut self.help;
+        if help.filtering {
+            match key.code {
+                KeyCode::Enter => help.filtering = false,
+                KeyCode::Esc => {
+                    help.filtering = false;
+                    help.filter = TextInput::new(String::new());
                 }
-            })
-            .collect();
-        self.mark_sidebar_dirty();
+                _ => handle_text_input(&mut help.filter, key),
+            }
+            help.scroll = 0;
+            return;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => help.scroll = help.scroll.saturating_add(1),
+            KeyCode::Up | KeyCode::Char('k') => help.scroll = help.scroll.saturating_sub(1),
+            KeyCode::PageDown => help.scroll = help.scroll.saturating_add(help.page),
+            KeyCode::Char('d') if ctrl => help.scroll = help.scroll.saturating_add(help.page),
+            KeyCode::PageUp => help.scroll = help.scroll.saturating_sub(help.page),
+            KeyCode::Char('u') if ctrl => help.scroll = help.scroll.saturating_sub(help.page),
+            KeyCode::Home | KeyCode::Char('g') => help.scroll = 0,
+            // Clamped to the last page when drawn
+            KeyCode::End | KeyCode::Char('G') => help.scroll = u16::MAX,
+            KeyCode::Char('/') => help.filtering = true,
+            KeyCode::Esc if !help.filter.value.is_empty() => {
+                help.filter = TextInput::new(String::new());
+                help.scroll = 0;
+            }
+            KeyCode::Esc | KeyCode::Char('?') | KeyCode::Char('q') => self.show_help = false,
+            _ => {}
+        }
     }
 
-    fn indent_selected(&mut self) {
-        let Some(selected) = self.sidebar_selected_id() else {
-            return;
-        };
-        let Some(node) = self.sidebar_tree.node(selected) else {
+    fn handle_sidebar_mode(&mut self, key: KeyEvent) {
+        if self.show_help {
+            self.handle_help_key(key);
             return;
-        };
-        let Some(parent_id) = node.parent_id else {
+        }
+
+        // Ctrl+N: environment quick-switch popup from sidebar mode
+        if key.code == KeyCode::Char('n') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.show_method_popup = false;
+            self.show_auth_type_popup = false;
+            self.show_body_mode_popup = false;
+            self.show_env_popup = !self.show_env_popup;
+            if self.show_env_popup {
+                self.env_popup_index = self
+                    .active_environment_name
+                    .as_ref()
+                    .and_then(|name| self.environments.iter().position(|e| e.name == *name))
+                    .map(|i| i + 1)
+                    .unwrap_or(0);
+            }
+            self.dirty = true;
             return;
-        };
-        let Some(parent) = self.sidebar_tree.node(parent_id) else {
+        }
+
+        if key.code == KeyCode::Esc {
+            if self.sidebar.popup.is_some() {
+                self.sidebar.popup = None;
+            }
+            if !self.sidebar.search_query.is_empty() {
+                self.sidebar.search_query.clear();
+            }
+            self.app_mode = AppMode::Navigation;
             return;
-        };
-        let siblings = &parent.children;
-        let index = siblings.iter().position(|id| *id == selected).unwrap_or(0);
-        if index == 0 {
+        }
+
+        if self.sidebar.popup.is_some() {
+            self.handle_sidebar_popup(key);
             return;
         }
-        let candidate_id = siblings[index - 1];
-        if let Some(candidate) = self.sidebar_tree.node(candidate_id) {
-            if candidate.kind == NodeKind::Folder || candidate.kind == NodeKind::Project {
-                if let Err(err) = self.move_selected(candidate_id) {
-                    self.response = ResponseStatus::Error(err);
+
+        self.handle_sidebar_key(key);
+    }
+
+    fn handle_editing_mode(
+        &mut self,
+        key: KeyEvent,
+        tx: mpsc::Sender<http::RequestEvent>,
+    ) {
+        // Ctrl+S: save current request
+        if key.code == KeyCode::Char('s') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            if let Some(request_id) = self.current_request_id {
+                if let Err(err) = self.save_request_by_id(request_id) {
+                    self.notify(Level::Error, err);
+                } else {
+                    self.request_dirty = false;
                 }
             }
+            return;
         }
-    }
 
-    fn outdent_selected(&mut self) {
-        let Some(selected) = self.sidebar_selected_id() else {
-            return;
-        };
-        let Some(node) = self.sidebar_tree.node(selected) else {
-            return;
-        };
-        let Some(parent_id) = node.parent_id else {
-            return;
-        };
-        let Some(parent) = self.sidebar_tree.node(parent_id) else {
+        // Ctrl+R: send request or cancel if loading, even in editing mode
+        if key.code == KeyCode::Char('r') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            if self.is_busy() {
+                self.cancel_request();
+            } else {
+                self.send_request(tx);
+            }
             return;
-        };
-        let Some(grand_parent_id) = parent.parent_id else {
+        }
+
+        // Ctrl+Space typing a body: pick a snippet to insert
+        if key.code == KeyCode::Char(' ')
+            && key.modifiers.contains(KeyModifiers::CONTROL)
+            && self.vim.mode == VimMode::Insert
+            && self.focus.panel == Panel::Request
+            && self.focus.request_field == RequestField::Body
+            && self.focus.body_field == BodyField::TextEditor
+            && self.kv_edit_textarea.is_none()
+            && !self.request_locked()
+        {
+            self.open_snippet_popup();
             return;
-        };
-        if let Err(err) = self.move_selected(grand_parent_id) {
-            self.response = ResponseStatus::Error(err);
         }
-    }
 
-    fn set_active_project(&mut self, project_id: Uuid) {
-        if self.active_project_id == project_id {
+        // Ctrl+G: edit the focused text in $VISUAL / $EDITOR
+        if key.code == KeyCode::Char('g') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.request_external_edit();
             return;
         }
-        self.save_current_request_if_dirty();
-        self.active_project_id = project_id;
-        self.rebuild_sidebar_tree();
-        self.sidebar.selection_id = Some(project_id);
-        self.sidebar.search_query.clear();
-        self.mark_sidebar_search_dirty();
-        self.persist_ui_state();
-    }
 
-    fn refresh_after_collection_change(&mut self) {
-        self.project_list = self.collection.list_projects();
-        self.rebuild_sidebar_tree();
-        self.persist_ui_state();
-    }
+        // Ctrl+U on a visual selection: codec helpers (elsewhere it scrolls)
+        if key.code == KeyCode::Char('u')
+            && key.modifiers.contains(KeyModifiers::CONTROL)
+            && self.vim.mode == VimMode::Visual
+        {
+            self.open_codec_popup();
+            return;
+        }
 
-    fn add_parent_id(&self) -> Uuid {
-        if let Some(selected) = self.sidebar_selected_node() {
-            match selected.kind {
-                NodeKind::Request => selected.parent_id.unwrap_or(self.active_project_id),
-                NodeKind::Folder | NodeKind::Project => selected.id,
+        // Ctrl+N: environment quick-switch popup, even in editing mode
+        if key.code == KeyCode::Char('n') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.show_method_popup = false;
+            self.show_auth_type_popup = false;
+            self.show_body_mode_popup = false;
+            self.show_env_popup = !self.show_env_popup;
+            if self.show_env_popup {
+                self.env_popup_index = self
+                    .active_environment_name
+                    .as_ref()
+                    .and_then(|name| self.environments.iter().position(|e| e.name == *name))
+                    .map(|i| i + 1)
+                    .unwrap_or(0);
             }
-        } else {
-            self.active_project_id
+            self.dirty = true;
+            return;
         }
-    }
 
-    fn find_child_folder(&self, parent_id: Uuid, name: &str) -> Option<Uuid> {
-        let parent = self.sidebar_tree.node(parent_id)?;
-        for child in &parent.children {
-            if let Some(node) = self.sidebar_tree.node(*child) {
-                if (node.kind == NodeKind::Folder || node.kind == NodeKind::Project)
-                    && node.name == name
-                {
-                    return Some(*child);
-                }
+        // Enter in URL insert mode: send request (or cancel if loading), then exit editing
+        if self.focus.panel == Panel::Request
+            && self.focus.request_field == RequestField::Url
+            && self.vim.mode == VimMode::Insert
+            && key.code == KeyCode::Enter
+        {
+            if self.is_busy() {
+                self.cancel_request();
+            } else {
+                self.send_request(tx);
             }
+            self.exit_editing();
+            return;
         }
-        None
-    }
 
-    fn active_yank_target(&self) -> Option<YankTarget> {
-        match self.focus.panel {
-            Panel::Response => match self.response_tab {
-                ResponseTab::Body => Some(YankTarget::ResponseBody),
-                ResponseTab::Headers => Some(YankTarget::ResponseHeaders),
-            },
-            Panel::Request => match self.focus.request_field {
-                RequestField::Url | RequestField::Headers | RequestField::Body => {
-                    Some(YankTarget::Request)
-                }
-                RequestField::Auth if self.is_auth_text_field() => Some(YankTarget::Request),
-                _ => None,
-            },
-            Panel::Sidebar => None,
-        }
-    }
+        let is_request = self.focus.panel == Panel::Request;
+        let is_response = self.focus.panel == Panel::Response;
+        let is_request_vim_switch = is_request
+            && matches!(self.vim.mode, VimMode::Normal | VimMode::Insert);
+        let is_response_vim_switch = is_response
+            && matches!(
+                self.vim.mode,
+                VimMode::Normal | VimMode::Visual | VimMode::Operator(_)
+            );
 
-    fn update_last_yank(&mut self, target: YankTarget, text: String) {
-        match target {
-            YankTarget::Request => self.last_yank_request = text,
-            YankTarget::ResponseBody => self.last_yank_response = text,
-            YankTarget::ResponseHeaders => self.last_yank_response_headers = text,
+        if is_request_vim_switch {
+            match key.code {
+                KeyCode::Char('H') => {
+                    self.prev_request_tab();
+                    return;
+                }
+                KeyCode::Char('L') => {
+                    self.next_request_tab();
+                    return;
+                }
+                _ => {}
+            }
         }
-    }
 
-    fn sync_clipboard_from_active_yank(&mut self) {
-        let mut new_yank: Option<String> = None;
-        match self.focus.panel {
-            Panel::Response => match self.response_tab {
-                ResponseTab::Body => {
-                    let yank = self.response_editor.yank_text();
-                    if self.last_yank_response != yank {
-                        self.last_yank_response = yank.clone();
-                        new_yank = Some(yank);
-                    }
-                }
-                ResponseTab::Headers => {
-                    let yank = self.response_headers_editor.yank_text();
-                    if self.last_yank_response_headers != yank {
-                        self.last_yank_response_headers = yank.clone();
-                        new_yank = Some(yank);
-                    }
+        if is_response_vim_switch {
+            match key.code {
+                KeyCode::Char('H') => {
+                    self.prev_response_tab();
+                    return;
                 }
-            },
-            Panel::Request => {
-                let yank = self.active_request_editor().map(|ta| ta.yank_text());
-                if let Some(yank) = yank {
-                    if self.last_yank_request != yank {
-                        self.last_yank_request = yank.clone();
-                        new_yank = Some(yank);
-                    }
+                KeyCode::Char('L') => {
+                    self.next_response_tab();
+                    return;
                 }
+                _ => {}
             }
-            Panel::Sidebar => {}
         }
 
-        if let Some(yank) = new_yank {
-            if let Err(_) = self.clipboard.set_text(yank) {
-                self.set_clipboard_toast("Clipboard write failed");
+        // `yv` on the response body copies the JSON value under the cursor
+        if is_response
+            && self.response_tab == ResponseTab::Body
+            && self.vim.mode == VimMode::Operator('y')
+            && key.code == KeyCode::Char('v')
+        {
+            self.vim = Vim::new(VimMode::Normal);
+            self.yank_json_value_at_cursor();
+            return;
+        }
+
+        // `B` on a response body selection makes it the request body; `R`
+        // sends it right away too
+        if is_response
+            && self.response_tab == ResponseTab::Body
+            && self.vim.mode == VimMode::Visual
+            && matches!(key.code, KeyCode::Char('B' | 'R'))
+        {
+            if self.selection_to_body() && key.code == KeyCode::Char('R') {
+                self.send_request(tx);
             }
+            return;
         }
-    }
 
-    fn handle_clipboard_paste_shortcut(&mut self) {
-        let target = match self.active_yank_target() {
-            Some(target) => target,
-            None => return,
-        };
+        // `gj`/`gk` on a wrapped body move by screen line
+        if is_request
+            && self.body_wrap
+            && self.focus.request_field == RequestField::Body
+            && self.focus.body_field == BodyField::TextEditor
+            && self.kv_edit_textarea.is_none()
+            && matches!(self.vim.mode, VimMode::Normal | VimMode::Visual)
+            && self.vim.pending_g()
+            && matches!(key.code, KeyCode::Char('j' | 'k'))
+        {
+            let width = self.body_wrap_cache.wrap_cache.width;
+            let editor = &mut self.request.body_editor;
+            let down = key.code == KeyCode::Char('j');
+            let (row, col) = ui::display_line_move(editor.lines(), editor.cursor(), width, down);
+            editor.move_cursor(CursorMove::Jump(
+                row.min(u16::MAX as usize) as u16,
+                col.min(u16::MAX as usize) as u16,
+            ));
+            self.vim = Vim::new(self.vim.mode);
+            return;
+        }
 
-        let clipboard_text = match self.clipboard.get_text() {
-            Ok(text) => Some(text),
-            Err(_) => {
-                self.set_clipboard_toast("Clipboard read failed; using internal yank");
-                None
+        // Locked requests can be browsed and yanked from, not changed
+        let read_only = is_request && self.request_locked();
+        let starts_insert = matches!(
+            key.code,
+            KeyCode::Char('i' | 'a' | 'o' | 'I' | 'A' | 'O' | 's' | 'S' | 'c' | 'C')
+        );
+        if read_only && self.vim.mode == VimMode::Normal && starts_insert {
+            self.refuse_locked_edit();
+            return;
+        }
+
+        // `"` in normal mode: pick an older yank to paste
+        if is_request
+            && !read_only
+            && self.vim.mode == VimMode::Normal
+            && key.code == KeyCode::Char('"')
+        {
+            self.open_clipboard_ring(true);
+            return;
+        }
+
+        let is_clipboard_modifier = key.modifiers.contains(KeyModifiers::CONTROL)
+            || key.modifiers.contains(KeyModifiers::SUPER);
+
+        if is_request && !read_only && key.code != KeyCode::Esc {
+            self.request_dirty = true;
+        }
+
+        if is_clipboard_modifier && matches!(key.code, KeyCode::Char('v') | KeyCode::Char('V')) {
+            if !is_response && !read_only {
+                self.handle_clipboard_paste_shortcut();
             }
-        };
+            return;
+        }
 
-        let mut last_yank_update: Option<(YankTarget, String)> = None;
-        let mut exit_to_normal = false;
-        let vim_mode = self.vim.mode;
+        if is_clipboard_modifier && matches!(key.code, KeyCode::Char('c') | KeyCode::Char('C')) {
+            self.handle_clipboard_copy_shortcut();
+            return;
+        }
 
-        match target {
-            YankTarget::Request => {
-                if let Some(textarea) = self.active_request_editor() {
-                    if let Some(text) = clipboard_text.as_ref() {
-                        textarea.set_yank_text(text.clone());
-                        if vim_mode == VimMode::Insert {
-                            textarea.insert_str(text.as_str());
-                        } else {
-                            textarea.paste();
-                            if matches!(vim_mode, VimMode::Visual | VimMode::Operator(_)) {
-                                exit_to_normal = true;
+        if matches!(self.vim.mode, VimMode::Normal | VimMode::Visual)
+            && key.modifiers.is_empty()
+            && key.code == KeyCode::Char('p')
+        {
+            if let Some(target) = self.active_yank_target() {
+                match self.clipboard.get_text() {
+                    Ok(text) => {
+                        match target {
+                            YankTarget::Request => {
+                                if let Some(textarea) =
+                                    self.active_request_editor()
+                                {
+                                    textarea.set_yank_text(text.clone());
+                                }
+                            }
+                            YankTarget::ResponseBody => {
+                                self.response_editor.set_yank_text(text.clone());
+                            }
+                            YankTarget::ResponseHeaders => {
+                                self.response_headers_editor.set_yank_text(text.clone());
                             }
                         }
-                        last_yank_update = Some((target, text.clone()));
-                    } else if vim_mode == VimMode::Insert {
-                        let fallback = textarea.yank_text();
-                        if !fallback.is_empty() {
-                            textarea.insert_str(fallback);
-                        }
-                    } else {
-                        textarea.paste();
-                        if matches!(vim_mode, VimMode::Visual | VimMode::Operator(_)) {
-                            exit_to_normal = true;
-                        }
+                        self.update_last_yank(target, text);
                     }
+                    Err(_) => self.report_clipboard_failure(),
                 }
             }
-            YankTarget::ResponseBody => {
-                let textarea = &mut self.response_editor;
-                if let Some(text) = clipboard_text.as_ref() {
-                    textarea.set_yank_text(text.clone());
-                    if self.vim.mode == VimMode::Insert {
-                        textarea.insert_str(text.as_str());
-                    } else {
-                        textarea.paste();
-                        if matches!(self.vim.mode, VimMode::Visual | VimMode::Operator(_)) {
-                            exit_to_normal = true;
-                        }
-                    }
-                    last_yank_update = Some((target, text.clone()));
-                } else if self.vim.mode == VimMode::Insert {
-                    let fallback = textarea.yank_text();
-                    if !fallback.is_empty() {
-                        textarea.insert_str(fallback);
-                    }
-                } else {
-                    textarea.paste();
-                    if matches!(self.vim.mode, VimMode::Visual | VimMode::Operator(_)) {
-                        exit_to_normal = true;
-                    }
+        }
+
+        let input: Input = key.into();
+
+        let transition = if is_response {
+            let response_tab = self.response_tab;
+            let vim = &self.vim;
+            match response_tab {
+                ResponseTab::Body => {
+                    vim.transition_read_only(input, &mut self.response_editor, false)
+                }
+                ResponseTab::Headers => {
+                    vim.transition_read_only(input, &mut self.response_headers_editor, false)
                 }
             }
-            YankTarget::ResponseHeaders => {
-                let textarea = &mut self.response_headers_editor;
-                if let Some(text) = clipboard_text.as_ref() {
-                    textarea.set_yank_text(text.clone());
-                    if self.vim.mode == VimMode::Insert {
-                        textarea.insert_str(text.as_str());
-                    } else {
-                        textarea.paste();
-                        if matches!(self.vim.mode, VimMode::Visual | VimMode::Operator(_)) {
-                            exit_to_normal = true;
-                        }
-                    }
-                    last_yank_update = Some((target, text.clone()));
-                } else if self.vim.mode == VimMode::Insert {
-                    let fallback = textarea.yank_text();
-                    if !fallback.is_empty() {
-                        textarea.insert_str(fallback);
-                    }
+        } else if let Some(textarea) = self.kv_edit_textarea.as_mut() {
+            // KV cell editing — route vim input to the temporary textarea
+            self.vim.transition(input, textarea, true)
+        } else {
+            let field = self.focus.request_field;
+            let single_line = field == RequestField::Url
+                || (field == RequestField::Auth && self.is_auth_text_field());
+            if let Some(textarea) = self.request.active_editor(field, self.focus.body_field) {
+                if read_only {
+                    self.vim.transition_read_only(input, textarea, single_line)
                 } else {
-                    textarea.paste();
-                    if matches!(self.vim.mode, VimMode::Visual | VimMode::Operator(_)) {
-                        exit_to_normal = true;
-                    }
+                    self.vim.transition(input, textarea, single_line)
                 }
+            } else {
+                self.exit_editing();
+                return;
             }
-        }
-
-        if let Some((target, text)) = last_yank_update {
-            self.update_last_yank(target, text);
-        }
-
-        if exit_to_normal {
-            self.vim = Vim::new(VimMode::Normal);
-            self.update_terminal_cursor();
-        }
-    }
-
-    fn handle_clipboard_copy_shortcut(&mut self) {
-        let target = match self.active_yank_target() {
-            Some(target) => target,
-            None => return,
         };
 
-        let mut yank: Option<String> = None;
-        let mut exit_visual = false;
-        let vim_mode = self.vim.mode;
-
-        match target {
-            YankTarget::Request => {
-                if let Some(textarea) = self.active_request_editor() {
-                    if textarea.is_selecting() {
-                        textarea.copy();
-                        yank = Some(textarea.yank_text());
-                        if vim_mode == VimMode::Visual {
-                            exit_visual = true;
-                        }
-                    }
+        match transition {
+            Transition::ExitField => {
+                self.commit_kv_cell_edit();
+                self.exit_editing();
+            }
+            Transition::Mode(new_mode) => {
+                if is_response {
+                    let response_tab = self.response_tab;
+                    let vim = std::mem::replace(&mut self.vim, Vim::new(VimMode::Normal));
+                    let new_vim = match response_tab {
+                        ResponseTab::Body => vim.apply_transition(
+                            Transition::Mode(new_mode),
+                            &mut self.response_editor,
+                        ),
+                        ResponseTab::Headers => vim.apply_transition(
+                            Transition::Mode(new_mode),
+                            &mut self.response_headers_editor,
+                        ),
+                    };
+                    self.vim = new_vim;
+                } else if let Some(textarea) = self.kv_edit_textarea.as_mut() {
+                    self.vim = std::mem::replace(&mut self.vim, Vim::new(VimMode::Normal))
+                        .apply_transition(Transition::Mode(new_mode), textarea);
+                } else {
+                    let textarea = self
+                        .request
+                        .active_editor(self.focus.request_field, self.focus.body_field)
+                        .unwrap();
+                    self.vim = std::mem::replace(&mut self.vim, Vim::new(VimMode::Normal))
+                        .apply_transition(Transition::Mode(new_mode), textarea);
                 }
+                self.update_terminal_cursor();
+                self.sync_clipboard_from_active_yank();
             }
-            YankTarget::ResponseBody => {
-                let textarea = &mut self.response_editor;
-                if textarea.is_selecting() {
-                    textarea.copy();
-                    yank = Some(textarea.yank_text());
-                    if self.vim.mode == VimMode::Visual {
-                        exit_visual = true;
-                    }
+            Transition::Pending(pending_input) => {
+                if is_response {
+                    let response_tab = self.response_tab;
+                    let vim = std::mem::replace(&mut self.vim, Vim::new(VimMode::Normal));
+                    let new_vim = match response_tab {
+                        ResponseTab::Body => vim.apply_transition(
+                            Transition::Pending(pending_input),
+                            &mut self.response_editor,
+                        ),
+                        ResponseTab::Headers => vim.apply_transition(
+                            Transition::Pending(pending_input),
+                            &mut self.response_headers_editor,
+                        ),
+                    };
+                    self.vim = new_vim;
+                } else if let Some(textarea) = self.kv_edit_textarea.as_mut() {
+                    self.vim = std::mem::replace(&mut self.vim, Vim::new(VimMode::Normal))
+                        .apply_transition(Transition::Pending(pending_input), textarea);
+                } else {
+                    let textarea = self
+                        .request
+                        .active_editor(self.focus.request_field, self.focus.body_field)
+                        .unwrap();
+                    self.vim = std::mem::replace(&mut self.vim, Vim::new(VimMode::Normal))
+                        .apply_transition(Transition::Pending(pending_input), textarea);
                 }
             }
-            YankTarget::ResponseHeaders => {
-                let textarea = &mut self.response_headers_editor;
-                if textarea.is_selecting() {
-                    textarea.copy();
-                    yank = Some(textarea.yank_text());
-                    if self.vim.mode == VimMode::Visual {
-                        exit_visual = true;
-                    }
+            Transition::NextStop => {
+                if let Some(textarea) =
+                    self.request.active_editor(self.focus.request_field, self.focus.body_field)
+                {
+                    self.vim = std::mem::replace(&mut self.vim, Vim::new(VimMode::Normal))
+                        .apply_transition(Transition::NextStop, textarea);
                 }
             }
+            Transition::Nop => {}
         }
+    }
 
-        if let Some(text) = yank {
-            self.update_last_yank(target, text.clone());
-            if let Err(_) = self.clipboard.set_text(text) {
-                self.set_clipboard_toast("Clipboard write failed");
-            }
-        }
-
-        if exit_visual {
-            self.vim = Vim::new(VimMode::Normal);
-            self.update_terminal_cursor();
-        }
+    fn enter_editing(&mut self, mode: VimMode) {
+        self.app_mode = AppMode::Editing;
+        self.vim = Vim::new(mode);
+        self.update_terminal_cursor();
     }
 
-    fn install_panic_hook(&self) {
-        let original_hook = panic::take_hook();
-        panic::set_hook(Box::new(move |panic_info| {
-            let _ = disable_raw_mode();
-            let _ = stdout().execute(LeaveAlternateScreen);
-            original_hook(panic_info);
-        }));
+    fn exit_editing(&mut self) {
+        self.write_draft();
+        self.app_mode = AppMode::Navigation;
+        self.vim = Vim::new(VimMode::Normal);
+        let _ = stdout().execute(SetCursorStyle::DefaultUserShape);
+        if self.focus.panel == Panel::Request && self.focus.request_field == RequestField::Body {
+            self.hint_long_body_line();
+        }
     }
 
-    fn setup_terminal(&self) -> Result<()> {
-        enable_raw_mode()?;
-        stdout().execute(EnterAlternateScreen)?;
-        Ok(())
+    fn update_terminal_cursor(&self) {
+        let style = match self.vim.mode {
+            VimMode::Normal => SetCursorStyle::SteadyBlock,
+            VimMode::Insert => SetCursorStyle::BlinkingUnderScore,
+            VimMode::Visual => SetCursorStyle::SteadyBlock,
+            VimMode::Operator(_) => SetCursorStyle::SteadyBlock,
+        };
+        let _ = stdout().execute(style);
     }
 
-    fn restore_terminal(&self) -> Result<()> {
-        disable_raw_mode()?;
-        stdout().execute(LeaveAlternateScreen)?;
-        Ok(())
+    fn send_request(&mut self, tx: mpsc::Sender<http::RequestEvent>) {
+        self.start_send(tx);
+        // A scratch message waits out the confirmations, and nothing else
+        let confirming = self.send_confirm.is_some()
+            || self.protected_confirm.is_some()
+            || self.unresolved_confirm.is_some()
+            || self.body_confirm.is_some();
+        if !confirming {
+            self.scratch_message = None;
+        }
     }
 
-    pub fn prepare_editors(&mut self) {
-        let is_editing = self.app_mode == AppMode::Editing;
-        let focused_field = self.focus.request_field;
-        let in_request = self.focus.panel == Panel::Request;
+    fn start_send(&mut self, tx: mpsc::Sender<http::RequestEvent>) {
+        let raw_url = match &self.scratch_message {
+            Some(scratch) => scratch.message.url.clone(),
+            None => self.request.url_text(),
+        };
+        if raw_url.is_empty() {
+            self.response = ResponseStatus::Error(http::RequestError::new(
+                http::ErrorKind::InvalidUrl,
+                "URL is required",
+            ));
+            return;
+        }
 
-        let url_focused = in_request && focused_field == RequestField::Url;
-        let headers_focused = in_request && focused_field == RequestField::Headers;
-        let body_focused = in_request && focused_field == RequestField::Body;
+        if matches!(self.response, ResponseStatus::Loading) {
+            return;
+        }
 
-        let url_border = if url_focused { Color::Green } else { Color::White };
+        // The one gate between a send and the network
+        if self.offline {
+            self.answer_offline();
+            return;
+        }
+        let Some((spec, client, secrets)) = self.prepare_send() else {
+            return;
+        };
+        let settings = self.request_settings.clone();
 
-        self.request.url_editor.set_block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(url_border)),
-        );
-        self.request
-            .headers_editor
-            .set_block(Block::default().borders(Borders::NONE));
-        self.request
-            .body_editor
-            .set_block(Block::default().borders(Borders::NONE));
+        self.response = ResponseStatus::Loading;
+        self.show_error_detail = false;
+        self.loading_progress = None;
+        self.loading_started = Some(Instant::now());
+        // A scratch response isn't the request's: it isn't stored, checked
+        // or polled for it
+        self.sent_request_id = self.current_request_id.filter(|_| self.scratch_message.is_none());
+        self.sent_schema = settings.schema.clone();
+        if let Some(request_id) = self.sent_request_id {
+            self.schema_checks.remove(&request_id);
+        }
+        self.start_send_log(&spec.url, &spec.headers, &secrets);
+        self.poll = settings.poll.clone().zip(self.sent_request_id).map(|(setting, id)| {
+            PollRun {
+                request_id: id,
+                setting,
+                attempt: 1,
+                next_at: None,
+            }
+        });
 
-        // Set cursor style based on mode
-        let cursor_style = if is_editing && url_focused {
-            self.vim_cursor_style()
-        } else {
-            Style::default().fg(Color::DarkGray)
+        let sender = http::Sender::new(client);
+        let (cancel_tx, cancel_rx) = oneshot::channel();
+        let mut transfer = http::Transfer {
+            events: tx.clone(),
+            cancel: cancel_rx,
         };
-        self.request.url_editor.set_cursor_style(cursor_style);
 
-        let cursor_style = if is_editing && headers_focused {
-            self.vim_cursor_style()
-        } else {
-            Style::default().fg(Color::DarkGray)
+        let handle = tokio::spawn(async move {
+            let result = sender.execute_with(spec, &mut transfer).await;
+            let _ = tx.send(http::RequestEvent::Done(result)).await;
+        });
+        self.request_handle = Some(handle.abort_handle());
+        self.request_cancel = Some(cancel_tx);
+    }
+
+    /// Takes the open request through every check a send makes, returning
+    /// what to send, the client to send it with and the secret values to
+    /// redact. A check that stops the send leaves its confirmation or
+    /// error in place and returns `None`.
+    fn prepare_send(&mut self) -> Option<(http::RequestSpec, Client, Vec<String>)> {
+        let raw_url = match &self.scratch_message {
+            Some(scratch) => scratch.message.url.clone(),
+            None => self.request.url_text(),
+        };
+        // Environment variables and references to other responses
+        if let Err(err) = self.active_variables() {
+            self.notify(Level::Error, err);
+            return None;
+        }
+        let resolver = self.resolver();
+        if let Err(err) = self.check_response_references(&resolver) {
+            self.notify(Level::Error, err);
+            return None;
+        }
+        let unresolved = environment::unresolved_in_fields(&self.sent_fields(), |name| {
+            resolver.resolve(name).ok().flatten()
+        });
+        let mut unresolved_warning = None;
+        if !unresolved.is_empty() && !self.unresolved_send_granted {
+            if self.config.http.confirm_unresolved {
+                self.unresolved_confirm = Some(UnresolvedConfirm { names: unresolved });
+                return None;
+            }
+            let names: Vec<String> =
+                unresolved.iter().map(|(name, _)| format!("{{{{{}}}}}", name)).collect();
+            unresolved_warning = Some(format!("Sent with unresolved {}", names.join(", ")));
+        }
+        let resolved = self.resolved_spec(&resolver);
+        let secrets = self.secret_values(&resolver);
+        if let Some(warning) = unresolved_warning {
+            self.notify(Level::Warn, warning);
+        }
+
+        let (mut spec, host_override) = match resolved {
+            Ok(resolved) => resolved,
+            Err(err) if self.scratch_message.is_some() => {
+                self.notify(Level::Error, format!("Scratch send: {}", err.message));
+                self.unresolved_send_granted = false;
+                self.body_send_granted = false;
+                return None;
+            }
+            Err(err) => {
+                self.url_error = Some((raw_url, err));
+                self.unresolved_send_granted = false;
+                self.body_send_granted = false;
+                return None;
+            }
+        };
+        self.url_error = None;
+        let settings = self.request_settings.clone();
+        if !self.body_send_granted {
+            let check = http::BodyCheck {
+                get_body: self.config.http.confirm_get_body,
+                delete_body: self.config.http.confirm_delete_body,
+                expects_body: settings.expects_body,
+            };
+            let method = spec.method.as_str();
+            if let Some(mismatch) = http::body_precheck(method, &spec.body, check) {
+                self.body_confirm = Some(BodyConfirm {
+                    method: method.to_string(),
+                    mismatch,
+                });
+                return None;
+            }
+        }
+        if self.needs_send_confirm(&spec.body) {
+            let lines = http::request_preview(&spec);
+            self.send_confirm = Some(SendConfirm { lines });
+            return None;
+        }
+        if !settings.skip_protect && !std::mem::take(&mut self.protected_send_granted) {
+            if let Some(confirm) = self.protected_send(spec.method.as_str(), &spec.url) {
+                self.notify(
+                    Level::Warn,
+                    format!("Protected: {} {} ({})", confirm.method, confirm.url, confirm.reason),
+                );
+                self.protected_confirm = Some(confirm);
+                return None;
+            }
+        }
+        self.unresolved_send_granted = false;
+        self.body_send_granted = false;
+        self.sent_host_override =
+            host_override.map(|(host, target)| format!("{} \u{2192} {}", host, target));
+
+        let client = match self.client_for_settings(&settings).and_then(|client| {
+            spec.options.preserve_header_case = self.header_case_connector(&settings)?;
+            Ok(client)
+        }) {
+            Ok(client) => client,
+            Err(err) => {
+                self.response = ResponseStatus::Error(http::RequestError::new(
+                    http::ErrorKind::Other,
+                    err,
+                ));
+                return None;
+            }
         };
-        self.request.headers_editor.set_cursor_style(cursor_style);
 
-        // Body editors — prepare based on body mode and sub-field
-        let body_text_focused = body_focused
-            && self.focus.body_field == BodyField::TextEditor
-            && self.request.body_mode.is_text_mode();
-        let body_binary_focused = body_focused
-            && self.focus.body_field == BodyField::BinaryPath
-            && self.request.body_mode == BodyMode::Binary;
+        Some((spec, client, secrets))
+    }
 
-        self.request
-            .body_editor
-            .set_block(Block::default().borders(Borders::NONE));
-        let cursor_style = if is_editing && body_text_focused {
-            self.vim_cursor_style()
-        } else {
-            Style::default().fg(Color::DarkGray)
+    /// Validates a JSON response against the schema its request had when
+    /// sent. Responses that aren't JSON, or were cut short, aren't checked.
+    fn check_schema(&mut self, request_id: Uuid, data: &ResponseData) {
+        let Some(setting) = self.sent_schema.take() else {
+            return;
         };
-        self.request.body_editor.set_cursor_style(cursor_style);
-
-        self.request
-            .body_binary_path_editor
-            .set_block(Block::default().borders(Borders::NONE));
-        let cursor_style = if is_editing && body_binary_focused {
-            self.vim_cursor_style()
-        } else {
-            Style::default().fg(Color::DarkGray)
+        if data.partial || data.is_binary || !is_json_like(&data.headers, &data.body) {
+            return;
+        }
+        let Ok(instance) = serde_json::from_str::<Value>(&data.body) else {
+            return;
         };
-        self.request
-            .body_binary_path_editor
-            .set_cursor_style(cursor_style);
+        let check = match self.load_schema(&setting) {
+            Ok(schema) => {
+                let violations = schema::validate(schema, &instance);
+                if violations.is_empty() {
+                    SchemaCheck::Passed
+                } else {
+                    SchemaCheck::Failed(violations)
+                }
+            }
+            Err(err) => SchemaCheck::SchemaError(err),
+        };
+        self.schema_checks.insert(request_id, check);
+    }
 
-        // KV cell edit textarea — update cursor style when active
-        let kv_cursor_style = if is_editing && body_focused && self.focus.body_field == BodyField::KvRow {
-            self.vim_cursor_style()
-        } else {
-            Style::default().fg(Color::DarkGray)
+    /// The schema a setting names, reading and checking files only when
+    /// they changed since last time.
+    fn load_schema<'a>(&'a mut self, setting: &'a SchemaSetting) -> Result<&'a Value, String> {
+        let path = match setting {
+            SchemaSetting::Inline { schema } => {
+                schema::check_schema(schema)?;
+                return Ok(schema);
+            }
+            SchemaSetting::File { path } => project_path(path),
         };
-        if let Some(ref mut kv_textarea) = self.kv_edit_textarea {
-            kv_textarea.set_cursor_style(kv_cursor_style);
+        let modified = std::fs::metadata(&path).and_then(|meta| meta.modified()).ok();
+        let stale = self
+            .schema_cache
+            .get(&path)
+            .is_none_or(|(cached, _)| cached.is_none() || *cached != modified);
+        if stale {
+            let parsed = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read schema {}: {}", path.display(), e))
+                .and_then(|text| {
+                    schema::parse_schema(&text)
+                        .map_err(|e| format!("{}: {}", path.display(), e))
+                });
+            self.schema_cache.insert(path.clone(), (modified, parsed));
         }
+        match &self.schema_cache[&path].1 {
+            Ok(schema) => Ok(schema),
+            Err(err) => Err(err.clone()),
+        }
+    }
 
-        // Auth editors — prepare only the ones relevant to current auth type
-        self.prepare_auth_editors();
+    /// The schema result of a request's latest response, for run reports.
+    fn schema_assertion(&self, request_id: Uuid) -> Option<runner::AssertionResult> {
+        self.schema_checks.get(&request_id).map(runner::check_schema)
+    }
 
-        // Response editor block/cursor
-        let response_editing = is_editing && self.focus.panel == Panel::Response;
-        self.response_editor.set_block(Block::default().borders(Borders::NONE));
-        self.response_headers_editor
-            .set_block(Block::default().borders(Borders::NONE));
-        let response_cursor = if response_editing {
-            self.vim_cursor_style()
-        } else {
-            Style::default().fg(Color::DarkGray)
+    /// The schema result for the response on screen.
+    pub fn current_schema_check(&self) -> Option<&SchemaCheck> {
+        if !matches!(self.response, ResponseStatus::Success(_)) {
+            return None;
+        }
+        self.schema_checks.get(&self.current_request_id?)
+    }
+
+    /// Values of the active environment's secret variables, as sent, for
+    /// redacting the execution log.
+    fn secret_values(&self, resolver: &Resolver) -> Vec<String> {
+        let Some(env) = self.active_environment() else {
+            return Vec::new();
         };
-        self.response_editor.set_cursor_style(response_cursor);
-        self.response_headers_editor
-            .set_cursor_style(response_cursor);
+        environment::chain_variables(env, &self.environments)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(var, _)| var)
+            .filter(|var| var.var_type == "secret")
+            .filter_map(|var| resolver.resolve(&var.key).ok().flatten())
+            .collect()
+    }
+
+    /// Sets up the execution log entry for a send about to go out, when
+    /// `log.file` is configured.
+    fn start_send_log(&mut self, url: &str, headers: &str, secrets: &[String]) {
+        self.pending_send_log = None;
+        if self.config.log.file.is_none() {
+            return;
+        }
+        let headers: Vec<(String, String)> = http::active_header_lines(headers)
+            .filter_map(|line| line.split_once(':'))
+            .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+            .collect();
+        let project = self
+            .project_list
+            .iter()
+            .find(|p| p.id == self.active_project_id)
+            .map(|p| p.name.clone())
+            .unwrap_or_default();
+        let request = self
+            .current_request_id
+            .map(|id| self.sidebar_tree.path_for(id).join("/"))
+            .unwrap_or_default();
+        let auth = match self.request.auth_type {
+            AuthType::NoAuth => None,
+            auth_type => Some(auth_type.as_str().to_string()),
+        };
+        self.pending_send_log = Some(sendlog::SendRecord {
+            timestamp: sendlog::timestamp(std::time::SystemTime::now()),
+            project,
+            request,
+            method: self.request.method.as_str().to_string(),
+            url: sendlog::redact(url, secrets),
+            headers: sendlog::redact_headers(&headers, secrets),
+            auth,
+            status: None,
+            error: None,
+            duration_ms: 0,
+            bytes: 0,
+        });
     }
 
-    fn prepare_auth_editors(&mut self) {
-        let is_editing = self.app_mode == AppMode::Editing;
-        let in_auth = self.focus.panel == Panel::Request
-            && self.focus.request_field == RequestField::Auth;
-        let auth_field = self.focus.auth_field;
-        let hidden_cursor = Style::default().fg(Color::DarkGray);
-        let vim_style = self.vim_cursor_style();
+    /// Completes and appends the pending execution log entry with the
+    /// status, duration and body size, or the error. Failing to write is
+    /// only reported; it never affects the request.
+    fn finish_send_log(&mut self, outcome: Result<(u16, u64, usize), String>) {
+        let Some(mut record) = self.pending_send_log.take() else {
+            return;
+        };
+        let Some(path) = self.config.log.file.clone() else {
+            return;
+        };
+        match outcome {
+            Ok((status, duration_ms, bytes)) => {
+                record.status = Some(status);
+                record.duration_ms = duration_ms;
+                record.bytes = bytes;
+            }
+            Err(err) => {
+                record.error = Some(err);
+                record.duration_ms = self
+                    .loading_started
+                    .map_or(0, |started| started.elapsed().as_millis() as u64);
+            }
+        }
+        let max_bytes = self.config.log.max_size_mb.saturating_mul(1024 * 1024);
+        match sendlog::append(&path, max_bytes, self.config.log.keep, &record) {
+            Ok(()) => self.send_log_error = None,
+            Err(err) => {
+                if self.send_log_error.as_ref() != Some(&err) {
+                    self.notify(Level::Warn, format!("Execution log: {}", err));
+                    self.send_log_error = Some(err);
+                }
+            }
+        }
+    }
 
-        let cursor_for = |field: AuthField| -> Style {
-            if is_editing && in_auth && auth_field == field {
-                vim_style
-            } else {
-                hidden_cursor
+    /// Handles a poll response: stops when the condition holds, the attempt
+    /// failed or attempts ran out, and otherwise schedules the next one.
+    fn advance_poll(&mut self) {
+        let Some(mut run) = self.poll.take() else {
+            return;
+        };
+        let attempts = run.attempt;
+        let (level, message) = match &self.response {
+            ResponseStatus::Success(data) if !data.partial => {
+                let response = chain::StoredResponse::from_data(data);
+                if chain::poll_condition_met(&run.setting.until, &response) {
+                    (Level::Info, format!("Poll condition met after {} attempts", attempts))
+                } else if attempts >= run.setting.max_attempts {
+                    (Level::Warn, format!("Poll gave up after {} attempts", attempts))
+                } else {
+                    let interval = Duration::from_secs(run.setting.interval_secs);
+                    run.next_at = Some(Instant::now() + interval);
+                    self.poll = Some(run);
+                    return;
+                }
             }
+            _ => (Level::Warn, format!("Polling stopped: attempt {} failed", attempts)),
         };
+        self.notify(level, message);
+    }
 
-        let auth_block = Block::default().borders(Borders::NONE);
+    fn resend_poll(&mut self, tx: mpsc::Sender<http::RequestEvent>) {
+        let Some(mut run) = self.poll.take() else {
+            return;
+        };
+        if self.current_request_id != Some(run.request_id) {
+            self.notify(Level::Warn, "Polling stopped: another request was opened");
+            return;
+        }
+        // The first send got past any protected-host or body confirmation
+        self.protected_send_granted = true;
+        self.body_send_granted = true;
+        self.send_request(tx);
+        self.protected_send_granted = false;
+        if matches!(self.response, ResponseStatus::Loading) {
+            run.attempt += 1;
+            run.next_at = None;
+            self.poll = Some(run);
+        } else {
+            self.poll = None;
+            self.notify(Level::Warn, "Polling stopped: the request could not be sent");
+        }
+    }
 
-        match self.request.auth_type {
-            AuthType::Bearer => {
-                self.request.auth_token_editor.set_block(auth_block);
-                self.request.auth_token_editor.set_cursor_style(cursor_for(AuthField::Token));
-            }
-            AuthType::Basic => {
-                self.request.auth_username_editor.set_block(auth_block.clone());
-                self.request.auth_username_editor.set_cursor_style(cursor_for(AuthField::Username));
-                self.request.auth_password_editor.set_block(auth_block);
-                self.request.auth_password_editor.set_cursor_style(cursor_for(AuthField::Password));
+    /// Status-line progress of a running poll.
+    pub fn poll_notice(&self) -> Option<String> {
+        let run = self.poll.as_ref()?;
+        let progress = format!("poll {}/{}", run.attempt, run.setting.max_attempts);
+        Some(match run.next_at {
+            Some(due) => {
+                let secs = due.saturating_duration_since(Instant::now()).as_secs_f32().ceil();
+                format!("{}, next in {}s", progress, secs as u64)
             }
-            AuthType::ApiKey => {
-                self.request.auth_key_name_editor.set_block(auth_block.clone());
-                self.request.auth_key_name_editor.set_cursor_style(cursor_for(AuthField::KeyName));
-                self.request.auth_key_value_editor.set_block(auth_block);
-                self.request.auth_key_value_editor.set_cursor_style(cursor_for(AuthField::KeyValue));
+            None => progress,
+        })
+    }
+
+    /// Whether Send should act as Cancel: a request is in flight, a poll
+    /// is waiting for its next attempt or a folder run is under way.
+    /// Sends the request sent last again with the current environment,
+    /// opening it first when another request is open.
+    fn resend_last(&mut self, tx: mpsc::Sender<http::RequestEvent>) {
+        if self.is_busy() {
+            self.notify(Level::Warn, "A request is still in flight");
+            return;
+        }
+        let Some(id) = self.sent_request_id.filter(|id| self.collection.get_item(*id).is_some())
+        else {
+            self.notify(Level::Warn, "Nothing sent yet");
+            return;
+        };
+        if self.current_request_id != Some(id) {
+            if self.app_mode == AppMode::Editing {
+                self.exit_editing();
             }
-            AuthType::NoAuth => {}
+            self.reveal_in_sidebar(id);
+            self.open_request(id);
+        }
+        self.send_request(tx);
+        if matches!(self.response, ResponseStatus::Loading) {
+            let name = self.collection.get_item(id).map(|item| item.name.clone());
+            self.notify(Level::Info, format!("Re-sent {}", name.unwrap_or_default()));
         }
     }
 
-    fn vim_cursor_style(&self) -> Style {
-        match self.vim.mode {
-            VimMode::Normal => Style::default()
-                .fg(Color::Reset)
-                .add_modifier(Modifier::REVERSED),
-            VimMode::Insert => Style::default()
-                .fg(Color::White)
-                .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
-            VimMode::Visual => Style::default()
-                .fg(Color::LightYellow)
-                .add_modifier(Modifier::REVERSED),
-            VimMode::Operator(_) => Style::default()
-                .fg(Color::LightGreen)
-                .add_modifier(Modifier::REVERSED),
+    pub fn is_busy(&self) -> bool {
+        matches!(self.response, ResponseStatus::Loading)
+            || self.poll.is_some()
+            || self.run.as_ref().is_some_and(|run| !run.finished)
+    }
+
+    fn open_run_setup(&mut self) {
+        if self.run.as_ref().is_some_and(|run| !run.finished) {
+            self.run_popup = Some(RunPopup::default());
+            return;
+        }
+        let Some(target) = self.sidebar_selected_id() else {
+            return;
+        };
+        match self.collection.get_item(target).map(runner::run_order) {
+            Some(requests) if !requests.is_empty() => {
+                let input = TextInput::new(String::new());
+                self.sidebar.popup = Some(SidebarPopup::RunSetup {
+                    target,
+                    input,
+                    export: false,
+                    parallel: None,
+                });
+            }
+            _ => self.notify(Level::Warn, "No requests to run here"),
         }
     }
 
-    async fn event_loop(&mut self) -> Result<()> {
-        let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
-        let (tx, mut rx) = mpsc::channel::<Result<ResponseData, String>>(1);
-        let mut last_spinner_tick = Instant::now();
-        let mut was_loading = false;
+    /// Starts running the requests under `target`, once per row of the
+    /// data file at `data_path` or once when it is empty. A data file that
+    /// doesn't parse stops the run before anything is sent. With `export`
+    /// each response is also written under the project's `runs/`. With
+    /// `parallel` that many steps are in flight at once, unless requests
+    /// here reference each other's responses or the app is offline, which
+    /// keep the run in order.
+    fn start_run(
+        &mut self,
+        target: Uuid,
+        data_path: &str,
+        export: bool,
+        parallel: Option<usize>,
+    ) -> Result<(), String> {
+        if self.is_busy() {
+            return Err("Wait for the current request to finish".to_string());
+        }
+        let item = self.collection.get_item(target).ok_or("Item not found")?;
+        let items: Vec<(Uuid, &PostmanItem)> = runner::run_order(item)
+            .into_iter()
+            .filter_map(|request| Some((Uuid::parse_str(&request.id).ok()?, request)))
+            .collect();
+        // Requests whose responses another one references go first
+        let depends: Vec<Vec<usize>> = items
+            .iter()
+            .enumerate()
+            .map(|(index, (_, item))| {
+                let names = item.request.as_ref().map(referenced_requests).unwrap_or_default();
+                (0..items.len())
+                    .filter(|&other| other != index && names.contains(&items[other].1.name))
+                    .collect()
+            })
+            .collect();
+        let order = runner::order_by_dependencies(&depends).map_err(|cycle| {
+            let names: Vec<&str> = cycle.iter().map(|&i| items[i].1.name.as_str()).collect();
+            format!("Requests reference each other in a loop: {}", names.join(" \u{2192} "))
+        })?;
+        let in_order = if depends.iter().any(|references| !references.is_empty()) {
+            Some("requests here use each other's responses")
+        } else if self.offline {
+            Some("offline")
+        } else {
+            None
+        };
+        let requests: Vec<Uuid> = order.iter().map(|&(index, _)| items[index].0).collect();
+        let depths: Vec<usize> = order.iter().map(|&(_, depth)| depth).collect();
+        let target_name = item.name.clone();
+        let (rows, data_file) = if data_path.is_empty() {
+            (vec![runner::DataRow::new()], None)
+        } else {
+            let path = config::expand_tilde(std::path::Path::new(data_path));
+            (runner::load_data_file(&path)?, Some(path.display().to_string()))
+        };
+        let export = if export {
+            let root = storage::find_project_root()
+                .ok_or("Exporting needs a project root to write runs/ in")?;
+            Some(runner::RunExport::create(&root, std::time::SystemTime::now())?)
+        } else {
+            None
+        };
+        self.save_current_request_if_dirty();
+        let iterations = rows
+            .into_iter()
+            .enumerate()
+            .map(|(i, variables)| runner::IterationReport {
+                iteration: i + 1,
+                variables,
+                results: Vec::new(),
+            })
+            .collect();
+        self.run = Some(FolderRun {
+            report: runner::RunReport {
+                target: target_name,
+                data_file,
+                iterations,
+                cancelled: false,
+            },
+            requests,
+            depths,
+            next: 0,
+            current: None,
+            finished: false,
+            export,
+            parallel: parallel.filter(|_| in_order.is_none()).map(ParallelSends::new),
+        });
+        self.run_popup = Some(RunPopup::default());
+        if let (Some(_), Some(reason)) = (parallel, in_order) {
+            self.notify(Level::Info, format!("Running in order: {}", reason));
+        }
+        Ok(())
+    }
 
-        while self.running {
-            let _loop_guard = perf::scope("event_loop_tick");
-            let is_loading = matches!(self.response, ResponseStatus::Loading);
-            if is_loading && !was_loading {
-                last_spinner_tick = Instant::now();
+    /// Sends the next step of the run. Steps that can't be sent are
+    /// recorded as failed and the run moves on.
+    fn advance_run(&mut self, tx: mpsc::Sender<http::RequestEvent>) {
+        loop {
+            let Some(run) = self.run.as_mut().filter(|run| run.is_waiting()) else {
+                return;
+            };
+            if run.next >= run.total_steps() {
+                run.finished = true;
+                let (passed, failed) = run.report.totals();
+                let level = if failed == 0 { Level::Info } else { Level::Warn };
+                let msg = format!("Run finished: {} passed, {} failed", passed, failed);
+                self.notify_run_end(level, msg);
+                return;
             }
-            was_loading = is_loading;
+            let step = RunStep {
+                iteration: run.next / run.requests.len(),
+                request_id: run.requests[run.next % run.requests.len()],
+                started: Instant::now(),
+            };
+            run.next += 1;
+            run.current = Some(step);
 
-            if let Ok(result) = rx.try_recv() {
-                if matches!(self.response, ResponseStatus::Loading) {
-                    self.response = match result {
-                        Ok(data) => ResponseStatus::Success(data),
-                        Err(e) => ResponseStatus::Error(e),
-                    };
-                    self.response_scroll = 0;
-                    self.response_tab = ResponseTab::Body;
-                    if let ResponseStatus::Success(ref data) = self.response {
-                        let formatted_body = format_json_if_possible(&data.headers, &data.body);
-                        let mut lines: Vec<String> =
-                            formatted_body.lines().map(String::from).collect();
-                        if lines.is_empty() {
-                            lines.push(String::new());
-                        }
-                        self.response_editor = TextArea::new(lines);
-                        self.response_editor.set_cursor_line_style(Style::default());
-                        let mut header_lines: Vec<String> = data
-                            .headers
-                            .iter()
-                            .map(|(k, v)| format!("{}: {}", k, v))
-                            .collect();
-                        if header_lines.is_empty() {
-                            header_lines.push(String::new());
-                        }
-                        self.response_headers_editor = TextArea::new(header_lines);
-                        self.response_headers_editor
-                            .set_cursor_line_style(Style::default());
-                        self.last_yank_response = self.response_editor.yank_text();
-                        self.last_yank_response_headers = self.response_headers_editor.yank_text();
-                        self.response_body_cache.dirty = true;
-                        self.response_headers_cache.dirty = true;
-                    }
-                    self.dirty = true;
-                }
-                self.request_handle = None;
+            if self.collection.get_item(step.request_id).is_none() {
+                self.record_run_step(Err("The request no longer exists".to_string()));
+                continue;
             }
-
-            if let Some((_, at)) = &self.clipboard_toast {
-                if at.elapsed() > Self::CLIPBOARD_TOAST_DURATION {
-                    self.clipboard_toast = None;
-                    self.dirty = true;
-                }
+            if self.app_mode == AppMode::Editing {
+                self.exit_editing();
             }
-
-            if is_loading && last_spinner_tick.elapsed() >= Self::SPINNER_TICK {
-                self.loading_tick = self.loading_tick.wrapping_add(1);
-                last_spinner_tick = Instant::now();
-                self.dirty = true;
+            self.open_request(step.request_id);
+            self.response = ResponseStatus::Empty;
+            let resolver = self.resolver();
+            if let Err(err) = self.check_response_references(&resolver) {
+                self.record_run_step(Err(err));
+                continue;
             }
-
-            if self.dirty {
-                self.prepare_editors();
-                let _draw_guard = perf::scope("terminal.draw");
-                terminal.draw(|frame| {
-                    let _render_guard = perf::scope("ui::render");
-                    ui::render(frame, self);
-                })?;
-                self.dirty = false;
+            if self.run.as_ref().is_some_and(|run| run.parallel.is_some()) {
+                self.send_parallel_step(step);
+                continue;
             }
-
-            let now = Instant::now();
-            let mut timeout = if is_loading {
-                let next_tick = last_spinner_tick + Self::SPINNER_TICK;
-                next_tick.saturating_duration_since(now)
-            } else {
-                Duration::from_millis(250)
-            };
-            if let Some((_, at)) = &self.clipboard_toast {
-                let deadline = *at + Self::CLIPBOARD_TOAST_DURATION;
-                let until_deadline = deadline.saturating_duration_since(now);
-                if until_deadline < timeout {
-                    timeout = until_deadline;
-                }
+            self.send_request(tx.clone());
+            // Runs send each request once, whatever its poll setting
+            self.poll = None;
+            if matches!(self.response, ResponseStatus::Loading) {
+                return;
             }
-            if timeout.is_zero() {
-                timeout = Duration::from_millis(1);
+            // Offline, the saved example stands in for the response
+            if let ResponseStatus::Success(data) = &self.response {
+                let status = data.status;
+                self.record_run_step(Ok(status));
+                continue;
             }
+            let error = self.unsent_reason();
+            self.record_run_step(Err(error));
+        }
+    }
+
+    /// Why the send just tried for a run step didn't go out, dropping the
+    /// confirmation it is waiting on.
+    fn unsent_reason(&mut self) -> String {
+        if self.send_confirm.take().is_some() {
+            "Not sent: the project isn't trusted".to_string()
+        } else if let Some(confirm) = self.protected_confirm.take() {
+            format!("Not sent: {} needs confirming ({})", confirm.method, confirm.reason)
+        } else if let Some(confirm) = self.unresolved_confirm.take() {
+            let names: Vec<String> =
+                confirm.names.iter().map(|(name, _)| format!("{{{{{}}}}}", name)).collect();
+            format!("Not sent: unresolved {}", names.join(", "))
+        } else if let Some(confirm) = self.body_confirm.take() {
+            format!("Not sent: {} needs confirming", confirm.describe())
+        } else if let Some((_, err)) = &self.url_error {
+            err.message.clone()
+        } else if let ResponseStatus::Error(err) = &self.response {
+            err.to_string()
+        } else {
+            "Not sent".to_string()
+        }
+    }
 
-            if event::poll(timeout)? {
-                if let Event::Key(key) = event::read()? {
-                    if key.kind == KeyEventKind::Press {
-                        self.handle_key(key, tx.clone());
-                        self.dirty = true;
-                    }
-                }
-            }
+    /// Sends the open request as `step` of a parallel run, in a task of its
+    /// own that reports back through the run's channel.
+    fn send_parallel_step(&mut self, step: RunStep) {
+        if self.request.url_text().is_empty() {
+            self.record_run_step(Err("URL is required".to_string()));
+            return;
         }
+        let Some((spec, client, _)) = self.prepare_send() else {
+            let error = self.unsent_reason();
+            self.record_run_step(Err(error));
+            return;
+        };
+        self.schema_checks.remove(&step.request_id);
+        let schema = self.request_settings.schema.clone();
+        let Some(run) = self.run.as_mut() else {
+            return;
+        };
+        let Some(sends) = run.parallel.as_mut() else {
+            return;
+        };
+        run.current = None;
+        let done = sends.done_tx.clone();
+        let sender = http::Sender::new(client);
+        let handle = tokio::spawn(async move {
+            let _ = done.send((step, sender.execute(spec).await));
+        });
+        sends.in_flight.push(InFlightStep {
+            step,
+            schema,
+            handle: handle.abort_handle(),
+        });
+    }
 
-        Ok(())
+    /// Records the parallel sends that have come back since the last tick.
+    fn collect_run_results(&mut self) {
+        loop {
+            let Some(sends) = self.run.as_mut().and_then(|run| run.parallel.as_mut()) else {
+                return;
+            };
+            let Ok((step, result)) = sends.done_rx.try_recv() else {
+                return;
+            };
+            let Some(at) = sends.in_flight.iter().position(|sent| sent.step.is(&step)) else {
+                continue;
+            };
+            let sent = sends.in_flight.remove(at);
+            self.sent_schema = sent.schema;
+            let elapsed_ms = step.started.elapsed().as_millis() as u64;
+            self.note_response(step.request_id, &result, elapsed_ms);
+            let outcome = match &result {
+                Ok(data) => Ok(data.status),
+                Err(err) => Err(err.to_string()),
+            };
+            self.record_run_result(step, outcome, result.as_ref().ok());
+            self.mark_dirty(DirtyPanels::ALL);
+        }
     }
 
-    fn handle_key(&mut self, key: KeyEvent, tx: mpsc::Sender<Result<ResponseData, String>>) {
-        match self.app_mode {
-            AppMode::Navigation => self.handle_navigation_mode(key, tx),
-            AppMode::Editing => self.handle_editing_mode(key, tx),
-            AppMode::Sidebar => self.handle_sidebar_mode(key),
+    /// Records the response that just arrived for the run step in flight.
+    fn finish_run_step(&mut self) {
+        if self.run.as_ref().and_then(|run| run.current).is_none() {
+            return;
         }
+        let outcome = match &self.response {
+            ResponseStatus::Success(data) if !data.partial => Ok(data.status),
+            ResponseStatus::Success(_) => Err("Download cancelled".to_string()),
+            ResponseStatus::Error(err) => Err(err.to_string()),
+            _ => Err("No response".to_string()),
+        };
+        self.record_run_step(outcome);
+    }
+
+    fn record_run_step(&mut self, outcome: Result<u16, String>) {
+        let Some(step) = self.run.as_mut().and_then(|run| run.current.take()) else {
+            return;
+        };
+        let response = std::mem::take(&mut self.response);
+        let data = match &response {
+            ResponseStatus::Success(data) => Some(data),
+            _ => None,
+        };
+        self.record_run_result(step, outcome, data);
+        self.response = response;
     }
 
-    fn handle_navigation_mode(
+    /// Adds the result of `step` to the report, in run order, exporting
+    /// `data` when the run is exported.
+    fn record_run_result(
         &mut self,
-        key: KeyEvent,
-        tx: mpsc::Sender<Result<ResponseData, String>>,
+        step: RunStep,
+        outcome: Result<u16, String>,
+        data: Option<&ResponseData>,
     ) {
-        // Handle help overlay first
-        if self.show_help {
-            if key.code == KeyCode::Char('?') || key.code == KeyCode::Esc {
-                self.show_help = false;
-            }
+        let Some(run) = self.run.as_mut() else {
             return;
-        }
-
-        // Handle environment popup when open
-        if self.show_env_popup {
-            match key.code {
-                KeyCode::Char('j') | KeyCode::Down => {
-                    let count = self.environments.len() + 1; // +1 for "No Environment"
-                    self.env_popup_index = (self.env_popup_index + 1) % count;
-                }
-                KeyCode::Char('k') | KeyCode::Up => {
-                    let count = self.environments.len() + 1;
-                    self.env_popup_index =
-                        (self.env_popup_index + count - 1) % count;
-                }
-                KeyCode::Enter => {
-                    self.active_environment_name = if self.env_popup_index == 0 {
+        };
+        let item = self.collection.get_item(step.request_id);
+        let (status, error) = match outcome {
+            Ok(status) => (Some(status), None),
+            Err(err) => (None, Some(err)),
+        };
+        let name = item.map(|item| item.name.clone()).unwrap_or_default();
+        let mut export_error = None;
+        let file = match (&mut run.export, data, status) {
+            (Some(export), Some(data), Some(_)) => {
+                match export.write_response(&name, &data.bytes, &data.headers) {
+                    Ok(file) => Some(file),
+                    Err(err) => {
+                        export_error = Some(err);
                         None
-                    } else {
-                        Some(self.environments[self.env_popup_index - 1].name.clone())
-                    };
-                    self.show_env_popup = false;
-                }
-                KeyCode::Esc | KeyCode::Char('q') => {
-                    self.show_env_popup = false;
+                    }
                 }
-                _ => {}
             }
-            self.dirty = true;
-            return;
-        }
-
-        // Handle body mode popup when open
-        if self.show_body_mode_popup {
-            self.handle_body_mode_popup(key);
-            return;
+            _ => None,
+        };
+        let result = runner::StepResult {
+            request_id: step.request_id.to_string(),
+            name,
+            method: item
+                .and_then(|item| item.request.as_ref())
+                .map(|request| request.method.clone())
+                .unwrap_or_default(),
+            status,
+            duration_ms: step.started.elapsed().as_millis() as u64,
+            error,
+            assertions: status
+                .map(runner::check_response)
+                .unwrap_or_default()
+                .into_iter()
+                .chain(status.and(
+                    self.schema_checks.get(&step.request_id).map(runner::check_schema),
+                ))
+                .collect(),
+            file,
+        };
+        let requests = &run.requests;
+        run.report.iterations[step.iteration]
+            .insert_ordered(result, |request_id| run_rank(requests, request_id));
+        if let Some(err) = export_error {
+            self.notify(Level::Error, err);
         }
+    }
 
-        // Handle auth type popup when open
-        if self.show_auth_type_popup {
-            self.handle_auth_type_popup(key);
-            return;
+    /// Reports the end of a run, after writing its summary when the run
+    /// is exported.
+    fn notify_run_end(&mut self, level: Level, msg: String) {
+        let summary = self.run.as_ref().and_then(|run| {
+            let export = run.export.as_ref()?;
+            Some(export.write_summary(&run.report).map(|()| export.dir.display().to_string()))
+        });
+        match summary {
+            Some(Ok(dir)) => self.notify(level, format!("{}; exported to {}", msg, dir)),
+            Some(Err(err)) => {
+                self.notify(level, msg);
+                self.notify(Level::Error, err);
+            }
+            None => self.notify(level, msg),
         }
+    }
 
-        // Handle method popup navigation when open
-        if self.show_method_popup {
-            let popup_item_count = HttpMethod::ALL.len() + 1; // 7 standard + "Custom..."
+    /// Status-line progress of a folder run.
+    pub fn run_notice(&self) -> Option<String> {
+        let run = self.run.as_ref().filter(|run| !run.finished)?;
+        let done = run.report.results().count();
+        Some(match &run.parallel {
+            Some(sends) => {
+                format!("run {}/{}, {} at once", done, run.total_steps(), sends.in_flight())
+            }
+            None => format!("run {}/{}", done + 1, run.total_steps()),
+        })
+    }
 
-            if self.method_popup_custom_mode {
-                // Text input mode for custom method
-                match key.code {
-                    KeyCode::Enter => {
-                        let input = self.method_custom_input.trim().to_string();
-                        if !input.is_empty()
-                            && input.is_ascii()
-                            && !input.contains(char::is_whitespace)
-                        {
-                            self.request.method = Method::Custom(input.to_uppercase());
-                            self.show_method_popup = false;
-                            self.method_popup_custom_mode = false;
-                            self.request_dirty = true;
-                        }
-                    }
-                    KeyCode::Esc => {
-                        self.method_custom_input.clear();
-                        self.method_popup_custom_mode = false;
-                        self.show_method_popup = false;
-                    }
-                    KeyCode::Backspace => {
-                        self.method_custom_input.pop();
-                    }
-                    KeyCode::Char(c) if c.is_ascii() && !c.is_whitespace() => {
-                        if self.method_custom_input.len() < 20 {
-                            self.method_custom_input.push(c.to_ascii_uppercase());
-                        }
-                    }
-                    _ => {}
-                }
-            } else {
-                // Standard popup navigation mode
-                match key.code {
-                    KeyCode::Down | KeyCode::Char('j') => {
-                        self.method_popup_index =
-                            (self.method_popup_index + 1) % popup_item_count;
-                    }
-                    KeyCode::Up | KeyCode::Char('k') => {
-                        self.method_popup_index = if self.method_popup_index == 0 {
-                            popup_item_count - 1
-                        } else {
-                            self.method_popup_index - 1
-                        };
-                    }
-                    KeyCode::Enter => {
-                        if self.method_popup_index < HttpMethod::ALL.len() {
-                            self.request.method = Method::Standard(
-                                HttpMethod::from_index(self.method_popup_index),
-                            );
-                            self.show_method_popup = false;
-                            self.request_dirty = true;
-                        } else {
-                            // "Custom..." selected — enter text input mode
-                            self.method_popup_custom_mode = true;
+    fn handle_run_popup(&mut self, key: KeyEvent) {
+        let Some(popup) = self.run_popup.as_mut() else {
+            return;
+        };
+        if let Some(input) = popup.save.as_mut() {
+            match key.code {
+                KeyCode::Esc => popup.save = None,
+                KeyCode::Enter if !input.value.trim().is_empty() => {
+                    let path = config::expand_tilde(std::path::Path::new(input.value.trim()));
+                    let result = match &self.run {
+                        Some(run) => run.report.save(&path),
+                        None => Err("There is no run to save".to_string()),
+                    };
+                    match result {
+                        Ok(()) => {
+                            popup.save = None;
+                            let msg = format!("Saved run report to {}", path.display());
+                            self.notify(Level::Info, msg);
                         }
+                        Err(err) => self.notify(Level::Error, err),
                     }
-                    KeyCode::Esc => {
-                        self.show_method_popup = false;
-                    }
-                    _ => {}
                 }
+                _ => handle_text_input(input, key),
             }
             return;
         }
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => popup.scroll = popup.scroll.saturating_add(1),
+            KeyCode::Up | KeyCode::Char('k') => popup.scroll = popup.scroll.saturating_sub(1),
+            KeyCode::Home | KeyCode::Char('g') => popup.scroll = 0,
+            // Clamped to the last page when drawn
+            KeyCode::End | KeyCode::Char('G') => popup.scroll = u16::MAX,
+            KeyCode::Char('s') => popup.save = Some(TextInput::new("run-report.json".to_string())),
+            KeyCode::Char('x') if self.run.as_ref().is_some_and(|run| !run.finished) => {
+                self.cancel_request();
+            }
+            KeyCode::Esc | KeyCode::Char('q') => self.run_popup = None,
+            _ => {}
+        }
+    }
 
-        if self.sidebar.popup.is_some() {
-            self.handle_sidebar_popup(key);
-            return;
+    fn compress_body(&self, settings: &RequestSettings) -> bool {
+        match settings.compress {
+            CompressSetting::Inherit => self.config.http.compress_body,
+            CompressSetting::Gzip => true,
+            CompressSetting::Off => false,
         }
+    }
 
-        let in_request = self.focus.panel == Panel::Request;
-        let in_response = self.focus.panel == Panel::Response;
-        let in_sidebar = self.focus.panel == Panel::Sidebar;
+    /// Whether a send would add `Content-Encoding: gzip`, given a body of
+    /// at least [`http::COMPRESS_MIN_BYTES`].
+    pub fn implies_gzip_body(&self) -> bool {
+        let compressible = matches!(
+            self.request.body_mode,
+            BodyMode::Raw | BodyMode::Json | BodyMode::Xml | BodyMode::FormUrlEncoded
+        );
+        compressible
+            && self.compress_body(&self.request_settings)
+            && !http::has_content_encoding_header(&self.request.headers_text())
+    }
 
-        // Ctrl+E toggles sidebar
-        if key.code == KeyCode::Char('e') && key.modifiers.contains(KeyModifiers::CONTROL) {
-            self.sidebar_visible = !self.sidebar_visible;
-            if self.sidebar_visible {
-                self.focus_sidebar();
-            } else {
-                if self.focus.panel == Panel::Sidebar {
-                    self.focus.panel = Panel::Request;
-                    self.focus.request_field = RequestField::Url;
+    fn send_options(&self, settings: &RequestSettings) -> http::SendOptions {
+        let follow_redirects = match settings.redirects {
+            RedirectSetting::Inherit => self.config.http.follow_redirects,
+            RedirectSetting::Follow => true,
+            RedirectSetting::Stop => false,
+        };
+        http::SendOptions {
+            accept_encoding: self.config.http.accept_encoding,
+            compress_body: self.compress_body(settings),
+            max_redirects: follow_redirects.then_some(self.config.http.max_redirects as usize),
+            timeout: (self.config.http.timeout > 0)
+                .then(|| Duration::from_secs(self.config.http.timeout)),
+            normalize_line_endings: !settings.preserve_line_endings,
+            user_agent: settings.user_agent.clone().or_else(|| self.config.http.user_agent.clone()),
+            // Set from `header_case_connector` by the callers that send
+            preserve_header_case: None,
+        }
+    }
+
+    /// The current request, resolved and built the way a send would build
+    /// it, as an HTTP message.
+    fn resolved_raw_message(&mut self) -> Result<String, String> {
+        let resolver = self.resolver();
+        self.check_response_references(&resolver)?;
+        let (spec, _) = self.resolved_spec(&resolver).map_err(|e| e.message)?;
+        let client = self.client_for_settings(&self.request_settings.clone())?;
+        let request = http::build_request(&client, spec).map_err(|e| e.to_string())?;
+        Ok(http::raw_message(&request))
+    }
+
+    /// The open request as a send makes it: variables substituted, auth
+    /// resolved and any `[hosts]` override applied. Sending, the raw view
+    /// and the send preview all build their request here.
+    fn resolved_spec(
+        &self,
+        resolver: &Resolver,
+    ) -> Result<(http::RequestSpec, Option<(String, config::HostTarget)>), http::UrlError> {
+        let scratch = self.scratch_message.as_ref();
+        let (method, raw_url, raw_headers) = match scratch {
+            Some(scratch) => (
+                Method::from_str(&scratch.message.method),
+                scratch.message.url.clone(),
+                scratch.headers_text(),
+            ),
+            None => {
+                (self.request.method.clone(), self.request.url_text(), self.request.headers_text())
+            }
+        };
+        let url = self.resolved_url(&raw_url, resolver)?;
+        let (url, host_override) = self.apply_host_override(url);
+        let (headers, _) = resolver.substitute(&raw_headers);
+        let body = match scratch {
+            Some(scratch) if scratch.body_edited => {
+                self.scratch_body_content(&scratch.message.body, resolver)
+            }
+            _ => self.build_resolved_body_content(resolver),
+        };
+        let spec = http::RequestSpec {
+            method,
+            url,
+            headers,
+            body,
+            auth: match scratch {
+                Some(scratch) if scratch.preflight => http::AuthConfig::NoAuth,
+                _ => self.build_resolved_auth_config(resolver),
+            },
+            options: self.send_options(&self.request_settings),
+        };
+        Ok((spec, host_override))
+    }
+
+    /// Opens the send preview: the request fully resolved, nothing sent.
+    fn open_send_preview(&mut self) {
+        let resolver = self.resolver();
+        let mut secrets = self.secret_values(&resolver);
+        let resolved = self
+            .check_response_references(&resolver)
+            .and_then(|()| self.resolved_spec(&resolver).map_err(|e| e.message));
+        let hidden = [
+            (AuthField::Token, &self.request.auth_token_editor),
+            (AuthField::Password, &self.request.auth_password_editor),
+            (AuthField::KeyValue, &self.request.auth_key_value_editor),
+        ];
+        let mut basic_hidden = false;
+        for (field, editor) in hidden {
+            let masked = match field {
+                AuthField::Token => self.privacy_mode,
+                _ => self.privacy_mode || !self.revealed_secrets.contains(&field),
+            };
+            if masked {
+                let (value, _) = resolver.substitute(&editor.lines().join(""));
+                basic_hidden |= field == AuthField::Password && !value.is_empty();
+                secrets.push(value);
+            }
+        }
+        basic_hidden &= self.request.auth_type == AuthType::Basic;
+        let preview = resolved.and_then(|(mut spec, _)| {
+            let settings = self.request_settings.clone();
+            let client = self.client_for_settings(&settings)?;
+            spec.options.preserve_header_case = self.header_case_connector(&settings)?;
+            http::send_preview(&client, spec).map_err(|e| e.to_string())
+        });
+        let preview = preview.map(|mut preview| {
+            preview.url = sendlog::redact(&preview.url, &secrets);
+            for (_, value, source) in preview.headers.iter_mut() {
+                *value = if *source == http::HeaderSource::Auth && basic_hidden {
+                    format!("Basic {}", environment::SECRET_MASK)
+                } else {
+                    sendlog::redact(value, &secrets)
+                };
+            }
+            preview.body = truncate_preview_body(sendlog::redact(&preview.body, &secrets));
+            preview
+        });
+        self.send_preview = Some(SendPreviewPopup { preview, scroll: 0 });
+    }
+
+    /// Opens the codec popup on the visual selection, or on the clipboard
+    /// when nothing is selected.
+    fn open_codec_popup(&mut self) {
+        let selection = match self.active_yank_target() {
+            Some(target) if self.vim.mode == VimMode::Visual => {
+                let textarea = match target {
+                    YankTarget::Request => self.active_request_editor(),
+                    YankTarget::ResponseBody => Some(&mut self.response_editor),
+                    YankTarget::ResponseHeaders => Some(&mut self.response_headers_editor),
+                };
+                textarea.filter(|t| t.is_selecting()).map(|textarea| {
+                    // Read the selection without clobbering the yank register
+                    let register = textarea.yank_text();
+                    textarea.copy();
+                    let text = textarea.yank_text();
+                    textarea.set_yank_text(register);
+                    let source = match target {
+                        YankTarget::Request => CodecSource::RequestSelection,
+                        _ => CodecSource::ResponseSelection,
+                    };
+                    (text, source)
+                })
+            }
+            _ => None,
+        };
+        let (input, source) = match selection {
+            Some(selection) => selection,
+            None => match self.clipboard.get_text() {
+                Ok(text) => (text, CodecSource::Clipboard),
+                Err(err) => {
+                    self.notify(Level::Warn, format!("Nothing selected; clipboard {}", err));
+                    return;
+                }
+            },
+        };
+        self.codec_popup = Some(CodecPopup::new(input, source));
+    }
+
+    fn handle_codec_popup(&mut self, key: KeyEvent) {
+        let Some(popup) = self.codec_popup.as_mut() else {
+            return;
+        };
+        let count = codec::Transform::ALL.len();
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => popup.select((popup.index + 1) % count),
+            KeyCode::Up | KeyCode::Char('k') => popup.select((popup.index + count - 1) % count),
+            KeyCode::Enter | KeyCode::Char('y') => {
+                if let Ok(result) = popup.result.clone() {
+                    let what = popup.transform().label().to_lowercase();
+                    self.codec_popup = None;
+                    self.copy_to_clipboard(&what, result);
+                }
+            }
+            KeyCode::Char('r') if popup.can_replace() => {
+                let result = popup.result.clone().unwrap_or_default();
+                self.codec_popup = None;
+                if self.refuse_locked_edit() {
+                    return;
                 }
-                if matches!(self.app_mode, AppMode::Sidebar) {
-                    self.app_mode = AppMode::Navigation;
+                if let Some(textarea) = self.active_request_editor() {
+                    textarea.cut();
+                    textarea.insert_str(&result);
                 }
+                self.vim = Vim::new(VimMode::Normal);
+                self.request_dirty = true;
+                self.update_terminal_cursor();
+            }
+            KeyCode::Esc | KeyCode::Char('q') => self.codec_popup = None,
+            _ => {}
+        }
+    }
+
+    /// Pins the current response for comparison, or clears the pin.
+    fn toggle_pin(&mut self) {
+        if self.pinned_response.take().is_some() {
+            self.compare_view = None;
+            self.notify(Level::Info, "Pin cleared");
+            return;
+        }
+        match &self.response {
+            ResponseStatus::Success(data) => {
+                self.pinned_response = Some(data.clone());
+                self.notify(Level::Info, "Response pinned; send again and press C to compare");
             }
+            _ => self.notify(Level::Warn, "No response to pin"),
+        }
+    }
+
+    fn open_compare_view(&mut self) {
+        let Some(pinned) = self.pinned_response.as_ref() else {
+            self.notify(Level::Warn, "Pin a response with p first");
+            return;
+        };
+        let ResponseStatus::Success(ref current) = self.response else {
+            self.notify(Level::Warn, "No response to compare with the pin");
             return;
-        }
+        };
+        self.compare_view = Some(CompareView::new(pinned, current, &self.json_style));
+    }
+
+    /// Status-line note while a response is pinned.
+    pub fn pin_notice(&self) -> Option<String> {
+        let pinned = self.pinned_response.as_ref()?;
+        Some(format!("pinned {} ({} ms)", pinned.status, pinned.duration_ms))
+    }
+
+    fn open_raw_view(&mut self) {
+        let text = self
+            .resolved_raw_message()
+            .unwrap_or_else(|err| format!("Can't build the request: {}", err));
+        self.raw_view = Some(RawView {
+            text,
+            scroll: 0,
+            editor: None,
+            error: None,
+            original_body: String::new(),
+        });
+    }
 
-        if key.code == KeyCode::Char('e') && key.modifiers.is_empty() {
-            self.focus_sidebar();
+    /// Switches the raw view to editing the unresolved message.
+    fn edit_raw_view(&mut self) {
+        if self.refuse_locked_edit() {
             return;
         }
+        let (mut editor, body) = self.unresolved_message_editor();
+        editor.set_cursor_line_style(Style::default());
+        if let Some(view) = self.raw_view.as_mut() {
+            view.editor = Some(editor);
+            view.error = None;
+            view.original_body = body;
+        }
+    }
 
-        // Ctrl+P: project switcher
-        if key.code == KeyCode::Char('p') && key.modifiers.contains(KeyModifiers::CONTROL) {
-            if self.sidebar_visible {
-                self.open_project_switcher();
-            }
-            return;
+    /// An editor on the request as an unresolved message, and the body
+    /// text it was rendered with.
+    fn unresolved_message_editor(&self) -> (TextArea<'static>, String) {
+        let headers_text = self.request.headers_text();
+        let headers = http::active_header_lines(&headers_text)
+            .map(|line| match line.split_once(':') {
+                Some((key, value)) => (key.trim().to_string(), value.trim().to_string()),
+                None => (line.trim().to_string(), String::new()),
+            })
+            .collect();
+        let body = self.request.body_text();
+        let message = raw_http::render(&raw_http::RawRequest {
+            method: self.request.method.as_str().to_string(),
+            url: self.request.url_text(),
+            headers,
+            body: body.clone(),
+        });
+        let editor = TextArea::new(message.split('\n').map(str::to_string).collect());
+        (editor, body)
+    }
+
+    /// Opens the scratch send as it was last put away for this request,
+    /// or filled from the request.
+    fn open_scratch_send(&mut self) {
+        let scratch = match self.scratch_drafts.remove(&self.current_request_id) {
+            Some(scratch) => scratch,
+            None => self.fresh_scratch(),
+        };
+        self.scratch_send = Some(scratch);
+    }
+
+    fn fresh_scratch(&self) -> ScratchSend {
+        let (mut editor, original_body) = self.unresolved_message_editor();
+        editor.set_cursor_line_style(Style::default());
+        ScratchSend {
+            editor,
+            error: None,
+            original_body,
         }
+    }
 
-        // Ctrl+[ / Ctrl+]: resize sidebar
-        if key.modifiers.contains(KeyModifiers::CONTROL) {
-            match key.code {
-                KeyCode::Char('[') => {
-                    self.sidebar_width = clamp_sidebar_width(self.sidebar_width.saturating_sub(2));
-                    self.persist_ui_state();
-                    return;
-                }
-                KeyCode::Char(']') => {
-                    self.sidebar_width = clamp_sidebar_width(self.sidebar_width.saturating_add(2));
-                    self.persist_ui_state();
-                    return;
-                }
-                _ => {}
-            }
+    /// Closes the scratch send, keeping its text for next time.
+    fn put_away_scratch(&mut self) {
+        if let Some(scratch) = self.scratch_send.take() {
+            self.scratch_drafts.insert(self.current_request_id, scratch);
         }
+    }
 
-        // Ctrl+S: save current request
-        if key.code == KeyCode::Char('s') && key.modifiers.contains(KeyModifiers::CONTROL) {
-            if let Some(request_id) = self.current_request_id {
-                if let Err(err) = self.save_request_by_id(request_id) {
-                    self.response = ResponseStatus::Error(err);
-                } else {
-                    self.request_dirty = false;
-                }
-            }
+    fn handle_scratch_send(&mut self, key: KeyEvent, tx: mpsc::Sender<http::RequestEvent>) {
+        let Some(scratch) = self.scratch_send.as_mut() else {
             return;
+        };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => self.put_away_scratch(),
+            KeyCode::Char('s') if ctrl => self.send_scratch(tx),
+            KeyCode::Char('l') if ctrl => self.scratch_send = Some(self.fresh_scratch()),
+            _ if input::is_scratch_send(key) => self.send_scratch(tx),
+            _ => {
+                scratch.editor.input(key);
+            }
         }
+    }
 
-        // Ctrl+R: send request or cancel if loading
-        if key.code == KeyCode::Char('r') && key.modifiers.contains(KeyModifiers::CONTROL) {
-            if matches!(self.response, ResponseStatus::Loading) {
-                self.cancel_request();
-            } else {
-                self.send_request(tx);
-            }
+    /// Sends the scratch message in place of the request, whose fields,
+    /// dirty flag and saved copy are left alone. On a parse error the
+    /// scratch stays open with the text as typed.
+    fn send_scratch(&mut self, tx: mpsc::Sender<http::RequestEvent>) {
+        if self.is_busy() {
+            self.notify(Level::Warn, "A request is still in flight");
             return;
         }
-
-        // Ctrl+N: environment quick-switch popup
-        if key.code == KeyCode::Char('n') && key.modifiers.contains(KeyModifiers::CONTROL) {
-            self.show_method_popup = false;
-            self.show_auth_type_popup = false;
-            self.show_body_mode_popup = false;
-            self.show_env_popup = !self.show_env_popup;
-            if self.show_env_popup {
-                self.env_popup_index = self
-                    .active_environment_name
-                    .as_ref()
-                    .and_then(|name| self.environments.iter().position(|e| e.name == *name))
-                    .map(|i| i + 1)
-                    .unwrap_or(0);
-            }
-            self.dirty = true;
+        let Some(scratch) = self.scratch_send.as_mut() else {
             return;
+        };
+        let text = scratch.editor.lines().join("\n");
+        let scheme = if self.request.url_text().starts_with("http://") { "http" } else { "https" };
+        let message = match raw_http::parse(&text, scheme) {
+            Ok(message) => message,
+            Err(err) => {
+                scratch.error = Some(err);
+                return;
+            }
+        };
+        scratch.error = None;
+        let body_edited = message.body != scratch.original_body;
+        let summary = format!("{} {}", message.method, message.url);
+        self.put_away_scratch();
+        self.scratch_message = Some(ScratchMessage {
+            message,
+            body_edited,
+            preflight: false,
+        });
+        self.send_request(tx);
+        if matches!(self.response, ResponseStatus::Loading) {
+            self.notify(Level::Info, format!("Scratch send: {}", summary));
         }
+    }
 
-        // Ctrl+h/l: horizontal navigation in input row
-        if in_request && key.modifiers.contains(KeyModifiers::CONTROL) {
-            match key.code {
-                KeyCode::Char('h') => {
-                    self.prev_horizontal();
-                    return;
-                }
-                KeyCode::Char('l') => {
-                    self.next_horizontal();
-                    return;
-                }
-                KeyCode::Char('j') => {
-                    self.next_vertical();
-                    return;
-                }
-                KeyCode::Char('k') => {
-                    self.prev_vertical();
-                    return;
-                }
-                _ => {}
-            }
+    /// An edited scratch body, sent as JSON or XML when the request's body
+    /// is, and as raw text otherwise.
+    fn scratch_body_content(&self, text: &str, resolver: &Resolver) -> http::BodyContent {
+        let (text, _) = resolver.substitute(text);
+        if text.trim().is_empty() {
+            return http::BodyContent::None;
         }
+        match self.request.body_mode {
+            BodyMode::Json => http::BodyContent::Json(text),
+            BodyMode::Xml => http::BodyContent::Xml(text),
+            _ => http::BodyContent::Raw(text),
+        }
+    }
 
-        // Auth sub-field navigation: j/k navigates within auth fields when focused
-        if in_request && self.focus.request_field == RequestField::Auth {
-            match key.code {
-                KeyCode::Down | KeyCode::Char('j') => {
-                    self.next_auth_field();
-                    return;
+    /// Parses the edited message into the request. On a parse error the
+    /// editor stays open with the text as typed.
+    fn external_edit_target(&self) -> Option<ExternalEdit> {
+        match self.focus.panel {
+            Panel::Request => match self.focus.request_field {
+                RequestField::Headers => Some(ExternalEdit::RequestHeaders),
+                RequestField::Body if self.content_body_field() == BodyField::TextEditor => {
+                    Some(ExternalEdit::RequestBody)
                 }
-                KeyCode::Up | KeyCode::Char('k') => {
-                    self.prev_auth_field();
-                    return;
+                _ => None,
+            },
+            Panel::Response => match (&self.response, self.response_tab) {
+                (ResponseStatus::Success(_), ResponseTab::Body) => Some(ExternalEdit::ResponseBody),
+                (ResponseStatus::Success(_), ResponseTab::Headers) => {
+                    Some(ExternalEdit::ResponseHeaders)
                 }
-                _ => {}
-            }
+                _ => None,
+            },
+            Panel::Sidebar => None,
         }
+    }
 
-        // Body sub-field navigation
-        if in_request && self.focus.request_field == RequestField::Body {
-            // KV-specific keys when on a KV row
-            if self.focus.body_field == BodyField::KvRow {
-                match key.code {
-                    KeyCode::Down | KeyCode::Char('j') => {
-                        self.next_body_field();
-                        return;
-                    }
-                    KeyCode::Up | KeyCode::Char('k') => {
-                        self.prev_body_field();
-                        return;
-                    }
-                    KeyCode::Tab | KeyCode::Char('l') | KeyCode::Right => {
-                        self.kv_next_column();
-                        return;
-                    }
-                    KeyCode::BackTab | KeyCode::Char('h') | KeyCode::Left => {
-                        self.kv_prev_column();
-                        return;
-                    }
-                    KeyCode::Char('a') | KeyCode::Char('o') => {
-                        self.kv_add_row();
-                        return;
-                    }
-                    KeyCode::Char('d') => {
-                        self.kv_delete_row();
-                        return;
-                    }
-                    KeyCode::Char(' ') => {
-                        self.kv_toggle_enabled();
-                        return;
-                    }
-                    KeyCode::Char('t') => {
-                        self.kv_toggle_multipart_type();
-                        return;
-                    }
-                    _ => {}
-                }
-            } else {
-                // Non-KV body fields: j/k navigation
-                match key.code {
-                    KeyCode::Down | KeyCode::Char('j') => {
-                        self.next_body_field();
-                        return;
-                    }
-                    KeyCode::Up | KeyCode::Char('k') => {
-                        self.prev_body_field();
-                        return;
-                    }
-                    _ => {}
-                }
-            }
+    fn request_external_edit(&mut self) {
+        let target = self.external_edit_target();
+        let edits_request =
+            matches!(target, Some(ExternalEdit::RequestHeaders | ExternalEdit::RequestBody));
+        if edits_request && self.refuse_locked_edit() {
+            return;
+        }
+        match target {
+            Some(target) => self.pending_external_edit = Some(target),
+            None => self.notify(
+                Level::Warn,
+                "Focus the headers, the body or a response to use the external editor",
+            ),
         }
+    }
 
-        // Arrow keys + bare hjkl for navigation
-        match key.code {
-            KeyCode::Left | KeyCode::Char('h') => {
-                self.prev_horizontal();
+    /// Re-indents a JSON body in place. Broken JSON is reported and left
+    /// as it was.
+    fn format_body(&mut self) {
+        if self.refuse_locked_edit() {
+            return;
+        }
+        if self.request.body_mode != BodyMode::Json {
+            self.notify(Level::Info, "Only JSON bodies can be formatted");
+            return;
+        }
+        let text = self.request.body_text();
+        let formatted = match format::format_json(&text, &self.json_style) {
+            Ok(formatted) => formatted,
+            Err(err) => {
+                self.notify(Level::Error, err);
                 return;
             }
-            KeyCode::Right | KeyCode::Char('l') => {
-                self.next_horizontal();
-                return;
+        };
+        if formatted == text {
+            return;
+        }
+        let (url, headers) = (self.request.url_text(), self.request.headers_text());
+        self.request.set_message(self.request.method.clone(), url, headers, Some(formatted));
+        self.apply_editor_tab_size();
+        self.request_dirty = true;
+        self.mark_dirty(DirtyPanels::REQUEST);
+        self.notify(Level::Info, "Body formatted");
+    }
+
+    /// Suspends the TUI, runs the editor on the target's text and loads the
+    /// result back. The terminal is restored whatever the editor did; on
+    /// failure the original text is left alone.
+    fn edit_externally(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+        target: ExternalEdit,
+    ) -> Result<()> {
+        let (text, extension) = match target {
+            ExternalEdit::RequestHeaders => (self.request.headers_text(), "txt"),
+            ExternalEdit::RequestBody => {
+                let extension = match self.request.body_mode {
+                    BodyMode::Json => "json",
+                    BodyMode::Xml => "xml",
+                    _ => "txt",
+                };
+                (self.request.body_text(), extension)
             }
-            KeyCode::Up | KeyCode::Char('k') => {
-                self.prev_vertical();
-                return;
+            ExternalEdit::ResponseBody => {
+                let extension = match self.response {
+                    ResponseStatus::Success(ref data) if !self.response_hex_view => {
+                        response_file_extension(data)
+                    }
+                    _ => "txt",
+                };
+                (self.response_editor.lines().join("\n"), extension)
             }
-            KeyCode::Down | KeyCode::Char('j') => {
-                self.next_vertical();
-                return;
+            ExternalEdit::ResponseHeaders => {
+                (self.response_headers_editor.lines().join("\n"), "txt")
             }
-            _ => {}
-        }
+        };
 
-        match key.code {
-            KeyCode::Char('?') => {
-                self.show_help = !self.show_help;
+        let edited = self.suspended(terminal, || editor::edit_text(&text, extension))?;
+        let edited = match edited {
+            Ok(edited) => edited,
+            Err(err) => {
+                self.notify(Level::Error, err);
+                return Ok(());
             }
-            // Enter: activate focused element
-            KeyCode::Enter => {
-                if in_sidebar {
-                    self.app_mode = AppMode::Sidebar;
-                } else if in_request {
-                    match self.focus.request_field {
-                        RequestField::Method => {
-                            match &self.request.method {
-                                Method::Standard(m) => {
-                                    self.method_popup_index = m.index();
-                                    self.method_custom_input.clear();
-                                }
-                                Method::Custom(s) => {
-                                    self.method_popup_index = HttpMethod::ALL.len();
-                                    self.method_custom_input = s.clone();
-                                }
-                            }
-                            self.method_popup_custom_mode = false;
-                            self.show_method_popup = true;
-                        }
-                        RequestField::Send => {
-                            if matches!(self.response, ResponseStatus::Loading) {
-                                self.cancel_request();
-                            } else {
-                                self.send_request(tx);
-                            }
-                        }
-                        RequestField::Url | RequestField::Headers => {
-                            self.enter_editing(VimMode::Normal);
-                        }
-                        RequestField::Body => {
-                            self.handle_body_enter();
-                        }
-                        RequestField::Auth => {
-                            self.handle_auth_enter();
-                        }
-                    }
-                } else if in_response
-                    && matches!(self.response, ResponseStatus::Success(_))
-                {
-                    self.enter_editing(VimMode::Normal);
+        };
+        if edited == text {
+            return Ok(());
+        }
+        let lines: Vec<String> = edited.split('\n').map(str::to_string).collect();
+        match target {
+            ExternalEdit::RequestHeaders | ExternalEdit::RequestBody => {
+                let (headers, body) = if target == ExternalEdit::RequestHeaders {
+                    (edited, None)
+                } else {
+                    (self.request.headers_text(), Some(edited))
+                };
+                let url = self.request.url_text();
+                self.request.set_message(self.request.method.clone(), url, headers, body);
+                self.apply_editor_tab_size();
+                self.request_dirty = true;
+                if self.app_mode == AppMode::Editing {
+                    self.vim = Vim::new(VimMode::Normal);
                 }
+                self.notify(Level::Info, "Updated from the external editor");
             }
-            // i on editable field: enter vim insert mode directly
-            KeyCode::Char('i') => {
-                if in_sidebar {
-                    self.app_mode = AppMode::Sidebar;
-                } else if in_request && self.focus.request_field == RequestField::Body {
-                    self.handle_body_enter();
-                } else if in_request && self.is_editable_field() {
-                    self.enter_editing(VimMode::Insert);
-                } else if in_request
-                    && self.focus.request_field == RequestField::Auth
-                    && self.is_auth_text_field()
-                {
-                    self.enter_editing(VimMode::Insert);
-                } else if in_response
-                    && matches!(self.response, ResponseStatus::Success(_))
-                {
-                    self.enter_editing(VimMode::Normal);
-                }
+            ExternalEdit::ResponseBody => {
+                self.response_editor = TextArea::new(lines);
+                self.response_editor.set_cursor_line_style(Style::default());
+                self.response_body_cache.dirty = true;
             }
-            KeyCode::Char('q') => {
-                self.save_current_request_if_dirty();
-                self.persist_session_state();
-                self.running = false;
+            ExternalEdit::ResponseHeaders => {
+                self.response_headers_editor = TextArea::new(lines);
+                self.response_headers_editor.set_cursor_line_style(Style::default());
+                self.response_headers_cache.dirty = true;
             }
-            _ => {}
         }
+        Ok(())
     }
 
-    fn handle_sidebar_mode(&mut self, key: KeyEvent) {
-        if self.show_help {
-            if key.code == KeyCode::Char('?') || key.code == KeyCode::Esc {
-                self.show_help = false;
+    /// Runs `f` with the TUI suspended, so a child process can have the
+    /// terminal, and redraws everything afterwards.
+    fn suspended<T>(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+        f: impl FnOnce() -> T,
+    ) -> Result<T> {
+        self.restore_terminal()?;
+        let result = f();
+        self.setup_terminal()?;
+        terminal.clear()?;
+        self.mark_dirty(DirtyPanels::ALL);
+        Ok(result)
+    }
+
+    /// The body `|` and `!` work on: the response as received, or the
+    /// request body when it has focus.
+    fn pipe_input(&self) -> Option<Vec<u8>> {
+        match self.focus.panel {
+            Panel::Response => match self.response {
+                ResponseStatus::Success(ref data) => Some(data.bytes.clone()),
+                _ => None,
+            },
+            Panel::Request
+                if self.focus.request_field == RequestField::Body
+                    && self.content_body_field() == BodyField::TextEditor =>
+            {
+                Some(self.request.body_text().into_bytes())
             }
+            _ => None,
+        }
+    }
+
+    fn open_pager(&mut self) {
+        match self.pipe_input() {
+            Some(input) => self.pending_pager = Some(input),
+            None => self.notify(Level::Warn, "Focus the request body or a response to page it"),
+        }
+    }
+
+    fn page_externally(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+        input: &[u8],
+    ) -> Result<()> {
+        if let Err(err) = self.suspended(terminal, || pager::page(input))? {
+            self.notify(Level::Error, err);
+        }
+        Ok(())
+    }
+
+    fn open_pipe_prompt(&mut self) {
+        if self.pipe_input().is_none() {
+            self.notify(Level::Warn, "Focus the request body or a response to pipe it");
             return;
         }
+        let input = TextInput::new(self.last_pipe_command.clone());
+        self.pipe_popup = Some(PipePopup::Command(input));
+    }
 
-        // Ctrl+N: environment quick-switch popup from sidebar mode
-        if key.code == KeyCode::Char('n') && key.modifiers.contains(KeyModifiers::CONTROL) {
-            self.show_method_popup = false;
-            self.show_auth_type_popup = false;
-            self.show_body_mode_popup = false;
-            self.show_env_popup = !self.show_env_popup;
-            if self.show_env_popup {
-                self.env_popup_index = self
-                    .active_environment_name
-                    .as_ref()
-                    .and_then(|name| self.environments.iter().position(|e| e.name == *name))
-                    .map(|i| i + 1)
-                    .unwrap_or(0);
+    fn handle_pipe_popup(&mut self, key: KeyEvent) {
+        let Some(popup) = self.pipe_popup.take() else {
+            return;
+        };
+        match popup {
+            PipePopup::Command(mut input) => match key.code {
+                KeyCode::Esc => {}
+                KeyCode::Enter => {
+                    let command = input.value.trim().to_string();
+                    if command.is_empty() {
+                        self.pipe_popup = Some(PipePopup::Command(input));
+                        return;
+                    }
+                    let Some(body) = self.pipe_input() else {
+                        return;
+                    };
+                    self.last_pipe_command = command.clone();
+                    match pager::pipe_through(&command, &body) {
+                        Ok(output) => {
+                            self.pipe_popup = Some(PipePopup::Output {
+                                command,
+                                output,
+                                scroll: 0,
+                            })
+                        }
+                        Err(err) => self.notify(Level::Error, err),
+                    }
+                }
+                _ => {
+                    handle_text_input(&mut input, key);
+                    self.pipe_popup = Some(PipePopup::Command(input));
+                }
+            },
+            PipePopup::Output {
+                command,
+                output,
+                mut scroll,
+            } => {
+                match key.code {
+                    KeyCode::Esc | KeyCode::Char('q') => return,
+                    KeyCode::Down | KeyCode::Char('j') => scroll = scroll.saturating_add(1),
+                    KeyCode::Up | KeyCode::Char('k') => scroll = scroll.saturating_sub(1),
+                    KeyCode::PageDown => scroll = scroll.saturating_add(20),
+                    KeyCode::PageUp => scroll = scroll.saturating_sub(20),
+                    KeyCode::Home | KeyCode::Char('g') => scroll = 0,
+                    _ => {}
+                }
+                self.pipe_popup = Some(PipePopup::Output {
+                    command,
+                    output,
+                    scroll,
+                });
             }
-            self.dirty = true;
-            return;
         }
+    }
 
-        if key.code == KeyCode::Esc {
-            if self.sidebar.popup.is_some() {
-                self.sidebar.popup = None;
-            }
-            if !self.sidebar.search_query.is_empty() {
-                self.sidebar.search_query.clear();
-            }
-            self.app_mode = AppMode::Navigation;
+    fn apply_raw_edit(&mut self) {
+        let Some(view) = self.raw_view.as_mut() else {
             return;
-        }
-
-        if self.sidebar.popup.is_some() {
-            self.handle_sidebar_popup(key);
+        };
+        let Some(editor) = view.editor.as_ref() else {
             return;
+        };
+        let text = editor.lines().join("\n");
+        let url = self.request.url_text();
+        let scheme = if url.starts_with("http://") { "http" } else { "https" };
+        let parsed = match raw_http::parse(&text, scheme) {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                view.error = Some(err);
+                return;
+            }
+        };
+        let body_changed = parsed.body != view.original_body;
+        // Disabled headers aren't part of the message; keep them as they were
+        let disabled = self
+            .request
+            .headers_text()
+            .lines()
+            .filter(|line| line.trim_start().starts_with('#'))
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        let headers = parsed
+            .headers
+            .iter()
+            .map(|(key, value)| format!("{}: {}", key, value))
+            .chain(disabled)
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.request.set_message(
+            Method::from_str(&parsed.method),
+            parsed.url,
+            headers,
+            body_changed.then_some(parsed.body),
+        );
+        if body_changed && !matches!(self.request.body_mode, BodyMode::Json | BodyMode::Xml) {
+            self.request.body_mode = BodyMode::Raw;
         }
-
-        self.handle_sidebar_key(key);
+        self.apply_editor_tab_size();
+        self.request_dirty = true;
+        self.raw_view = None;
+        self.notify(Level::Info, "Request updated from the raw message");
     }
 
-    fn handle_editing_mode(
-        &mut self,
-        key: KeyEvent,
-        tx: mpsc::Sender<Result<ResponseData, String>>,
-    ) {
-        // Ctrl+S: save current request
-        if key.code == KeyCode::Char('s') && key.modifiers.contains(KeyModifiers::CONTROL) {
-            if let Some(request_id) = self.current_request_id {
-                if let Err(err) = self.save_request_by_id(request_id) {
-                    self.response = ResponseStatus::Error(err);
-                } else {
-                    self.request_dirty = false;
+    fn handle_raw_view(&mut self, key: KeyEvent) {
+        let Some(view) = self.raw_view.as_mut() else {
+            return;
+        };
+        if let Some(editor) = view.editor.as_mut() {
+            match key.code {
+                KeyCode::Esc => {
+                    view.editor = None;
+                    view.error = None;
+                }
+                KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.apply_raw_edit();
+                }
+                _ => {
+                    editor.input(key);
                 }
             }
             return;
         }
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => view.scroll = view.scroll.saturating_add(1),
+            KeyCode::Up | KeyCode::Char('k') => view.scroll = view.scroll.saturating_sub(1),
+            KeyCode::Char('e') => self.edit_raw_view(),
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('V') => self.raw_view = None,
+            _ => {}
+        }
+    }
 
-        // Ctrl+R: send request or cancel if loading, even in editing mode
-        if key.code == KeyCode::Char('r') && key.modifiers.contains(KeyModifiers::CONTROL) {
-            if matches!(self.response, ResponseStatus::Loading) {
-                self.cancel_request();
-            } else {
-                self.send_request(tx);
-            }
+    fn handle_url_outline(&mut self, key: KeyEvent) {
+        let Some(popup) = self.url_outline.as_mut() else {
             return;
+        };
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => popup.scroll = popup.scroll.saturating_add(1),
+            KeyCode::Up | KeyCode::Char('k') => popup.scroll = popup.scroll.saturating_sub(1),
+            KeyCode::Char('y') => {
+                let url = popup.url.clone();
+                self.copy_to_clipboard("URL", url);
+            }
+            KeyCode::Char('Y') => {
+                let decoded = http::decode_url(&popup.url);
+                self.copy_to_clipboard("decoded URL", decoded);
+            }
+            // q with or without Ctrl, which opened it
+            KeyCode::Esc | KeyCode::Char('q') => self.url_outline = None,
+            _ => {}
         }
+    }
 
-        // Ctrl+N: environment quick-switch popup, even in editing mode
-        if key.code == KeyCode::Char('n') && key.modifiers.contains(KeyModifiers::CONTROL) {
-            self.show_method_popup = false;
-            self.show_auth_type_popup = false;
-            self.show_body_mode_popup = false;
-            self.show_env_popup = !self.show_env_popup;
-            if self.show_env_popup {
-                self.env_popup_index = self
-                    .active_environment_name
-                    .as_ref()
-                    .and_then(|name| self.environments.iter().position(|e| e.name == *name))
-                    .map(|i| i + 1)
-                    .unwrap_or(0);
-            }
-            self.dirty = true;
+    fn handle_send_preview(&mut self, key: KeyEvent) {
+        let Some(popup) = self.send_preview.as_mut() else {
             return;
+        };
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => popup.scroll = popup.scroll.saturating_add(1),
+            KeyCode::Up | KeyCode::Char('k') => popup.scroll = popup.scroll.saturating_sub(1),
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('P') => self.send_preview = None,
+            _ => {}
         }
+    }
 
-        // Enter in URL insert mode: send request (or cancel if loading), then exit editing
-        if self.focus.panel == Panel::Request
-            && self.focus.request_field == RequestField::Url
-            && self.vim.mode == VimMode::Insert
-            && key.code == KeyCode::Enter
-        {
-            if matches!(self.response, ResponseStatus::Loading) {
-                self.cancel_request();
-            } else {
-                self.send_request(tx);
-            }
-            self.exit_editing();
+    /// Asks for the origin to check the open request's CORS preflight from.
+    fn open_cors_check(&mut self) {
+        if self.request.url_text().is_empty() {
+            self.notify(Level::Warn, "The request has no URL to check");
+            return;
+        }
+        if self.offline {
+            self.notify(Level::Warn, "Offline: a preflight can't be checked against an example");
             return;
         }
+        let origin = self.config.http.cors_origin.clone().unwrap_or_default();
+        self.cors_popup = Some(CorsPopup {
+            origin: TextInput::new(origin),
+            preflight: None,
+            verdict: None,
+        });
+    }
 
-        let is_request = self.focus.panel == Panel::Request;
-        let is_response = self.focus.panel == Panel::Response;
-        let is_request_vim_switch = is_request
-            && matches!(self.vim.mode, VimMode::Normal | VimMode::Insert);
-        let is_response_vim_switch = is_response
-            && matches!(
-                self.vim.mode,
-                VimMode::Normal | VimMode::Visual | VimMode::Operator(_)
-            );
+    /// The preflight a browser on `origin` would send before the open
+    /// request: its headers as sent, less what the browser sets itself.
+    fn cors_preflight(&mut self, origin: &str) -> Result<cors::Preflight, String> {
+        let resolver = self.resolver();
+        self.check_response_references(&resolver)?;
+        let (spec, _) = self.resolved_spec(&resolver).map_err(|e| e.message)?;
+        let method = spec.method.as_str().to_string();
+        let client = self.client_for_settings(&self.request_settings.clone())?;
+        let preview = http::send_preview(&client, spec).map_err(|e| e.to_string())?;
+        let headers: Vec<(String, String)> = preview
+            .headers
+            .into_iter()
+            .filter(|(name, _, source)| {
+                let automatic = *source == http::HeaderSource::Automatic;
+                !automatic || name.eq_ignore_ascii_case("content-type")
+            })
+            .map(|(name, value, _)| (name, value))
+            .collect();
+        Ok(cors::Preflight::new(origin, &method, &headers))
+    }
 
-        if is_request_vim_switch {
-            match key.code {
-                KeyCode::Char('H') => {
-                    self.prev_request_tab();
-                    return;
-                }
-                KeyCode::Char('L') => {
-                    self.next_request_tab();
-                    return;
-                }
-                _ => {}
+    fn handle_cors_popup(&mut self, key: KeyEvent, tx: mpsc::Sender<http::RequestEvent>) {
+        let Some(popup) = self.cors_popup.as_mut() else {
+            return;
+        };
+        if popup.preflight.is_some() {
+            if matches!(key.code, KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('K')) {
+                self.cors_popup = None;
             }
+            return;
         }
-
-        if is_response_vim_switch {
-            match key.code {
-                KeyCode::Char('H') => {
-                    self.prev_response_tab();
+        match key.code {
+            KeyCode::Esc => self.cors_popup = None,
+            KeyCode::Enter => {
+                let origin = popup.origin.value.trim().to_string();
+                if !cors::is_origin(&origin) {
+                    let msg = format!("'{}' isn't an origin like https://app.example.com", origin);
+                    self.notify(Level::Error, msg);
                     return;
                 }
-                KeyCode::Char('L') => {
-                    self.next_response_tab();
+                if self.is_busy() {
+                    self.notify(Level::Warn, "A request is still in flight");
                     return;
                 }
-                _ => {}
+                let preflight = match self.cors_preflight(&origin) {
+                    Ok(preflight) => preflight,
+                    Err(err) => {
+                        self.notify(Level::Error, err);
+                        return;
+                    }
+                };
+                self.scratch_message = Some(ScratchMessage {
+                    message: raw_http::RawRequest {
+                        method: "OPTIONS".to_string(),
+                        url: self.request.url_text(),
+                        headers: preflight.request_headers(),
+                        body: String::new(),
+                    },
+                    body_edited: true,
+                    preflight: true,
+                });
+                self.send_request(tx);
+                let pending = matches!(self.response, ResponseStatus::Loading)
+                    || self.send_confirm.is_some()
+                    || self.protected_confirm.is_some()
+                    || self.unresolved_confirm.is_some()
+                    || self.body_confirm.is_some();
+                if let Some(popup) = self.cors_popup.as_mut().filter(|_| pending) {
+                    popup.preflight = Some(preflight);
+                }
             }
+            _ => handle_text_input(&mut popup.origin, key),
         }
+    }
 
-        let is_clipboard_modifier = key.modifiers.contains(KeyModifiers::CONTROL)
-            || key.modifiers.contains(KeyModifiers::SUPER);
-
-        if is_request {
-            if key.code != KeyCode::Esc {
-                self.request_dirty = true;
+    /// Judges the preflight that just came back, when one was sent.
+    fn finish_cors_check(&mut self) {
+        let Some(popup) = self.cors_popup.as_mut().filter(|popup| popup.verdict.is_none()) else {
+            return;
+        };
+        let Some(preflight) = &popup.preflight else {
+            return;
+        };
+        popup.verdict = Some(match &self.response {
+            ResponseStatus::Success(data) => {
+                Ok(cors::evaluate(preflight, data.status, &data.headers))
             }
-        }
+            ResponseStatus::Error(err) => Err(err.to_string()),
+            _ => Err("No response".to_string()),
+        });
+    }
 
-        if is_clipboard_modifier && matches!(key.code, KeyCode::Char('v') | KeyCode::Char('V')) {
-            if !is_response {
-                self.handle_clipboard_paste_shortcut();
-            }
-            return;
+    /// The validation error from the last send attempt, as long as the URL
+    /// hasn't been edited since.
+    pub fn url_error(&self) -> Option<&http::UrlError> {
+        match &self.url_error {
+            Some((typed, err)) if *typed == self.request.url_text() => Some(err),
+            _ => None,
         }
+    }
 
-        if is_clipboard_modifier && matches!(key.code, KeyCode::Char('c') | KeyCode::Char('C')) {
-            self.handle_clipboard_copy_shortcut();
-            return;
+    /// The substituted, normalized URL when it differs from what was typed.
+    pub fn url_preview(&self) -> Option<String> {
+        let raw_url = self.request.url_text();
+        if raw_url.trim().is_empty() {
+            return None;
         }
-
-        if matches!(self.vim.mode, VimMode::Normal | VimMode::Visual)
-            && key.modifiers.is_empty()
-            && key.code == KeyCode::Char('p')
-        {
-            if let Some(target) = self.active_yank_target() {
-                match self.clipboard.get_text() {
-                    Ok(text) => {
-                        match target {
-                            YankTarget::Request => {
-                                if let Some(textarea) =
-                                    self.active_request_editor()
-                                {
-                                    textarea.set_yank_text(text.clone());
-                                }
-                            }
-                            YankTarget::ResponseBody => {
-                                self.response_editor.set_yank_text(text.clone());
-                            }
-                            YankTarget::ResponseHeaders => {
-                                self.response_headers_editor.set_yank_text(text.clone());
-                            }
-                        }
-                        self.update_last_yank(target, text);
-                    }
-                    Err(_) => {
-                        self.set_clipboard_toast("Clipboard read failed; using internal yank");
-                    }
-                }
-            }
+        let mut resolver = self.resolver();
+        if self.privacy_mode {
+            let env = self.active_environment();
+            environment::mask_secrets(&mut resolver.variables, env, &self.environments);
+        }
+        let normalized = self.resolved_url(&raw_url, &resolver).ok()?;
+        if normalized == raw_url {
+            None
+        } else {
+            Some(normalized)
         }
+    }
 
-        let input: Input = key.into();
+    fn resolver(&self) -> Resolver<'_> {
+        let mut variables = self.active_variables().unwrap_or_default();
+        if let Some(row) = self.run.as_ref().and_then(FolderRun::current_variables) {
+            variables.extend(row.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+        Resolver {
+            variables,
+            app: self,
+        }
+    }
 
-        let transition = if is_response {
-            let response_tab = self.response_tab;
-            let vim = &self.vim;
-            match response_tab {
-                ResponseTab::Body => {
-                    vim.transition_read_only(input, &mut self.response_editor, false)
-                }
-                ResponseTab::Headers => {
-                    vim.transition_read_only(input, &mut self.response_headers_editor, false)
-                }
+    /// Every text field of the current request that goes through
+    /// substitution when it is sent.
+    fn request_templates(&self) -> Vec<String> {
+        let (url, headers, body) = match &self.scratch_message {
+            Some(scratch) => {
+                (scratch.message.url.clone(), scratch.headers_text(), scratch.message.body.clone())
             }
-        } else if let Some(textarea) = self.kv_edit_textarea.as_mut() {
-            // KV cell editing — route vim input to the temporary textarea
-            self.vim.transition(input, textarea, true)
-        } else {
-            let field = self.focus.request_field;
-            let single_line = field == RequestField::Url
-                || (field == RequestField::Auth && self.is_auth_text_field());
-            if let Some(textarea) = self.request.active_editor(field, self.focus.body_field) {
-                self.vim.transition(input, textarea, single_line)
-            } else {
-                self.exit_editing();
-                return;
+            None => {
+                (self.request.url_text(), self.request.headers_text(), self.request.body_text())
             }
         };
-
-        match transition {
-            Transition::ExitField => {
-                self.commit_kv_cell_edit();
-                self.exit_editing();
+        let mut templates = vec![
+            url,
+            headers,
+            body,
+            self.request.body_binary_path_text(),
+        ];
+        match self.request.auth_type {
+            AuthType::NoAuth => {}
+            AuthType::Bearer => templates.push(self.request.auth_token_text()),
+            AuthType::Basic => {
+                templates.push(self.request.auth_username_text());
+                templates.push(self.request.auth_password_text());
             }
-            Transition::Mode(new_mode) => {
-                if is_response {
-                    let response_tab = self.response_tab;
-                    let vim = std::mem::replace(&mut self.vim, Vim::new(VimMode::Normal));
-                    let new_vim = match response_tab {
-                        ResponseTab::Body => vim.apply_transition(
-                            Transition::Mode(new_mode),
-                            &mut self.response_editor,
-                        ),
-                        ResponseTab::Headers => vim.apply_transition(
-                            Transition::Mode(new_mode),
-                            &mut self.response_headers_editor,
-                        ),
-                    };
-                    self.vim = new_vim;
-                } else if let Some(textarea) = self.kv_edit_textarea.as_mut() {
-                    self.vim = std::mem::replace(&mut self.vim, Vim::new(VimMode::Normal))
-                        .apply_transition(Transition::Mode(new_mode), textarea);
-                } else {
-                    let textarea = self
-                        .request
-                        .active_editor(self.focus.request_field, self.focus.body_field)
-                        .unwrap();
-                    self.vim = std::mem::replace(&mut self.vim, Vim::new(VimMode::Normal))
-                        .apply_transition(Transition::Mode(new_mode), textarea);
+            AuthType::ApiKey => {
+                templates.push(self.request.auth_key_name_text());
+                templates.push(self.request.auth_key_value_text());
+            }
+        }
+        for pair in self.request.body_form_pairs.iter().filter(|p| p.enabled) {
+            templates.push(pair.key.clone());
+            templates.push(pair.value.clone());
+        }
+        for field in self.request.body_multipart_fields.iter().filter(|f| f.enabled) {
+            templates.push(field.key.clone());
+            templates.push(field.value.clone());
+        }
+        templates
+    }
+
+    /// The text fields a send actually uses, labelled for messages: the URL,
+    /// enabled headers, the body of the current mode and the auth fields.
+    fn sent_fields(&self) -> Vec<(&'static str, String)> {
+        let scratch = self.scratch_message.as_ref();
+        let (url, headers_text) = match scratch {
+            Some(scratch) => (scratch.message.url.clone(), scratch.headers_text()),
+            None => (self.request.url_text(), self.request.headers_text()),
+        };
+        let headers = http::active_header_lines(&headers_text).collect::<Vec<_>>().join("\n");
+        let mut fields = vec![("URL", url), ("Headers", headers)];
+        let edited_body = scratch.filter(|scratch| scratch.body_edited);
+        match self.request.body_mode {
+            _ if edited_body.is_some() => {
+                fields.extend(edited_body.map(|scratch| ("Body", scratch.message.body.clone())));
+            }
+            _ if self.request.body_disabled => {}
+            BodyMode::Raw | BodyMode::Json | BodyMode::Xml => {
+                fields.push(("Body", self.request.body_text()));
+            }
+            BodyMode::FormUrlEncoded => {
+                for pair in self.request.body_form_pairs.iter().filter(|p| p.enabled) {
+                    fields.push(("Body", pair.key.clone()));
+                    fields.push(("Body", pair.value.clone()));
                 }
-                self.update_terminal_cursor();
-                self.sync_clipboard_from_active_yank();
             }
-            Transition::Pending(pending_input) => {
-                if is_response {
-                    let response_tab = self.response_tab;
-                    let vim = std::mem::replace(&mut self.vim, Vim::new(VimMode::Normal));
-                    let new_vim = match response_tab {
-                        ResponseTab::Body => vim.apply_transition(
-                            Transition::Pending(pending_input),
-                            &mut self.response_editor,
-                        ),
-                        ResponseTab::Headers => vim.apply_transition(
-                            Transition::Pending(pending_input),
-                            &mut self.response_headers_editor,
-                        ),
-                    };
-                    self.vim = new_vim;
-                } else if let Some(textarea) = self.kv_edit_textarea.as_mut() {
-                    self.vim = std::mem::replace(&mut self.vim, Vim::new(VimMode::Normal))
-                        .apply_transition(Transition::Pending(pending_input), textarea);
-                } else {
-                    let textarea = self
-                        .request
-                        .active_editor(self.focus.request_field, self.focus.body_field)
-                        .unwrap();
-                    self.vim = std::mem::replace(&mut self.vim, Vim::new(VimMode::Normal))
-                        .apply_transition(Transition::Pending(pending_input), textarea);
+            BodyMode::Multipart => {
+                for field in self.request.body_multipart_fields.iter().filter(|f| f.enabled) {
+                    fields.push(("Body", field.key.clone()));
+                    fields.push(("Body", field.value.clone()));
                 }
             }
-            Transition::Nop => {}
+            BodyMode::Binary => fields.push(("Body", self.request.body_binary_path_text())),
+        }
+        match self.request.auth_type {
+            _ if self.request.auth_disabled => {}
+            AuthType::NoAuth => {}
+            AuthType::Bearer => fields.push(("Auth", self.request.auth_token_text())),
+            AuthType::Basic => {
+                fields.push(("Auth", self.request.auth_username_text()));
+                fields.push(("Auth", self.request.auth_password_text()));
+            }
+            AuthType::ApiKey => {
+                fields.push(("Auth", self.request.auth_key_name_text()));
+                fields.push(("Auth", self.request.auth_key_value_text()));
+            }
         }
+        fields
     }
 
-    fn enter_editing(&mut self, mode: VimMode) {
-        self.app_mode = AppMode::Editing;
-        self.vim = Vim::new(mode);
-        self.update_terminal_cursor();
+    /// Fails with a user-facing message when a `{{Request.body...}}`
+    /// reference in the request can't be resolved yet.
+    fn check_response_references(&self, resolver: &Resolver) -> Result<(), String> {
+        for template in self.request_templates() {
+            let (_, unresolved) = environment::substitute_with(&template, |_| None);
+            for name in unresolved {
+                resolver.resolve(&name).map_err(|err| format!("{{{{{}}}}}: {}", name, err))?;
+            }
+        }
+        Ok(())
     }
 
-    fn exit_editing(&mut self) {
-        self.app_mode = AppMode::Navigation;
-        self.vim = Vim::new(VimMode::Normal);
-        let _ = stdout().execute(SetCursorStyle::DefaultUserShape);
+    fn resolved_url(&self, raw_url: &str, resolver: &Resolver) -> Result<String, http::UrlError> {
+        let (url, _) = resolver.substitute(raw_url);
+        http::normalize_url(&url, &self.config.http.default_scheme)
     }
 
-    fn update_terminal_cursor(&self) {
-        let style = match self.vim.mode {
-            VimMode::Normal => SetCursorStyle::SteadyBlock,
-            VimMode::Insert => SetCursorStyle::BlinkingUnderScore,
-            VimMode::Visual => SetCursorStyle::SteadyBlock,
-            VimMode::Operator(_) => SetCursorStyle::SteadyBlock,
-        };
-        let _ = stdout().execute(style);
+    /// The `[hosts]` override that applies to the current request's URL.
+    pub fn current_host_override(&self) -> Option<(String, config::HostTarget)> {
+        if self.config.hosts.is_empty() {
+            return None;
+        }
+        let url = self.resolved_url(&self.request.url_text(), &self.resolver()).ok()?;
+        let host = reqwest::Url::parse(&url).ok()?.host_str()?.to_string();
+        let target = self.config.host_override(&host)?;
+        Some((host, target))
     }
 
-    fn send_request(&mut self, tx: mpsc::Sender<Result<ResponseData, String>>) {
-        let raw_url = self.request.url_text();
-        if raw_url.is_empty() {
-            self.response = ResponseStatus::Error("URL is required".to_string());
-            return;
+    /// Rewrites the URL port when the matching `[hosts]` target carries one.
+    fn apply_host_override(&self, url: String) -> (String, Option<(String, config::HostTarget)>) {
+        let Ok(mut parsed) = reqwest::Url::parse(&url) else {
+            return (url, None);
+        };
+        let Some(host) = parsed.host_str().map(str::to_string) else {
+            return (url, None);
+        };
+        let Some(target) = self.config.host_override(&host) else {
+            return (url, None);
+        };
+        match target.port {
+            Some(port) if parsed.set_port(Some(port)).is_ok() => {
+                (parsed.to_string(), Some((host, target)))
+            }
+            _ => (url, Some((host, target))),
         }
+    }
 
-        if matches!(self.response, ResponseStatus::Loading) {
-            return;
+    /// Cache key for a settings-specific client, or `None` when the shared
+    /// client built from the global config applies.
+    fn client_cache_key(settings: &RequestSettings) -> Option<String> {
+        let mut parts = Vec::new();
+        match &settings.proxy {
+            ProxySetting::Inherit => {}
+            ProxySetting::None => parts.push("proxy=none".to_string()),
+            ProxySetting::Custom { url } => parts.push(format!("proxy={}", url)),
         }
+        match settings.http_version {
+            HttpVersionSetting::Auto => {}
+            HttpVersionSetting::Http1 => parts.push("http=1".to_string()),
+            HttpVersionSetting::Http2 => parts.push("http=2".to_string()),
+        }
+        let tls = &settings.tls;
+        if let Some(verify) = tls.verify {
+            parts.push(format!("verify={}", verify));
+        }
+        if let Some(path) = &tls.ca_cert {
+            parts.push(format!("ca={}", path));
+        }
+        if let (Some(cert), Some(key)) = (&tls.client_cert, &tls.client_key) {
+            parts.push(format!("identity={}|{}", cert, key));
+        }
+        (!parts.is_empty()).then(|| parts.join(";"))
+    }
 
-        // Resolve variables from active environment
-        let variables = environment::resolve_variables(self.active_environment());
-
-        let (url, _) = environment::substitute(&raw_url, &variables);
-        let (headers, _) =
-            environment::substitute(&self.request.headers_text(), &variables);
-        let body = self.build_resolved_body_content(&variables);
-        let auth = self.build_resolved_auth_config(&variables);
+    fn client_for_settings(&mut self, settings: &RequestSettings) -> Result<Client, String> {
+        let Some(key) = Self::client_cache_key(settings) else {
+            return Ok(self.client.clone());
+        };
+        if let Some(client) = self.client_cache.get(&key) {
+            return Ok(client.clone());
+        }
+        let client = Self::build_client(&self.config, settings).map_err(|e| e.to_string())?;
+        self.client_cache.insert(key, client.clone());
+        Ok(client)
+    }
 
-        self.response = ResponseStatus::Loading;
+    /// [`Self::build_header_case_connector`], cached like the clients so TLS
+    /// files are read once.
+    fn header_case_connector(
+        &mut self,
+        settings: &RequestSettings,
+    ) -> Result<Option<http1::Connector>, String> {
+        let key = Self::client_cache_key(settings).unwrap_or_default();
+        if let Some(connector) = self.connector_cache.get(&key) {
+            return Ok(connector.clone());
+        }
+        let connector = Self::build_header_case_connector(&self.config, settings)
+            .map_err(|e| e.to_string())?;
+        self.connector_cache.insert(key, connector.clone());
+        Ok(connector)
+    }
 
-        let client = self.client.clone();
-        let method = self.request.method.clone();
+    /// Whether the current request is sent without verifying certificates,
+    /// by its own setting or the `[ssl]` config.
+    pub fn tls_verification_disabled(&self) -> bool {
+        !self.request_settings.tls.verify.unwrap_or(self.config.ssl.verify)
+    }
 
-        let handle = tokio::spawn(async move {
-            let result =
-                http::send_request(&client, &method, &url, &headers, body, &auth).await;
-            let _ = tx.send(result).await;
-        });
-        self.request_handle = Some(handle.abort_handle());
+    /// Status-line description of the current request's proxy, when it
+    /// deviates from the global configuration.
+    pub fn proxy_notice(&self) -> Option<String> {
+        match &self.request_settings.proxy {
+            ProxySetting::Inherit => None,
+            ProxySetting::None => Some("proxy: bypassed".to_string()),
+            ProxySetting::Custom { url } => Some(format!("proxy: {}", url)),
+        }
     }
 
-    fn build_resolved_auth_config(
-        &self,
-        variables: &std::collections::HashMap<String, String>,
-    ) -> http::AuthConfig {
+    fn build_resolved_auth_config(&self, resolver: &Resolver) -> http::AuthConfig {
+        if self.request.auth_disabled {
+            return http::AuthConfig::NoAuth;
+        }
         match self.request.auth_type {
             AuthType::NoAuth => http::AuthConfig::NoAuth,
             AuthType::Bearer => {
-                let (token, _) =
-                    environment::substitute(&self.request.auth_token_text(), variables);
+                let (token, _) = resolver.substitute(&self.request.auth_token_text());
                 http::AuthConfig::Bearer { token }
             }
             AuthType::Basic => {
-                let (username, _) =
-                    environment::substitute(&self.request.auth_username_text(), variables);
-                let (password, _) =
-                    environment::substitute(&self.request.auth_password_text(), variables);
+                let (username, _) = resolver.substitute(&self.request.auth_username_text());
+                let (password, _) = resolver.substitute(&self.request.auth_password_text());
                 http::AuthConfig::Basic { username, password }
             }
             AuthType::ApiKey => {
-                let (key, _) =
-                    environment::substitute(&self.request.auth_key_name_text(), variables);
-                let (value, _) =
-                    environment::substitute(&self.request.auth_key_value_text(), variables);
+                let (key, _) = resolver.substitute(&self.request.auth_key_name_text());
+                let (value, _) = resolver.substitute(&self.request.auth_key_value_text());
                 http::AuthConfig::ApiKey {
                     key,
                     value,
@@ -3611,13 +11038,13 @@ impl App {
         }
     }
 
-    fn build_resolved_body_content(
-        &self,
-        variables: &std::collections::HashMap<String, String>,
-    ) -> http::BodyContent {
+    fn build_resolved_body_content(&self, resolver: &Resolver) -> http::BodyContent {
+        if self.request.body_disabled {
+            return http::BodyContent::None;
+        }
         match self.request.body_mode {
             BodyMode::Raw => {
-                let (text, _) = environment::substitute(&self.request.body_text(), variables);
+                let (text, _) = resolver.substitute(&self.request.body_text());
                 if text.trim().is_empty() {
                     http::BodyContent::None
                 } else {
@@ -3625,7 +11052,7 @@ impl App {
                 }
             }
             BodyMode::Json => {
-                let (text, _) = environment::substitute(&self.request.body_text(), variables);
+                let (text, _) = resolver.substitute(&self.request.body_text());
                 if text.trim().is_empty() {
                     http::BodyContent::None
                 } else {
@@ -3633,7 +11060,7 @@ impl App {
                 }
             }
             BodyMode::Xml => {
-                let (text, _) = environment::substitute(&self.request.body_text(), variables);
+                let (text, _) = resolver.substitute(&self.request.body_text());
                 if text.trim().is_empty() {
                     http::BodyContent::None
                 } else {
@@ -3647,8 +11074,8 @@ impl App {
                     .iter()
                     .filter(|p| p.enabled && !(p.key.is_empty() && p.value.is_empty()))
                     .map(|p| {
-                        let (k, _) = environment::substitute(&p.key, variables);
-                        let (v, _) = environment::substitute(&p.value, variables);
+                        let (k, _) = resolver.substitute(&p.key);
+                        let (v, _) = resolver.substitute(&p.value);
                         (k, v)
                     })
                     .collect();
@@ -3665,8 +11092,8 @@ impl App {
                     .iter()
                     .filter(|f| f.enabled && !f.key.is_empty())
                     .map(|f| {
-                        let (k, _) = environment::substitute(&f.key, variables);
-                        let (v, _) = environment::substitute(&f.value, variables);
+                        let (k, _) = resolver.substitute(&f.key);
+                        let (v, _) = resolver.substitute(&f.value);
                         http::MultipartPart {
                             key: k,
                             value: v,
@@ -3684,8 +11111,7 @@ impl App {
                 }
             }
             BodyMode::Binary => {
-                let (path, _) =
-                    environment::substitute(&self.request.body_binary_path_text(), variables);
+                let (path, _) = resolver.substitute(&self.request.body_binary_path_text());
                 if path.trim().is_empty() {
                     http::BodyContent::None
                 } else {
@@ -3695,10 +11121,240 @@ impl App {
         }
     }
 
+    fn handle_settings_popup(&mut self, key: KeyEvent) {
+        let Some(mut popup) = self.settings_popup.take() else {
+            return;
+        };
+
+        if let Some(mut input) = popup.editing.take() {
+            match key.code {
+                KeyCode::Enter => {
+                    let row = popup.selected_row();
+                    match popup.apply_row_text(row, &input.value) {
+                        Ok(()) => {
+                            popup.error = None;
+                            self.commit_request_settings(&mut popup);
+                        }
+                        Err(err) => {
+                            popup.error = Some(err);
+                            popup.editing = Some(input);
+                        }
+                    }
+                }
+                KeyCode::Esc => {
+                    let row = popup.selected_row();
+                    popup.draft = self.request_settings.clone();
+                    if row == SettingsRow::ProxyUrl
+                        && !matches!(popup.draft.proxy, ProxySetting::Custom { .. })
+                    {
+                        popup.index = 0;
+                    }
+                    popup.index = popup.index.min(popup.rows().len() - 1);
+                    popup.error = None;
+                }
+                _ => {
+                    handle_text_input(&mut input, key);
+                    popup.editing = Some(input);
+                }
+            }
+            self.settings_popup = Some(popup);
+            return;
+        }
+
+        let row_count = popup.rows().len();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => return,
+            KeyCode::Down | KeyCode::Char('j') => {
+                popup.index = (popup.index + 1) % row_count;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                popup.index = (popup.index + row_count - 1) % row_count;
+            }
+            KeyCode::Enter | KeyCode::Char(' ') | KeyCode::Char('l') | KeyCode::Char('h') => {
+                match popup.selected_row() {
+                    SettingsRow::Proxy => {
+                        let backwards = key.code == KeyCode::Char('h');
+                        popup.draft.proxy = match (&popup.draft.proxy, backwards) {
+                            (ProxySetting::Inherit, false) | (ProxySetting::Custom { .. }, true) => {
+                                ProxySetting::None
+                            }
+                            (ProxySetting::None, false) | (ProxySetting::Inherit, true) => {
+                                ProxySetting::Custom { url: String::new() }
+                            }
+                            (ProxySetting::Custom { .. }, false) | (ProxySetting::None, true) => {
+                                ProxySetting::Inherit
+                            }
+                        };
+                        if let ProxySetting::Custom { url } = &popup.draft.proxy {
+                            // A custom proxy needs a URL before it can be saved
+                            popup.index = 1;
+                            popup.editing = Some(TextInput::new(url.clone()));
+                        } else {
+                            self.commit_request_settings(&mut popup);
+                        }
+                    }
+                    SettingsRow::Poll => {
+                        let backwards = key.code == KeyCode::Char('h');
+                        let status = || PollUntil::Status { codes: vec![200] };
+                        let json = || PollUntil::Json {
+                            path: "$.status".to_string(),
+                            value: "done".to_string(),
+                        };
+                        let current = popup.draft.poll.take();
+                        popup.draft.poll = match (current.as_ref().map(|p| &p.until), backwards) {
+                            (None, false) => Some(PollSetting::new(status())),
+                            (None, true) => Some(PollSetting::new(json())),
+                            (Some(PollUntil::Status { .. }), false) => {
+                                current.map(|p| PollSetting { until: json(), ..p })
+                            }
+                            (Some(PollUntil::Json { .. }), true) => {
+                                current.map(|p| PollSetting { until: status(), ..p })
+                            }
+                            (Some(_), _) => None,
+                        };
+                        self.commit_request_settings(&mut popup);
+                    }
+                    SettingsRow::Redirects => {
+                        let backwards = key.code == KeyCode::Char('h');
+                        popup.draft.redirects = match (popup.draft.redirects, backwards) {
+                            (RedirectSetting::Inherit, false) | (RedirectSetting::Stop, true) => {
+                                RedirectSetting::Follow
+                            }
+                            (RedirectSetting::Follow, false) | (RedirectSetting::Inherit, true) => {
+                                RedirectSetting::Stop
+                            }
+                            (RedirectSetting::Stop, false) | (RedirectSetting::Follow, true) => {
+                                RedirectSetting::Inherit
+                            }
+                        };
+                        self.commit_request_settings(&mut popup);
+                    }
+                    SettingsRow::Compress => {
+                        let backwards = key.code == KeyCode::Char('h');
+                        popup.draft.compress = match (popup.draft.compress, backwards) {
+                            (CompressSetting::Inherit, false) | (CompressSetting::Off, true) => {
+                                CompressSetting::Gzip
+                            }
+                            (CompressSetting::Gzip, false) | (CompressSetting::Inherit, true) => {
+                                CompressSetting::Off
+                            }
+                            (CompressSetting::Off, false) | (CompressSetting::Gzip, true) => {
+                                CompressSetting::Inherit
+                            }
+                        };
+                        self.commit_request_settings(&mut popup);
+                    }
+                    SettingsRow::HttpVersion => {
+                        let backwards = key.code == KeyCode::Char('h');
+                        popup.draft.http_version = match (popup.draft.http_version, backwards) {
+                            (HttpVersionSetting::Auto, false)
+                            | (HttpVersionSetting::Http2, true) => HttpVersionSetting::Http1,
+                            (HttpVersionSetting::Http1, false)
+                            | (HttpVersionSetting::Auto, true) => HttpVersionSetting::Http2,
+                            (HttpVersionSetting::Http2, false)
+                            | (HttpVersionSetting::Http1, true) => HttpVersionSetting::Auto,
+                        };
+                        self.commit_request_settings(&mut popup);
+                    }
+                    SettingsRow::Protect => {
+                        popup.draft.skip_protect = !popup.draft.skip_protect;
+                        self.commit_request_settings(&mut popup);
+                    }
+                    SettingsRow::LineEndings => {
+                        popup.draft.preserve_line_endings = !popup.draft.preserve_line_endings;
+                        self.commit_request_settings(&mut popup);
+                    }
+                    SettingsRow::ExpectsBody => {
+                        popup.draft.expects_body = !popup.draft.expects_body;
+                        self.commit_request_settings(&mut popup);
+                    }
+                    SettingsRow::TlsVerify => {
+                        let backwards = key.code == KeyCode::Char('h');
+                        popup.draft.tls.verify = match (popup.draft.tls.verify, backwards) {
+                            (None, false) | (Some(true), true) => Some(false),
+                            (Some(false), false) | (None, true) => Some(true),
+                            (Some(true), false) | (Some(false), true) => None,
+                        };
+                        self.commit_request_settings(&mut popup);
+                    }
+                    row => {
+                        if let Some(text) = popup.row_text(row) {
+                            popup.editing = Some(TextInput::new(text));
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        self.settings_popup = Some(popup);
+    }
+
+    /// Persists the popup's draft onto the open request item.
+    fn commit_request_settings(&mut self, popup: &mut SettingsPopup) {
+        let Some(request_id) = self.current_request_id else {
+            return;
+        };
+        let result = self
+            .collection
+            .update_settings(request_id, popup.draft.clone())
+            .and_then(|_| {
+                self.save_collection();
+                match self.sidebar_tree.node(request_id).and_then(|n| n.parent_id) {
+                    Some(parent_id) => {
+                        self.collection
+                            .save_request_file(request_id, parent_id, self.active_project_id)
+                    }
+                    None => Ok(()),
+                }
+            });
+        match result {
+            Ok(()) => {
+                self.request_settings = popup.draft.clone();
+                popup.error = None;
+                self.mark_dirty(DirtyPanels::RESPONSE);
+            }
+            Err(err) => popup.error = Some(err),
+        }
+    }
+
     fn cancel_request(&mut self) {
+        if let Some(run) = self.run.as_mut().filter(|run| !run.finished) {
+            run.finished = true;
+            run.report.cancelled = true;
+            run.current = None;
+            if let Some(sends) = run.parallel.as_mut() {
+                for sent in sends.in_flight.drain(..) {
+                    sent.handle.abort();
+                }
+            }
+            self.notify_run_end(Level::Info, "Run stopped".to_string());
+            if !matches!(self.response, ResponseStatus::Loading) {
+                return;
+            }
+        }
+        if let Some(run) = self.poll.take() {
+            self.notify(Level::Info, format!("Polling stopped after {} attempts", run.attempt));
+            if !matches!(self.response, ResponseStatus::Loading) {
+                return;
+            }
+        }
+        // Once the body is streaming, ask the task to stop so it can hand back
+        // what it has. Before that (or on a second press) there is nothing
+        // worth waiting for.
+        if self.loading_progress.is_some() {
+            if let Some(cancel) = self.request_cancel.take() {
+                if cancel.send(()).is_ok() {
+                    return;
+                }
+            }
+        }
         if let Some(handle) = self.request_handle.take() {
             handle.abort();
+            self.finish_send_log(Err("Cancelled".to_string()));
         }
+        self.request_cancel = None;
+        self.loading_progress = None;
+        self.loading_started = None;
         self.response = ResponseStatus::Cancelled;
     }
 
@@ -3886,8 +11542,10 @@ impl App {
     fn handle_body_enter(&mut self) {
         match self.focus.body_field {
             BodyField::ModeSelector => {
-                self.body_mode_popup_index = self.request.body_mode.index();
-                self.show_body_mode_popup = true;
+                if !self.refuse_locked_edit() {
+                    self.body_mode_popup_index = self.request.body_mode.index();
+                    self.show_body_mode_popup = true;
+                }
             }
             BodyField::TextEditor => {
                 if self.request.body_mode.is_text_mode() {
@@ -3933,12 +11591,8 @@ impl App {
             BodyField::ModeSelector => {
                 self.focus.request_field = RequestField::Url;
             }
-            BodyField::KvRow => {
-                if self.focus.kv_focus.row > 0 {
-                    self.focus.kv_focus.row -= 1;
-                } else {
-                    self.focus.body_field = BodyField::ModeSelector;
-                }
+            BodyField::KvRow if self.focus.kv_focus.row > 0 => {
+                self.focus.kv_focus.row -= 1;
             }
             _ => {
                 self.focus.body_field = BodyField::ModeSelector;
@@ -3955,7 +11609,25 @@ impl App {
         }
     }
 
+    /// Whether the key/value table being worked on is the headers table
+    /// rather than a body form.
+    fn header_table_focused(&self) -> bool {
+        self.headers_table && self.focus.request_field == RequestField::Headers
+    }
+
+    fn toggle_headers_table(&mut self) {
+        self.headers_table = !self.headers_table;
+        if self.headers_table {
+            self.request.load_header_rows();
+        }
+        self.focus.kv_focus = KvFocus::default();
+        self.persist_session_state();
+    }
+
     fn kv_row_count(&self) -> usize {
+        if self.header_table_focused() {
+            return self.request.header_rows.len();
+        }
         match self.request.body_mode {
             BodyMode::FormUrlEncoded => self.request.body_form_pairs.len(),
             BodyMode::Multipart => self.request.body_multipart_fields.len(),
@@ -3964,6 +11636,9 @@ impl App {
     }
 
     fn start_kv_cell_edit(&mut self) {
+        if self.refuse_locked_edit() {
+            return;
+        }
         let text = self.get_kv_cell_text();
         let mut textarea = TextArea::new(vec![text]);
         configure_editor(&mut textarea, "");
@@ -3976,6 +11651,17 @@ impl App {
     fn get_kv_cell_text(&self) -> String {
         let row = self.focus.kv_focus.row;
         let col = self.focus.kv_focus.column;
+        if self.header_table_focused() {
+            return self
+                .request
+                .header_rows
+                .get(row)
+                .map(|pair| match col {
+                    KvColumn::Key => pair.key.clone(),
+                    KvColumn::Value => pair.value.clone(),
+                })
+                .unwrap_or_default();
+        }
         match self.request.body_mode {
             BodyMode::FormUrlEncoded => {
                 if let Some(pair) = self.request.body_form_pairs.get(row) {
@@ -4006,6 +11692,25 @@ impl App {
             let text = textarea.lines().join("");
             let row = self.focus.kv_focus.row;
             let col = self.focus.kv_focus.column;
+            if self.header_table_focused() {
+                if let Some(pair) = self.request.header_rows.get_mut(row) {
+                    match col {
+                        KvColumn::Key => pair.key = text,
+                        KvColumn::Value => pair.value = text,
+                    }
+                }
+                let needs_new = self
+                    .request
+                    .header_rows
+                    .last()
+                    .is_none_or(|p| !p.key.is_empty() || !p.value.is_empty());
+                if needs_new {
+                    self.request.header_rows.push(KvPair::new_empty());
+                }
+                self.request.store_header_rows();
+                self.request_dirty = true;
+                return;
+            }
             match self.request.body_mode {
                 BodyMode::FormUrlEncoded => {
                     if let Some(pair) = self.request.body_form_pairs.get_mut(row) {
@@ -4058,7 +11763,17 @@ impl App {
     }
 
     fn kv_add_row(&mut self) {
+        if self.refuse_locked_edit() {
+            return;
+        }
         let row = self.focus.kv_focus.row;
+        if self.header_table_focused() {
+            let at = (row + 1).min(self.request.header_rows.len());
+            self.request.header_rows.insert(at, KvPair::new_empty());
+            self.focus.kv_focus.row = at;
+            self.focus.kv_focus.column = KvColumn::Key;
+            return;
+        }
         match self.request.body_mode {
             BodyMode::FormUrlEncoded => {
                 self.request
@@ -4080,24 +11795,32 @@ impl App {
     }
 
     fn kv_delete_row(&mut self) {
+        if self.refuse_locked_edit() {
+            return;
+        }
         let row = self.focus.kv_focus.row;
+        if self.header_table_focused() {
+            let rows = &mut self.request.header_rows;
+            if row < rows.len() && rows.len() > 1 {
+                rows.remove(row);
+                self.focus.kv_focus.row = row.min(rows.len() - 1);
+                self.request.store_header_rows();
+                self.request_dirty = true;
+            }
+            return;
+        }
         match self.request.body_mode {
-            BodyMode::FormUrlEncoded => {
-                if self.request.body_form_pairs.len() > 1 {
-                    self.request.body_form_pairs.remove(row);
-                    if self.focus.kv_focus.row >= self.request.body_form_pairs.len() {
-                        self.focus.kv_focus.row =
-                            self.request.body_form_pairs.len().saturating_sub(1);
-                    }
+            BodyMode::FormUrlEncoded if self.request.body_form_pairs.len() > 1 => {
+                self.request.body_form_pairs.remove(row);
+                if self.focus.kv_focus.row >= self.request.body_form_pairs.len() {
+                    self.focus.kv_focus.row = self.request.body_form_pairs.len().saturating_sub(1);
                 }
             }
-            BodyMode::Multipart => {
-                if self.request.body_multipart_fields.len() > 1 {
-                    self.request.body_multipart_fields.remove(row);
-                    if self.focus.kv_focus.row >= self.request.body_multipart_fields.len() {
-                        self.focus.kv_focus.row =
-                            self.request.body_multipart_fields.len().saturating_sub(1);
-                    }
+            BodyMode::Multipart if self.request.body_multipart_fields.len() > 1 => {
+                self.request.body_multipart_fields.remove(row);
+                if self.focus.kv_focus.row >= self.request.body_multipart_fields.len() {
+                    self.focus.kv_focus.row =
+                        self.request.body_multipart_fields.len().saturating_sub(1);
                 }
             }
             _ => {}
@@ -4106,7 +11829,18 @@ impl App {
     }
 
     fn kv_toggle_enabled(&mut self) {
+        if self.refuse_locked_edit() {
+            return;
+        }
         let row = self.focus.kv_focus.row;
+        if self.header_table_focused() {
+            if let Some(pair) = self.request.header_rows.get_mut(row) {
+                pair.enabled = !pair.enabled;
+                self.request.store_header_rows();
+                self.request_dirty = true;
+            }
+            return;
+        }
         match self.request.body_mode {
             BodyMode::FormUrlEncoded => {
                 if let Some(pair) = self.request.body_form_pairs.get_mut(row) {
@@ -4124,7 +11858,7 @@ impl App {
     }
 
     fn kv_toggle_multipart_type(&mut self) {
-        if self.request.body_mode != BodyMode::Multipart {
+        if self.request.body_mode != BodyMode::Multipart || self.refuse_locked_edit() {
             return;
         }
         let row = self.focus.kv_focus.row;
@@ -4209,6 +11943,11 @@ impl App {
     }
 
     fn handle_auth_enter(&mut self) {
+        let edits_in_place =
+            matches!(self.focus.auth_field, AuthField::AuthType | AuthField::KeyLocation);
+        if edits_in_place && self.refuse_locked_edit() {
+            return;
+        }
         match self.focus.auth_field {
             AuthField::AuthType => {
                 self.auth_type_popup_index = self.request.auth_type.index();
@@ -4311,7 +12050,8 @@ impl App {
         if self.focus.request_field == RequestField::Auth {
             self.active_auth_editor()
         } else {
-            self.active_request_editor()
+            self.request
+                .active_editor(self.focus.request_field, self.focus.body_field)
         }
     }
 }
@@ -4356,6 +12096,103 @@ fn collect_request_ids(item: &PostmanItem, out: &mut Vec<Uuid>) {
     }
 }
 
+/// Numbered hops for the Headers tab. Empty unless the response was
+/// redirected or is itself an unfollowed redirect.
+fn redirect_chain_lines(data: &ResponseData) -> Vec<String> {
+    let location = data
+        .headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("location"))
+        .map(|(_, v)| v.as_str());
+    let unfollowed = (300..400).contains(&data.status) && location.is_some();
+    if data.redirects.is_empty() && !unfollowed {
+        return Vec::new();
+    }
+    let mut lines = vec!["# Redirect chain".to_string()];
+    for (i, hop) in data.redirects.iter().enumerate() {
+        lines.push(format!(
+            "# {}. {} {} \u{2192} {}",
+            i + 1,
+            hop.status,
+            hop.url,
+            hop.location
+        ));
+    }
+    let mut last = format!("# {}. {} {}", data.redirects.len() + 1, data.status, data.final_url);
+    if let (true, Some(location)) = (unfollowed, location) {
+        last.push_str(&format!(" \u{2192} {} (not followed)", location));
+    }
+    lines.push(last);
+    lines
+}
+
+/// Asks on the plain terminal (before the TUI starts) whether to replace a
+/// corrupt collection with its backup. Anything but "y" declines.
+fn confirm_restore(err: &storage::LoadError, backup: &std::path::Path) -> bool {
+    use std::io::{BufRead, IsTerminal, Write};
+
+    if !std::io::stdin().is_terminal() {
+        return false;
+    }
+    eprintln!("{}", err);
+    eprint!("Restore from backup {}? [y/N] ", backup.display());
+    let _ = std::io::stderr().flush();
+    let mut answer = String::new();
+    if std::io::stdin().lock().read_line(&mut answer).is_err() {
+        return false;
+    }
+    answer.trim().eq_ignore_ascii_case("y")
+}
+
+/// 1s, 2s, 4s, ... capped at a minute.
+fn save_retry_delay(attempts: u32) -> Duration {
+    Duration::from_secs(1u64 << attempts.saturating_sub(1).min(6)).min(Duration::from_secs(60))
+}
+
+fn binary_summary_lines(data: &ResponseData) -> Vec<String> {
+    let content_type = http::header_value(&data.headers, "content-type")
+        .map(|v| v.split(';').next().unwrap_or("").trim().to_string())
+        .filter(|v| !v.is_empty());
+    let sniffed = http::sniff_image_type(&data.bytes);
+    let kind = match (content_type, sniffed) {
+        (Some(ct), Some(img)) if ct != img => format!("{} (looks like {})", ct, img),
+        (Some(ct), _) => ct,
+        (None, Some(img)) => img.to_string(),
+        (None, None) => "unknown type".to_string(),
+    };
+    vec![
+        format!(
+            "Binary response, {}, {}",
+            http::format_bytes(data.bytes.len()),
+            kind
+        ),
+        String::new(),
+        "S: save to file   H: hex view".to_string(),
+    ]
+}
+
+fn response_file_extension(data: &ResponseData) -> &'static str {
+    if let Some(img) = http::sniff_image_type(&data.bytes) {
+        return img.trim_start_matches("image/");
+    }
+    let content_type = http::header_value(&data.headers, "content-type")
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    if content_type.contains("json") {
+        "json"
+    } else if content_type.contains("html") {
+        "html"
+    } else if content_type.contains("xml") {
+        "xml"
+    } else if content_type.contains("pdf") {
+        "pdf"
+    } else if data.is_binary {
+        "bin"
+    } else {
+        "txt"
+    }
+}
+
 fn clamp_sidebar_width(value: u16) -> u16 {
     value.clamp(28, 60)
 }
@@ -4378,12 +12215,92 @@ fn headers_to_text(headers: &[PostmanHeader]) -> String {
         if header.key.trim().is_empty() {
             continue;
         }
-        lines.push(format!("{}: {}", header.key, header.value));
+        let prefix = if header.disabled == Some(true) { "# " } else { "" };
+        lines.push(format!("{}{}: {}", prefix, header.key, header.value));
     }
     lines.join("\n")
 }
 
+/// The text of each field of a saved request a variable can be used in,
+/// laid out so lines match the editors: headers and raw bodies as edited,
+/// form fields and auth values one entry per line.
+fn usage_fields(request: &PostmanRequest) -> Vec<(RequestField, String)> {
+    let mut fields = vec![
+        (RequestField::Url, extract_url(&request.url)),
+        (RequestField::Headers, headers_to_text(&request.header)),
+    ];
+    if let Some(body) = &request.body {
+        let lines: Vec<String> = match body.mode.as_str() {
+            "urlencoded" => body
+                .urlencoded
+                .iter()
+                .flatten()
+                .map(|pair| format!("{} {}", pair.key, pair.value))
+                .collect(),
+            "formdata" => body
+                .formdata
+                .iter()
+                .flatten()
+                .map(|param| {
+                    let value = param.value.as_deref().or(param.src.as_deref());
+                    format!("{} {}", param.key, value.unwrap_or_default())
+                })
+                .collect(),
+            "file" => body.file.iter().filter_map(|file| file.src.clone()).collect(),
+            _ => body.raw.iter().cloned().collect(),
+        };
+        fields.push((RequestField::Body, lines.join("\n")));
+    }
+    if let Some(auth) = &request.auth {
+        let attributes = match auth.auth_type.as_str() {
+            "bearer" => &auth.bearer,
+            "basic" => &auth.basic,
+            "apikey" => &auth.apikey,
+            _ => &None,
+        };
+        let values: Vec<String> = attributes
+            .iter()
+            .flatten()
+            .filter_map(|attr| match attr.value.as_ref()? {
+                Value::String(text) => Some(text.clone()),
+                _ => None,
+            })
+            .collect();
+        fields.push((RequestField::Auth, values.join("\n")));
+    }
+    fields
+}
+
+/// Names of the requests whose responses `request` references, from every
+/// field a placeholder can be in.
+fn referenced_requests(request: &PostmanRequest) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for (_, text) in usage_fields(request) {
+        for name in chain::referenced_requests(&text) {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    names
+}
+
+/// `name-2`, `name-3`, ... whichever is first not taken in `scope`.
+fn free_environment_name(name: &str, scope: EnvScope) -> String {
+    (2..)
+        .map(|n| format!("{}-{}", name, n))
+        .find(|candidate| !environment::environment_exists(candidate, scope))
+        .unwrap_or_else(|| name.to_string())
+}
+
 fn handle_text_input(input: &mut TextInput, key: KeyEvent) {
+    let ctrl_w = key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('w');
+    let alt_backspace =
+        key.modifiers.contains(KeyModifiers::ALT) && key.code == KeyCode::Backspace;
+    if ctrl_w || alt_backspace {
+        input.delete_word();
+        return;
+    }
     if key.modifiers.contains(KeyModifiers::CONTROL)
         || key.modifiers.contains(KeyModifiers::ALT)
         || key.modifiers.contains(KeyModifiers::SUPER)
@@ -0,0 +1,130 @@
+//! The URL audit (`U` in the sidebar): requests whose URL hard-codes a host
+//! instead of a `{{variable}}`, or uses variables the active environment
+//! doesn't define.
+
+use crate::chain;
+use crate::http;
+use crate::storage::environment;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Finding {
+    /// The URL starts with this literal `scheme://host[:port]`.
+    LiteralHost(String),
+    /// Placeholders that are neither environment variables nor references
+    /// to another request's response.
+    UndefinedVariables(Vec<String>),
+}
+
+impl Finding {
+    pub fn describe(&self) -> String {
+        match self {
+            Finding::LiteralHost(base) => format!("literal host {}", base),
+            Finding::UndefinedVariables(names) => {
+                let names: Vec<String> = names.iter().map(|n| format!("{{{{{}}}}}", n)).collect();
+                format!("undefined {}", names.join(", "))
+            }
+        }
+    }
+}
+
+/// What's wrong with `url`, with `defined` telling which variables the
+/// active environment has.
+pub fn audit_url(url: &str, defined: impl Fn(&str) -> bool) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    if let Some(base) = literal_base(url) {
+        findings.push(Finding::LiteralHost(base));
+    }
+    let (_, names) = environment::substitute_with(url, |_| None);
+    let mut undefined: Vec<String> = Vec::new();
+    for name in names {
+        if !defined(&name) && chain::parse_reference(&name).is_none() && !undefined.contains(&name)
+        {
+            undefined.push(name);
+        }
+    }
+    if !undefined.is_empty() {
+        findings.push(Finding::UndefinedVariables(undefined));
+    }
+    findings
+}
+
+/// The hard-coded `scheme://host[:port]` (or bare `host[:port]`) that
+/// `url` starts with. URLs whose host comes from a placeholder have none.
+pub fn literal_base(url: &str) -> Option<String> {
+    let url = url.trim();
+    let (scheme, rest) = match url.split_once("://") {
+        Some((scheme, rest)) if !scheme.is_empty() && !scheme.contains(['/', '{']) => {
+            (Some(scheme), rest)
+        }
+        _ if http::looks_like_url(url) => (None, url),
+        _ => return None,
+    };
+    let end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    let authority = &rest[..end];
+    if authority.is_empty() || authority.contains("{{") {
+        return None;
+    }
+    Some(match scheme {
+        Some(scheme) => format!("{}://{}", scheme, authority),
+        None => authority.to_string(),
+    })
+}
+
+/// `url` with its literal base swapped for `{{variable}}`, when it starts
+/// with `base`.
+pub fn replace_base(url: &str, base: &str, variable: &str) -> Option<String> {
+    let url = url.trim();
+    if literal_base(url).as_deref() != Some(base) {
+        return None;
+    }
+    Some(format!("{{{{{}}}}}{}", variable, &url[base.len()..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_literal_base() {
+        assert_eq!(
+            literal_base("http://localhost:8080/users?x=1").as_deref(),
+            Some("http://localhost:8080")
+        );
+        assert_eq!(
+            literal_base("https://api.example.com").as_deref(),
+            Some("https://api.example.com")
+        );
+        assert_eq!(literal_base("api.example.com/v1").as_deref(), Some("api.example.com"));
+        assert_eq!(literal_base("{{base_url}}/users"), None);
+        assert_eq!(literal_base("https://{{host}}/users"), None);
+        assert_eq!(literal_base(""), None);
+        assert_eq!(literal_base("/relative"), None);
+    }
+
+    #[test]
+    fn test_audit_url() {
+        let defined = |name: &str| name == "base_url";
+        assert!(audit_url("{{base_url}}/users", defined).is_empty());
+        assert!(audit_url("{{base_url}}/users/{{Login.body.id}}", defined).is_empty());
+        assert_eq!(
+            audit_url("http://localhost:3000/users/{{id}}?q={{id}}", defined),
+            vec![
+                Finding::LiteralHost("http://localhost:3000".to_string()),
+                Finding::UndefinedVariables(vec!["id".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_replace_base() {
+        assert_eq!(
+            replace_base("http://localhost:3000/users?page=2", "http://localhost:3000", "base_url")
+                .as_deref(),
+            Some("{{base_url}}/users?page=2")
+        );
+        let bare = replace_base("http://localhost:3000", "http://localhost:3000", "b");
+        assert_eq!(bare.as_deref(), Some("{{b}}"));
+        // A longer port is a different base
+        assert_eq!(replace_base("http://localhost:30001/x", "http://localhost:3000", "b"), None);
+    }
+}
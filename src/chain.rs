@@ -0,0 +1,245 @@
+//! References to other requests' most recent responses, written as
+//! `{{Login.body.token}}` or `{{responses["Login"].headers.etag}}` and
+//! resolved when a request is sent.
+
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::app::ResponseData;
+use crate::json_path::{self, Segment};
+use crate::storage::environment::{self, TemplateToken};
+use crate::storage::PollUntil;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Part {
+    Status,
+    /// The body, or a JSON value inside it when the path is non-empty.
+    Body(Vec<Segment>),
+    Header(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    pub request: String,
+    pub part: Part,
+}
+
+/// Parses a placeholder name as a response reference. Names whose second
+/// segment isn't `status`, `body` or `headers` are not references, so dotted
+/// environment variables keep working.
+pub fn parse_reference(expr: &str) -> Option<Reference> {
+    let expr = expr.trim();
+    let (request, rest) = if let Some(after) = expr.strip_prefix("responses[") {
+        let quote = after.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let close = after[1..].find(quote)? + 1;
+        let rest = after[close + 1..].strip_prefix(']')?;
+        (after[1..close].to_string(), rest.strip_prefix('.')?)
+    } else {
+        let (request, rest) = expr.split_once('.')?;
+        (request.trim().to_string(), rest)
+    };
+    if request.is_empty() {
+        return None;
+    }
+    let part = if rest == "status" {
+        Part::Status
+    } else if let Some(path) = rest.strip_prefix("body") {
+        Part::Body(json_path::parse_path(path)?)
+    } else if let Some(header) = rest.strip_prefix("headers") {
+        match json_path::parse_path(header)?.as_slice() {
+            [Segment::Key(name)] => Part::Header(name.clone()),
+            _ => return None,
+        }
+    } else {
+        return None;
+    };
+    Some(Reference { request, part })
+}
+
+/// Names of the requests whose responses `text` references, in order of
+/// first use.
+pub fn referenced_requests(text: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for token in environment::tokenize(text) {
+        let TemplateToken::Placeholder(name) = token else {
+            continue;
+        };
+        if let Some(reference) = parse_reference(name) {
+            if !names.contains(&reference.request) {
+                names.push(reference.request);
+            }
+        }
+    }
+    names
+}
+
+/// What's kept of a response for later references.
+#[derive(Debug, Clone)]
+pub struct StoredResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl StoredResponse {
+    pub fn from_data(data: &ResponseData) -> Self {
+        Self {
+            status: data.status,
+            headers: data.headers.clone(),
+            body: data.body.clone(),
+        }
+    }
+}
+
+/// The most recent complete response of each request sent this session.
+#[derive(Debug, Default)]
+pub struct ResponseStore {
+    responses: HashMap<Uuid, StoredResponse>,
+}
+
+impl ResponseStore {
+    pub fn insert(&mut self, request_id: Uuid, data: &ResponseData) {
+        self.responses.insert(request_id, StoredResponse::from_data(data));
+    }
+
+    pub fn get(&self, request_id: Uuid) -> Option<&StoredResponse> {
+        self.responses.get(&request_id)
+    }
+}
+
+/// Pulls the referenced part out of a stored response. JSON strings come
+/// back without quotes; other JSON values are serialized compactly.
+pub fn extract(reference: &Reference, response: &StoredResponse) -> Result<String, String> {
+    match &reference.part {
+        Part::Status => Ok(response.status.to_string()),
+        Part::Header(name) => response
+            .headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.clone())
+            .ok_or_else(|| format!("its last response has no \"{}\" header", name)),
+        Part::Body(path) if path.is_empty() => Ok(response.body.clone()),
+        Part::Body(path) => {
+            let value: serde_json::Value = serde_json::from_str(&response.body)
+                .map_err(|_| "its last response body is not JSON".to_string())?;
+            let display = json_path::format_path(path).replacen('$', "body", 1);
+            match json_path::lookup(&value, path) {
+                Some(serde_json::Value::String(s)) => Ok(s.clone()),
+                Some(other) => Ok(other.to_string()),
+                None => Err(format!("its last response has no {}", display)),
+            }
+        }
+    }
+}
+
+/// Whether a poll can stop at `response`. A JSON condition whose path is
+/// missing (or whose body isn't JSON) doesn't hold yet.
+pub fn poll_condition_met(until: &PollUntil, response: &StoredResponse) -> bool {
+    match until {
+        PollUntil::Status { codes } => codes.contains(&response.status),
+        PollUntil::Json { path, value } => {
+            let Some(path) = json_path::parse_path(path) else {
+                return false;
+            };
+            let reference = Reference {
+                request: String::new(),
+                part: Part::Body(path),
+            };
+            extract(&reference, response).is_ok_and(|found| found == *value)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stored() -> StoredResponse {
+        StoredResponse {
+            status: 201,
+            headers: vec![("X-Request-Id".to_string(), "abc".to_string())],
+            body: r#"{"token":"t0k","user":{"id":7,"roles":["admin"]}}"#.to_string(),
+        }
+    }
+
+    fn resolve(expr: &str) -> Result<String, String> {
+        extract(&parse_reference(expr).unwrap(), &stored())
+    }
+
+    #[test]
+    fn test_poll_condition_met() {
+        let status = |codes: Vec<u16>| PollUntil::Status { codes };
+        assert!(poll_condition_met(&status(vec![200, 201]), &stored()));
+        assert!(!poll_condition_met(&status(vec![200]), &stored()));
+
+        let json = |path: &str, value: &str| PollUntil::Json {
+            path: path.to_string(),
+            value: value.to_string(),
+        };
+        assert!(poll_condition_met(&json("$.token", "t0k"), &stored()));
+        assert!(poll_condition_met(&json(".user.id", "7"), &stored()));
+        assert!(poll_condition_met(&json("$.user.roles", r#"["admin"]"#), &stored()));
+        assert!(!poll_condition_met(&json("$.token", "other"), &stored()));
+        assert!(!poll_condition_met(&json("$.missing", ""), &stored()));
+        assert!(!poll_condition_met(&json("not a path", "t0k"), &stored()));
+    }
+
+    #[test]
+    fn test_parse_reference_forms() {
+        let simple = parse_reference("Login.body.token").unwrap();
+        assert_eq!(simple.request, "Login");
+        assert_eq!(simple.part, Part::Body(vec![Segment::Key("token".to_string())]));
+
+        let indexed = parse_reference(r#"responses["Get user"].status"#).unwrap();
+        assert_eq!(indexed.request, "Get user");
+        assert_eq!(indexed.part, Part::Status);
+
+        let header = parse_reference(r#"Login.headers["x-request-id"]"#).unwrap();
+        assert_eq!(header.part, Part::Header("x-request-id".to_string()));
+    }
+
+    #[test]
+    fn test_parse_reference_rejects_plain_variables() {
+        assert!(parse_reference("base_url").is_none());
+        assert!(parse_reference("api.key").is_none());
+        assert!(parse_reference(".body").is_none());
+        assert!(parse_reference("Login.bodyx").is_none());
+    }
+
+    #[test]
+    fn test_referenced_requests() {
+        let text = concat!(
+            "{{base_url}}/users/{{Login.body.user.id}}",
+            r#"?e={{responses["Get etag"].headers.etag}}"#,
+            "&again={{Login.status}}&{{api.key}}",
+        );
+        assert_eq!(referenced_requests(text), vec!["Login", "Get etag"]);
+        assert!(referenced_requests("{{Login.body.token").is_empty());
+    }
+
+    #[test]
+    fn test_extract_body_paths() {
+        assert_eq!(resolve("Login.body.token").unwrap(), "t0k");
+        assert_eq!(resolve("Login.body.user.id").unwrap(), "7");
+        assert_eq!(resolve("Login.body.user.roles[0]").unwrap(), "admin");
+        assert_eq!(resolve("Login.body.user.roles").unwrap(), r#"["admin"]"#);
+        assert_eq!(resolve("Login.body").unwrap(), stored().body);
+        assert!(resolve("Login.body.missing").unwrap_err().contains("body.missing"));
+    }
+
+    #[test]
+    fn test_extract_headers_and_status() {
+        assert_eq!(resolve("Login.headers.x-request-id").unwrap(), "abc");
+        assert_eq!(resolve("Login.status").unwrap(), "201");
+        assert!(resolve("Login.headers.etag").is_err());
+    }
+
+    #[test]
+    fn test_extract_non_json_body() {
+        let mut response = stored();
+        response.body = "plain".to_string();
+        let reference = parse_reference("Login.body.token").unwrap();
+        assert!(extract(&reference, &response).unwrap_err().contains("not JSON"));
+    }
+}
@@ -1,12 +1,21 @@
+use std::collections::VecDeque;
 use std::fmt;
+use std::io::Write;
 
 use arboard::Clipboard;
 
+use crate::util::codec;
+
+/// Yanks kept for the `"` popup, newest first.
+pub const RING_SIZE: usize = 10;
+
 #[derive(Debug)]
 pub enum ClipboardError {
     Init(arboard::Error),
     Read(arboard::Error),
     Write(arboard::Error),
+    /// The system clipboard failed before and isn't tried again.
+    Unavailable,
 }
 
 impl fmt::Display for ClipboardError {
@@ -15,40 +24,159 @@ impl fmt::Display for ClipboardError {
             ClipboardError::Init(err) => write!(f, "init failed: {err}"),
             ClipboardError::Read(err) => write!(f, "read failed: {err}"),
             ClipboardError::Write(err) => write!(f, "write failed: {err}"),
+            ClipboardError::Unavailable => write!(f, "unavailable"),
         }
     }
 }
 
+/// The system clipboard, with an in-app ring of recent yanks that stands
+/// in for it when it can't be reached (a headless SSH session, say).
+#[cfg_attr(test, derive(Default))]
 pub struct ClipboardProvider {
     clipboard: Option<Clipboard>,
+    /// Set on the first failure; the system clipboard isn't tried again.
+    unavailable: bool,
+    /// Whether the failure has been reported to the user yet.
+    reported: bool,
+    /// Also send copies to the terminal as OSC 52 escape sequences.
+    osc52: bool,
+    ring: VecDeque<String>,
 }
 
 impl ClipboardProvider {
-    pub fn new() -> Self {
+    pub fn new(osc52: bool) -> Self {
+        let clipboard = Clipboard::new().ok();
         Self {
-            clipboard: Clipboard::new().ok(),
+            unavailable: clipboard.is_none(),
+            clipboard,
+            reported: false,
+            osc52,
+            ring: VecDeque::new(),
         }
     }
 
+    pub fn set_osc52(&mut self, osc52: bool) {
+        self.osc52 = osc52;
+    }
+
+    pub fn osc52(&self) -> bool {
+        self.osc52
+    }
+
+    /// Recent yanks, newest first.
+    pub fn ring(&self) -> impl Iterator<Item = &str> {
+        self.ring.iter().map(String::as_str)
+    }
+
+    /// True the first time it's asked after the system clipboard failed,
+    /// so the failure is reported once rather than on every yank.
+    pub fn take_failure_notice(&mut self) -> bool {
+        if self.unavailable && !self.reported {
+            self.reported = true;
+            return true;
+        }
+        false
+    }
+
+    /// The system clipboard's text, or the newest yank once the system
+    /// clipboard is known to be unavailable.
     pub fn get_text(&mut self) -> Result<String, ClipboardError> {
-        if self.clipboard.is_none() {
-            self.clipboard = Some(Clipboard::new().map_err(ClipboardError::Init)?);
+        if self.unavailable {
+            return self.ring.front().cloned().ok_or(ClipboardError::Unavailable);
         }
-        self.clipboard
-            .as_mut()
-            .expect("clipboard must be initialized")
-            .get_text()
-            .map_err(ClipboardError::Read)
+        let result = self.system().and_then(|clipboard| {
+            clipboard.get_text().map_err(ClipboardError::Read)
+        });
+        self.note_failure(result)
     }
 
+    /// Puts `text` at the front of the ring and on the system clipboard.
+    /// The ring keeps it even when the system clipboard fails.
     pub fn set_text(&mut self, text: String) -> Result<(), ClipboardError> {
+        self.remember(text.clone());
+        if self.osc52 {
+            let _ = write_osc52(&text);
+        }
+        if self.unavailable {
+            return Err(ClipboardError::Unavailable);
+        }
+        let result = self
+            .system()
+            .and_then(|clipboard| clipboard.set_text(text).map_err(ClipboardError::Write));
+        self.note_failure(result)
+    }
+
+    fn system(&mut self) -> Result<&mut Clipboard, ClipboardError> {
         if self.clipboard.is_none() {
             self.clipboard = Some(Clipboard::new().map_err(ClipboardError::Init)?);
         }
-        self.clipboard
-            .as_mut()
-            .expect("clipboard must be initialized")
-            .set_text(text)
-            .map_err(ClipboardError::Write)
+        Ok(self.clipboard.as_mut().expect("clipboard must be initialized"))
+    }
+
+    fn note_failure<T>(&mut self, result: Result<T, ClipboardError>) -> Result<T, ClipboardError> {
+        if result.is_err() {
+            self.unavailable = true;
+            self.clipboard = None;
+        }
+        result
+    }
+
+    fn remember(&mut self, text: String) {
+        if text.is_empty() {
+            return;
+        }
+        self.ring.retain(|yank| *yank != text);
+        self.ring.push_front(text);
+        self.ring.truncate(RING_SIZE);
+    }
+}
+
+/// `text` as an OSC 52 "set clipboard" sequence, which many terminals
+/// honor even over SSH.
+fn osc52_sequence(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", codec::base64_encode(text.as_bytes()))
+}
+
+fn write_osc52(text: &str) -> std::io::Result<()> {
+    let mut stdout = std::io::stdout();
+    stdout.write_all(osc52_sequence(text).as_bytes())?;
+    stdout.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unavailable() -> ClipboardProvider {
+        ClipboardProvider {
+            clipboard: None,
+            unavailable: true,
+            reported: false,
+            osc52: false,
+            ring: VecDeque::new(),
+        }
+    }
+
+    #[test]
+    fn test_ring_stands_in_for_an_unavailable_clipboard() {
+        let mut clipboard = unavailable();
+        assert!(matches!(clipboard.get_text(), Err(ClipboardError::Unavailable)));
+        for i in 0..12 {
+            let _ = clipboard.set_text(format!("yank {}", i));
+        }
+        let _ = clipboard.set_text("yank 5".to_string());
+        let _ = clipboard.set_text(String::new());
+        let ring: Vec<&str> = clipboard.ring().collect();
+        assert_eq!(ring.len(), RING_SIZE);
+        assert_eq!(ring[..3], ["yank 5", "yank 11", "yank 10"]);
+        assert_eq!(clipboard.get_text().unwrap(), "yank 5");
+
+        assert!(clipboard.take_failure_notice());
+        assert!(!clipboard.take_failure_notice());
+    }
+
+    #[test]
+    fn test_osc52_sequence() {
+        assert_eq!(osc52_sequence("hi"), "\x1b]52;c;aGk=\x07");
     }
 }
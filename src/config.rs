@@ -1,16 +1,19 @@
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
+use std::net::{IpAddr, SocketAddr};
 use std::path::{Path, PathBuf};
 
+use reqwest::header::HeaderValue;
 use serde::Deserialize;
 
-use crate::storage::find_project_root;
+use crate::storage::{find_project_root, PostmanAuth, PostmanBody, PostmanRequest};
 
 // ---------------------------------------------------------------------------
 // Top-level Config — all fields have defaults, unknown keys silently ignored.
 // ---------------------------------------------------------------------------
 
-#[derive(Debug, Clone, Default, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
 #[serde(default)]
 pub struct Config {
     pub http: HttpConfig,
@@ -18,25 +21,55 @@ pub struct Config {
     pub ssl: SslConfig,
     pub ui: UiConfig,
     pub editor: EditorConfig,
+    pub clipboard: ClipboardConfig,
+    pub json: JsonConfig,
+    pub log: LogConfig,
+    pub protect: ProtectConfig,
+    pub defaults: DefaultsConfig,
+    /// Hostname → `IP` or `IP:port` overrides, like an `/etc/hosts` entry.
+    pub hosts: BTreeMap<String, String>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 #[serde(default)]
 pub struct HttpConfig {
     /// Timeout in seconds. 0 = no timeout.
     pub timeout: u64,
     pub follow_redirects: bool,
     pub max_redirects: u32,
+    /// Scheme prepended to URLs typed without one ("http" or "https").
+    pub default_scheme: String,
+    /// Advertise gzip/deflate/br support via `Accept-Encoding`.
+    pub accept_encoding: bool,
+    /// Gzip request bodies and send `Content-Encoding: gzip`; requests can
+    /// override it in their settings.
+    pub compress_body: bool,
+    /// Ask before sending a request with unresolved `{{variables}}`; when
+    /// false the send goes ahead with a status-bar warning.
+    pub confirm_unresolved: bool,
+    /// Ask before sending a body with GET or HEAD.
+    pub confirm_get_body: bool,
+    /// Ask before sending a body with DELETE.
+    pub confirm_delete_body: bool,
+    /// Origin the CORS preflight check (`K`) starts with, e.g.
+    /// "http://localhost:3000".
+    pub cors_origin: Option<String>,
+    /// `User-Agent` sent unless a request sets its own; unset sends none.
+    pub user_agent: Option<String>,
+    /// Send requests with oddly cased header names (`SOAPAction`) over
+    /// HTTP/1.1 with names as typed, and show their response's names as the
+    /// server spelled them, rather than lowercased.
+    pub preserve_header_case: bool,
 }
 
-#[derive(Debug, Clone, Default, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
 #[serde(default)]
 pub struct ProxyConfig {
     pub url: Option<String>,
     pub no_proxy: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 #[serde(default)]
 pub struct SslConfig {
     pub verify: bool,
@@ -45,18 +78,202 @@ pub struct SslConfig {
     pub client_key: Option<PathBuf>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 #[serde(default)]
 pub struct UiConfig {
     pub sidebar_width: u16,
+    /// Tab shown for requests that haven't been opened before: "headers",
+    /// "auth" or "body". Requests opened before get their last tab back.
+    pub default_request_tab: String,
+    /// Same for the response panel: "body" or "headers".
+    pub default_response_tab: String,
+    /// Show each request's last status code after its name in the sidebar.
+    pub sidebar_run_status: bool,
+    /// Show focus and selection with bold and reverse video, not color alone.
+    pub high_contrast: bool,
+    /// Show an elapsed-seconds counter instead of the loading spinner.
+    pub reduced_motion: bool,
+    pub statusbar: StatusBarConfig,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct StatusBarConfig {
+    /// Segments to show, left to right.
+    pub segments: Vec<StatusSegment>,
+}
+
+/// A part of the status bar, as named in `ui.statusbar.segments`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusSegment {
+    /// NAVIGATION / VIM / INSERT badge.
+    Mode,
+    /// Focused panel and field.
+    Panel,
+    /// Name of the open request, with a marker when it has unsaved edits.
+    Request,
+    /// Key hints, or the URL error while the URL doesn't parse.
+    Hints,
+    /// Proxy, polling, run, pin, redirect, host override, privacy and save
+    /// notices.
+    Notices,
+    /// Active environment.
+    Environment,
+    /// Latest notification.
+    Notification,
+}
+
+impl StatusSegment {
+    pub fn name(self) -> &'static str {
+        match self {
+            StatusSegment::Mode => "mode",
+            StatusSegment::Panel => "panel",
+            StatusSegment::Request => "request",
+            StatusSegment::Hints => "hints",
+            StatusSegment::Notices => "notices",
+            StatusSegment::Environment => "environment",
+            StatusSegment::Notification => "notification",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 #[serde(default)]
 pub struct EditorConfig {
     pub tab_size: u8,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ClipboardConfig {
+    /// Also copy through the terminal with OSC 52 escape sequences, which
+    /// reach the local clipboard from an SSH session.
+    pub osc52: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct JsonConfig {
+    /// Indentation of pretty-printed JSON: a number of spaces or `"tab"`.
+    pub indent: JsonIndent,
+    /// Object keys in sorted order instead of as received.
+    pub sort_keys: bool,
+    /// Characters outside ASCII written as `\uXXXX` escapes.
+    pub ascii_escape: bool,
+}
+
+/// `json.indent`: a number of spaces, or `"tab"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonIndent {
+    Spaces(u8),
+    Tab,
+}
+
+impl Default for JsonIndent {
+    fn default() -> Self {
+        JsonIndent::Spaces(2)
+    }
+}
+
+impl std::fmt::Display for JsonIndent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JsonIndent::Spaces(n) => write!(f, "{}", n),
+            JsonIndent::Tab => f.write_str("\"tab\""),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for JsonIndent {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct IndentVisitor;
+
+        impl serde::de::Visitor<'_> for IndentVisitor {
+            type Value = JsonIndent;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a number of spaces or \"tab\"")
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, n: i64) -> Result<JsonIndent, E> {
+                u8::try_from(n)
+                    .map(JsonIndent::Spaces)
+                    .map_err(|_| E::custom(format!("indent of {} spaces is out of range", n)))
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, n: u64) -> Result<JsonIndent, E> {
+                u8::try_from(n)
+                    .map(JsonIndent::Spaces)
+                    .map_err(|_| E::custom(format!("indent of {} spaces is out of range", n)))
+            }
+
+            fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<JsonIndent, E> {
+                if s.eq_ignore_ascii_case("tab") {
+                    Ok(JsonIndent::Tab)
+                } else {
+                    Err(E::invalid_value(serde::de::Unexpected::Str(s), &self))
+                }
+            }
+        }
+
+        deserializer.deserialize_any(IndentVisitor)
+    }
+}
+
+impl JsonConfig {
+    /// The layout the response view and body formatter use.
+    pub fn style(&self) -> crate::util::format::JsonStyle {
+        let indent = match self.indent {
+            JsonIndent::Spaces(n) => " ".repeat(usize::from(n)),
+            JsonIndent::Tab => "\t".to_string(),
+        };
+        crate::util::format::JsonStyle {
+            indent,
+            sort_keys: self.sort_keys,
+            ascii_escape: self.ascii_escape,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct LogConfig {
+    /// Append a JSON line per send to this file. Unset = no execution log.
+    pub file: Option<PathBuf>,
+    /// Size in megabytes at which the log is rotated.
+    pub max_size_mb: u64,
+    /// Rotated files kept next to the log (`file.1` … `file.N`).
+    pub keep: u32,
+}
+
+/// Where a send with a method other than GET or HEAD has to be confirmed
+/// by typing the method first.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ProtectConfig {
+    /// Host patterns: `api.example.com`, or `*.prod.example.com` for every
+    /// host under `prod.example.com`.
+    pub hosts: Vec<String>,
+    /// Environment names, compared case-insensitively.
+    pub environments: Vec<String>,
+}
+
+/// How a request added blank from the Add popup starts out. Requests added
+/// from a template take everything from the template instead.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct DefaultsConfig {
+    pub new_request_method: String,
+    /// "raw", "json", "xml", "form" or "multipart".
+    pub body_mode: String,
+    /// "none", "bearer", "basic" or "apikey".
+    pub auth_type: String,
+    /// With bearer auth, the variable the token comes from: "token" sends
+    /// `{{token}}`.
+    pub auth_token_variable: Option<String>,
+}
+
 // ---------------------------------------------------------------------------
 // Defaults
 // ---------------------------------------------------------------------------
@@ -67,6 +284,15 @@ impl Default for HttpConfig {
             timeout: 30,
             follow_redirects: true,
             max_redirects: 10,
+            default_scheme: "https".to_string(),
+            accept_encoding: true,
+            compress_body: false,
+            confirm_unresolved: true,
+            confirm_get_body: true,
+            confirm_delete_body: false,
+            cors_origin: None,
+            user_agent: None,
+            preserve_header_case: true,
         }
     }
 }
@@ -84,7 +310,31 @@ impl Default for SslConfig {
 
 impl Default for UiConfig {
     fn default() -> Self {
-        Self { sidebar_width: 32 }
+        Self {
+            sidebar_width: 32,
+            default_request_tab: "headers".to_string(),
+            default_response_tab: "body".to_string(),
+            sidebar_run_status: true,
+            high_contrast: false,
+            reduced_motion: false,
+            statusbar: StatusBarConfig::default(),
+        }
+    }
+}
+
+impl Default for StatusBarConfig {
+    fn default() -> Self {
+        Self {
+            segments: vec![
+                StatusSegment::Mode,
+                StatusSegment::Panel,
+                StatusSegment::Request,
+                StatusSegment::Hints,
+                StatusSegment::Notices,
+                StatusSegment::Environment,
+                StatusSegment::Notification,
+            ],
+        }
     }
 }
 
@@ -94,6 +344,75 @@ impl Default for EditorConfig {
     }
 }
 
+impl Default for DefaultsConfig {
+    fn default() -> Self {
+        Self {
+            new_request_method: "GET".to_string(),
+            body_mode: "raw".to_string(),
+            auth_type: "none".to_string(),
+            auth_token_variable: None,
+        }
+    }
+}
+
+impl DefaultsConfig {
+    /// A new request to `url` set up as configured, with an empty body.
+    pub fn new_request(&self, url: String) -> PostmanRequest {
+        let method = self.new_request_method.to_ascii_uppercase();
+        let mut request = PostmanRequest::new(method, url, Vec::new(), None);
+        request.body = match self.body_mode.as_str() {
+            "json" => Some(PostmanBody::json("")),
+            "xml" => Some(PostmanBody::xml("")),
+            "form" => Some(PostmanBody::urlencoded(Vec::new())),
+            "multipart" => Some(PostmanBody::formdata(Vec::new())),
+            _ => None,
+        };
+        request.auth = match self.auth_type.as_str() {
+            "bearer" => Some(PostmanAuth::bearer(&self.bearer_token())),
+            "basic" => Some(PostmanAuth::basic("", "")),
+            "apikey" => Some(PostmanAuth::apikey("", "", "header")),
+            _ => None,
+        };
+        request
+    }
+
+    /// "POST, JSON body, Bearer {{token}}", or `None` while every value is
+    /// the default.
+    pub fn summary(&self) -> Option<String> {
+        if *self == Self::default() {
+            return None;
+        }
+        let mut parts = vec![self.new_request_method.to_ascii_uppercase()];
+        if self.body_mode != "raw" {
+            parts.push(format!("{} body", self.body_mode.to_uppercase()));
+        }
+        match self.auth_type.as_str() {
+            "bearer" => parts.push(format!("Bearer {}", self.bearer_token()).trim().to_string()),
+            "basic" => parts.push("Basic auth".to_string()),
+            "apikey" => parts.push("API key".to_string()),
+            _ => {}
+        }
+        Some(parts.join(", "))
+    }
+
+    fn bearer_token(&self) -> String {
+        self.auth_token_variable
+            .as_ref()
+            .map(|name| format!("{{{{{}}}}}", name))
+            .unwrap_or_default()
+    }
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            file: None,
+            max_size_mb: 10,
+            keep: 5,
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Overlay config — partial deserialization for field-level merging.
 // ---------------------------------------------------------------------------
@@ -106,6 +425,12 @@ struct OverlayConfig {
     ssl: OverlaySslConfig,
     ui: OverlayUiConfig,
     editor: OverlayEditorConfig,
+    clipboard: OverlayClipboardConfig,
+    json: OverlayJsonConfig,
+    log: OverlayLogConfig,
+    protect: OverlayProtectConfig,
+    defaults: OverlayDefaultsConfig,
+    hosts: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Default)]
@@ -114,6 +439,15 @@ struct OverlayHttpConfig {
     timeout: Option<u64>,
     follow_redirects: Option<bool>,
     max_redirects: Option<u32>,
+    default_scheme: Option<String>,
+    accept_encoding: Option<bool>,
+    compress_body: Option<bool>,
+    confirm_unresolved: Option<bool>,
+    confirm_get_body: Option<bool>,
+    confirm_delete_body: Option<bool>,
+    cors_origin: Option<String>,
+    user_agent: Option<String>,
+    preserve_header_case: Option<bool>,
 }
 
 #[derive(Debug, Clone, Deserialize, Default)]
@@ -136,6 +470,18 @@ struct OverlaySslConfig {
 #[serde(default)]
 struct OverlayUiConfig {
     sidebar_width: Option<u16>,
+    default_request_tab: Option<String>,
+    default_response_tab: Option<String>,
+    sidebar_run_status: Option<bool>,
+    high_contrast: Option<bool>,
+    reduced_motion: Option<bool>,
+    statusbar: OverlayStatusBarConfig,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+struct OverlayStatusBarConfig {
+    segments: Option<Vec<StatusSegment>>,
 }
 
 #[derive(Debug, Clone, Deserialize, Default)]
@@ -144,6 +490,44 @@ struct OverlayEditorConfig {
     tab_size: Option<u8>,
 }
 
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+struct OverlayClipboardConfig {
+    osc52: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+struct OverlayJsonConfig {
+    indent: Option<JsonIndent>,
+    sort_keys: Option<bool>,
+    ascii_escape: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+struct OverlayLogConfig {
+    file: Option<PathBuf>,
+    max_size_mb: Option<u64>,
+    keep: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+struct OverlayProtectConfig {
+    hosts: Option<Vec<String>>,
+    environments: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+struct OverlayDefaultsConfig {
+    new_request_method: Option<String>,
+    body_mode: Option<String>,
+    auth_type: Option<String>,
+    auth_token_variable: Option<String>,
+}
+
 impl Config {
     /// Apply overlay values over self. Only `Some` fields are overridden.
     fn merge(mut self, overlay: OverlayConfig) -> Self {
@@ -156,6 +540,33 @@ impl Config {
         if let Some(v) = overlay.http.max_redirects {
             self.http.max_redirects = v;
         }
+        if let Some(v) = overlay.http.default_scheme {
+            self.http.default_scheme = v;
+        }
+        if let Some(v) = overlay.http.confirm_unresolved {
+            self.http.confirm_unresolved = v;
+        }
+        if let Some(v) = overlay.http.confirm_get_body {
+            self.http.confirm_get_body = v;
+        }
+        if let Some(v) = overlay.http.confirm_delete_body {
+            self.http.confirm_delete_body = v;
+        }
+        if let Some(v) = overlay.http.accept_encoding {
+            self.http.accept_encoding = v;
+        }
+        if let Some(v) = overlay.http.compress_body {
+            self.http.compress_body = v;
+        }
+        if let Some(v) = overlay.http.cors_origin {
+            self.http.cors_origin = Some(v);
+        }
+        if let Some(v) = overlay.http.user_agent {
+            self.http.user_agent = Some(v);
+        }
+        if let Some(v) = overlay.http.preserve_header_case {
+            self.http.preserve_header_case = v;
+        }
         if let Some(v) = overlay.proxy.url {
             self.proxy.url = Some(v);
         }
@@ -177,13 +588,380 @@ impl Config {
         if let Some(v) = overlay.ui.sidebar_width {
             self.ui.sidebar_width = v;
         }
+        if let Some(v) = overlay.ui.default_request_tab {
+            self.ui.default_request_tab = v;
+        }
+        if let Some(v) = overlay.ui.default_response_tab {
+            self.ui.default_response_tab = v;
+        }
+        if let Some(v) = overlay.ui.sidebar_run_status {
+            self.ui.sidebar_run_status = v;
+        }
+        if let Some(v) = overlay.ui.high_contrast {
+            self.ui.high_contrast = v;
+        }
+        if let Some(v) = overlay.ui.reduced_motion {
+            self.ui.reduced_motion = v;
+        }
+        if let Some(v) = overlay.ui.statusbar.segments {
+            self.ui.statusbar.segments = v;
+        }
         if let Some(v) = overlay.editor.tab_size {
             self.editor.tab_size = v;
         }
+        if let Some(v) = overlay.clipboard.osc52 {
+            self.clipboard.osc52 = v;
+        }
+        if let Some(v) = overlay.json.indent {
+            self.json.indent = v;
+        }
+        if let Some(v) = overlay.json.sort_keys {
+            self.json.sort_keys = v;
+        }
+        if let Some(v) = overlay.json.ascii_escape {
+            self.json.ascii_escape = v;
+        }
+        if let Some(v) = overlay.log.file {
+            self.log.file = Some(v);
+        }
+        if let Some(v) = overlay.log.max_size_mb {
+            self.log.max_size_mb = v;
+        }
+        if let Some(v) = overlay.log.keep {
+            self.log.keep = v;
+        }
+        if let Some(v) = overlay.protect.hosts {
+            self.protect.hosts = v;
+        }
+        if let Some(v) = overlay.protect.environments {
+            self.protect.environments = v;
+        }
+        if let Some(v) = overlay.defaults.new_request_method {
+            self.defaults.new_request_method = v;
+        }
+        if let Some(v) = overlay.defaults.body_mode {
+            self.defaults.body_mode = v;
+        }
+        if let Some(v) = overlay.defaults.auth_type {
+            self.defaults.auth_type = v;
+        }
+        if let Some(v) = overlay.defaults.auth_token_variable {
+            self.defaults.auth_token_variable = Some(v);
+        }
+        // Host overrides merge per hostname
+        for (host, target) in overlay.hosts {
+            self.hosts.insert(host.to_ascii_lowercase(), target);
+        }
         self
     }
 }
 
+impl OverlayConfig {
+    /// Dotted keys of every field this overlay sets.
+    fn set_keys(&self) -> Vec<String> {
+        let mut keys = Vec::new();
+        let mut push = |set: bool, key: &str| {
+            if set {
+                keys.push(key.to_string());
+            }
+        };
+        push(self.http.timeout.is_some(), "http.timeout");
+        push(self.http.follow_redirects.is_some(), "http.follow_redirects");
+        push(self.http.max_redirects.is_some(), "http.max_redirects");
+        push(self.http.default_scheme.is_some(), "http.default_scheme");
+        push(self.http.accept_encoding.is_some(), "http.accept_encoding");
+        push(self.http.compress_body.is_some(), "http.compress_body");
+        push(self.http.confirm_unresolved.is_some(), "http.confirm_unresolved");
+        push(self.http.confirm_get_body.is_some(), "http.confirm_get_body");
+        push(self.http.confirm_delete_body.is_some(), "http.confirm_delete_body");
+        push(self.http.cors_origin.is_some(), "http.cors_origin");
+        push(self.http.user_agent.is_some(), "http.user_agent");
+        push(self.http.preserve_header_case.is_some(), "http.preserve_header_case");
+        push(self.proxy.url.is_some(), "proxy.url");
+        push(self.proxy.no_proxy.is_some(), "proxy.no_proxy");
+        push(self.ssl.verify.is_some(), "ssl.verify");
+        push(self.ssl.ca_cert.is_some(), "ssl.ca_cert");
+        push(self.ssl.client_cert.is_some(), "ssl.client_cert");
+        push(self.ssl.client_key.is_some(), "ssl.client_key");
+        push(self.ui.sidebar_width.is_some(), "ui.sidebar_width");
+        push(self.ui.default_request_tab.is_some(), "ui.default_request_tab");
+        push(self.ui.default_response_tab.is_some(), "ui.default_response_tab");
+        push(self.ui.sidebar_run_status.is_some(), "ui.sidebar_run_status");
+        push(self.ui.high_contrast.is_some(), "ui.high_contrast");
+        push(self.ui.reduced_motion.is_some(), "ui.reduced_motion");
+        push(self.ui.statusbar.segments.is_some(), "ui.statusbar.segments");
+        push(self.editor.tab_size.is_some(), "editor.tab_size");
+        push(self.clipboard.osc52.is_some(), "clipboard.osc52");
+        push(self.json.indent.is_some(), "json.indent");
+        push(self.json.sort_keys.is_some(), "json.sort_keys");
+        push(self.json.ascii_escape.is_some(), "json.ascii_escape");
+        push(self.log.file.is_some(), "log.file");
+        push(self.log.max_size_mb.is_some(), "log.max_size_mb");
+        push(self.log.keep.is_some(), "log.keep");
+        push(self.protect.hosts.is_some(), "protect.hosts");
+        push(self.protect.environments.is_some(), "protect.environments");
+        push(self.defaults.new_request_method.is_some(), "defaults.new_request_method");
+        push(self.defaults.body_mode.is_some(), "defaults.body_mode");
+        push(self.defaults.auth_type.is_some(), "defaults.auth_type");
+        push(self.defaults.auth_token_variable.is_some(), "defaults.auth_token_variable");
+        for host in self.hosts.keys() {
+            keys.push(format!("hosts.{}", host.to_ascii_lowercase()));
+        }
+        keys
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Provenance — which layer each effective value came from.
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigSource {
+    Default,
+    Global,
+    Project,
+}
+
+impl ConfigSource {
+    pub fn label(self) -> &'static str {
+        match self {
+            ConfigSource::Default => "default",
+            ConfigSource::Global => "global",
+            ConfigSource::Project => "project",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ConfigProvenance {
+    pub global_path: Option<PathBuf>,
+    pub project_path: Option<PathBuf>,
+    sources: BTreeMap<String, ConfigSource>,
+}
+
+impl ConfigProvenance {
+    fn record(&mut self, overlay: &OverlayConfig, source: ConfigSource) {
+        for key in overlay.set_keys() {
+            self.sources.insert(key, source);
+        }
+    }
+
+    pub fn source(&self, key: &str) -> ConfigSource {
+        self.sources
+            .get(key)
+            .copied()
+            .unwrap_or(ConfigSource::Default)
+    }
+}
+
+impl Config {
+    /// Effective values as `(dotted key, display value)` pairs, in file order.
+    pub fn entries(&self) -> Vec<(String, String)> {
+        fn opt<T: std::fmt::Display>(value: &Option<T>) -> String {
+            value
+                .as_ref()
+                .map(|v| format!("\"{}\"", v))
+                .unwrap_or_else(|| "(unset)".to_string())
+        }
+        fn path(value: &Option<PathBuf>) -> String {
+            opt(&value.as_ref().map(|p| p.display().to_string()))
+        }
+        fn list(values: &[String]) -> String {
+            let quoted: Vec<String> = values.iter().map(|v| format!("\"{}\"", v)).collect();
+            format!("[{}]", quoted.join(", "))
+        }
+
+        let segments: Vec<String> = self
+            .ui
+            .statusbar
+            .segments
+            .iter()
+            .map(|segment| format!("\"{}\"", segment.name()))
+            .collect();
+        let mut entries = vec![
+            ("http.timeout".to_string(), self.http.timeout.to_string()),
+            (
+                "http.follow_redirects".to_string(),
+                self.http.follow_redirects.to_string(),
+            ),
+            ("http.max_redirects".to_string(), self.http.max_redirects.to_string()),
+            (
+                "http.default_scheme".to_string(),
+                format!("\"{}\"", self.http.default_scheme),
+            ),
+            (
+                "http.accept_encoding".to_string(),
+                self.http.accept_encoding.to_string(),
+            ),
+            ("http.compress_body".to_string(), self.http.compress_body.to_string()),
+            (
+                "http.confirm_unresolved".to_string(),
+                self.http.confirm_unresolved.to_string(),
+            ),
+            (
+                "http.confirm_get_body".to_string(),
+                self.http.confirm_get_body.to_string(),
+            ),
+            (
+                "http.confirm_delete_body".to_string(),
+                self.http.confirm_delete_body.to_string(),
+            ),
+            ("http.cors_origin".to_string(), opt(&self.http.cors_origin)),
+            ("http.user_agent".to_string(), opt(&self.http.user_agent)),
+            (
+                "http.preserve_header_case".to_string(),
+                self.http.preserve_header_case.to_string(),
+            ),
+            ("proxy.url".to_string(), opt(&self.proxy.url)),
+            ("proxy.no_proxy".to_string(), opt(&self.proxy.no_proxy)),
+            ("ssl.verify".to_string(), self.ssl.verify.to_string()),
+            ("ssl.ca_cert".to_string(), path(&self.ssl.ca_cert)),
+            ("ssl.client_cert".to_string(), path(&self.ssl.client_cert)),
+            ("ssl.client_key".to_string(), path(&self.ssl.client_key)),
+            ("ui.sidebar_width".to_string(), self.ui.sidebar_width.to_string()),
+            (
+                "ui.default_request_tab".to_string(),
+                format!("\"{}\"", self.ui.default_request_tab),
+            ),
+            (
+                "ui.default_response_tab".to_string(),
+                format!("\"{}\"", self.ui.default_response_tab),
+            ),
+            (
+                "ui.sidebar_run_status".to_string(),
+                self.ui.sidebar_run_status.to_string(),
+            ),
+            ("ui.high_contrast".to_string(), self.ui.high_contrast.to_string()),
+            ("ui.reduced_motion".to_string(), self.ui.reduced_motion.to_string()),
+            (
+                "ui.statusbar.segments".to_string(),
+                format!("[{}]", segments.join(", ")),
+            ),
+            ("editor.tab_size".to_string(), self.editor.tab_size.to_string()),
+            ("clipboard.osc52".to_string(), self.clipboard.osc52.to_string()),
+            ("json.indent".to_string(), self.json.indent.to_string()),
+            ("json.sort_keys".to_string(), self.json.sort_keys.to_string()),
+            ("json.ascii_escape".to_string(), self.json.ascii_escape.to_string()),
+            ("log.file".to_string(), path(&self.log.file)),
+            ("log.max_size_mb".to_string(), self.log.max_size_mb.to_string()),
+            ("log.keep".to_string(), self.log.keep.to_string()),
+            ("protect.hosts".to_string(), list(&self.protect.hosts)),
+            ("protect.environments".to_string(), list(&self.protect.environments)),
+            (
+                "defaults.new_request_method".to_string(),
+                format!("\"{}\"", self.defaults.new_request_method),
+            ),
+            ("defaults.body_mode".to_string(), format!("\"{}\"", self.defaults.body_mode)),
+            ("defaults.auth_type".to_string(), format!("\"{}\"", self.defaults.auth_type)),
+            (
+                "defaults.auth_token_variable".to_string(),
+                opt(&self.defaults.auth_token_variable),
+            ),
+        ];
+        for (host, target) in &self.hosts {
+            entries.push((format!("hosts.{}", host), format!("\"{}\"", target)));
+        }
+        entries
+    }
+
+    /// Whether switching to `other` requires a new HTTP client.
+    pub fn client_settings_differ(&self, other: &Config) -> bool {
+        self.http != other.http
+            || self.proxy != other.proxy
+            || self.ssl != other.ssl
+            || self.hosts != other.hosts
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Host overrides
+// ---------------------------------------------------------------------------
+
+/// Parsed `[hosts]` target. The port, when given, replaces the URL's port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HostTarget {
+    pub ip: IpAddr,
+    pub port: Option<u16>,
+}
+
+impl std::fmt::Display for HostTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.port {
+            Some(port) => write!(f, "{}", SocketAddr::new(self.ip, port)),
+            None => write!(f, "{}", self.ip),
+        }
+    }
+}
+
+pub fn parse_host_target(value: &str) -> Option<HostTarget> {
+    let value = value.trim();
+    if let Ok(addr) = value.parse::<SocketAddr>() {
+        return Some(HostTarget {
+            ip: addr.ip(),
+            port: Some(addr.port()),
+        });
+    }
+    let ip = value
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+        .ok()?;
+    Some(HostTarget { ip, port: None })
+}
+
+impl Config {
+    /// All valid host overrides. Invalid entries are rejected by `validate`.
+    pub fn host_targets(&self) -> impl Iterator<Item = (&str, HostTarget)> {
+        self.hosts
+            .iter()
+            .filter_map(|(host, value)| Some((host.as_str(), parse_host_target(value)?)))
+    }
+
+    pub fn host_override(&self, host: &str) -> Option<HostTarget> {
+        self.hosts
+            .get(&host.to_ascii_lowercase())
+            .and_then(|value| parse_host_target(value))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Protected hosts
+// ---------------------------------------------------------------------------
+
+/// Whether `host` matches a `[protect]` host pattern. `*.example.com`
+/// matches hosts under `example.com` but not `example.com` itself.
+pub fn host_pattern_matches(pattern: &str, host: &str) -> bool {
+    let pattern = pattern.trim().to_ascii_lowercase();
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    match pattern.strip_prefix("*.") {
+        Some(domain) => host
+            .strip_suffix(domain)
+            .is_some_and(|sub| sub.len() > 1 && sub.ends_with('.')),
+        None => host == pattern,
+    }
+}
+
+impl Config {
+    /// Why a send to `host` with `environment` active must be confirmed:
+    /// the host pattern or environment it matched.
+    pub fn protection(&self, host: &str, environment: Option<&str>) -> Option<String> {
+        if let Some(pattern) = self
+            .protect
+            .hosts
+            .iter()
+            .find(|pattern| host_pattern_matches(pattern, host))
+        {
+            return Some(format!("host matches {}", pattern));
+        }
+        let environment = environment?;
+        self.protect
+            .environments
+            .iter()
+            .find(|name| name.eq_ignore_ascii_case(environment))
+            .map(|name| format!("environment {} is protected", name))
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Path resolution
 // ---------------------------------------------------------------------------
@@ -223,7 +1001,7 @@ fn project_config_path() -> Option<PathBuf> {
 // Tilde expansion
 // ---------------------------------------------------------------------------
 
-fn expand_tilde(path: &Path) -> PathBuf {
+pub fn expand_tilde(path: &Path) -> PathBuf {
     if let Some(s) = path.to_str() {
         if let Some(rest) = s.strip_prefix('~') {
             if let Ok(home) = env::var("HOME") {
@@ -270,18 +1048,134 @@ impl Config {
                 self.http.max_redirects
             ));
         }
+        if !matches!(self.http.default_scheme.as_str(), "http" | "https") {
+            errors.push(format!(
+                "config error: http.default_scheme = \"{}\" must be \"http\" or \"https\"",
+                self.http.default_scheme
+            ));
+        }
+        let cors_origin = self.http.cors_origin.as_deref();
+        if let Some(origin) = cors_origin.filter(|origin| !crate::cors::is_origin(origin)) {
+            errors.push(format!(
+                "config error: http.cors_origin = \"{}\" must be an origin like \
+                 \"https://app.example.com\"",
+                origin
+            ));
+        }
+        let user_agent = self.http.user_agent.as_deref();
+        if let Some(agent) = user_agent.filter(|agent| HeaderValue::from_str(agent).is_err()) {
+            errors.push(format!(
+                "config error: http.user_agent = {:?} can't be sent as a header value",
+                agent
+            ));
+        }
         if !(28..=60).contains(&self.ui.sidebar_width) {
             errors.push(format!(
                 "config error: ui.sidebar_width = {} is out of range (28..=60)",
                 self.ui.sidebar_width
             ));
         }
+        if !matches!(self.ui.default_request_tab.as_str(), "headers" | "auth" | "body") {
+            errors.push(format!(
+                "config error: ui.default_request_tab = \"{}\" must be \"headers\", \"auth\" or \"body\"",
+                self.ui.default_request_tab
+            ));
+        }
+        if !matches!(self.ui.default_response_tab.as_str(), "body" | "headers") {
+            errors.push(format!(
+                "config error: ui.default_response_tab = \"{}\" must be \"body\" or \"headers\"",
+                self.ui.default_response_tab
+            ));
+        }
+        let segments = &self.ui.statusbar.segments;
+        for (i, segment) in segments.iter().enumerate() {
+            if segments[..i].contains(segment) {
+                errors.push(format!(
+                    "config error: ui.statusbar.segments lists \"{}\" more than once",
+                    segment.name()
+                ));
+            }
+        }
         if !(1..=8).contains(&self.editor.tab_size) {
             errors.push(format!(
                 "config error: editor.tab_size = {} is out of range (1..=8)",
                 self.editor.tab_size
             ));
         }
+        if let JsonIndent::Spaces(n) = self.json.indent {
+            if !(1..=8).contains(&n) {
+                errors.push(format!(
+                    "config error: json.indent = {} is out of range (1..=8, or \"tab\")",
+                    n
+                ));
+            }
+        }
+        if self.log.max_size_mb == 0 {
+            errors.push(
+                "config error: log.max_size_mb = 0 is out of range (must be at least 1)"
+                    .to_string(),
+            );
+        }
+
+        let method = &self.defaults.new_request_method;
+        if method.is_empty() || !method.chars().all(|c| c.is_ascii_alphabetic()) {
+            errors.push(format!(
+                "config error: defaults.new_request_method = \"{}\" is not an HTTP method",
+                method
+            ));
+        }
+        if !matches!(
+            self.defaults.body_mode.as_str(),
+            "raw" | "json" | "xml" | "form" | "multipart"
+        ) {
+            errors.push(format!(
+                "config error: defaults.body_mode = \"{}\" must be \"raw\", \"json\", \"xml\", \
+                 \"form\" or \"multipart\"",
+                self.defaults.body_mode
+            ));
+        }
+        if !matches!(self.defaults.auth_type.as_str(), "none" | "bearer" | "basic" | "apikey") {
+            errors.push(format!(
+                "config error: defaults.auth_type = \"{}\" must be \"none\", \"bearer\", \"basic\" \
+                 or \"apikey\"",
+                self.defaults.auth_type
+            ));
+        }
+        if let Some(ref name) = self.defaults.auth_token_variable {
+            let malformed = name.contains(['{', '}']) || name.contains(char::is_whitespace);
+            if name.trim().is_empty() || malformed {
+                errors.push(format!(
+                    "config error: defaults.auth_token_variable = \"{}\" must be a variable name \
+                     without braces",
+                    name
+                ));
+            } else if self.defaults.auth_type != "bearer" {
+                errors.push(
+                    "config error: defaults.auth_token_variable needs \
+                     defaults.auth_type = \"bearer\""
+                        .to_string(),
+                );
+            }
+        }
+
+        for (host, value) in &self.hosts {
+            if parse_host_target(value).is_none() {
+                errors.push(format!(
+                    "config error: hosts.\"{}\" = \"{}\" is not an IP address or IP:port",
+                    host, value
+                ));
+            }
+        }
+
+        for pattern in &self.protect.hosts {
+            let rest = pattern.strip_prefix("*.").unwrap_or(pattern);
+            if rest.trim().is_empty() || rest.contains(['*', '/', ':']) {
+                errors.push(format!(
+                    "config error: protect.hosts entry \"{}\" is not a host or *.host pattern",
+                    pattern
+                ));
+            }
+        }
 
         if let Some(ref url) = self.proxy.url {
             if reqwest::Url::parse(url).is_err() {
@@ -347,6 +1241,9 @@ impl Config {
         if let Some(ref path) = self.ssl.client_key {
             self.ssl.client_key = Some(expand_tilde(path));
         }
+        if let Some(ref path) = self.log.file {
+            self.log.file = Some(expand_tilde(path));
+        }
     }
 }
 
@@ -371,16 +1268,20 @@ fn load_overlay(path: &Path) -> Result<OverlayConfig, String> {
     })
 }
 
-/// Load configuration from global and project config files.
+/// Load configuration from global and project config files, along with
+/// which layer set each field.
 /// Missing files are silently skipped (all defaults apply).
 /// Parse or validation errors are returned as `Err`.
-pub fn load_config() -> Result<Config, String> {
+pub fn load_config() -> Result<(Config, ConfigProvenance), String> {
     let mut config = Config::default();
+    let mut provenance = ConfigProvenance::default();
 
     // Global config layer
     if let Some(path) = global_config_path() {
         if path.exists() {
             let overlay = load_overlay(&path)?;
+            provenance.record(&overlay, ConfigSource::Global);
+            provenance.global_path = Some(path);
             config = config.merge(overlay);
         }
     }
@@ -388,13 +1289,15 @@ pub fn load_config() -> Result<Config, String> {
     // Project config layer
     if let Some(path) = project_config_path() {
         let overlay = load_overlay(&path)?;
+        provenance.record(&overlay, ConfigSource::Project);
+        provenance.project_path = Some(path);
         config = config.merge(overlay);
     }
 
     config.expand_paths();
     config.validate().map_err(|e| e.to_string())?;
 
-    Ok(config)
+    Ok((config, provenance))
 }
 
 // ---------------------------------------------------------------------------
@@ -411,6 +1314,10 @@ mod tests {
         assert_eq!(config.http.timeout, 30);
         assert!(config.http.follow_redirects);
         assert_eq!(config.http.max_redirects, 10);
+        assert_eq!(config.http.default_scheme, "https");
+        assert!(config.http.accept_encoding);
+        assert!(!config.http.compress_body);
+        assert!(config.http.confirm_unresolved);
         assert!(config.proxy.url.is_none());
         assert!(config.proxy.no_proxy.is_none());
         assert!(config.ssl.verify);
@@ -419,6 +1326,10 @@ mod tests {
         assert!(config.ssl.client_key.is_none());
         assert_eq!(config.ui.sidebar_width, 32);
         assert_eq!(config.editor.tab_size, 2);
+        assert!(!config.clipboard.osc52);
+        assert_eq!(config.json.indent, JsonIndent::Spaces(2));
+        assert!(!config.json.sort_keys);
+        assert!(!config.json.ascii_escape);
     }
 
     #[test]
@@ -441,6 +1352,9 @@ sidebar_width = 36
 
 [editor]
 tab_size = 4
+
+[clipboard]
+osc52 = true
 "#;
         let config: Config = toml::from_str(toml_str).unwrap();
         assert_eq!(config.http.timeout, 10);
@@ -451,6 +1365,7 @@ tab_size = 4
         assert!(!config.ssl.verify);
         assert_eq!(config.ui.sidebar_width, 36);
         assert_eq!(config.editor.tab_size, 4);
+        assert!(config.clipboard.osc52);
     }
 
     #[test]
@@ -516,6 +1431,58 @@ timeout = 60
         assert_eq!(merged.ui.sidebar_width, 32);
     }
 
+    #[test]
+    fn test_merge_confirm_unresolved_opt_out() {
+        let overlay: OverlayConfig = toml::from_str("[http]\nconfirm_unresolved = false").unwrap();
+        let merged = Config::default().merge(overlay.clone());
+        assert!(!merged.http.confirm_unresolved);
+        assert!(merged.http.accept_encoding);
+
+        let mut provenance = ConfigProvenance::default();
+        provenance.record(&overlay, ConfigSource::Project);
+        assert_eq!(provenance.source("http.confirm_unresolved"), ConfigSource::Project);
+    }
+
+    #[test]
+    fn test_merge_body_confirmations() {
+        let overlay: OverlayConfig =
+            toml::from_str("[http]\nconfirm_get_body = false\nconfirm_delete_body = true").unwrap();
+        let merged = Config::default().merge(overlay);
+        assert!(!merged.http.confirm_get_body);
+        assert!(merged.http.confirm_delete_body);
+        assert!(Config::default().http.confirm_get_body);
+        assert!(!Config::default().http.confirm_delete_body);
+    }
+
+    #[test]
+    fn test_merge_log_file() {
+        let overlay: OverlayConfig =
+            toml::from_str("[log]\nfile = \"~/perseus-sends.log\"\nkeep = 2").unwrap();
+        let mut merged = Config::default().merge(overlay);
+        merged.expand_paths();
+        assert!(merged.log.file.as_ref().unwrap().ends_with("perseus-sends.log"));
+        assert!(!merged.log.file.as_ref().unwrap().starts_with("~"));
+        assert_eq!(merged.log.keep, 2);
+        assert_eq!(merged.log.max_size_mb, 10);
+
+        merged.log.max_size_mb = 0;
+        let err = merged.validate().unwrap_err();
+        assert!(err.messages[0].contains("log.max_size_mb"));
+    }
+
+    #[test]
+    fn test_merge_json_style() {
+        let base: Config = toml::from_str("[json]\nindent = 4\nsort_keys = true").unwrap();
+        assert_eq!(base.json.indent, JsonIndent::Spaces(4));
+        let overlay: OverlayConfig = toml::from_str("[json]\nindent = \"tab\"").unwrap();
+        let merged = base.merge(overlay);
+        assert_eq!(merged.json.indent, JsonIndent::Tab);
+        assert!(merged.json.sort_keys);
+        assert!(!merged.json.ascii_escape);
+        assert_eq!(merged.json.style().indent, "\t");
+        assert!(toml::from_str::<Config>("[json]\nindent = \"wide\"").is_err());
+    }
+
     #[test]
     fn test_merge_proxy_field_level() {
         let mut base = Config::default();
@@ -535,6 +1502,129 @@ url = "http://project-proxy:9090"
         assert_eq!(merged.proxy.no_proxy.as_deref(), Some("localhost"));
     }
 
+    #[test]
+    fn test_merge_hosts_per_entry() {
+        let mut base = Config::default();
+        base.hosts.insert("api.example.com".into(), "10.0.0.1".into());
+        base.hosts.insert("auth.example.com".into(), "10.0.0.2".into());
+
+        let overlay_str = r#"
+[hosts]
+"API.example.com" = "10.0.0.9:8443"
+"#;
+        let overlay: OverlayConfig = toml::from_str(overlay_str).unwrap();
+        let merged = base.merge(overlay);
+
+        assert_eq!(merged.hosts.get("api.example.com").unwrap(), "10.0.0.9:8443");
+        assert_eq!(merged.hosts.get("auth.example.com").unwrap(), "10.0.0.2");
+    }
+
+    #[test]
+    fn test_merge_statusbar_segments() {
+        let overlay: OverlayConfig = toml::from_str(
+            r#"
+[ui]
+sidebar_width = 40
+
+[ui.statusbar]
+segments = ["mode", "environment", "request"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(overlay.set_keys(), vec!["ui.sidebar_width", "ui.statusbar.segments"]);
+        let merged = Config::default().merge(overlay);
+        assert_eq!(
+            merged.ui.statusbar.segments,
+            vec![StatusSegment::Mode, StatusSegment::Environment, StatusSegment::Request]
+        );
+        assert!(toml::from_str::<OverlayConfig>("[ui.statusbar]\nsegments = [\"clock\"]").is_err());
+
+        let mut twice = merged;
+        twice.ui.statusbar.segments.push(StatusSegment::Mode);
+        let err = twice.validate().unwrap_err();
+        assert!(err.messages[0].contains("\"mode\" more than once"));
+    }
+
+    // -- Provenance tests --
+
+    #[test]
+    fn test_provenance_tracks_last_layer() {
+        let global: OverlayConfig = toml::from_str(
+            r#"
+[http]
+timeout = 10
+max_redirects = 3
+"#,
+        )
+        .unwrap();
+        let project: OverlayConfig = toml::from_str(
+            r#"
+[http]
+timeout = 60
+
+[hosts]
+"API.example.com" = "10.0.0.1"
+"#,
+        )
+        .unwrap();
+
+        let mut provenance = ConfigProvenance::default();
+        provenance.record(&global, ConfigSource::Global);
+        provenance.record(&project, ConfigSource::Project);
+
+        assert_eq!(provenance.source("http.timeout"), ConfigSource::Project);
+        assert_eq!(provenance.source("http.max_redirects"), ConfigSource::Global);
+        assert_eq!(provenance.source("hosts.api.example.com"), ConfigSource::Project);
+        assert_eq!(provenance.source("ui.sidebar_width"), ConfigSource::Default);
+    }
+
+    #[test]
+    fn test_entries_cover_hosts() {
+        let mut config = Config::default();
+        config.hosts.insert("api.example.com".into(), "10.0.0.1".into());
+        let entries = config.entries();
+        assert!(entries.iter().any(|(k, v)| k == "http.timeout" && v == "30"));
+        assert!(entries
+            .iter()
+            .any(|(k, v)| k == "hosts.api.example.com" && v == "\"10.0.0.1\""));
+    }
+
+    #[test]
+    fn test_client_settings_differ() {
+        let base = Config::default();
+        let mut other = base.clone();
+        other.editor.tab_size = 4;
+        assert!(!base.client_settings_differ(&other));
+        other.http.timeout = 5;
+        assert!(base.client_settings_differ(&other));
+    }
+
+    // -- Host override tests --
+
+    #[test]
+    fn test_parse_host_target() {
+        let target = parse_host_target("10.0.0.5:8443").unwrap();
+        assert_eq!(target.ip.to_string(), "10.0.0.5");
+        assert_eq!(target.port, Some(8443));
+
+        let target = parse_host_target("127.0.0.1").unwrap();
+        assert_eq!(target.port, None);
+
+        let target = parse_host_target("[::1]:9000").unwrap();
+        assert_eq!(target.to_string(), "[::1]:9000");
+
+        assert!(parse_host_target("::1").is_some());
+        assert!(parse_host_target("staging.internal").is_none());
+    }
+
+    #[test]
+    fn test_host_override_case_insensitive() {
+        let mut config = Config::default();
+        config.hosts.insert("api.example.com".into(), "10.0.0.5".into());
+        assert!(config.host_override("API.Example.com").is_some());
+        assert!(config.host_override("other.example.com").is_none());
+    }
+
     // -- Validation tests --
 
     #[test]
@@ -560,6 +1650,158 @@ url = "http://project-proxy:9090"
         assert!(err.messages[0].contains("http.max_redirects"));
     }
 
+    #[test]
+    fn test_validate_default_scheme() {
+        let mut config = Config::default();
+        config.http.default_scheme = "ftp".into();
+        let err = config.validate().unwrap_err();
+        assert!(err.messages[0].contains("http.default_scheme"));
+
+        config.http.default_scheme = "http".into();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_cors_origin() {
+        let mut config = Config::default();
+        config.http.cors_origin = Some("localhost:3000".into());
+        let err = config.validate().unwrap_err();
+        assert!(err.messages[0].contains("http.cors_origin"));
+
+        config.http.cors_origin = Some("http://localhost:3000".into());
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_merge_and_validate_user_agent() {
+        let overlay: OverlayConfig =
+            toml::from_str("[http]\nuser_agent = \"gateway/2.1\"\npreserve_header_case = false")
+                .unwrap();
+        let mut config = Config::default().merge(overlay);
+        assert_eq!(config.http.user_agent.as_deref(), Some("gateway/2.1"));
+        assert!(!config.http.preserve_header_case);
+        assert!(config.validate().is_ok());
+
+        config.http.user_agent = Some("bad\nagent".into());
+        let err = config.validate().unwrap_err();
+        assert!(err.messages[0].contains("http.user_agent"));
+    }
+
+    #[test]
+    fn test_validate_default_tabs() {
+        let mut config = Config::default();
+        config.ui.default_request_tab = "Body".into();
+        config.ui.default_response_tab = "raw".into();
+        let err = config.validate().unwrap_err();
+        assert!(err.messages[0].contains("ui.default_request_tab"));
+        assert!(err.messages[1].contains("ui.default_response_tab"));
+
+        config.ui.default_request_tab = "body".into();
+        config.ui.default_response_tab = "headers".into();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_request_defaults() {
+        let mut config = Config::default();
+        config.defaults.new_request_method = "PO ST".into();
+        config.defaults.body_mode = "yaml".into();
+        config.defaults.auth_type = "oauth2".into();
+        config.defaults.auth_token_variable = Some("{{token}}".into());
+        let err = config.validate().unwrap_err();
+        assert!(err.messages[0].contains("defaults.new_request_method"));
+        assert!(err.messages[1].contains("defaults.body_mode"));
+        assert!(err.messages[2].contains("defaults.auth_type"));
+        assert!(err.messages[3].contains("without braces"));
+
+        config.defaults.new_request_method = "post".into();
+        config.defaults.body_mode = "json".into();
+        config.defaults.auth_type = "basic".into();
+        config.defaults.auth_token_variable = Some("token".into());
+        let err = config.validate().unwrap_err();
+        assert!(err.messages[0].contains("needs defaults.auth_type"));
+        config.defaults.auth_type = "bearer".into();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_project_defaults_shape_new_requests() {
+        let global: OverlayConfig =
+            toml::from_str("[defaults]\nnew_request_method = \"post\"\nbody_mode = \"json\"")
+                .unwrap();
+        let project: OverlayConfig = toml::from_str(
+            "[defaults]\nauth_type = \"bearer\"\nauth_token_variable = \"api_token\"",
+        )
+        .unwrap();
+        let config = Config::default().merge(global).merge(project.clone());
+        let mut provenance = ConfigProvenance::default();
+        provenance.record(&project, ConfigSource::Project);
+        assert_eq!(provenance.source("defaults.auth_type"), ConfigSource::Project);
+        assert_eq!(provenance.source("defaults.body_mode"), ConfigSource::Default);
+
+        let request = config.defaults.new_request("{{base_url}}/users".to_string());
+        assert_eq!(request.method, "POST");
+        let body = request.body.unwrap();
+        assert_eq!(body.options.unwrap().raw.unwrap().language, "json");
+        let auth = serde_json::to_value(request.auth.unwrap()).unwrap();
+        assert_eq!(auth["type"], "bearer");
+        assert_eq!(auth["bearer"][0]["value"], "{{api_token}}");
+        assert_eq!(
+            config.defaults.summary().as_deref(),
+            Some("POST, JSON body, Bearer {{api_token}}")
+        );
+
+        let stock = Config::default().defaults;
+        assert_eq!(stock.summary(), None);
+        let request = stock.new_request(String::new());
+        assert_eq!(request.method, "GET");
+        assert!(request.body.is_none() && request.auth.is_none());
+    }
+
+    #[test]
+    fn test_protection_matches_hosts_and_environments() {
+        let overlay: OverlayConfig = toml::from_str(
+            r#"
+[protect]
+hosts = ["*.prod.example.com", "API.example.com"]
+environments = ["Production"]
+"#,
+        )
+        .unwrap();
+        let config = Config::default().merge(overlay);
+        assert!(config.validate().is_ok());
+
+        assert!(host_pattern_matches("*.prod.example.com", "eu.api.prod.example.com"));
+        assert!(!host_pattern_matches("*.prod.example.com", "prod.example.com"));
+        assert!(!host_pattern_matches("*.prod.example.com", "notprod.example.com"));
+        assert_eq!(
+            config.protection("api.example.com.", None).as_deref(),
+            Some("host matches API.example.com")
+        );
+        assert_eq!(
+            config.protection("localhost", Some("production")).as_deref(),
+            Some("environment Production is protected")
+        );
+        assert_eq!(config.protection("staging.example.com", Some("staging")), None);
+    }
+
+    #[test]
+    fn test_validate_protect_host_patterns() {
+        let mut config = Config::default();
+        config.protect.hosts = vec!["api.*.com".into(), "*.".into(), "https://x.com".into()];
+        let err = config.validate().unwrap_err();
+        assert_eq!(err.messages.len(), 3);
+        assert!(err.messages[0].contains("protect.hosts"));
+    }
+
+    #[test]
+    fn test_validate_invalid_host_target() {
+        let mut config = Config::default();
+        config.hosts.insert("api.example.com".into(), "staging".into());
+        let err = config.validate().unwrap_err();
+        assert!(err.messages[0].contains("hosts.\"api.example.com\""));
+    }
+
     #[test]
     fn test_validate_sidebar_width_out_of_range() {
         let mut config = Config::default();
@@ -576,6 +1818,16 @@ url = "http://project-proxy:9090"
         assert!(err.messages[0].contains("editor.tab_size"));
     }
 
+    #[test]
+    fn test_validate_json_indent_out_of_range() {
+        let mut config = Config::default();
+        config.json.indent = JsonIndent::Spaces(0);
+        let err = config.validate().unwrap_err();
+        assert!(err.messages[0].contains("json.indent"));
+        config.json.indent = JsonIndent::Tab;
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     fn test_validate_invalid_proxy_url() {
         let mut config = Config::default();
@@ -0,0 +1,330 @@
+//! The CORS preflight check (`K` on the request panel): the `OPTIONS`
+//! request a browser would send before the open request, and a verdict on
+//! whether the response lets the real request through.
+
+/// Headers a browser sends without asking in the preflight.
+const SAFELISTED_HEADERS: [&str; 4] = ["accept", "accept-language", "content-language", "range"];
+
+/// `Content-Type` values that don't need a preflight either.
+const SAFELISTED_CONTENT_TYPES: [&str; 3] =
+    ["application/x-www-form-urlencoded", "multipart/form-data", "text/plain"];
+
+/// Methods a browser never needs to have allowed.
+const SAFELISTED_METHODS: [&str; 3] = ["GET", "HEAD", "POST"];
+
+/// Browsers cache a preflight this long when the response doesn't say.
+const DEFAULT_MAX_AGE_SECS: u32 = 5;
+
+/// The preflight for one request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preflight {
+    pub origin: String,
+    pub method: String,
+    /// Lowercase names the browser would list in
+    /// `Access-Control-Request-Headers`, sorted.
+    pub headers: Vec<String>,
+}
+
+impl Preflight {
+    /// The preflight for a `method` request from `origin` that sends
+    /// `headers` (name and value). Safelisted headers are left out, as a
+    /// browser leaves them out.
+    pub fn new(origin: &str, method: &str, headers: &[(String, String)]) -> Self {
+        let mut names: Vec<String> = headers
+            .iter()
+            .filter(|(name, value)| !is_safelisted(name, value))
+            .map(|(name, _)| name.trim().to_ascii_lowercase())
+            .filter(|name| !name.is_empty())
+            .collect();
+        names.sort();
+        names.dedup();
+        Self {
+            origin: origin.trim().trim_end_matches('/').to_string(),
+            method: method.to_ascii_uppercase(),
+            headers: names,
+        }
+    }
+
+    /// The headers the `OPTIONS` request carries.
+    pub fn request_headers(&self) -> Vec<(String, String)> {
+        let mut headers = vec![
+            ("Origin".to_string(), self.origin.clone()),
+            ("Access-Control-Request-Method".to_string(), self.method.clone()),
+        ];
+        if !self.headers.is_empty() {
+            headers.push((
+                "Access-Control-Request-Headers".to_string(),
+                self.headers.join(","),
+            ));
+        }
+        headers
+    }
+}
+
+fn is_safelisted(name: &str, value: &str) -> bool {
+    let name = name.trim().to_ascii_lowercase();
+    if name == "content-type" {
+        let essence = value.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+        return SAFELISTED_CONTENT_TYPES.contains(&essence.as_str());
+    }
+    SAFELISTED_HEADERS.contains(&name.as_str())
+}
+
+/// Whether `text` is an origin: a scheme and host, with no path.
+pub fn is_origin(text: &str) -> bool {
+    let Some((scheme, host)) = text.split_once("://") else {
+        return false;
+    };
+    matches!(scheme, "http" | "https")
+        && !host.is_empty()
+        && !host.trim_end_matches('/').contains(['/', '?', '#'])
+}
+
+/// One part of the verdict.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub label: &'static str,
+    pub passed: bool,
+    /// What came back, or what was missing.
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verdict {
+    pub checks: Vec<Check>,
+    /// `Access-Control-Max-Age` as received.
+    pub max_age: Option<String>,
+}
+
+impl Verdict {
+    /// Whether a browser would go on to send the request.
+    pub fn allowed(&self) -> bool {
+        self.checks.iter().all(|check| check.passed)
+    }
+
+    /// "600 s", or the browser default when the response doesn't say.
+    pub fn max_age_text(&self) -> String {
+        let Some(age) = self.max_age.as_deref() else {
+            return format!("not set (browsers cache it for {} s)", DEFAULT_MAX_AGE_SECS);
+        };
+        match age.trim().parse::<i64>() {
+            Ok(secs) if secs <= 0 => "not cached (0 s)".to_string(),
+            Ok(secs) => format!("{} s", secs),
+            Err(_) => format!("\"{}\" isn't a number; browsers ignore it", age),
+        }
+    }
+}
+
+/// Whether a preflight answered with `status` and `headers` (lowercase
+/// names) lets `preflight`'s request through, check by check.
+pub fn evaluate(preflight: &Preflight, status: u16, headers: &[(String, String)]) -> Verdict {
+    let header = |name: &str| {
+        headers.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, v)| v.trim())
+    };
+    let credentials = header("access-control-allow-credentials") == Some("true");
+    let mut checks = vec![Check {
+        label: "Status",
+        passed: (200..300).contains(&status),
+        detail: if (200..300).contains(&status) {
+            status.to_string()
+        } else {
+            format!("{}, browsers need a 2xx answer", status)
+        },
+    }];
+
+    checks.push(match header("access-control-allow-origin") {
+        None => missing("Origin", "Access-Control-Allow-Origin"),
+        Some("*") if credentials => Check {
+            label: "Origin",
+            passed: false,
+            detail: "* isn't allowed with Access-Control-Allow-Credentials: true".to_string(),
+        },
+        Some(allowed) => Check {
+            label: "Origin",
+            passed: allowed == "*" || allowed == preflight.origin,
+            detail: if allowed == "*" || allowed == preflight.origin {
+                allowed.to_string()
+            } else {
+                format!("{} (not {})", allowed, preflight.origin)
+            },
+        },
+    });
+
+    let methods = list(header("access-control-allow-methods"));
+    let method_allowed = methods.iter().any(|m| *m == preflight.method)
+        || (methods.contains(&"*") && !credentials);
+    checks.push(if SAFELISTED_METHODS.contains(&preflight.method.as_str()) {
+        Check {
+            label: "Method",
+            passed: true,
+            detail: format!("{} needs no permission", preflight.method),
+        }
+    } else if methods.is_empty() {
+        missing("Method", "Access-Control-Allow-Methods")
+    } else {
+        Check {
+            label: "Method",
+            passed: method_allowed,
+            detail: if method_allowed {
+                format!("{} in {}", preflight.method, methods.join(", "))
+            } else {
+                format!("{} not in {}", preflight.method, methods.join(", "))
+            },
+        }
+    });
+
+    if !preflight.headers.is_empty() {
+        let allowed = list(header("access-control-allow-headers"));
+        let wildcard = allowed.contains(&"*") && !credentials;
+        // `*` never covers Authorization
+        let refused: Vec<&str> = preflight
+            .headers
+            .iter()
+            .map(String::as_str)
+            .filter(|name| {
+                let listed = allowed.iter().any(|a| a.eq_ignore_ascii_case(name));
+                !listed && (!wildcard || *name == "authorization")
+            })
+            .collect();
+        checks.push(if allowed.is_empty() {
+            missing("Headers", "Access-Control-Allow-Headers")
+        } else {
+            Check {
+                label: "Headers",
+                passed: refused.is_empty(),
+                detail: if refused.is_empty() {
+                    preflight.headers.join(", ")
+                } else {
+                    format!("not allowed: {}", refused.join(", "))
+                },
+            }
+        });
+    }
+
+    Verdict {
+        checks,
+        max_age: header("access-control-max-age").map(str::to_string),
+    }
+}
+
+fn missing(label: &'static str, header: &str) -> Check {
+    Check {
+        label,
+        passed: false,
+        detail: format!("no {} header", header),
+    }
+}
+
+/// The entries of a comma-separated header value.
+fn list(value: Option<&str>) -> Vec<&str> {
+    value
+        .unwrap_or("")
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(list: &[(&str, &str)]) -> Vec<(String, String)> {
+        list.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    fn put_preflight() -> Preflight {
+        let headers = pairs(&[
+            ("Content-Type", "application/json"),
+            ("X-Trace", "{{trace}}"),
+            ("Accept", "*/*"),
+            ("x-trace", "again"),
+            ("Authorization", "Bearer t"),
+        ]);
+        Preflight::new("https://app.example.com/", "put", &headers)
+    }
+
+    #[test]
+    fn test_preflight_lists_non_safelisted_headers() {
+        let preflight = put_preflight();
+        assert_eq!(preflight.origin, "https://app.example.com");
+        assert_eq!(preflight.method, "PUT");
+        assert_eq!(preflight.headers, vec!["authorization", "content-type", "x-trace"]);
+        assert_eq!(
+            preflight.request_headers()[2],
+            (
+                "Access-Control-Request-Headers".to_string(),
+                "authorization,content-type,x-trace".to_string()
+            )
+        );
+
+        // A form post needs no header list at all
+        let form = pairs(&[("Content-Type", "text/plain; charset=utf-8")]);
+        let simple = Preflight::new("http://localhost:3000", "POST", &form);
+        assert!(simple.headers.is_empty());
+        assert_eq!(simple.request_headers().len(), 2);
+    }
+
+    #[test]
+    fn test_evaluate_allowed_preflight() {
+        let headers = pairs(&[
+            ("access-control-allow-origin", "https://app.example.com"),
+            ("access-control-allow-methods", "GET, PUT, DELETE"),
+            ("access-control-allow-headers", "Authorization, Content-Type, X-Trace"),
+            ("access-control-max-age", "600"),
+        ]);
+        let verdict = evaluate(&put_preflight(), 204, &headers);
+        assert!(verdict.allowed(), "{:?}", verdict);
+        assert_eq!(verdict.checks.len(), 4);
+        assert_eq!(verdict.max_age_text(), "600 s");
+    }
+
+    #[test]
+    fn test_evaluate_blocked_preflights() {
+        let preflight = put_preflight();
+        // No CORS headers at all, as from a server that doesn't know CORS
+        let verdict = evaluate(&preflight, 404, &[]);
+        assert!(!verdict.allowed());
+        assert!(verdict.checks.iter().all(|check| !check.passed));
+        assert_eq!(verdict.checks[1].detail, "no Access-Control-Allow-Origin header");
+        assert!(verdict.max_age_text().starts_with("not set"));
+
+        // Another origin, a missing method and a wildcard that skips
+        // Authorization
+        let headers = pairs(&[
+            ("access-control-allow-origin", "https://admin.example.com"),
+            ("access-control-allow-methods", "GET, POST"),
+            ("access-control-allow-headers", "*"),
+        ]);
+        let verdict = evaluate(&preflight, 200, &headers);
+        let failed: Vec<&str> =
+            verdict.checks.iter().filter(|c| !c.passed).map(|c| c.label).collect();
+        assert_eq!(failed, vec!["Origin", "Method", "Headers"]);
+        assert_eq!(verdict.checks[3].detail, "not allowed: authorization");
+
+        // Credentials rule out the wildcards
+        let headers = pairs(&[
+            ("access-control-allow-origin", "*"),
+            ("access-control-allow-credentials", "true"),
+            ("access-control-allow-methods", "*"),
+        ]);
+        let verdict = evaluate(&preflight, 200, &headers);
+        assert!(!verdict.checks[1].passed);
+        assert!(!verdict.checks[2].passed);
+    }
+
+    #[test]
+    fn test_evaluate_safelisted_method_and_origin() {
+        let preflight = Preflight::new("http://localhost:3000", "GET", &[]);
+        let headers = pairs(&[("access-control-allow-origin", "*")]);
+        let verdict = evaluate(&preflight, 200, &headers);
+        assert!(verdict.allowed());
+        assert_eq!(verdict.checks.len(), 3);
+
+        assert!(is_origin("http://localhost:3000"));
+        assert!(is_origin("https://app.example.com/"));
+        assert!(!is_origin("app.example.com"));
+        assert!(!is_origin("https://app.example.com/login"));
+        assert!(!is_origin("ftp://files.example.com"));
+    }
+}
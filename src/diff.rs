@@ -0,0 +1,192 @@
+//! Line diffs and header deltas for comparing two responses.
+
+/// Line-pair budget for the LCS table; larger inputs fall back to showing
+/// the differing middle as one removed block followed by one added block.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+/// Diffs two texts line by line (longest common subsequence), keeping the
+/// common prefix and suffix out of the quadratic part.
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut out: Vec<DiffLine> =
+        old[..prefix].iter().map(|l| DiffLine::Same(l.to_string())).collect();
+    if (old_mid.len() + 1) * (new_mid.len() + 1) > MAX_DIFF_CELLS {
+        out.extend(old_mid.iter().map(|l| DiffLine::Removed(l.to_string())));
+        out.extend(new_mid.iter().map(|l| DiffLine::Added(l.to_string())));
+    } else {
+        out.extend(lcs_diff(old_mid, new_mid));
+    }
+    out.extend(old[old.len() - suffix..].iter().map(|l| DiffLine::Same(l.to_string())));
+    out
+}
+
+fn lcs_diff(old: &[&str], new: &[&str]) -> Vec<DiffLine> {
+    let (n, m) = (old.len(), new.len());
+    // lengths[i][j]: LCS length of old[i..] and new[j..]
+    let mut lengths = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut out = Vec::with_capacity(n + m);
+    while i < n && j < m {
+        if old[i] == new[j] {
+            out.push(DiffLine::Same(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            out.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            out.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+    out.extend(old[i..].iter().map(|l| DiffLine::Removed(l.to_string())));
+    out.extend(new[j..].iter().map(|l| DiffLine::Added(l.to_string())));
+    out
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeaderChange {
+    Added { name: String, value: String },
+    Removed { name: String, value: String },
+    Changed { name: String, old: String, new: String },
+}
+
+/// Header-by-header differences, matching names case-insensitively. Repeated
+/// headers are compared by their joined values. Ordered by header name.
+pub fn header_delta(old: &[(String, String)], new: &[(String, String)]) -> Vec<HeaderChange> {
+    let collect = |headers: &[(String, String)]| {
+        let mut map: std::collections::BTreeMap<String, Vec<&str>> = Default::default();
+        for (name, value) in headers {
+            map.entry(name.to_ascii_lowercase()).or_default().push(value.as_str());
+        }
+        map.into_iter()
+            .map(|(name, values)| (name, values.join(", ")))
+            .collect::<std::collections::BTreeMap<_, _>>()
+    };
+    let old = collect(old);
+    let new = collect(new);
+    let mut names: Vec<&String> = old.keys().chain(new.keys()).collect();
+    names.sort();
+    names.dedup();
+    names
+        .into_iter()
+        .filter_map(|name| match (old.get(name), new.get(name)) {
+            (Some(a), Some(b)) if a == b => None,
+            (Some(a), Some(b)) => Some(HeaderChange::Changed {
+                name: name.clone(),
+                old: a.clone(),
+                new: b.clone(),
+            }),
+            (Some(a), None) => Some(HeaderChange::Removed {
+                name: name.clone(),
+                value: a.clone(),
+            }),
+            (None, Some(b)) => Some(HeaderChange::Added {
+                name: name.clone(),
+                value: b.clone(),
+            }),
+            (None, None) => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(lines: &[DiffLine]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|line| match line {
+                DiffLine::Same(l) => format!(" {}", l),
+                DiffLine::Removed(l) => format!("-{}", l),
+                DiffLine::Added(l) => format!("+{}", l),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_lines_marks_changes() {
+        let old = "{\n  \"id\": 1,\n  \"env\": \"dev\",\n  \"ok\": true\n}";
+        let new = "{\n  \"id\": 1,\n  \"env\": \"staging\",\n  \"ok\": true,\n  \"x\": 2\n}";
+        assert_eq!(
+            render(&diff_lines(old, new)),
+            vec![
+                " {",
+                "   \"id\": 1,",
+                "-  \"env\": \"dev\",",
+                "-  \"ok\": true",
+                "+  \"env\": \"staging\",",
+                "+  \"ok\": true,",
+                "+  \"x\": 2",
+                " }",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_lines_identical_and_empty() {
+        assert!(diff_lines("a\nb", "a\nb").iter().all(|l| matches!(l, DiffLine::Same(_))));
+        assert_eq!(render(&diff_lines("", "a")), vec!["+a"]);
+        assert_eq!(render(&diff_lines("a", "")), vec!["-a"]);
+    }
+
+    #[test]
+    fn test_header_delta() {
+        let old = vec![
+            ("Content-Type".to_string(), "application/json".to_string()),
+            ("ETag".to_string(), "\"1\"".to_string()),
+            ("X-Old".to_string(), "yes".to_string()),
+        ];
+        let new = vec![
+            ("content-type".to_string(), "application/json".to_string()),
+            ("etag".to_string(), "\"2\"".to_string()),
+            ("X-New".to_string(), "1".to_string()),
+        ];
+        assert_eq!(
+            header_delta(&old, &new),
+            vec![
+                HeaderChange::Changed {
+                    name: "etag".to_string(),
+                    old: "\"1\"".to_string(),
+                    new: "\"2\"".to_string(),
+                },
+                HeaderChange::Added {
+                    name: "x-new".to_string(),
+                    value: "1".to_string(),
+                },
+                HeaderChange::Removed {
+                    name: "x-old".to_string(),
+                    value: "yes".to_string(),
+                },
+            ]
+        );
+    }
+}
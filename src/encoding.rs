@@ -0,0 +1,182 @@
+//! Decoding response bodies into display text. The charset comes from a
+//! byte order mark, else the `charset` parameter of `Content-Type`, else
+//! UTF-8. Bytes that aren't valid in it become U+FFFD and the result is
+//! flagged as lossy; the raw bytes are kept elsewhere for saving.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Charset {
+    #[default]
+    Utf8,
+    Latin1,
+    Windows1252,
+    Utf16Le,
+    Utf16Be,
+}
+
+/// Windows-1252 characters for bytes 0x80–0x9F. The five bytes it leaves
+/// undefined map to the C1 controls, as browsers do.
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '\u{20ac}', '\u{81}', '\u{201a}', '\u{192}', '\u{201e}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{2c6}', '\u{2030}', '\u{160}', '\u{2039}', '\u{152}', '\u{8d}', '\u{17d}', '\u{8f}',
+    '\u{90}', '\u{2018}', '\u{2019}', '\u{201c}', '\u{201d}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{2dc}', '\u{2122}', '\u{161}', '\u{203a}', '\u{153}', '\u{9d}', '\u{17e}', '\u{178}',
+];
+
+impl Charset {
+    pub fn label(self) -> &'static str {
+        match self {
+            Charset::Utf8 => "utf-8",
+            Charset::Latin1 => "iso-8859-1",
+            Charset::Windows1252 => "windows-1252",
+            Charset::Utf16Le => "utf-16le",
+            Charset::Utf16Be => "utf-16be",
+        }
+    }
+
+    /// The charset a `charset=` label names, if it is one we decode.
+    pub fn from_label(label: &str) -> Option<Self> {
+        let label = label.trim().trim_matches('"').to_ascii_lowercase();
+        let charset = match label.as_str() {
+            "utf-8" | "utf8" | "unicode-1-1-utf-8" | "us-ascii" | "ascii" => Charset::Utf8,
+            "iso-8859-1" | "iso8859-1" | "iso_8859-1" | "latin1" | "latin-1" | "l1" => {
+                Charset::Latin1
+            }
+            "windows-1252" | "cp1252" | "x-cp1252" => Charset::Windows1252,
+            // Without a byte order mark UTF-16 is big-endian (RFC 2781)
+            "utf-16" | "utf-16be" => Charset::Utf16Be,
+            "utf-16le" => Charset::Utf16Le,
+            _ => return None,
+        };
+        Some(charset)
+    }
+
+    pub fn is_utf16(self) -> bool {
+        matches!(self, Charset::Utf16Le | Charset::Utf16Be)
+    }
+}
+
+/// The charset named in a `Content-Type` value, if any.
+pub fn declared_charset(content_type: &str) -> Option<Charset> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim().eq_ignore_ascii_case("charset").then(|| Charset::from_label(value))?
+    })
+}
+
+/// The charset to decode `bytes` with. A byte order mark wins over the
+/// declared charset.
+pub fn detect(bytes: &[u8], content_type: Option<&str>) -> Charset {
+    match bytes {
+        [0xef, 0xbb, 0xbf, ..] => Charset::Utf8,
+        [0xff, 0xfe, ..] => Charset::Utf16Le,
+        [0xfe, 0xff, ..] => Charset::Utf16Be,
+        _ => content_type.and_then(declared_charset).unwrap_or_default(),
+    }
+}
+
+/// `bytes` as text in `charset`, without a byte order mark, and whether
+/// anything had to be replaced.
+pub fn decode(bytes: &[u8], charset: Charset) -> (String, bool) {
+    match charset {
+        Charset::Utf8 => {
+            let bytes = bytes.strip_prefix(&[0xef, 0xbb, 0xbf]).unwrap_or(bytes);
+            match std::str::from_utf8(bytes) {
+                Ok(text) => (text.to_string(), false),
+                Err(_) => (String::from_utf8_lossy(bytes).into_owned(), true),
+            }
+        }
+        Charset::Latin1 => (bytes.iter().map(|&b| char::from(b)).collect(), false),
+        Charset::Windows1252 => {
+            let text = bytes
+                .iter()
+                .map(|&b| match b {
+                    0x80..=0x9f => WINDOWS_1252_HIGH[usize::from(b - 0x80)],
+                    _ => char::from(b),
+                })
+                .collect();
+            (text, false)
+        }
+        Charset::Utf16Le | Charset::Utf16Be => {
+            let little_endian = charset == Charset::Utf16Le;
+            let bom: &[u8] = if little_endian { &[0xff, 0xfe] } else { &[0xfe, 0xff] };
+            let bytes = bytes.strip_prefix(bom).unwrap_or(bytes);
+            let units = bytes.chunks_exact(2).map(|pair| {
+                let pair = [pair[0], pair[1]];
+                if little_endian {
+                    u16::from_le_bytes(pair)
+                } else {
+                    u16::from_be_bytes(pair)
+                }
+            });
+            let mut lossy = bytes.len() % 2 == 1;
+            let mut text: String = char::decode_utf16(units)
+                .map(|unit| {
+                    unit.unwrap_or_else(|_| {
+                        lossy = true;
+                        char::REPLACEMENT_CHARACTER
+                    })
+                })
+                .collect();
+            if bytes.len() % 2 == 1 {
+                text.push(char::REPLACEMENT_CHARACTER);
+            }
+            (text, lossy)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_declared_charset() {
+        let declared = declared_charset;
+        assert_eq!(declared("text/plain; charset=ISO-8859-1"), Some(Charset::Latin1));
+        assert_eq!(declared("text/html;charset=\"windows-1252\""), Some(Charset::Windows1252));
+        assert_eq!(declared("application/json; charset=utf-16le"), Some(Charset::Utf16Le));
+        assert_eq!(declared("text/plain; format=flowed"), None);
+        assert_eq!(declared("text/plain; charset=koi8-r"), None);
+        assert_eq!(declared("text/plain"), None);
+    }
+
+    #[test]
+    fn test_detect_prefers_byte_order_mark() {
+        let latin1 = Some("text/plain; charset=latin1");
+        assert_eq!(detect(b"caf\xe9", latin1), Charset::Latin1);
+        assert_eq!(detect(b"\xff\xfeh\x00", latin1), Charset::Utf16Le);
+        assert_eq!(detect(b"\xfe\xff\x00h", None), Charset::Utf16Be);
+        assert_eq!(detect(b"\xef\xbb\xbfhi", latin1), Charset::Utf8);
+        assert_eq!(detect(b"plain", None), Charset::Utf8);
+    }
+
+    #[test]
+    fn test_decode_latin1_and_windows_1252() {
+        let bytes = b"caf\xe9 \x80 \x93quoted\x94";
+        assert_eq!(
+            decode(bytes, Charset::Latin1),
+            ("caf\u{e9} \u{80} \u{93}quoted\u{94}".to_string(), false)
+        );
+        assert_eq!(
+            decode(bytes, Charset::Windows1252),
+            ("caf\u{e9} \u{20ac} \u{201c}quoted\u{201d}".to_string(), false)
+        );
+    }
+
+    #[test]
+    fn test_decode_utf16() {
+        let le = b"\xff\xfeh\x00\xe9\x00=\xd8\x00\xde";
+        assert_eq!(decode(le, Charset::Utf16Le), ("h\u{e9}\u{1f600}".to_string(), false));
+        let be = b"\x00h\x00\xe9";
+        assert_eq!(decode(be, Charset::Utf16Be), ("h\u{e9}".to_string(), false));
+        // A lone surrogate and a dangling byte
+        let broken = b"\x00\xd8h\x00!";
+        assert_eq!(decode(broken, Charset::Utf16Le), ("\u{fffd}h\u{fffd}".to_string(), true));
+    }
+
+    #[test]
+    fn test_decode_utf8_falls_back_to_replacement() {
+        assert_eq!(decode(b"\xef\xbb\xbfok", Charset::Utf8), ("ok".to_string(), false));
+        assert_eq!(decode(b"caf\xe9", Charset::Utf8), ("caf\u{fffd}".to_string(), true));
+    }
+}
@@ -0,0 +1,386 @@
+//! Small HTML-to-text converter used by the response preview. It is not a
+//! browser: it walks the tag stream once, drops invisible content, and turns
+//! block structure into line breaks so a page reads sensibly in a terminal.
+
+/// Elements whose content is never shown.
+const HIDDEN_TAGS: &[&str] = &["script", "style", "head", "title", "noscript", "template", "svg"];
+
+/// Elements that start and end on their own line.
+const BLOCK_TAGS: &[&str] = &[
+    "p", "div", "section", "article", "header", "footer", "nav", "main", "aside", "form",
+    "table", "tr", "ul", "ol", "dl", "dt", "dd", "blockquote", "figure", "figcaption",
+    "address", "fieldset", "details", "summary",
+];
+
+pub fn is_html(headers: &[(String, String)]) -> bool {
+    crate::http::header_value(headers, "content-type")
+        .is_some_and(|v| v.to_ascii_lowercase().contains("text/html"))
+}
+
+enum ListKind {
+    Unordered,
+    Ordered(usize),
+}
+
+struct Writer {
+    out: String,
+    /// Whitespace was seen since the last emitted character.
+    pending_space: bool,
+    pre_depth: usize,
+    hidden_depth: usize,
+    lists: Vec<ListKind>,
+    /// Open anchors: (href, output offset where the link text starts).
+    links: Vec<(Option<String>, usize)>,
+}
+
+impl Writer {
+    fn new() -> Self {
+        Self {
+            out: String::new(),
+            pending_space: false,
+            pre_depth: 0,
+            hidden_depth: 0,
+            lists: Vec::new(),
+            links: Vec::new(),
+        }
+    }
+
+    fn at_line_start(&self) -> bool {
+        self.out.is_empty() || self.out.ends_with('\n')
+    }
+
+    fn newline(&mut self) {
+        if !self.at_line_start() {
+            self.out.push('\n');
+        }
+        self.pending_space = false;
+    }
+
+    fn blank_line(&mut self) {
+        self.newline();
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    fn indent(&mut self) {
+        if self.at_line_start() && !self.lists.is_empty() {
+            let depth = self.lists.len().saturating_sub(1);
+            self.out.push_str(&"  ".repeat(depth));
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.hidden_depth > 0 {
+            return;
+        }
+        if self.pre_depth > 0 {
+            self.out.push_str(text);
+            return;
+        }
+        for ch in text.chars() {
+            if ch.is_whitespace() {
+                self.pending_space = true;
+                continue;
+            }
+            if self.pending_space && !self.at_line_start() && !self.out.ends_with(' ') {
+                self.out.push(' ');
+            }
+            self.pending_space = false;
+            self.indent();
+            self.out.push(ch);
+        }
+    }
+
+    fn open(&mut self, name: &str, attrs: &str) {
+        if HIDDEN_TAGS.contains(&name) {
+            self.hidden_depth += 1;
+            return;
+        }
+        if self.hidden_depth > 0 {
+            return;
+        }
+        match name {
+            "br" => {
+                self.out.push('\n');
+                self.pending_space = false;
+            }
+            "hr" => {
+                self.newline();
+                self.out.push_str("----------------------------------------\n");
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.blank_line();
+                let level = name[1..].parse::<usize>().unwrap_or(1);
+                self.out.push_str(&"#".repeat(level));
+                self.out.push(' ');
+            }
+            "pre" => {
+                self.blank_line();
+                self.pre_depth += 1;
+            }
+            "ul" => {
+                self.newline();
+                self.lists.push(ListKind::Unordered);
+            }
+            "ol" => {
+                self.newline();
+                self.lists.push(ListKind::Ordered(0));
+            }
+            "li" => {
+                self.newline();
+                self.indent();
+                let marker = match self.lists.last_mut() {
+                    Some(ListKind::Ordered(n)) => {
+                        *n += 1;
+                        format!("{}. ", n)
+                    }
+                    _ => "- ".to_string(),
+                };
+                self.out.push_str(&marker);
+            }
+            "td" | "th" => {
+                if !self.at_line_start() {
+                    self.out.push_str(" | ");
+                }
+                self.pending_space = false;
+            }
+            "a" => {
+                let href = attr_value(attrs, "href");
+                self.links.push((href, self.out.len()));
+            }
+            "img" => {
+                if let Some(alt) = attr_value(attrs, "alt").filter(|a| !a.trim().is_empty()) {
+                    self.text(&format!("[{}]", alt.trim()));
+                }
+            }
+            "p" | "blockquote" => self.blank_line(),
+            _ if BLOCK_TAGS.contains(&name) => self.newline(),
+            _ => {}
+        }
+    }
+
+    fn close(&mut self, name: &str) {
+        if HIDDEN_TAGS.contains(&name) {
+            self.hidden_depth = self.hidden_depth.saturating_sub(1);
+            return;
+        }
+        if self.hidden_depth > 0 {
+            return;
+        }
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "p" | "blockquote" => self.blank_line(),
+            "pre" => {
+                self.pre_depth = self.pre_depth.saturating_sub(1);
+                self.blank_line();
+            }
+            "ul" | "ol" => {
+                self.lists.pop();
+                self.newline();
+            }
+            "a" => {
+                if let Some((Some(href), start)) = self.links.pop() {
+                    let label = self.out.get(start..).unwrap_or("").trim();
+                    let plain = href.starts_with('#') || href.starts_with("javascript:");
+                    if !plain && label != href {
+                        if label.is_empty() {
+                            self.text(&href);
+                        } else {
+                            self.out.push_str(&format!(" ({})", href));
+                        }
+                    }
+                }
+            }
+            _ if BLOCK_TAGS.contains(&name) => self.newline(),
+            _ => {}
+        }
+    }
+}
+
+/// Converts an HTML document to readable plain text: headings become `#`
+/// lines, lists get `-`/`1.` markers, links are written as `text (url)` and
+/// `<pre>` content keeps its whitespace.
+pub fn html_to_text(html: &str) -> String {
+    let mut writer = Writer::new();
+    let mut rest = html;
+    while !rest.is_empty() {
+        let Some(lt) = rest.find('<') else {
+            writer.text(&decode_entities(rest));
+            break;
+        };
+        if lt > 0 {
+            writer.text(&decode_entities(&rest[..lt]));
+        }
+        rest = &rest[lt..];
+        if let Some(after) = rest.strip_prefix("<!--") {
+            rest = after.find("-->").map(|end| &after[end + 3..]).unwrap_or("");
+            continue;
+        }
+        let Some(gt) = rest.find('>') else {
+            writer.text(&decode_entities(rest));
+            break;
+        };
+        let tag = &rest[1..gt];
+        rest = &rest[gt + 1..];
+        if tag.starts_with('!') || tag.starts_with('?') {
+            continue;
+        }
+        let (closing, tag) = match tag.strip_prefix('/') {
+            Some(t) => (true, t),
+            None => (false, tag),
+        };
+        let tag = tag.trim_end_matches('/');
+        let name_end = tag
+            .find(|c: char| c.is_whitespace())
+            .unwrap_or(tag.len());
+        let name = tag[..name_end].to_ascii_lowercase();
+        if name.is_empty() {
+            writer.text("<");
+            continue;
+        }
+        if closing {
+            writer.close(&name);
+        } else {
+            writer.open(&name, &tag[name_end..]);
+            if (name == "script" || name == "style") && writer.hidden_depth > 0 {
+                // Raw text elements: skip to the matching close tag so `<` in
+                // scripts isn't parsed as markup.
+                let close = format!("</{}", name);
+                let end = rest.to_ascii_lowercase().find(&close).unwrap_or(rest.len());
+                rest = &rest[end..];
+            }
+        }
+    }
+
+    let mut lines: Vec<&str> = writer.out.lines().map(|l| l.trim_end()).collect();
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+    let start = lines.iter().position(|l| !l.is_empty()).unwrap_or(lines.len());
+    let mut result = String::new();
+    let mut blank_run = 0;
+    for line in &lines[start..] {
+        if line.is_empty() {
+            blank_run += 1;
+            if blank_run > 1 {
+                continue;
+            }
+        } else {
+            blank_run = 0;
+        }
+        result.push_str(line);
+        result.push('\n');
+    }
+    result.pop();
+    result
+}
+
+fn attr_value(attrs: &str, name: &str) -> Option<String> {
+    let lower = attrs.to_ascii_lowercase();
+    let mut search = 0;
+    while let Some(pos) = lower[search..].find(name) {
+        let idx = search + pos;
+        search = idx + name.len();
+        let boundary = idx == 0 || lower.as_bytes()[idx - 1].is_ascii_whitespace();
+        let rest = attrs[search..].trim_start();
+        if !boundary || !rest.starts_with('=') {
+            continue;
+        }
+        let rest = rest[1..].trim_start();
+        let value = match rest.chars().next() {
+            Some(q @ ('"' | '\'')) => rest[1..].split(q).next().unwrap_or(""),
+            _ => rest.split(|c: char| c.is_whitespace()).next().unwrap_or(""),
+        };
+        return Some(decode_entities(value));
+    }
+    None
+}
+
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest[1..].find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end + 1];
+            let ch = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                "mdash" => Some('—'),
+                "ndash" => Some('–'),
+                "hellip" => Some('…'),
+                "copy" => Some('©'),
+                _ => {
+                    let num = entity.strip_prefix('#')?;
+                    let code = match num.strip_prefix(['x', 'X']) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                        None => num.parse::<u32>().ok(),
+                    };
+                    code.and_then(char::from_u32)
+                }
+            };
+            ch.map(|c| (c, end + 2))
+        });
+        match decoded {
+            Some((ch, len)) => {
+                out.push(ch);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_to_text_strips_tags_and_hidden_content() {
+        let html = "<html><head><title>T</title><style>p{}</style></head>\
+                    <body><script>if (a < b) {}</script><p>Hello <b>world</b></p></body></html>";
+        assert_eq!(html_to_text(html), "Hello world");
+    }
+
+    #[test]
+    fn test_html_to_text_headings_links_and_lists() {
+        let html = "<h1>Title</h1><p>See <a href=\"https://x.io\">docs</a>.</p>\
+                    <ul><li>one</li><li>two</li></ul><ol><li>a</li><li>b</li></ol>";
+        assert_eq!(
+            html_to_text(html),
+            "# Title\n\nSee docs (https://x.io).\n\n- one\n- two\n1. a\n2. b"
+        );
+    }
+
+    #[test]
+    fn test_html_to_text_preserves_pre() {
+        let html = "<p>code:</p><pre>fn main() {\n    x &lt; 1;\n}</pre>";
+        assert_eq!(html_to_text(html), "code:\n\nfn main() {\n    x < 1;\n}");
+    }
+
+    #[test]
+    fn test_html_to_text_collapses_whitespace_and_decodes_entities() {
+        let html = "<div>  a\n\n   b &amp; c&#33; &#x41;&unknown;</div>";
+        assert_eq!(html_to_text(html), "a b & c! A&unknown;");
+    }
+
+    #[test]
+    fn test_is_html() {
+        let headers = vec![("Content-Type".to_string(), "text/html; charset=utf-8".to_string())];
+        assert!(is_html(&headers));
+        assert!(!is_html(&[("content-type".to_string(), "application/json".to_string())]));
+    }
+}
@@ -0,0 +1,458 @@
+//! HTTP/1.1 written and read by hand, for sends that keep header names as
+//! typed. hyper, under reqwest, lowercases names on the way out and on the
+//! way in; here they go out spelled as given (`SOAPAction`) and come back
+//! as the server sent them.
+
+use std::fmt;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use hyper_util::client::proxy::matcher::Matcher;
+use reqwest::header::{ACCEPT, CONTENT_LENGTH, HOST, TRANSFER_ENCODING};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// A response head larger than this is refused rather than buffered.
+const MAX_HEAD_BYTES: usize = 64 * 1024;
+
+trait Stream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Stream for T {}
+
+/// Where sends connect to: the TLS settings and `[hosts]` overrides the
+/// reqwest client is built with, and the proxies it would use.
+#[derive(Clone)]
+pub struct Connector {
+    tls: tokio_native_tls::TlsConnector,
+    resolve: Vec<(String, IpAddr)>,
+    proxies: Arc<Matcher>,
+}
+
+impl Connector {
+    pub fn new(
+        tls: native_tls::TlsConnector,
+        resolve: Vec<(String, IpAddr)>,
+        proxies: Matcher,
+    ) -> Self {
+        Self {
+            tls: tls.into(),
+            resolve,
+            proxies: Arc::new(proxies),
+        }
+    }
+
+    /// Whether `request` can be sent here: plain or TLS HTTP/1.1 with the
+    /// body already in memory, to a URL no proxy applies to. Streamed
+    /// (multipart) bodies and proxied requests stay with reqwest.
+    pub fn sends(&self, request: &reqwest::Request) -> bool {
+        let direct = request
+            .url()
+            .as_str()
+            .parse::<http::Uri>()
+            .is_ok_and(|uri| self.proxies.intercept(&uri).is_none());
+        direct
+            && matches!(request.url().scheme(), "http" | "https")
+            && request.body().is_none_or(|body| body.as_bytes().is_some())
+    }
+
+    async fn connect(&self, url: &reqwest::Url) -> Result<Box<dyn Stream>, Error> {
+        let host = url.host_str().unwrap_or_default();
+        // IPv6 literals keep their brackets in the URL only
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let port = url.port_or_known_default().unwrap_or(80);
+        let addrs: Vec<SocketAddr> = match self.resolve.iter().find(|(name, _)| name == host) {
+            Some((_, ip)) => vec![SocketAddr::new(*ip, port)],
+            None => tokio::net::lookup_host((host, port)).await.map_err(Error::Dns)?.collect(),
+        };
+        let mut failure = io::Error::new(io::ErrorKind::NotFound, "no addresses to connect to");
+        let mut tcp = None;
+        for addr in addrs {
+            match TcpStream::connect(addr).await {
+                Ok(stream) => {
+                    tcp = Some(stream);
+                    break;
+                }
+                Err(err) => failure = err,
+            }
+        }
+        let tcp = tcp.ok_or(Error::Connect(failure))?;
+        if url.scheme() == "https" {
+            let stream = self.tls.connect(host, tcp).await.map_err(Error::Tls)?;
+            Ok(Box::new(stream))
+        } else {
+            Ok(Box::new(tcp))
+        }
+    }
+}
+
+/// Why a send failed before its response head was read.
+#[derive(Debug)]
+pub enum Error {
+    Dns(io::Error),
+    Connect(io::Error),
+    Tls(native_tls::Error),
+    /// Writing the request or reading the response head.
+    Io(io::Error),
+    /// The response head isn't HTTP/1.x.
+    Malformed(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Dns(err) => write!(f, "dns error: {}", err),
+            Error::Connect(err) => write!(f, "error trying to connect: {}", err),
+            Error::Tls(err) => write!(f, "tls handshake failed: {}", err),
+            Error::Io(err) => write!(f, "connection error: {}", err),
+            Error::Malformed(msg) => write!(f, "invalid response: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Dns(err) | Error::Connect(err) | Error::Io(err) => Some(err),
+            Error::Tls(err) => Some(err),
+            Error::Malformed(_) => None,
+        }
+    }
+}
+
+/// Header names spelled the way [`send`] writes them: as typed where one of
+/// the typed names matches, each typed spelling used once, else title-cased.
+pub struct Spelling<'a> {
+    typed: Vec<&'a str>,
+}
+
+impl<'a> Spelling<'a> {
+    pub fn new(typed: &'a [String]) -> Self {
+        Self {
+            typed: typed.iter().map(String::as_str).collect(),
+        }
+    }
+
+    pub fn of(&mut self, name: &str) -> String {
+        match self.typed.iter().position(|typed| typed.eq_ignore_ascii_case(name)) {
+            Some(index) => self.typed.remove(index).to_string(),
+            None => crate::http::canonical_header_name(name),
+        }
+    }
+}
+
+/// The request line and headers of `request`, with `Host`, `Accept` and
+/// `Content-Length` added where reqwest would add them.
+fn request_head(request: &reqwest::Request, typed: &[String]) -> Vec<u8> {
+    let url = request.url();
+    let mut target = url.path().to_string();
+    if let Some(query) = url.query() {
+        target.push('?');
+        target.push_str(query);
+    }
+    let mut head = format!("{} {} HTTP/1.1\r\n", request.method(), target).into_bytes();
+    let mut line = |name: &str, value: &[u8]| {
+        head.extend_from_slice(name.as_bytes());
+        head.extend_from_slice(b": ");
+        head.extend_from_slice(value);
+        head.extend_from_slice(b"\r\n");
+    };
+    let headers = request.headers();
+    if !headers.contains_key(HOST) {
+        let host = url.host_str().unwrap_or_default();
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        };
+        line("Host", host.as_bytes());
+    }
+    let mut spelling = Spelling::new(typed);
+    for (name, value) in headers {
+        line(&spelling.of(name.as_str()), value.as_bytes());
+    }
+    if !headers.contains_key(ACCEPT) {
+        line("Accept", b"*/*");
+    }
+    let body = request.body().and_then(|body| body.as_bytes());
+    if let Some(body) = body {
+        if !headers.contains_key(CONTENT_LENGTH) && !headers.contains_key(TRANSFER_ENCODING) {
+            line("Content-Length", body.len().to_string().as_bytes());
+        }
+    }
+    head.extend_from_slice(b"\r\n");
+    head
+}
+
+/// Sends `request`, naming its headers as in `typed` (see [`Spelling`]), and
+/// reads the response head.
+pub async fn send(
+    connector: &Connector,
+    request: &reqwest::Request,
+    typed: &[String],
+) -> Result<Response, Error> {
+    let mut stream = connector.connect(request.url()).await?;
+    let mut message = request_head(request, typed);
+    if let Some(body) = request.body().and_then(|body| body.as_bytes()) {
+        message.extend_from_slice(body);
+    }
+    stream.write_all(&message).await.map_err(Error::Io)?;
+    stream.flush().await.map_err(Error::Io)?;
+    let head_request = request.method() == reqwest::Method::HEAD;
+    Response::read(stream, head_request).await
+}
+
+/// How the end of the body is found.
+enum Framing {
+    Length(u64),
+    Chunked(Chunk),
+    UntilClose,
+    Done,
+}
+
+/// Where a chunked body is between reads.
+#[derive(Clone, Copy)]
+enum Chunk {
+    Size,
+    Data(u64),
+    DataEnd,
+    Trailers,
+}
+
+/// A response whose head has been read; the body is read with
+/// [`Response::chunk`].
+pub struct Response {
+    pub version: &'static str,
+    pub status: u16,
+    /// Names as the server spelled them, one entry per value.
+    pub headers: Vec<(String, String)>,
+    stream: Box<dyn Stream>,
+    /// Bytes read past what has been handed out.
+    buf: Vec<u8>,
+    framing: Framing,
+    length: Option<u64>,
+}
+
+impl Response {
+    async fn read(mut stream: Box<dyn Stream>, head_request: bool) -> Result<Self, Error> {
+        let mut buf = Vec::new();
+        loop {
+            if let Some((version, status, headers, len)) = parse_head(&buf)? {
+                buf.drain(..len);
+                // 100 Continue and the like come before the real response
+                if (100..200).contains(&status) && status != 101 {
+                    continue;
+                }
+                let no_body = head_request || (100..200).contains(&status) || status == 204
+                    || status == 304;
+                let value = |name: &str| crate::http::header_value(&headers, name);
+                let chunked = value("transfer-encoding")
+                    .and_then(|codings| codings.rsplit(',').next())
+                    .is_some_and(|last| last.trim().eq_ignore_ascii_case("chunked"));
+                let length = value("content-length").and_then(|v| v.trim().parse::<u64>().ok());
+                let framing = if no_body {
+                    Framing::Done
+                } else if chunked {
+                    Framing::Chunked(Chunk::Size)
+                } else if let Some(length) = length {
+                    Framing::Length(length)
+                } else {
+                    Framing::UntilClose
+                };
+                return Ok(Self {
+                    version,
+                    status,
+                    headers,
+                    stream,
+                    buf,
+                    framing,
+                    length: length.filter(|_| !chunked),
+                });
+            }
+            if buf.len() > MAX_HEAD_BYTES {
+                return Err(Error::Malformed("response head too large".to_string()));
+            }
+            if fill(&mut stream, &mut buf).await.map_err(Error::Io)? == 0 {
+                let msg = "connection closed before the response head";
+                return Err(Error::Malformed(msg.to_string()));
+            }
+        }
+    }
+
+    /// `Content-Length`, when the server sent one for an unchunked body.
+    pub fn content_length(&self) -> Option<u64> {
+        self.length
+    }
+
+    /// The next piece of the body, de-chunked, or `None` at its end.
+    pub async fn chunk(&mut self) -> io::Result<Option<Vec<u8>>> {
+        loop {
+            match self.framing {
+                Framing::Done => return Ok(None),
+                Framing::UntilClose => {
+                    if self.buf.is_empty() && self.fill().await? == 0 {
+                        self.framing = Framing::Done;
+                        return Ok(None);
+                    }
+                    return Ok(Some(std::mem::take(&mut self.buf)));
+                }
+                Framing::Length(0) => self.framing = Framing::Done,
+                Framing::Length(left) => {
+                    let data = self.take(left).await?;
+                    self.framing = Framing::Length(left - data.len() as u64);
+                    return Ok(Some(data));
+                }
+                Framing::Chunked(Chunk::Data(left)) => {
+                    let data = self.take(left).await?;
+                    let left = left - data.len() as u64;
+                    let next = if left == 0 { Chunk::DataEnd } else { Chunk::Data(left) };
+                    self.framing = Framing::Chunked(next);
+                    return Ok(Some(data));
+                }
+                Framing::Chunked(Chunk::DataEnd) => {
+                    while self.buf.len() < 2 {
+                        self.fill_or_eof().await?;
+                    }
+                    if &self.buf[..2] != b"\r\n" {
+                        return Err(invalid("chunk not followed by CRLF"));
+                    }
+                    self.buf.drain(..2);
+                    self.framing = Framing::Chunked(Chunk::Size);
+                }
+                Framing::Chunked(Chunk::Size) => match httparse::parse_chunk_size(&self.buf) {
+                    Ok(httparse::Status::Complete((len, size))) => {
+                        self.buf.drain(..len);
+                        let next = if size == 0 { Chunk::Trailers } else { Chunk::Data(size) };
+                        self.framing = Framing::Chunked(next);
+                    }
+                    Ok(httparse::Status::Partial) => self.fill_or_eof().await?,
+                    Err(_) => return Err(invalid("invalid chunk size")),
+                },
+                Framing::Chunked(Chunk::Trailers) => {
+                    match self.buf.windows(2).position(|pair| pair == b"\r\n") {
+                        Some(0) => {
+                            self.buf.drain(..2);
+                            self.framing = Framing::Done;
+                        }
+                        Some(end) => drop(self.buf.drain(..end + 2)),
+                        None => self.fill_or_eof().await?,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Up to `left` bytes, reading more when none are buffered.
+    async fn take(&mut self, left: u64) -> io::Result<Vec<u8>> {
+        if self.buf.is_empty() {
+            self.fill_or_eof().await?;
+        }
+        let len = left.min(self.buf.len() as u64) as usize;
+        Ok(self.buf.drain(..len).collect())
+    }
+
+    async fn fill(&mut self) -> io::Result<usize> {
+        fill(&mut self.stream, &mut self.buf).await
+    }
+
+    async fn fill_or_eof(&mut self) -> io::Result<()> {
+        match self.fill().await? {
+            0 => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "body cut short")),
+            _ => Ok(()),
+        }
+    }
+}
+
+type Head = (&'static str, u16, Vec<(String, String)>, usize);
+
+/// The version, status, headers and length of the head at the start of
+/// `buf`, or `None` while it's incomplete.
+fn parse_head(buf: &[u8]) -> Result<Option<Head>, Error> {
+    let mut headers = [httparse::EMPTY_HEADER; 128];
+    let mut response = httparse::Response::new(&mut headers);
+    let len = match response.parse(buf) {
+        Ok(httparse::Status::Complete(len)) => len,
+        Ok(httparse::Status::Partial) => return Ok(None),
+        Err(err) => return Err(Error::Malformed(err.to_string())),
+    };
+    let version = match response.version {
+        Some(0) => "HTTP/1.0",
+        _ => "HTTP/1.1",
+    };
+    let status = response.code.unwrap_or_default();
+    let headers = response
+        .headers
+        .iter()
+        .map(|h| (h.name.to_string(), String::from_utf8_lossy(h.value).into_owned()))
+        .collect();
+    Ok(Some((version, status, headers, len)))
+}
+
+async fn fill(stream: &mut Box<dyn Stream>, buf: &mut Vec<u8>) -> io::Result<usize> {
+    let mut chunk = [0u8; 8192];
+    let n = stream.read(&mut chunk).await?;
+    buf.extend_from_slice(&chunk[..n]);
+    Ok(n)
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_head_spells_names_as_typed() {
+        let client = reqwest::Client::new();
+        let request = client
+            .post("http://api.test:8080/soap?v=2")
+            .header("soapaction", "run")
+            .header("x-trace", "a")
+            .header("x-trace", "b")
+            .header("content-type", "text/xml")
+            .body("<a/>")
+            .build()
+            .unwrap();
+        let typed = ["SOAPAction".to_string(), "X-TRACE".to_string(), "x-trace".to_string()];
+        let head = String::from_utf8(request_head(&request, &typed)).unwrap();
+        assert_eq!(
+            head,
+            "POST /soap?v=2 HTTP/1.1\r\nHost: api.test:8080\r\nSOAPAction: run\r\n\
+             X-TRACE: a\r\nx-trace: b\r\nContent-Type: text/xml\r\nAccept: */*\r\n\
+             Content-Length: 4\r\n\r\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_response_keeps_names_and_dechunks() {
+        let (mut server, client) = tokio::io::duplex(64);
+        tokio::spawn(async move {
+            let response = b"HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 200 OK\r\n\
+                X-Legacy-ID: 7\r\nTransfer-Encoding: chunked\r\n\r\n\
+                4;ext=1\r\nWiki\r\n5\r\npedia\r\n0\r\nX-Trailer: t\r\n\r\n";
+            server.write_all(response).await.unwrap();
+        });
+        let mut response = Response::read(Box::new(client), false).await.unwrap();
+        assert_eq!((response.version, response.status), ("HTTP/1.1", 200));
+        assert_eq!(response.headers[0], ("X-Legacy-ID".to_string(), "7".to_string()));
+        assert_eq!(response.content_length(), None);
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await.unwrap() {
+            body.extend(chunk);
+        }
+        assert_eq!(body, b"Wikipedia");
+
+        // A body shorter than its Content-Length is an error, not an end
+        let (mut server, client) = tokio::io::duplex(64);
+        tokio::spawn(async move {
+            let response = b"HTTP/1.0 200 OK\r\nContent-Length: 10\r\n\r\nshort";
+            server.write_all(response).await.unwrap();
+        });
+        let mut response = Response::read(Box::new(client), false).await.unwrap();
+        assert_eq!((response.version, response.content_length()), ("HTTP/1.0", Some(10)));
+        assert_eq!(response.chunk().await.unwrap(), Some(b"short".to_vec()));
+        let err = response.chunk().await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
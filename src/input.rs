@@ -0,0 +1,1035 @@
+//! Key dispatch. The app state that decides what a key means is captured in
+//! an [`InputContext`]; [`dispatch`] maps context and key to an [`Action`],
+//! and `App::apply_action` carries it out. The order of the checks here is
+//! the order in which popups, overlays and panels get to see a key.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::app::{AppMode, BodyField, Panel, RequestField};
+
+/// A popup or overlay that takes every key while it is open, listed from
+/// the one that gets keys first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overlay {
+    // Open over any mode
+    Onboarding,
+    LockPrompt,
+    TrustPrompt,
+    DraftPrompt,
+    SpecUpdate,
+    SendConfirm,
+    ProtectedConfirm,
+    UnresolvedConfirm,
+    BodyConfirm,
+    Codec,
+    ClipboardRing,
+    Snippets,
+    RunPopup,
+    UrlAudit,
+    Changelog,
+    Usages,
+    EnvVariables,
+    EnvFilePrompt,
+    ScratchSend,
+    /// Lets Ctrl+N through so it can close the popup again.
+    EnvPopup,
+    // Open from navigation mode
+    Help,
+    ErrorPopup,
+    ConfigViewer,
+    BodyModePopup,
+    AuthTypePopup,
+    MethodPopup,
+    Settings,
+    SaveResponse,
+    Example,
+    Pipe,
+    RawView,
+    UrlOutline,
+    SendPreview,
+    CorsCheck,
+    Compare,
+    SchemaCheck,
+    SendHistory,
+    Notes,
+    MessageLog,
+    RequestFiles,
+    HeaderFilter,
+    SidebarPopup,
+}
+
+impl Overlay {
+    /// Whether the overlay takes keys in any mode rather than only in
+    /// navigation mode.
+    pub fn is_global(self) -> bool {
+        matches!(
+            self,
+            Overlay::Onboarding
+                | Overlay::LockPrompt
+                | Overlay::TrustPrompt
+                | Overlay::DraftPrompt
+                | Overlay::SpecUpdate
+                | Overlay::SendConfirm
+                | Overlay::ProtectedConfirm
+                | Overlay::UnresolvedConfirm
+                | Overlay::BodyConfirm
+                | Overlay::Codec
+                | Overlay::ClipboardRing
+                | Overlay::Snippets
+                | Overlay::RunPopup
+                | Overlay::UrlAudit
+                | Overlay::Changelog
+                | Overlay::Usages
+                | Overlay::EnvVariables
+                | Overlay::EnvFilePrompt
+                | Overlay::ScratchSend
+                | Overlay::EnvPopup
+        )
+    }
+}
+
+/// What a key press depends on, taken from the app before dispatching.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct InputContext {
+    pub mode: AppMode,
+    /// The overlay that gets keys first, if one is open.
+    pub overlay: Option<Overlay>,
+    pub panel: Panel,
+    pub request_field: RequestField,
+    pub body_field: BodyField,
+    /// Headers are shown as a table rather than text.
+    pub headers_table: bool,
+    /// The focused field is a password or API key value.
+    pub secret_field: bool,
+    pub response_wrap: bool,
+    /// The unwrapped response is scrolled away from its first column.
+    pub response_hscrolled: bool,
+    /// The response panel shows a failed request.
+    pub response_error: bool,
+    /// The response panel is on its Headers tab.
+    pub response_headers: bool,
+}
+
+/// A response scroll distance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pages {
+    Down,
+    Up,
+    HalfDown,
+    HalfUp,
+}
+
+impl Pages {
+    pub fn count(self) -> f32 {
+        match self {
+            Pages::Down => 1.0,
+            Pages::Up => -1.0,
+            Pages::HalfDown => 0.5,
+            Pages::HalfUp => -0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// The key goes to the overlay's own handler.
+    Overlay(Overlay, KeyEvent),
+    /// The key goes to the editor of the focused field.
+    Editing(KeyEvent),
+    /// The key goes to the sidebar tree.
+    Sidebar(KeyEvent),
+    TogglePerfOverlay,
+    ToggleSecretReveal,
+    ToggleSidebar,
+    FocusSidebar,
+    OpenProjectSwitcher,
+    NarrowSidebar,
+    WidenSidebar,
+    SaveRequest,
+    SendOrCancel,
+    /// Send the request sent last again, whichever request is open.
+    ResendLast,
+    /// Open the scratch send: the request edited for one send only.
+    ScratchSend,
+    OpenCodec,
+    ExternalEdit,
+    OpenTrustPrompt,
+    TogglePrivacy,
+    ToggleOffline,
+    OpenConfigViewer,
+    OpenSettings,
+    ToggleEnvPopup,
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    ToggleHeadersTable,
+    KvDown,
+    KvUp,
+    KvNextColumn,
+    KvPrevColumn,
+    KvAddRow,
+    KvDeleteRow,
+    KvToggleEnabled,
+    KvEditCell,
+    KvToggleMultipart,
+    NextAuthField,
+    PrevAuthField,
+    NextBodyField,
+    PrevBodyField,
+    ScrollResponseLeft,
+    ScrollResponseRight,
+    /// Ctrl+F/B/D/U in the response panel: scroll by this many pages.
+    ScrollResponsePages(Pages),
+    OpenHelp,
+    OpenClipboardRing,
+    OpenMessageLog,
+    OpenRawView,
+    OpenUrlOutline,
+    OpenSendPreview,
+    /// Select the open request in the sidebar, expanding its folders.
+    RevealInSidebar,
+    OpenCorsCheck,
+    OpenSchemaCheck,
+    /// The open request's recent sends and their durations.
+    OpenSendHistory,
+    OpenNotes,
+    TogglePin,
+    OpenCompare,
+    SaveResponse,
+    SaveExample,
+    ListExamples,
+    YankBody,
+    YankHeaders,
+    YankAllHeaders,
+    ToggleHexView,
+    ToggleHtmlPreview,
+    ToggleWrap,
+    ToggleBodyWrap,
+    /// Ctrl+Space on the Auth or Body tab: leave it out of sends or put it
+    /// back.
+    ToggleSectionEnabled,
+    FormatBody,
+    ToggleHeaderCounts,
+    ToggleHeaderSort,
+    OpenHeaderFilter,
+    OpenPager,
+    OpenPipePrompt,
+    ToggleErrorDetail,
+    /// Enter: open or edit whatever has focus.
+    Activate,
+    /// `i`: edit the focused field, in insert mode where it has text.
+    Insert,
+    Quit,
+}
+
+/// The action a key press stands for in `ctx`, or `None` when it does
+/// nothing there.
+pub fn dispatch(ctx: &InputContext, key: KeyEvent) -> Option<Action> {
+    if key.code == KeyCode::F(12) {
+        return Some(Action::TogglePerfOverlay);
+    }
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match ctx.overlay {
+        Some(Overlay::EnvPopup) if is_env_popup_toggle(key) => {}
+        Some(overlay) if overlay.is_global() => return Some(Action::Overlay(overlay, key)),
+        _ => {}
+    }
+    // Ctrl+H on a password or API key value: reveal or mask it
+    if ctrl && key.code == KeyCode::Char('h') && ctx.secret_field {
+        return Some(Action::ToggleSecretReveal);
+    }
+    if ctx.overlay.is_none() && is_resend_last(key) {
+        return Some(Action::ResendLast);
+    }
+    if ctx.overlay.is_none() && is_scratch_send(key) {
+        return Some(Action::ScratchSend);
+    }
+    match ctx.mode {
+        AppMode::Navigation => navigation_action(ctx, key),
+        AppMode::Editing => Some(Action::Editing(key)),
+        AppMode::Sidebar => Some(Action::Sidebar(key)),
+    }
+}
+
+/// Ctrl+N opens and closes the environment switcher from every mode.
+fn is_env_popup_toggle(key: KeyEvent) -> bool {
+    key.code == KeyCode::Char('n') && key.modifiers.contains(KeyModifiers::CONTROL)
+}
+
+/// Alt+R, or Ctrl+Shift+R in terminals that report Shift with Ctrl; the
+/// others send it as plain Ctrl+R.
+fn is_resend_last(key: KeyEvent) -> bool {
+    let alt = key.modifiers.contains(KeyModifiers::ALT);
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Char('r') => alt && !ctrl,
+        KeyCode::Char('R') => alt || ctrl,
+        _ => false,
+    }
+}
+
+/// Ctrl+Shift+Enter, or Alt+Enter in terminals that send Enter without
+/// Ctrl and Shift.
+pub fn is_scratch_send(key: KeyEvent) -> bool {
+    let ctrl_shift = KeyModifiers::CONTROL | KeyModifiers::SHIFT;
+    key.code == KeyCode::Enter
+        && (key.modifiers.contains(ctrl_shift) || key.modifiers == KeyModifiers::ALT)
+}
+
+fn navigation_action(ctx: &InputContext, key: KeyEvent) -> Option<Action> {
+    if let Some(overlay) = ctx.overlay.filter(|overlay| !overlay.is_global()) {
+        return Some(Action::Overlay(overlay, key));
+    }
+    if let Some(action) = ctrl_action(ctx, key) {
+        return Some(action);
+    }
+    let in_request = ctx.panel == Panel::Request;
+    let in_response = ctx.panel == Panel::Response;
+    if key.code == KeyCode::Char('e') && key.modifiers.is_empty() {
+        return Some(Action::FocusSidebar);
+    }
+    if in_request {
+        if let Some(action) = request_field_action(ctx, key) {
+            return Some(action);
+        }
+    }
+    // Without wrapping, h/l scroll the response sideways; h at the first
+    // column moves focus as usual
+    if in_response && !ctx.response_wrap {
+        match key.code {
+            KeyCode::Right | KeyCode::Char('l') => return Some(Action::ScrollResponseRight),
+            KeyCode::Left | KeyCode::Char('h') if ctx.response_hscrolled => {
+                return Some(Action::ScrollResponseLeft);
+            }
+            _ => {}
+        }
+    }
+    let action = match key.code {
+        KeyCode::Left | KeyCode::Char('h') => Action::MoveLeft,
+        KeyCode::Right | KeyCode::Char('l') => Action::MoveRight,
+        KeyCode::Up | KeyCode::Char('k') => Action::MoveUp,
+        KeyCode::Down | KeyCode::Char('j') => Action::MoveDown,
+        KeyCode::Char('?') => Action::OpenHelp,
+        KeyCode::Char('"') => Action::OpenClipboardRing,
+        KeyCode::Char('M') => Action::OpenMessageLog,
+        KeyCode::Char('O') => Action::ToggleOffline,
+        KeyCode::Char('V') if in_request => Action::OpenRawView,
+        KeyCode::Char('V') if in_response => Action::OpenSchemaCheck,
+        KeyCode::Char('p') if in_response => Action::TogglePin,
+        KeyCode::Char('n') if in_response => Action::OpenNotes,
+        KeyCode::Char('C') if in_response => Action::OpenCompare,
+        KeyCode::Char('S') if in_response => Action::SaveResponse,
+        KeyCode::Char('E') if in_response => Action::SaveExample,
+        KeyCode::Char('X') if in_response => Action::ListExamples,
+        KeyCode::Char('y') if in_response => Action::YankBody,
+        KeyCode::Char('Y') if in_response => Action::YankHeaders,
+        KeyCode::Char('A') if in_response => Action::YankAllHeaders,
+        KeyCode::Char('H') if in_response => Action::ToggleHexView,
+        KeyCode::Char('P') if in_response => Action::ToggleHtmlPreview,
+        KeyCode::Char('P') if in_request => Action::OpenSendPreview,
+        KeyCode::Char('B') if in_request => Action::RevealInSidebar,
+        KeyCode::Char('K') if in_request => Action::OpenCorsCheck,
+        KeyCode::Char('w') if in_response => Action::ToggleWrap,
+        KeyCode::Char('#') if in_response => Action::ToggleHeaderCounts,
+        KeyCode::Char('o') if ctx.response_headers => Action::ToggleHeaderSort,
+        KeyCode::Char('/') if ctx.response_headers => Action::OpenHeaderFilter,
+        KeyCode::Char('|') if in_response || in_request => Action::OpenPager,
+        KeyCode::Char('!') if in_response || in_request => Action::OpenPipePrompt,
+        KeyCode::Char('D') if in_response && ctx.response_error => Action::ToggleErrorDetail,
+        KeyCode::Enter => Action::Activate,
+        KeyCode::Char('i') => Action::Insert,
+        KeyCode::Char('q') => Action::Quit,
+        _ => return None,
+    };
+    Some(action)
+}
+
+/// Ctrl combinations, which win over the bare letters of the panels.
+fn ctrl_action(ctx: &InputContext, key: KeyEvent) -> Option<Action> {
+    if !key.modifiers.contains(KeyModifiers::CONTROL) {
+        return None;
+    }
+    let KeyCode::Char(c) = key.code else {
+        return None;
+    };
+    let in_response = ctx.panel == Panel::Response;
+    let action = match c {
+        // Vim's paging keys; Ctrl+U opens the codec everywhere else
+        'f' if in_response => Action::ScrollResponsePages(Pages::Down),
+        'b' if in_response => Action::ScrollResponsePages(Pages::Up),
+        'd' if in_response => Action::ScrollResponsePages(Pages::HalfDown),
+        'u' if in_response => Action::ScrollResponsePages(Pages::HalfUp),
+        't' if in_response => Action::OpenSendHistory,
+        'q' if ctx.panel == Panel::Request && ctx.request_field == RequestField::Url => {
+            Action::OpenUrlOutline
+        }
+        'e' => Action::ToggleSidebar,
+        'p' => Action::OpenProjectSwitcher,
+        '[' => Action::NarrowSidebar,
+        ']' => Action::WidenSidebar,
+        's' => Action::SaveRequest,
+        'r' => Action::SendOrCancel,
+        'u' => Action::OpenCodec,
+        'g' => Action::ExternalEdit,
+        't' => Action::OpenTrustPrompt,
+        'x' => Action::TogglePrivacy,
+        ',' => Action::OpenConfigViewer,
+        'o' => Action::OpenSettings,
+        'n' => Action::ToggleEnvPopup,
+        // Ctrl+h/j/k/l move between fields even where the bare letters
+        // work inside one
+        'h' if ctx.panel == Panel::Request => Action::MoveLeft,
+        'l' if ctx.panel == Panel::Request => Action::MoveRight,
+        'j' if ctx.panel == Panel::Request => Action::MoveDown,
+        'k' if ctx.panel == Panel::Request => Action::MoveUp,
+        ' ' if matches!(ctx.request_field, RequestField::Auth | RequestField::Body)
+            && ctx.panel == Panel::Request =>
+        {
+            Action::ToggleSectionEnabled
+        }
+        _ => return None,
+    };
+    Some(action)
+}
+
+/// Keys with a meaning inside the focused request field: the headers
+/// table, auth fields and body fields.
+fn request_field_action(ctx: &InputContext, key: KeyEvent) -> Option<Action> {
+    let action = match ctx.request_field {
+        RequestField::Headers if key.code == KeyCode::Char('t') => Action::ToggleHeadersTable,
+        RequestField::Headers if ctx.headers_table => match key.code {
+            KeyCode::Down | KeyCode::Char('j') => Action::KvDown,
+            KeyCode::Up | KeyCode::Char('k') => Action::KvUp,
+            KeyCode::Tab | KeyCode::Char('l') | KeyCode::Right => Action::KvNextColumn,
+            KeyCode::BackTab | KeyCode::Char('h') | KeyCode::Left => Action::KvPrevColumn,
+            KeyCode::Char('a') | KeyCode::Char('o') => Action::KvAddRow,
+            KeyCode::Char('d') => Action::KvDeleteRow,
+            KeyCode::Char(' ') => Action::KvToggleEnabled,
+            KeyCode::Enter | KeyCode::Char('i') => Action::KvEditCell,
+            _ => return None,
+        },
+        RequestField::Auth => match key.code {
+            KeyCode::Down | KeyCode::Char('j') => Action::NextAuthField,
+            KeyCode::Up | KeyCode::Char('k') => Action::PrevAuthField,
+            _ => return None,
+        },
+        RequestField::Body => match key.code {
+            KeyCode::Down | KeyCode::Char('j') => Action::NextBodyField,
+            KeyCode::Up | KeyCode::Char('k') => Action::PrevBodyField,
+            KeyCode::Char('w') if ctx.body_field == BodyField::TextEditor => {
+                Action::ToggleBodyWrap
+            }
+            KeyCode::Char('F') if ctx.body_field == BodyField::TextEditor => Action::FormatBody,
+            _ if ctx.body_field != BodyField::KvRow => return None,
+            KeyCode::Tab | KeyCode::Char('l') | KeyCode::Right => Action::KvNextColumn,
+            KeyCode::BackTab | KeyCode::Char('h') | KeyCode::Left => Action::KvPrevColumn,
+            KeyCode::Char('a') | KeyCode::Char('o') => Action::KvAddRow,
+            KeyCode::Char('d') => Action::KvDeleteRow,
+            KeyCode::Char(' ') => Action::KvToggleEnabled,
+            KeyCode::Char('t') => Action::KvToggleMultipart,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(action)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{App, Method, RequestTab, ResponseStatus, ResponseTab, SidebarFilter};
+    use crate::storage::{PostmanExample, PostmanRequest};
+    use tokio::sync::mpsc;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn ctrl(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    fn with_overlay(overlay: Overlay) -> InputContext {
+        InputContext { overlay: Some(overlay), ..InputContext::default() }
+    }
+
+    fn apply(app: &mut App, action: Action) {
+        let (tx, _rx) = mpsc::channel(1);
+        app.apply_action(action, tx);
+    }
+
+    #[test]
+    fn test_help_overlay_takes_keys_first() {
+        let ctx = with_overlay(Overlay::Help);
+        for k in [key(KeyCode::Char('q')), key(KeyCode::Char('j')), ctrl('r'), ctrl('e')] {
+            assert_eq!(dispatch(&ctx, k), Some(Action::Overlay(Overlay::Help, k)));
+        }
+    }
+
+    #[test]
+    fn test_popups_before_panel_keys() {
+        let ctx = InputContext {
+            request_field: RequestField::Headers,
+            headers_table: true,
+            ..with_overlay(Overlay::MethodPopup)
+        };
+        let j = key(KeyCode::Char('j'));
+        assert_eq!(dispatch(&ctx, j), Some(Action::Overlay(Overlay::MethodPopup, j)));
+
+        let ctx = InputContext { overlay: None, ..ctx };
+        assert_eq!(dispatch(&ctx, j), Some(Action::KvDown));
+        assert_eq!(dispatch(&ctx, key(KeyCode::Char('t'))), Some(Action::ToggleHeadersTable));
+    }
+
+    #[test]
+    fn test_global_overlays_win_in_every_mode() {
+        let q = key(KeyCode::Char('q'));
+        for mode in [AppMode::Navigation, AppMode::Editing, AppMode::Sidebar] {
+            let ctx = InputContext { mode, ..with_overlay(Overlay::TrustPrompt) };
+            assert_eq!(dispatch(&ctx, q), Some(Action::Overlay(Overlay::TrustPrompt, q)));
+        }
+        // F12 still reaches the performance overlay
+        let f12 = key(KeyCode::F(12));
+        let onboarding = with_overlay(Overlay::Onboarding);
+        assert_eq!(dispatch(&onboarding, f12), Some(Action::TogglePerfOverlay));
+    }
+
+    #[test]
+    fn test_resend_last_in_every_mode_but_not_in_popups() {
+        let alt_r = KeyEvent::new(KeyCode::Char('r'), KeyModifiers::ALT);
+        let ctrl_shift_r =
+            KeyEvent::new(KeyCode::Char('R'), KeyModifiers::CONTROL | KeyModifiers::SHIFT);
+        for mode in [AppMode::Navigation, AppMode::Editing, AppMode::Sidebar] {
+            let ctx = InputContext { mode, ..InputContext::default() };
+            assert_eq!(dispatch(&ctx, alt_r), Some(Action::ResendLast));
+            assert_eq!(dispatch(&ctx, ctrl_shift_r), Some(Action::ResendLast));
+        }
+        assert_eq!(dispatch(&InputContext::default(), ctrl('r')), Some(Action::SendOrCancel));
+        let ctx = with_overlay(Overlay::Help);
+        assert_eq!(dispatch(&ctx, alt_r), Some(Action::Overlay(Overlay::Help, alt_r)));
+    }
+
+    #[test]
+    fn test_scratch_send_in_every_mode() {
+        let alt_enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::ALT);
+        let ctrl_shift_enter =
+            KeyEvent::new(KeyCode::Enter, KeyModifiers::CONTROL | KeyModifiers::SHIFT);
+        for mode in [AppMode::Navigation, AppMode::Editing, AppMode::Sidebar] {
+            let ctx = InputContext { mode, ..InputContext::default() };
+            assert_eq!(dispatch(&ctx, alt_enter), Some(Action::ScratchSend));
+            assert_eq!(dispatch(&ctx, ctrl_shift_enter), Some(Action::ScratchSend));
+        }
+        let enter = key(KeyCode::Enter);
+        assert_eq!(dispatch(&InputContext::default(), enter), Some(Action::Activate));
+        // Inside the scratch the same keys send it
+        let ctx = with_overlay(Overlay::ScratchSend);
+        let action = Action::Overlay(Overlay::ScratchSend, alt_enter);
+        assert_eq!(dispatch(&ctx, alt_enter), Some(action));
+    }
+
+    #[test]
+    fn test_env_popup_lets_its_toggle_through() {
+        let ctx = with_overlay(Overlay::EnvPopup);
+        let j = key(KeyCode::Char('j'));
+        assert_eq!(dispatch(&ctx, j), Some(Action::Overlay(Overlay::EnvPopup, j)));
+        assert_eq!(dispatch(&ctx, ctrl('n')), Some(Action::ToggleEnvPopup));
+    }
+
+    #[test]
+    fn test_ctrl_combos_before_bare_letters() {
+        let ctx = InputContext::default();
+        assert_eq!(dispatch(&ctx, ctrl('e')), Some(Action::ToggleSidebar));
+        assert_eq!(dispatch(&ctx, key(KeyCode::Char('e'))), Some(Action::FocusSidebar));
+        assert_eq!(dispatch(&ctx, ctrl('r')), Some(Action::SendOrCancel));
+        assert_eq!(dispatch(&ctx, ctrl('s')), Some(Action::SaveRequest));
+        assert_eq!(dispatch(&ctx, ctrl('l')), Some(Action::MoveRight));
+
+        // In the headers table Ctrl+J leaves the field while j moves a row
+        let ctx = InputContext {
+            request_field: RequestField::Headers,
+            headers_table: true,
+            ..ctx
+        };
+        assert_eq!(dispatch(&ctx, ctrl('j')), Some(Action::MoveDown));
+        assert_eq!(dispatch(&ctx, key(KeyCode::Char('j'))), Some(Action::KvDown));
+
+        // Ctrl+H reveals a focused secret before it moves focus
+        let ctx = InputContext { request_field: RequestField::Auth, ..ctx };
+        assert_eq!(dispatch(&ctx, ctrl('h')), Some(Action::MoveLeft));
+        let ctx = InputContext { secret_field: true, ..ctx };
+        assert_eq!(dispatch(&ctx, ctrl('h')), Some(Action::ToggleSecretReveal));
+    }
+
+    #[test]
+    fn test_response_keys() {
+        let ctx = InputContext { panel: Panel::Response, ..InputContext::default() };
+        assert_eq!(dispatch(&ctx, ctrl('f')), Some(Action::ScrollResponsePages(Pages::Down)));
+        assert_eq!(dispatch(&ctx, ctrl('u')), Some(Action::ScrollResponsePages(Pages::HalfUp)));
+        let request = InputContext::default();
+        assert_eq!(dispatch(&request, ctrl('u')), Some(Action::OpenCodec));
+        assert_eq!(dispatch(&request, ctrl('f')), None);
+        assert_eq!(dispatch(&ctx, ctrl('t')), Some(Action::OpenSendHistory));
+        assert_eq!(dispatch(&request, ctrl('t')), Some(Action::OpenTrustPrompt));
+        let n = key(KeyCode::Char('n'));
+        assert_eq!(dispatch(&ctx, n), Some(Action::OpenNotes));
+        assert_eq!(dispatch(&request, n), None);
+        let (h, l) = (key(KeyCode::Char('h')), key(KeyCode::Char('l')));
+        assert_eq!(dispatch(&ctx, l), Some(Action::ScrollResponseRight));
+        assert_eq!(dispatch(&ctx, h), Some(Action::MoveLeft));
+        let scrolled = InputContext { response_hscrolled: true, ..ctx };
+        assert_eq!(dispatch(&scrolled, h), Some(Action::ScrollResponseLeft));
+        let wrapped = InputContext { response_wrap: true, ..scrolled };
+        assert_eq!(dispatch(&wrapped, l), Some(Action::MoveRight));
+
+        let (o, slash) = (key(KeyCode::Char('o')), key(KeyCode::Char('/')));
+        assert_eq!(dispatch(&ctx, o), None);
+        let headers = InputContext { response_headers: true, ..ctx };
+        assert_eq!(dispatch(&headers, o), Some(Action::ToggleHeaderSort));
+        assert_eq!(dispatch(&headers, slash), Some(Action::OpenHeaderFilter));
+
+        let d = key(KeyCode::Char('D'));
+        assert_eq!(dispatch(&ctx, d), None);
+        let failed = InputContext { response_error: true, ..ctx };
+        assert_eq!(dispatch(&failed, d), Some(Action::ToggleErrorDetail));
+        assert_eq!(dispatch(&ctx, key(KeyCode::Char('V'))), Some(Action::OpenSchemaCheck));
+        let request = InputContext { panel: Panel::Request, ..ctx };
+        assert_eq!(dispatch(&request, key(KeyCode::Char('V'))), Some(Action::OpenRawView));
+        assert_eq!(dispatch(&request, key(KeyCode::Char('P'))), Some(Action::OpenSendPreview));
+        assert_eq!(dispatch(&request, key(KeyCode::Char('B'))), Some(Action::RevealInSidebar));
+        assert_eq!(dispatch(&request, key(KeyCode::Char('K'))), Some(Action::OpenCorsCheck));
+        assert_eq!(dispatch(&request, ctrl('q')), Some(Action::OpenUrlOutline));
+        let method = InputContext { request_field: RequestField::Method, ..request };
+        assert_eq!(dispatch(&method, ctrl('q')), Some(Action::Quit));
+    }
+
+    #[test]
+    fn test_body_text_keys() {
+        let ctx = InputContext {
+            request_field: RequestField::Body,
+            body_field: BodyField::TextEditor,
+            ..InputContext::default()
+        };
+        assert_eq!(dispatch(&ctx, key(KeyCode::Char('w'))), Some(Action::ToggleBodyWrap));
+        assert_eq!(dispatch(&ctx, key(KeyCode::Char('F'))), Some(Action::FormatBody));
+        assert_eq!(dispatch(&ctx, key(KeyCode::Char('j'))), Some(Action::NextBodyField));
+        let selector = InputContext { body_field: BodyField::ModeSelector, ..ctx };
+        assert_eq!(dispatch(&selector, key(KeyCode::Char('w'))), None);
+    }
+
+    #[test]
+    fn test_ctrl_space_leaves_body_and_auth_out() {
+        let ctrl_space = ctrl(' ');
+        let body = InputContext { request_field: RequestField::Body, ..InputContext::default() };
+        assert_eq!(dispatch(&body, ctrl_space), Some(Action::ToggleSectionEnabled));
+        let url = InputContext { request_field: RequestField::Url, ..body };
+        assert_eq!(dispatch(&url, ctrl_space), None);
+
+        let mut app = App::default();
+        app.request_tab = RequestTab::Body;
+        apply(&mut app, Action::ToggleSectionEnabled);
+        assert!(app.request.body_disabled && !app.request.auth_disabled);
+        app.request_tab = RequestTab::Auth;
+        apply(&mut app, Action::ToggleSectionEnabled);
+        app.request_tab = RequestTab::Body;
+        apply(&mut app, Action::ToggleSectionEnabled);
+        assert!(!app.request.body_disabled && app.request.auth_disabled);
+    }
+
+    #[test]
+    fn test_editing_and_sidebar_modes_pass_keys_through() {
+        let q = key(KeyCode::Char('q'));
+        let editing = InputContext { mode: AppMode::Editing, ..InputContext::default() };
+        assert_eq!(dispatch(&editing, q), Some(Action::Editing(q)));
+        assert_eq!(dispatch(&editing, ctrl('r')), Some(Action::Editing(ctrl('r'))));
+        // Navigation overlays don't apply outside navigation mode
+        let sidebar = InputContext { mode: AppMode::Sidebar, ..with_overlay(Overlay::Help) };
+        assert_eq!(dispatch(&sidebar, q), Some(Action::Sidebar(q)));
+    }
+
+    #[test]
+    fn test_apply_action_opens_and_closes_overlays() {
+        let mut app = App::default();
+        apply(&mut app, Action::OpenHelp);
+        assert_eq!(app.input_context().overlay, Some(Overlay::Help));
+        apply(&mut app, Action::Overlay(Overlay::Help, key(KeyCode::Esc)));
+        assert_eq!(app.input_context().overlay, None);
+
+        apply(&mut app, Action::OpenMessageLog);
+        assert_eq!(app.input_context().overlay, Some(Overlay::MessageLog));
+        let j = key(KeyCode::Char('j'));
+        apply(&mut app, Action::Overlay(Overlay::MessageLog, j));
+        assert_eq!(app.input_context().overlay, Some(Overlay::MessageLog));
+        apply(&mut app, Action::Overlay(Overlay::MessageLog, key(KeyCode::Char('M'))));
+        assert_eq!(app.input_context().overlay, None);
+
+        apply(&mut app, Action::OpenConfigViewer);
+        apply(&mut app, Action::OpenHelp);
+        // Help is checked first while both are open
+        assert_eq!(app.input_context().overlay, Some(Overlay::Help));
+    }
+
+    #[test]
+    fn test_offline_send_shows_the_saved_example() {
+        let mut app = App::default();
+        let url = "https://api.example.com/users";
+        let request = PostmanRequest::new("GET".to_string(), url.to_string(), vec![], None);
+        let project = app.collection.add_project("Project".to_string()).unwrap();
+        let id = app.collection.add_request(project, "Users".to_string(), request.clone()).unwrap();
+        app.current_request_id = Some(id);
+        let method = Method::from_str("GET");
+        app.request.set_contents(method, url.to_string(), String::new(), String::new());
+        apply(&mut app, Action::ToggleOffline);
+
+        apply(&mut app, Action::SendOrCancel);
+        let ResponseStatus::Error(err) = &app.response else {
+            panic!("expected the send to be refused");
+        };
+        assert_eq!(err.kind, crate::http::ErrorKind::Offline);
+
+        let name = "Listed".to_string();
+        let example = PostmanExample::new(name, 200, "OK".to_string(), &[], "[]", request);
+        let item = app.collection.get_item_mut(id).unwrap();
+        item.response.push(serde_json::to_value(&example).unwrap());
+        apply(&mut app, Action::SendOrCancel);
+        let ResponseStatus::Success(data) = &app.response else {
+            panic!("expected the example");
+        };
+        assert!(data.example);
+        assert_eq!((data.status, data.body.as_str()), (200, "[]"));
+    }
+
+    #[test]
+    fn test_snippet_tab_stops_end_with_insert_mode() {
+        use crate::app::BodyField;
+        use crate::vim::{Vim, VimMode};
+
+        let mut app = App::default();
+        app.focus.panel = Panel::Request;
+        app.focus.request_field = RequestField::Body;
+        app.focus.body_field = BodyField::TextEditor;
+        app.app_mode = AppMode::Editing;
+        app.vim = Vim::new(VimMode::Insert);
+        let press = |app: &mut App, key: KeyEvent| {
+            let action = dispatch(&app.input_context(), key).unwrap();
+            apply(app, action);
+        };
+        let ctrl_space = KeyEvent::new(KeyCode::Char(' '), KeyModifiers::CONTROL);
+        press(&mut app, ctrl_space);
+        assert_eq!(app.input_context().overlay, Some(Overlay::Snippets));
+        for c in "graphql".chars() {
+            press(&mut app, key(KeyCode::Char(c)));
+        }
+        press(&mut app, key(KeyCode::Enter));
+        assert_eq!(app.input_context().overlay, None);
+
+        // Typing fills the first stop; Tab moves on past what was typed
+        for c in "Me".chars() {
+            press(&mut app, key(KeyCode::Char(c)));
+        }
+        press(&mut app, key(KeyCode::Tab));
+        for c in "me".chars() {
+            press(&mut app, key(KeyCode::Char(c)));
+        }
+        assert!(app.vim.in_snippet());
+        // Esc mid-snippet leaves the stops behind, so Tab types again
+        press(&mut app, key(KeyCode::Esc));
+        assert_eq!(app.vim.mode, VimMode::Normal);
+        assert!(!app.vim.in_snippet());
+        assert_eq!(
+            app.request.body_editor.lines().join("\n"),
+            "{\n  \"query\": \"query Me { me }\",\n  \"variables\": {}\n}"
+        );
+    }
+
+    #[test]
+    fn test_response_tabs_scroll_separately_within_content() {
+        use ratatui::text::Line;
+
+        let mut app = App::default();
+        app.response_view_height = 10;
+        app.response_body_cache.lines = vec![Line::from("row"); 35];
+        app.response_headers_cache.lines = vec![Line::from("header"); 4];
+        let mut scroll = |pages| {
+            apply(&mut app, Action::ScrollResponsePages(pages));
+            (app.response_body_cache.scroll, app.response_scroll_percent())
+        };
+        assert_eq!(scroll(Pages::HalfDown), (5, Some(20)));
+        assert_eq!(scroll(Pages::Down), (15, Some(60)));
+        // Stops with the last row at the bottom of the panel
+        assert_eq!(scroll(Pages::Down), (25, Some(100)));
+        assert_eq!(scroll(Pages::Down), (25, Some(100)));
+        assert_eq!(scroll(Pages::HalfUp), (20, Some(80)));
+
+        app.response_tab = ResponseTab::Headers;
+        apply(&mut app, Action::ScrollResponsePages(Pages::Down));
+        assert_eq!((app.response_headers_cache.scroll, app.response_scroll_percent()), (0, None));
+        app.response_tab = ResponseTab::Body;
+        assert_eq!(app.response_body_cache.scroll, 20);
+    }
+
+    /// An app with `body` in the response panel, focused for editing.
+    fn response_body_app(body: &str) -> App {
+        let mut app = App::default();
+        app.response_editor = tui_textarea::TextArea::new(body.lines().map(String::from).collect());
+        app.focus.panel = Panel::Response;
+        app.response_tab = ResponseTab::Body;
+        app.app_mode = AppMode::Editing;
+        app
+    }
+
+    fn press(app: &mut App, key: KeyEvent) {
+        let action = dispatch(&app.input_context(), key).unwrap();
+        apply(app, action);
+    }
+
+    fn press_keys(app: &mut App, keys: &str) {
+        for c in keys.chars() {
+            press(app, key(KeyCode::Char(c)));
+        }
+    }
+
+    /// An app on an empty project with the sidebar focused. Storage is
+    /// pointed at a scratch directory first so saves stay out of the checkout.
+    fn project_app() -> App {
+        static ROOT: std::sync::Once = std::sync::Once::new();
+        ROOT.call_once(|| {
+            let dir = std::env::temp_dir().join(format!("perseus-app-{}", std::process::id()));
+            std::fs::create_dir_all(&dir).unwrap();
+            crate::storage::init_project_root(Some(dir)).unwrap();
+        });
+        let root = crate::storage::find_project_root().unwrap();
+        assert!(root.to_string_lossy().contains("perseus-app-"), "{}", root.display());
+
+        let mut app = App::default();
+        app.active_project_id = app.collection.add_project("Project".to_string()).unwrap();
+        app.sidebar_width = 40;
+        app
+    }
+
+    /// Adds `path` (`Folder/Request`) under the project from the sidebar's
+    /// add popup, which also opens it.
+    fn add_from_sidebar(app: &mut App, path: &str) -> uuid::Uuid {
+        app.app_mode = AppMode::Sidebar;
+        app.focus.panel = Panel::Sidebar;
+        app.sidebar.selection_id = Some(app.active_project_id);
+        press_keys(app, "a");
+        press_keys(app, path);
+        press(app, key(KeyCode::Enter));
+        app.app_mode = AppMode::Sidebar;
+        app.current_request_id.unwrap()
+    }
+
+    fn item_name(app: &App, id: uuid::Uuid) -> &str {
+        &app.collection.get_item(id).unwrap().name
+    }
+
+    /// Picks `filter` from the sidebar's `f` popup, which opens on the
+    /// current one.
+    fn pick_filter(app: &mut App, filter: SidebarFilter) {
+        let choices = SidebarFilter::choices(&[]);
+        let at = |wanted: &SidebarFilter| choices.iter().position(|f| f == wanted).unwrap();
+        let steps = (at(&filter) + choices.len() - at(&app.sidebar.filter)) % choices.len();
+        app.app_mode = AppMode::Sidebar;
+        press_keys(app, "f");
+        press_keys(app, &"j".repeat(steps));
+        press(app, key(KeyCode::Enter));
+        assert_eq!(app.sidebar.filter, filter);
+    }
+
+    fn sidebar_ids(app: &mut App) -> Vec<uuid::Uuid> {
+        app.sidebar_lines().iter().map(|line| line.id).collect()
+    }
+
+    #[tokio::test]
+    async fn test_sidebar_filters_narrow_the_tree() {
+        use crate::app::HttpMethod;
+
+        let mut app = project_app();
+        app.config.defaults.new_request_method = "POST".to_string();
+        let create = add_from_sidebar(&mut app, "Create");
+        app.config.defaults.new_request_method = "GET".to_string();
+        add_from_sidebar(&mut app, "List");
+        let health = add_from_sidebar(&mut app, "Health");
+        assert_eq!(sidebar_ids(&mut app).len(), 4);
+
+        pick_filter(&mut app, SidebarFilter::Method(HttpMethod::Post));
+        assert_eq!(sidebar_ids(&mut app), vec![create]);
+
+        // Health is open, and shows up once it has unsaved edits
+        pick_filter(&mut app, SidebarFilter::Unsaved);
+        assert!(sidebar_ids(&mut app).is_empty());
+        app.request_dirty = true;
+        assert_eq!(sidebar_ids(&mut app), vec![health]);
+        app.request_dirty = false;
+
+        // Nothing listens on port 1, so the send fails
+        app.project_trusted = true;
+        let url = "http://127.0.0.1:1/".to_string();
+        app.request.set_contents(Method::from_str("GET"), url, String::new(), String::new());
+        let (tx, mut rx) = mpsc::channel(16);
+        app.apply_action(Action::SendOrCancel, tx);
+        while matches!(app.response, ResponseStatus::Loading) {
+            let event = rx.recv().await.unwrap();
+            app.handle_request_event(event);
+        }
+        assert!(matches!(app.response, ResponseStatus::Error(_)));
+        pick_filter(&mut app, SidebarFilter::Failing);
+        assert_eq!(sidebar_ids(&mut app), vec![health]);
+    }
+
+    #[test]
+    fn test_inline_rename_commits_on_enter_and_cancels_on_esc() {
+        use crate::app::SidebarPopup;
+
+        let mut app = project_app();
+        let id = add_from_sidebar(&mut app, "Users");
+        press_keys(&mut app, "r");
+        assert!(matches!(app.sidebar.popup, Some(SidebarPopup::RenameInline(_))));
+        // The field starts with the name, cursor at the end
+        press_keys(&mut app, " v2");
+        press(&mut app, key(KeyCode::Enter));
+        assert!(app.sidebar.popup.is_none());
+        assert_eq!(item_name(&app, id), "Users v2");
+
+        press_keys(&mut app, "rx");
+        press(&mut app, key(KeyCode::Esc));
+        assert!(app.sidebar.popup.is_none());
+        assert_eq!(item_name(&app, id), "Users v2");
+
+        // Esc also leaves the sidebar, so `e` comes back to it. Word-delete
+        // clears the field; an empty name is refused and the row stays in
+        // edit mode
+        press_keys(&mut app, "er");
+        press(&mut app, ctrl('w'));
+        press(&mut app, ctrl('w'));
+        press(&mut app, key(KeyCode::Enter));
+        assert!(matches!(app.sidebar.popup, Some(SidebarPopup::RenameInline(_))));
+        assert_eq!(app.notifications.log().last().unwrap().message, "Name cannot be empty");
+        assert_eq!(item_name(&app, id), "Users v2");
+    }
+
+    #[test]
+    fn test_opening_from_search_reveals_the_request() {
+        let mut app = project_app();
+        let id = add_from_sidebar(&mut app, "Admin/Users/Delete user");
+        for n in 0..30 {
+            add_from_sidebar(&mut app, &format!("Admin/Users/Create {:02}", n));
+            add_from_sidebar(&mut app, &format!("Status {:02}", n));
+        }
+        press_keys(&mut app, "H/delete");
+        press(&mut app, key(KeyCode::Enter));
+        let found: Vec<_> = app.sidebar_lines().iter().map(|line| line.id).collect();
+        assert_eq!(found, vec![id]);
+        press_keys(&mut app, "j");
+        assert_eq!(app.sidebar_selected_id(), Some(id));
+
+        press(&mut app, key(KeyCode::Enter));
+        assert_eq!(app.current_request_id, Some(id));
+        assert!(app.sidebar.search_query.is_empty());
+        let users = app.sidebar_tree.node(id).unwrap().parent_id.unwrap();
+        let admin = app.sidebar_tree.node(users).unwrap().parent_id.unwrap();
+        assert!(app.sidebar.expanded.contains(&users) && app.sidebar.expanded.contains(&admin));
+        // Below the first screenful, so the view scrolls to center it
+        let index = app.sidebar_lines().iter().position(|line| line.id == id).unwrap();
+        assert!(index > 10, "{}", index);
+        assert_eq!(app.sidebar_scroll_for(10), index - 5);
+        assert_eq!(app.sidebar.flash.map(|(flashed, _)| flashed), Some(id));
+    }
+
+    #[test]
+    fn test_response_selection_becomes_the_body() {
+        use crate::app::BodyMode;
+
+        let mut app = response_body_app("id=7 name=x\nnext");
+        press_keys(&mut app, "vlllB");
+        assert_eq!(app.request.body_editor.lines().join("\n"), "id=7");
+        assert_eq!(app.request.body_mode, BodyMode::Raw);
+        let focus = (app.focus.panel, app.focus.request_field);
+        assert_eq!(focus, (Panel::Request, RequestField::Body));
+        let last = app.notifications.log().last().unwrap();
+        assert_eq!(last.message, "Selection is now the request body");
+
+        // Ending on the last character doesn't take the line break along
+        let mut app = response_body_app("first second\nnext");
+        press_keys(&mut app, "wv$B");
+        assert_eq!(app.request.body_editor.lines().join("\n"), "second");
+    }
+
+    #[test]
+    fn test_response_selection_reports_a_form_body_switch() {
+        use crate::app::BodyMode;
+        use crate::notify::Level;
+
+        let mut app = response_body_app("{\"a\": 1}");
+        app.request.body_mode = BodyMode::FormUrlEncoded;
+        press_keys(&mut app, "v$B");
+        assert_eq!(app.request.body_mode, BodyMode::Json);
+        assert_eq!(app.request.body_editor.lines().join("\n"), "{\"a\": 1}");
+        let last = app.notifications.log().last().unwrap();
+        assert_eq!(last.level, Level::Warn);
+        assert!(last.message.ends_with("body mode Form URL-Encoded \u{2192} JSON"));
+    }
+
+    fn render(app: &mut App) -> String {
+        let backend = ratatui::backend::TestBackend::new(120, 30);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        terminal.draw(|frame| crate::ui::render(frame, app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let row = |y| (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect::<String>();
+        (0..buffer.area.height).map(row).collect::<Vec<_>>().join("\n")
+    }
+
+    #[test]
+    fn test_progress_event_puts_the_status_on_the_loading_line() {
+        use crate::http::{Progress, RequestEvent};
+
+        let mut app = App::default();
+        app.response = ResponseStatus::Loading;
+        app.loading_started = Some(std::time::Instant::now());
+        assert!(render(&mut app).contains("Sending request..."));
+
+        let progress = Progress { status: 200, status_text: "OK", received: 1536, total: None };
+        app.handle_request_event(RequestEvent::Progress(progress));
+        let screen = render(&mut app);
+        assert!(screen.contains("\u{2014} 200 OK \u{2014} 1.5 KB received"), "{}", screen);
+        assert!(!screen.contains("Sending request..."));
+
+        // A late update after the send ended is dropped
+        app.response = ResponseStatus::Cancelled;
+        app.loading_progress = None;
+        app.handle_request_event(RequestEvent::Progress(progress));
+        assert_eq!(app.loading_progress, None);
+    }
+
+    #[test]
+    fn test_method_popup_type_ahead() {
+        let mut app = App::default();
+        app.show_method_popup = true;
+        app.recent_methods = vec!["PROPFIND".to_string(), "REPORT".to_string()];
+        let mut press = |c| {
+            apply(&mut app, Action::Overlay(Overlay::MethodPopup, key(KeyCode::Char(c))));
+            app.method_popup_index
+        };
+        // GET, POST, PUT, PATCH, DELETE, HEAD, OPTIONS, Custom..., PROPFIND, REPORT
+        assert_eq!([press('p'), press('p'), press('P'), press('p')], [1, 2, 3, 8]);
+        assert_eq!([press('p'), press('r'), press('x')], [1, 9, 9]);
+        // j/k still move one entry
+        assert_eq!([press('j'), press('j'), press('k')], [0, 1, 0]);
+    }
+
+    #[test]
+    fn test_apply_action_moves_focus() {
+        let mut app = App::default();
+        apply(&mut app, Action::MoveRight);
+        assert_eq!(app.input_context().request_field, RequestField::Send);
+
+        apply(&mut app, Action::ToggleSidebar);
+        let ctx = app.input_context();
+        assert_eq!((ctx.mode, ctx.panel), (AppMode::Sidebar, Panel::Sidebar));
+        apply(&mut app, Action::ToggleSidebar);
+        let ctx = app.input_context();
+        assert_eq!((ctx.mode, ctx.panel), (AppMode::Navigation, Panel::Request));
+        assert_eq!(ctx.request_field, RequestField::Url);
+    }
+}
@@ -0,0 +1,358 @@
+//! Locating values inside JSON text. Works on the text as displayed rather
+//! than on a parsed `Value`, so a cursor position can be mapped back to the
+//! path of the value under it.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// Renders a path as `$.data[0].name`; keys that aren't plain identifiers
+/// use bracket notation (`$["content-type"]`).
+pub fn format_path(path: &[Segment]) -> String {
+    let mut out = String::from("$");
+    for segment in path {
+        match segment {
+            Segment::Index(i) => out.push_str(&format!("[{}]", i)),
+            Segment::Key(key) => {
+                let plain = !key.is_empty()
+                    && !key.starts_with(|c: char| c.is_ascii_digit())
+                    && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+                if plain {
+                    out.push('.');
+                    out.push_str(key);
+                } else {
+                    out.push_str(&format!("[{}]", serde_json::Value::from(key.as_str())));
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Parses a path relative to some root: `.data[0].name`, `["content-type"]`
+/// or `[2]`. The leading `$` is optional. Returns `None` on malformed input.
+pub fn parse_path(expr: &str) -> Option<Vec<Segment>> {
+    let mut rest = expr.strip_prefix('$').unwrap_or(expr);
+    let mut path = Vec::new();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            if end == 0 {
+                return None;
+            }
+            path.push(Segment::Key(after[..end].to_string()));
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let quote = after.chars().next()?;
+            if quote == '"' || quote == '\'' {
+                let close = after[1..].find(quote)? + 1;
+                path.push(Segment::Key(after[1..close].to_string()));
+                rest = after[close + 1..].strip_prefix(']')?;
+            } else {
+                let close = after.find(']')?;
+                path.push(Segment::Index(after[..close].trim().parse().ok()?));
+                rest = &after[close + 1..];
+            }
+        } else {
+            return None;
+        }
+    }
+    Some(path)
+}
+
+/// Follows `path` from `value`.
+pub fn lookup<'a>(value: &'a serde_json::Value, path: &[Segment]) -> Option<&'a serde_json::Value> {
+    path.iter().try_fold(value, |current, segment| match segment {
+        Segment::Key(key) => current.get(key.as_str()),
+        Segment::Index(i) => current.get(*i),
+    })
+}
+
+/// A value found in JSON text: its path and byte range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueAt {
+    pub path: Vec<Segment>,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl ValueAt {
+    /// The value's text for copying: string contents without quotes, other
+    /// values as written. Multi-line objects and arrays are dedented by the
+    /// indentation of their first line so key order and layout survive.
+    pub fn extract(&self, text: &str) -> String {
+        let raw = &text[self.start..self.end];
+        if raw.starts_with('"') {
+            return serde_json::from_str::<String>(raw).unwrap_or_else(|_| raw.to_string());
+        }
+        let line_start = text[..self.start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let indent = text[line_start..self.start]
+            .chars()
+            .take_while(|c| *c == ' ' || *c == '\t')
+            .count();
+        raw.lines()
+            .enumerate()
+            .map(|(i, line)| {
+                let strip = line
+                    .chars()
+                    .take(indent)
+                    .take_while(|c| *c == ' ' || *c == '\t')
+                    .count();
+                if i == 0 {
+                    line
+                } else {
+                    &line[strip..]
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Finds the innermost value containing byte `offset`. A cursor on an
+/// object key (or between the key and its value) selects that member's
+/// value. Returns `None` for text that isn't valid JSON.
+pub fn value_at(text: &str, offset: usize) -> Option<ValueAt> {
+    let mut scanner = Scanner {
+        bytes: text.as_bytes(),
+        pos: 0,
+        offset,
+        path: Vec::new(),
+        found: None,
+    };
+    scanner.skip_ws();
+    scanner.value()?;
+    scanner.skip_ws();
+    if scanner.pos != scanner.bytes.len() {
+        return None;
+    }
+    scanner.found
+}
+
+/// Byte offset of a (row, char column) cursor within `lines` joined by `\n`.
+pub fn cursor_offset(lines: &[String], row: usize, col: usize) -> usize {
+    let before: usize = lines.iter().take(row).map(|l| l.len() + 1).sum();
+    let line = lines.get(row).map(String::as_str).unwrap_or("");
+    let within = line
+        .char_indices()
+        .nth(col)
+        .map(|(i, _)| i)
+        .unwrap_or(line.len());
+    before + within
+}
+
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    offset: usize,
+    path: Vec<Segment>,
+    found: Option<ValueAt>,
+}
+
+impl Scanner<'_> {
+    fn skip_ws(&mut self) {
+        while self.pos < self.bytes.len() && self.bytes[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        if self.bytes.get(self.pos) == Some(&byte) {
+            self.pos += 1;
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    /// Parses one value at `pos`; records it if it spans `offset`.
+    fn value(&mut self) -> Option<()> {
+        let start = self.pos;
+        match *self.bytes.get(self.pos)? {
+            b'{' => self.object()?,
+            b'[' => self.array()?,
+            b'"' => self.string()?,
+            _ => self.scalar()?,
+        }
+        let end = self.pos;
+        let within = start <= self.offset && self.offset < end;
+        // Children are recorded after their parent returns, so only take a
+        // span when nothing deeper claimed the offset.
+        let deeper = self
+            .found
+            .as_ref()
+            .is_some_and(|f| f.start >= start && f.end <= end && f.path.len() > self.path.len());
+        if within && !deeper {
+            self.found = Some(ValueAt {
+                path: self.path.clone(),
+                start,
+                end,
+            });
+        }
+        Some(())
+    }
+
+    fn object(&mut self) -> Option<()> {
+        self.expect(b'{')?;
+        self.skip_ws();
+        if self.expect(b'}').is_some() {
+            return Some(());
+        }
+        loop {
+            self.skip_ws();
+            let key_start = self.pos;
+            self.string()?;
+            let key: String = serde_json::from_slice(&self.bytes[key_start..self.pos]).ok()?;
+            self.skip_ws();
+            self.expect(b':')?;
+            self.skip_ws();
+            let value_start = self.pos;
+            self.path.push(Segment::Key(key));
+            self.value()?;
+            if key_start <= self.offset && self.offset < value_start {
+                self.found = Some(ValueAt {
+                    path: self.path.clone(),
+                    start: value_start,
+                    end: self.pos,
+                });
+            }
+            self.path.pop();
+            self.skip_ws();
+            if self.expect(b',').is_some() {
+                continue;
+            }
+            return self.expect(b'}');
+        }
+    }
+
+    fn array(&mut self) -> Option<()> {
+        self.expect(b'[')?;
+        self.skip_ws();
+        if self.expect(b']').is_some() {
+            return Some(());
+        }
+        let mut index = 0;
+        loop {
+            self.skip_ws();
+            self.path.push(Segment::Index(index));
+            self.value()?;
+            self.path.pop();
+            index += 1;
+            self.skip_ws();
+            if self.expect(b',').is_some() {
+                continue;
+            }
+            return self.expect(b']');
+        }
+    }
+
+    fn string(&mut self) -> Option<()> {
+        self.expect(b'"')?;
+        while let Some(&b) = self.bytes.get(self.pos) {
+            self.pos += 1;
+            match b {
+                b'\\' => self.pos += 1,
+                b'"' => return Some(()),
+                _ => {}
+            }
+        }
+        None
+    }
+
+    fn scalar(&mut self) -> Option<()> {
+        let start = self.pos;
+        while let Some(&b) = self.bytes.get(self.pos) {
+            if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'+' | b'.') {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        (self.pos > start).then_some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = r#"{
+  "data": [
+    {
+      "name": "Ada \"L\"",
+      "age": 36
+    }
+  ],
+  "content-type": null
+}"#;
+
+    fn offset_of(needle: &str) -> usize {
+        DOC.find(needle).unwrap()
+    }
+
+    #[test]
+    fn test_value_at_string_under_cursor() {
+        let found = value_at(DOC, offset_of("Ada") + 1).unwrap();
+        assert_eq!(format_path(&found.path), "$.data[0].name");
+        assert_eq!(found.extract(DOC), "Ada \"L\"");
+    }
+
+    #[test]
+    fn test_value_at_key_selects_member_value() {
+        let found = value_at(DOC, offset_of("\"age\"")).unwrap();
+        assert_eq!(format_path(&found.path), "$.data[0].age");
+        assert_eq!(found.extract(DOC), "36");
+    }
+
+    #[test]
+    fn test_value_at_structural_char_selects_container() {
+        let found = value_at(DOC, offset_of("[")).unwrap();
+        assert_eq!(format_path(&found.path), "$.data");
+        assert!(found.extract(DOC).starts_with("[\n  {\n    \"name\""));
+        assert!(found.extract(DOC).ends_with("\n  }\n]"));
+
+        let root = value_at(DOC, 0).unwrap();
+        assert!(root.path.is_empty());
+        assert_eq!(format_path(&root.path), "$");
+    }
+
+    #[test]
+    fn test_format_path_quotes_unusual_keys() {
+        let found = value_at(DOC, offset_of("null")).unwrap();
+        assert_eq!(format_path(&found.path), "$[\"content-type\"]");
+    }
+
+    #[test]
+    fn test_value_at_invalid_json() {
+        assert!(value_at("{\"a\": ", 2).is_none());
+        assert!(value_at("not json", 0).is_none());
+    }
+
+    #[test]
+    fn test_parse_path_and_lookup() {
+        let path = parse_path(".data[0].name").unwrap();
+        assert_eq!(format_path(&path), "$.data[0].name");
+        assert_eq!(
+            parse_path("$[\"content-type\"]").unwrap(),
+            vec![Segment::Key("content-type".into())]
+        );
+        assert_eq!(parse_path("").unwrap(), Vec::new());
+        assert!(parse_path("data").is_none());
+        assert!(parse_path(".a[x]").is_none());
+        assert!(parse_path(".a..b").is_none());
+
+        let value: serde_json::Value = serde_json::from_str(DOC).unwrap();
+        assert_eq!(lookup(&value, &path).unwrap(), "Ada \"L\"");
+        assert!(lookup(&value, &parse_path(".data[3]").unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_cursor_offset() {
+        let lines = vec!["{".to_string(), "  \"é\": 1".to_string(), "}".to_string()];
+        assert_eq!(cursor_offset(&lines, 0, 0), 0);
+        assert_eq!(cursor_offset(&lines, 1, 4), 2 + 5);
+        assert_eq!(cursor_offset(&lines, 2, 0), 2 + 10);
+    }
+}
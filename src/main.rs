@@ -1,17 +1,72 @@
 mod app;
+mod audit;
+mod chain;
 mod clipboard;
 mod config;
+mod cors;
+mod diff;
+mod encoding;
+mod html;
 mod http;
+mod http1;
+mod input;
+mod json_path;
+mod notify;
 mod perf;
+mod raw_http;
+mod runner;
+mod schema;
+mod sendlog;
+mod snippets;
 mod storage;
 mod ui;
+mod util;
 mod vim;
+mod yaml;
 
-use anyhow::Result;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
 use app::App;
 
+const USAGE: &str = "usage: perseus [--root <path>]";
+
+/// What the command line asks for.
+enum Cli {
+    Help,
+    /// Open the TUI, with `--root` if given.
+    Run { root: Option<PathBuf> },
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
+    let root = match parse_args(std::env::args().skip(1)) {
+        Ok(Cli::Run { root }) => root,
+        Ok(Cli::Help) => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+        Err(err) => return Err(anyhow!("{}\n{}", err, USAGE)),
+    };
+    storage::init_project_root(root).map_err(|err| anyhow!(err))?;
     let mut app = App::new()?;
     app.run().await
 }
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Cli, String> {
+    let mut root = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Cli::Help),
+            "--root" => match args.next() {
+                Some(path) => root = Some(PathBuf::from(path)),
+                None => return Err("--root needs a directory".to_string()),
+            },
+            _ => match arg.strip_prefix("--root=") {
+                Some(path) => root = Some(PathBuf::from(path)),
+                None => return Err(format!("unknown argument: {}", arg)),
+            },
+        }
+    }
+    Ok(Cli::Run { root })
+}
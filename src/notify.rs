@@ -0,0 +1,164 @@
+//! Transient status-bar notifications plus a bounded history of everything
+//! that was shown, for the message log popup.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Notifications shown at once; older ones are pushed out early.
+const MAX_VISIBLE: usize = 3;
+/// Entries kept for the message log.
+const LOG_CAPACITY: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Level::Info => "info",
+            Level::Warn => "warn",
+            Level::Error => "error",
+        }
+    }
+
+    /// Errors stay up longer so they can be read.
+    fn duration(&self) -> Duration {
+        match self {
+            Level::Info => Duration::from_secs(2),
+            Level::Warn => Duration::from_secs(4),
+            Level::Error => Duration::from_secs(6),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub level: Level,
+    pub message: String,
+    /// Wall-clock time, for the log.
+    pub time: SystemTime,
+    expires: Instant,
+}
+
+impl Notification {
+    /// "HH:MM:SS" in UTC.
+    pub fn clock(&self) -> String {
+        let secs = self
+            .time
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let day = secs % 86_400;
+        format!("{:02}:{:02}:{:02}", day / 3600, day % 3600 / 60, day % 60)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Notifications {
+    active: VecDeque<Notification>,
+    log: VecDeque<Notification>,
+}
+
+impl Notifications {
+    pub fn push(&mut self, level: Level, message: impl Into<String>) {
+        self.push_at(level, message.into(), Instant::now());
+    }
+
+    fn push_at(&mut self, level: Level, message: String, now: Instant) {
+        let notification = Notification {
+            level,
+            message,
+            time: SystemTime::now(),
+            expires: now + level.duration(),
+        };
+        if self.log.len() == LOG_CAPACITY {
+            self.log.pop_front();
+        }
+        self.log.push_back(notification.clone());
+        if self.active.len() == MAX_VISIBLE {
+            self.active.pop_front();
+        }
+        self.active.push_back(notification);
+    }
+
+    /// Drops expired notifications; returns true if any were removed.
+    pub fn expire(&mut self, now: Instant) -> bool {
+        let before = self.active.len();
+        self.active.retain(|n| n.expires > now);
+        self.active.len() != before
+    }
+
+    /// Currently shown notifications, oldest first.
+    pub fn visible(&self) -> impl DoubleEndedIterator<Item = &Notification> {
+        self.active.iter()
+    }
+
+    /// When the next visible notification expires.
+    pub fn next_expiry(&self) -> Option<Instant> {
+        self.active.iter().map(|n| n.expires).min()
+    }
+
+    /// History, oldest first.
+    pub fn log(&self) -> impl DoubleEndedIterator<Item = &Notification> + ExactSizeIterator {
+        self.log.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_visible_stack_is_capped() {
+        let mut notes = Notifications::default();
+        let now = Instant::now();
+        for i in 0..5 {
+            notes.push_at(Level::Info, format!("m{}", i), now);
+        }
+        let visible: Vec<&str> = notes.visible().map(|n| n.message.as_str()).collect();
+        assert_eq!(visible, vec!["m2", "m3", "m4"]);
+        assert_eq!(notes.log().len(), 5);
+    }
+
+    #[test]
+    fn test_expire_by_level() {
+        let mut notes = Notifications::default();
+        let now = Instant::now();
+        notes.push_at(Level::Info, "info".to_string(), now);
+        notes.push_at(Level::Error, "error".to_string(), now);
+        assert_eq!(notes.next_expiry(), Some(now + Duration::from_secs(2)));
+
+        assert!(notes.expire(now + Duration::from_secs(3)));
+        let visible: Vec<&str> = notes.visible().map(|n| n.message.as_str()).collect();
+        assert_eq!(visible, vec!["error"]);
+        assert!(!notes.expire(now + Duration::from_secs(3)));
+        assert!(notes.expire(now + Duration::from_secs(7)));
+        assert_eq!(notes.visible().count(), 0);
+        assert_eq!(notes.log().len(), 2);
+    }
+
+    #[test]
+    fn test_log_is_bounded() {
+        let mut notes = Notifications::default();
+        for i in 0..(LOG_CAPACITY + 10) {
+            notes.push(Level::Warn, format!("m{}", i));
+        }
+        assert_eq!(notes.log().len(), LOG_CAPACITY);
+        assert_eq!(notes.log().next().unwrap().message, "m10");
+    }
+
+    #[test]
+    fn test_clock_format() {
+        let note = Notification {
+            level: Level::Info,
+            message: String::new(),
+            time: UNIX_EPOCH + Duration::from_secs(86_400 * 3 + 3600 * 13 + 60 * 5 + 9),
+            expires: Instant::now(),
+        };
+        assert_eq!(note.clock(), "13:05:09");
+    }
+}
@@ -1,40 +1,101 @@
-#[cfg(feature = "perf")]
-use std::time::Instant;
+//! Scoped timings. With the `perf` feature every scope is logged to stderr;
+//! at runtime, recording collects the scopes of each frame for the overlay.
+
+use std::cell::{Cell, RefCell};
+use std::time::{Duration, Instant};
+
+thread_local! {
+    static RECORDING: Cell<bool> = const { Cell::new(false) };
+    static FRAME: RefCell<Vec<(&'static str, Duration)>> = const { RefCell::new(Vec::new()) };
+}
 
-#[cfg(feature = "perf")]
 pub struct PerfGuard {
     label: &'static str,
-    start: Instant,
+    start: Option<Instant>,
 }
 
-#[cfg(feature = "perf")]
 impl Drop for PerfGuard {
     fn drop(&mut self) {
-        let elapsed = self.start.elapsed();
-        let micros = elapsed.as_secs() * 1_000_000 + u64::from(elapsed.subsec_micros());
-        eprintln!("[perf] {}: {}us", self.label, micros);
+        let Some(start) = self.start else {
+            return;
+        };
+        let elapsed = start.elapsed();
+        #[cfg(feature = "perf")]
+        {
+            let micros = elapsed.as_secs() * 1_000_000 + u64::from(elapsed.subsec_micros());
+            eprintln!("[perf] {}: {}us", self.label, micros);
+        }
+        if RECORDING.get() {
+            FRAME.with_borrow_mut(|frame| frame.push((self.label, elapsed)));
+        }
     }
 }
 
-#[cfg(feature = "perf")]
 #[inline]
 pub fn scope(label: &'static str) -> PerfGuard {
+    let timed = cfg!(feature = "perf") || RECORDING.get();
     PerfGuard {
         label,
-        start: Instant::now(),
+        start: timed.then(Instant::now),
     }
 }
 
-#[cfg(not(feature = "perf"))]
-pub struct PerfGuard;
+/// Turns per-frame recording on or off; turning it off drops anything
+/// recorded so far.
+pub fn set_recording(on: bool) {
+    RECORDING.set(on);
+    if !on {
+        FRAME.with_borrow_mut(Vec::clear);
+    }
+}
 
-#[cfg(not(feature = "perf"))]
-impl Drop for PerfGuard {
-    fn drop(&mut self) {}
+/// Total time spent in one scope label during a frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScopeTiming {
+    pub label: &'static str,
+    pub total: Duration,
+    pub calls: usize,
 }
 
-#[cfg(not(feature = "perf"))]
-#[inline]
-pub fn scope(_label: &'static str) -> PerfGuard {
-    PerfGuard
+/// Drains the scopes recorded since the last call, merged by label in the
+/// order they first finished.
+pub fn take_frame() -> Vec<ScopeTiming> {
+    let recorded = FRAME.with_borrow_mut(std::mem::take);
+    let mut timings: Vec<ScopeTiming> = Vec::new();
+    for (label, elapsed) in recorded {
+        match timings.iter_mut().find(|t| t.label == label) {
+            Some(timing) => {
+                timing.total += elapsed;
+                timing.calls += 1;
+            }
+            None => timings.push(ScopeTiming {
+                label,
+                total: elapsed,
+                calls: 1,
+            }),
+        }
+    }
+    timings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_frame_merges_labels_while_recording() {
+        drop(scope("ignored"));
+        set_recording(true);
+        drop(scope("inner"));
+        drop(scope("outer"));
+        drop(scope("inner"));
+        let frame = take_frame();
+        let summary: Vec<_> = frame.iter().map(|t| (t.label, t.calls)).collect();
+        assert_eq!(summary, vec![("inner", 2), ("outer", 1)]);
+        assert!(take_frame().is_empty());
+
+        set_recording(false);
+        drop(scope("after"));
+        assert!(take_frame().is_empty());
+    }
 }
@@ -0,0 +1,147 @@
+//! The request as an editable HTTP/1.1 message: `METHOD URL HTTP/1.1`,
+//! header lines, a blank line and the body. Placeholders are kept as typed,
+//! so a message can be parsed back into the request without losing them.
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+/// Why a message couldn't be parsed, with the 1-based line it refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+pub fn render(request: &RawRequest) -> String {
+    let mut out = format!("{} {} HTTP/1.1\n", request.method, request.url);
+    for (key, value) in &request.headers {
+        out.push_str(&format!("{}: {}\n", key, value));
+    }
+    out.push('\n');
+    out.push_str(&request.body);
+    out
+}
+
+/// Parses a message written by [`render`] or by hand. The target may also
+/// be a path (`GET /users`), in which case the `Host` header supplies the
+/// authority and `scheme` is used for the URL.
+pub fn parse(text: &str, scheme: &str) -> Result<RawRequest, ParseError> {
+    let error = |line: usize, message: &str| ParseError {
+        line,
+        message: message.to_string(),
+    };
+    let mut lines = text.split('\n').map(|l| l.strip_suffix('\r').unwrap_or(l));
+
+    let request_line = lines.next().unwrap_or("");
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(error(1, "expected \"METHOD URL HTTP/1.1\""));
+    };
+    if let Some(version) = parts.next() {
+        if !version.starts_with("HTTP/") {
+            return Err(error(1, "expected the HTTP version after the URL"));
+        }
+    }
+    if parts.next().is_some() {
+        return Err(error(1, "unexpected text after the HTTP version"));
+    }
+    if !method.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(error(1, "the method may only contain letters"));
+    }
+
+    let mut headers = Vec::new();
+    let mut consumed = 1;
+    for line in lines.by_ref() {
+        consumed += 1;
+        if line.trim().is_empty() {
+            break;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            return Err(error(consumed, "expected \"Name: value\""));
+        };
+        let key = key.trim();
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            return Err(error(consumed, "invalid header name"));
+        }
+        headers.push((key.to_string(), value.trim().to_string()));
+    }
+    let body = lines.collect::<Vec<_>>().join("\n");
+
+    let url = if target.starts_with('/') {
+        let host = headers
+            .iter()
+            .position(|(k, _)| k.eq_ignore_ascii_case("host"))
+            .map(|i| headers.remove(i).1)
+            .ok_or_else(|| error(1, "a path target needs a Host header"))?;
+        format!("{}://{}{}", scheme, host, target)
+    } else {
+        target.to_string()
+    };
+
+    Ok(RawRequest {
+        method: method.to_ascii_uppercase(),
+        url,
+        headers,
+        body,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> RawRequest {
+        RawRequest {
+            method: "POST".to_string(),
+            url: "{{base_url}}/users?page=1".to_string(),
+            headers: vec![
+                ("Content-Type".to_string(), "application/json".to_string()),
+                ("X-Token".to_string(), "{{token}}".to_string()),
+            ],
+            body: "{\n  \"name\": \"Ada\"\n}".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_render_and_parse_round_trip() {
+        let text = render(&sample());
+        assert!(text.starts_with("POST {{base_url}}/users?page=1 HTTP/1.1\nContent-Type:"));
+        assert_eq!(parse(&text, "https").unwrap(), sample());
+    }
+
+    #[test]
+    fn test_parse_origin_form_uses_host_header() {
+        let text = "get /a?b=1\r\nHost: api.test:8080\r\nAccept: */*\r\n\r\n";
+        let parsed = parse(text, "http").unwrap();
+        assert_eq!(parsed.method, "GET");
+        assert_eq!(parsed.url, "http://api.test:8080/a?b=1");
+        assert_eq!(parsed.headers, vec![("Accept".to_string(), "*/*".to_string())]);
+        assert_eq!(parsed.body, "");
+    }
+
+    #[test]
+    fn test_parse_errors_name_the_line() {
+        assert_eq!(parse("", "http").unwrap_err().line, 1);
+        assert_eq!(parse("GET", "http").unwrap_err().line, 1);
+        assert_eq!(parse("GET /x HTTP/1.1", "http").unwrap_err().line, 1);
+        assert_eq!(parse("GET http://x FTP", "http").unwrap_err().line, 1);
+
+        let err = parse("GET http://x HTTP/1.1\nAccept: */*\nbroken\n\nbody", "http").unwrap_err();
+        assert_eq!(err.line, 3);
+        assert_eq!(err.to_string(), "line 3: expected \"Name: value\"");
+        assert_eq!(parse("GET http://x\nBad Name: 1", "http").unwrap_err().line, 2);
+    }
+}
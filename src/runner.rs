@@ -0,0 +1,624 @@
+//! Running every request of a folder in order, or several at a time,
+//! optionally once per row of a CSV or JSON data file whose columns become
+//! variables for that iteration. The app drives the sends; this module
+//! holds the data files, the run order, the report and the export of a
+//! run's responses.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::Serialize;
+
+use crate::schema::SchemaCheck;
+use crate::sendlog;
+use crate::storage::PostmanItem;
+
+/// Directory under the project root that run exports go in.
+pub const RUNS_DIR: &str = "runs";
+const SUMMARY_FILE: &str = "summary.json";
+
+/// Sends in flight at once when a run is made parallel.
+pub const DEFAULT_CONCURRENCY: usize = 4;
+pub const MAX_CONCURRENCY: usize = 16;
+
+/// The variables of one iteration, by column name.
+pub type DataRow = BTreeMap<String, String>;
+
+/// Reads a data file: a JSON array of objects when the file is `.json` or
+/// starts with `[`, CSV with a header row otherwise. Every row is checked
+/// here so a bad file fails before anything is sent.
+pub fn load_data_file(path: &Path) -> Result<Vec<DataRow>, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let is_json = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+        || text.trim_start().starts_with('[');
+    let rows = if is_json {
+        parse_json_data(&text)?
+    } else {
+        parse_csv_data(&text)?
+    };
+    if rows.is_empty() {
+        return Err(format!("{} has no data rows", path.display()));
+    }
+    Ok(rows)
+}
+
+/// Rows of a CSV file keyed by its header row. Blank lines are skipped;
+/// a row with a different number of fields than the header is an error.
+pub fn parse_csv_data(text: &str) -> Result<Vec<DataRow>, String> {
+    let mut records = parse_csv(text)?.into_iter();
+    let Some((_, header)) = records.next() else {
+        return Err("The CSV file is empty".to_string());
+    };
+    let header: Vec<String> = header.into_iter().map(|name| name.trim().to_string()).collect();
+    if let Some(column) = header.iter().position(|name| name.is_empty()) {
+        return Err(format!("CSV column {} has no name", column + 1));
+    }
+    for (i, name) in header.iter().enumerate() {
+        if header[..i].contains(name) {
+            return Err(format!("CSV column '{}' appears twice", name));
+        }
+    }
+    records
+        .map(|(line, fields)| {
+            if fields.len() != header.len() {
+                return Err(format!(
+                    "CSV line {} has {} fields but the header has {}",
+                    line,
+                    fields.len(),
+                    header.len()
+                ));
+            }
+            Ok(header.iter().cloned().zip(fields).collect())
+        })
+        .collect()
+}
+
+/// Splits CSV text into records, each with the line it starts on. Fields
+/// may be quoted with `"`, which allows commas, newlines and `""` for a
+/// quote inside them.
+fn parse_csv(text: &str) -> Result<Vec<(usize, Vec<String>)>, String> {
+    let mut records = Vec::new();
+    let mut fields: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = text.chars().peekable();
+    // Whether the current field was quoted, and is still inside its quotes
+    let mut quoted = false;
+    let mut in_quotes = false;
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                '\n' => {
+                    line += 1;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() && !quoted => {
+                quoted = true;
+                in_quotes = true;
+            }
+            ',' => {
+                fields.push(std::mem::take(&mut field));
+                quoted = false;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                fields.push(std::mem::take(&mut field));
+                quoted = false;
+                if !(fields.len() == 1 && fields[0].is_empty()) {
+                    records.push((record_line, std::mem::take(&mut fields)));
+                }
+                fields.clear();
+                line += 1;
+                record_line = line;
+            }
+            _ if quoted => {
+                return Err(format!(
+                    "CSV line {}: unexpected '{}' after a closing quote",
+                    line, c
+                ));
+            }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(format!("CSV line {}: quoted field is never closed", record_line));
+    }
+    if quoted || !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push((record_line, fields));
+    }
+    Ok(records)
+}
+
+/// Rows of a JSON array of objects. Strings are used as they are, other
+/// scalars as their JSON text, `null` as an empty value.
+pub fn parse_json_data(text: &str) -> Result<Vec<DataRow>, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(text).map_err(|e| format!("Invalid JSON data file: {}", e))?;
+    let serde_json::Value::Array(items) = value else {
+        return Err("The JSON data file must be an array of objects".to_string());
+    };
+    items
+        .into_iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let serde_json::Value::Object(object) = item else {
+                return Err(format!("JSON data item {} is not an object", i + 1));
+            };
+            Ok(object
+                .into_iter()
+                .map(|(key, value)| {
+                    let value = match value {
+                        serde_json::Value::String(s) => s,
+                        serde_json::Value::Null => String::new(),
+                        other => other.to_string(),
+                    };
+                    (key, value)
+                })
+                .collect())
+        })
+        .collect()
+}
+
+/// Requests under `item` in sidebar order, or `item` itself when it is a
+/// request.
+pub fn run_order(item: &PostmanItem) -> Vec<&PostmanItem> {
+    if item.is_request() {
+        return vec![item];
+    }
+    item.item.iter().flat_map(run_order).collect()
+}
+
+/// Orders a run so each request comes after the requests it references,
+/// keeping sidebar order where dependencies allow. `depends[i]` lists the
+/// indices request `i` references. Returns `(index, depth)` pairs in run
+/// order, depth counting the providers above a request; or, when the
+/// references loop, the indices around the cycle with the first repeated
+/// at the end.
+pub fn order_by_dependencies(depends: &[Vec<usize>]) -> Result<Vec<(usize, usize)>, Vec<usize>> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Mark {
+        New,
+        Visiting,
+        Done(usize),
+    }
+
+    fn visit(
+        index: usize,
+        depends: &[Vec<usize>],
+        marks: &mut [Mark],
+        path: &mut Vec<usize>,
+        order: &mut Vec<(usize, usize)>,
+    ) -> Result<usize, Vec<usize>> {
+        match marks[index] {
+            Mark::Done(depth) => return Ok(depth),
+            Mark::Visiting => {
+                let start = path.iter().position(|&i| i == index).unwrap_or(0);
+                let mut cycle = path[start..].to_vec();
+                cycle.push(index);
+                return Err(cycle);
+            }
+            Mark::New => {}
+        }
+        marks[index] = Mark::Visiting;
+        path.push(index);
+        let mut depth = 0;
+        for &provider in &depends[index] {
+            depth = depth.max(visit(provider, depends, marks, path, order)? + 1);
+        }
+        path.pop();
+        marks[index] = Mark::Done(depth);
+        order.push((index, depth));
+        Ok(depth)
+    }
+
+    let mut marks = vec![Mark::New; depends.len()];
+    let mut order = Vec::with_capacity(depends.len());
+    for index in 0..depends.len() {
+        visit(index, depends, &mut marks, &mut Vec::new(), &mut order)?;
+    }
+    Ok(order)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AssertionResult {
+    pub name: String,
+    pub passed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// The checks every response in a run goes through.
+pub fn check_response(status: u16) -> Vec<AssertionResult> {
+    let passed = status < 400;
+    vec![AssertionResult {
+        name: "Status is not an error".to_string(),
+        passed,
+        message: (!passed).then(|| format!("got {}", status)),
+    }]
+}
+
+/// The result of validating a response against its request's schema.
+pub fn check_schema(check: &SchemaCheck) -> AssertionResult {
+    let message = match check {
+        SchemaCheck::Passed => None,
+        SchemaCheck::Failed(violations) => violations.first().map(|first| {
+            let more = match violations.len() {
+                1 => String::new(),
+                n => format!(" (and {} more)", n - 1),
+            };
+            format!("{}: {}{}", first.path, first.message, more)
+        }),
+        SchemaCheck::SchemaError(err) => Some(format!("schema error: {}", err)),
+    };
+    AssertionResult {
+        name: "Body matches schema".to_string(),
+        passed: check.passed(),
+        message,
+    }
+}
+
+/// One request sent in one iteration.
+#[derive(Debug, Clone, Serialize)]
+pub struct StepResult {
+    pub request_id: String,
+    pub name: String,
+    pub method: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    pub duration_ms: u64,
+    /// Why no response came back.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub assertions: Vec<AssertionResult>,
+    /// The exported body, relative to the export directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+}
+
+impl StepResult {
+    pub fn passed(&self) -> bool {
+        self.error.is_none() && self.assertions.iter().all(|a| a.passed)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IterationReport {
+    /// Counted from 1.
+    pub iteration: usize,
+    pub variables: DataRow,
+    pub results: Vec<StepResult>,
+}
+
+impl IterationReport {
+    /// Adds `result` after the results whose requests `rank` puts before
+    /// or level with its own, so steps that finish out of order are still
+    /// listed in run order.
+    pub fn insert_ordered(&mut self, result: StepResult, rank: impl Fn(&str) -> usize) {
+        let at = rank(&result.request_id);
+        let index = self.results.partition_point(|other| rank(&other.request_id) <= at);
+        self.results.insert(index, result);
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RunReport {
+    pub target: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_file: Option<String>,
+    pub iterations: Vec<IterationReport>,
+    /// Set when the run was stopped before every request was sent.
+    pub cancelled: bool,
+}
+
+impl RunReport {
+    pub fn results(&self) -> impl Iterator<Item = &StepResult> {
+        self.iterations.iter().flat_map(|iteration| iteration.results.iter())
+    }
+
+    /// `(passed, failed)` steps so far.
+    pub fn totals(&self) -> (usize, usize) {
+        let passed = self.results().filter(|step| step.passed()).count();
+        (passed, self.results().count() - passed)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize run report: {}", e))?;
+        std::fs::write(path, json)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+/// A run's responses written to `runs/<timestamp>/` as they arrive: the
+/// body of each as `<request name>.json` with its headers beside it in
+/// `<request name>.headers.txt`, and the report as `summary.json` at the
+/// end.
+#[derive(Debug, Clone)]
+pub struct RunExport {
+    pub dir: PathBuf,
+    /// File stems given out so far.
+    taken: HashSet<String>,
+}
+
+impl RunExport {
+    /// Creates the export directory for a run started at `started`.
+    pub fn create(root: &Path, started: SystemTime) -> Result<Self, String> {
+        // Colons aren't allowed in Windows file names
+        let stamp = sendlog::timestamp(started).replace(':', "-");
+        let runs = root.join(RUNS_DIR);
+        let dir = (1..)
+            .map(|n| match n {
+                1 => runs.join(&stamp),
+                n => runs.join(format!("{}-{}", stamp, n)),
+            })
+            .find(|dir| !dir.exists())
+            .unwrap_or_else(|| runs.join(&stamp));
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        let taken = HashSet::from([SUMMARY_FILE.trim_end_matches(".json").to_string()]);
+        Ok(Self { dir, taken })
+    }
+
+    /// Writes one response and returns the body's file name.
+    pub fn write_response(
+        &mut self,
+        name: &str,
+        body: &[u8],
+        headers: &[(String, String)],
+    ) -> Result<String, String> {
+        let stem = free_stem(&export_file_stem(name), &self.taken);
+        self.taken.insert(stem.to_ascii_lowercase());
+        let file = format!("{}.json", stem);
+        let header_text: String =
+            headers.iter().map(|(name, value)| format!("{}: {}\n", name, value)).collect();
+        self.write(&file, body)?;
+        self.write(&format!("{}.headers.txt", stem), header_text.as_bytes())?;
+        Ok(file)
+    }
+
+    pub fn write_summary(&self, report: &RunReport) -> Result<(), String> {
+        report.save(&self.dir.join(SUMMARY_FILE))
+    }
+
+    fn write(&self, file: &str, contents: &[u8]) -> Result<(), String> {
+        let path = self.dir.join(file);
+        fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+/// `name` made safe as a file name on every platform: path separators,
+/// reserved punctuation and control characters become `_`.
+pub fn export_file_stem(name: &str) -> String {
+    let stem: String = name
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    // Leading dots would hide the file, trailing ones Windows drops
+    let stem = stem.trim_matches(|c: char| c == '.' || c.is_whitespace());
+    if stem.is_empty() {
+        "request".to_string()
+    } else {
+        stem.to_string()
+    }
+}
+
+/// `stem`, or `stem-2`, `stem-3`… when one is taken. Compared without
+/// case, as case-insensitive file systems would.
+fn free_stem(stem: &str, taken: &HashSet<String>) -> String {
+    if !taken.contains(&stem.to_ascii_lowercase()) {
+        return stem.to_string();
+    }
+    (2..)
+        .map(|n| format!("{}-{}", stem, n))
+        .find(|candidate| !taken.contains(&candidate.to_ascii_lowercase()))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::PostmanRequest;
+
+    fn row(pairs: &[(&str, &str)]) -> DataRow {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_parse_csv_quoting() {
+        let text = "user,note\r\nalice,\"says \"\"hi\"\", twice\"\n\nbob,\"two\nlines\"\ncarol,";
+        let rows = parse_csv_data(text).unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                row(&[("user", "alice"), ("note", "says \"hi\", twice")]),
+                row(&[("user", "bob"), ("note", "two\nlines")]),
+                row(&[("user", "carol"), ("note", "")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_csv_errors() {
+        let err = parse_csv_data("a,b\n1,2\n3\n").unwrap_err();
+        assert_eq!(err, "CSV line 3 has 1 fields but the header has 2");
+        let err = parse_csv_data("a,b\n\"1,2\n").unwrap_err();
+        assert!(err.contains("line 2: quoted field is never closed"), "{}", err);
+        let err = parse_csv_data("a\n\"x\"y\n").unwrap_err();
+        assert!(err.contains("after a closing quote"), "{}", err);
+        assert!(parse_csv_data("a,,c\n1,2,3").unwrap_err().contains("column 2 has no name"));
+        assert!(parse_csv_data("a,a\n1,2").unwrap_err().contains("appears twice"));
+        assert!(parse_csv_data("").is_err());
+    }
+
+    #[test]
+    fn test_parse_json_data() {
+        let rows = parse_json_data(r#"[{"id": 7, "name": "x", "on": true, "gone": null}]"#);
+        assert_eq!(
+            rows.unwrap(),
+            vec![row(&[("gone", ""), ("id", "7"), ("name", "x"), ("on", "true")])]
+        );
+        assert!(parse_json_data(r#"{"id": 1}"#).unwrap_err().contains("array"));
+        assert!(parse_json_data("[1]").unwrap_err().contains("item 1 is not an object"));
+    }
+
+    #[test]
+    fn test_run_order_is_depth_first() {
+        let request = |name: &str| {
+            PostmanItem::new_request(
+                name.to_string(),
+                PostmanRequest::new("GET".to_string(), String::new(), vec![], None),
+            )
+        };
+        let mut folder = PostmanItem::new_folder("Users".to_string());
+        folder.item.push(request("List"));
+        folder.item.push(request("Create"));
+        let mut project = PostmanItem::new_folder("API".to_string());
+        project.item.push(request("Login"));
+        project.item.push(folder);
+        project.item.push(request("Logout"));
+
+        let names: Vec<_> = run_order(&project).iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, vec!["Login", "List", "Create", "Logout"]);
+        assert_eq!(run_order(&project.item[0]).len(), 1);
+    }
+
+    #[test]
+    fn test_order_by_dependencies() {
+        // 0: List uses 2: Login; 1: Logout has none; 3: Get uses 0 and 2
+        let depends = vec![vec![2], vec![], vec![], vec![0, 2]];
+        assert_eq!(
+            order_by_dependencies(&depends).unwrap(),
+            vec![(2, 0), (0, 1), (1, 0), (3, 2)]
+        );
+        // Independent requests keep their order
+        assert_eq!(
+            order_by_dependencies(&[vec![], vec![], vec![]]).unwrap(),
+            vec![(0, 0), (1, 0), (2, 0)]
+        );
+        assert!(order_by_dependencies(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_order_by_dependencies_finds_cycles() {
+        let depends = vec![vec![], vec![3], vec![1], vec![2]];
+        assert_eq!(order_by_dependencies(&depends).unwrap_err(), vec![1, 3, 2, 1]);
+        assert_eq!(order_by_dependencies(&[vec![0]]).unwrap_err(), vec![0, 0]);
+    }
+
+    #[test]
+    fn test_report_totals() {
+        let step = |status, error: Option<&str>| StepResult {
+            request_id: String::new(),
+            name: "r".to_string(),
+            method: "GET".to_string(),
+            status,
+            duration_ms: 1,
+            error: error.map(str::to_string),
+            assertions: status.map(check_response).unwrap_or_default(),
+            file: None,
+        };
+        let report = RunReport {
+            target: "API".to_string(),
+            data_file: None,
+            iterations: vec![IterationReport {
+                iteration: 1,
+                variables: DataRow::new(),
+                results: vec![step(Some(200), None), step(Some(500), None), step(None, Some("x"))],
+            }],
+            cancelled: false,
+        };
+        assert_eq!(report.totals(), (1, 2));
+    }
+
+    #[test]
+    fn test_insert_ordered_keeps_run_order() {
+        let step = |id: &str| StepResult {
+            request_id: id.to_string(),
+            name: id.to_string(),
+            method: "GET".to_string(),
+            status: Some(200),
+            duration_ms: 1,
+            error: None,
+            assertions: Vec::new(),
+            file: None,
+        };
+        let order = ["a", "b", "c", "d"];
+        let rank = |id: &str| order.iter().position(|o| *o == id).unwrap_or(order.len());
+        let mut iteration = IterationReport {
+            iteration: 1,
+            variables: DataRow::new(),
+            results: Vec::new(),
+        };
+        // Finished as c, a, d, b
+        for id in ["c", "a", "d", "b"] {
+            iteration.insert_ordered(step(id), rank);
+        }
+        let names: Vec<&str> = iteration.results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, order);
+    }
+
+    #[test]
+    fn test_export_file_stem() {
+        assert_eq!(export_file_stem("Get user"), "Get user");
+        assert_eq!(export_file_stem("GET /users/{{id}}"), "GET _users_{{id}}");
+        assert_eq!(export_file_stem("a:b*c?\"d\"<e>|f\\g"), "a_b_c__d__e__f_g");
+        assert_eq!(export_file_stem(" ..hidden. "), "hidden");
+        assert_eq!(export_file_stem("tab\there"), "tab_here");
+        assert_eq!(export_file_stem("//"), "__");
+        assert_eq!(export_file_stem("..."), "request");
+    }
+
+    #[test]
+    fn test_export_writes_bodies_headers_and_summary() {
+        let root = std::env::temp_dir().join(format!("perseus-run-{}", uuid::Uuid::new_v4()));
+        let started = SystemTime::UNIX_EPOCH;
+        let mut export = RunExport::create(&root, started).unwrap();
+        assert_eq!(export.dir, root.join("runs").join("1970-01-01T00-00-00.000Z"));
+        // A second run in the same millisecond gets its own directory
+        let again = RunExport::create(&root, started).unwrap();
+        assert!(again.dir.ends_with("1970-01-01T00-00-00.000Z-2"));
+
+        let headers = vec![("content-type".to_string(), "application/json".to_string())];
+        let file = export.write_response("Users/List", b"[]", &headers).unwrap();
+        assert_eq!(file, "Users_List.json");
+        assert_eq!(export.write_response("users/list", b"{}", &[]).unwrap(), "users_list-2.json");
+        assert_eq!(export.write_response("Summary", b"", &[]).unwrap(), "Summary-2.json");
+        assert_eq!(fs::read(export.dir.join("Users_List.json")).unwrap(), b"[]");
+        assert_eq!(
+            fs::read_to_string(export.dir.join("Users_List.headers.txt")).unwrap(),
+            "content-type: application/json\n"
+        );
+
+        let report = RunReport {
+            target: "API".to_string(),
+            data_file: None,
+            iterations: Vec::new(),
+            cancelled: true,
+        };
+        export.write_summary(&report).unwrap();
+        let summary = fs::read_to_string(export.dir.join("summary.json")).unwrap();
+        assert!(summary.contains("\"cancelled\": true"), "{}", summary);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,607 @@
+//! JSON Schema (draft-07) validation of responses against the schema a
+//! request has attached. Every validation keyword of the draft is checked
+//! except `pattern`, `patternProperties` and `format`, which would need a
+//! regex engine; `$ref` resolves within the schema document.
+
+use serde_json::{Map, Value};
+
+use crate::json_path::{self, Segment};
+
+/// How deep `$ref`s and subschemas may nest before validation gives up,
+/// which also stops a schema that refers to itself without consuming any
+/// of the instance.
+const MAX_DEPTH: usize = 64;
+
+const TYPE_NAMES: &[&str] = &["null", "boolean", "object", "array", "number", "string", "integer"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// Where in the response, as `$.data[0].id`.
+    pub path: String,
+    pub message: String,
+}
+
+/// Outcome of validating a response against its request's schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaCheck {
+    Passed,
+    Failed(Vec<Violation>),
+    /// The schema couldn't be read or isn't a usable schema; nothing was
+    /// validated.
+    SchemaError(String),
+}
+
+impl SchemaCheck {
+    pub fn passed(&self) -> bool {
+        matches!(self, SchemaCheck::Passed)
+    }
+}
+
+/// Parses schema text and checks it is usable, so problems with the schema
+/// are reported as such rather than as violations.
+pub fn parse_schema(text: &str) -> Result<Value, String> {
+    let schema: Value =
+        serde_json::from_str(text).map_err(|e| format!("Schema is not valid JSON: {}", e))?;
+    check_schema(&schema)?;
+    Ok(schema)
+}
+
+/// Checks that `schema` and its subschemas are objects or booleans, that
+/// keywords have the types the draft requires, and that every `$ref`
+/// resolves.
+pub fn check_schema(schema: &Value) -> Result<(), String> {
+    check_subschema(schema, schema, "#")
+}
+
+fn check_subschema(root: &Value, schema: &Value, at: &str) -> Result<(), String> {
+    let object = match schema {
+        Value::Bool(_) => return Ok(()),
+        Value::Object(object) => object,
+        _ => return Err(format!("{} is not a schema (expected an object or boolean)", at)),
+    };
+    let wrong = |keyword: &str, expected: &str| {
+        Err(format!("\"{}\" at {} must be {}", keyword, at, expected))
+    };
+    for (keyword, value) in object {
+        let nested = format!("{}/{}", at, keyword);
+        match keyword.as_str() {
+            "$ref" => {
+                let Some(reference) = value.as_str() else {
+                    return wrong(keyword, "a string");
+                };
+                if resolve_ref(root, reference).is_none() {
+                    return Err(format!(
+                        "\"$ref\" at {} points to {}, which isn't in this schema",
+                        at, reference
+                    ));
+                }
+            }
+            "type" => {
+                let names: Vec<&Value> = match value {
+                    Value::Array(names) => names.iter().collect(),
+                    name => vec![name],
+                };
+                let known = |name: &&Value| name.as_str().is_some_and(|n| TYPE_NAMES.contains(&n));
+                if names.is_empty() || !names.iter().all(known) {
+                    return wrong(keyword, "a type name or a list of them");
+                }
+            }
+            "enum" if !value.is_array() => return wrong(keyword, "an array"),
+            "required" => {
+                let names = value.as_array().filter(|names| names.iter().all(Value::is_string));
+                if names.is_none() {
+                    return wrong(keyword, "an array of property names");
+                }
+            }
+            "multipleOf" if !value.as_f64().is_some_and(|n| n > 0.0) => {
+                return wrong(keyword, "a number above 0");
+            }
+            "maximum" | "exclusiveMaximum" | "minimum" | "exclusiveMinimum"
+                if !value.is_number() =>
+            {
+                return wrong(keyword, "a number");
+            }
+            "maxLength" | "minLength" | "maxItems" | "minItems" | "maxProperties"
+            | "minProperties"
+                if !value.is_u64() =>
+            {
+                return wrong(keyword, "a whole number");
+            }
+            "uniqueItems" if !value.is_boolean() => return wrong(keyword, "true or false"),
+            "items" => match value {
+                Value::Array(schemas) => {
+                    for (i, item) in schemas.iter().enumerate() {
+                        check_subschema(root, item, &format!("{}/{}", nested, i))?;
+                    }
+                }
+                schema => check_subschema(root, schema, &nested)?,
+            },
+            "allOf" | "anyOf" | "oneOf" => {
+                let Some(schemas) = value.as_array().filter(|schemas| !schemas.is_empty()) else {
+                    return wrong(keyword, "a non-empty array of schemas");
+                };
+                for (i, item) in schemas.iter().enumerate() {
+                    check_subschema(root, item, &format!("{}/{}", nested, i))?;
+                }
+            }
+            "properties" | "definitions" | "patternProperties" => {
+                let Some(schemas) = value.as_object() else {
+                    return wrong(keyword, "an object of schemas");
+                };
+                for (name, item) in schemas {
+                    check_subschema(root, item, &format!("{}/{}", nested, name))?;
+                }
+            }
+            "dependencies" => {
+                let Some(dependencies) = value.as_object() else {
+                    return wrong(keyword, "an object");
+                };
+                for (name, item) in dependencies {
+                    if !item.is_array() {
+                        check_subschema(root, item, &format!("{}/{}", nested, name))?;
+                    }
+                }
+            }
+            "additionalItems" | "additionalProperties" | "contains" | "propertyNames" | "not"
+            | "if" | "then" | "else" => check_subschema(root, value, &nested)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// The subschema a `$ref` within the document points to: `#` or a JSON
+/// pointer such as `#/definitions/user`.
+fn resolve_ref<'a>(root: &'a Value, reference: &str) -> Option<&'a Value> {
+    let pointer = reference.strip_prefix('#')?;
+    if pointer.is_empty() {
+        return Some(root);
+    }
+    root.pointer(pointer)
+}
+
+/// Every violation of `schema` in `instance`, in document order. `schema`
+/// should have passed [`check_schema`].
+pub fn validate(schema: &Value, instance: &Value) -> Vec<Violation> {
+    let validator = Validator { root: schema };
+    let mut violations = Vec::new();
+    validator.validate(schema, instance, &mut Vec::new(), 0, &mut violations);
+    violations
+}
+
+struct Validator<'a> {
+    root: &'a Value,
+}
+
+impl Validator<'_> {
+    fn is_valid(
+        &self,
+        schema: &Value,
+        instance: &Value,
+        path: &mut Vec<Segment>,
+        depth: usize,
+    ) -> bool {
+        let mut violations = Vec::new();
+        self.validate(schema, instance, path, depth, &mut violations);
+        violations.is_empty()
+    }
+
+    fn validate(
+        &self,
+        schema: &Value,
+        instance: &Value,
+        path: &mut Vec<Segment>,
+        depth: usize,
+        out: &mut Vec<Violation>,
+    ) {
+        let mut fail = |message: String| {
+            out.push(Violation {
+                path: json_path::format_path(path),
+                message,
+            })
+        };
+        if depth > MAX_DEPTH {
+            fail("the schema nests too deeply to check".to_string());
+            return;
+        }
+        let schema = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => {
+                fail("no value is allowed here".to_string());
+                return;
+            }
+            Value::Object(schema) => schema,
+            _ => return,
+        };
+        // In draft-07 a $ref replaces the rest of the schema it sits in
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            match resolve_ref(self.root, reference) {
+                Some(target) => self.validate(target, instance, path, depth + 1, out),
+                None => fail(format!("{} isn't in this schema", reference)),
+            }
+            return;
+        }
+
+        if let Some(expected) = schema.get("type") {
+            let names: Vec<&str> = match expected {
+                Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+                name => name.as_str().into_iter().collect(),
+            };
+            if !names.iter().any(|name| has_type(instance, name)) {
+                fail(format!("expected {}, got {}", names.join(" or "), type_name(instance)));
+            }
+        }
+        if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+            if !allowed.iter().any(|value| json_equal(value, instance)) {
+                let listed: Vec<String> = allowed.iter().map(Value::to_string).collect();
+                fail(format!("must be one of {}", listed.join(", ")));
+            }
+        }
+        if let Some(expected) = schema.get("const") {
+            if !json_equal(expected, instance) {
+                fail(format!("must be {}", expected));
+            }
+        }
+
+        match instance {
+            Value::Number(number) => {
+                let n = number.as_f64().unwrap_or_default();
+                let bound = |keyword: &str| schema.get(keyword).and_then(Value::as_f64);
+                if let Some(divisor) = bound("multipleOf") {
+                    let quotient = n / divisor;
+                    if (quotient - quotient.round()).abs() > 1e-9 {
+                        fail(format!("must be a multiple of {}", divisor));
+                    }
+                }
+                if let Some(max) = bound("maximum").filter(|max| n > *max) {
+                    fail(format!("must be at most {}", max));
+                }
+                if let Some(max) = bound("exclusiveMaximum").filter(|max| n >= *max) {
+                    fail(format!("must be below {}", max));
+                }
+                if let Some(min) = bound("minimum").filter(|min| n < *min) {
+                    fail(format!("must be at least {}", min));
+                }
+                if let Some(min) = bound("exclusiveMinimum").filter(|min| n <= *min) {
+                    fail(format!("must be above {}", min));
+                }
+            }
+            Value::String(text) => {
+                let len = text.chars().count() as u64;
+                if let Some(max) = limit(schema, "maxLength").filter(|max| len > *max) {
+                    fail(format!("must be at most {} characters long", max));
+                }
+                if let Some(min) = limit(schema, "minLength").filter(|min| len < *min) {
+                    fail(format!("must be at least {} characters long", min));
+                }
+            }
+            Value::Array(items) => {
+                self.validate_array(schema, items, path, depth, out);
+                return self.validate_combinators(schema, instance, path, depth, out);
+            }
+            Value::Object(object) => {
+                self.validate_object(schema, object, path, depth, out);
+                return self.validate_combinators(schema, instance, path, depth, out);
+            }
+            _ => {}
+        }
+        self.validate_combinators(schema, instance, path, depth, out);
+    }
+
+    fn validate_array(
+        &self,
+        schema: &Map<String, Value>,
+        items: &[Value],
+        path: &mut Vec<Segment>,
+        depth: usize,
+        out: &mut Vec<Violation>,
+    ) {
+        let len = items.len() as u64;
+        if let Some(max) = limit(schema, "maxItems").filter(|max| len > *max) {
+            push(out, path, format!("must have at most {} items", max));
+        }
+        if let Some(min) = limit(schema, "minItems").filter(|min| len < *min) {
+            push(out, path, format!("must have at least {} items", min));
+        }
+        if schema.get("uniqueItems") == Some(&Value::Bool(true)) {
+            let repeated = (1..items.len())
+                .find(|&i| items[..i].iter().any(|earlier| json_equal(earlier, &items[i])));
+            if let Some(i) = repeated {
+                push(out, path, format!("items must be unique, but item {} repeats one", i));
+            }
+        }
+        match schema.get("items") {
+            Some(Value::Array(schemas)) => {
+                let additional = schema.get("additionalItems");
+                for (i, item) in items.iter().enumerate() {
+                    let Some(item_schema) = schemas.get(i).or(additional) else {
+                        break;
+                    };
+                    path.push(Segment::Index(i));
+                    self.validate(item_schema, item, path, depth + 1, out);
+                    path.pop();
+                }
+            }
+            Some(item_schema) => {
+                for (i, item) in items.iter().enumerate() {
+                    path.push(Segment::Index(i));
+                    self.validate(item_schema, item, path, depth + 1, out);
+                    path.pop();
+                }
+            }
+            None => {}
+        }
+        if let Some(contains) = schema.get("contains") {
+            let found = items.iter().enumerate().any(|(i, item)| {
+                path.push(Segment::Index(i));
+                let valid = self.is_valid(contains, item, path, depth + 1);
+                path.pop();
+                valid
+            });
+            if !found {
+                push(out, path, "no item matches \"contains\"".to_string());
+            }
+        }
+    }
+
+    fn validate_object(
+        &self,
+        schema: &Map<String, Value>,
+        object: &Map<String, Value>,
+        path: &mut Vec<Segment>,
+        depth: usize,
+        out: &mut Vec<Violation>,
+    ) {
+        let len = object.len() as u64;
+        if let Some(max) = limit(schema, "maxProperties").filter(|max| len > *max) {
+            push(out, path, format!("must have at most {} properties", max));
+        }
+        if let Some(min) = limit(schema, "minProperties").filter(|min| len < *min) {
+            push(out, path, format!("must have at least {} properties", min));
+        }
+        for name in schema.get("required").and_then(Value::as_array).into_iter().flatten() {
+            if let Some(name) = name.as_str().filter(|name| !object.contains_key(*name)) {
+                push(out, path, format!("missing required property \"{}\"", name));
+            }
+        }
+        let properties = schema.get("properties").and_then(Value::as_object);
+        // Without a regex engine there's no telling which names
+        // patternProperties covers, so additionalProperties can't be judged
+        let additional = schema
+            .get("additionalProperties")
+            .filter(|_| !schema.contains_key("patternProperties"));
+        for (name, value) in object {
+            let property_schema = properties.and_then(|properties| properties.get(name));
+            path.push(Segment::Key(name.clone()));
+            match (property_schema, additional) {
+                (Some(property_schema), _) => {
+                    self.validate(property_schema, value, path, depth + 1, out)
+                }
+                (None, Some(Value::Bool(false))) => {
+                    path.pop();
+                    push(out, path, format!("property \"{}\" is not allowed", name));
+                    continue;
+                }
+                (None, Some(additional)) => self.validate(additional, value, path, depth + 1, out),
+                (None, None) => {}
+            }
+            path.pop();
+        }
+        if let Some(names) = schema.get("propertyNames") {
+            for name in object.keys() {
+                if !self.is_valid(names, &Value::String(name.clone()), path, depth + 1) {
+                    push(out, path, format!("property name \"{}\" is not allowed", name));
+                }
+            }
+        }
+        let dependencies = schema.get("dependencies").and_then(Value::as_object);
+        for (name, dependency) in dependencies.into_iter().flatten() {
+            if !object.contains_key(name) {
+                continue;
+            }
+            match dependency {
+                Value::Array(needed) => {
+                    for other in needed.iter().filter_map(Value::as_str) {
+                        if !object.contains_key(other) {
+                            push(
+                                out,
+                                path,
+                                format!("property \"{}\" requires \"{}\"", name, other),
+                            );
+                        }
+                    }
+                }
+                dependency => {
+                    let instance = Value::Object(object.clone());
+                    self.validate(dependency, &instance, path, depth + 1, out);
+                }
+            }
+        }
+    }
+
+    fn validate_combinators(
+        &self,
+        schema: &Map<String, Value>,
+        instance: &Value,
+        path: &mut Vec<Segment>,
+        depth: usize,
+        out: &mut Vec<Violation>,
+    ) {
+        for sub in schema.get("allOf").and_then(Value::as_array).into_iter().flatten() {
+            self.validate(sub, instance, path, depth + 1, out);
+        }
+        if let Some(schemas) = schema.get("anyOf").and_then(Value::as_array) {
+            if !schemas.iter().any(|sub| self.is_valid(sub, instance, path, depth + 1)) {
+                push(out, path, "matches none of the \"anyOf\" schemas".to_string());
+            }
+        }
+        if let Some(schemas) = schema.get("oneOf").and_then(Value::as_array) {
+            let matching = schemas
+                .iter()
+                .filter(|sub| self.is_valid(sub, instance, path, depth + 1))
+                .count();
+            if matching != 1 {
+                push(
+                    out,
+                    path,
+                    format!("must match exactly one \"oneOf\" schema, matches {}", matching),
+                );
+            }
+        }
+        if let Some(not) = schema.get("not") {
+            if self.is_valid(not, instance, path, depth + 1) {
+                push(out, path, "must not match the \"not\" schema".to_string());
+            }
+        }
+        if let Some(condition) = schema.get("if") {
+            let branch = if self.is_valid(condition, instance, path, depth + 1) {
+                schema.get("then")
+            } else {
+                schema.get("else")
+            };
+            if let Some(branch) = branch {
+                self.validate(branch, instance, path, depth + 1, out);
+            }
+        }
+    }
+}
+
+fn push(out: &mut Vec<Violation>, path: &[Segment], message: String) {
+    out.push(Violation {
+        path: json_path::format_path(path),
+        message,
+    });
+}
+
+fn limit(schema: &Map<String, Value>, keyword: &str) -> Option<u64> {
+    schema.get(keyword).and_then(Value::as_u64)
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match (name, value) {
+        ("integer", Value::Number(n)) => {
+            n.is_i64() || n.is_u64() || n.as_f64().is_some_and(|f| f.fract() == 0.0)
+        }
+        ("number", Value::Number(_)) => true,
+        _ => type_name(value) == name,
+    }
+}
+
+/// Equality as the draft defines it: numbers compare by value, so `1` and
+/// `1.0` are equal.
+fn json_equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| json_equal(a, b))
+        }
+        (Value::Object(a), Value::Object(b)) => {
+            a.len() == b.len()
+                && a.iter().all(|(key, value)| {
+                    b.get(key).is_some_and(|other| json_equal(value, other))
+                })
+        }
+        _ => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn violations(schema: Value, instance: Value) -> Vec<(String, String)> {
+        check_schema(&schema).unwrap();
+        validate(&schema, &instance)
+            .into_iter()
+            .map(|v| (v.path, v.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_object_keywords_report_paths() {
+        let schema = json!({
+            "type": "object",
+            "required": ["id", "name"],
+            "properties": {
+                "id": {"type": "integer", "minimum": 1},
+                "name": {"type": "string"},
+                "tags": {"type": "array", "items": {"type": "string"}, "uniqueItems": true}
+            },
+            "additionalProperties": false
+        });
+        let found = violations(schema.clone(), json!({"id": 0, "tags": ["a", 2, "a"], "x": 1}));
+        assert_eq!(
+            found,
+            vec![
+                ("$".to_string(), "missing required property \"name\"".to_string()),
+                ("$.id".to_string(), "must be at least 1".to_string()),
+                ("$.tags".to_string(), "items must be unique, but item 2 repeats one".to_string()),
+                ("$.tags[1]".to_string(), "expected string, got integer".to_string()),
+                ("$".to_string(), "property \"x\" is not allowed".to_string()),
+            ]
+        );
+        assert!(violations(schema, json!({"id": 3, "name": "n", "tags": []})).is_empty());
+    }
+
+    #[test]
+    fn test_refs_and_combinators() {
+        let schema = json!({
+            "definitions": {
+                "node": {
+                    "type": "object",
+                    "properties": {
+                        "children": {"type": "array", "items": {"$ref": "#/definitions/node"}}
+                    }
+                }
+            },
+            "oneOf": [{"$ref": "#/definitions/node"}, {"type": "null"}],
+            "not": {"const": {"children": []}}
+        });
+        assert!(violations(schema.clone(), json!({"children": [{"children": []}]})).is_empty());
+        assert!(violations(schema.clone(), Value::Null).is_empty());
+        let found = violations(schema.clone(), json!({"children": []}));
+        assert_eq!(found, vec![("$".to_string(), "must not match the \"not\" schema".to_string())]);
+        let found = violations(schema, json!(4));
+        assert_eq!(found[0].1, "must match exactly one \"oneOf\" schema, matches 0");
+
+        let schema = json!({
+            "if": {"properties": {"kind": {"const": "card"}}},
+            "then": {"required": ["last4"]},
+            "else": {"required": ["iban"]}
+        });
+        assert_eq!(violations(schema.clone(), json!({"kind": "card"})).len(), 1);
+        assert!(violations(schema, json!({"kind": "bank", "iban": "x"})).is_empty());
+    }
+
+    #[test]
+    fn test_numbers_compare_by_value() {
+        assert!(violations(json!({"type": "integer", "enum": [1, 2]}), json!(2.0)).is_empty());
+        assert!(violations(json!({"multipleOf": 0.1}), json!(0.3)).is_empty());
+        let found = violations(json!({"exclusiveMaximum": 10}), json!(10));
+        assert_eq!(found[0].1, "must be below 10");
+    }
+
+    #[test]
+    fn test_schema_errors_are_reported_separately() {
+        assert!(parse_schema("{").unwrap_err().contains("not valid JSON"));
+        let err = parse_schema(r#"{"properties": {"id": {"type": "int"}}}"#).unwrap_err();
+        assert!(err.contains("#/properties/id"), "{}", err);
+        let err = parse_schema(r##"{"items": {"$ref": "#/definitions/missing"}}"##).unwrap_err();
+        assert!(err.contains("#/definitions/missing"), "{}", err);
+        assert!(parse_schema("[]").is_err());
+        assert!(parse_schema("true").is_ok());
+    }
+}
@@ -0,0 +1,217 @@
+//! The opt-in execution log (`log.file`): one JSON line per send, for
+//! auditing what was sent where. Secret values never reach the file, and
+//! the file is rotated by size. Writing it is best effort; the caller
+//! reports failures instead of failing the request.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::storage::environment::SECRET_MASK;
+
+/// Headers whose values are masked whatever they hold.
+const MASKED_HEADERS: &[&str] = &["authorization", "proxy-authorization"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SendRecord {
+    /// When the request was sent, as RFC 3339 in UTC.
+    pub timestamp: String,
+    pub project: String,
+    /// Folder path and name of the request in the sidebar.
+    pub request: String,
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    /// Auth type added at send time ("Bearer Token", "Basic Auth", …), if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// Why no response came back.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub duration_ms: u64,
+    /// Response body bytes received, as sent over the wire.
+    pub bytes: usize,
+}
+
+/// Replaces every occurrence of the `secrets` in `text`, longest first so a
+/// secret containing another is masked whole.
+pub fn redact(text: &str, secrets: &[String]) -> String {
+    let mut secrets: Vec<&String> = secrets.iter().filter(|s| !s.is_empty()).collect();
+    secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+    let mut text = text.to_string();
+    for secret in secrets {
+        text = text.replace(secret.as_str(), SECRET_MASK);
+    }
+    text
+}
+
+/// `headers` as logged: secrets redacted, and authorization headers masked
+/// outright.
+pub fn redact_headers(headers: &[(String, String)], secrets: &[String]) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(key, value)| {
+            let value = if MASKED_HEADERS.contains(&key.to_ascii_lowercase().as_str()) {
+                SECRET_MASK.to_string()
+            } else {
+                redact(value, secrets)
+            };
+            (key.clone(), value)
+        })
+        .collect()
+}
+
+/// Appends `record` to the log at `path`, first rotating the file when the
+/// line would take it past `max_bytes`. `keep` rotated files are kept as
+/// `path.1` (newest) to `path.<keep>`.
+pub fn append(path: &Path, max_bytes: u64, keep: u32, record: &SendRecord) -> Result<(), String> {
+    let mut line = serde_json::to_string(record)
+        .map_err(|e| format!("Failed to serialize log entry: {}", e))?;
+    line.push('\n');
+    append_rotated(path, max_bytes, keep, &line)
+}
+
+/// Appends `text` to the file at `path`, rotating it first as [`append`]
+/// does.
+pub fn append_rotated(path: &Path, max_bytes: u64, keep: u32, text: &str) -> Result<(), String> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let size = fs::metadata(path).map(|meta| meta.len()).unwrap_or(0);
+    if size > 0 && size + text.len() as u64 > max_bytes {
+        rotate(path, keep)?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    file.write_all(text.as_bytes())
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn rotate(path: &Path, keep: u32) -> Result<(), String> {
+    let failed = |e: std::io::Error| format!("Failed to rotate {}: {}", path.display(), e);
+    if keep == 0 {
+        return fs::remove_file(path).map_err(failed);
+    }
+    let _ = fs::remove_file(rotated(path, keep));
+    for n in (1..keep).rev() {
+        let from = rotated(path, n);
+        if from.exists() {
+            fs::rename(&from, rotated(path, n + 1)).map_err(failed)?;
+        }
+    }
+    fs::rename(path, rotated(path, 1)).map_err(failed)
+}
+
+/// `path.<n>`, the n-th newest rotated file.
+pub fn rotated(path: &Path, n: u32) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+/// `time` as `2024-05-01T12:30:05.123Z`.
+pub fn timestamp(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let rest = secs % 86_400;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        rest / 3600,
+        rest % 3600 / 60,
+        rest % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+/// The proleptic Gregorian date `days` after 1970-01-01, after Howard
+/// Hinnant's `civil_from_days`.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn record(url: &str) -> SendRecord {
+        SendRecord {
+            timestamp: timestamp(UNIX_EPOCH),
+            project: "API".to_string(),
+            request: "Users/List users".to_string(),
+            method: "GET".to_string(),
+            url: url.to_string(),
+            headers: Vec::new(),
+            auth: None,
+            status: Some(200),
+            error: None,
+            duration_ms: 12,
+            bytes: 42,
+        }
+    }
+
+    #[test]
+    fn test_timestamp() {
+        assert_eq!(timestamp(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+        let leap_day = UNIX_EPOCH + Duration::from_millis(1_709_210_096_789);
+        assert_eq!(timestamp(leap_day), "2024-02-29T12:34:56.789Z");
+    }
+
+    #[test]
+    fn test_redaction() {
+        let secrets = vec!["s3cret".to_string(), "s3cret-long".to_string(), String::new()];
+        assert_eq!(
+            redact("https://api.test/?key=s3cret-long&other=s3cret", &secrets),
+            format!("https://api.test/?key={}&other={}", SECRET_MASK, SECRET_MASK)
+        );
+        let headers = vec![
+            ("Authorization".to_string(), "Bearer abc".to_string()),
+            ("X-Api-Key".to_string(), "s3cret".to_string()),
+            ("Accept".to_string(), "*/*".to_string()),
+        ];
+        let logged = redact_headers(&headers, &secrets);
+        assert_eq!(logged[0].1, SECRET_MASK);
+        assert_eq!(logged[1].1, SECRET_MASK);
+        assert_eq!(logged[2].1, "*/*");
+    }
+
+    #[test]
+    fn test_append_rotates_by_size() {
+        let dir = std::env::temp_dir().join(format!("perseus-sendlog-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("sends.log");
+        let line_len = serde_json::to_string(&record("https://a.test/1")).unwrap().len() as u64 + 1;
+        for i in 0..5 {
+            append(&path, line_len * 2, 2, &record(&format!("https://a.test/{}", i))).unwrap();
+        }
+        let read = |path: &Path| fs::read_to_string(path).unwrap_or_default();
+        assert_eq!(read(&path).lines().count(), 1);
+        assert!(read(&path).contains("https://a.test/4"));
+        assert!(read(&rotated(&path, 1)).contains("https://a.test/3"));
+        assert!(read(&rotated(&path, 2)).contains("https://a.test/1"));
+        assert!(!rotated(&path, 3).exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,220 @@
+//! Body snippets (`Ctrl+Space` in insert mode on the body): named templates
+//! with `$1`, `$2`… tab stops. A few are built in; `.perseus/snippets.toml`
+//! adds more and replaces built-ins of the same name:
+//!
+//! ```toml
+//! [envelope]
+//! description = "Data with a meta block"
+//! body = '''
+//! {"data": $1, "meta": {"requestId": "$2"}}'''
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Deserialize;
+
+pub const SNIPPETS_FILE_NAME: &str = "snippets.toml";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snippet {
+    pub name: String,
+    pub description: String,
+    pub body: String,
+    /// Defined in the project's file rather than built in.
+    pub project: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SnippetEntry {
+    #[serde(default)]
+    description: String,
+    body: String,
+}
+
+const BUILT_IN: [(&str, &str, &str); 5] = [
+    (
+        "page",
+        "Pagination envelope",
+        "{\n  \"data\": [$1],\n  \"page\": ${2},\n  \"perPage\": $3,\n  \"total\": $4\n}",
+    ),
+    (
+        "jsonapi",
+        "JSON:API resource",
+        concat!(
+            "{\n  \"data\": {\n    \"type\": \"$1\",\n    \"id\": \"$2\",\n",
+            "    \"attributes\": {\n      $3\n    }\n  }\n}",
+        ),
+    ),
+    (
+        "graphql",
+        "GraphQL query with variables",
+        "{\n  \"query\": \"query $1 { $2 }\",\n  \"variables\": {$3}\n}",
+    ),
+    (
+        "patch",
+        "JSON Patch operation",
+        "[\n  { \"op\": \"${1}\", \"path\": \"$2\", \"value\": $3 }\n]",
+    ),
+    ("object", "Object with one member", "{\n  \"$1\": $2\n}$0"),
+];
+
+/// The built-in snippets, then the project's from `path`, which replace
+/// built-ins of the same name. A file that doesn't parse leaves the
+/// built-ins, with the reason.
+pub fn load_snippets(path: Option<&Path>) -> (Vec<Snippet>, Option<String>) {
+    let mut snippets: Vec<Snippet> = BUILT_IN
+        .iter()
+        .map(|(name, description, body)| Snippet {
+            name: name.to_string(),
+            description: description.to_string(),
+            body: body.to_string(),
+            project: false,
+        })
+        .collect();
+    let Some(path) = path.filter(|path| path.exists()) else {
+        return (snippets, None);
+    };
+    let parsed = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+        .and_then(|text| {
+            toml::from_str::<BTreeMap<String, SnippetEntry>>(&text)
+                .map_err(|e| format!("{}: {}", path.display(), e.message()))
+        });
+    match parsed {
+        Ok(entries) => {
+            for (name, entry) in entries {
+                let snippet = Snippet {
+                    name,
+                    description: entry.description,
+                    body: entry.body,
+                    project: true,
+                };
+                match snippets.iter_mut().find(|s| s.name == snippet.name) {
+                    Some(existing) => *existing = snippet,
+                    None => snippets.push(snippet),
+                }
+            }
+            (snippets, None)
+        }
+        Err(err) => (snippets, Some(err)),
+    }
+}
+
+/// Snippets whose name or description contains `query`, ignoring case.
+pub fn filter<'a>(snippets: &'a [Snippet], query: &str) -> Vec<&'a Snippet> {
+    let query = query.trim().to_lowercase();
+    snippets
+        .iter()
+        .filter(|snippet| {
+            snippet.name.to_lowercase().contains(&query)
+                || snippet.description.to_lowercase().contains(&query)
+        })
+        .collect()
+}
+
+/// A snippet body as inserted: the text without its tab stops, and where
+/// the stops were as `(row, col)` in chars, in the order Tab visits them.
+/// `$1` and `${1}` come first, `$0` (the final cursor) last; `$$` is a
+/// literal `$`, and a `$` not followed by a number stays as it is.
+pub fn expand(body: &str) -> (String, Vec<(usize, usize)>) {
+    let mut text = String::with_capacity(body.len());
+    let mut stops: Vec<(u32, (usize, usize))> = Vec::new();
+    let (mut row, mut col) = (0, 0);
+    let mut chars = body.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '$' {
+            if chars.peek() == Some(&'$') {
+                chars.next();
+                text.push('$');
+                col += 1;
+                continue;
+            }
+            let braced = chars.peek() == Some(&'{');
+            let mut rest = chars.clone();
+            if braced {
+                rest.next();
+            }
+            let digits: String =
+                std::iter::from_fn(|| rest.next_if(char::is_ascii_digit)).collect();
+            let closed = !braced || rest.next_if_eq(&'}').is_some();
+            if let Some(number) = digits.parse::<u32>().ok().filter(|_| closed) {
+                chars = rest;
+                if !stops.iter().any(|(n, _)| *n == number) {
+                    stops.push((number, (row, col)));
+                }
+                continue;
+            }
+        }
+        text.push(c);
+        if c == '\n' {
+            row += 1;
+            col = 0;
+        } else {
+            col += 1;
+        }
+    }
+    // $0 sorts after every numbered stop
+    stops.sort_by_key(|(number, _)| if *number == 0 { u32::MAX } else { *number });
+    (text, stops.into_iter().map(|(_, at)| at).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_tab_stops() {
+        let (text, stops) = expand("{\"a\": $2,\n  \"b\": \"${1}\"}$0 $$5 $x");
+        assert_eq!(text, "{\"a\": ,\n  \"b\": \"\"} $5 $x");
+        assert_eq!(stops, vec![(1, 8), (0, 6), (1, 10)]);
+
+        // A repeated stop is visited once, where it first appears
+        let (text, stops) = expand("$1-$1 ${2");
+        assert_eq!(text, "- ${2");
+        assert_eq!(stops, vec![(0, 0)]);
+    }
+
+    #[test]
+    fn test_built_ins_expand() {
+        let (snippets, err) = load_snippets(None);
+        assert!(err.is_none());
+        for snippet in &snippets {
+            let (text, stops) = expand(&snippet.body);
+            assert!(!stops.is_empty(), "{}", snippet.name);
+            assert!(!text.contains("$1"), "{}", snippet.name);
+        }
+    }
+
+    #[test]
+    fn test_project_snippets_extend_and_override() {
+        let dir = std::env::temp_dir().join(format!("perseus-snippets-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(SNIPPETS_FILE_NAME);
+        std::fs::write(
+            &path,
+            "[page]\nbody = \"{\\\"cursor\\\": \\\"$1\\\"}\"\n\n\
+             [login]\ndescription = \"Credentials\"\nbody = '''\n{\"user\": \"$1\"}'''\n",
+        )
+        .unwrap();
+        let (snippets, err) = load_snippets(Some(&path));
+        assert!(err.is_none(), "{:?}", err);
+        let page = snippets.iter().find(|s| s.name == "page").unwrap();
+        assert_eq!(page.body, "{\"cursor\": \"$1\"}");
+        assert!(page.project);
+        assert_eq!(snippets.iter().filter(|s| s.name == "page").count(), 1);
+        let login = snippets.last().unwrap();
+        assert_eq!((login.name.as_str(), login.body.as_str()), ("login", "{\"user\": \"$1\"}"));
+
+        let found: Vec<&str> = filter(&snippets, "CRED").iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(found, vec!["login"]);
+        assert_eq!(filter(&snippets, "").len(), snippets.len());
+
+        std::fs::write(&path, "[broken]\ndescription = \"no body\"\n").unwrap();
+        let (snippets, err) = load_snippets(Some(&path));
+        assert!(err.unwrap().contains("body"));
+        assert_eq!(snippets.len(), BUILT_IN.len());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,707 @@
+//! Project archives: one project of the collection packed into a zip with
+//! its request files, the project environments and the session state, so a
+//! workspace can move between machines. Imports get fresh ids throughout.
+//! A plain Postman collection can be imported the same way, as a project
+//! with no environments or session.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::storage::collection::{CollectionStore, RequestFile};
+use crate::storage::environment::{self, Environment};
+use crate::storage::postman::{new_id, PostmanCollection, PostmanItem};
+use crate::storage::project::{ensure_environments_dir, environments_dir, requests_dir};
+use crate::storage::session_state::{ProjectSidebarState, SessionState};
+
+/// Bumped when the layout changes in a way older versions can't read.
+const ARCHIVE_FORMAT: u32 = 1;
+const MANIFEST_ENTRY: &str = "perseus-archive.json";
+const PROJECT_ENTRY: &str = "project.json";
+const SESSION_ENTRY: &str = "session.json";
+const REQUESTS_PREFIX: &str = "requests/";
+const ENVIRONMENTS_PREFIX: &str = "environments/";
+/// Entries larger than this are refused rather than read into memory.
+const MAX_ENTRY_SIZE: u64 = 64 * 1024 * 1024;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    format: u32,
+    project: String,
+    exported_with: String,
+}
+
+/// A validated archive, read whole before anything is written.
+#[derive(Debug, Clone)]
+pub struct ProjectArchive {
+    pub project: PostmanItem,
+    pub environments: Vec<Environment>,
+    pub session: Option<SessionState>,
+}
+
+/// What an import added to the current project root.
+#[derive(Debug, Clone)]
+pub struct ImportedProject {
+    pub project_id: Uuid,
+    pub name: String,
+    pub requests: usize,
+    pub environments: Vec<String>,
+    /// Archived environments left out because one with the name exists.
+    pub skipped_environments: Vec<String>,
+    /// The archived session, with ids pointing at the imported items.
+    pub session: Option<SessionState>,
+}
+
+/// Writes the project `project_id` to a zip at `path`, with every project
+/// environment and `session` narrowed to the project's items. Returns the
+/// number of requests archived.
+pub fn export_project_archive(
+    store: &CollectionStore,
+    project_id: Uuid,
+    session: Option<&SessionState>,
+    path: &Path,
+) -> Result<usize, String> {
+    let project = store.get_item(project_id).ok_or("Project not found")?;
+    let environments = match environments_dir() {
+        Some(dir) => environment::load_layered_environments(Some(&dir), None)?,
+        None => Vec::new(),
+    };
+    let file =
+        fs::File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let result = write_archive(project, &environments, session, file);
+    if result.is_err() {
+        let _ = fs::remove_file(path);
+    }
+    result
+}
+
+fn write_archive<W: Write + Seek>(
+    project: &PostmanItem,
+    environments: &[Environment],
+    session: Option<&SessionState>,
+    writer: W,
+) -> Result<usize, String> {
+    let mut zip = ZipWriter::new(writer);
+    let manifest = Manifest {
+        format: ARCHIVE_FORMAT,
+        project: project.name.clone(),
+        exported_with: format!("perseus {}", env!("CARGO_PKG_VERSION")),
+    };
+    write_json_entry(&mut zip, MANIFEST_ENTRY, &manifest)?;
+    let project = &shareable(project);
+    write_json_entry(&mut zip, PROJECT_ENTRY, project)?;
+
+    let files = request_files(project);
+    for file in &files {
+        write_json_entry(&mut zip, &format!("{}{}.json", REQUESTS_PREFIX, file.id), file)?;
+    }
+    for env in environments {
+        write_json_entry(&mut zip, &format!("{}{}.json", ENVIRONMENTS_PREFIX, env.name), env)?;
+    }
+    if let Some(session) = session {
+        let ids = item_ids(project);
+        write_json_entry(&mut zip, SESSION_ENTRY, &narrow_session(session, &ids))?;
+    }
+    zip.finish().map_err(|e| format!("Failed to finish archive: {}", e))?;
+    Ok(files.len())
+}
+
+/// `project` as it's archived: TLS certificate and key paths are left out,
+/// as they only make sense on this machine.
+fn shareable(project: &PostmanItem) -> PostmanItem {
+    let mut project = project.clone();
+    let mut stack = vec![&mut project];
+    while let Some(item) = stack.pop() {
+        item.settings.tls = item.settings.tls.without_paths();
+        stack.extend(item.item.iter_mut());
+    }
+    project
+}
+
+fn write_json_entry<W: Write + Seek, T: Serialize + ?Sized>(
+    zip: &mut ZipWriter<W>,
+    name: &str,
+    value: &T,
+) -> Result<(), String> {
+    let json = serde_json::to_vec_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", name, e))?;
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    zip.start_file(name, options)
+        .and_then(|()| zip.write_all(&json).map_err(Into::into))
+        .map_err(|e| format!("Failed to write {}: {}", name, e))
+}
+
+/// Reads and validates the archive at `path`. Nothing in it is trusted
+/// until every entry has parsed and the request files match the project.
+pub fn read_project_archive(path: &Path) -> Result<ProjectArchive, String> {
+    let file =
+        fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    parse_archive(file)
+}
+
+/// Reads the Postman collection (v2.0 or v2.1) at `path` as an archive of
+/// one project named after the collection.
+pub fn read_postman_collection(path: &Path) -> Result<ProjectArchive, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    parse_postman_collection(&contents)
+}
+
+fn parse_postman_collection(contents: &str) -> Result<ProjectArchive, String> {
+    let collection: PostmanCollection = serde_json::from_str(contents)
+        .map_err(|e| format!("Not a Postman collection: {}", e))?;
+    let name = collection.info.name.trim();
+    let mut project = PostmanItem::new_folder(if name.is_empty() {
+        "Imported collection".to_string()
+    } else {
+        name.to_string()
+    });
+    project.item = collection.item;
+    Ok(ProjectArchive {
+        project,
+        environments: Vec::new(),
+        session: None,
+    })
+}
+
+fn parse_archive<R: Read + Seek>(reader: R) -> Result<ProjectArchive, String> {
+    let mut zip = ZipArchive::new(reader).map_err(|e| format!("Not a project archive: {}", e))?;
+    let mut manifest: Option<Manifest> = None;
+    let mut project: Option<PostmanItem> = None;
+    let mut session: Option<SessionState> = None;
+    let mut request_files: Vec<RequestFile> = Vec::new();
+    let mut environments: Vec<Environment> = Vec::new();
+
+    for index in 0..zip.len() {
+        let mut entry = zip
+            .by_index(index)
+            .map_err(|e| format!("Failed to read archive entry: {}", e))?;
+        if entry.is_dir() {
+            continue;
+        }
+        let name = entry.name().to_string();
+        if entry.enclosed_name().is_none() {
+            return Err(format!("Archive entry '{}' has an unsafe path", name));
+        }
+        if entry.size() > MAX_ENTRY_SIZE {
+            return Err(format!("Archive entry '{}' is too large", name));
+        }
+        let mut bytes = Vec::new();
+        entry
+            .read_to_end(&mut bytes)
+            .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+
+        if name == MANIFEST_ENTRY {
+            manifest = Some(parse_entry(&name, &bytes)?);
+        } else if name == PROJECT_ENTRY {
+            project = Some(parse_entry(&name, &bytes)?);
+        } else if name == SESSION_ENTRY {
+            let mut parsed: SessionState = parse_entry(&name, &bytes)?;
+            parsed.migrate();
+            session = Some(parsed);
+        } else if let Some(file) = nested_json_name(&name, REQUESTS_PREFIX) {
+            let request: RequestFile = parse_entry(&name, &bytes)?;
+            if request.id != file {
+                return Err(format!("{} holds request {}", name, request.id));
+            }
+            request_files.push(request);
+        } else if let Some(file) = nested_json_name(&name, ENVIRONMENTS_PREFIX) {
+            let env: Environment = parse_entry(&name, &bytes)?;
+            if env.name != file || !environment::is_safe_env_name(&env.name) {
+                return Err(format!("{} has an invalid environment name", name));
+            }
+            environments.push(env);
+        } else {
+            return Err(format!("Unexpected archive entry '{}'", name));
+        }
+    }
+
+    let manifest = manifest.ok_or("Not a project archive: perseus-archive.json is missing")?;
+    if manifest.format != ARCHIVE_FORMAT {
+        return Err(format!(
+            "Unsupported archive format {} (expected {})",
+            manifest.format, ARCHIVE_FORMAT
+        ));
+    }
+    let project = project.ok_or("Archive has no project.json")?;
+    if project.is_request() {
+        return Err("project.json holds a request, not a project".to_string());
+    }
+    check_ids(&project)?;
+
+    let expected: HashSet<String> = request_files_ids(&project);
+    let found: HashSet<String> = request_files.iter().map(|file| file.id.clone()).collect();
+    if expected != found {
+        return Err("Request files in the archive don't match its project".to_string());
+    }
+    if let Some(file) = request_files.iter().find(|file| file.project_id != project.id) {
+        return Err(format!("Request file {} belongs to another project", file.id));
+    }
+
+    Ok(ProjectArchive {
+        project,
+        environments,
+        session,
+    })
+}
+
+fn parse_entry<T: for<'de> Deserialize<'de>>(name: &str, bytes: &[u8]) -> Result<T, String> {
+    serde_json::from_slice(bytes).map_err(|e| format!("Failed to parse {}: {}", name, e))
+}
+
+/// `<stem>` for an entry named `<prefix><stem>.json` directly under `prefix`.
+fn nested_json_name<'a>(name: &'a str, prefix: &str) -> Option<&'a str> {
+    let stem = name.strip_prefix(prefix)?.strip_suffix(".json")?;
+    (!stem.is_empty() && !stem.contains('/')).then_some(stem)
+}
+
+/// Every item needs a unique uuid for the request files and the session to
+/// refer to.
+fn check_ids(project: &PostmanItem) -> Result<(), String> {
+    let mut seen = HashSet::new();
+    let mut stack = vec![project];
+    while let Some(item) = stack.pop() {
+        if Uuid::parse_str(&item.id).is_err() || !seen.insert(item.id.as_str()) {
+            return Err(format!("Item '{}' has a missing or duplicate id", item.name));
+        }
+        stack.extend(item.item.iter());
+    }
+    Ok(())
+}
+
+impl ProjectArchive {
+    /// Gives the project and everything in it new ids, rewriting the session
+    /// to match, so importing never collides with items already present.
+    pub fn remap_ids(&mut self) {
+        let mut ids = HashMap::new();
+        remap_item(&mut self.project, &mut ids);
+        if let Some(session) = &mut self.session {
+            *session = remap_session(session, &ids);
+        }
+    }
+}
+
+fn remap_item(item: &mut PostmanItem, ids: &mut HashMap<String, String>) {
+    let id = new_id();
+    ids.insert(std::mem::replace(&mut item.id, id.clone()), id);
+    for child in &mut item.item {
+        remap_item(child, ids);
+    }
+}
+
+fn remap_session(session: &SessionState, ids: &HashMap<String, String>) -> SessionState {
+    let map = |id: &String| ids.get(id).cloned();
+    SessionState {
+        active_project_id: map(&session.active_project_id).unwrap_or_default(),
+        current_request_id: session.current_request_id.as_ref().and_then(map),
+        projects: session
+            .projects
+            .iter()
+            .filter_map(|(id, sidebar)| {
+                let sidebar = ProjectSidebarState {
+                    expanded: sidebar.expanded.iter().filter_map(map).collect(),
+                    selection_id: sidebar.selection_id.as_ref().and_then(map),
+                };
+                Some((map(id)?, sidebar))
+            })
+            .collect(),
+        positions: session
+            .positions
+            .iter()
+            .filter_map(|(id, position)| Some((map(id)?, position.clone())))
+            .collect(),
+        notes: session
+            .notes
+            .iter()
+            .filter_map(|(id, note)| Some((map(id)?, note.clone())))
+            .collect(),
+        ..session.clone()
+    }
+}
+
+/// The parts of `session` that refer to items in `ids`.
+fn narrow_session(session: &SessionState, ids: &HashSet<String>) -> SessionState {
+    let keep = |id: &String| ids.contains(id).then(|| id.clone());
+    SessionState {
+        active_project_id: keep(&session.active_project_id).unwrap_or_default(),
+        current_request_id: session.current_request_id.as_ref().and_then(keep),
+        projects: session
+            .projects
+            .iter()
+            .filter_map(|(id, sidebar)| {
+                let sidebar = ProjectSidebarState {
+                    expanded: sidebar.expanded.iter().filter_map(keep).collect(),
+                    selection_id: sidebar.selection_id.as_ref().and_then(keep),
+                };
+                Some((keep(id)?, sidebar))
+            })
+            .collect(),
+        positions: session
+            .positions
+            .iter()
+            .filter(|(id, _)| ids.contains(*id))
+            .map(|(id, position)| (id.clone(), position.clone()))
+            .collect(),
+        notes: session
+            .notes
+            .iter()
+            .filter(|(id, _)| ids.contains(*id))
+            .map(|(id, note)| (id.clone(), note.clone()))
+            .collect(),
+        // Send outcomes describe this machine's servers, not the project
+        last_runs: HashMap::new(),
+        send_history: HashMap::new(),
+        ..session.clone()
+    }
+}
+
+fn item_ids(project: &PostmanItem) -> HashSet<String> {
+    let mut ids = HashSet::new();
+    let mut stack = vec![project];
+    while let Some(item) = stack.pop() {
+        ids.insert(item.id.clone());
+        stack.extend(item.item.iter());
+    }
+    ids
+}
+
+fn request_files_ids(project: &PostmanItem) -> HashSet<String> {
+    request_files(project).into_iter().map(|file| file.id).collect()
+}
+
+/// The request files for every request under `project`.
+fn request_files(project: &PostmanItem) -> Vec<RequestFile> {
+    let mut files = Vec::new();
+    let mut stack = vec![project];
+    while let Some(parent) = stack.pop() {
+        for item in &parent.item {
+            if item.is_request() {
+                files.push(RequestFile {
+                    id: item.id.clone(),
+                    parent_id: parent.id.clone(),
+                    project_id: project.id.clone(),
+                    item: item.clone(),
+                });
+            }
+            stack.push(item);
+        }
+    }
+    files
+}
+
+/// Adds the archived project to `store` under a free name, along with its
+/// request files and any environments whose names aren't taken. On failure
+/// everything written so far is removed and the collection left as it was.
+pub fn install_project_archive(
+    store: &mut CollectionStore,
+    mut archive: ProjectArchive,
+) -> Result<ImportedProject, String> {
+    archive.remap_ids();
+    let taken: Vec<String> = store.list_projects().into_iter().map(|p| p.name).collect();
+    archive.project.name = free_project_name(&archive.project.name, &taken);
+    let project_id = Uuid::parse_str(&archive.project.id).map_err(|e| e.to_string())?;
+
+    let mut created: Vec<PathBuf> = Vec::new();
+    let result = write_import(store, &archive, &mut created);
+    match result {
+        Ok((environments, skipped_environments)) => Ok(ImportedProject {
+            project_id,
+            name: archive.project.name.clone(),
+            requests: request_files(&archive.project).len(),
+            environments,
+            skipped_environments,
+            session: archive.session,
+        }),
+        Err(err) => {
+            for path in created.iter().rev() {
+                let _ = fs::remove_file(path);
+            }
+            if store.get_item(project_id).is_some() {
+                let _ = store.delete_item(project_id);
+                let _ = store.save();
+            }
+            Err(err)
+        }
+    }
+}
+
+fn write_import(
+    store: &mut CollectionStore,
+    archive: &ProjectArchive,
+    created: &mut Vec<PathBuf>,
+) -> Result<(Vec<String>, Vec<String>), String> {
+    let mut added = Vec::new();
+    let mut skipped = Vec::new();
+    if !archive.environments.is_empty() {
+        let dir = ensure_environments_dir()?;
+        for env in &archive.environments {
+            let path = dir.join(format!("{}.json", env.name));
+            if path.exists() {
+                skipped.push(env.name.clone());
+                continue;
+            }
+            let json = serde_json::to_string_pretty(env)
+                .map_err(|e| format!("Failed to serialize environment: {}", e))?;
+            fs::write(&path, json)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            created.push(path);
+            added.push(env.name.clone());
+        }
+    }
+
+    let dir = requests_dir().ok_or("Could not find project root")?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create request dir: {}", e))?;
+    for file in request_files(&archive.project) {
+        let path = dir.join(format!("{}.json", file.id));
+        let json = serde_json::to_string_pretty(&file)
+            .map_err(|e| format!("Failed to serialize request file: {}", e))?;
+        fs::write(&path, json)
+            .map_err(|e| format!("Failed to write request file: {}", e))?;
+        created.push(path);
+    }
+
+    store.collection.item.push(archive.project.clone());
+    store.save()?;
+    Ok((added, skipped))
+}
+
+/// Suggested file name for archiving the project `name`.
+pub fn archive_file_name(name: &str) -> String {
+    let stem: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+        .collect();
+    let stem = stem.trim_matches('-');
+    format!("{}.perseus.zip", if stem.is_empty() { "project" } else { stem })
+}
+
+/// `name`, or `name (2)`, `name (3)`… if a project already has it.
+fn free_project_name(name: &str, taken: &[String]) -> String {
+    if !taken.iter().any(|t| t == name) {
+        return name.to_string();
+    }
+    (2..)
+        .map(|n| format!("{} ({})", name, n))
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    use crate::storage::environment::EnvironmentVariable;
+    use crate::storage::postman::{PostmanRequest, TlsSetting};
+    use crate::storage::session_state::{LastRun, RequestPosition, SendStat};
+
+    fn project_with_requests() -> PostmanItem {
+        let request = |name: &str| {
+            PostmanItem::new_request(
+                name.to_string(),
+                PostmanRequest::new("GET".to_string(), "{{base}}/x".to_string(), vec![], None),
+            )
+        };
+        let mut folder = PostmanItem::new_folder("Users".to_string());
+        folder.item.push(request("List users"));
+        let mut project = PostmanItem::new_folder("API".to_string());
+        project.item.push(folder);
+        project.item.push(request("Health"));
+        project
+    }
+
+    fn session_for(project: &PostmanItem) -> SessionState {
+        let folder = &project.item[0];
+        let request = &folder.item[0];
+        SessionState {
+            active_project_id: project.id.clone(),
+            sidebar_width: 40,
+            sidebar_visible: true,
+            current_request_id: Some(request.id.clone()),
+            projects: HashMap::from([(
+                project.id.clone(),
+                ProjectSidebarState {
+                    expanded: vec![folder.id.clone(), new_id()],
+                    selection_id: Some(request.id.clone()),
+                },
+            )]),
+            legacy_selection_id: None,
+            legacy_expanded: Vec::new(),
+            request_tab: "body".to_string(),
+            response_tab: "Body".to_string(),
+            headers_table: false,
+            response_wrap: true,
+            body_wrap: false,
+            positions: HashMap::from([
+                (request.id.clone(), RequestPosition::default()),
+                (new_id(), RequestPosition::default()),
+            ]),
+            last_runs: HashMap::from([(request.id.clone(), LastRun { status: Some(200) })]),
+            send_history: HashMap::from([(
+                request.id.clone(),
+                vec![SendStat {
+                    at: 1_700_000_000,
+                    status: Some(200),
+                    duration_ms: 84,
+                }],
+            )]),
+            notes: HashMap::from([
+                (request.id.clone(), "500s when amount > 10000".to_string()),
+                (new_id(), "elsewhere".to_string()),
+            ]),
+        }
+    }
+
+    fn archive_bytes(project: &PostmanItem, session: Option<&SessionState>) -> Vec<u8> {
+        let env = Environment {
+            name: "dev".to_string(),
+            values: vec![EnvironmentVariable::new("base", "http://localhost")],
+            extends: None,
+            scope: Default::default(),
+        };
+        let mut cursor = Cursor::new(Vec::new());
+        write_archive(project, &[env], session, &mut cursor).unwrap();
+        cursor.into_inner()
+    }
+
+    fn zip_of(entries: &[(&str, &str)]) -> Vec<u8> {
+        let mut cursor = Cursor::new(Vec::new());
+        let mut zip = ZipWriter::new(&mut cursor);
+        for (name, contents) in entries {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(contents.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+        cursor.into_inner()
+    }
+
+    #[test]
+    fn test_archive_round_trip_remaps_ids() {
+        let project = project_with_requests();
+        let session = session_for(&project);
+        let bytes = archive_bytes(&project, Some(&session));
+
+        let mut archive = parse_archive(Cursor::new(bytes)).unwrap();
+        assert_eq!(archive.project.name, "API");
+        assert_eq!(archive.environments[0].values[0].value, "http://localhost");
+        let narrowed = archive.session.clone().unwrap();
+        let sidebar = &narrowed.projects[&project.id];
+        assert_eq!(sidebar.expanded, vec![project.item[0].id.clone()]);
+        assert_eq!(narrowed.positions.len(), 1);
+        assert!(narrowed.last_runs.is_empty());
+        assert!(narrowed.send_history.is_empty());
+        assert_eq!(narrowed.notes.len(), 1);
+
+        archive.remap_ids();
+        let old = item_ids(&project);
+        let new = item_ids(&archive.project);
+        assert_eq!(new.len(), old.len());
+        assert!(new.is_disjoint(&old));
+
+        let remapped = archive.session.unwrap();
+        let list_users = &archive.project.item[0].item[0];
+        assert_eq!(list_users.name, "List users");
+        assert_eq!(remapped.active_project_id, archive.project.id);
+        assert_eq!(remapped.current_request_id.as_ref(), Some(&list_users.id));
+        let sidebar = &remapped.projects[&archive.project.id];
+        assert_eq!(sidebar.expanded, vec![archive.project.item[0].id.clone()]);
+        assert_eq!(sidebar.selection_id.as_ref(), Some(&list_users.id));
+        assert!(remapped.positions.contains_key(&list_users.id));
+        assert_eq!(remapped.notes[&list_users.id], "500s when amount > 10000");
+    }
+
+    #[test]
+    fn test_archive_leaves_out_tls_paths() {
+        let mut project = project_with_requests();
+        project.item[1].settings.tls = TlsSetting {
+            verify: Some(false),
+            client_cert: Some("/home/me/.certs/me.pem".to_string()),
+            client_key: Some("/home/me/.certs/me.key".to_string()),
+            ..TlsSetting::default()
+        };
+        let archive = parse_archive(Cursor::new(archive_bytes(&project, None))).unwrap();
+        let health = &archive.project.item[1];
+        assert_eq!(health.name, "Health");
+        assert_eq!(health.settings.tls.verify, Some(false));
+        assert!(health.settings.tls.client_cert.is_none());
+        assert!(health.settings.tls.client_key.is_none());
+    }
+
+    #[test]
+    fn test_invalid_archives_are_rejected() {
+        assert!(parse_archive(Cursor::new(b"not a zip".to_vec())).is_err());
+
+        let project = project_with_requests();
+        let project_json = serde_json::to_string(&project).unwrap();
+        let manifest = r#"{"format":1,"project":"API","exported_with":"perseus"}"#;
+        let err = parse_archive(Cursor::new(zip_of(&[("project.json", &project_json)])));
+        assert!(err.unwrap_err().contains("perseus-archive.json is missing"));
+
+        let future = r#"{"format":9,"project":"API","exported_with":"perseus"}"#;
+        let bytes = zip_of(&[(MANIFEST_ENTRY, future), (PROJECT_ENTRY, &project_json)]);
+        assert!(parse_archive(Cursor::new(bytes)).unwrap_err().contains("format 9"));
+
+        // Request files left out of the archive
+        let bytes = zip_of(&[(MANIFEST_ENTRY, manifest), (PROJECT_ENTRY, &project_json)]);
+        assert!(parse_archive(Cursor::new(bytes)).unwrap_err().contains("don't match"));
+
+        let bytes = zip_of(&[
+            (MANIFEST_ENTRY, manifest),
+            (PROJECT_ENTRY, "{}"),
+            ("../outside.json", "{}"),
+        ]);
+        assert!(parse_archive(Cursor::new(bytes)).is_err());
+
+        let bytes = zip_of(&[(MANIFEST_ENTRY, manifest), ("notes.txt", "hi")]);
+        assert!(parse_archive(Cursor::new(bytes)).unwrap_err().contains("Unexpected"));
+    }
+
+    #[test]
+    fn test_postman_collection_becomes_a_project() {
+        let json = r#"{
+            "info": {
+                "name": "Petstore",
+                "_postman_id": "abc",
+                "schema": "https://schema.getpostman.com/json/collection/v2.1.0/collection.json"
+            },
+            "item": [{
+                "name": "Pets",
+                "item": [{
+                    "name": "List pets",
+                    "description": { "content": "All of them", "type": "text/plain" },
+                    "request": { "method": "GET", "url": { "raw": "{{base}}/pets" } }
+                }]
+            }]
+        }"#;
+        let mut archive = parse_postman_collection(json).unwrap();
+        assert_eq!(archive.project.name, "Petstore");
+        assert!(archive.environments.is_empty() && archive.session.is_none());
+        archive.remap_ids();
+        assert!(check_ids(&archive.project).is_ok());
+        let list_pets = &archive.project.item[0].item[0];
+        assert_eq!(list_pets.description_text(), Some("All of them"));
+        assert_eq!(request_files(&archive.project).len(), 1);
+
+        assert!(parse_postman_collection(r#"{"item": []}"#).is_err());
+    }
+
+    #[test]
+    fn test_free_project_name() {
+        let taken = vec!["API".to_string(), "API (2)".to_string()];
+        assert_eq!(free_project_name("Web", &taken), "Web");
+        assert_eq!(free_project_name("API", &taken), "API (3)");
+    }
+
+    #[test]
+    fn test_archive_file_name() {
+        assert_eq!(archive_file_name("My API"), "My-API.perseus.zip");
+        assert_eq!(archive_file_name(" ../ "), "project.perseus.zip");
+    }
+}
@@ -0,0 +1,226 @@
+//! The collection's changelog, `.perseus/changelog.jsonl`: one JSON line
+//! per structural edit (an item added, renamed, moved, deleted or
+//! duplicated), and one summing up each change to `collection.json` made
+//! outside this instance, such as a `git pull`. Rotated by size like the
+//! execution log.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::sendlog;
+
+/// Size at which the changelog is rotated to `changelog.jsonl.1`.
+pub const CHANGELOG_MAX_BYTES: u64 = 256 * 1024;
+
+/// Rotated files kept besides the current one.
+const CHANGELOG_KEEP: u32 = 1;
+
+/// Names listed in an external change summary for each kind of change.
+const SUMMARY_NAMES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Renamed,
+    Moved,
+    Deleted,
+    Duplicated,
+    /// `collection.json` changed under this instance.
+    External,
+}
+
+impl ChangeKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            ChangeKind::Added => "added",
+            ChangeKind::Renamed => "renamed",
+            ChangeKind::Moved => "moved",
+            ChangeKind::Deleted => "deleted",
+            ChangeKind::Duplicated => "duplicated",
+            ChangeKind::External => "on disk",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangeEntry {
+    /// When the change was made or noticed, as RFC 3339 in UTC.
+    pub timestamp: String,
+    pub kind: ChangeKind,
+    /// The item's path in the sidebar, `Project/Folder/Request`, as it is
+    /// after the change (before it, for a deletion). Empty for an external
+    /// change.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub path: String,
+    /// The old path of a renamed or moved item, the original of a
+    /// duplicate, or what an external change did.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Where each item of a collection sits, by id: its parent (`None` for a
+/// project) and its name.
+pub type ItemLayout = HashMap<Uuid, (Option<Uuid>, String)>;
+
+/// Appends `entries` to the changelog at `path`, rotating it once it
+/// passes [`CHANGELOG_MAX_BYTES`].
+pub fn append_changes(path: &Path, entries: &[ChangeEntry]) -> Result<(), String> {
+    let mut text = String::new();
+    for entry in entries {
+        let line = serde_json::to_string(entry)
+            .map_err(|e| format!("Failed to serialize changelog entry: {}", e))?;
+        text.push_str(&line);
+        text.push('\n');
+    }
+    sendlog::append_rotated(path, CHANGELOG_MAX_BYTES, CHANGELOG_KEEP, &text)
+}
+
+/// The last `limit` entries of the changelog at `path` and its rotated
+/// file, newest first. Lines that don't parse are skipped.
+pub fn read_changes(path: &Path, limit: usize) -> Vec<ChangeEntry> {
+    let mut entries: Vec<ChangeEntry> = [sendlog::rotated(path, 1), path.to_path_buf()]
+        .iter()
+        .filter_map(|file| fs::read_to_string(file).ok())
+        .flat_map(|text| {
+            text.lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect::<Vec<ChangeEntry>>()
+        })
+        .collect();
+    entries.reverse();
+    entries.truncate(limit);
+    entries
+}
+
+/// The path of `id` in `layout`, `Project/Folder/Request`.
+pub fn item_path(layout: &ItemLayout, id: Uuid) -> String {
+    let mut names = Vec::new();
+    let mut current = Some(id);
+    while let Some((parent, name)) = current.and_then(|id| layout.get(&id)) {
+        names.push(name.as_str());
+        current = *parent;
+    }
+    names.reverse();
+    names.join("/")
+}
+
+/// What changed between two layouts of a collection, as
+/// `1 added: Users/Create; 1 renamed: Users/Login → Sign in`, or `None`
+/// when no item was added, removed, renamed or moved. An added or removed
+/// folder stands for everything in it.
+pub fn summarize_layout_change(before: &ItemLayout, after: &ItemLayout) -> Option<String> {
+    let added = top_level(after, |id| !before.contains_key(&id));
+    let removed = top_level(before, |id| !after.contains_key(&id));
+    let mut renamed = Vec::new();
+    let mut moved = Vec::new();
+    for (id, (parent, name)) in after {
+        let Some((old_parent, old_name)) = before.get(id) else {
+            continue;
+        };
+        if old_name != name {
+            renamed.push(format!("{} \u{2192} {}", item_path(before, *id), name));
+        }
+        if old_parent != parent {
+            let to = parent.map(|parent| item_path(after, parent)).unwrap_or_default();
+            moved.push(format!("{} \u{2192} {}/", item_path(before, *id), to));
+        }
+    }
+    let parts: Vec<String> = [
+        ("added", added.iter().map(|id| item_path(after, *id)).collect()),
+        ("removed", removed.iter().map(|id| item_path(before, *id)).collect()),
+        ("renamed", renamed),
+        ("moved", moved),
+    ]
+    .into_iter()
+    .filter(|(_, names): &(&str, Vec<String>)| !names.is_empty())
+    .map(|(what, mut names)| {
+        names.sort();
+        let count = names.len();
+        let more = count.saturating_sub(SUMMARY_NAMES);
+        names.truncate(SUMMARY_NAMES);
+        let mut listed = names.join(", ");
+        if more > 0 {
+            listed.push_str(&format!(" and {} more", more));
+        }
+        format!("{} {}: {}", count, what, listed)
+    })
+    .collect();
+    (!parts.is_empty()).then(|| parts.join("; "))
+}
+
+/// The ids in `layout` that `changed`, leaving out those whose parent
+/// changed too.
+fn top_level(layout: &ItemLayout, changed: impl Fn(Uuid) -> bool) -> Vec<Uuid> {
+    layout
+        .iter()
+        .filter(|(id, (parent, _))| changed(**id) && !parent.is_some_and(&changed))
+        .map(|(id, _)| *id)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(kind: ChangeKind, path: &str) -> ChangeEntry {
+        ChangeEntry {
+            timestamp: "2024-05-01T12:30:05.123Z".to_string(),
+            kind,
+            path: path.to_string(),
+            detail: None,
+        }
+    }
+
+    #[test]
+    fn test_append_and_read_changes_newest_first() {
+        let dir = std::env::temp_dir().join(format!("perseus-changelog-{}", Uuid::new_v4()));
+        let path = dir.join("changelog.jsonl");
+        append_changes(&path, &[entry(ChangeKind::Added, "Api/Users")]).unwrap();
+        let renamed = ChangeEntry {
+            detail: Some("Api/Users".to_string()),
+            ..entry(ChangeKind::Renamed, "Api/People")
+        };
+        append_changes(&path, std::slice::from_ref(&renamed)).unwrap();
+        fs::write(dir.join("changelog.jsonl.1"), "not json\n").unwrap();
+        let entries = read_changes(&path, 10);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0], renamed);
+        assert_eq!(read_changes(&path, 1), vec![renamed]);
+
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.starts_with("{\"timestamp\":\"2024-05-01T12:30:05.123Z\",\"kind\":\"added\""));
+        assert!(!text.lines().next().unwrap().contains("detail"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_summarize_layout_change() {
+        let ids: Vec<Uuid> = (0..6).map(|_| Uuid::new_v4()).collect();
+        let item = |parent: Option<usize>, name: &str| (parent.map(|i| ids[i]), name.to_string());
+        let before: ItemLayout = HashMap::from([
+            (ids[0], item(None, "Api")),
+            (ids[1], item(Some(0), "Users")),
+            (ids[2], item(Some(1), "Login")),
+            (ids[3], item(Some(0), "Old")),
+            (ids[4], item(Some(3), "Inside old")),
+        ]);
+        assert_eq!(summarize_layout_change(&before, &before), None);
+
+        let mut after = before.clone();
+        after.remove(&ids[3]);
+        after.remove(&ids[4]);
+        after.insert(ids[2], item(Some(0), "Sign in"));
+        after.insert(ids[5], item(Some(1), "Create"));
+        assert_eq!(
+            summarize_layout_change(&before, &after).unwrap(),
+            "1 added: Api/Users/Create; 1 removed: Api/Old; \
+             1 renamed: Api/Users/Login \u{2192} Sign in; 1 moved: Api/Users/Login \u{2192} Api/"
+        );
+        assert_eq!(item_path(&after, ids[2]), "Api/Sign in");
+    }
+}
@@ -1,12 +1,27 @@
 use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use uuid::Uuid;
 
+use crate::http;
+use crate::perf;
+
+use crate::storage::changelog::ItemLayout;
 use crate::storage::migrate::{load_legacy_requests, migrate_legacy};
-use crate::storage::postman::{new_id, PostmanCollection, PostmanHeader, PostmanItem, PostmanRequest};
-use crate::storage::project::{collection_path, ensure_storage_dir, find_project_root, requests_dir};
+use crate::storage::postman::{
+    new_id, ItemTag, PostmanCollection, PostmanHeader, PostmanItem, PostmanRequest,
+    RequestSettings,
+};
+use crate::storage::project::{
+    backup_path, collection_path, ensure_storage_dir, find_project_root, move_aside_corrupt,
+    requests_dir, write_atomic,
+};
+
+/// Folder, directly under a project, holding the requests offered as
+/// templates by the Add popup.
+pub const TEMPLATES_FOLDER: &str = "Templates";
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NodeKind {
@@ -24,9 +39,13 @@ pub struct TreeNode {
     pub request_method: Option<String>,
     pub parent_id: Option<Uuid>,
     pub children: Vec<Uuid>,
+    /// Locked itself, not through an ancestor.
+    pub locked: bool,
+    pub tags: Vec<ItemTag>,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(test, derive(Default))]
 pub struct ProjectTree {
     pub root_id: Uuid,
     pub nodes: HashMap<Uuid, TreeNode>,
@@ -39,6 +58,7 @@ pub struct ProjectInfo {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(test, derive(Default))]
 pub struct CollectionStore {
     pub root: PathBuf,
     pub collection: PostmanCollection,
@@ -52,8 +72,78 @@ pub struct RequestFile {
     pub item: PostmanItem,
 }
 
+/// A [`RequestFile`] borrowing its item, so writing every request file
+/// doesn't clone the collection.
+#[derive(serde::Serialize)]
+struct RequestFileRef<'a> {
+    id: String,
+    parent_id: String,
+    project_id: String,
+    item: &'a PostmanItem,
+}
+
+/// A file in `.perseus/requests` that isn't valid request JSON, usually
+/// after a hand edit. It is neither rewritten nor removed until it parses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestFileError {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+/// What mirroring the request files did.
+#[derive(Debug, Default)]
+pub struct RequestFilesReport {
+    pub written: usize,
+    pub unreadable: Vec<RequestFileError>,
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+    /// `collection.json` exists but doesn't parse.
+    Corrupt { path: PathBuf, message: String },
+    Other(String),
+}
+
+impl LoadError {
+    /// For a corrupt collection with a backup next to it: the collection path
+    /// and the backup that could replace it.
+    pub fn restorable_backup(&self) -> Option<(&std::path::Path, PathBuf)> {
+        match self {
+            LoadError::Corrupt { path, .. } => {
+                let backup = backup_path(path);
+                backup.exists().then_some((path.as_path(), backup))
+            }
+            LoadError::Other(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Corrupt { path, message } => {
+                write!(f, "Failed to parse collection {}: {}", path.display(), message)
+            }
+            LoadError::Other(message) => f.write_str(message),
+        }
+    }
+}
+
+impl From<String> for LoadError {
+    fn from(message: String) -> Self {
+        LoadError::Other(message)
+    }
+}
+
+impl From<&str> for LoadError {
+    fn from(message: &str) -> Self {
+        LoadError::Other(message.to_string())
+    }
+}
+
 impl CollectionStore {
-    pub fn load_or_init() -> Result<Self, String> {
+    pub fn load_or_init() -> Result<Self, LoadError> {
+        let _guard = perf::scope("collection.load");
         let root = find_project_root()
             .ok_or("Could not find project root. Run from a directory with .git, Cargo.toml, package.json, or create a .perseus folder.")?;
         let _ = ensure_storage_dir()?;
@@ -62,8 +152,12 @@ impl CollectionStore {
         let mut collection = if path.exists() {
             let contents =
                 fs::read_to_string(&path).map_err(|e| format!("Failed to read collection: {}", e))?;
-            serde_json::from_str::<PostmanCollection>(&contents)
-                .map_err(|e| format!("Failed to parse collection: {}", e))?
+            serde_json::from_str::<PostmanCollection>(&contents).map_err(|e| {
+                LoadError::Corrupt {
+                    path: path.clone(),
+                    message: e.to_string(),
+                }
+            })?
         } else {
             let legacy = load_legacy_requests()?;
             let root_name = root
@@ -91,15 +185,46 @@ impl CollectionStore {
         Ok(store)
     }
 
+    /// Reads the collection as it is on disk, writing nothing; for an
+    /// instance that doesn't own the project.
+    pub fn load_read_only() -> Result<Self, LoadError> {
+        let _guard = perf::scope("collection.load");
+        let root = find_project_root().ok_or("Could not find project root")?;
+        let path = collection_path().ok_or("Could not find project root")?;
+        let contents =
+            fs::read_to_string(&path).map_err(|e| format!("Failed to read collection: {}", e))?;
+        let mut collection =
+            serde_json::from_str::<PostmanCollection>(&contents).map_err(|e| {
+                LoadError::Corrupt {
+                    path: path.clone(),
+                    message: e.to_string(),
+                }
+            })?;
+        ensure_ids(&mut collection);
+        sort_collection(&mut collection);
+        Ok(Self { root, collection })
+    }
+
     pub fn save(&self) -> Result<(), String> {
         let _ = ensure_storage_dir()?;
         let path = collection_path().ok_or("Could not find project root")?;
         let json = serde_json::to_string_pretty(&self.collection)
             .map_err(|e| format!("Failed to serialize collection: {}", e))?;
-        fs::write(path, json).map_err(|e| format!("Failed to write collection: {}", e))?;
+        write_atomic(&path, json.as_bytes(), true)
+            .map_err(|e| format!("Failed to write collection: {}", e))?;
         Ok(())
     }
 
+    /// Replaces a corrupt `collection.json` with its backup. The corrupt file
+    /// is kept as `collection.json.corrupt`; its path is returned.
+    pub fn restore_backup(path: &std::path::Path) -> Result<PathBuf, String> {
+        let backup = backup_path(path);
+        let corrupt = move_aside_corrupt(path)?;
+        fs::copy(&backup, path)
+            .map_err(|e| format!("Failed to restore {}: {}", backup.display(), e))?;
+        Ok(corrupt)
+    }
+
     pub fn list_projects(&self) -> Vec<ProjectInfo> {
         self.collection
             .item
@@ -120,6 +245,7 @@ impl CollectionStore {
     }
 
     pub fn build_tree(&self, project_id: Uuid) -> Result<ProjectTree, String> {
+        let _guard = perf::scope("collection.build_tree");
         let project_item = find_item(&self.collection.item, &project_id.to_string())
             .ok_or("Project not found")?;
         let mut nodes = HashMap::new();
@@ -132,6 +258,8 @@ impl CollectionStore {
             request_method: None,
             parent_id: None,
             children: Vec::new(),
+            locked: project_item.locked,
+            tags: project_item.tags.clone(),
         };
 
         for child in &project_item.item {
@@ -149,6 +277,21 @@ impl CollectionStore {
         })
     }
 
+    /// The parent and name of every item, for the changelog.
+    pub fn layout(&self) -> ItemLayout {
+        let mut layout = HashMap::new();
+        let mut stack: Vec<(&PostmanItem, Option<Uuid>)> =
+            self.collection.item.iter().map(|item| (item, None)).collect();
+        while let Some((item, parent)) = stack.pop() {
+            let Some(id) = parse_uuid(&item.id) else {
+                continue;
+            };
+            layout.insert(id, (parent, item.name.clone()));
+            stack.extend(item.item.iter().map(|child| (child, Some(id))));
+        }
+        layout
+    }
+
     pub fn get_item(&self, id: Uuid) -> Option<&PostmanItem> {
         find_item(&self.collection.item, &id.to_string())
     }
@@ -162,7 +305,48 @@ impl CollectionStore {
             .get_item_mut(id)
             .ok_or("Item not found for rename")?;
         item.name = name;
+        // A name given by hand is kept from now on
+        item.name_is_auto = false;
+        sort_collection(&mut self.collection);
+        Ok(())
+    }
+
+    /// Names request `id` after its method and path and marks the name as
+    /// derived. Returns whether the name changed.
+    pub fn apply_auto_name(&mut self, id: Uuid) -> Result<bool, String> {
+        let item = self.get_item_mut(id).ok_or("Request not found")?;
+        let request = item.request.as_ref().ok_or("Not a request")?;
+        let url = (request.url.as_str())
+            .or_else(|| request.url.get("raw").and_then(|raw| raw.as_str()))
+            .unwrap_or_default();
+        let name = http::request_name_from_method_and_path(&request.method, url);
+        item.name_is_auto = true;
+        if item.name == name {
+            return Ok(false);
+        }
+        item.name = name;
         sort_collection(&mut self.collection);
+        Ok(true)
+    }
+
+    /// Turns following method and path for auto-named requests on or off
+    /// in a project, returning whether it is now on.
+    pub fn toggle_auto_names(&mut self, project_id: Uuid) -> Result<bool, String> {
+        let project = self.get_item_mut(project_id).ok_or("Project not found")?;
+        project.auto_names = !project.auto_names;
+        Ok(project.auto_names)
+    }
+
+    /// Locks or unlocks `id`, returning whether it is now locked.
+    pub fn toggle_locked(&mut self, id: Uuid) -> Result<bool, String> {
+        let item = self.get_item_mut(id).ok_or("Item not found for lock")?;
+        item.locked = !item.locked;
+        Ok(item.locked)
+    }
+
+    pub fn set_tags(&mut self, id: Uuid, tags: Vec<ItemTag>) -> Result<(), String> {
+        let item = self.get_item_mut(id).ok_or("Item not found for tags")?;
+        item.tags = tags;
         Ok(())
     }
 
@@ -176,7 +360,9 @@ impl CollectionStore {
     pub fn duplicate_item(&mut self, id: Uuid) -> Result<Uuid, String> {
         let (parent_items, index) = find_parent_vec_mut(&mut self.collection.item, &id.to_string())
             .ok_or("Item not found for duplicate")?;
-        let clone = clone_with_new_ids(&parent_items[index]);
+        let mut clone = clone_with_new_ids(&parent_items[index]);
+        // A copy is for editing, so it starts out unlocked
+        clone.locked = false;
         let clone_id = parse_uuid(&clone.id).ok_or("Invalid cloned id")?;
         parent_items.insert(index + 1, clone);
         sort_collection(&mut self.collection);
@@ -232,6 +418,69 @@ impl CollectionStore {
         Ok(id)
     }
 
+    /// The project's templates folder, if it has been created.
+    pub fn templates_folder(&self, project_id: Uuid) -> Option<Uuid> {
+        self.get_item(project_id)?
+            .item
+            .iter()
+            .find(|item| !item.is_request() && item.name == TEMPLATES_FOLDER)
+            .and_then(|item| parse_uuid(&item.id))
+    }
+
+    /// Templates in the project, sorted by name.
+    pub fn list_templates(&self, project_id: Uuid) -> Vec<(Uuid, String)> {
+        let Some(folder) = self.templates_folder(project_id).and_then(|id| self.get_item(id))
+        else {
+            return Vec::new();
+        };
+        folder
+            .item
+            .iter()
+            .filter(|item| item.is_request())
+            .filter_map(|item| parse_uuid(&item.id).map(|id| (id, item.name.clone())))
+            .collect()
+    }
+
+    /// Copies a request into the templates folder, creating the folder on
+    /// first use. Returns the template's id.
+    pub fn save_as_template(&mut self, project_id: Uuid, request_id: Uuid) -> Result<Uuid, String> {
+        let item = self.get_item(request_id).ok_or("Request not found")?;
+        if !item.is_request() {
+            return Err("Only requests can be templates".to_string());
+        }
+        let template = clone_with_new_ids(item);
+        let folder_id = match self.templates_folder(project_id) {
+            Some(_) if self.list_templates(project_id).iter().any(|(t, _)| *t == request_id) => {
+                return Err("Already a template".to_string());
+            }
+            Some(id) => id,
+            None => self.add_folder(project_id, TEMPLATES_FOLDER.to_string())?,
+        };
+        let template_id = parse_uuid(&template.id).ok_or("Invalid template id")?;
+        self.get_item_mut(folder_id)
+            .ok_or("Templates folder not found")?
+            .item
+            .push(template);
+        sort_collection(&mut self.collection);
+        Ok(template_id)
+    }
+
+    /// Adds a request whose method, URL, headers, body, auth and settings
+    /// are copied from a template.
+    pub fn add_request_from_template(
+        &mut self,
+        parent_id: Uuid,
+        name: String,
+        template_id: Uuid,
+    ) -> Result<Uuid, String> {
+        let template = self.get_item(template_id).ok_or("Template not found")?;
+        let request = template.request.clone().ok_or("Template is not a request")?;
+        let settings = template.settings.clone();
+        let id = self.add_request(parent_id, name, request)?;
+        self.update_settings(id, settings)?;
+        Ok(id)
+    }
+
     pub fn update_request(&mut self, id: Uuid, request: PostmanRequest) -> Result<(), String> {
         let item = self
             .get_item_mut(id)
@@ -240,6 +489,14 @@ impl CollectionStore {
         Ok(())
     }
 
+    pub fn update_settings(&mut self, id: Uuid, settings: RequestSettings) -> Result<(), String> {
+        let item = self
+            .get_item_mut(id)
+            .ok_or("Item not found for update")?;
+        item.settings = settings;
+        Ok(())
+    }
+
     pub fn save_request_file(
         &self,
         request_id: Uuid,
@@ -283,14 +540,23 @@ impl CollectionStore {
         Ok(())
     }
 
-    pub fn write_all_request_files(&self) -> Result<(), String> {
+    /// Mirrors every request into `.perseus/requests`, rewriting only the
+    /// files whose contents changed and removing those of deleted requests.
+    /// Files that don't parse are left alone and reported.
+    pub fn write_all_request_files(&self) -> Result<RequestFilesReport, String> {
         let dir = match requests_dir() {
             Some(d) => d,
             None => return Err("Could not find project root".to_string()),
         };
-        fs::create_dir_all(&dir)
+        self.write_all_request_files_in(&dir)
+    }
+
+    fn write_all_request_files_in(&self, dir: &Path) -> Result<RequestFilesReport, String> {
+        let _guard = perf::scope("collection.write_all_request_files");
+        fs::create_dir_all(dir)
             .map_err(|e| format!("Failed to create request dir: {}", e))?;
 
+        let mut report = RequestFilesReport::default();
         let mut seen: HashSet<String> = HashSet::new();
         let mut stack: Vec<(&PostmanItem, Option<Uuid>, Option<Uuid>)> = Vec::new();
         for project in &self.collection.item {
@@ -305,17 +571,16 @@ impl CollectionStore {
                     (parent_id, project_id, parse_uuid(&item.id))
                 {
                     seen.insert(id.to_string());
-                    let file = RequestFile {
+                    let file = RequestFileRef {
                         id: id.to_string(),
                         parent_id: pid.to_string(),
                         project_id: proj_id.to_string(),
-                        item: item.clone(),
+                        item,
                     };
                     let json = serde_json::to_string_pretty(&file)
                         .map_err(|e| format!("Failed to serialize request file: {}", e))?;
                     let path = dir.join(format!("{}.json", id));
-                    fs::write(path, json)
-                        .map_err(|e| format!("Failed to write request file: {}", e))?;
+                    sync_request_file(&path, &json, &mut report)?;
                 }
             }
 
@@ -327,21 +592,60 @@ impl CollectionStore {
             }
         }
 
-        let entries = fs::read_dir(&dir)
+        let entries = fs::read_dir(dir)
             .map_err(|e| format!("Failed to read request dir: {}", e))?;
         for entry in entries.flatten() {
             let path = entry.path();
             if path.extension().is_some_and(|ext| ext == "json") {
                 if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
-                    if !seen.contains(stem) {
-                        let _ = fs::remove_file(&path);
+                    if seen.contains(stem) {
+                        continue;
+                    }
+                    let broken = fs::read(&path).ok().and_then(|bytes| parse_error(&path, &bytes));
+                    match broken {
+                        Some(err) => report.unreadable.push(err),
+                        None => {
+                            let _ = fs::remove_file(&path);
+                        }
                     }
                 }
             }
         }
 
-        Ok(())
+        report.unreadable.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(report)
+    }
+}
+
+/// Writes `json` to `path` unless the file already holds it or doesn't
+/// parse. Reading is cheaper than writing, and an unchanged file keeps its
+/// mtime for editors and file watchers.
+fn sync_request_file(
+    path: &Path,
+    json: &str,
+    report: &mut RequestFilesReport,
+) -> Result<(), String> {
+    let existing = fs::read(path).ok();
+    if existing.as_deref() == Some(json.as_bytes()) {
+        return Ok(());
+    }
+    if let Some(err) = existing.and_then(|bytes| parse_error(path, &bytes)) {
+        report.unreadable.push(err);
+        return Ok(());
     }
+    fs::write(path, json).map_err(|e| format!("Failed to write request file: {}", e))?;
+    report.written += 1;
+    Ok(())
+}
+
+/// Why `bytes`, read from `path`, isn't a request file, if it isn't.
+fn parse_error(path: &Path, bytes: &[u8]) -> Option<RequestFileError> {
+    serde_json::from_slice::<RequestFile>(bytes)
+        .err()
+        .map(|e| RequestFileError {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        })
 }
 
 impl ProjectTree {
@@ -360,6 +664,36 @@ impl ProjectTree {
         false
     }
 
+    /// The nearest of `id` and its ancestors that is locked.
+    pub fn locked_by(&self, id: Uuid) -> Option<Uuid> {
+        let mut current = Some(id);
+        while let Some(node) = current.and_then(|id| self.nodes.get(&id)) {
+            if node.locked {
+                return Some(node.id);
+            }
+            current = node.parent_id;
+        }
+        None
+    }
+
+    /// Whether `id` is locked, or holds something that is.
+    pub fn contains_locked(&self, id: Uuid) -> bool {
+        self.locked_by(id).is_some()
+            || self.nodes.values().any(|node| node.locked && self.is_descendant(id, node.id))
+    }
+
+    /// Every tag used in the project, one per name, sorted by name.
+    pub fn tags(&self) -> Vec<ItemTag> {
+        let mut tags: Vec<ItemTag> = Vec::new();
+        for tag in self.nodes.values().flat_map(|node| &node.tags) {
+            if !tags.iter().any(|seen| seen.name == tag.name) {
+                tags.push(tag.clone());
+            }
+        }
+        tags.sort_by(|a, b| a.name.cmp(&b.name));
+        tags
+    }
+
     pub fn path_for(&self, id: Uuid) -> Vec<String> {
         let mut segments = Vec::new();
         let mut current = Some(id);
@@ -395,6 +729,8 @@ fn build_tree_node(item: &PostmanItem, parent_id: Uuid, nodes: &mut HashMap<Uuid
         request_method,
         parent_id: Some(parent_id),
         children: Vec::new(),
+        locked: item.locked,
+        tags: item.tags.clone(),
     };
 
     for child in &item.item {
@@ -437,20 +773,25 @@ fn sort_collection(collection: &mut PostmanCollection) -> bool {
     sort_items(&mut collection.item)
 }
 
-fn sort_items(items: &mut Vec<PostmanItem>) -> bool {
-    let before: Vec<String> = items.iter().map(|i| i.id.clone()).collect();
-    items.sort_by(|a, b| {
-        let an = a.name.to_lowercase();
-        let bn = b.name.to_lowercase();
-        an.cmp(&bn).then_with(|| a.id.cmp(&b.id))
-    });
-    let mut changed = before != items.iter().map(|i| i.id.clone()).collect::<Vec<_>>();
+fn sort_items(items: &mut [PostmanItem]) -> bool {
+    // Almost always already sorted on load; checking costs no allocation
+    let mut changed = !items.is_sorted_by(|a, b| item_order(a, b).is_le());
+    if changed {
+        items.sort_by(item_order);
+    }
     for item in items.iter_mut() {
         changed |= sort_items(&mut item.item);
     }
     changed
 }
 
+/// By name ignoring case, then by id.
+fn item_order(a: &PostmanItem, b: &PostmanItem) -> std::cmp::Ordering {
+    let an = a.name.chars().flat_map(char::to_lowercase);
+    let bn = b.name.chars().flat_map(char::to_lowercase);
+    an.cmp(bn).then_with(|| a.id.cmp(&b.id))
+}
+
 fn parse_uuid(value: &str) -> Option<Uuid> {
     Uuid::parse_str(value).ok()
 }
@@ -467,7 +808,7 @@ fn find_item<'a>(items: &'a [PostmanItem], id: &str) -> Option<&'a PostmanItem>
     None
 }
 
-fn find_item_mut<'a>(items: &'a mut Vec<PostmanItem>, id: &str) -> Option<&'a mut PostmanItem> {
+fn find_item_mut<'a>(items: &'a mut [PostmanItem], id: &str) -> Option<&'a mut PostmanItem> {
     for item in items.iter_mut() {
         if item.id == id {
             return Some(item);
@@ -511,6 +852,8 @@ fn find_item_path(items: &[PostmanItem], id: &str) -> Option<Vec<usize>> {
 fn clone_with_new_ids(item: &PostmanItem) -> PostmanItem {
     let mut clone = item.clone();
     clone.id = new_id();
+    // Re-importing the spec updates the original only
+    clone.operation_id = None;
     clone.item = clone
         .item
         .iter()
@@ -519,6 +862,8 @@ fn clone_with_new_ids(item: &PostmanItem) -> PostmanItem {
     clone
 }
 
+/// Parses `Key: Value` lines. A line commented out with `#` is a disabled
+/// header, kept with the request but not sent.
 pub fn parse_headers(raw: &str) -> Vec<PostmanHeader> {
     raw.lines()
         .filter_map(|line| {
@@ -526,6 +871,10 @@ pub fn parse_headers(raw: &str) -> Vec<PostmanHeader> {
             if trimmed.is_empty() {
                 return None;
             }
+            let (disabled, trimmed) = match trimmed.strip_prefix('#') {
+                Some(rest) => (Some(true), rest.trim_start()),
+                None => (None, trimmed),
+            };
             let mut parts = trimmed.splitn(2, ':');
             let key = parts.next()?.trim();
             let value = parts.next().unwrap_or("").trim();
@@ -535,8 +884,304 @@ pub fn parse_headers(raw: &str) -> Vec<PostmanHeader> {
             Some(PostmanHeader {
                 key: key.to_string(),
                 value: value.to_string(),
-                disabled: None,
+                disabled,
             })
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::postman::PostmanAuth;
+
+    fn store_with_request() -> (CollectionStore, Uuid, Uuid) {
+        let mut store = CollectionStore {
+            root: PathBuf::new(),
+            collection: PostmanCollection::new("Test".to_string()),
+        };
+        let project = store.add_project("Project".to_string()).unwrap();
+        let mut request = PostmanRequest::new(
+            "POST".to_string(),
+            "{{base_url}}/users".to_string(),
+            parse_headers("Accept: application/json\nX-Team: core"),
+            Some("{\"name\": \"\"}".to_string()),
+        );
+        request.auth = Some(PostmanAuth::bearer("{{token}}"));
+        let id = store.add_request(project, "Create user".to_string(), request).unwrap();
+        (store, project, id)
+    }
+
+    /// A project of `folders` folders holding `per_folder` requests each,
+    /// with headers and a body like a real collection's.
+    fn large_store(folders: usize, per_folder: usize) -> (CollectionStore, Uuid) {
+        let mut store = CollectionStore {
+            root: PathBuf::new(),
+            collection: PostmanCollection::new("Large".to_string()),
+        };
+        let project = store.add_project("Project".to_string()).unwrap();
+        for f in 0..folders {
+            let folder = store.add_folder(project, format!("Folder {:03}", f)).unwrap();
+            for r in 0..per_folder {
+                let request = PostmanRequest::new(
+                    "POST".to_string(),
+                    format!("{{{{base_url}}}}/folders/{}/items/{}", f, r),
+                    parse_headers("Accept: application/json\nAuthorization: Bearer {{token}}"),
+                    Some(format!("{{\"folder\": {}, \"item\": {}}}", f, r)),
+                );
+                store.add_request(folder, format!("Request {:03}", r), request).unwrap();
+            }
+        }
+        (store, project)
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("perseus-{}-{}", name, Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    // -- Load path tests --
+
+    #[test]
+    fn test_write_all_request_files_skips_unchanged_files() {
+        let (mut store, _) = large_store(3, 4);
+        let dir = temp_dir("requests");
+        assert_eq!(store.write_all_request_files_in(&dir).unwrap().written, 12);
+        assert_eq!(store.write_all_request_files_in(&dir).unwrap().written, 0);
+
+        let id = parse_uuid(&store.collection.item[0].item[1].item[2].id).unwrap();
+        let mut request = store.get_item(id).unwrap().request.clone().unwrap();
+        request.method = "PUT".to_string();
+        store.update_request(id, request).unwrap();
+        assert_eq!(store.write_all_request_files_in(&dir).unwrap().written, 1);
+
+        let written: RequestFile =
+            serde_json::from_str(&fs::read_to_string(dir.join(format!("{}.json", id))).unwrap())
+                .unwrap();
+        assert_eq!(written.item.request.unwrap().method, "PUT");
+
+        // Files of requests no longer in the collection go away
+        store.delete_item(id).unwrap();
+        assert_eq!(store.write_all_request_files_in(&dir).unwrap().written, 0);
+        assert!(!dir.join(format!("{}.json", id)).exists());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 11);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_unparsable_request_files_are_kept_and_reported() {
+        let (mut store, _) = large_store(1, 3);
+        let dir = temp_dir("broken");
+        store.write_all_request_files_in(&dir).unwrap();
+
+        // A hand edit leaving a trailing comma, to a request that still exists
+        let kept = parse_uuid(&store.collection.item[0].item[0].item[0].id).unwrap();
+        let kept_path = dir.join(format!("{}.json", kept));
+        let text = fs::read_to_string(&kept_path).unwrap();
+        let broken = text.replacen("\"id\"", "\"id\": 1,,\"x\"", 1);
+        fs::write(&kept_path, &broken).unwrap();
+        // and one to a request deleted since, which would otherwise be removed
+        let gone = parse_uuid(&store.collection.item[0].item[0].item[1].id).unwrap();
+        let gone_path = dir.join(format!("{}.json", gone));
+        fs::write(&gone_path, "{\"id\": \"x\",}").unwrap();
+        store.delete_item(gone).unwrap();
+
+        let report = store.write_all_request_files_in(&dir).unwrap();
+        assert_eq!(report.written, 0);
+        let mut paths = vec![kept_path.clone(), gone_path.clone()];
+        paths.sort();
+        let reported: Vec<PathBuf> = report.unreadable.iter().map(|e| e.path.clone()).collect();
+        assert_eq!(reported, paths);
+        assert!(report.unreadable.iter().all(|e| e.message.contains("line")));
+        assert_eq!(fs::read_to_string(&kept_path).unwrap(), broken);
+        assert!(gone_path.exists());
+
+        // Once fixed they are synced again
+        fs::write(&kept_path, text.replace("POST", "GET")).unwrap();
+        fs::write(&gone_path, &text).unwrap();
+        let report = store.write_all_request_files_in(&dir).unwrap();
+        assert!(report.unreadable.is_empty());
+        assert_eq!(report.written, 1);
+        assert!(!gone_path.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_sorted_collection_reports_no_change() {
+        let (mut store, _) = large_store(2, 3);
+        assert!(!ensure_ids(&mut store.collection));
+        assert!(!sort_collection(&mut store.collection));
+
+        store.collection.item[0].item.reverse();
+        assert!(sort_collection(&mut store.collection));
+        assert!(!sort_collection(&mut store.collection));
+        let names: Vec<&str> =
+            store.collection.item[0].item.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, ["Folder 000", "Folder 001"]);
+    }
+
+    /// Warm startup on 3000 requests: parse, id and sort checks, the
+    /// sidebar tree and the request files, which are all unchanged. Run with
+    /// `cargo test --release -- --ignored bench_`.
+    #[test]
+    #[ignore]
+    fn bench_warm_load_of_3000_requests() {
+        let (store, project) = large_store(30, 100);
+        let json = serde_json::to_string_pretty(&store.collection).unwrap();
+        let dir = temp_dir("bench");
+        store.write_all_request_files_in(&dir).unwrap();
+
+        let start = std::time::Instant::now();
+        let mut collection: PostmanCollection = serde_json::from_str(&json).unwrap();
+        assert!(!ensure_ids(&mut collection));
+        assert!(!sort_collection(&mut collection));
+        let loaded = CollectionStore {
+            root: PathBuf::new(),
+            collection,
+        };
+        let tree = loaded.build_tree(project).unwrap();
+        assert_eq!(loaded.write_all_request_files_in(&dir).unwrap().written, 0);
+        let elapsed = start.elapsed();
+
+        assert_eq!(tree.nodes.len(), 3031);
+        eprintln!("warm load of 3000 requests: {:?}", elapsed);
+        assert!(elapsed < std::time::Duration::from_millis(150), "{:?}", elapsed);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // -- Lock tests --
+
+    #[test]
+    fn test_lock_covers_subtree_and_copies_start_unlocked() {
+        let (mut store, project, id) = store_with_request();
+        let folder = store.add_folder(project, "Curated".to_string()).unwrap();
+        store.move_item(id, folder).unwrap();
+        assert!(store.toggle_locked(folder).unwrap());
+
+        let tree = store.build_tree(project).unwrap();
+        assert_eq!(tree.locked_by(id), Some(folder));
+        assert_eq!(tree.locked_by(project), None);
+        assert!(tree.contains_locked(project));
+
+        let copy = store.duplicate_item(folder).unwrap();
+        let tree = store.build_tree(project).unwrap();
+        assert_eq!(tree.locked_by(copy), None);
+        assert!(!store.get_item(copy).unwrap().locked);
+
+        let json = serde_json::to_string(store.get_item(folder).unwrap()).unwrap();
+        assert!(json.contains(r#""_perseus_locked":true"#));
+        assert!(!store.toggle_locked(folder).unwrap());
+        let json = serde_json::to_string(store.get_item(folder).unwrap()).unwrap();
+        assert!(!json.contains("_perseus_locked"));
+    }
+
+    #[test]
+    fn test_tags_survive_duplication() {
+        let (mut store, project, id) = store_with_request();
+        let tags = crate::storage::parse_tags("auth:red, slow").unwrap();
+        store.set_tags(id, tags.clone()).unwrap();
+        let copy = store.duplicate_item(id).unwrap();
+        assert_eq!(store.get_item(copy).unwrap().tags, tags);
+
+        let tree = store.build_tree(project).unwrap();
+        assert_eq!(tree.node(copy).unwrap().tags, tags);
+        let names: Vec<String> = tree.tags().into_iter().map(|tag| tag.name).collect();
+        assert_eq!(names, ["auth", "slow"]);
+    }
+
+    #[test]
+    fn test_auto_name_follows_method_and_path_until_renamed() {
+        let (mut store, project, id) = store_with_request();
+        assert!(store.apply_auto_name(id).unwrap());
+        let item = store.get_item(id).unwrap();
+        assert_eq!((item.name.as_str(), item.name_is_auto), ("POST /users", true));
+        assert!(!store.apply_auto_name(id).unwrap());
+
+        assert!(store.toggle_auto_names(project).unwrap());
+        let json = serde_json::to_string(store.get_item(project).unwrap()).unwrap();
+        assert!(json.contains(r#""_perseus_auto_names":true"#));
+
+        store.rename_item(id, "Create user".to_string()).unwrap();
+        let item = store.get_item(id).unwrap();
+        assert!(!item.name_is_auto);
+        assert!(!serde_json::to_string(item).unwrap().contains("_perseus_name_auto"));
+        assert!(store.apply_auto_name(project).is_err());
+    }
+
+    // -- Template tests --
+
+    #[test]
+    fn test_save_as_template_creates_folder_once() {
+        let (mut store, project, id) = store_with_request();
+        assert!(store.templates_folder(project).is_none());
+        assert!(store.list_templates(project).is_empty());
+
+        let first = store.save_as_template(project, id).unwrap();
+        let folder = store.templates_folder(project).unwrap();
+        let second = store.save_as_template(project, id).unwrap();
+        assert_eq!(store.templates_folder(project), Some(folder));
+        assert_ne!(first, second);
+        assert_eq!(store.list_templates(project).len(), 2);
+        assert!(store.save_as_template(project, first).is_err());
+        assert!(store.save_as_template(project, folder).is_err());
+    }
+
+    #[test]
+    fn test_add_request_from_template_copies_request() {
+        let (mut store, project, id) = store_with_request();
+        let template = store.save_as_template(project, id).unwrap();
+        let (listed, name) = store.list_templates(project)[0].clone();
+        assert_eq!((listed, name.as_str()), (template, "Create user"));
+
+        let new_id = store
+            .add_request_from_template(project, "Another".to_string(), template)
+            .unwrap();
+        let original = serde_json::to_value(&store.get_item(template).unwrap().request).unwrap();
+        let copy = serde_json::to_value(&store.get_item(new_id).unwrap().request).unwrap();
+        assert_eq!(original, copy);
+        assert_eq!(store.get_item(new_id).unwrap().name, "Another");
+    }
+
+    #[test]
+    fn test_editing_copy_leaves_template_unchanged() {
+        let (mut store, project, id) = store_with_request();
+        let template = store.save_as_template(project, id).unwrap();
+        let new_id = store
+            .add_request_from_template(project, "Another".to_string(), template)
+            .unwrap();
+
+        let mut edited = store.get_item(new_id).unwrap().request.clone().unwrap();
+        edited.method = "PUT".to_string();
+        edited.header.push(PostmanHeader {
+            key: "X-Extra".to_string(),
+            value: "1".to_string(),
+            disabled: None,
+        });
+        store.update_request(new_id, edited).unwrap();
+
+        let kept = store.get_item(template).unwrap().request.as_ref().unwrap();
+        assert_eq!(kept.method, "POST");
+        assert_eq!(kept.header.len(), 2);
+    }
+
+    // -- Header parsing tests --
+
+    #[test]
+    fn test_parse_headers_reads_disabled_lines() {
+        let headers = parse_headers("Accept: */*\n\n#X-Debug: 1\n  # X-Trace :  on \nbroken");
+        let parsed: Vec<(&str, &str, Option<bool>)> = headers
+            .iter()
+            .map(|h| (h.key.as_str(), h.value.as_str(), h.disabled))
+            .collect();
+        assert_eq!(
+            parsed,
+            vec![
+                ("Accept", "*/*", None),
+                ("X-Debug", "1", Some(true)),
+                ("X-Trace", "on", Some(true)),
+                ("broken", "", None),
+            ]
+        );
+    }
+}
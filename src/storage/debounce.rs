@@ -0,0 +1,130 @@
+//! Coalesces writes of small state files that change on many key presses.
+
+use std::time::{Duration, Instant};
+
+/// Shortest gap between two writes of the same file.
+pub const WRITE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Tracks whether a file has unwritten changes and when it was last
+/// written. The first change after a quiet spell is due at once; changes
+/// following a write wait until [`WRITE_INTERVAL`] has passed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WriteDebounce {
+    pending: bool,
+    last_write: Option<Instant>,
+}
+
+impl WriteDebounce {
+    /// Marks the file as changed.
+    pub fn request(&mut self) {
+        self.pending = true;
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.pending
+    }
+
+    /// When the pending write is due, or `None` with nothing to write.
+    pub fn due_at(&self, now: Instant) -> Option<Instant> {
+        self.pending.then(|| match self.last_write {
+            Some(last) => (last + WRITE_INTERVAL).max(now),
+            None => now,
+        })
+    }
+
+    pub fn is_due(&self, now: Instant) -> bool {
+        self.due_at(now).is_some_and(|due| due <= now)
+    }
+
+    /// Records a write at `now`, failed or not; a failure is retried only
+    /// once something changes again.
+    pub fn wrote(&mut self, now: Instant) {
+        self.pending = false;
+        self.last_write = Some(now);
+    }
+}
+
+/// A write quitting still has to make.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PendingWrite {
+    Collection,
+    Session,
+    UiState,
+    Changelog,
+}
+
+/// The writes left on quit, in the order they are made: the collection
+/// first, since the session and changelog name its requests, then the
+/// state files whose debounce interval hadn't come up yet.
+pub fn shutdown_writes(
+    collection_unsaved: bool,
+    session: &WriteDebounce,
+    ui_state: &WriteDebounce,
+    changelog: &WriteDebounce,
+) -> Vec<PendingWrite> {
+    let files = [
+        (PendingWrite::Session, session),
+        (PendingWrite::UiState, ui_state),
+        (PendingWrite::Changelog, changelog),
+    ];
+    collection_unsaved
+        .then_some(PendingWrite::Collection)
+        .into_iter()
+        .chain(files.into_iter().filter(|(_, write)| write.is_pending()).map(|(file, _)| file))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shutdown_writes_collection_first() {
+        let now = Instant::now();
+        let mut session = WriteDebounce::default();
+        let mut ui_state = WriteDebounce::default();
+        let changelog = WriteDebounce::default();
+        assert!(shutdown_writes(false, &session, &ui_state, &changelog).is_empty());
+
+        // Held back by the interval, yet still written on quit
+        session.wrote(now);
+        session.request();
+        ui_state.request();
+        assert!(!session.is_due(now));
+        assert_eq!(
+            shutdown_writes(true, &session, &ui_state, &changelog),
+            [PendingWrite::Collection, PendingWrite::Session, PendingWrite::UiState]
+        );
+        ui_state.wrote(now);
+        assert_eq!(
+            shutdown_writes(false, &session, &ui_state, &changelog),
+            [PendingWrite::Session]
+        );
+    }
+
+    #[test]
+    fn test_writes_at_most_once_per_interval() {
+        let start = Instant::now();
+        let mut debounce = WriteDebounce::default();
+        assert_eq!(debounce.due_at(start), None);
+
+        debounce.request();
+        assert!(debounce.is_due(start));
+        debounce.wrote(start);
+        assert!(!debounce.is_pending());
+
+        // Changes right after a write wait out the interval
+        let soon = start + Duration::from_millis(100);
+        debounce.request();
+        debounce.request();
+        assert!(!debounce.is_due(soon));
+        assert_eq!(debounce.due_at(soon), Some(start + WRITE_INTERVAL));
+        assert!(debounce.is_due(start + WRITE_INTERVAL));
+
+        // After a quiet spell the next change is written at once
+        let later = start + Duration::from_secs(3);
+        debounce.wrote(start + WRITE_INTERVAL);
+        debounce.request();
+        assert_eq!(debounce.due_at(later), Some(later));
+    }
+}
@@ -0,0 +1,188 @@
+//! Snapshots of unsaved request edits, so a crash or a killed terminal
+//! doesn't lose them. One file per request in `.perseus/drafts/`, removed
+//! once the request is saved.
+
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::storage::postman::{PostmanHeader, PostmanRequest};
+use crate::storage::project::{collection_path, drafts_dir, requests_dir, write_atomic};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Draft {
+    pub request_id: String,
+    /// When the draft was written, in milliseconds since the Unix epoch.
+    pub saved_at: u64,
+    pub request: PostmanRequest,
+}
+
+impl Draft {
+    pub fn new(request_id: String, request: PostmanRequest) -> Self {
+        Self {
+            request_id,
+            saved_at: millis(SystemTime::now()),
+            request,
+        }
+    }
+
+    /// Whether the draft was written after the request was last saved.
+    pub fn is_newer_than_saved(&self) -> bool {
+        saved_at(&self.request_id).is_none_or(|saved| self.saved_at > saved)
+    }
+}
+
+pub fn save_draft(draft: &Draft) -> Result<(), String> {
+    let dir = drafts_dir().ok_or("Could not find project root")?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create drafts directory: {}", e))?;
+    let json = serde_json::to_string_pretty(draft)
+        .map_err(|e| format!("Failed to serialize draft: {}", e))?;
+    write_atomic(&dir.join(format!("{}.json", draft.request_id)), json.as_bytes(), false)
+        .map_err(|e| format!("Failed to write draft: {}", e))
+}
+
+/// Every readable draft, oldest first. Files that don't parse are skipped.
+pub fn load_drafts() -> Result<Vec<Draft>, String> {
+    let dir = match drafts_dir() {
+        Some(dir) if dir.exists() => dir,
+        _ => return Ok(Vec::new()),
+    };
+    let entries =
+        fs::read_dir(&dir).map_err(|e| format!("Failed to read drafts directory: {}", e))?;
+    let mut drafts: Vec<Draft> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| serde_json::from_str(&fs::read_to_string(path).ok()?).ok())
+        .collect();
+    drafts.sort_by_key(|draft| draft.saved_at);
+    Ok(drafts)
+}
+
+pub fn delete_draft(request_id: &str) -> Result<(), String> {
+    let Some(dir) = drafts_dir() else {
+        return Ok(());
+    };
+    let path = dir.join(format!("{}.json", request_id));
+    if path.exists() {
+        fs::remove_file(path).map_err(|e| format!("Failed to delete draft: {}", e))?;
+    }
+    Ok(())
+}
+
+/// When the request was last written: its request file, or the collection
+/// for requests without one.
+fn saved_at(request_id: &str) -> Option<u64> {
+    let path = requests_dir()
+        .map(|dir| dir.join(format!("{}.json", request_id)))
+        .filter(|path| path.exists())
+        .or_else(collection_path)?;
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(millis(modified))
+}
+
+fn millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64)
+}
+
+/// What `draft` changes in `saved`, one short phrase per part:
+/// "method GET → POST", "URL", "headers +1 −0 ~2", "body", "auth".
+pub fn draft_changes(saved: &PostmanRequest, draft: &PostmanRequest) -> Vec<String> {
+    let mut changes = Vec::new();
+    if !saved.method.eq_ignore_ascii_case(&draft.method) {
+        changes.push(format!("method {} \u{2192} {}", saved.method, draft.method));
+    }
+    if url_text(&saved.url) != url_text(&draft.url) {
+        changes.push("URL".to_string());
+    }
+    let (added, removed, changed) = header_changes(&saved.header, &draft.header);
+    if added + removed + changed > 0 {
+        changes.push(format!("headers +{} \u{2212}{} ~{}", added, removed, changed));
+    }
+    if serde_json::to_value(&saved.body).ok() != serde_json::to_value(&draft.body).ok() {
+        changes.push("body".to_string());
+    }
+    let auth_type = |request: &PostmanRequest| {
+        request.auth.as_ref().map_or("none".to_string(), |auth| auth.auth_type.clone())
+    };
+    if auth_type(saved) != auth_type(draft) {
+        changes.push(format!("auth {} \u{2192} {}", auth_type(saved), auth_type(draft)));
+    } else if serde_json::to_value(&saved.auth).ok() != serde_json::to_value(&draft.auth).ok() {
+        changes.push("auth".to_string());
+    }
+    changes
+}
+
+fn url_text(url: &Value) -> &str {
+    match url {
+        Value::String(raw) => raw,
+        Value::Object(map) => map.get("raw").and_then(Value::as_str).unwrap_or_default(),
+        _ => "",
+    }
+}
+
+/// `(added, removed, changed)` headers, matched by name case-insensitively.
+fn header_changes(saved: &[PostmanHeader], draft: &[PostmanHeader]) -> (usize, usize, usize) {
+    let find = |headers: &'_ [PostmanHeader], key: &str| {
+        headers.iter().position(|h| h.key.eq_ignore_ascii_case(key))
+    };
+    let mut added = 0;
+    let mut changed = 0;
+    for header in draft {
+        match find(saved, &header.key).map(|i| &saved[i]) {
+            Some(old) if old.value != header.value || old.disabled != header.disabled => {
+                changed += 1
+            }
+            Some(_) => {}
+            None => added += 1,
+        }
+    }
+    let removed = saved.iter().filter(|h| find(draft, &h.key).is_none()).count();
+    (added, removed, changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::postman::PostmanAuth;
+
+    fn header(key: &str, value: &str) -> PostmanHeader {
+        PostmanHeader {
+            key: key.to_string(),
+            value: value.to_string(),
+            disabled: None,
+        }
+    }
+
+    #[test]
+    fn test_draft_changes() {
+        let saved = PostmanRequest::new(
+            "GET".to_string(),
+            "{{base}}/users".to_string(),
+            vec![header("Accept", "*/*"), header("X-Trace", "1")],
+            None,
+        );
+        assert!(draft_changes(&saved, &saved.clone()).is_empty());
+
+        let mut draft = saved.clone();
+        draft.method = "POST".to_string();
+        draft.url = Value::String("{{base}}/users/1".to_string());
+        draft.header = vec![header("accept", "application/json"), header("X-New", "x")];
+        draft.auth = Some(PostmanAuth::bearer("token"));
+        assert_eq!(
+            draft_changes(&saved, &draft),
+            vec![
+                "method GET \u{2192} POST",
+                "URL",
+                "headers +1 \u{2212}1 ~1",
+                "auth none \u{2192} bearer",
+            ]
+        );
+
+        let mut rotated = draft.clone();
+        rotated.auth = Some(PostmanAuth::bearer("other"));
+        assert_eq!(draft_changes(&draft, &rotated), vec!["auth"]);
+    }
+}
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 
@@ -42,6 +42,42 @@ pub struct Environment {
     pub name: String,
     #[serde(default)]
     pub values: Vec<EnvironmentVariable>,
+    /// The environment this one builds on: its variables apply unless this
+    /// one sets the same key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+    /// Where the environment was loaded from; not part of the file.
+    #[serde(skip)]
+    pub scope: EnvScope,
+}
+
+/// Project environments live in `.perseus/environments/` and are meant to be
+/// committed; user environments live under the user config dir.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EnvScope {
+    #[default]
+    Project,
+    User,
+    /// A project environment with a user environment of the same name
+    /// layered on top.
+    Layered,
+}
+
+impl EnvScope {
+    pub fn label(self) -> &'static str {
+        match self {
+            EnvScope::Project => "project",
+            EnvScope::User => "user",
+            EnvScope::Layered => "project+user",
+        }
+    }
+
+    fn dir(self) -> Option<std::path::PathBuf> {
+        match self {
+            EnvScope::Project | EnvScope::Layered => project::environments_dir(),
+            EnvScope::User => project::user_environments_dir(),
+        }
+    }
 }
 
 // --- File I/O ---
@@ -54,47 +90,111 @@ pub fn load_environment(path: &Path) -> Result<Environment, String> {
 }
 
 pub fn save_environment(env: &Environment) -> Result<(), String> {
+    save_environment_in(env, EnvScope::Project)
+}
+
+/// Writes `env` to the project or user environments directory. A layered
+/// environment is written whole to the project directory.
+pub fn save_environment_in(env: &Environment, scope: EnvScope) -> Result<(), String> {
     if !is_safe_env_name(&env.name) {
         return Err(format!(
             "Invalid environment name '{}': must be non-empty and contain only alphanumeric, underscore, or hyphen characters",
             env.name
         ));
     }
-    let dir = project::ensure_environments_dir()?;
+    let dir = match scope {
+        EnvScope::Project | EnvScope::Layered => project::ensure_environments_dir()?,
+        EnvScope::User => project::ensure_user_environments_dir()?,
+    };
     let path = dir.join(format!("{}.json", env.name));
     let json = serde_json::to_string_pretty(env)
         .map_err(|e| format!("Failed to serialize environment: {}", e))?;
     fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
 }
 
+/// Whether a file for `name` already exists in `scope`'s directory.
+pub fn environment_exists(name: &str, scope: EnvScope) -> bool {
+    scope
+        .dir()
+        .is_some_and(|dir| dir.join(format!("{}.json", name)).exists())
+}
+
+/// Project and user environments of the current project, layered.
 pub fn load_all_environments() -> Result<Vec<Environment>, String> {
-    let dir = match project::environments_dir() {
-        Some(d) => d,
-        None => return Ok(Vec::new()),
-    };
-    if !dir.exists() {
-        return Ok(Vec::new());
+    load_layered_environments(
+        project::environments_dir().as_deref(),
+        project::user_environments_dir().as_deref(),
+    )
+}
+
+/// Loads both directories (either may be missing). When both define an
+/// environment with the same name, the user file is layered on the
+/// project one variable by variable: user values replace project values
+/// with the same key and user-only variables are appended.
+pub fn load_layered_environments(
+    project_dir: Option<&Path>,
+    user_dir: Option<&Path>,
+) -> Result<Vec<Environment>, String> {
+    let mut environments = load_environments_in(project_dir, EnvScope::Project)?;
+    for user_env in load_environments_in(user_dir, EnvScope::User)? {
+        match environments.iter_mut().find(|e| e.name == user_env.name) {
+            Some(base) => {
+                for var in user_env.values {
+                    match base.values.iter_mut().find(|v| v.key == var.key) {
+                        Some(existing) => *existing = var,
+                        None => base.values.push(var),
+                    }
+                }
+                if user_env.extends.is_some() {
+                    base.extends = user_env.extends;
+                }
+                base.scope = EnvScope::Layered;
+            }
+            None => environments.push(user_env),
+        }
     }
+    environments.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(environments)
+}
+
+fn load_environments_in(dir: Option<&Path>, scope: EnvScope) -> Result<Vec<Environment>, String> {
+    let Some(dir) = dir.filter(|dir| dir.exists()) else {
+        return Ok(Vec::new());
+    };
 
     let mut environments = Vec::new();
     let entries =
-        fs::read_dir(&dir).map_err(|e| format!("Failed to read environments dir: {}", e))?;
+        fs::read_dir(dir).map_err(|e| format!("Failed to read environments dir: {}", e))?;
 
     for entry in entries {
         let entry = entry.map_err(|e| format!("Failed to read dir entry: {}", e))?;
         let path = entry.path();
         if path.extension().and_then(|ext| ext.to_str()) == Some("json") {
             match load_environment(&path) {
-                Ok(env) => environments.push(env),
+                Ok(env) => environments.push(Environment { scope, ..env }),
                 Err(err) => eprintln!("Warning: skipping environment file: {}", err),
             }
         }
     }
-
-    environments.sort_by(|a, b| a.name.cmp(&b.name));
     Ok(environments)
 }
 
+/// Copies `var` into the file of `env` itself, so a variable it inherits
+/// can be changed there. A layered environment gets it in its project file.
+pub fn override_variable(env: &Environment, var: &EnvironmentVariable) -> Result<(), String> {
+    let scope = match env.scope {
+        EnvScope::User => EnvScope::User,
+        EnvScope::Project | EnvScope::Layered => EnvScope::Project,
+    };
+    let dir = scope.dir().ok_or("Could not find environments directory")?;
+    let mut file = load_environment(&dir.join(format!("{}.json", env.name)))?;
+    if file.values.iter().any(|v| v.key == var.key) {
+        return Err(format!("'{}' is already set in '{}'", var.key, env.name));
+    }
+    file.values.push(var.clone());
+    save_environment_in(&file, scope)
+}
+
 pub fn delete_environment_file(name: &str) -> Result<(), String> {
     let dir = project::environments_dir()
         .ok_or("Could not find environments directory")?;
@@ -106,7 +206,216 @@ pub fn delete_environment_file(name: &str) -> Result<(), String> {
     Ok(())
 }
 
-fn is_safe_env_name(name: &str) -> bool {
+// --- Renaming variables ---
+
+/// A variable renamed across environment files, worked out but not yet
+/// written.
+#[derive(Debug, Clone)]
+pub struct VariableRename {
+    /// Each file that changes, with its text before and after.
+    files: Vec<(PathBuf, String, String)>,
+    /// Keys renamed plus placeholders rewritten in values.
+    pub occurrences: usize,
+}
+
+impl VariableRename {
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.files.iter().map(|(path, _, _)| path.as_path())
+    }
+
+    /// Writes every changed file, or none: files written before a failure
+    /// get their old text back.
+    pub fn apply(&self) -> Result<(), String> {
+        for (i, (path, _, after)) in self.files.iter().enumerate() {
+            if let Err(e) = project::write_atomic(path, after.as_bytes(), false) {
+                for (written, before, _) in &self.files[..i] {
+                    let _ = project::write_atomic(written, before.as_bytes(), false);
+                }
+                return Err(format!("Failed to write {}: {}", path.display(), e));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Renaming `from` to `to` in every project and user environment file:
+/// the variable itself and `{{from}}` in other variables' values. Fails if
+/// any environment already has `to`.
+pub fn plan_variable_rename(from: &str, to: &str) -> Result<VariableRename, String> {
+    let dirs = [project::environments_dir(), project::user_environments_dir()];
+    let dirs: Vec<&Path> = dirs.iter().flatten().map(PathBuf::as_path).collect();
+    plan_variable_rename_in(&dirs, from, to)
+}
+
+fn plan_variable_rename_in(
+    dirs: &[&Path],
+    from: &str,
+    to: &str,
+) -> Result<VariableRename, String> {
+    let mut rename = VariableRename {
+        files: Vec::new(),
+        occurrences: 0,
+    };
+    for dir in dirs.iter().filter(|dir| dir.exists()) {
+        let entries =
+            fs::read_dir(dir).map_err(|e| format!("Failed to read environments dir: {}", e))?;
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("json"))
+            .collect();
+        paths.sort();
+        for path in paths {
+            let before = fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            // Files that don't parse aren't loaded either
+            let Ok(mut env) = serde_json::from_str::<Environment>(&before) else {
+                continue;
+            };
+            if env.values.iter().any(|var| var.key == to) {
+                return Err(format!("'{}' already exists in environment '{}'", to, env.name));
+            }
+            let mut count = 0;
+            for var in &mut env.values {
+                if var.key == from {
+                    var.key = to.to_string();
+                    count += 1;
+                }
+                let (value, renamed) = rename_placeholder(&var.value, from, to);
+                var.value = value;
+                count += renamed;
+            }
+            if count > 0 {
+                let after = serde_json::to_string_pretty(&env)
+                    .map_err(|e| format!("Failed to serialize environment: {}", e))?;
+                rename.files.push((path, before, after));
+                rename.occurrences += count;
+            }
+        }
+    }
+    Ok(rename)
+}
+
+// --- Postman environment exports ---
+
+/// The scope Postman writes into environment exports, as opposed to
+/// `"globals"`.
+const POSTMAN_ENVIRONMENT_SCOPE: &str = "environment";
+
+#[derive(Serialize, Deserialize)]
+struct PostmanEnvironment {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    name: String,
+    #[serde(default)]
+    values: Vec<PostmanEnvironmentValue>,
+    #[serde(rename = "_postman_variable_scope", default)]
+    scope: Option<String>,
+    #[serde(rename = "_postman_exported_using", default, skip_serializing_if = "Option::is_none")]
+    exported_using: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PostmanEnvironmentValue {
+    key: String,
+    /// Postman allows any JSON here; non-strings are kept as their JSON text.
+    #[serde(default)]
+    value: serde_json::Value,
+    #[serde(rename = "type", default = "default_type")]
+    var_type: String,
+    #[serde(default = "default_true")]
+    enabled: bool,
+}
+
+/// Reads a Postman environment export. The name is made safe for a file
+/// name; the original is only lost if it had other characters.
+pub fn parse_postman_environment(text: &str) -> Result<Environment, String> {
+    let export: PostmanEnvironment = serde_json::from_str(text)
+        .map_err(|e| format!("Not a Postman environment export: {}", e))?;
+    if let Some(scope) = export.scope.as_deref() {
+        if scope != POSTMAN_ENVIRONMENT_SCOPE {
+            return Err(format!("Expected an environment export, found scope \"{}\"", scope));
+        }
+    }
+    let values = export
+        .values
+        .into_iter()
+        .map(|v| EnvironmentVariable {
+            key: v.key,
+            value: match v.value {
+                serde_json::Value::String(s) => s,
+                serde_json::Value::Null => String::new(),
+                other => other.to_string(),
+            },
+            enabled: v.enabled,
+            var_type: v.var_type,
+        })
+        .collect();
+    Ok(Environment {
+        name: safe_env_name(&export.name),
+        values,
+        extends: None,
+        scope: EnvScope::default(),
+    })
+}
+
+/// Renders an environment the way Postman exports one, so it can be
+/// imported there. Postman has no inheritance, so the variables `env`
+/// inherits from `environments` are written into it.
+pub fn postman_environment_json(
+    env: &Environment,
+    environments: &[Environment],
+) -> Result<String, String> {
+    let export = PostmanEnvironment {
+        id: Some(uuid::Uuid::new_v4().to_string()),
+        name: env.name.clone(),
+        values: chain_variables(env, environments)?
+            .into_iter()
+            .map(|(v, _)| PostmanEnvironmentValue {
+                key: v.key.clone(),
+                value: serde_json::Value::String(v.value.clone()),
+                var_type: v.var_type.clone(),
+                enabled: v.enabled,
+            })
+            .collect(),
+        scope: Some(POSTMAN_ENVIRONMENT_SCOPE.to_string()),
+        exported_using: Some(format!("perseus/{}", env!("CARGO_PKG_VERSION"))),
+    };
+    serde_json::to_string_pretty(&export)
+        .map_err(|e| format!("Failed to serialize environment: {}", e))
+}
+
+pub fn import_postman_environment(path: &Path) -> Result<Environment, String> {
+    let contents =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    parse_postman_environment(&contents)
+}
+
+pub fn export_postman_environment(
+    env: &Environment,
+    environments: &[Environment],
+    path: &Path,
+) -> Result<(), String> {
+    let json = postman_environment_json(env, environments)?;
+    fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// `name` with every character a file name can't safely use replaced by
+/// `-`; `"imported"` when nothing usable is left.
+pub fn safe_env_name(name: &str) -> String {
+    let replaced: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '_' || c == '-' { c } else { '-' })
+        .collect();
+    let trimmed = replaced.trim_matches('-');
+    if trimmed.is_empty() {
+        "imported".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+pub(crate) fn is_safe_env_name(name: &str) -> bool {
     !name.is_empty()
         && name
             .chars()
@@ -118,59 +427,212 @@ fn is_safe_env_name(name: &str) -> bool {
 /// Replace `{{variable}}` patterns with values from the given map.
 /// Returns `(resolved_text, unresolved_variable_names)`.
 pub fn substitute(template: &str, variables: &HashMap<String, String>) -> (String, Vec<String>) {
+    substitute_with(template, |name| variables.get(name).cloned())
+}
+
+/// Like [`substitute`], but asks `resolve` for each placeholder name so
+/// values can come from somewhere other than a flat map.
+pub fn substitute_with(
+    template: &str,
+    mut resolve: impl FnMut(&str) -> Option<String>,
+) -> (String, Vec<String>) {
     let mut result = String::with_capacity(template.len());
     let mut unresolved = Vec::new();
-    let mut chars = template.chars().peekable();
-
-    while let Some(c) = chars.next() {
-        if c == '{' && chars.peek() == Some(&'{') {
-            chars.next(); // consume second '{'
-            let mut name = String::new();
-            let mut closed = false;
-            while let Some(nc) = chars.next() {
-                if nc == '}' && chars.peek() == Some(&'}') {
-                    chars.next(); // consume second '}'
-                    closed = true;
-                    break;
-                }
-                name.push(nc);
-            }
-            if closed && !name.is_empty() {
-                if let Some(val) = variables.get(&name) {
-                    result.push_str(val);
-                } else {
+    for token in tokenize(template) {
+        match token {
+            TemplateToken::Text(text) => result.push_str(text),
+            TemplateToken::Placeholder(name) => match resolve(name) {
+                Some(val) => result.push_str(&val),
+                None => {
                     result.push_str("{{");
-                    result.push_str(&name);
+                    result.push_str(name);
                     result.push_str("}}");
-                    unresolved.push(name);
+                    unresolved.push(name.to_string());
                 }
-            } else {
-                // Unclosed braces or empty name — leave as literal
+            },
+        }
+    }
+    (result, unresolved)
+}
+
+/// A piece of a template as [`substitute`] reads it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemplateToken<'a> {
+    Text(&'a str),
+    /// The name between `{{` and the first `}}` after it.
+    Placeholder(&'a str),
+}
+
+/// Splits `template` into text and `{{name}}` placeholders. Unclosed braces
+/// and `{{}}` are text.
+pub fn tokenize(template: &str) -> Vec<TemplateToken<'_>> {
+    let mut tokens = Vec::new();
+    let mut text_start = 0;
+    let mut pos = 0;
+    while let Some(found) = template[pos..].find("{{") {
+        let name_start = pos + found + 2;
+        let Some(len) = template[name_start..].find("}}") else {
+            break;
+        };
+        // An empty name stays part of the text around it
+        if len > 0 {
+            if text_start < name_start - 2 {
+                tokens.push(TemplateToken::Text(&template[text_start..name_start - 2]));
+            }
+            tokens.push(TemplateToken::Placeholder(&template[name_start..name_start + len]));
+            text_start = name_start + len + 2;
+        }
+        pos = name_start + len + 2;
+    }
+    if text_start < template.len() {
+        tokens.push(TemplateToken::Text(&template[text_start..]));
+    }
+    tokens
+}
+
+/// `text` with each `{{from}}` placeholder renamed to `{{to}}`, and how
+/// many were.
+pub fn rename_placeholder(text: &str, from: &str, to: &str) -> (String, usize) {
+    let mut result = String::with_capacity(text.len());
+    let mut count = 0;
+    for token in tokenize(text) {
+        match token {
+            TemplateToken::Text(text) => result.push_str(text),
+            TemplateToken::Placeholder(name) => {
+                let name = if name == from {
+                    count += 1;
+                    to
+                } else {
+                    name
+                };
                 result.push_str("{{");
-                result.push_str(&name);
-                if closed {
-                    // empty name case: {{}}
-                    result.push_str("}}");
-                }
+                result.push_str(name);
+                result.push_str("}}");
             }
-        } else {
-            result.push(c);
         }
     }
-    (result, unresolved)
+    (result, count)
+}
+
+/// The lines of `text`, counting from 1, that use the placeholder `name`.
+pub fn placeholder_lines(text: &str, name: &str) -> Vec<usize> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| tokenize(line).contains(&TemplateToken::Placeholder(name)))
+        .map(|(index, _)| index + 1)
+        .collect()
+}
+
+/// Placeholders `resolve` can't fill across several labelled fields: each
+/// name once, in order of first use, with the labels of the fields it's in.
+pub fn unresolved_in_fields<'a>(
+    fields: &[(&'a str, String)],
+    mut resolve: impl FnMut(&str) -> Option<String>,
+) -> Vec<(String, Vec<&'a str>)> {
+    let mut found: Vec<(String, Vec<&'a str>)> = Vec::new();
+    for (label, template) in fields {
+        let (_, unresolved) = substitute_with(template, &mut resolve);
+        for name in unresolved {
+            match found.iter_mut().find(|(known, _)| *known == name) {
+                Some((_, labels)) if !labels.contains(label) => labels.push(label),
+                Some(_) => {}
+                None => found.push((name, vec![label])),
+            }
+        }
+    }
+    found
+}
+
+// --- Inheritance ---
+
+/// `env` and the environments it extends from `environments`, the one
+/// furthest up first. Fails on a missing parent or a cycle.
+pub fn environment_chain<'a>(
+    env: &'a Environment,
+    environments: &'a [Environment],
+) -> Result<Vec<&'a Environment>, String> {
+    let mut chain = vec![env];
+    let mut current = env;
+    while let Some(parent) = current.extends.as_deref() {
+        if chain.iter().any(|e| e.name == parent) {
+            let mut names: Vec<&str> = chain.iter().map(|e| e.name.as_str()).collect();
+            names.push(parent);
+            return Err(format!(
+                "Environment '{}' extends itself: {}",
+                env.name,
+                names.join(" \u{2192} ")
+            ));
+        }
+        current = environments.iter().find(|e| e.name == parent).ok_or_else(|| {
+            format!(
+                "Environment '{}' extends '{}', which doesn't exist",
+                current.name, parent
+            )
+        })?;
+        chain.push(current);
+    }
+    chain.reverse();
+    Ok(chain)
 }
 
-/// Collect enabled variables from an environment into a lookup map.
-pub fn resolve_variables(env: Option<&Environment>) -> HashMap<String, String> {
+/// Every variable of `env` with the ones it inherits, in the order of the
+/// environment furthest up. A variable set further down replaces the one
+/// above it, enabled or not. Inherited variables come with the name of the
+/// environment they are from.
+pub fn chain_variables<'a>(
+    env: &'a Environment,
+    environments: &'a [Environment],
+) -> Result<Vec<(&'a EnvironmentVariable, Option<&'a str>)>, String> {
+    let mut vars: Vec<(&EnvironmentVariable, Option<&str>)> = Vec::new();
+    for link in environment_chain(env, environments)? {
+        let from = (link.name != env.name).then_some(link.name.as_str());
+        for var in &link.values {
+            match vars.iter_mut().find(|(v, _)| v.key == var.key) {
+                Some(existing) => *existing = (var, from),
+                None => vars.push((var, from)),
+            }
+        }
+    }
+    Ok(vars)
+}
+
+/// Collect enabled variables from an environment and the ones it extends
+/// into a lookup map.
+pub fn resolve_variables(
+    env: Option<&Environment>,
+    environments: &[Environment],
+) -> Result<HashMap<String, String>, String> {
     let mut vars = HashMap::new();
     if let Some(env) = env {
-        for var in &env.values {
+        for (var, _) in chain_variables(env, environments)? {
             if var.enabled {
                 vars.insert(var.key.clone(), var.value.clone());
             }
         }
     }
-    vars
+    Ok(vars)
+}
+
+/// Shown instead of a secret variable's value.
+pub const SECRET_MASK: &str = "\u{2022}\u{2022}\u{2022}\u{2022}\u{2022}\u{2022}";
+
+/// Replaces the values of `secret`-typed variables in `vars` for display,
+/// inherited ones included.
+pub fn mask_secrets(
+    vars: &mut HashMap<String, String>,
+    env: Option<&Environment>,
+    environments: &[Environment],
+) {
+    let Some(env) = env else {
+        return;
+    };
+    let chain = chain_variables(env, environments)
+        .unwrap_or_else(|_| env.values.iter().map(|var| (var, None)).collect());
+    for (var, _) in chain.into_iter().filter(|(v, _)| v.var_type == "secret") {
+        if let Some(value) = vars.get_mut(&var.key) {
+            *value = SECRET_MASK.to_string();
+        }
+    }
 }
 
 #[cfg(test)]
@@ -192,6 +654,8 @@ mod tests {
                     var_type: "secret".to_string(),
                 },
             ],
+            extends: None,
+            scope: EnvScope::Project,
         };
 
         let json = serde_json::to_string_pretty(&env).unwrap();
@@ -210,7 +674,7 @@ mod tests {
     fn test_environment_deserialize_with_defaults() {
         let json = r#"{"name":"test","values":[{"key":"k","value":"v"}]}"#;
         let env: Environment = serde_json::from_str(json).unwrap();
-        assert_eq!(env.values[0].enabled, true);
+        assert!(env.values[0].enabled);
         assert_eq!(env.values[0].var_type, "default");
     }
 
@@ -292,6 +756,110 @@ mod tests {
         assert_eq!(result, "{{}}");
     }
 
+    #[test]
+    fn test_tokenize_matches_substitute_rules() {
+        use TemplateToken::{Placeholder, Text};
+        assert_eq!(
+            tokenize("a{{x}}b{{}}c{{{y}}}"),
+            vec![Text("a"), Placeholder("x"), Text("b{{}}c"), Placeholder("{y"), Text("}")]
+        );
+        assert_eq!(tokenize("{{a}}{{b}}"), vec![Placeholder("a"), Placeholder("b")]);
+        assert_eq!(tokenize("x {{open"), vec![Text("x {{open")]);
+        assert!(tokenize("").is_empty());
+    }
+
+    #[test]
+    fn test_placeholder_lines_skip_larger_tokens() {
+        let text = "{{api_key_v2}}\n{{my_api_key}}\n{{ api_key }}\n{{api_key\n\
+                    X-Key: {{api_key}}\n{{{api_key}}}\n{{a}}{{api_key}}";
+        assert_eq!(placeholder_lines(text, "api_key"), vec![5, 7]);
+        assert_eq!(placeholder_lines(text, "api_key_v2"), vec![1]);
+        assert!(placeholder_lines("", "api_key").is_empty());
+    }
+
+    #[test]
+    fn test_rename_placeholder_only_whole_names() {
+        let (text, count) =
+            rename_placeholder("{{base_url}}/x?{{base_url_v2}}&{{base_url}}{{", "base_url", "api");
+        assert_eq!(text, "{{api}}/x?{{base_url_v2}}&{{api}}{{");
+        assert_eq!(count, 2);
+        assert_eq!(rename_placeholder("{{}} {x}", "x", "y"), ("{{}} {x}".to_string(), 0));
+    }
+
+    #[test]
+    fn test_plan_variable_rename_across_dirs() {
+        let root = std::env::temp_dir().join(format!("perseus-rename-{}", uuid::Uuid::new_v4()));
+        let (project_dir, user_dir) = (root.join("project"), root.join("user"));
+        fs::create_dir_all(&project_dir).unwrap();
+        fs::create_dir_all(&user_dir).unwrap();
+        let write = |path: PathBuf, values: Vec<EnvironmentVariable>| {
+            let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+            let env = Environment {
+                name,
+                values,
+                extends: None,
+                scope: EnvScope::Project,
+            };
+            fs::write(path, serde_json::to_string_pretty(&env).unwrap()).unwrap();
+        };
+        write(
+            project_dir.join("dev.json"),
+            vec![
+                EnvironmentVariable::new("base_url", "http://localhost"),
+                EnvironmentVariable::new("users", "{{base_url}}/users"),
+            ],
+        );
+        write(project_dir.join("prod.json"), vec![EnvironmentVariable::new("other", "1")]);
+        write(user_dir.join("dev.json"), vec![EnvironmentVariable::new("base_url", "http://me")]);
+        let dirs = [project_dir.as_path(), user_dir.as_path()];
+
+        let rename = plan_variable_rename_in(&dirs, "base_url", "api_base").unwrap();
+        assert_eq!(rename.occurrences, 3);
+        assert_eq!(rename.paths().count(), 2);
+        rename.apply().unwrap();
+        let dev = load_environment(&project_dir.join("dev.json")).unwrap();
+        assert_eq!(dev.values[0].key, "api_base");
+        assert_eq!(dev.values[1].value, "{{api_base}}/users");
+        let user = load_environment(&user_dir.join("dev.json")).unwrap();
+        assert_eq!(user.values[0].key, "api_base");
+
+        // The target name is taken
+        let err = plan_variable_rename_in(&dirs, "other", "api_base").unwrap_err();
+        assert!(err.contains("already exists"));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_substitute_with_resolver() {
+        let (result, unresolved) = substitute_with("{{a}}-{{b}}", |name| {
+            (name == "a").then(|| "1".to_string())
+        });
+        assert_eq!(result, "1-{{b}}");
+        assert_eq!(unresolved, vec!["b"]);
+    }
+
+    #[test]
+    fn test_unresolved_in_fields_aggregates() {
+        let mut vars = HashMap::new();
+        vars.insert("host".to_string(), "api.test".to_string());
+        let fields = vec![
+            ("URL", "https://{{host}}/{{basUrl}}/users/{{id}}".to_string()),
+            ("Headers", "X-Id: {{id}}\nX-Trace: {{trace}}".to_string()),
+            ("Body", "{\"id\": \"{{id}}\"}".to_string()),
+            ("Auth", "{{host}}".to_string()),
+        ];
+        let found = unresolved_in_fields(&fields, |name| vars.get(name).cloned());
+        assert_eq!(
+            found,
+            vec![
+                ("basUrl".to_string(), vec!["URL"]),
+                ("id".to_string(), vec!["URL", "Headers", "Body"]),
+                ("trace".to_string(), vec!["Headers"]),
+            ]
+        );
+        assert!(unresolved_in_fields(&fields[3..], |name| vars.get(name).cloned()).is_empty());
+    }
+
     #[test]
     fn test_resolve_variables_enabled_only() {
         let env = Environment {
@@ -305,18 +873,259 @@ mod tests {
                     var_type: "default".to_string(),
                 },
             ],
+            extends: None,
+            scope: EnvScope::Project,
         };
-        let vars = resolve_variables(Some(&env));
+        let vars = resolve_variables(Some(&env), &[]).unwrap();
         assert_eq!(vars.get("enabled_var"), Some(&"yes".to_string()));
         assert_eq!(vars.get("disabled_var"), None);
     }
 
+    #[test]
+    fn test_mask_secrets() {
+        let env = Environment {
+            name: "test".to_string(),
+            values: vec![
+                EnvironmentVariable::new("host", "api.test"),
+                EnvironmentVariable {
+                    key: "token".to_string(),
+                    value: "s3cret".to_string(),
+                    enabled: true,
+                    var_type: "secret".to_string(),
+                },
+            ],
+            extends: None,
+            scope: EnvScope::Project,
+        };
+        let mut vars = resolve_variables(Some(&env), &[]).unwrap();
+        mask_secrets(&mut vars, Some(&env), &[]);
+        assert_eq!(vars["host"], "api.test");
+        assert_eq!(vars["token"], SECRET_MASK);
+    }
+
+    #[test]
+    fn test_load_layered_environments() {
+        let root = std::env::temp_dir().join(format!("perseus-envs-{}", uuid::Uuid::new_v4()));
+        let project_dir = root.join("project");
+        let user_dir = root.join("user");
+        fs::create_dir_all(&project_dir).unwrap();
+        fs::create_dir_all(&user_dir).unwrap();
+        let write = |dir: &Path, name: &str, values: &str| {
+            let json = format!(r#"{{"name": "{}", "values": {}}}"#, name, values);
+            fs::write(dir.join(format!("{}.json", name)), json).unwrap();
+        };
+        write(
+            &project_dir,
+            "dev",
+            r#"[{"key": "base_url", "value": "http://dev"}, {"key": "token", "value": ""}]"#,
+        );
+        write(&project_dir, "prod", r#"[{"key": "base_url", "value": "https://prod"}]"#);
+        write(
+            &user_dir,
+            "dev",
+            r#"[
+                {"key": "token", "value": "mine", "type": "secret"},
+                {"key": "me", "value": "ada"}
+            ]"#,
+        );
+        write(&user_dir, "scratch", r#"[]"#);
+
+        let envs = load_layered_environments(Some(&project_dir), Some(&user_dir)).unwrap();
+        let summary: Vec<_> = envs.iter().map(|e| (e.name.as_str(), e.scope)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("dev", EnvScope::Layered),
+                ("prod", EnvScope::Project),
+                ("scratch", EnvScope::User),
+            ]
+        );
+        let vars = resolve_variables(Some(&envs[0]), &envs).unwrap();
+        assert_eq!(vars["base_url"], "http://dev");
+        assert_eq!(vars["token"], "mine");
+        assert_eq!(vars["me"], "ada");
+        assert_eq!(envs[0].values[1].var_type, "secret");
+
+        let missing = root.join("missing");
+        let only_user = load_layered_environments(Some(&missing), Some(&user_dir)).unwrap();
+        assert_eq!(only_user.len(), 2);
+        assert!(load_layered_environments(None, None).unwrap().is_empty());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    fn env_extending(name: &str, extends: Option<&str>, values: &[(&str, &str)]) -> Environment {
+        Environment {
+            name: name.to_string(),
+            values: values.iter().map(|(k, v)| EnvironmentVariable::new(k, v)).collect(),
+            extends: extends.map(str::to_string),
+            scope: EnvScope::Project,
+        }
+    }
+
+    #[test]
+    fn test_resolve_variables_chain_of_three() {
+        let envs = vec![
+            env_extending("base", None, &[("host", "api.example"), ("retries", "3")]),
+            env_extending("staging", Some("base"), &[("host", "staging.example"), ("tier", "s")]),
+            env_extending("staging-eu", Some("staging"), &[("region", "eu")]),
+        ];
+        let chain: Vec<_> = environment_chain(&envs[2], &envs)
+            .unwrap()
+            .iter()
+            .map(|e| e.name.as_str())
+            .collect();
+        assert_eq!(chain, vec!["base", "staging", "staging-eu"]);
+
+        let vars = resolve_variables(Some(&envs[2]), &envs).unwrap();
+        assert_eq!(vars["host"], "staging.example");
+        assert_eq!(vars["retries"], "3");
+        assert_eq!(vars["tier"], "s");
+        assert_eq!(vars["region"], "eu");
+
+        let origins: Vec<_> = chain_variables(&envs[2], &envs)
+            .unwrap()
+            .into_iter()
+            .map(|(var, from)| (var.key.as_str(), from))
+            .collect();
+        assert_eq!(
+            origins,
+            vec![
+                ("host", Some("staging")),
+                ("retries", Some("base")),
+                ("tier", Some("staging")),
+                ("region", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_variables_detects_cycles() {
+        let envs = vec![
+            env_extending("a", Some("c"), &[]),
+            env_extending("b", Some("a"), &[]),
+            env_extending("c", Some("b"), &[]),
+            env_extending("self", Some("self"), &[]),
+        ];
+        let err = resolve_variables(Some(&envs[0]), &envs).unwrap_err();
+        assert!(err.contains("'a' extends itself"), "{}", err);
+        assert!(err.contains("a \u{2192} c \u{2192} b \u{2192} a"), "{}", err);
+        assert!(environment_chain(&envs[3], &envs).is_err());
+    }
+
+    #[test]
+    fn test_resolve_variables_missing_parent() {
+        let envs = vec![
+            env_extending("prod", Some("shared"), &[("host", "prod.example")]),
+            env_extending("shared", Some("gone"), &[]),
+        ];
+        let err = resolve_variables(Some(&envs[0]), &envs).unwrap_err();
+        assert_eq!(err, "Environment 'shared' extends 'gone', which doesn't exist");
+    }
+
+    #[test]
+    fn test_extends_is_optional_in_files() {
+        let env: Environment = serde_json::from_str(r#"{"name":"dev","values":[]}"#).unwrap();
+        assert!(env.extends.is_none());
+        assert!(!serde_json::to_string(&env).unwrap().contains("extends"));
+
+        let child = env_extending("dev", Some("base"), &[]);
+        let json = serde_json::to_string(&child).unwrap();
+        let parsed: Environment = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.extends.as_deref(), Some("base"));
+    }
+
     #[test]
     fn test_resolve_variables_none() {
-        let vars = resolve_variables(None);
+        let vars = resolve_variables(None, &[]).unwrap();
         assert!(vars.is_empty());
     }
 
+    // --- Postman export tests ---
+
+    const POSTMAN_EXPORT: &str = r#"{
+        "id": "5f1c2b9e-8a0d-4a53-9f0e-6a8d1e2f3b4c",
+        "name": "Staging API",
+        "values": [
+            {
+                "key": "base_url",
+                "value": "https://staging.example.com",
+                "type": "default",
+                "enabled": true
+            },
+            {"key": "api_token", "value": "s3cr3t", "type": "secret", "enabled": true},
+            {"key": "retries", "value": 3, "enabled": false},
+            {"key": "empty", "value": null}
+        ],
+        "_postman_variable_scope": "environment",
+        "_postman_exported_at": "2024-05-02T09:12:44.123Z",
+        "_postman_exported_using": "Postman/10.24.3"
+    }"#;
+
+    #[test]
+    fn test_parse_postman_environment() {
+        let env = parse_postman_environment(POSTMAN_EXPORT).unwrap();
+        assert_eq!(env.name, "Staging-API");
+        let summary: Vec<_> = env
+            .values
+            .iter()
+            .map(|v| (v.key.as_str(), v.value.as_str(), v.var_type.as_str(), v.enabled))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("base_url", "https://staging.example.com", "default", true),
+                ("api_token", "s3cr3t", "secret", true),
+                ("retries", "3", "default", false),
+                ("empty", "", "default", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_postman_environment_round_trip() {
+        let env = parse_postman_environment(POSTMAN_EXPORT).unwrap();
+        let json = postman_environment_json(&env, &[]).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["_postman_variable_scope"], "environment");
+        assert_eq!(parsed["values"][1]["type"], "secret");
+        assert_eq!(parsed["values"][2]["enabled"], false);
+
+        let again = parse_postman_environment(&json).unwrap();
+        assert_eq!(again.name, env.name);
+        assert_eq!(
+            serde_json::to_value(&again.values).unwrap(),
+            serde_json::to_value(&env.values).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_postman_export_flattens_inheritance() {
+        let envs = vec![
+            env_extending("base", None, &[("host", "api.example"), ("retries", "3")]),
+            env_extending("prod", Some("base"), &[("host", "prod.example")]),
+        ];
+        let json = postman_environment_json(&envs[1], &envs).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["values"].as_array().unwrap().len(), 2);
+        assert_eq!(parsed["values"][0]["value"], "prod.example");
+        assert_eq!(parsed["values"][1]["key"], "retries");
+        assert!(parsed.get("extends").is_none());
+    }
+
+    #[test]
+    fn test_parse_postman_environment_rejects_globals() {
+        let globals = r#"{"name": "Globals", "values": [], "_postman_variable_scope": "globals"}"#;
+        assert!(parse_postman_environment(globals).unwrap_err().contains("globals"));
+        assert!(parse_postman_environment("[]").is_err());
+    }
+
+    #[test]
+    fn test_safe_env_name_replaces_unsafe_characters() {
+        assert_eq!(safe_env_name("Staging API"), "Staging-API");
+        assert_eq!(safe_env_name(" prod/eu "), "prod-eu");
+        assert_eq!(safe_env_name("***"), "imported");
+    }
+
     #[test]
     fn test_safe_env_name() {
         assert!(is_safe_env_name("dev"));
@@ -0,0 +1,204 @@
+//! One writer per project: `.perseus/perseus.lock` holds the pid and start
+//! time of the instance that writes the collection and the session. A
+//! second instance that finds a running owner opens read-only instead of
+//! overwriting what the first one saves.
+
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::storage::project::write_atomic;
+
+pub const LOCK_FILE_NAME: &str = "perseus.lock";
+
+/// The instance a lock file names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockOwner {
+    pub pid: u32,
+    /// When it took the lock, in seconds since the Unix epoch.
+    pub since: u64,
+}
+
+impl LockOwner {
+    fn current() -> Self {
+        let since = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Self {
+            pid: process::id(),
+            since,
+        }
+    }
+
+    /// "pid 4242, 12 min ago".
+    pub fn describe(&self) -> String {
+        let now = LockOwner::current().since;
+        let minutes = now.saturating_sub(self.since) / 60;
+        let age = match minutes {
+            0 => "just now".to_string(),
+            1..=119 => format!("{} min ago", minutes),
+            _ => format!("{} h ago", minutes / 60),
+        };
+        format!("pid {}, {}", self.pid, age)
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        let mut lines = text.lines().map(str::trim);
+        let pid = lines.next()?.parse().ok()?;
+        let since = lines.next()?.parse().ok()?;
+        Some(Self { pid, since })
+    }
+
+    fn contents(&self) -> String {
+        format!("{}\n{}\n", self.pid, self.since)
+    }
+}
+
+/// The lock file while this instance holds it. Dropping it removes the
+/// file, unless another instance has taken it over since.
+#[derive(Debug)]
+pub struct ProjectLock {
+    path: PathBuf,
+}
+
+impl ProjectLock {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ProjectLock {
+    fn drop(&mut self) {
+        release_lock_at(&self.path);
+    }
+}
+
+#[derive(Debug)]
+pub enum LockStatus {
+    Acquired(ProjectLock),
+    /// A running instance holds the lock.
+    Held(LockOwner),
+    /// The lock was left by an instance that is gone, or can't be read.
+    Stale(Option<LockOwner>),
+}
+
+/// Takes the lock in `dir` if nobody holds it.
+pub fn acquire_lock(dir: &Path) -> Result<LockStatus, String> {
+    let path = dir.join(LOCK_FILE_NAME);
+    let created = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(LockOwner::current().contents().as_bytes()));
+    match created {
+        Ok(()) => return Ok(LockStatus::Acquired(ProjectLock { path })),
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+        Err(e) => return Err(format!("Failed to create {}: {}", path.display(), e)),
+    }
+    let owner = read_owner(&path);
+    Ok(match owner {
+        // Left by an earlier process that had this pid, or by this one
+        Some(owner) if owner.pid == process::id() => {
+            write_lock(&path)?;
+            LockStatus::Acquired(ProjectLock { path })
+        }
+        Some(owner) if pid_alive(owner.pid) => LockStatus::Held(owner),
+        _ => LockStatus::Stale(owner),
+    })
+}
+
+/// Replaces a stale lock in `dir` with this instance's.
+pub fn take_over_lock(dir: &Path) -> Result<ProjectLock, String> {
+    let path = dir.join(LOCK_FILE_NAME);
+    write_lock(&path)?;
+    Ok(ProjectLock { path })
+}
+
+/// Removes the lock file at `path` if this process holds it. Safe to call
+/// more than once, and from the panic hook.
+pub fn release_lock_at(path: &Path) {
+    if read_owner(path).is_some_and(|owner| owner.pid == process::id()) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+fn write_lock(path: &Path) -> Result<(), String> {
+    write_atomic(path, LockOwner::current().contents().as_bytes(), false)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn read_owner(path: &Path) -> Option<LockOwner> {
+    LockOwner::parse(&fs::read_to_string(path).ok()?)
+}
+
+/// Whether a process with this pid is running. Where that can't be told
+/// the owner is taken to be alive, which errs on the side of read-only.
+#[cfg(target_os = "linux")]
+fn pid_alive(pid: u32) -> bool {
+    Path::new("/proc").join(pid.to_string()).exists()
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn pid_alive(pid: u32) -> bool {
+    process::Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stderr(process::Stdio::null())
+        .status()
+        .map_or(true, |status| status.success())
+}
+
+#[cfg(not(unix))]
+fn pid_alive(_pid: u32) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_second_instance_finds_the_lock_held() {
+        let dir = env::temp_dir().join(format!("perseus-lock-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(LOCK_FILE_NAME);
+
+        let LockStatus::Acquired(lock) = acquire_lock(&dir).unwrap() else {
+            panic!("expected the lock to be free");
+        };
+        assert_eq!(read_owner(&path).unwrap().pid, process::id());
+
+        // A running owner other than us holds it; pid 1 always runs
+        fs::write(&path, "1\n1700000000\n").unwrap();
+        let owner = LockOwner {
+            pid: 1,
+            since: 1_700_000_000,
+        };
+        assert!(matches!(acquire_lock(&dir).unwrap(), LockStatus::Held(o) if o == owner));
+        // Not ours, so dropping our handle leaves it alone
+        drop(lock);
+        assert!(path.exists());
+
+        // An owner that has exited leaves a stale lock
+        let mut child = process::Command::new("true").spawn().unwrap();
+        let dead = child.id();
+        child.wait().unwrap();
+        fs::write(&path, format!("{}\n1700000000\n", dead)).unwrap();
+        let status = acquire_lock(&dir).unwrap();
+        assert!(matches!(status, LockStatus::Stale(Some(o)) if o.pid == dead));
+        fs::write(&path, "garbage").unwrap();
+        assert!(matches!(
+            acquire_lock(&dir).unwrap(),
+            LockStatus::Stale(None)
+        ));
+
+        let lock = take_over_lock(&dir).unwrap();
+        assert_eq!(read_owner(lock.path()).unwrap().pid, process::id());
+        drop(lock);
+        assert!(!path.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
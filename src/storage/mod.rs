@@ -1,32 +1,54 @@
 #![allow(unused)]
 
+pub mod archive;
+mod changelog;
 mod collection;
+mod debounce;
+mod draft;
 pub mod environment;
+mod lock;
 mod migrate;
 mod models;
+pub mod openapi;
 mod postman;
 mod project;
+pub mod sample;
 mod session_state;
 mod ui_state;
 
+pub use changelog::{
+    append_changes, item_path, read_changes, summarize_layout_change, ChangeEntry, ChangeKind,
+    ItemLayout,
+};
 pub use collection::{
-    parse_headers, CollectionStore, NodeKind, ProjectInfo, ProjectTree, RequestFile, TreeNode,
+    parse_headers, CollectionStore, LoadError, NodeKind, ProjectInfo, ProjectTree, RequestFile,
+    RequestFileError, TreeNode, TEMPLATES_FOLDER,
 };
+pub use debounce::{shutdown_writes, PendingWrite, WriteDebounce};
+pub use draft::{delete_draft, draft_changes, load_drafts, save_draft, Draft};
 pub use environment::{
     delete_environment_file, load_all_environments, save_environment, Environment,
     EnvironmentVariable,
 };
+pub use lock::{
+    acquire_lock, release_lock_at, take_over_lock, LockOwner, LockStatus, ProjectLock,
+    LOCK_FILE_NAME,
+};
 pub use postman::{
-    PostmanAuth, PostmanBody, PostmanFormParam, PostmanHeader, PostmanItem, PostmanKvPair,
-    PostmanRequest,
+    format_status_codes, format_tags, parse_status_codes, parse_tags, CompressSetting,
+    HttpVersionSetting, ItemTag, PostmanAuth, PostmanBody, PostmanExample, PostmanFormParam,
+    PostmanHeader, PostmanItem, PostmanKvPair, PostmanRequest, PollSetting, PollUntil,
+    ProxySetting, RedirectSetting, RequestSettings, SchemaSetting, TagColor, TlsSetting, MAX_TAGS,
 };
 pub use models::SavedRequest;
 pub use project::{
-    collection_path, ensure_environments_dir, ensure_storage_dir, environments_dir,
-    find_project_root, project_root_key, requests_dir, storage_dir, ui_state_path,
+    changelog_path, collection_path, ensure_environments_dir, ensure_storage_dir, environments_dir,
+    find_project_root, init_project_root, overridden_root_name, project_root_key, requests_dir,
+    storage_dir, ui_state_path, ROOT_ENV_VAR,
 };
 pub use session_state::{
-    load_session_for_root, load_sessions, save_session_for_root, save_sessions, SessionState,
-    SessionStore,
+    load_session_for_root, load_sessions, load_trust_for_root, save_session_for_root,
+    save_sessions, save_trust_for_root, CursorPosition, LastRun, ProjectSidebarState,
+    RequestPosition, SendStat, SessionState, SessionStore,
 };
 pub use ui_state::{load_ui_state, save_ui_state, UiState};
@@ -0,0 +1,716 @@
+//! OpenAPI 3.x descriptions, in JSON or YAML, imported as a project: a
+//! folder per tag (or per first path segment for untagged operations) and
+//! a request per operation. Requests keep their `operationId`, so importing
+//! a newer version of the spec can update them instead of adding copies.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+use crate::storage::archive::ProjectArchive;
+use crate::storage::collection::CollectionStore;
+use crate::storage::postman::{
+    PostmanBody, PostmanHeader, PostmanItem, PostmanKvPair, PostmanRequest,
+};
+use crate::yaml;
+
+const METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+/// How deep request schemas are followed when building a body skeleton.
+const MAX_SCHEMA_DEPTH: usize = 8;
+
+/// A parsed spec, ready to install as a project or to update one.
+#[derive(Debug, Clone)]
+pub struct OpenApiImport {
+    pub archive: ProjectArchive,
+    pub operations: usize,
+    /// Parts of the spec left out, with how often they occurred.
+    pub skipped: BTreeMap<&'static str, usize>,
+}
+
+/// What [`update_from_spec`] changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpecUpdate {
+    pub updated: usize,
+    pub added: usize,
+}
+
+impl OpenApiImport {
+    /// ", skipped 2 callbacks and 1 webhook", or nothing.
+    pub fn skipped_summary(&self) -> String {
+        let parts: Vec<String> = self
+            .skipped
+            .iter()
+            .map(|(what, n)| format!("{} {}{}", n, what, if *n == 1 { "" } else { "s" }))
+            .collect();
+        match parts.split_last() {
+            None => String::new(),
+            Some((last, [])) => format!(", skipped {}", last),
+            Some((last, rest)) => format!(", skipped {} and {}", rest.join(", "), last),
+        }
+    }
+
+    fn requests(&self) -> impl Iterator<Item = (Option<&str>, &PostmanItem)> {
+        self.archive.project.item.iter().flat_map(|item| {
+            if item.is_request() {
+                vec![(None, item)]
+            } else {
+                item.item
+                    .iter()
+                    .map(|child| (Some(item.name.as_str()), child))
+                    .collect()
+            }
+        })
+    }
+}
+
+/// Whether `path` holds an OpenAPI or Swagger document rather than a
+/// Postman collection: any YAML file, or JSON with an `openapi` key.
+pub fn is_spec_file(path: &Path) -> bool {
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    match ext.as_str() {
+        "yaml" | "yml" => true,
+        "json" => fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str::<Value>(&text).ok())
+            .is_some_and(|doc| doc.get("openapi").is_some() || doc.get("swagger").is_some()),
+        _ => false,
+    }
+}
+
+pub fn read_openapi_spec(path: &Path) -> Result<OpenApiImport, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let name = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("API");
+    parse_openapi(&text, name)
+}
+
+fn parse_openapi(text: &str, fallback_name: &str) -> Result<OpenApiImport, String> {
+    let doc: Value = if text.trim_start().starts_with('{') {
+        serde_json::from_str(text).map_err(|e| format!("Invalid JSON: {}", e))?
+    } else {
+        yaml::parse(text)?
+    };
+    let version = match &doc["openapi"] {
+        Value::String(version) => version.clone(),
+        Value::Number(version) => version.to_string(),
+        _ if doc.get("swagger").is_some() => {
+            return Err("Swagger 2.0 isn't supported; convert the spec to OpenAPI 3".to_string())
+        }
+        _ => return Err("Not an OpenAPI document: there is no \"openapi\" version".to_string()),
+    };
+    if !version.starts_with("3.") {
+        return Err(format!("OpenAPI {} isn't supported (only 3.x)", version));
+    }
+
+    let title = doc["info"]["title"].as_str().map(str::trim).unwrap_or("");
+    let mut project = PostmanItem::new_folder(
+        if title.is_empty() {
+            fallback_name
+        } else {
+            title
+        }
+        .to_string(),
+    );
+    let mut skipped = BTreeMap::new();
+    if let Some(webhooks) = doc["webhooks"].as_object().filter(|w| !w.is_empty()) {
+        skipped.insert("webhook", webhooks.len());
+    }
+
+    let mut folders: Vec<PostmanItem> = Vec::new();
+    let mut operations = 0;
+    for (path, path_item) in doc["paths"].as_object().into_iter().flatten() {
+        let path_item = resolve(&doc, path_item);
+        for method in METHODS {
+            let Some(operation) = path_item.get(method).filter(|op| op.is_object()) else {
+                continue;
+            };
+            operations += 1;
+            if let Some(callbacks) = operation["callbacks"].as_object() {
+                *skipped.entry("callback").or_insert(0) += callbacks.len();
+            }
+            let item = operation_item(&doc, path, method, path_item, operation, &mut skipped);
+            let group = operation["tags"][0]
+                .as_str()
+                .map(str::to_string)
+                .or_else(|| path_prefix(path));
+            match group {
+                Some(group) => match folders.iter_mut().find(|folder| folder.name == group) {
+                    Some(folder) => folder.item.push(item),
+                    None => {
+                        let mut folder = PostmanItem::new_folder(group);
+                        folder.item.push(item);
+                        folders.push(folder);
+                    }
+                },
+                None => project.item.push(item),
+            }
+        }
+    }
+    if operations == 0 {
+        return Err("The spec has no operations under \"paths\"".to_string());
+    }
+    project.item.extend(folders);
+    Ok(OpenApiImport {
+        archive: ProjectArchive {
+            project,
+            environments: Vec::new(),
+            session: None,
+        },
+        operations,
+        skipped,
+    })
+}
+
+/// The first literal segment of `path`: `users` for `/users/{id}`.
+fn path_prefix(path: &str) -> Option<String> {
+    path.split('/')
+        .find(|segment| !segment.is_empty())
+        .filter(|segment| !segment.starts_with('{'))
+        .map(str::to_string)
+}
+
+/// Follows local `$ref`s (`#/components/...`); anything else is returned
+/// as it is.
+fn resolve<'a>(doc: &'a Value, value: &'a Value) -> &'a Value {
+    let mut value = value;
+    for _ in 0..16 {
+        match value["$ref"].as_str().and_then(|r| r.strip_prefix('#')) {
+            Some(pointer) => match doc.pointer(pointer) {
+                Some(target) => value = target,
+                None => return &Value::Null,
+            },
+            None => break,
+        }
+    }
+    value
+}
+
+fn operation_item(
+    doc: &Value,
+    path: &str,
+    method: &str,
+    path_item: &Value,
+    operation: &Value,
+    skipped: &mut BTreeMap<&'static str, usize>,
+) -> PostmanItem {
+    let base = [
+        &operation["servers"],
+        &path_item["servers"],
+        &doc["servers"],
+    ]
+    .into_iter()
+    .find_map(server_url)
+    .unwrap_or_else(|| "{{baseUrl}}".to_string());
+    let mut url = format!("{}{}", base, path.replace('{', "{{").replace('}', "}}"));
+
+    // Operation parameters replace path-level ones of the same name and place
+    let mut parameters: Vec<&Value> = Vec::new();
+    for parameter in [&path_item["parameters"], &operation["parameters"]]
+        .into_iter()
+        .filter_map(Value::as_array)
+        .flatten()
+    {
+        let parameter = resolve(doc, parameter);
+        let key = |p: &Value| (p["name"].clone(), p["in"].clone());
+        parameters.retain(|existing| key(existing) != key(parameter));
+        parameters.push(parameter);
+    }
+    let mut query = Vec::new();
+    let mut headers = Vec::new();
+    for parameter in parameters {
+        let name = parameter["name"].as_str().unwrap_or_default().to_string();
+        let value = parameter_value(doc, parameter);
+        match parameter["in"].as_str() {
+            Some("query") => query.push(format!("{}={}", name, value)),
+            // Set from the request's body and auth instead, as the spec says
+            Some("header")
+                if ["accept", "content-type", "authorization"]
+                    .contains(&name.to_ascii_lowercase().as_str()) => {}
+            Some("header") => headers.push(PostmanHeader {
+                key: name,
+                value,
+                disabled: None,
+            }),
+            Some("cookie") => *skipped.entry("cookie parameter").or_insert(0) += 1,
+            _ => {}
+        }
+    }
+    if !query.is_empty() {
+        url.push('?');
+        url.push_str(&query.join("&"));
+    }
+
+    let mut body = None;
+    let request_body = resolve(doc, &operation["requestBody"]);
+    if let Some(content) = request_body["content"]
+        .as_object()
+        .filter(|c| !c.is_empty())
+    {
+        let json = content
+            .iter()
+            .find(|(media, _)| media.starts_with("application/json") || media.ends_with("+json"));
+        let form = content.get("application/x-www-form-urlencoded");
+        let content_type = if let Some((media, spec)) = json {
+            let example = spec.get("example").cloned();
+            let value = example.unwrap_or_else(|| skeleton(doc, &spec["schema"], 0));
+            let text = serde_json::to_string_pretty(&value).unwrap_or_default();
+            body = Some(PostmanBody::json(&text));
+            media.clone()
+        } else if let Some(spec) = form {
+            let pairs = match skeleton(doc, &spec["schema"], 0) {
+                Value::Object(fields) => fields
+                    .into_iter()
+                    .map(|(key, value)| PostmanKvPair {
+                        key,
+                        value: scalar_text(&value),
+                        disabled: None,
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            };
+            body = Some(PostmanBody::urlencoded(pairs));
+            "application/x-www-form-urlencoded".to_string()
+        } else {
+            *skipped.entry("non-JSON request body").or_insert(0) += 1;
+            content.keys().next().cloned().unwrap_or_default()
+        };
+        headers.insert(
+            0,
+            PostmanHeader {
+                key: "Content-Type".to_string(),
+                value: content_type,
+                disabled: None,
+            },
+        );
+    }
+
+    let method = method.to_ascii_uppercase();
+    let name = [&operation["summary"], &operation["operationId"]]
+        .into_iter()
+        .filter_map(Value::as_str)
+        .map(str::trim)
+        .find(|name| !name.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{} {}", method, path));
+    let mut request = PostmanRequest::new(method, url, headers, None);
+    request.body = body;
+    let mut item = PostmanItem::new_request(name, request);
+    item.description = operation["description"]
+        .as_str()
+        .filter(|text| !text.trim().is_empty())
+        .map(Value::from);
+    item.operation_id = operation["operationId"].as_str().map(str::to_string);
+    item
+}
+
+/// The first server's URL with its `{variables}` set to their defaults and
+/// no trailing slash. Relative URLs go after `{{baseUrl}}`.
+fn server_url(servers: &Value) -> Option<String> {
+    let server = servers.get(0)?;
+    let mut url = server["url"].as_str()?.to_string();
+    for (name, variable) in server["variables"].as_object().into_iter().flatten() {
+        if let Some(default) = variable["default"].as_str() {
+            url = url.replace(&format!("{{{}}}", name), default);
+        }
+    }
+    let url = url.trim_end_matches('/');
+    Some(if url.contains("://") {
+        url.to_string()
+    } else {
+        format!("{{{{baseUrl}}}}{}", url)
+    })
+}
+
+/// A parameter's example or default, or nothing to fill in.
+fn parameter_value(doc: &Value, parameter: &Value) -> String {
+    let schema = resolve(doc, &parameter["schema"]);
+    let first_example = parameter["examples"]
+        .as_object()
+        .and_then(|examples| examples.values().next())
+        .map(|example| &resolve(doc, example)["value"]);
+    [
+        parameter.get("example"),
+        first_example,
+        schema.get("example"),
+        schema.get("default"),
+        schema["enum"].get(0),
+    ]
+    .into_iter()
+    .flatten()
+    .find(|value| !value.is_null())
+    .map(scalar_text)
+    .unwrap_or_default()
+}
+
+fn scalar_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// A value shaped like `schema` holding only what it requires: examples
+/// and defaults where given, otherwise empty strings, zeros and `false`.
+fn skeleton(doc: &Value, schema: &Value, depth: usize) -> Value {
+    let schema = resolve(doc, schema);
+    if depth > MAX_SCHEMA_DEPTH {
+        return Value::Null;
+    }
+    if let Some(value) = ["example", "default"]
+        .iter()
+        .find_map(|key| schema.get(*key))
+    {
+        return value.clone();
+    }
+    if let Some(first) = schema["enum"].get(0) {
+        return first.clone();
+    }
+    if let Some(first) = schema["oneOf"].get(0).or_else(|| schema["anyOf"].get(0)) {
+        return skeleton(doc, first, depth + 1);
+    }
+    let kind = match &schema["type"] {
+        Value::String(kind) => kind.as_str(),
+        Value::Array(kinds) => kinds
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|k| *k != "null")
+            .unwrap_or(""),
+        _ if schema.get("properties").is_some() || schema.get("allOf").is_some() => "object",
+        _ => "",
+    };
+    match kind {
+        "object" => {
+            let mut properties = Map::new();
+            let mut required = Vec::new();
+            collect_object(doc, schema, &mut properties, &mut required, depth);
+            let mut object = Map::new();
+            for name in required {
+                let property = properties.get(&name).cloned().unwrap_or(Value::Null);
+                object.insert(name, skeleton(doc, &property, depth + 1));
+            }
+            Value::Object(object)
+        }
+        "array" => Value::Array(Vec::new()),
+        "string" => Value::String(String::new()),
+        "integer" | "number" => Value::from(0),
+        "boolean" => Value::Bool(false),
+        _ => Value::Null,
+    }
+}
+
+/// Properties and required names of an object schema, through `allOf`.
+fn collect_object(
+    doc: &Value,
+    schema: &Value,
+    properties: &mut Map<String, Value>,
+    required: &mut Vec<String>,
+    depth: usize,
+) {
+    let schema = resolve(doc, schema);
+    if depth > MAX_SCHEMA_DEPTH {
+        return;
+    }
+    for part in schema["allOf"].as_array().into_iter().flatten() {
+        collect_object(doc, part, properties, required, depth + 1);
+    }
+    if let Some(own) = schema["properties"].as_object() {
+        properties.extend(own.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+    for name in schema["required"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+    {
+        if !required.iter().any(|r| r == name) {
+            required.push(name.to_string());
+        }
+    }
+}
+
+/// Requests anywhere in `store` imported from one of the spec's operations,
+/// by `operationId`, with the project each is in.
+fn imported_operations(store: &CollectionStore) -> HashMap<String, (Uuid, Uuid)> {
+    let mut found = HashMap::new();
+    for project in &store.collection.item {
+        let Ok(project_id) = Uuid::parse_str(&project.id) else {
+            continue;
+        };
+        let mut stack = vec![project];
+        while let Some(item) = stack.pop() {
+            if let (Some(op), Ok(id)) = (&item.operation_id, Uuid::parse_str(&item.id)) {
+                found.entry(op.clone()).or_insert((id, project_id));
+            }
+            stack.extend(item.item.iter());
+        }
+    }
+    found
+}
+
+/// How many of the spec's operations are already in `store`.
+pub fn matching_requests(store: &CollectionStore, import: &OpenApiImport) -> usize {
+    let existing = imported_operations(store);
+    import
+        .requests()
+        .filter(|(_, item)| {
+            item.operation_id
+                .as_ref()
+                .is_some_and(|op| existing.contains_key(op))
+        })
+        .count()
+}
+
+/// Updates the requests imported from the spec before: method, URL,
+/// headers and body come from the spec, while names, auth, settings and
+/// examples stay. Operations not seen before are added to the project most
+/// of the others are in, under their tag's folder. The caller saves.
+pub fn update_from_spec(
+    store: &mut CollectionStore,
+    import: &OpenApiImport,
+) -> Result<SpecUpdate, String> {
+    let existing = imported_operations(store);
+    let mut projects: HashMap<Uuid, usize> = HashMap::new();
+    for (_, item) in import.requests() {
+        if let Some((_, project)) = item.operation_id.as_ref().and_then(|op| existing.get(op)) {
+            *projects.entry(*project).or_insert(0) += 1;
+        }
+    }
+    let project_id = projects
+        .into_iter()
+        .max_by_key(|(_, count)| *count)
+        .map(|(id, _)| id)
+        .ok_or("None of the spec's operations were imported before")?;
+
+    let mut update = SpecUpdate {
+        updated: 0,
+        added: 0,
+    };
+    for (group, spec_item) in import.requests() {
+        let Some(request) = spec_item.request.clone() else {
+            continue;
+        };
+        let matched = spec_item
+            .operation_id
+            .as_ref()
+            .and_then(|op| existing.get(op));
+        if let Some((id, _)) = matched {
+            let item = store
+                .get_item_mut(*id)
+                .ok_or("Request not found for update")?;
+            let auth = item.request.as_ref().and_then(|old| old.auth.clone());
+            item.request = Some(PostmanRequest { auth, ..request });
+            if spec_item.description.is_some() {
+                item.description = spec_item.description.clone();
+            }
+            update.updated += 1;
+            continue;
+        }
+        let parent_id = match group {
+            Some(group) => {
+                let project = store.get_item(project_id).ok_or("Project not found")?;
+                let folder = project
+                    .item
+                    .iter()
+                    .find(|item| !item.is_request() && item.name == group)
+                    .and_then(|item| Uuid::parse_str(&item.id).ok());
+                match folder {
+                    Some(id) => id,
+                    None => store.add_folder(project_id, group.to_string())?,
+                }
+            }
+            None => project_id,
+        };
+        let id = store.add_request(parent_id, spec_item.name.clone(), request)?;
+        let item = store
+            .get_item_mut(id)
+            .ok_or("Request not found after adding")?;
+        item.description = spec_item.description.clone();
+        item.operation_id = spec_item.operation_id.clone();
+        update.added += 1;
+    }
+    Ok(update)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::postman::PostmanCollection;
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    const SPEC: &str = r#"
+openapi: 3.0.3
+info:
+  title: Pets
+servers:
+  - url: https://{region}.pets.example.com/v1/
+    variables:
+      region:
+        default: eu
+paths:
+  /pets/{petId}:
+    parameters:
+      - $ref: '#/components/parameters/PetId'
+    get:
+      operationId: getPet
+      summary: Get a pet
+      tags: [pets]
+      parameters:
+        - name: fields
+          in: query
+          schema: {type: string, default: name}
+        - name: X-Trace
+          in: header
+          example: abc
+        - name: session
+          in: cookie
+      callbacks:
+        onChange: {}
+    put:
+      operationId: updatePet
+      tags: [pets]
+      requestBody:
+        content:
+          application/json:
+            schema:
+              $ref: '#/components/schemas/Pet'
+  /health:
+    get:
+      operationId: health
+components:
+  parameters:
+    PetId:
+      name: petId
+      in: path
+      required: true
+  schemas:
+    Pet:
+      allOf:
+        - $ref: '#/components/schemas/Named'
+        - type: object
+          required: [age, tags]
+          properties:
+            age: {type: integer}
+            tags: {type: array, items: {type: string}}
+            owner: {type: string}
+    Named:
+      type: object
+      required: [name]
+      properties:
+        name: {type: string}
+"#;
+
+    #[test]
+    fn test_operations_become_grouped_requests() {
+        let import = parse_openapi(SPEC, "pets").unwrap();
+        assert_eq!(import.operations, 3);
+        assert_eq!(
+            import.skipped_summary(),
+            ", skipped 1 callback and 1 cookie parameter"
+        );
+        let project = &import.archive.project;
+        assert_eq!(project.name, "Pets");
+
+        let folders: Vec<&str> = project.item.iter().map(|item| item.name.as_str()).collect();
+        assert_eq!(folders, vec!["health", "pets"]);
+        let pets = &project.item[1];
+        let get = pets
+            .item
+            .iter()
+            .find(|item| item.name == "Get a pet")
+            .unwrap();
+        assert_eq!(get.operation_id.as_deref(), Some("getPet"));
+        let request = get.request.as_ref().unwrap();
+        assert_eq!(request.method, "GET");
+        assert_eq!(
+            request.url,
+            json!("https://eu.pets.example.com/v1/pets/{{petId}}?fields=name")
+        );
+        assert_eq!(request.header[0].key, "X-Trace");
+        assert_eq!(request.header[0].value, "abc");
+
+        let put = pets
+            .item
+            .iter()
+            .find(|item| item.name == "updatePet")
+            .unwrap();
+        let request = put.request.as_ref().unwrap();
+        assert_eq!(request.header[0].value, "application/json");
+        let body: Value =
+            serde_json::from_str(request.body.as_ref().unwrap().raw.as_deref().unwrap()).unwrap();
+        assert_eq!(body, json!({"name": "", "age": 0, "tags": []}));
+    }
+
+    #[test]
+    fn test_non_openapi_documents_are_refused() {
+        assert!(parse_openapi("swagger: '2.0'", "x")
+            .unwrap_err()
+            .contains("Swagger 2.0"));
+        assert!(parse_openapi(r#"{"info": {}}"#, "x")
+            .unwrap_err()
+            .contains("Not an OpenAPI"));
+        assert!(parse_openapi("openapi: 3.1.0\npaths: {}", "x")
+            .unwrap_err()
+            .contains("no operations"));
+    }
+
+    #[test]
+    fn test_reimport_updates_by_operation_id() {
+        let mut store = CollectionStore {
+            root: PathBuf::new(),
+            collection: PostmanCollection::new("Test".to_string()),
+        };
+        let first = parse_openapi(SPEC, "pets").unwrap();
+        store.collection.item.push(first.archive.project.clone());
+        let get_id = first
+            .requests()
+            .find(|(_, item)| item.name == "Get a pet")
+            .unwrap()
+            .1
+            .id
+            .clone();
+        let get_id = Uuid::parse_str(&get_id).unwrap();
+        store.get_item_mut(get_id).unwrap().name = "Renamed".to_string();
+
+        let changed = SPEC.replace("default: name", "default: id").replace(
+            "  /health:",
+            "  /owners:\n    post:\n      operationId: addOwner\n  /health:",
+        );
+        let second = parse_openapi(&changed, "pets").unwrap();
+        assert_eq!(matching_requests(&store, &second), 3);
+        let update = update_from_spec(&mut store, &second).unwrap();
+        assert_eq!(
+            update,
+            SpecUpdate {
+                updated: 3,
+                added: 1
+            }
+        );
+
+        let get = store.get_item(get_id).unwrap();
+        assert_eq!(get.name, "Renamed");
+        assert!(get
+            .request
+            .as_ref()
+            .unwrap()
+            .url
+            .as_str()
+            .unwrap()
+            .ends_with("?fields=id"));
+        let project = &store.collection.item[0];
+        assert!(project.item.iter().any(|folder| folder.name == "owners"));
+    }
+}
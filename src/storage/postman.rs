@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use super::environment;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(Default))]
 pub struct PostmanCollection {
     pub info: PostmanInfo,
     #[serde(default)]
@@ -9,6 +12,7 @@ pub struct PostmanCollection {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(Default))]
 pub struct PostmanInfo {
     pub name: String,
     #[serde(rename = "_postman_id")]
@@ -27,6 +31,366 @@ pub struct PostmanItem {
     pub request: Option<PostmanRequest>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub response: Vec<Value>,
+    /// Postman's notes on the item: a string, or `{ "content": … }`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<Value>,
+    #[serde(rename = "_perseus", default, skip_serializing_if = "RequestSettings::is_default")]
+    pub settings: RequestSettings,
+    /// The item and everything under it open read-only.
+    #[serde(rename = "_perseus_locked", default, skip_serializing_if = "is_false")]
+    pub locked: bool,
+    /// The OpenAPI `operationId` the request was imported from, so importing
+    /// the spec again updates it.
+    #[serde(rename = "_perseus_operation_id", default, skip_serializing_if = "Option::is_none")]
+    pub operation_id: Option<String>,
+    /// Colored labels shown after the name in the sidebar.
+    #[serde(rename = "_perseus_tags", default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<ItemTag>,
+    /// The request's name was derived from its method and path and hasn't
+    /// been renamed by hand since.
+    #[serde(rename = "_perseus_name_auto", default, skip_serializing_if = "is_false")]
+    pub name_is_auto: bool,
+    /// On a project: requests with [`Self::name_is_auto`] are renamed when
+    /// their method or path changes.
+    #[serde(rename = "_perseus_auto_names", default, skip_serializing_if = "is_false")]
+    pub auto_names: bool,
+}
+
+/// Most tags one item carries.
+pub const MAX_TAGS: usize = 4;
+/// Longest tag name, in characters.
+pub const MAX_TAG_LEN: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ItemTag {
+    pub name: String,
+    pub color: TagColor,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagColor {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+}
+
+impl TagColor {
+    pub const ALL: [TagColor; 6] = [
+        TagColor::Red,
+        TagColor::Green,
+        TagColor::Yellow,
+        TagColor::Blue,
+        TagColor::Magenta,
+        TagColor::Cyan,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TagColor::Red => "red",
+            TagColor::Green => "green",
+            TagColor::Yellow => "yellow",
+            TagColor::Blue => "blue",
+            TagColor::Magenta => "magenta",
+            TagColor::Cyan => "cyan",
+        }
+    }
+
+    /// The color a tag gets when none is given, the same for every use of
+    /// a name.
+    fn for_name(name: &str) -> TagColor {
+        let sum = name.bytes().fold(0usize, |sum, b| sum.wrapping_add(b as usize));
+        Self::ALL[sum % Self::ALL.len()]
+    }
+}
+
+/// Perseus-only per-request settings, stored under a non-standard key that
+/// Postman ignores on import.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequestSettings {
+    #[serde(default, skip_serializing_if = "ProxySetting::is_inherit")]
+    pub proxy: ProxySetting,
+    #[serde(default, skip_serializing_if = "RedirectSetting::is_inherit")]
+    pub redirects: RedirectSetting,
+    #[serde(default, skip_serializing_if = "CompressSetting::is_inherit")]
+    pub compress: CompressSetting,
+    #[serde(default, skip_serializing_if = "HttpVersionSetting::is_auto")]
+    pub http_version: HttpVersionSetting,
+    /// Sent as `User-Agent` instead of `http.user_agent`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+    #[serde(default, skip_serializing_if = "TlsSetting::is_inherit")]
+    pub tls: TlsSetting,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub poll: Option<PollSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<SchemaSetting>,
+    /// Sends to `[protect]` hosts without asking, for requests run unattended.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub skip_protect: bool,
+    /// Sends `\r\n` in the body as typed instead of as `\n`.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub preserve_line_endings: bool,
+    /// Asks before sending POST, PUT or PATCH with an empty body.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub expects_body: bool,
+}
+
+impl RequestSettings {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+pub enum ProxySetting {
+    /// Use the global `[proxy]` configuration.
+    #[default]
+    Inherit,
+    /// Connect directly, ignoring configured and system proxies.
+    None,
+    Custom { url: String },
+}
+
+impl ProxySetting {
+    pub fn is_inherit(&self) -> bool {
+        matches!(self, ProxySetting::Inherit)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ProxySetting::Inherit => "Inherit",
+            ProxySetting::None => "None",
+            ProxySetting::Custom { .. } => "Custom",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RedirectSetting {
+    /// Use `http.follow_redirects` from the config.
+    #[default]
+    Inherit,
+    Follow,
+    /// Return the 3xx response as-is.
+    Stop,
+}
+
+impl RedirectSetting {
+    pub fn is_inherit(&self) -> bool {
+        matches!(self, RedirectSetting::Inherit)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            RedirectSetting::Inherit => "Inherit",
+            RedirectSetting::Follow => "Follow",
+            RedirectSetting::Stop => "Don't follow",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompressSetting {
+    /// Use `http.compress_body` from the config.
+    #[default]
+    Inherit,
+    Gzip,
+    /// Send the body as-is.
+    Off,
+}
+
+impl CompressSetting {
+    pub fn is_inherit(&self) -> bool {
+        matches!(self, CompressSetting::Inherit)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CompressSetting::Inherit => "Inherit",
+            CompressSetting::Gzip => "Gzip",
+            CompressSetting::Off => "Off",
+        }
+    }
+}
+
+/// The HTTP version a request is sent over. HTTP/2 isn't offered during
+/// the TLS handshake, so only prior knowledge reaches it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HttpVersionSetting {
+    /// HTTP/1.1.
+    #[default]
+    Auto,
+    /// HTTP/1.1, pinned so the request keeps it if negotiation is added.
+    Http1,
+    /// HTTP/2 from the first byte, without negotiating (h2c on plain http).
+    Http2,
+}
+
+impl HttpVersionSetting {
+    pub fn is_auto(&self) -> bool {
+        matches!(self, HttpVersionSetting::Auto)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            HttpVersionSetting::Auto => "Auto",
+            HttpVersionSetting::Http1 => "HTTP/1.1 only",
+            HttpVersionSetting::Http2 => "HTTP/2 prior knowledge",
+        }
+    }
+}
+
+/// TLS overrides for one request. Unset fields use the `[ssl]` config;
+/// paths are relative to the project root unless absolute.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TlsSetting {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verify: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_cert: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_cert: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_key: Option<String>,
+}
+
+impl TlsSetting {
+    pub fn is_inherit(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn verify_label(&self) -> &'static str {
+        match self.verify {
+            None => "Inherit",
+            Some(true) => "On",
+            Some(false) => "Off",
+        }
+    }
+
+    /// Drops the certificate and key paths, which point into one machine's
+    /// filesystem and shouldn't travel with a shared project.
+    pub fn without_paths(&self) -> Self {
+        Self {
+            verify: self.verify,
+            ..Self::default()
+        }
+    }
+}
+
+/// JSON Schema the request's JSON responses are validated against.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "lowercase")]
+pub enum SchemaSetting {
+    /// A schema file, relative to the project root unless absolute.
+    File { path: String },
+    Inline { schema: Value },
+}
+
+/// Send the request again every `interval_secs` after each response until
+/// `until` holds or `max_attempts` responses have come back.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PollSetting {
+    pub interval_secs: u64,
+    pub max_attempts: u32,
+    pub until: PollUntil,
+}
+
+impl PollSetting {
+    pub fn new(until: PollUntil) -> Self {
+        Self {
+            interval_secs: 5,
+            max_attempts: 20,
+            until,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum PollUntil {
+    /// The response status is one of `codes`.
+    Status { codes: Vec<u16> },
+    /// The body's JSON value at `path` (`$.state`) equals `value`; strings
+    /// compare without quotes, other values as compact JSON.
+    Json { path: String, value: String },
+}
+
+impl PollUntil {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PollUntil::Status { .. } => "Until status",
+            PollUntil::Json { .. } => "Until JSON value",
+        }
+    }
+}
+
+/// Parses a comma or space separated list of status codes (`200, 201`).
+pub fn parse_status_codes(text: &str) -> Result<Vec<u16>, String> {
+    let codes = text
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .map(|part| match part.parse::<u16>() {
+            Ok(code) if (100..=599).contains(&code) => Ok(code),
+            _ => Err(format!("\"{}\" is not a status code", part)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if codes.is_empty() {
+        return Err("Enter at least one status code".to_string());
+    }
+    Ok(codes)
+}
+
+pub fn format_status_codes(codes: &[u16]) -> String {
+    codes.iter().map(u16::to_string).collect::<Vec<_>>().join(", ")
+}
+
+/// Parses a comma separated tag list, each `name` or `name:color`
+/// (`auth, slow:yellow`). An empty list clears the tags.
+pub fn parse_tags(text: &str) -> Result<Vec<ItemTag>, String> {
+    let mut tags: Vec<ItemTag> = Vec::new();
+    for part in text.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+        let (name, color) = match part.split_once(':') {
+            Some((name, color)) => {
+                let color = color.trim().to_lowercase();
+                let color = TagColor::ALL
+                    .into_iter()
+                    .find(|c| c.as_str() == color)
+                    .ok_or_else(|| format!("\"{}\" is not a tag color", color))?;
+                (name.trim(), Some(color))
+            }
+            None => (part, None),
+        };
+        if name.is_empty() || name.chars().any(char::is_whitespace) {
+            return Err(format!("\"{}\" is not a tag; use one word", name));
+        }
+        if name.chars().count() > MAX_TAG_LEN {
+            return Err(format!("Tag \"{}\" is over {} characters", name, MAX_TAG_LEN));
+        }
+        if tags.iter().any(|tag| tag.name == name) {
+            continue;
+        }
+        let color = color.unwrap_or_else(|| TagColor::for_name(name));
+        tags.push(ItemTag { name: name.to_string(), color });
+    }
+    if tags.len() > MAX_TAGS {
+        return Err(format!("At most {} tags per item", MAX_TAGS));
+    }
+    Ok(tags)
+}
+
+pub fn format_tags(tags: &[ItemTag]) -> String {
+    tags.iter()
+        .map(|tag| format!("{}:{}", tag.name, tag.color.as_str()))
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,6 +412,9 @@ pub struct PostmanAuth {
     pub basic: Option<Vec<PostmanAuthAttribute>>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub apikey: Option<Vec<PostmanAuthAttribute>>,
+    /// Kept with the request but left out of sends.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disabled: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,6 +430,59 @@ pub struct PostmanRequest {
     pub auth: Option<PostmanAuth>,
 }
 
+impl PostmanRequest {
+    /// Renames each `{{from}}` placeholder in the URL, headers, body and
+    /// auth to `{{to}}`, returning how many were.
+    pub fn rename_placeholder(&mut self, from: &str, to: &str) -> usize {
+        let mut count = 0;
+        let mut rename = |text: &mut String| {
+            let (renamed, n) = environment::rename_placeholder(text, from, to);
+            if n > 0 {
+                *text = renamed;
+                count += n;
+            }
+        };
+        let mut url = match &self.url {
+            Value::String(raw) => raw.clone(),
+            Value::Object(map) => map.get("raw").and_then(Value::as_str).unwrap_or("").to_string(),
+            _ => String::new(),
+        };
+        let before = url.clone();
+        rename(&mut url);
+        if url != before {
+            self.url = Value::String(url);
+        }
+        for header in &mut self.header {
+            rename(&mut header.key);
+            rename(&mut header.value);
+        }
+        if let Some(body) = &mut self.body {
+            body.raw.iter_mut().for_each(&mut rename);
+            for pair in body.urlencoded.iter_mut().flatten() {
+                rename(&mut pair.key);
+                rename(&mut pair.value);
+            }
+            for param in body.formdata.iter_mut().flatten() {
+                rename(&mut param.key);
+                param.value.iter_mut().for_each(&mut rename);
+                param.src.iter_mut().for_each(&mut rename);
+            }
+            if let Some(file) = &mut body.file {
+                file.src.iter_mut().for_each(&mut rename);
+            }
+        }
+        if let Some(auth) = &mut self.auth {
+            let attributes = [&mut auth.bearer, &mut auth.basic, &mut auth.apikey];
+            for attribute in attributes.into_iter().flatten().flatten() {
+                if let Some(Value::String(value)) = &mut attribute.value {
+                    rename(value);
+                }
+            }
+        }
+        count
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PostmanHeader {
     pub key: String,
@@ -126,6 +546,84 @@ pub struct PostmanBody {
     pub formdata: Option<Vec<PostmanFormParam>>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub file: Option<PostmanFileRef>,
+    /// Kept with the request but left out of sends.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disabled: Option<bool>,
+}
+
+/// A saved example response, in the shape Postman keeps under
+/// `item.response`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostmanExample {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "originalRequest", default, skip_serializing_if = "Option::is_none")]
+    pub original_request: Option<PostmanRequest>,
+    #[serde(default)]
+    pub status: String,
+    #[serde(default)]
+    pub code: u16,
+    #[serde(rename = "_postman_previewlanguage", default, skip_serializing_if = "Option::is_none")]
+    pub preview_language: Option<String>,
+    #[serde(default)]
+    pub header: Vec<PostmanHeader>,
+    #[serde(default)]
+    pub cookie: Vec<Value>,
+    #[serde(default)]
+    pub body: String,
+    /// Set when the body was cut at [`PostmanExample::MAX_BODY`] on saving.
+    #[serde(rename = "_perseus_truncated", default, skip_serializing_if = "is_false")]
+    pub truncated: bool,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+impl PostmanExample {
+    /// Body bytes kept per example; the rest is dropped.
+    pub const MAX_BODY: usize = 256 * 1024;
+
+    pub fn new(
+        name: String,
+        code: u16,
+        status: String,
+        headers: &[(String, String)],
+        body: &str,
+        original_request: PostmanRequest,
+    ) -> Self {
+        let mut end = body.len().min(Self::MAX_BODY);
+        while !body.is_char_boundary(end) {
+            end -= 1;
+        }
+        let content_type = crate::http::header_value(headers, "content-type")
+            .map(str::to_ascii_lowercase)
+            .unwrap_or_default();
+        let preview_language = ["json", "xml", "html", "javascript"]
+            .into_iter()
+            .find(|language| content_type.contains(language))
+            .unwrap_or("text");
+        Self {
+            id: new_id(),
+            name,
+            original_request: Some(original_request),
+            status,
+            code,
+            preview_language: Some(preview_language.to_string()),
+            header: headers
+                .iter()
+                .map(|(key, value)| PostmanHeader {
+                    key: key.clone(),
+                    value: value.clone(),
+                    disabled: None,
+                })
+                .collect(),
+            cookie: Vec::new(),
+            body: body[..end].to_string(),
+            truncated: end < body.len(),
+        }
+    }
 }
 
 impl PostmanCollection {
@@ -150,6 +648,13 @@ impl PostmanItem {
             item: Vec::new(),
             request: None,
             response: Vec::new(),
+            description: None,
+            settings: RequestSettings::default(),
+            locked: false,
+            operation_id: None,
+            tags: Vec::new(),
+            name_is_auto: false,
+            auto_names: false,
         }
     }
 
@@ -160,12 +665,48 @@ impl PostmanItem {
             item: Vec::new(),
             request: Some(request),
             response: Vec::new(),
+            description: None,
+            settings: RequestSettings::default(),
+            locked: false,
+            operation_id: None,
+            tags: Vec::new(),
+            name_is_auto: false,
+            auto_names: false,
         }
     }
 
     pub fn is_request(&self) -> bool {
         self.request.is_some()
     }
+
+    /// The saved examples with their index in `response`. Entries that
+    /// aren't examples Perseus can read are skipped but kept.
+    pub fn examples(&self) -> Vec<(usize, PostmanExample)> {
+        self.response
+            .iter()
+            .enumerate()
+            .filter_map(|(i, value)| Some((i, serde_json::from_value(value.clone()).ok()?)))
+            .collect()
+    }
+
+    /// The example offline mode answers with: the first successful one, or
+    /// the first of any status.
+    pub fn offline_example(&self) -> Option<PostmanExample> {
+        let mut examples: Vec<PostmanExample> =
+            self.examples().into_iter().map(|(_, example)| example).collect();
+        let index = examples.iter().position(|e| (200..300).contains(&e.code)).unwrap_or(0);
+        (index < examples.len()).then(|| examples.swap_remove(index))
+    }
+
+    /// The description's text, in either of the shapes Postman writes.
+    pub fn description_text(&self) -> Option<&str> {
+        let text = match self.description.as_ref()? {
+            Value::String(text) => text.as_str(),
+            Value::Object(map) => map.get("content")?.as_str()?,
+            _ => return None,
+        };
+        (!text.trim().is_empty()).then_some(text)
+    }
 }
 
 impl PostmanRequest {
@@ -181,6 +722,7 @@ impl PostmanRequest {
                     urlencoded: None,
                     formdata: None,
                     file: None,
+                    disabled: None,
                 })
             }
         });
@@ -204,6 +746,7 @@ impl PostmanBody {
             urlencoded: None,
             formdata: None,
             file: None,
+            disabled: None,
         }
     }
 
@@ -219,6 +762,7 @@ impl PostmanBody {
             urlencoded: None,
             formdata: None,
             file: None,
+            disabled: None,
         }
     }
 
@@ -234,6 +778,7 @@ impl PostmanBody {
             urlencoded: None,
             formdata: None,
             file: None,
+            disabled: None,
         }
     }
 
@@ -245,6 +790,7 @@ impl PostmanBody {
             urlencoded: Some(pairs),
             formdata: None,
             file: None,
+            disabled: None,
         }
     }
 
@@ -256,6 +802,7 @@ impl PostmanBody {
             urlencoded: None,
             formdata: Some(params),
             file: None,
+            disabled: None,
         }
     }
 
@@ -269,6 +816,7 @@ impl PostmanBody {
             file: Some(PostmanFileRef {
                 src: Some(path.to_string()),
             }),
+            disabled: None,
         }
     }
 }
@@ -284,6 +832,7 @@ impl PostmanAuth {
             }]),
             basic: None,
             apikey: None,
+            disabled: None,
         }
     }
 
@@ -304,6 +853,7 @@ impl PostmanAuth {
                 },
             ]),
             apikey: None,
+            disabled: None,
         }
     }
 
@@ -329,6 +879,7 @@ impl PostmanAuth {
                     attr_type: Some("string".to_string()),
                 },
             ]),
+            disabled: None,
         }
     }
 
@@ -363,3 +914,190 @@ impl PostmanAuth {
 pub fn new_id() -> String {
     uuid::Uuid::new_v4().to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_example_shape_and_truncation() {
+        let request = PostmanRequest::new("GET".to_string(), "/users".to_string(), vec![], None);
+        let headers = vec![("Content-Type".to_string(), "application/json".to_string())];
+        let example = PostmanExample::new(
+            "Found".to_string(),
+            200,
+            "OK".to_string(),
+            &headers,
+            r#"{"id":1}"#,
+            request.clone(),
+        );
+        let value = serde_json::to_value(&example).unwrap();
+        assert_eq!(value["code"], 200);
+        assert_eq!(value["status"], "OK");
+        assert_eq!(value["_postman_previewlanguage"], "json");
+        assert_eq!(value["originalRequest"]["method"], "GET");
+        assert_eq!(value["header"][0]["key"], "Content-Type");
+        assert!(value.get("_perseus_truncated").is_none());
+
+        let mut item = PostmanItem::new_request("Users".to_string(), request.clone());
+        item.response.push(serde_json::json!({"unrelated": true}));
+        item.response.push(value);
+        let examples = item.examples();
+        assert_eq!(examples.len(), 1);
+        assert_eq!((examples[0].0, examples[0].1.body.as_str()), (1, r#"{"id":1}"#));
+
+        // Cut on a character boundary
+        let body = format!("{}é", "a".repeat(PostmanExample::MAX_BODY - 1));
+        let big = PostmanExample::new("Big".to_string(), 200, String::new(), &[], &body, request);
+        assert!(big.truncated);
+        assert_eq!(big.body.len(), PostmanExample::MAX_BODY - 1);
+    }
+
+    #[test]
+    fn test_offline_example_prefers_success() {
+        let request = PostmanRequest::new("GET".to_string(), "/users".to_string(), vec![], None);
+        let mut item = PostmanItem::new_request("Users".to_string(), request.clone());
+        assert!(item.offline_example().is_none());
+        for (name, code) in [("Missing", 404), ("Found", 200), ("Created", 201)] {
+            let name = name.to_string();
+            let example = PostmanExample::new(name, code, String::new(), &[], "", request.clone());
+            item.response.push(serde_json::to_value(&example).unwrap());
+        }
+        assert_eq!(item.offline_example().unwrap().name, "Found");
+        item.response.remove(1);
+        assert_eq!(item.offline_example().unwrap().name, "Created");
+        item.response.truncate(1);
+        assert_eq!(item.offline_example().unwrap().name, "Missing");
+    }
+
+    #[test]
+    fn test_default_settings_not_serialized() {
+        let item = PostmanItem::new_folder("Folder".to_string());
+        let json = serde_json::to_string(&item).unwrap();
+        assert!(!json.contains("_perseus"));
+    }
+
+    #[test]
+    fn test_proxy_setting_roundtrip() {
+        let mut item = PostmanItem::new_folder("Folder".to_string());
+        item.settings.proxy = ProxySetting::Custom {
+            url: "http://proxy.local:3128".to_string(),
+        };
+        let json = serde_json::to_string(&item).unwrap();
+        assert!(json.contains(r#""_perseus":{"proxy":{"mode":"custom","url":"http://proxy.local:3128"}}"#));
+
+        let parsed: PostmanItem = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.settings, item.settings);
+    }
+
+    #[test]
+    fn test_redirect_setting_roundtrip() {
+        let mut item = PostmanItem::new_folder("Folder".to_string());
+        item.settings.redirects = RedirectSetting::Stop;
+        let json = serde_json::to_string(&item).unwrap();
+        assert!(json.contains(r#""_perseus":{"redirects":"stop"}"#));
+
+        let parsed: PostmanItem = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.settings.redirects, RedirectSetting::Stop);
+    }
+
+    #[test]
+    fn test_request_rename_placeholder() {
+        let headers = vec![PostmanHeader {
+            key: "X-{{key}}".to_string(),
+            value: "{{key}}-{{keyring}}".to_string(),
+            disabled: None,
+        }];
+        let body = Some(r#"{"k": "{{key}}"}"#.to_string());
+        let mut request =
+            PostmanRequest::new("POST".to_string(), "{{key}}/a".to_string(), headers, body);
+        request.url = serde_json::json!({"raw": "{{key}}/a", "host": ["{{key}}"]});
+        request.auth = Some(PostmanAuth::bearer("{{key}}"));
+
+        assert_eq!(request.rename_placeholder("key", "token"), 5);
+        assert_eq!(request.url, Value::String("{{token}}/a".to_string()));
+        assert_eq!(request.header[0].key, "X-{{token}}");
+        assert_eq!(request.header[0].value, "{{token}}-{{keyring}}");
+        assert_eq!(request.body.unwrap().raw.unwrap(), r#"{"k": "{{token}}"}"#);
+        let auth = request.auth.unwrap();
+        assert_eq!(auth.bearer.unwrap()[0].value, Some(Value::String("{{token}}".to_string())));
+    }
+
+    #[test]
+    fn test_http_version_setting_roundtrip() {
+        let mut item = PostmanItem::new_folder("Folder".to_string());
+        item.settings.http_version = HttpVersionSetting::Http2;
+        let json = serde_json::to_string(&item).unwrap();
+        assert!(json.contains(r#""_perseus":{"http_version":"http2"}"#));
+
+        let parsed: PostmanItem = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.settings.http_version, HttpVersionSetting::Http2);
+    }
+
+    #[test]
+    fn test_tls_setting_roundtrip() {
+        let mut item = PostmanItem::new_folder("Folder".to_string());
+        item.settings.tls = TlsSetting {
+            verify: Some(false),
+            ca_cert: Some("certs/internal-ca.pem".to_string()),
+            ..TlsSetting::default()
+        };
+        let json = serde_json::to_string(&item).unwrap();
+        assert!(json.contains(
+            r#""_perseus":{"tls":{"verify":false,"ca_cert":"certs/internal-ca.pem"}}"#
+        ));
+
+        let parsed: PostmanItem = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.settings, item.settings);
+        assert_eq!(item.settings.tls.without_paths().verify, Some(false));
+        assert!(item.settings.tls.without_paths().ca_cert.is_none());
+    }
+
+    #[test]
+    fn test_poll_setting_roundtrip() {
+        let mut item = PostmanItem::new_folder("Folder".to_string());
+        item.settings.poll = Some(PollSetting::new(PollUntil::Json {
+            path: "$.state".to_string(),
+            value: "done".to_string(),
+        }));
+        let json = serde_json::to_string(&item).unwrap();
+        assert!(json.contains(r#""poll":{"interval_secs":5,"max_attempts":20,"#));
+        assert!(json.contains(r#""until":{"kind":"json","path":"$.state","value":"done"}"#));
+
+        let parsed: PostmanItem = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.settings, item.settings);
+    }
+
+    #[test]
+    fn test_parse_status_codes() {
+        assert_eq!(parse_status_codes("200, 201 404").unwrap(), vec![200, 201, 404]);
+        assert_eq!(format_status_codes(&[200, 201]), "200, 201");
+        assert!(parse_status_codes("").is_err());
+        assert!(parse_status_codes("200, 6000").unwrap_err().contains("6000"));
+        assert!(parse_status_codes("ok").is_err());
+    }
+
+    #[test]
+    fn test_parse_tags() {
+        let tags = parse_tags("auth:red, slow ,auth, deprecated:Yellow").unwrap();
+        let slow = TagColor::for_name("slow").as_str();
+        assert_eq!(format_tags(&tags), format!("auth:red, slow:{}, deprecated:yellow", slow));
+        assert!(parse_tags("").unwrap().is_empty());
+        assert!(parse_tags("a:pink").unwrap_err().contains("pink"));
+        assert!(parse_tags("two words").is_err());
+        assert!(parse_tags("a, b, c, d, e").unwrap_err().contains("At most"));
+
+        let mut item = PostmanItem::new_folder("f".to_string());
+        item.tags = tags;
+        let json = serde_json::to_string(&item).unwrap();
+        assert!(json.contains(r#""_perseus_tags":[{"name":"auth","color":"red"}"#));
+        let parsed: PostmanItem = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.tags, item.tags);
+    }
+
+    #[test]
+    fn test_item_without_settings_deserializes() {
+        let parsed: PostmanItem = serde_json::from_str(r#"{"name":"x","id":"1"}"#).unwrap();
+        assert!(parsed.settings.is_default());
+    }
+}
@@ -1,10 +1,94 @@
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 const PROJECT_MARKERS: &[&str] = &[".git", "Cargo.toml", "package.json", ".perseus"];
 
+const NO_ROOT: &str = concat!(
+    "Could not find project root. Run from a directory with .git, Cargo.toml, package.json ",
+    "or a .perseus folder, or pass --root."
+);
+
+/// Sets the project root like `--root`, which wins over it.
+pub const ROOT_ENV_VAR: &str = "PERSEUS_ROOT";
+
+/// The directory every storage path is taken from.
+#[derive(Debug, Clone)]
+struct ProjectRoot {
+    path: PathBuf,
+    /// The canonical path, which keys per-project session and trust state.
+    key: Option<String>,
+    /// Given with `--root` or `PERSEUS_ROOT` rather than found.
+    overridden: bool,
+}
+
+impl ProjectRoot {
+    fn new(path: PathBuf, overridden: bool) -> Self {
+        let key = fs::canonicalize(&path).ok().map(|p| p.to_string_lossy().to_string());
+        Self {
+            path,
+            key,
+            overridden,
+        }
+    }
+}
+
+/// Resolved once per run, by [`init_project_root`] or on first use.
+static PROJECT_ROOT: OnceLock<Option<ProjectRoot>> = OnceLock::new();
+
+/// Settles the project root for the run: `flag` (from `--root`), else
+/// `PERSEUS_ROOT`, else the nearest directory with a project marker from
+/// the current one up. An override has to be an existing directory.
+pub fn init_project_root(flag: Option<PathBuf>) -> Result<(), String> {
+    let from_env = env::var_os(ROOT_ENV_VAR).filter(|value| !value.is_empty()).map(PathBuf::from);
+    let root = match override_root(flag, from_env)? {
+        Some(path) => Some(ProjectRoot::new(path, true)),
+        None => discover_root().map(|path| ProjectRoot::new(path, false)),
+    };
+    let _ = PROJECT_ROOT.set(root);
+    Ok(())
+}
+
+/// The directory `--root` or else `PERSEUS_ROOT` names, made absolute.
+fn override_root(
+    flag: Option<PathBuf>,
+    from_env: Option<PathBuf>,
+) -> Result<Option<PathBuf>, String> {
+    let (path, source) = match (flag, from_env) {
+        (Some(path), _) => (path, "--root"),
+        (None, Some(path)) => (path, ROOT_ENV_VAR),
+        (None, None) => return Ok(None),
+    };
+    let path = crate::config::expand_tilde(&path);
+    if !path.is_dir() {
+        let problem = if path.exists() { "is not a directory" } else { "does not exist" };
+        return Err(format!("{} {}: {}", source, path.display(), problem));
+    }
+    fs::canonicalize(&path)
+        .map(Some)
+        .map_err(|e| format!("{} {}: {}", source, path.display(), e))
+}
+
+fn project_root() -> Option<&'static ProjectRoot> {
+    PROJECT_ROOT
+        .get_or_init(|| discover_root().map(|path| ProjectRoot::new(path, false)))
+        .as_ref()
+}
+
 pub fn find_project_root() -> Option<PathBuf> {
+    project_root().map(|root| root.path.clone())
+}
+
+/// The root's directory name when `--root` or `PERSEUS_ROOT` chose it.
+pub fn overridden_root_name() -> Option<String> {
+    let root = project_root().filter(|root| root.overridden)?;
+    let name = root.path.file_name().unwrap_or(root.path.as_os_str());
+    Some(name.to_string_lossy().to_string())
+}
+
+fn discover_root() -> Option<PathBuf> {
     let current = env::current_dir().ok()?;
     let mut dir = current.as_path();
 
@@ -24,9 +108,7 @@ pub fn find_project_root() -> Option<PathBuf> {
 }
 
 pub fn project_root_key() -> Option<String> {
-    let root = find_project_root()?;
-    let canonical = fs::canonicalize(root).ok()?;
-    Some(canonical.to_string_lossy().to_string())
+    project_root()?.key.clone()
 }
 
 pub fn storage_dir() -> Option<PathBuf> {
@@ -34,9 +116,7 @@ pub fn storage_dir() -> Option<PathBuf> {
 }
 
 pub fn ensure_storage_dir() -> Result<PathBuf, String> {
-    let dir = storage_dir().ok_or(
-        "Could not find project root. Run from a directory with .git, Cargo.toml, package.json, or create a .perseus folder.",
-    )?;
+    let dir = storage_dir().ok_or(NO_ROOT)?;
     fs::create_dir_all(&dir).map_err(|e| format!("Failed to create storage directory: {}", e))?;
     Ok(dir)
 }
@@ -49,19 +129,175 @@ pub fn requests_dir() -> Option<PathBuf> {
     storage_dir().map(|root| root.join("requests"))
 }
 
+pub fn drafts_dir() -> Option<PathBuf> {
+    storage_dir().map(|root| root.join("drafts"))
+}
+
 pub fn ui_state_path() -> Option<PathBuf> {
     storage_dir().map(|root| root.join("ui.json"))
 }
 
+pub fn changelog_path() -> Option<PathBuf> {
+    storage_dir().map(|root| root.join("changelog.jsonl"))
+}
+
 pub fn environments_dir() -> Option<PathBuf> {
     storage_dir().map(|root| root.join("environments"))
 }
 
 pub fn ensure_environments_dir() -> Result<PathBuf, String> {
-    let dir = environments_dir().ok_or(
-        "Could not find project root. Run from a directory with .git, Cargo.toml, package.json, or create a .perseus folder.",
-    )?;
+    let dir = environments_dir().ok_or(NO_ROOT)?;
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create environments directory: {}", e))?;
+    Ok(dir)
+}
+
+/// Personal environments for this project, kept outside the repository:
+/// `~/.config/perseus/environments/<project>/` (or under `$XDG_CONFIG_HOME`).
+pub fn user_environments_dir() -> Option<PathBuf> {
+    let config = match env::var("XDG_CONFIG_HOME") {
+        Ok(dir) if !dir.trim().is_empty() => PathBuf::from(dir),
+        _ => {
+            let home = env::var("HOME").ok().filter(|h| !h.trim().is_empty())?;
+            PathBuf::from(home).join(".config")
+        }
+    };
+    let key = project_root_key()?;
+    Some(config.join("perseus").join("environments").join(root_key_dir_name(&key)))
+}
+
+pub fn ensure_user_environments_dir() -> Result<PathBuf, String> {
+    let dir = user_environments_dir().ok_or("Could not resolve the user config directory")?;
     fs::create_dir_all(&dir)
         .map_err(|e| format!("Failed to create environments directory: {}", e))?;
     Ok(dir)
 }
+
+/// A directory name for a project root: the path's last components made
+/// readable, plus a hash of the whole path so different roots never share
+/// a directory.
+fn root_key_dir_name(key: &str) -> String {
+    // FNV-1a, which is stable across releases unlike `DefaultHasher`
+    let hash = key
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325u64, |h, b| (h ^ u64::from(b)).wrapping_mul(0x100_0000_01b3));
+    let readable: String = key
+        .rsplit(['/', '\\'])
+        .find(|part| !part.is_empty())
+        .unwrap_or("root")
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    format!("{}-{:016x}", readable, hash)
+}
+
+/// `<path>.bak`, where [`write_atomic`] keeps the previous version.
+pub fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+/// Writes `contents` to a temp file next to `path`, syncs it and renames it
+/// into place, so a crash mid-write never leaves a truncated file. With
+/// `backup`, the current file is first copied to [`backup_path`].
+pub fn write_atomic(path: &Path, contents: &[u8], backup: bool) -> std::io::Result<()> {
+    let mut tmp_name = std::ffi::OsString::from(".");
+    tmp_name.push(path.file_name().unwrap_or_default());
+    tmp_name.push(".tmp");
+    let tmp = path.with_file_name(tmp_name);
+
+    let result = (|| {
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        if backup && path.exists() {
+            fs::copy(path, backup_path(path))?;
+        }
+        fs::rename(&tmp, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+/// Renames an unreadable `path` to `<path>.corrupt`, so the next load starts
+/// fresh while the damaged file stays around for inspection.
+pub fn move_aside_corrupt(path: &Path) -> Result<PathBuf, String> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".corrupt");
+    let corrupt = path.with_file_name(name);
+    fs::rename(path, &corrupt)
+        .map_err(|e| format!("Failed to move aside {}: {}", path.display(), e))?;
+    Ok(corrupt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_root_key_dir_name() {
+        let name = root_key_dir_name("/home/ada/work/api client");
+        assert!(name.starts_with("api_client-"));
+        assert_eq!(name, root_key_dir_name("/home/ada/work/api client"));
+        assert_ne!(
+            root_key_dir_name("/home/ada/a/api"),
+            root_key_dir_name("/home/ada/b/api")
+        );
+        assert!(root_key_dir_name("/").starts_with("root-"));
+    }
+
+    #[test]
+    fn test_write_atomic_keeps_backup() {
+        let dir = env::temp_dir().join(format!("perseus-project-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("collection.json");
+
+        write_atomic(&path, b"one", true).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"one");
+        assert!(!backup_path(&path).exists());
+
+        write_atomic(&path, b"two", true).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"two");
+        assert_eq!(fs::read(backup_path(&path)).unwrap(), b"one");
+
+        let leftovers = fs::read_dir(&dir)
+            .unwrap()
+            .flatten()
+            .filter(|e| e.file_name().to_string_lossy().ends_with(".tmp"))
+            .count();
+        assert_eq!(leftovers, 0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_root_override_must_be_a_directory() {
+        let dir = env::temp_dir().join(format!("perseus-root-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(dir.join("svc")).unwrap();
+        fs::write(dir.join("notes.txt"), "").unwrap();
+        let canonical = fs::canonicalize(dir.join("svc")).unwrap();
+
+        assert_eq!(override_root(None, None), Ok(None));
+        assert_eq!(override_root(None, Some(dir.join("svc"))), Ok(Some(canonical.clone())));
+        // The flag wins over the environment variable
+        let both = override_root(Some(dir.join("svc")), Some(dir.join("missing")));
+        assert_eq!(both, Ok(Some(canonical)));
+
+        let err = override_root(None, Some(dir.join("missing"))).unwrap_err();
+        assert!(err.starts_with("PERSEUS_ROOT "), "{}", err);
+        assert!(err.ends_with("does not exist"), "{}", err);
+        let err = override_root(Some(dir.join("notes.txt")), None).unwrap_err();
+        assert!(err.starts_with("--root ") && err.ends_with("is not a directory"), "{}", err);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_backup_path() {
+        assert_eq!(
+            backup_path(Path::new("/x/.perseus/collection.json")),
+            PathBuf::from("/x/.perseus/collection.json.bak")
+        );
+    }
+}
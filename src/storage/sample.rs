@@ -0,0 +1,206 @@
+//! The sample project offered on first run: a few documented requests
+//! against httpbin.org and an environment to send them with. It is built
+//! here rather than shipped as JSON so it always matches the current
+//! collection format, and installed like an imported archive.
+
+use crate::storage::archive::{self, ImportedProject, ProjectArchive};
+use crate::storage::collection::CollectionStore;
+use crate::storage::environment::{EnvScope, Environment, EnvironmentVariable};
+use crate::storage::postman::{
+    PostmanAuth, PostmanBody, PostmanHeader, PostmanItem, PostmanRequest,
+};
+
+pub const SAMPLE_PROJECT: &str = "httpbin sample";
+pub const SAMPLE_ENVIRONMENT: &str = "httpbin";
+
+/// Adds the sample project to `store`, with its environment unless one
+/// named [`SAMPLE_ENVIRONMENT`] already exists.
+pub fn create_sample_project(store: &mut CollectionStore) -> Result<ImportedProject, String> {
+    archive::install_project_archive(store, sample_archive())
+}
+
+fn sample_archive() -> ProjectArchive {
+    let mut project = PostmanItem::new_folder(SAMPLE_PROJECT.to_string());
+    project.description = Some(
+        "Requests against httpbin.org, which echoes back what it receives. Pick the \
+         httpbin environment with Ctrl+n, then send any of them with Ctrl+r."
+            .into(),
+    );
+    project.item = vec![
+        folder(
+            "Basics",
+            vec![
+                request(
+                    "Query parameters",
+                    "GET",
+                    "{{base_url}}/get?greeting=hello&from=perseus",
+                    "httpbin answers with the query parameters it parsed under \"args\".",
+                ),
+                with_body(
+                    request(
+                        "Post JSON",
+                        "POST",
+                        "{{base_url}}/post",
+                        "Sends the body as JSON; the echo shows it under \"json\".",
+                    ),
+                    PostmanBody::json("{\n  \"name\": \"perseus\",\n  \"tags\": [\"sample\"]\n}"),
+                ),
+                with_header(
+                    request(
+                        "Custom headers",
+                        "GET",
+                        "{{base_url}}/headers",
+                        "Shows every header that arrived, including X-Sample set here.",
+                    ),
+                    "X-Sample",
+                    "perseus",
+                ),
+            ],
+        ),
+        folder(
+            "Auth",
+            vec![
+                with_auth(
+                    request(
+                        "Basic auth",
+                        "GET",
+                        "{{base_url}}/basic-auth/{{username}}/{{password}}",
+                        "Succeeds only with the username and password from the environment.",
+                    ),
+                    PostmanAuth::basic("{{username}}", "{{password}}"),
+                ),
+                with_auth(
+                    request(
+                        "Bearer token",
+                        "GET",
+                        "{{base_url}}/bearer",
+                        "Any token is accepted; remove the auth to see a 401.",
+                    ),
+                    PostmanAuth::bearer("{{token}}"),
+                ),
+            ],
+        ),
+        folder(
+            "Responses",
+            vec![
+                request(
+                    "JSON document",
+                    "GET",
+                    "{{base_url}}/json",
+                    "A nested JSON document; yv copies the value under the cursor.",
+                ),
+                request(
+                    "Status code",
+                    "GET",
+                    "{{base_url}}/status/418",
+                    "Responds with whatever status the path asks for.",
+                ),
+                request(
+                    "Redirects",
+                    "GET",
+                    "{{base_url}}/redirect/2",
+                    "Redirects twice before answering; Ctrl+o turns following off.",
+                ),
+                request(
+                    "Slow response",
+                    "GET",
+                    "{{base_url}}/delay/2",
+                    "Takes two seconds to answer.",
+                ),
+            ],
+        ),
+    ];
+    let environment = Environment {
+        name: SAMPLE_ENVIRONMENT.to_string(),
+        values: vec![
+            EnvironmentVariable::new("base_url", "https://httpbin.org"),
+            EnvironmentVariable::new("username", "perseus"),
+            EnvironmentVariable::new("password", "sample"),
+            EnvironmentVariable::new("token", "sample-token"),
+        ],
+        extends: None,
+        scope: EnvScope::Project,
+    };
+    ProjectArchive {
+        project,
+        environments: vec![environment],
+        session: None,
+    }
+}
+
+fn folder(name: &str, items: Vec<PostmanItem>) -> PostmanItem {
+    let mut folder = PostmanItem::new_folder(name.to_string());
+    folder.item = items;
+    folder
+}
+
+fn request(name: &str, method: &str, url: &str, description: &str) -> PostmanItem {
+    let request = PostmanRequest::new(method.to_string(), url.to_string(), Vec::new(), None);
+    let mut item = PostmanItem::new_request(name.to_string(), request);
+    item.description = Some(description.into());
+    item
+}
+
+fn with_body(mut item: PostmanItem, body: PostmanBody) -> PostmanItem {
+    if let Some(request) = item.request.as_mut() {
+        request.header.push(header("Content-Type", "application/json"));
+        request.body = Some(body);
+    }
+    item
+}
+
+fn with_header(mut item: PostmanItem, key: &str, value: &str) -> PostmanItem {
+    if let Some(request) = item.request.as_mut() {
+        request.header.push(header(key, value));
+    }
+    item
+}
+
+fn with_auth(mut item: PostmanItem, auth: PostmanAuth) -> PostmanItem {
+    if let Some(request) = item.request.as_mut() {
+        request.auth = Some(auth);
+    }
+    item
+}
+
+fn header(key: &str, value: &str) -> PostmanHeader {
+    PostmanHeader {
+        key: key.to_string(),
+        value: value.to_string(),
+        disabled: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit;
+    use crate::storage::environment::{is_safe_env_name, resolve_variables};
+
+    fn requests(item: &PostmanItem) -> Vec<&PostmanItem> {
+        let mut found = Vec::new();
+        for child in &item.item {
+            if child.is_request() {
+                found.push(child);
+            }
+            found.extend(requests(child));
+        }
+        found
+    }
+
+    #[test]
+    fn test_sample_requests_are_documented_and_resolve() {
+        let archive = sample_archive();
+        let env = &archive.environments[0];
+        let variables = resolve_variables(Some(env), &archive.environments).unwrap();
+        let all = requests(&archive.project);
+        assert!(all.len() >= 8);
+        for item in all {
+            assert!(item.description_text().is_some(), "{} has no description", item.name);
+            let url = item.request.as_ref().unwrap().url.as_str().unwrap();
+            let findings = audit::audit_url(url, |name| variables.contains_key(name));
+            assert!(findings.is_empty(), "{}", url);
+        }
+        assert!(is_safe_env_name(&env.name));
+    }
+}
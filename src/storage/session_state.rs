@@ -2,7 +2,9 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use crate::storage::project::{move_aside_corrupt, write_atomic};
 
 const SESSION_VERSION: u32 = 1;
 const SESSION_DIR_NAME: &str = "perseus";
@@ -13,17 +15,130 @@ pub struct SessionState {
     pub active_project_id: String,
     pub sidebar_width: u16,
     pub sidebar_visible: bool,
-    pub selection_id: Option<String>,
     pub current_request_id: Option<String>,
-    pub expanded: Vec<String>,
+    /// Expanded folders and the selected item of each project, keyed by
+    /// project id, so switching projects doesn't lose them.
+    #[serde(default)]
+    pub projects: HashMap<String, ProjectSidebarState>,
+    /// Sessions written before `projects` kept one project's sidebar here;
+    /// [`SessionState::migrate`] moves it into `projects`.
+    #[serde(rename = "selection_id", default, skip_serializing)]
+    pub legacy_selection_id: Option<String>,
+    #[serde(rename = "expanded", default, skip_serializing)]
+    pub legacy_expanded: Vec<String>,
     pub request_tab: String,
     pub response_tab: String,
+    /// Headers tab shown as a key/value table rather than text.
+    #[serde(default)]
+    pub headers_table: bool,
+    /// Response panel soft-wraps long lines rather than scrolling sideways.
+    #[serde(default = "default_response_wrap")]
+    pub response_wrap: bool,
+    /// Request body editor soft-wraps long lines.
+    #[serde(default)]
+    pub body_wrap: bool,
+    /// Where the user left each request they opened, keyed by request id.
+    #[serde(default)]
+    pub positions: HashMap<String, RequestPosition>,
+    /// How the last send of each request went, keyed by request id.
+    #[serde(default)]
+    pub last_runs: HashMap<String, LastRun>,
+    /// Recent sends of each request, oldest first, keyed by request id.
+    #[serde(default)]
+    pub send_history: HashMap<String, Vec<SendStat>>,
+    /// The user's own notes on each request, keyed by request id. They
+    /// stay out of the collection so teammates don't get them.
+    #[serde(default)]
+    pub notes: HashMap<String, String>,
+}
+
+fn default_response_wrap() -> bool {
+    true
+}
+
+/// One project's sidebar as the user left it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectSidebarState {
+    pub expanded: Vec<String>,
+    pub selection_id: Option<String>,
+}
+
+impl SessionState {
+    /// Moves the flat sidebar fields of an older session into `projects`,
+    /// under the project that was active then.
+    pub(super) fn migrate(&mut self) {
+        let selection_id = self.legacy_selection_id.take();
+        let expanded = std::mem::take(&mut self.legacy_expanded);
+        if selection_id.is_none() && expanded.is_empty() {
+            return;
+        }
+        self.projects
+            .entry(self.active_project_id.clone())
+            .or_insert(ProjectSidebarState { expanded, selection_id });
+    }
+}
+
+/// A (row, column) cursor in a text editor, in characters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CursorPosition {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl CursorPosition {
+    /// The position moved onto `lines`: past the last line lands on the last
+    /// line, past the end of a line lands at its end. Text edited outside
+    /// the app can leave a stored position beyond either.
+    pub fn clamped(self, lines: &[String]) -> CursorPosition {
+        let Some(last) = lines.len().checked_sub(1) else {
+            return CursorPosition::default();
+        };
+        let row = self.row.min(last);
+        CursorPosition {
+            row,
+            col: self.col.min(lines[row].chars().count()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RequestPosition {
+    pub url: CursorPosition,
+    pub headers: CursorPosition,
+    pub body: CursorPosition,
+    pub request_tab: Option<String>,
+    pub response_tab: Option<String>,
+    /// Cursor line in the response body, which sets how far it's scrolled.
+    pub response_scroll: usize,
+}
+
+/// The outcome of a request's most recent send, shown in the sidebar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LastRun {
+    /// Response status, or `None` when no response came back.
+    pub status: Option<u16>,
+}
+
+/// One send of a request, for its timing history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SendStat {
+    /// When it was sent, in seconds since the Unix epoch.
+    pub at: u64,
+    /// Response status, or `None` when no response came back.
+    pub status: Option<u16>,
+    pub duration_ms: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionStore {
     pub version: u32,
     pub sessions: HashMap<String, SessionState>,
+    /// Whether the user trusts each project root. Roots without an entry
+    /// haven't been asked yet.
+    #[serde(default)]
+    pub trust: HashMap<String, bool>,
 }
 
 impl Default for SessionStore {
@@ -31,6 +146,7 @@ impl Default for SessionStore {
         Self {
             version: SESSION_VERSION,
             sessions: HashMap::new(),
+            trust: HashMap::new(),
         }
     }
 }
@@ -63,21 +179,35 @@ fn ensure_session_dir() -> Result<PathBuf, String> {
 }
 
 pub fn load_sessions() -> Result<SessionStore, String> {
-    let path = match session_store_path() {
-        Some(path) if path.exists() => path,
-        _ => return Ok(SessionStore::default()),
-    };
+    match session_store_path() {
+        Some(path) if path.exists() => read_sessions(&path),
+        _ => Ok(SessionStore::default()),
+    }
+}
 
+/// Reads the store at `path`. A file that doesn't parse is moved aside, so
+/// the error is reported once and the next load starts from defaults.
+fn read_sessions(path: &Path) -> Result<SessionStore, String> {
     let contents =
-        fs::read_to_string(&path).map_err(|e| format!("Failed to read session store: {}", e))?;
-    let store: SessionStore =
-        serde_json::from_str(&contents).map_err(|e| format!("Failed to parse session store: {}", e))?;
+        fs::read_to_string(path).map_err(|e| format!("Failed to read session store: {}", e))?;
+    let mut store: SessionStore = match serde_json::from_str(&contents) {
+        Ok(store) => store,
+        Err(err) => {
+            let corrupt = move_aside_corrupt(path)?;
+            return Err(format!(
+                "Session store is corrupt ({}); moved to {}, starting fresh",
+                err,
+                corrupt.display()
+            ));
+        }
+    };
     if store.version != SESSION_VERSION {
         return Err(format!(
             "Unsupported session store version: {}",
             store.version
         ));
     }
+    store.sessions.values_mut().for_each(SessionState::migrate);
     Ok(store)
 }
 
@@ -86,7 +216,8 @@ pub fn save_sessions(store: &SessionStore) -> Result<(), String> {
     let path = session_store_path().ok_or("Could not resolve session store path")?;
     let json = serde_json::to_string_pretty(store)
         .map_err(|e| format!("Failed to serialize session store: {}", e))?;
-    fs::write(path, json).map_err(|e| format!("Failed to write session store: {}", e))?;
+    write_atomic(&path, json.as_bytes(), false)
+        .map_err(|e| format!("Failed to write session store: {}", e))?;
     Ok(())
 }
 
@@ -107,3 +238,161 @@ pub fn save_session_for_root(root_key: &str, session: SessionState) -> Result<()
     store.sessions.insert(root_key.to_string(), session);
     save_sessions(&store)
 }
+
+pub fn load_trust_for_root(root_key: &str) -> Result<Option<bool>, String> {
+    if root_key.trim().is_empty() {
+        return Ok(None);
+    }
+    let store = load_sessions()?;
+    Ok(store.trust.get(root_key).copied())
+}
+
+pub fn save_trust_for_root(root_key: &str, trusted: bool) -> Result<(), String> {
+    if root_key.trim().is_empty() {
+        return Err("Session root key is empty".to_string());
+    }
+    let mut store = load_sessions()?;
+    store.version = SESSION_VERSION;
+    store.trust.insert(root_key.to_string(), trusted);
+    save_sessions(&store)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_state_without_positions_still_loads() {
+        let json = r#"{
+            "active_project_id": "p",
+            "sidebar_width": 32,
+            "sidebar_visible": true,
+            "selection_id": null,
+            "current_request_id": null,
+            "expanded": [],
+            "request_tab": "Body",
+            "response_tab": "Body"
+        }"#;
+        let state: SessionState = serde_json::from_str(json).unwrap();
+        assert!(state.positions.is_empty());
+        assert!(state.last_runs.is_empty());
+        assert!(!state.headers_table);
+        assert!(state.response_wrap);
+
+        let partial: RequestPosition =
+            serde_json::from_str(r#"{"body": {"row": 3, "col": 1}}"#).unwrap();
+        assert_eq!(partial.body, CursorPosition { row: 3, col: 1 });
+        assert_eq!(partial.url, CursorPosition::default());
+        assert_eq!(partial.request_tab, None);
+        assert_eq!(partial.response_tab, None);
+    }
+
+    #[test]
+    fn test_last_runs_round_trip() {
+        let json = r#"{"ok": {"status": 204}, "timeout": {"status": null}}"#;
+        let runs: HashMap<String, LastRun> = serde_json::from_str(json).unwrap();
+        assert_eq!(runs["ok"], LastRun { status: Some(204) });
+        assert_eq!(runs["timeout"], LastRun { status: None });
+        let back: HashMap<String, LastRun> =
+            serde_json::from_str(&serde_json::to_string(&runs).unwrap()).unwrap();
+        assert_eq!(back, runs);
+
+        let json = r#"[{"at": 1700000000, "status": 200, "duration_ms": 84}]"#;
+        let history: Vec<SendStat> = serde_json::from_str(json).unwrap();
+        assert_eq!(history[0].duration_ms, 84);
+        assert_eq!(history[0].status, Some(200));
+    }
+
+    #[test]
+    fn test_notes_round_trip_through_the_store() {
+        let dir = env::temp_dir().join(format!("perseus-session-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(SESSION_FILE_NAME);
+        let json = r#"{
+            "active_project_id": "p",
+            "sidebar_width": 32,
+            "sidebar_visible": true,
+            "current_request_id": "r",
+            "request_tab": "Body",
+            "response_tab": "Body"
+        }"#;
+        let mut state: SessionState = serde_json::from_str(json).unwrap();
+        // Sessions from before notes load without any
+        assert!(state.notes.is_empty());
+
+        let note = "500 only when amount > 10000\nsee the \"limits\" doc";
+        state.notes.insert("r".to_string(), note.to_string());
+        let mut store = SessionStore::default();
+        store.sessions.insert("/work/api".to_string(), state);
+        write_atomic(&path, serde_json::to_string(&store).unwrap().as_bytes(), false).unwrap();
+
+        let reread = read_sessions(&path).unwrap();
+        let notes = &reread.sessions["/work/api"].notes;
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes["r"], note);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_flat_sidebar_fields_migrate_to_active_project() {
+        let json = r#"{
+            "active_project_id": "p",
+            "sidebar_width": 32,
+            "sidebar_visible": true,
+            "selection_id": "r",
+            "current_request_id": null,
+            "expanded": ["p", "f"],
+            "request_tab": "Body",
+            "response_tab": "Body"
+        }"#;
+        let mut state: SessionState = serde_json::from_str(json).unwrap();
+        state.migrate();
+        let sidebar = ProjectSidebarState {
+            expanded: vec!["p".to_string(), "f".to_string()],
+            selection_id: Some("r".to_string()),
+        };
+        assert_eq!(state.projects.get("p"), Some(&sidebar));
+
+        // Written back per project only, and read the same way
+        let written = serde_json::to_string(&state).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&written).unwrap();
+        assert!(value.get("selection_id").is_none() && value.get("expanded").is_none());
+        let mut reread: SessionState = serde_json::from_str(&written).unwrap();
+        reread.migrate();
+        assert_eq!(reread.projects, state.projects);
+        assert!(reread.legacy_expanded.is_empty() && reread.legacy_selection_id.is_none());
+    }
+
+    #[test]
+    fn test_corrupt_session_store_is_moved_aside() {
+        let dir = env::temp_dir().join(format!("perseus-session-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(SESSION_FILE_NAME);
+        fs::write(&path, r#"{"version": 1, "sessions": {"#).unwrap();
+
+        let err = read_sessions(&path).unwrap_err();
+        assert!(err.contains("corrupt"), "{}", err);
+        assert!(!path.exists());
+        assert_eq!(
+            fs::read_to_string(dir.join("session.json.corrupt")).unwrap(),
+            r#"{"version": 1, "sessions": {"#
+        );
+
+        // A readable store of another version is left where it is
+        fs::write(&path, r#"{"version": 99, "sessions": {}}"#).unwrap();
+        assert!(read_sessions(&path).unwrap_err().contains("version"));
+        assert!(path.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_cursor_position_clamps_to_text() {
+        let lines = vec!["{".to_string(), "  \"é\": 1".to_string()];
+        let at = |row, col| CursorPosition { row, col }.clamped(&lines);
+        assert_eq!(at(1, 4), CursorPosition { row: 1, col: 4 });
+        assert_eq!(at(1, 40), CursorPosition { row: 1, col: 8 });
+        assert_eq!(at(9, 3), CursorPosition { row: 1, col: 3 });
+        assert_eq!(at(9, 9), CursorPosition { row: 1, col: 8 });
+        assert_eq!(CursorPosition { row: 2, col: 2 }.clamped(&[]), CursorPosition::default());
+    }
+}
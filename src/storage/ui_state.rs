@@ -1,12 +1,21 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::path::Path;
 
-use crate::storage::project::{ensure_storage_dir, ui_state_path};
+use crate::storage::project::{
+    ensure_storage_dir, move_aside_corrupt, ui_state_path, write_atomic,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UiState {
     pub active_project_id: String,
     pub sidebar_width: u16,
+    /// The first-run overlay was shown and hasn't been answered yet.
+    #[serde(default)]
+    pub onboarding_pending: bool,
+    /// Custom HTTP methods used lately, most recent first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recent_methods: Vec<String>,
 }
 
 impl UiState {
@@ -14,21 +23,32 @@ impl UiState {
         Self {
             active_project_id,
             sidebar_width,
+            onboarding_pending: false,
+            recent_methods: Vec::new(),
         }
     }
 }
 
 pub fn load_ui_state() -> Result<Option<UiState>, String> {
-    let path = match ui_state_path() {
-        Some(path) if path.exists() => path,
-        _ => return Ok(None),
-    };
+    match ui_state_path() {
+        Some(path) if path.exists() => read_ui_state(&path).map(Some),
+        _ => Ok(None),
+    }
+}
 
+/// Reads the state at `path`. A file that doesn't parse is moved aside, so
+/// the error is reported once and the next load starts from defaults.
+fn read_ui_state(path: &Path) -> Result<UiState, String> {
     let contents =
-        fs::read_to_string(&path).map_err(|e| format!("Failed to read UI state: {}", e))?;
-    let state: UiState =
-        serde_json::from_str(&contents).map_err(|e| format!("Failed to parse UI state: {}", e))?;
-    Ok(Some(state))
+        fs::read_to_string(path).map_err(|e| format!("Failed to read UI state: {}", e))?;
+    serde_json::from_str(&contents).or_else(|err| {
+        let corrupt = move_aside_corrupt(path)?;
+        Err(format!(
+            "UI state is corrupt ({}); moved to {}, starting fresh",
+            err,
+            corrupt.display()
+        ))
+    })
 }
 
 pub fn save_ui_state(state: &UiState) -> Result<(), String> {
@@ -36,6 +56,31 @@ pub fn save_ui_state(state: &UiState) -> Result<(), String> {
     let path = ui_state_path().ok_or("Could not find project root")?;
     let json = serde_json::to_string_pretty(state)
         .map_err(|e| format!("Failed to serialize UI state: {}", e))?;
-    fs::write(path, json).map_err(|e| format!("Failed to write UI state: {}", e))?;
+    write_atomic(&path, json.as_bytes(), false)
+        .map_err(|e| format!("Failed to write UI state: {}", e))?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_corrupt_ui_state_is_moved_aside() {
+        let dir = env::temp_dir().join(format!("perseus-ui-state-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("ui.json");
+        fs::write(&path, "{\"active_project_id\": \"p\", \"sidebar_").unwrap();
+
+        let err = read_ui_state(&path).unwrap_err();
+        assert!(err.contains("corrupt"), "{}", err);
+        assert!(!path.exists());
+        assert!(dir.join("ui.json.corrupt").exists());
+
+        let state = UiState::new("p".to_string(), 32);
+        fs::write(&path, serde_json::to_string(&state).unwrap()).unwrap();
+        assert_eq!(read_ui_state(&path).unwrap().active_project_id, "p");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
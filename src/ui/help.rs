@@ -0,0 +1,242 @@
+// Key binding table behind the `?` cheat sheet
+
+/// Where focus was when the cheat sheet opened; its section is listed first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HelpContext {
+    Navigation,
+    Request,
+    Response,
+    Sidebar,
+    Editing,
+}
+
+pub struct HelpSection {
+    pub title: &'static str,
+    pub context: HelpContext,
+    /// `(keys, action)` pairs.
+    pub bindings: &'static [(&'static str, &'static str)],
+}
+
+pub const SECTIONS: &[HelpSection] = &[
+    HelpSection {
+        title: "Navigation Mode",
+        context: HelpContext::Navigation,
+        bindings: &[
+            ("h/j/k/l", "Move focus across UI"),
+            ("Arrow keys", "Same as h/j/k/l"),
+            ("e", "Focus sidebar"),
+            ("Enter", "Activate field (vim normal mode)"),
+            ("i", "Enter field (vim insert mode)"),
+            ("Ctrl+r", "Send request"),
+            ("Alt+r", "Re-send the last sent request (Ctrl+Shift+R where supported)"),
+            ("Alt+Enter", "Scratch send: edit and send once, request unchanged (Ctrl+Shift+Enter)"),
+            ("Ctrl+e", "Toggle sidebar (enter sidebar when opening)"),
+            ("Ctrl+p", "Project switcher (x archive, i import)"),
+            ("Ctrl+s", "Save request"),
+            ("Ctrl+n", "Switch environment (i import, x export, u usages, v variables)"),
+            ("Ctrl+o", "Request settings (proxy, redirects, polling)"),
+            ("Ctrl+,", "Config viewer (r reload, c contrast, m motion, s sort keys)"),
+            ("Ctrl+t", "Trust or untrust this project (outside the response panel)"),
+            ("Ctrl+x", "Privacy mode (mask tokens, passwords, secrets)"),
+            ("Ctrl+u", "Encode/decode clipboard (visual mode: the selection)"),
+            ("Ctrl+g", "Edit headers, body or response in $VISUAL / $EDITOR"),
+            ("\"", "Recent yanks (Enter copies one again)"),
+            ("M", "Message log (unreadable request files first)"),
+            ("O", "Offline mode: sends show the request's saved example"),
+            ("F12", "Frame timings overlay"),
+            ("?", "This cheat sheet (/ filters, j/k and PgUp/PgDn scroll)"),
+            ("q / Esc", "Quit"),
+        ],
+    },
+    HelpSection {
+        title: "Request Panel",
+        context: HelpContext::Request,
+        bindings: &[
+            ("Ctrl+h", "Show / hide password or API key value (auth)"),
+            ("V", "Raw HTTP message, e to edit"),
+            ("P", "Preview the resolved request without sending"),
+            ("B", "Show the open request in the sidebar (path in the panel title)"),
+            ("K", "Check the CORS preflight a browser would send first"),
+            ("Ctrl+q", "URL: a part per line, query decoded (y / Y copy it as is / decoded)"),
+            ("t", "Headers as text / table (Space toggles a row)"),
+            ("w", "Body: wrap long lines on / off (gj / gk move by screen line)"),
+            ("F", "Body: format JSON"),
+            ("Ctrl+Space", "Auth / Body: leave it out of sends, kept as (off)"),
+            ("| / !", "Page the body in $PAGER / pipe it through a command"),
+            ("a-z", "Method popup: jump to the next method with that letter"),
+        ],
+    },
+    HelpSection {
+        title: "Response Panel",
+        context: HelpContext::Response,
+        bindings: &[
+            ("Ctrl+f / Ctrl+b", "Scroll down / up a page (each tab keeps its place)"),
+            ("Ctrl+d / Ctrl+u", "Scroll down / up half a page"),
+            ("Ctrl+t", "Recent sends of the request with their durations"),
+            ("y / Y", "Copy response body / headers"),
+            ("yv", "Copy JSON value under cursor (response body, vim)"),
+            ("B / R", "Visual selection in the body: make it the request body / and send"),
+            ("p / C", "Pin response / compare with pin"),
+            ("n", "Your notes on the request (kept in the session, 📝 in the title)"),
+            ("S", "Save response body to file"),
+            ("E / X", "Save response as an example / list saved examples"),
+            ("H", "Hex view for binary responses"),
+            ("P", "Text preview for HTML responses"),
+            ("w", "Wrap long lines on / off (off: h/l scroll sideways)"),
+            ("#", "Number repeated header names (Set-Cookie [1/2])"),
+            ("o / /", "Headers tab: sort by group and name / filter (Esc clears)"),
+            ("A", "Copy all response headers (Y copies the shown ones)"),
+            ("| / !", "Page the body in $PAGER / pipe it through a command"),
+            ("D", "Show / hide the full error of a failed request"),
+            ("V", "Schema validation result (schema set in Ctrl+o settings)"),
+        ],
+    },
+    HelpSection {
+        title: "Sidebar",
+        context: HelpContext::Sidebar,
+        bindings: &[
+            ("Enter / i", "Edit sidebar"),
+            ("Esc", "Return to navigation"),
+            ("j/k or ↑/↓", "Move selection"),
+            ("h", "Collapse / parent"),
+            ("l / Enter", "Toggle folder / open request"),
+            ("a", "Add request, folder or URL (Tab picks a template)"),
+            ("r", "Rename"),
+            ("d", "Delete"),
+            ("D", "Duplicate"),
+            ("m", "Move"),
+            ("p", "Lock / unlock (locked requests open read-only)"),
+            ("n", "Name requests after method and path"),
+            ("N", "Toggle auto naming for the project"),
+            ("R", "Run requests here, optionally once per data file row or in parallel"),
+            ("U", "Audit URLs for literal hosts and undefined variables"),
+            ("C", "Changelog: recent adds, renames, moves and deletes, and outside edits"),
+            ("c", "Copy path"),
+            ("t", "Save request as template"),
+            ("T", "Edit tags (name:color, comma separated)"),
+            ("/", "Search"),
+            ("f", "Filter by method, tag, unsaved changes or failing (Esc clears)"),
+            ("[ / ]", "Outdent / indent"),
+            ("Shift+h/l", "Collapse / expand all"),
+        ],
+    },
+    HelpSection {
+        title: "Vim Editing Mode",
+        context: HelpContext::Editing,
+        bindings: &[
+            ("h/j/k/l", "Cursor movement"),
+            ("w/b/e", "Word forward/back/end"),
+            ("0/^/$", "Line start/end"),
+            ("gg/G", "Top/bottom"),
+            ("i/a/I/A", "Enter insert mode"),
+            ("o/O", "New line below/above (multiline)"),
+            ("v/V", "Visual / visual line"),
+            ("d/c/y", "Delete/change/yank (+ motion)"),
+            ("dd/cc/yy", "Operate on line"),
+            ("x/X", "Delete char forward/backward"),
+            ("D/C", "Delete/change to end of line"),
+            ("p", "Paste"),
+            ("\"", "Pick one of the last 10 yanks to paste"),
+            ("clipboard", "y/d/c/x/D/C -> system; p from system"),
+            ("Cmd/Ctrl+C", "Copy selection to system clipboard"),
+            ("Cmd/Ctrl+V", "Paste from system clipboard"),
+            ("u / Ctrl+r", "Undo / redo"),
+            ("Ctrl+Space", "Body snippet (insert mode; .perseus/snippets.toml adds more)"),
+            ("Tab", "Next snippet tab stop (insert mode)"),
+            ("Enter", "Send request (URL field only)"),
+            ("Esc", "Exit to navigation mode"),
+        ],
+    },
+];
+
+/// Sections in display order: the one for `context` first, then the
+/// navigation bindings, then the rest as listed in [`SECTIONS`].
+pub fn ordered_sections(context: HelpContext) -> Vec<&'static HelpSection> {
+    let rank = |section: &HelpSection| {
+        if section.context == context {
+            0
+        } else if section.context == HelpContext::Navigation {
+            1
+        } else {
+            2
+        }
+    };
+    let mut sections: Vec<&HelpSection> = SECTIONS.iter().collect();
+    sections.sort_by_key(|section| rank(section));
+    sections
+}
+
+/// The sections and bindings to show for `filter`, matched case-insensitively
+/// against keys and actions. A filter matching a section title keeps the
+/// whole section; sections left without bindings are dropped.
+pub fn visible_bindings(
+    context: HelpContext,
+    filter: &str,
+) -> Vec<(&'static HelpSection, Vec<(&'static str, &'static str)>)> {
+    let needle = filter.trim().to_lowercase();
+    ordered_sections(context)
+        .into_iter()
+        .filter_map(|section| {
+            let whole = needle.is_empty() || section.title.to_lowercase().contains(&needle);
+            let bindings: Vec<_> = section
+                .bindings
+                .iter()
+                .copied()
+                .filter(|(keys, action)| {
+                    whole
+                        || keys.to_lowercase().contains(&needle)
+                        || action.to_lowercase().contains(&needle)
+                })
+                .collect();
+            (!bindings.is_empty()).then_some((section, bindings))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn titles(context: HelpContext, filter: &str) -> Vec<&'static str> {
+        visible_bindings(context, filter)
+            .into_iter()
+            .map(|(section, _)| section.title)
+            .collect()
+    }
+
+    #[test]
+    fn test_focused_section_comes_first() {
+        assert_eq!(
+            titles(HelpContext::Response, ""),
+            vec![
+                "Response Panel",
+                "Navigation Mode",
+                "Request Panel",
+                "Sidebar",
+                "Vim Editing Mode",
+            ]
+        );
+        assert_eq!(titles(HelpContext::Editing, "")[..2], ["Vim Editing Mode", "Navigation Mode"]);
+        assert_eq!(titles(HelpContext::Navigation, "")[0], "Navigation Mode");
+    }
+
+    #[test]
+    fn test_filter_matches_keys_actions_and_titles() {
+        let found = visible_bindings(HelpContext::Request, "CLIPBOARD");
+        let actions: Vec<_> = found
+            .iter()
+            .flat_map(|(_, bindings)| bindings.iter().map(|(_, action)| *action))
+            .collect();
+        assert!(actions.contains(&"Encode/decode clipboard (visual mode: the selection)"));
+        assert!(actions.contains(&"Paste from system clipboard"));
+        assert!(!titles(HelpContext::Request, "clipboard").contains(&"Sidebar"));
+
+        let sidebar = visible_bindings(HelpContext::Request, "sidebar");
+        let whole = sidebar.iter().find(|(s, _)| s.title == "Sidebar").unwrap();
+        assert_eq!(whole.1.len(), whole.0.bindings.len());
+
+        let f12 = visible_bindings(HelpContext::Sidebar, "F12");
+        assert_eq!(f12[0].1, vec![("F12", "Frame timings overlay")]);
+        assert!(visible_bindings(HelpContext::Sidebar, "no such binding").is_empty());
+    }
+}
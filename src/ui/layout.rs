@@ -137,3 +137,84 @@ impl ResponseLayout {
         }
     }
 }
+
+/// Smallest terminal the full layout is drawn in; anything smaller gets a
+/// placeholder instead.
+pub const MIN_WIDTH: u16 = 60;
+pub const MIN_HEIGHT: u16 = 15;
+
+pub fn fits_minimum(area: Rect) -> bool {
+    area.width >= MIN_WIDTH && area.height >= MIN_HEIGHT
+}
+
+/// A `width` x `height` rectangle centered in `area`, shrunk to fit.
+pub fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+/// A `width` x `height` rectangle with its top-left corner at `(x, y)`,
+/// shrunk and moved up or left as needed to stay inside `bounds`.
+pub fn anchored(bounds: Rect, x: u16, y: u16, width: u16, height: u16) -> Rect {
+    let width = width.min(bounds.width);
+    let height = height.min(bounds.height);
+    Rect::new(
+        x.clamp(bounds.x, bounds.right() - width),
+        y.clamp(bounds.y, bounds.bottom() - height),
+        width,
+        height,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inside(inner: Rect, outer: Rect) -> bool {
+        inner.x >= outer.x
+            && inner.y >= outer.y
+            && inner.right() <= outer.right()
+            && inner.bottom() <= outer.bottom()
+    }
+
+    #[test]
+    fn test_centered_fits_any_area() {
+        let frame = Rect::new(0, 0, 80, 24);
+        assert_eq!(centered(frame, 30, 10), Rect::new(25, 7, 30, 10));
+
+        for (w, h) in [(0, 0), (1, 1), (12, 3), (19, 40)] {
+            let area = Rect::new(0, 0, w, h);
+            let popup = centered(area, 60, 10);
+            assert!(inside(popup, area), "{:?} in {:?}", popup, area);
+        }
+
+        let offset = Rect::new(5, 3, 20, 8);
+        assert!(inside(centered(offset, 60, 10), offset));
+    }
+
+    #[test]
+    fn test_anchored_stays_in_bounds() {
+        let frame = Rect::new(0, 0, 80, 24);
+        assert_eq!(anchored(frame, 2, 3, 15, 10), Rect::new(2, 3, 15, 10));
+        // Below the bottom edge: moved up rather than drawn off screen
+        assert_eq!(anchored(frame, 2, 20, 15, 10), Rect::new(2, 14, 15, 10));
+
+        let tiny = Rect::new(0, 0, 10, 4);
+        let popup = anchored(tiny, 8, 3, 15, 10);
+        assert_eq!(popup, Rect::new(0, 0, 10, 4));
+        assert!(inside(anchored(Rect::new(0, 0, 0, 0), 3, 3, 15, 10), Rect::default()));
+    }
+
+    #[test]
+    fn test_fits_minimum() {
+        assert!(fits_minimum(Rect::new(0, 0, MIN_WIDTH, MIN_HEIGHT)));
+        assert!(!fits_minimum(Rect::new(0, 0, MIN_WIDTH - 1, 40)));
+        assert!(!fits_minimum(Rect::new(0, 0, 200, MIN_HEIGHT - 1)));
+    }
+}
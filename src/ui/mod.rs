@@ -1,7 +1,21 @@
+mod help;
 mod layout;
+mod statusbar;
+mod theme;
 mod widgets;
 
-use layout::{AppLayout, BodyLayout, RequestInputLayout, RequestLayout, ResponseLayout};
+pub use theme::Theme;
+pub use widgets::{display_line_move, hex_dump_lines};
+
+use help::HelpContext;
+use widgets::{breadcrumb, clip_spans, cursor_window, percentile, sparkline, width_before};
+use layout::{
+    anchored, centered, AppLayout, BodyLayout, RequestInputLayout, RequestLayout, ResponseLayout,
+};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, UNIX_EPOCH};
+
 use ratatui::{
     layout::{Alignment, Constraint, Layout, Rect},
     style::{Color, Modifier, Style},
@@ -10,19 +24,37 @@ use ratatui::{
     Frame,
 };
 use tui_textarea::TextArea;
-use unicode_width::UnicodeWidthChar;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 use crate::app::{
-    App, AppMode, AuthField, AuthType, BodyField, BodyMode, HttpMethod, KvColumn, KvFocus, KvPair,
-    Method, MultipartField, MultipartFieldType, Panel, RequestField, RequestTab,
-    ResponseBodyRenderCache, ResponseHeadersRenderCache, ResponseStatus, ResponseTab,
-    SidebarPopup, WrapCache,
+    App, AppMode, AuthField, AuthType, BodyConfirm, BodyField, BodyMode, BodyWrapCache,
+    ChangelogPopup, ClipboardRingPopup, CodecPopup, CodecSource, CompareView, CorsPopup,
+    DraftPrompt, EnvFilePrompt, ErrorPopup, ExamplePopup, HttpMethod, KvColumn, KvFocus, KvPair,
+    Method, MultipartField, MultipartFieldType, NotesPopup, Onboarding,
+    Panel, PanelCache, PipePopup, ProtectedConfirm, RawView, RequestField, RequestTab,
+    EnvVariablesPopup, ScratchSend, SnippetPopup, UsagesPopup,
+    ResponseBodyRenderCache, ResponseHeadersRenderCache, ResponseStatus, ResponseTab, SaveStatus,
+    SendConfirm, SendPreviewPopup, SettingsPopup, SettingsRow, SidebarFilter, SidebarPopup,
+    SpecUpdatePrompt, UnresolvedConfirm, UrlAuditPopup, UrlOutlinePopup, WrapCache,
+    HEADER_GROUP_MARK, ONBOARDING_CHOICES,
+};
+use crate::config::{ConfigSource, StatusSegment};
+use crate::encoding::Charset;
+use crate::notify::Level;
+use crate::{diff, http, perf, sendlog};
+use crate::storage::environment::{self, EnvScope};
+use crate::schema::SchemaCheck;
+use crate::storage::{
+    ChangeKind, ItemTag, LockOwner, NodeKind, ProxySetting, RequestFileError, SchemaSetting,
+    SendStat, TagColor, MAX_TAGS,
 };
-use crate::perf;
-use crate::storage::NodeKind;
 use crate::vim::VimMode;
 
 pub fn render(frame: &mut Frame, app: &mut App) {
+    if !layout::fits_minimum(frame.area()) {
+        render_too_small(frame);
+        return;
+    }
     let layout = AppLayout::new(frame.area(), app.sidebar_visible, app.sidebar_width);
     let request_split = Layout::vertical([Constraint::Length(3), Constraint::Min(3)])
         .split(layout.request_area);
@@ -35,6 +67,7 @@ pub fn render(frame: &mut Frame, app: &mut App) {
     render_request_panel(frame, app, request_split[1]);
     render_response_panel(frame, app, layout.response_area);
     render_status_bar(frame, app, layout.status_bar);
+    render_notification_stack(frame, app, layout.status_bar);
 
     if app.show_method_popup {
         render_method_popup(frame, app, input_layout.method_area);
@@ -52,25 +85,198 @@ pub fn render(frame: &mut Frame, app: &mut App) {
         render_env_popup(frame, app);
     }
 
+    if let Some(ref prompt) = app.env_file_prompt {
+        render_env_file_prompt(frame, prompt);
+    }
+
+    if let Some(ref popup) = app.settings_popup {
+        render_settings_popup(frame, popup);
+    }
+
+    if let Some(ref input) = app.save_response_popup {
+        render_save_response_popup(frame, input);
+    }
+
+    if let Some(ref popup) = app.example_popup {
+        render_example_popup(frame, app, popup);
+    }
+
+    if let Some(ref popup) = app.pipe_popup {
+        render_pipe_popup(frame, popup);
+    }
+
+    if let Some(ref view) = app.raw_view {
+        render_raw_view(frame, view);
+    }
+
+    if let Some(ref popup) = app.url_outline {
+        render_url_outline(frame, popup);
+    }
+
+    if let Some(ref popup) = app.send_preview {
+        render_send_preview(frame, popup);
+    }
+
+    if let Some(ref popup) = app.cors_popup {
+        render_cors_popup(frame, popup);
+    }
+
+    if let Some(ref scratch) = app.scratch_send {
+        render_scratch_send(frame, scratch);
+    }
+
+    if let Some(ref view) = app.compare_view {
+        render_compare_view(frame, view);
+    }
+
+    if let Some(ref popup) = app.codec_popup {
+        render_codec_popup(frame, popup, app.theme);
+    }
+
+    if let Some(popup) = app.clipboard_ring {
+        render_clipboard_ring(frame, app, popup);
+    }
+
+    if let Some(ref popup) = app.snippet_popup {
+        render_snippet_popup(frame, popup);
+    }
+
+    if app.run_popup.is_some() {
+        render_run_popup(frame, app);
+    }
+
+    if let Some(ref popup) = app.url_audit {
+        render_url_audit(frame, popup, app.theme);
+    }
+
+    if let Some(ref popup) = app.changelog_popup {
+        render_changelog(frame, popup);
+    }
+
+    if let Some(ref popup) = app.usages_popup {
+        render_usages(frame, popup, app.theme);
+    }
+
+    if let Some(ref popup) = app.env_variables {
+        render_env_variables(frame, app, popup);
+    }
+
+    if app.show_config_viewer {
+        render_config_viewer(frame, app);
+    }
+
+    if let Some(ref confirm) = app.send_confirm {
+        render_send_confirm(frame, confirm);
+    }
+
+    if let Some(ref confirm) = app.protected_confirm {
+        render_protected_confirm(frame, confirm);
+    }
+
+    if let Some(ref confirm) = app.unresolved_confirm {
+        render_unresolved_confirm(frame, confirm);
+    }
+
+    if let Some(ref confirm) = app.body_confirm {
+        render_body_confirm(frame, confirm);
+    }
+
+    if let Some(ref prompt) = app.draft_prompt {
+        render_draft_prompt(frame, prompt);
+    }
+
+    if let Some(ref prompt) = app.spec_update {
+        render_spec_update(frame, prompt);
+    }
+
+    if let Some(ref root) = app.trust_prompt {
+        render_trust_prompt(frame, root, app.project_trusted);
+    }
+
+    if app.lock_prompt {
+        render_lock_prompt(frame, app.lock_owner);
+    }
+
+    if let Some(ref onboarding) = app.onboarding {
+        render_onboarding(frame, onboarding, app.theme);
+    }
+
+    if app.show_send_history {
+        render_send_history(frame, app);
+    }
+
+    if let Some(ref popup) = app.notes_popup {
+        render_notes_popup(frame, popup);
+    }
+
+    if app.show_schema_check {
+        render_schema_check(frame, app);
+    }
+
+    if app.show_message_log {
+        render_message_log(frame, app);
+    }
+
+    if let Some(index) = app.request_file_popup {
+        render_request_files(frame, &app.request_file_errors, index, app.theme);
+    }
+
+    if let Some(ref popup) = app.error_popup {
+        render_error_popup(frame, popup);
+    }
+
     if app.show_help {
-        render_help_overlay(frame);
+        render_help_overlay(frame, app);
     }
+
+    if app.show_perf_overlay {
+        render_perf_overlay(frame, app);
+    }
+}
+
+fn render_too_small(frame: &mut Frame) {
+    let area = frame.area();
+    let text = format!(
+        "terminal too small (need {}x{})",
+        layout::MIN_WIDTH,
+        layout::MIN_HEIGHT
+    );
+    let height = 3.min(area.height);
+    let placeholder = Paragraph::new(text)
+        .style(Style::default().fg(Color::Yellow))
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    frame.render_widget(placeholder, centered(area, area.width, height));
 }
 
 fn render_sidebar(frame: &mut Frame, app: &mut App, area: Rect) {
-    let border_color = if app.focus.panel == Panel::Sidebar {
-        Color::Green
-    } else {
-        Color::DarkGray
+    let focused = app.focus.panel == Panel::Sidebar;
+    let title = match &app.sidebar.filter {
+        SidebarFilter::All => "Explorer".to_string(),
+        filter => format!("Explorer [{}]", filter.label()),
     };
     let block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(border_color))
-        .title("Explorer");
+        .border_style(app.theme.border(focused, Color::DarkGray))
+        .title(app.theme.title(&title, focused));
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
+    if app.panel_caches.sidebar.is_stale(app.dirty_panels.sidebar, inner) {
+        let lines = sidebar_paragraph_lines(app, inner);
+        app.panel_caches.sidebar = PanelCache { area: inner, lines };
+    }
+    let paragraph =
+        Paragraph::new(app.panel_caches.sidebar.lines.clone()).wrap(Wrap { trim: false });
+    frame.render_widget(paragraph, inner);
+
+    if let Some(popup) = &app.sidebar.popup {
+        render_sidebar_popup(frame, app, popup, area);
+    }
+}
+
+fn sidebar_paragraph_lines(app: &mut App, inner: Rect) -> Vec<Line<'static>> {
     let project_name = app
         .project_list
         .iter()
@@ -80,8 +286,12 @@ fn render_sidebar(frame: &mut Frame, app: &mut App, area: Rect) {
 
     let search_query = app.sidebar.search_query.clone();
     let selected_id = app.sidebar.selection_id;
+    let inline_rename = match &app.sidebar.popup {
+        Some(SidebarPopup::RenameInline(input)) => Some(input.clone()),
+        _ => None,
+    };
 
-    let mut lines: Vec<Line> = Vec::new();
+    let mut lines: Vec<Line<'static>> = Vec::new();
     let header = Line::from(vec![
         Span::styled(
             format!("Project: {}", project_name),
@@ -102,6 +312,13 @@ fn render_sidebar(frame: &mut Frame, app: &mut App, area: Rect) {
     }
 
     let width = inner.width as usize;
+    let list_height = (inner.height as usize).saturating_sub(lines.len());
+    let scroll = app.sidebar_scroll_for(list_height);
+    let flash_id = app.sidebar.flash.map(|(id, _)| id);
+    let audit_flags = app.url_audit_flags.clone();
+    let chained = app.chained_requests.clone();
+    let run_status = app.config.ui.sidebar_run_status;
+    let selection_style = app.theme.selection();
     {
         let items = app.sidebar_lines();
         if items.is_empty() {
@@ -110,10 +327,15 @@ fn render_sidebar(frame: &mut Frame, app: &mut App, area: Rect) {
                 Style::default().fg(Color::DarkGray),
             )));
         } else {
-            for item in items.iter() {
+            for item in items.iter().skip(scroll).take(list_height) {
                 let is_selected = Some(item.id) == selected_id;
-                let base_style = if is_selected {
-                    Style::default().bg(Color::DarkGray).fg(Color::White)
+                let dimmed = inline_rename.is_some() && !is_selected;
+                let base_style = if flash_id == Some(item.id) {
+                    Style::default().bg(Color::Yellow).fg(Color::Black)
+                } else if is_selected {
+                    selection_style
+                } else if dimmed {
+                    Style::default().fg(Color::DarkGray)
                 } else {
                     Style::default().fg(Color::White)
                 };
@@ -122,18 +344,41 @@ fn render_sidebar(frame: &mut Frame, app: &mut App, area: Rect) {
 
                 let push_span =
                     |content: String, style: Style, spans: &mut Vec<Span>, len: &mut usize| {
-                        *len = len.saturating_add(content.chars().count());
+                        *len = len.saturating_add(content.width());
                         spans.push(Span::styled(content, style));
                     };
 
+                if let Some(input) = inline_rename.as_ref().filter(|_| is_selected) {
+                    push_span(item.lead(), base_style, &mut spans, &mut text_len);
+                    let (before, rest) = input.value.split_at(input.cursor.min(input.value.len()));
+                    let mut rest = rest.chars();
+                    let at_cursor = rest.next().map(String::from).unwrap_or_else(|| " ".into());
+                    let edit_style = Style::default().fg(Color::White).bg(Color::Black);
+                    push_span(before.to_string(), edit_style, &mut spans, &mut text_len);
+                    push_span(
+                        at_cursor,
+                        edit_style.add_modifier(Modifier::REVERSED),
+                        &mut spans,
+                        &mut text_len,
+                    );
+                    push_span(rest.collect(), edit_style, &mut spans, &mut text_len);
+                    lines.push(Line::from(spans));
+                    continue;
+                }
+
                 if !item.prefix.is_empty() {
                     push_span(item.prefix.clone(), base_style, &mut spans, &mut text_len);
                 }
+                let label_end;
 
                 match item.kind {
                     NodeKind::Request => {
                         if let Some(ref method) = item.method {
-                            let method_style = base_style.fg(method_color(method));
+                            let method_style = if dimmed {
+                                base_style
+                            } else {
+                                base_style.fg(method_color(method))
+                            };
                             push_span(
                                 method.as_str().to_string(),
                                 method_style,
@@ -143,6 +388,35 @@ fn render_sidebar(frame: &mut Frame, app: &mut App, area: Rect) {
                             push_span(" ".to_string(), base_style, &mut spans, &mut text_len);
                         }
                         push_span(item.label.clone(), base_style, &mut spans, &mut text_len);
+                        label_end = spans.len();
+                        if chained.contains(&item.id) {
+                            let style = if is_selected {
+                                base_style
+                            } else {
+                                Style::default().fg(Color::DarkGray)
+                            };
+                            push_span(" \u{21b3}".to_string(), style, &mut spans, &mut text_len);
+                        }
+                        if let Some(run) = item.last_run.filter(|_| run_status) {
+                            let (text, color) = match run.status {
+                                Some(status) => (status.to_string(), status_color(status)),
+                                None => ("ERR".to_string(), Color::Yellow),
+                            };
+                            let style = if is_selected || dimmed {
+                                base_style
+                            } else {
+                                Style::default().fg(color)
+                            };
+                            push_span(format!(" {}", text), style, &mut spans, &mut text_len);
+                        }
+                        if audit_flags.contains(&item.id) {
+                            let style = if is_selected {
+                                base_style
+                            } else {
+                                Style::default().fg(Color::Yellow)
+                            };
+                            push_span(" !".to_string(), style, &mut spans, &mut text_len);
+                        }
                     }
                     NodeKind::Folder | NodeKind::Project => {
                         let label = if item.marker.is_empty() {
@@ -151,8 +425,28 @@ fn render_sidebar(frame: &mut Frame, app: &mut App, area: Rect) {
                             format!("{} {}", item.marker, item.label)
                         };
                         push_span(label, base_style, &mut spans, &mut text_len);
+                        label_end = spans.len();
+                    }
+                }
+                if item.locked {
+                    push_span(" \u{1f512}".to_string(), base_style, &mut spans, &mut text_len);
+                }
+
+                // Tags go after the name while they fit; a narrow sidebar
+                // drops them rather than wrapping the row
+                let mut room = width.saturating_sub(1).saturating_sub(text_len);
+                let mut chips = Vec::new();
+                for tag in &item.tags {
+                    let chip_width = tag.name.width() + 1;
+                    if chip_width > room {
+                        break;
                     }
+                    room -= chip_width;
+                    text_len += chip_width;
+                    chips.push(Span::styled(" ", base_style));
+                    chips.push(tag_chip(tag));
                 }
+                spans.splice(label_end..label_end, chips);
 
                 let max_width = width.saturating_sub(1);
                 if max_width > text_len {
@@ -164,25 +458,77 @@ fn render_sidebar(frame: &mut Frame, app: &mut App, area: Rect) {
             }
         }
     }
+    lines
+}
 
-    let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false });
-    frame.render_widget(paragraph, inner);
-
-    if let Some(popup) = &app.sidebar.popup {
-        render_sidebar_popup(frame, app, popup, area);
-    }
+fn tag_chip(tag: &ItemTag) -> Span<'static> {
+    let color = match tag.color {
+        TagColor::Red => Color::Red,
+        TagColor::Green => Color::Green,
+        TagColor::Yellow => Color::Yellow,
+        TagColor::Blue => Color::Blue,
+        TagColor::Magenta => Color::Magenta,
+        TagColor::Cyan => Color::Cyan,
+    };
+    Span::styled(tag.name.clone(), Style::default().fg(Color::Black).bg(color))
 }
 
 fn render_sidebar_popup(frame: &mut Frame, app: &App, popup: &SidebarPopup, area: Rect) {
     let (title, body_lines) = match popup {
-        SidebarPopup::Add(input) => (
-            "Add",
+        SidebarPopup::Add {
+            input,
+            templates,
+            template,
+        } => {
+            let mut lines = vec![
+                Line::from("Name, path (folder/req or folder/) or URL"),
+                Line::from(""),
+                render_input_line(input),
+                Line::from(""),
+            ];
+            let defaults = app.config.defaults.summary();
+            if templates.is_empty() {
+                if let Some(summary) = defaults {
+                    lines.push(Line::from(Span::styled(
+                        format!("New requests: {}", summary),
+                        Style::default().fg(Color::DarkGray),
+                    )));
+                    lines.push(Line::from(""));
+                }
+                lines.push(Line::from("Enter: create  Esc: cancel"));
+            } else {
+                let blank = match defaults {
+                    Some(summary) => format!("blank: {}", summary),
+                    None => "blank".to_string(),
+                };
+                let choice = template
+                    .checked_sub(1)
+                    .map(|i| templates[i].1.as_str())
+                    .unwrap_or(&blank);
+                lines.push(Line::from(vec![
+                    Span::raw("From: "),
+                    Span::styled(
+                        format!("< {} >", choice),
+                        Style::default().fg(Color::Yellow),
+                    ),
+                ]));
+                lines.push(Line::from(""));
+                lines.push(Line::from("Enter: create  Tab: template  Esc: cancel"));
+            }
+            ("Add", lines)
+        }
+        SidebarPopup::Tags { input, .. } => (
+            "Tags",
             vec![
-                Line::from("Name or path (folder/req or folder/)"),
+                Line::from(format!("Up to {} tags: name or name:color, comma separated", MAX_TAGS)),
+                Line::from(Span::styled(
+                    TagColor::ALL.map(|color| color.as_str()).join(" "),
+                    Style::default().fg(Color::DarkGray),
+                )),
                 Line::from(""),
-                Line::from(render_input_line(input)),
+                render_input_line(input),
                 Line::from(""),
-                Line::from("Enter: create  Esc: cancel"),
+                Line::from("Enter: save (empty clears)  Esc: cancel"),
             ],
         ),
         SidebarPopup::Rename(input) => (
@@ -190,17 +536,19 @@ fn render_sidebar_popup(frame: &mut Frame, app: &App, popup: &SidebarPopup, area
             vec![
                 Line::from("New name"),
                 Line::from(""),
-                Line::from(render_input_line(input)),
+                render_input_line(input),
                 Line::from(""),
                 Line::from("Enter: rename  Esc: cancel"),
             ],
         ),
+        // Drawn over the selected row by render_sidebar
+        SidebarPopup::RenameInline(_) => return,
         SidebarPopup::Search(input) => (
             "Search",
             vec![
                 Line::from("Filter items"),
                 Line::from(""),
-                Line::from(render_input_line(input)),
+                render_input_line(input),
                 Line::from(""),
                 Line::from("Enter: apply  Esc: clear"),
             ],
@@ -209,22 +557,77 @@ fn render_sidebar_popup(frame: &mut Frame, app: &App, popup: &SidebarPopup, area
             let mut lines = vec![Line::from("Select project"), Line::from("")];
             for (i, project) in app.project_list.iter().enumerate() {
                 let style = if i == *index {
-                    Style::default().bg(Color::DarkGray).fg(Color::White)
+                    app.theme.selection()
                 } else {
                     Style::default().fg(Color::White)
                 };
                 lines.push(Line::from(Span::styled(project.name.clone(), style)));
             }
             lines.push(Line::from(""));
-            lines.push(Line::from("Enter: switch  Esc: cancel"));
+            lines.push(Line::from("Enter: switch  x: archive  i: import  Esc: cancel"));
             ("Projects", lines)
         }
+        SidebarPopup::ArchiveExport { project_id, input, notes } => {
+            let name = app
+                .project_list
+                .iter()
+                .find(|p| p.id == *project_id)
+                .map(|p| p.name.clone())
+                .unwrap_or_default();
+            (
+                "Archive Project",
+                vec![
+                    Line::from(format!("Write '{}' to zip file", name)),
+                    Line::from(""),
+                    render_input_line(input),
+                    Line::from(format!(
+                        "[{}] Include my request notes",
+                        if *notes { "x" } else { " " }
+                    )),
+                    Line::from(""),
+                    Line::from("Enter: export  Tab: notes  Esc: cancel"),
+                ],
+            )
+        }
+        SidebarPopup::RunSetup { target, input, export, parallel } => {
+            let item = app.collection.get_item(*target);
+            let name = item.map(|item| item.name.clone()).unwrap_or_default();
+            let count = item.map(|item| crate::runner::run_order(item).len()).unwrap_or(0);
+            (
+                "Run",
+                vec![
+                    Line::from(format!("Run {} requests in '{}'", count, name)),
+                    Line::from("Data file, once per row (CSV or JSON, optional)"),
+                    render_input_line(input),
+                    Line::from(format!(
+                        "[{}] Export responses to runs/<time>/",
+                        if *export { "x" } else { " " }
+                    )),
+                    Line::from(match parallel {
+                        Some(limit) => format!("[x] Parallel, {} at once (Up/Down)", limit),
+                        None => "[ ] Parallel".to_string(),
+                    }),
+                    Line::from(""),
+                    Line::from("Enter: run  Tab: export  Ctrl+P: parallel  Esc: cancel"),
+                ],
+            )
+        }
+        SidebarPopup::ArchiveImport(input) => (
+            "Import Project",
+            vec![
+                Line::from("Project archive (.zip), Postman collection or OpenAPI spec to import"),
+                Line::from(""),
+                render_input_line(input),
+                Line::from(""),
+                Line::from("Enter: import  Esc: cancel"),
+            ],
+        ),
         SidebarPopup::Move { index, candidates } => {
             let mut lines = vec![Line::from("Move to"), Line::from("")];
             for (i, id) in candidates.iter().enumerate() {
                 let path = app.sidebar_tree.path_for(*id).join("/");
                 let style = if i == *index {
-                    Style::default().bg(Color::DarkGray).fg(Color::White)
+                    app.theme.selection()
                 } else {
                     Style::default().fg(Color::White)
                 };
@@ -243,13 +646,56 @@ fn render_sidebar_popup(frame: &mut Frame, app: &App, popup: &SidebarPopup, area
                 Line::from("n / Esc: cancel"),
             ],
         ),
+        SidebarPopup::LockedConfirm(action) => {
+            let holds = match app.sidebar_selected_id() {
+                Some(id) if app.sidebar_tree.locked_by(id).is_none() => "holds locked items",
+                _ => "is locked",
+            };
+            (
+                action.verb(),
+                vec![
+                    Line::from(format!("This item {}. {} it anyway?", holds, action.verb())),
+                    Line::from(""),
+                    Line::from("y / Enter: confirm"),
+                    Line::from("n / Esc: cancel"),
+                ],
+            )
+        }
+        SidebarPopup::Filter { index } => {
+            let tags = app.sidebar_tree.tags();
+            let mut lines = vec![Line::from("Show only"), Line::from("")];
+            for (i, filter) in SidebarFilter::choices(&tags).iter().enumerate() {
+                let style = if i == *index {
+                    app.theme.selection()
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                let label = match filter {
+                    SidebarFilter::All => "All items".to_string(),
+                    SidebarFilter::Method(method) => format!("{} requests", method.as_str()),
+                    SidebarFilter::Unsaved => "Requests with unsaved changes".to_string(),
+                    SidebarFilter::Failing => "Requests that failed last time".to_string(),
+                    SidebarFilter::Tag(name) => format!("Tagged {}", name),
+                };
+                let mut line = Line::from(Span::styled(label, style));
+                if let SidebarFilter::Tag(name) = filter {
+                    if let Some(tag) = tags.iter().find(|tag| tag.name == *name) {
+                        line.spans.insert(0, Span::raw(" "));
+                        line.spans.insert(0, tag_chip(tag));
+                    }
+                }
+                lines.push(line);
+            }
+            lines.push(Line::from(""));
+            lines.push(Line::from("Enter: apply  Esc: cancel"));
+            ("Filter", lines)
+        }
     };
 
     let width = std::cmp::min(60, area.width.saturating_sub(4));
-    let height = std::cmp::min(10, area.height.saturating_sub(4));
-    let x = area.x + (area.width.saturating_sub(width)) / 2;
-    let y = area.y + (area.height.saturating_sub(height)) / 2;
-    let popup_area = Rect::new(x, y, width, height);
+    let wanted = std::cmp::max(10, body_lines.len() as u16 + 2);
+    let height = std::cmp::min(wanted, area.height.saturating_sub(4));
+    let popup_area = centered(area, width, height);
 
     frame.render_widget(Clear, popup_area);
     let block = Block::default()
@@ -276,12 +722,12 @@ fn render_input_line(input: &crate::app::TextInput) -> Line<'static> {
 }
 
 fn render_method_popup(frame: &mut Frame, app: &App, method_area: Rect) {
-    let popup_item_count = HttpMethod::ALL.len() + 1; // 7 standard + "Custom..."
-    let width: u16 = 15;
-    let height: u16 = popup_item_count as u16 + 2;
-    let x = method_area.x;
-    let y = method_area.y + method_area.height;
-    let popup_area = Rect::new(x, y, width, height);
+    // 7 standard + "Custom..." + recent custom methods
+    let popup_item_count = HttpMethod::ALL.len() + 1 + app.recent_methods.len();
+    let longest_recent = app.recent_methods.iter().map(|m| m.len()).max().unwrap_or(0);
+    let width = (longest_recent as u16 + 4).max(15);
+    let height = popup_item_count as u16 + 2;
+    let popup_area = anchored(frame.area(), method_area.x, method_area.bottom(), width, height);
 
     frame.render_widget(Clear, popup_area);
 
@@ -334,11 +780,23 @@ fn render_method_popup(frame: &mut Frame, app: &App, method_area: Rect) {
         lines.push(Line::from(Span::styled(" Custom... ", style)));
     }
 
-    let list = Paragraph::new(lines);
+    for (i, method) in app.recent_methods.iter().enumerate() {
+        let color = method_color(&Method::Custom(method.clone()));
+        let style = if app.method_popup_index == custom_index + 1 + i {
+            Style::default().fg(Color::Black).bg(color)
+        } else {
+            Style::default().fg(color)
+        };
+        lines.push(Line::from(Span::styled(format!(" {} ", method), style)));
+    }
+
+    // Short frames clip the list; keep the selection in view
+    let scroll = app.method_popup_index.saturating_sub(inner.height.saturating_sub(1) as usize);
+    let list = Paragraph::new(lines).scroll((scroll as u16, 0));
     frame.render_widget(list, inner);
 }
 
-fn render_body_panel(frame: &mut Frame, app: &App, area: Rect) {
+fn render_body_panel(frame: &mut Frame, app: &mut App, area: Rect) {
     let layout = BodyLayout::new(area);
 
     render_body_mode_selector(frame, app, layout.mode_selector_area);
@@ -348,7 +806,16 @@ fn render_body_panel(frame: &mut Frame, app: &App, area: Rect) {
 
     match app.request.body_mode {
         BodyMode::Raw | BodyMode::Json | BodyMode::Xml => {
-            frame.render_widget(&app.request.body_editor, layout.content_area);
+            let editor = &app.request.body_editor;
+            if app.body_wrap && editor.lines().iter().any(|line| !line.is_empty()) {
+                let editing = body_focused
+                    && app.focus.body_field == BodyField::TextEditor
+                    && app.app_mode == AppMode::Editing;
+                let cache = &mut app.body_wrap_cache;
+                render_wrapped_body(frame, editor, cache, layout.content_area, editing);
+            } else {
+                frame.render_widget(editor, layout.content_area);
+            }
         }
         BodyMode::FormUrlEncoded => {
             render_kv_table(
@@ -382,6 +849,37 @@ fn render_body_panel(frame: &mut Frame, app: &App, area: Rect) {
     }
 }
 
+/// The body text soft-wrapped to `area`, with the editor's cursor and
+/// selection drawn into the wrapped rows while editing.
+fn render_wrapped_body(
+    frame: &mut Frame,
+    editor: &TextArea<'static>,
+    cache: &mut BodyWrapCache,
+    area: Rect,
+    editing: bool,
+) {
+    let mut hasher = DefaultHasher::new();
+    editor.lines().hash(&mut hasher);
+    let text_hash = hasher.finish();
+    if cache.text_hash != text_hash || cache.lines.is_empty() {
+        cache.lines = editor.lines().iter().map(|line| Line::from(line.clone())).collect();
+        cache.text_hash = text_hash;
+    }
+    let cursor = editing.then(|| editor.cursor());
+    let selection = if editing { editor.selection_range() } else { None };
+    cache.scroll = render_wrapped_response_cached(
+        frame,
+        area,
+        &cache.lines,
+        &mut cache.wrap_cache,
+        cache.text_hash,
+        cursor,
+        selection,
+        cache.scroll,
+        editing,
+    );
+}
+
 fn render_binary_panel(frame: &mut Frame, app: &App, area: Rect) {
     let chunks = Layout::vertical([
         Constraint::Length(1),
@@ -551,13 +1049,7 @@ fn render_kv_table(
                 frame.render_widget(ta, cols[1]);
             }
         } else {
-            let key_display = if row.key.is_empty() && !is_active_row {
-                ""
-            } else if row.key.is_empty() {
-                ""
-            } else {
-                row.key
-            };
+            let key_display = if row.key.is_empty() { "" } else { row.key };
             frame.render_widget(
                 Paragraph::new(format!(" {}", key_display)).style(key_style),
                 cols[1],
@@ -665,14 +1157,7 @@ fn render_body_mode_selector(frame: &mut Frame, app: &App, area: Rect) {
 fn render_body_mode_popup(frame: &mut Frame, app: &App, area: Rect) {
     let width: u16 = 22;
     let height: u16 = BodyMode::ALL.len() as u16 + 2;
-    let x = area.x + 2;
-    let y = area.y + 2;
-    let popup_area = Rect::new(
-        x.min(area.right().saturating_sub(width)),
-        y.min(area.bottom().saturating_sub(height)),
-        width.min(area.width),
-        height.min(area.height),
-    );
+    let popup_area = anchored(area, area.x + 2, area.y + 2, width, height);
 
     frame.render_widget(Clear, popup_area);
 
@@ -705,14 +1190,7 @@ fn render_body_mode_popup(frame: &mut Frame, app: &App, area: Rect) {
 fn render_auth_type_popup(frame: &mut Frame, app: &App, area: Rect) {
     let width: u16 = 20;
     let height: u16 = AuthType::ALL.len() as u16 + 2;
-    let x = area.x + 2;
-    let y = area.y + 2;
-    let popup_area = Rect::new(
-        x.min(area.right().saturating_sub(width)),
-        y.min(area.bottom().saturating_sub(height)),
-        width.min(area.width),
-        height.min(area.height),
-    );
+    let popup_area = anchored(area, area.x + 2, area.y + 2, width, height);
 
     frame.render_widget(Clear, popup_area);
 
@@ -746,11 +1224,9 @@ fn render_env_popup(frame: &mut Frame, app: &App) {
     let area = frame.area();
 
     let item_count = app.environments.len() + 1; // +1 for "No Environment"
-    let width: u16 = 30;
-    let height: u16 = item_count as u16 + 2; // +2 for border
-    let x = (area.width.saturating_sub(width)) / 2;
-    let y = (area.height.saturating_sub(height)) / 2;
-    let popup_area = Rect::new(x, y, width.min(area.width), height.min(area.height));
+    let width: u16 = 50;
+    let height: u16 = item_count as u16 + 4; // +2 for border, +2 for hint
+    let popup_area = centered(area, width, height);
 
     frame.render_widget(Clear, popup_area);
 
@@ -790,134 +1266,2182 @@ fn render_env_popup(frame: &mut Frame, app: &App) {
         } else {
             format!("   {} ", env.name)
         };
-        let style = if is_selected {
-            Style::default().fg(Color::Black).bg(Color::Cyan)
+        let (style, scope_style) = if is_selected {
+            let style = Style::default().fg(Color::Black).bg(Color::Cyan);
+            (style, style)
         } else {
-            Style::default().fg(Color::White)
+            (Style::default().fg(Color::White), Style::default().fg(Color::DarkGray))
         };
-        lines.push(Line::from(Span::styled(label, style)));
+        lines.push(Line::from(vec![
+            Span::styled(label, style),
+            Span::styled(format!("[{}] ", env.scope.label()), scope_style),
+        ]));
     }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        " i: import  x: export  u: usages  v: variables",
+        Style::default().fg(Color::DarkGray),
+    )));
 
     let list = Paragraph::new(lines);
     frame.render_widget(list, inner);
 }
 
-fn is_field_focused(app: &App, field: RequestField) -> bool {
-    app.focus.panel == Panel::Request && app.focus.request_field == field
-}
-
-fn method_color(method: &Method) -> Color {
-    match method {
-        Method::Standard(m) => match m {
-            HttpMethod::Get => Color::Green,
-            HttpMethod::Post => Color::Blue,
-            HttpMethod::Put => Color::Yellow,
-            HttpMethod::Patch => Color::Magenta,
-            HttpMethod::Delete => Color::Red,
-            HttpMethod::Head => Color::Cyan,
-            HttpMethod::Options => Color::White,
-        },
-        Method::Custom(_) => Color::DarkGray,
-    }
-}
-
-fn render_request_input_row(frame: &mut Frame, app: &App, layout: &RequestInputLayout) {
-    // Render Method box with method-specific color
-    let method_focused = is_field_focused(app, RequestField::Method);
-    let method_col = method_color(&app.request.method);
-    let method_border = if method_focused { Color::Green } else { Color::DarkGray };
-    let method_block = Block::default()
-        .borders(Borders::ALL)
-        .border_style(Style::default().fg(method_border));
-    // Truncate method display to fit area (inner width minus padding)
-    let display_str = app.request.method.as_str();
-    let max_width = layout.method_area.width.saturating_sub(2) as usize; // account for border
-    let display = if display_str.len() > max_width {
-        format!("{}\u{2026}", &display_str[..max_width.saturating_sub(1)])
-    } else {
-        display_str.to_string()
+fn render_env_variables(frame: &mut Frame, app: &App, popup: &EnvVariablesPopup) {
+    let Some(env) = app.environments.iter().find(|e| e.name == popup.name) else {
+        return;
     };
-    let method_text = Paragraph::new(Line::from(display))
-        .style(Style::default().fg(method_col))
-        .alignment(Alignment::Center)
-        .block(method_block);
-    frame.render_widget(method_text, layout.method_area);
-
-    // Render URL editor (TextArea handles its own cursor)
-    frame.render_widget(&app.request.url_editor, layout.url_area);
+    let area = frame.area();
+    let width = area.width.saturating_sub(8).clamp(20, 80);
+    let height = area.height.saturating_sub(4).clamp(5, 24);
+    let popup_area = centered(area, width, height);
+    frame.render_widget(Clear, popup_area);
 
-    // Render Send/Cancel button with focus highlight
-    let send_focused = is_field_focused(app, RequestField::Send);
-    let is_loading = matches!(app.response, ResponseStatus::Loading);
-    let (btn_label, btn_color) = if is_loading {
-        ("[ Cancel ]", Color::Red)
-    } else {
-        ("[ Send ]", Color::Green)
-    };
-    let send_border_color = if send_focused { Color::Green } else { Color::DarkGray };
-    let send_block = Block::default()
+    let dim = Style::default().fg(Color::DarkGray);
+    let block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(send_border_color));
-    let send_text = Paragraph::new(Line::from(btn_label))
-        .style(Style::default().fg(btn_color))
-        .block(send_block);
-    frame.render_widget(send_text, layout.send_area);
-}
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(format!(" Variables: {} ", env.name))
+        .title_bottom(Line::from(Span::styled(
+            " \u{2191}\u{2193}: move  o: override here  Esc: close ",
+            dim,
+        )));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
 
-fn render_request_panel(frame: &mut Frame, app: &App, area: Rect) {
-    let request_panel_focused = app.focus.panel == Panel::Request
-        && matches!(
-            app.focus.request_field,
-            RequestField::Headers | RequestField::Auth | RequestField::Body
-        );
-    let border_color = if request_panel_focused {
-        Color::Green
-    } else {
-        Color::White
+    let mut lines = Vec::new();
+    if let Some(ref parent) = env.extends {
+        lines.push(Line::from(Span::styled(format!(" extends {}", parent), dim)));
+    }
+    let vars = match environment::chain_variables(env, &app.environments) {
+        Ok(vars) => vars,
+        Err(err) => {
+            lines.push(Line::from(Span::styled(
+                format!(" {}", err),
+                Style::default().fg(Color::Red),
+            )));
+            env.values.iter().map(|var| (var, None)).collect()
+        }
     };
+    if !lines.is_empty() {
+        lines.push(Line::from(""));
+    }
+    if vars.is_empty() {
+        lines.push(Line::from(Span::styled(" No variables", dim)));
+    }
+    let visible = (inner.height as usize).saturating_sub(lines.len()).max(1);
+    let first = popup.index.saturating_sub(visible - 1);
+    for (i, (var, from)) in vars.iter().enumerate().skip(first).take(visible) {
+        let value = if var.var_type == "secret" {
+            environment::SECRET_MASK
+        } else {
+            var.value.as_str()
+        };
+        let style = if i == popup.index {
+            app.theme.selection()
+        } else if from.is_some() || !var.enabled {
+            dim
+        } else {
+            Style::default().fg(Color::White)
+        };
+        let mut spans = vec![Span::styled(format!(" {} = {} ", var.key, value), style)];
+        if let Some(from) = from {
+            spans.push(Span::styled(format!(" from {}", from), dim));
+        }
+        if !var.enabled {
+            spans.push(Span::styled(" (off)", dim));
+        }
+        lines.push(Line::from(spans));
+    }
+    frame.render_widget(Paragraph::new(lines), inner);
+}
 
-    let outer_block = Block::default()
-        .borders(Borders::ALL)
-        .border_style(Style::default().fg(border_color))
-        .title("Request");
+fn render_env_file_prompt(frame: &mut Frame, prompt: &EnvFilePrompt) {
+    let area = frame.area();
+    let width = std::cmp::min(64, area.width.saturating_sub(4));
+    let height = std::cmp::min(7, area.height);
+    let popup_area = centered(area, width, height);
 
-    let inner_area = outer_block.inner(area);
-    frame.render_widget(outer_block, area);
+    let (title, lines) = match prompt {
+        EnvFilePrompt::Import { input, scope } => (
+            " Import Postman Environment ".to_string(),
+            vec![
+                Line::from("Path to an exported environment (.json)"),
+                render_input_line(input),
+                Line::from(vec![
+                    Span::raw("Save as: "),
+                    Span::styled(scope.label(), Style::default().fg(Color::Cyan)),
+                    Span::styled(
+                        match scope {
+                            EnvScope::User => " (only you, outside the repo)",
+                            _ => " (.perseus/environments)",
+                        },
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                ]),
+                Line::from(""),
+                Line::from("Enter: import  Tab: project/user  Esc: cancel"),
+            ],
+        ),
+        EnvFilePrompt::Export { name, input } => (
+            format!(" Export '{}' ", name),
+            vec![
+                Line::from("File path (relative to the working directory)"),
+                Line::from(""),
+                render_input_line(input),
+                Line::from(""),
+                Line::from("Enter: export  Esc: cancel"),
+            ],
+        ),
+        EnvFilePrompt::Conflict {
+            env,
+            scope,
+            rename: None,
+        } => (
+            " Environment Exists ".to_string(),
+            vec![
+                Line::from(format!(
+                    "A {} environment named '{}' already exists.",
+                    scope.label(),
+                    env.name
+                )),
+                Line::from(""),
+                Line::from("o: overwrite it  r: import under another name"),
+                Line::from("n / Esc: cancel"),
+            ],
+        ),
+        EnvFilePrompt::Conflict {
+            rename: Some(input),
+            ..
+        } => (
+            " Import As ".to_string(),
+            vec![
+                Line::from("Name for the imported environment"),
+                Line::from(""),
+                render_input_line(input),
+                Line::from(""),
+                Line::from("Enter: import  Esc: back"),
+            ],
+        ),
+    };
+
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(title);
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+fn render_settings_popup(frame: &mut Frame, popup: &SettingsPopup) {
+    let area = frame.area();
+    let rows = popup.rows();
+    let width: u16 = 56;
+    let height: u16 = rows.len() as u16 + 4 + u16::from(popup.error.is_some()); // border + blank + hint
+    let popup_area = centered(area, width, height);
+
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(" Request Settings ");
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let selected = popup.selected_row();
+    let mut lines: Vec<Line> = Vec::new();
+    for row in rows {
+        let is_selected = row == selected;
+        let label_style = if is_selected {
+            Style::default().fg(Color::Black).bg(Color::Cyan)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        let (label, value) = match row {
+            SettingsRow::Proxy => (
+                " Proxy     ",
+                format!("\u{2039} {} \u{203a}", popup.draft.proxy.label()),
+            ),
+            SettingsRow::ProxyUrl => {
+                let url = match &popup.draft.proxy {
+                    ProxySetting::Custom { url } => url.clone(),
+                    _ => String::new(),
+                };
+                (" Proxy URL ", url)
+            }
+            SettingsRow::Redirects => (
+                " Redirects ",
+                format!("\u{2039} {} \u{203a}", popup.draft.redirects.label()),
+            ),
+            SettingsRow::Compress => (
+                " Gzip body ",
+                format!("\u{2039} {} \u{203a}", popup.draft.compress.label()),
+            ),
+            SettingsRow::HttpVersion => (
+                " HTTP      ",
+                format!("\u{2039} {} \u{203a}", popup.draft.http_version.label()),
+            ),
+            SettingsRow::UserAgent => {
+                let agent = popup.draft.user_agent.clone();
+                (" User-Agent", agent.unwrap_or_else(|| "inherit".to_string()))
+            }
+            SettingsRow::Protect => {
+                let mode = if popup.draft.skip_protect { "Skip" } else { "Confirm" };
+                (" Protected ", format!("\u{2039} {} \u{203a}", mode))
+            }
+            SettingsRow::LineEndings => {
+                let mode = if popup.draft.preserve_line_endings { "Preserve" } else { "CRLF→LF" };
+                (" Line ends ", format!("\u{2039} {} \u{203a}", mode))
+            }
+            SettingsRow::ExpectsBody => {
+                let mode = if popup.draft.expects_body { "Required" } else { "Optional" };
+                (" Body      ", format!("\u{2039} {} \u{203a}", mode))
+            }
+            SettingsRow::TlsVerify => (
+                " TLS verify",
+                format!("\u{2039} {} \u{203a}", popup.draft.tls.verify_label()),
+            ),
+            SettingsRow::TlsCaCert | SettingsRow::TlsClientCert | SettingsRow::TlsClientKey => {
+                let label = match row {
+                    SettingsRow::TlsCaCert => " TLS CA    ",
+                    SettingsRow::TlsClientCert => " TLS cert  ",
+                    _ => " TLS key   ",
+                };
+                let path = popup.row_text(row).filter(|path| !path.is_empty());
+                (label, path.unwrap_or_else(|| "inherit".to_string()))
+            }
+            SettingsRow::Schema => {
+                let schema = match &popup.draft.schema {
+                    Some(SchemaSetting::File { path }) => path.clone(),
+                    Some(SchemaSetting::Inline { .. }) => "inline".to_string(),
+                    None => "none".to_string(),
+                };
+                (" Schema    ", schema)
+            }
+            SettingsRow::Poll => {
+                let mode = popup.draft.poll.as_ref().map_or("Off", |p| p.until.label());
+                (" Poll      ", format!("\u{2039} {} \u{203a}", mode))
+            }
+            SettingsRow::PollInterval => (
+                " Every     ",
+                format!("{}s", popup.row_text(row).unwrap_or_default()),
+            ),
+            SettingsRow::PollAttempts => (
+                " Attempts  ",
+                popup.row_text(row).unwrap_or_default(),
+            ),
+            SettingsRow::PollCodes => (" Status    ", popup.row_text(row).unwrap_or_default()),
+            SettingsRow::PollPath => (" JSON path ", popup.row_text(row).unwrap_or_default()),
+            SettingsRow::PollValue => (" Equals    ", popup.row_text(row).unwrap_or_default()),
+        };
+        let mut spans = vec![Span::styled(label, label_style), Span::raw(" ")];
+        match &popup.editing {
+            Some(input) if is_selected => spans.extend(render_input_line(input).spans),
+            _ => spans.push(Span::styled(value, Style::default().fg(Color::Yellow))),
+        }
+        lines.push(Line::from(spans));
+    }
+    lines.push(Line::from(""));
+    if let Some(ref err) = popup.error {
+        lines.push(Line::from(Span::styled(
+            format!(" {}", err),
+            Style::default().fg(Color::Red),
+        )));
+    }
+    let hint = if popup.editing.is_some() {
+        " Enter: save  Esc: cancel"
+    } else {
+        " j/k: move  Enter/h/l: change  Esc: close"
+    };
+    lines.push(Line::from(Span::styled(hint, Style::default().fg(Color::DarkGray))));
+
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+fn render_config_viewer(frame: &mut Frame, app: &App) {
+    let area = frame.area();
+    let width = area.width.saturating_sub(8).clamp(20, 90);
+    let height = area.height.saturating_sub(4).max(5);
+    let popup_area = centered(area, width, height);
+
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(" Config (r: reload, c: contrast, m: motion, s: sort keys, Esc: close) ");
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let provenance = &app.config_provenance;
+    let dim = Style::default().fg(Color::DarkGray);
+    let path_line = |label: &str, path: &Option<std::path::PathBuf>| {
+        Line::from(vec![
+            Span::styled(format!(" {:<8}", label), dim),
+            Span::raw(
+                path.as_ref()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_else(|| "(not loaded)".to_string()),
+            ),
+        ])
+    };
+
+    let mut lines: Vec<Line> = vec![
+        path_line("global", &provenance.global_path),
+        path_line("project", &provenance.project_path),
+    ];
+    if let Some(ref notice) = app.config_viewer_notice {
+        lines.push(Line::from(Span::styled(
+            format!(" \u{2713} {}", notice),
+            Style::default().fg(Color::Green),
+        )));
+    }
+    lines.push(Line::from(""));
+
+    let entries = app.config.entries();
+    let key_width = entries.iter().map(|(k, _)| k.len()).max().unwrap_or(0);
+    for (key, value) in entries {
+        let source = provenance.source(&key);
+        let source_color = match source {
+            ConfigSource::Default => Color::DarkGray,
+            ConfigSource::Global => Color::Blue,
+            ConfigSource::Project => Color::Magenta,
+        };
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!(" {:<width$}", key, width = key_width),
+                Style::default().fg(Color::Cyan),
+            ),
+            Span::raw(" = "),
+            Span::raw(value),
+            Span::raw("  "),
+            Span::styled(format!("[{}]", source.label()), Style::default().fg(source_color)),
+        ]));
+    }
+
+    frame.render_widget(
+        Paragraph::new(lines).scroll((app.config_viewer_scroll, 0)),
+        inner,
+    );
+}
+
+fn level_color(level: Level) -> Color {
+    match level {
+        Level::Info => Color::Green,
+        Level::Warn => Color::Yellow,
+        Level::Error => Color::Red,
+    }
+}
+
+/// Older visible notifications, stacked right-aligned above the status bar;
+/// the newest one is drawn in the status bar itself.
+fn render_notification_stack(frame: &mut Frame, app: &App, status_bar: Rect) {
+    let older: Vec<_> = app.notifications.visible().rev().skip(1).collect();
+    for (i, note) in older.iter().enumerate() {
+        let row = i as u16 + 1;
+        if status_bar.y < row {
+            break;
+        }
+        let text = format!(" {} ", note.message);
+        let width = (text.chars().count() as u16).min(status_bar.width);
+        let area = Rect::new(
+            status_bar.x + status_bar.width - width,
+            status_bar.y - row,
+            width,
+            1,
+        );
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(text).style(
+                Style::default()
+                    .fg(level_color(note.level))
+                    .bg(Color::Black),
+            ),
+            area,
+        );
+    }
+}
+
+fn render_message_log(frame: &mut Frame, app: &App) {
+    let area = frame.area();
+    let width = area.width.saturating_sub(8).clamp(20, 100);
+    let height = area.height.saturating_sub(4).max(5);
+    let popup_area = centered(area, width, height);
+
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(" Messages (UTC, newest first, Esc: close) ");
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    if app.notifications.log().len() == 0 {
+        frame.render_widget(
+            Paragraph::new(" No messages yet").style(Style::default().fg(Color::DarkGray)),
+            inner,
+        );
+        return;
+    }
+    let lines: Vec<Line> = app
+        .notifications
+        .log()
+        .rev()
+        .map(|note| {
+            Line::from(vec![
+                Span::styled(format!(" {} ", note.clock()), Style::default().fg(Color::DarkGray)),
+                Span::styled(
+                    format!("{:<5} ", note.level.label()),
+                    Style::default().fg(level_color(note.level)),
+                ),
+                Span::raw(note.message.clone()),
+            ])
+        })
+        .collect();
+    frame.render_widget(
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .scroll((app.message_log_scroll, 0)),
+        inner,
+    );
+}
+
+/// Request files that don't parse, each with the parser's message.
+fn render_request_files(
+    frame: &mut Frame,
+    errors: &[RequestFileError],
+    index: usize,
+    theme: Theme,
+) {
+    let area = frame.area();
+    let width = area.width.saturating_sub(8).clamp(20, 100);
+    let height = area.height.saturating_sub(4).max(5);
+    let popup_area = centered(area, width, height);
+    frame.render_widget(Clear, popup_area);
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title(format!(" Unreadable request files ({}) ", errors.len()))
+        .title_bottom(Line::from(Span::styled(
+            " Enter: open in $EDITOR  M: messages  Esc: close ",
+            dim,
+        )));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    // Two rows per file: path, then the parse error
+    let visible = (inner.height / 2).max(1) as usize;
+    let first = index.saturating_sub(visible - 1);
+    let mut lines = Vec::new();
+    for (i, err) in errors.iter().enumerate().skip(first).take(visible) {
+        let style = if i == index {
+            theme.selection()
+        } else {
+            Style::default().fg(Color::White)
+        };
+        lines.push(Line::from(Span::styled(format!(" {} ", err.path.display()), style)));
+        lines.push(Line::from(Span::styled(
+            format!("   {}", err.message),
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+/// The first violations of the response's schema check, or why the
+/// schema couldn't be used.
+fn render_schema_check(frame: &mut Frame, app: &App) {
+    const SHOWN: usize = 10;
+    let Some(check) = app.current_schema_check() else {
+        return;
+    };
+    let area = frame.area();
+    let dim = Style::default().fg(Color::DarkGray);
+    let mut lines: Vec<Line> = Vec::new();
+    let title = match check {
+        SchemaCheck::Passed => {
+            lines.push(Line::from(Span::styled(
+                " The response matches the schema",
+                Style::default().fg(Color::Green),
+            )));
+            " Schema: passed "
+        }
+        SchemaCheck::Failed(violations) => {
+            for violation in violations.iter().take(SHOWN) {
+                lines.push(Line::from(vec![
+                    Span::styled(format!(" {}", violation.path), Style::default().fg(Color::Cyan)),
+                    Span::raw(format!("  {}", violation.message)),
+                ]));
+            }
+            if violations.len() > SHOWN {
+                lines.push(Line::from(Span::styled(
+                    format!(" \u{2026} and {} more", violations.len() - SHOWN),
+                    dim,
+                )));
+            }
+            " Schema: failed "
+        }
+        SchemaCheck::SchemaError(err) => {
+            lines.push(Line::from(Span::styled(
+                " The schema couldn't be used, so nothing was validated:",
+                Style::default().fg(Color::Yellow),
+            )));
+            lines.push(Line::from(format!(" {}", err)));
+            " Schema: error "
+        }
+    };
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(" Esc: close", dim)));
+
+    let width = area.width.saturating_sub(8).clamp(20, 90);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup_area = centered(area, width, height);
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(title);
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner);
+}
+
+/// The open request's recent sends, newest first.
+fn render_send_history(frame: &mut Frame, app: &App) {
+    let sends = app.send_history();
+    let area = frame.area();
+    let dim = Style::default().fg(Color::DarkGray);
+    let mut lines: Vec<Line> = Vec::new();
+    if let Some(summary) = send_history_summary(sends) {
+        lines.push(Line::from(format!(" {}", summary)));
+        lines.push(Line::from(""));
+    }
+    lines.push(Line::from(Span::styled(
+        format!(" {:<19}  {:>6}  {:>9}", "Sent (UTC)", "Status", "Duration"),
+        dim,
+    )));
+    for send in sends.iter().rev() {
+        let at = UNIX_EPOCH + Duration::from_secs(send.at);
+        let sent = sendlog::timestamp(at)[..19].replace('T', " ");
+        let (status, color) = match send.status {
+            Some(status) => (status.to_string(), status_color(status)),
+            None => ("error".to_string(), Color::Red),
+        };
+        lines.push(Line::from(vec![
+            Span::raw(format!(" {}  ", sent)),
+            Span::styled(format!("{:>6}", status), Style::default().fg(color)),
+            Span::raw(format!("  {:>7}ms", send.duration_ms)),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(" Esc: close", dim)));
+
+    let width = 46.min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup_area = centered(area, width, height);
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(format!(" Recent sends ({}) ", sends.len()));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+/// Timings of the previous frame, in the top-right corner so the panels
+/// underneath stay usable.
+fn render_perf_overlay(frame: &mut Frame, app: &App) {
+    let area = frame.area();
+    let width = 44.min(area.width);
+    let height = (app.perf_last_frame.len() as u16 + 2).max(3).min(area.height);
+    let popup_area = anchored(area, area.right(), area.y, width, height);
+
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Magenta))
+        .title(" Last frame (F12) ");
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    if app.perf_last_frame.is_empty() {
+        frame.render_widget(
+            Paragraph::new(" waiting for a frame").style(Style::default().fg(Color::DarkGray)),
+            inner,
+        );
+        return;
+    }
+    let lines: Vec<Line> = app
+        .perf_last_frame
+        .iter()
+        .map(|timing| {
+            let micros = timing.total.as_micros();
+            let calls = if timing.calls > 1 {
+                format!(" x{}", timing.calls)
+            } else {
+                String::new()
+            };
+            Line::from(vec![
+                Span::raw(format!(" {:<28}", timing.label)),
+                Span::styled(
+                    format!("{:>7}us", micros),
+                    Style::default().fg(if micros >= 16_000 {
+                        Color::Red
+                    } else {
+                        Color::Green
+                    }),
+                ),
+                Span::styled(calls, Style::default().fg(Color::DarkGray)),
+            ])
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+fn render_save_response_popup(frame: &mut Frame, input: &crate::app::TextInput) {
+    let area = frame.area();
+    let width = std::cmp::min(60, area.width.saturating_sub(4));
+    let height = std::cmp::min(7, area.height);
+    let popup_area = centered(area, width, height);
+
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(" Save Response ");
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+    let lines = vec![
+        Line::from("File path (relative to the working directory)"),
+        Line::from(""),
+        render_input_line(input),
+        Line::from(""),
+        Line::from("Enter: save  Esc: cancel"),
+    ];
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+fn render_example_popup(frame: &mut Frame, app: &App, popup: &ExamplePopup) {
+    let area = frame.area();
+    match popup {
+        ExamplePopup::Name(input) => {
+            let width = std::cmp::min(60, area.width.saturating_sub(4));
+            let height = std::cmp::min(7, area.height);
+            let popup_area = centered(area, width, height);
+            frame.render_widget(Clear, popup_area);
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan))
+                .title(" Save as Example ");
+            let inner = block.inner(popup_area);
+            frame.render_widget(block, popup_area);
+            let lines = vec![
+                Line::from("Example name"),
+                Line::from(""),
+                render_input_line(input),
+                Line::from(""),
+                Line::from("Enter: save  Esc: cancel"),
+            ];
+            frame.render_widget(Paragraph::new(lines), inner);
+        }
+        ExamplePopup::List { index } => {
+            let examples = app.current_examples();
+            let width = area.width.saturating_sub(8).clamp(20, 70);
+            let height = (examples.len() as u16 + 4).min(area.height.saturating_sub(2));
+            let popup_area = centered(area, width, height);
+            frame.render_widget(Clear, popup_area);
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan))
+                .title(" Examples ");
+            let inner = block.inner(popup_area);
+            frame.render_widget(block, popup_area);
+
+            let visible = inner.height.saturating_sub(2) as usize;
+            let first = (*index + 1).saturating_sub(visible);
+            let mut lines: Vec<Line> = examples
+                .iter()
+                .enumerate()
+                .skip(first)
+                .take(visible)
+                .map(|(i, (_, example))| {
+                    let selected = i == *index;
+                    let marker = if selected { "> " } else { "  " };
+                    let name_style = if selected {
+                        Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default()
+                    };
+                    Line::from(vec![
+                        Span::styled(marker, name_style),
+                        Span::styled(
+                            format!("{:<4}", example.code),
+                            Style::default().fg(status_color(example.code)),
+                        ),
+                        Span::styled(example.name.clone(), name_style),
+                    ])
+                })
+                .collect();
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "Enter: show  d: delete  Esc: close",
+                Style::default().fg(Color::DarkGray),
+            )));
+            frame.render_widget(Paragraph::new(lines), inner);
+        }
+    }
+}
+
+fn render_pipe_popup(frame: &mut Frame, popup: &PipePopup) {
+    let area = frame.area();
+    match popup {
+        PipePopup::Command(input) => {
+            let width = std::cmp::min(70, area.width.saturating_sub(4));
+            let height = std::cmp::min(7, area.height);
+            let popup_area = centered(area, width, height);
+            frame.render_widget(Clear, popup_area);
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan))
+                .title(" Pipe Body ");
+            let inner = block.inner(popup_area);
+            frame.render_widget(block, popup_area);
+            let lines = vec![
+                Line::from("Shell command, e.g. jq '.items | length'"),
+                Line::from(""),
+                render_input_line(input),
+                Line::from(""),
+                Line::from("Enter: run  Esc: cancel"),
+            ];
+            frame.render_widget(Paragraph::new(lines), inner);
+        }
+        PipePopup::Output {
+            command,
+            output,
+            scroll,
+        } => {
+            let width = area.width.saturating_sub(8).clamp(20, 100);
+            let height = area.height.saturating_sub(4).max(5);
+            let popup_area = centered(area, width, height);
+            frame.render_widget(Clear, popup_area);
+            let mut block = Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan))
+                .title(format!(" | {} ", command))
+                .title_bottom(
+                    Line::from(" j/k: scroll  Esc: close ")
+                        .style(Style::default().fg(Color::DarkGray)),
+                );
+            if output.truncated {
+                block = block.title_bottom(
+                    Line::from(format!(
+                        " first {} only ",
+                        http::format_bytes(crate::util::pager::MAX_OUTPUT)
+                    ))
+                    .right_aligned()
+                    .style(Style::default().fg(Color::Yellow)),
+                );
+            }
+            let inner = block.inner(popup_area);
+            frame.render_widget(block, popup_area);
+
+            if output.text.trim().is_empty() {
+                frame.render_widget(
+                    Paragraph::new(" (no output)").style(Style::default().fg(Color::DarkGray)),
+                    inner,
+                );
+                return;
+            }
+            let lines: Vec<Line> = output.text.lines().map(|l| Line::from(l.to_string())).collect();
+            frame.render_widget(Paragraph::new(lines).scroll((*scroll, 0)), inner);
+        }
+    }
+}
+
+fn render_clipboard_ring(frame: &mut Frame, app: &App, popup: ClipboardRingPopup) {
+    let area = frame.area();
+    let yanks = app.recent_yanks();
+    let width = area.width.saturating_sub(8).clamp(20, 80);
+    let height = (yanks.len() as u16 + 4).min(area.height.saturating_sub(2));
+    let popup_area = centered(area, width, height);
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(" Recent Yanks ");
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    // One line per yank: its first line, and how many more it has
+    let text_width = (inner.width as usize).saturating_sub(2);
+    let mut lines: Vec<Line> = yanks
+        .iter()
+        .enumerate()
+        .map(|(i, yank)| {
+            let selected = i == popup.index;
+            let marker = if selected { "> " } else { "  " };
+            let style = if selected {
+                Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            let mut text_lines = yank.lines();
+            let first: String =
+                text_lines.next().unwrap_or_default().chars().take(text_width).collect();
+            let more = text_lines.count();
+            let mut spans = vec![Span::styled(marker, style), Span::styled(first, style)];
+            if more > 0 {
+                spans.push(Span::styled(
+                    format!("  +{} lines", more),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            Line::from(spans)
+        })
+        .collect();
+    lines.push(Line::from(""));
+    let action = if popup.paste { "paste" } else { "copy" };
+    lines.push(Line::from(Span::styled(
+        format!("Enter: {}  Esc: close", action),
+        Style::default().fg(Color::DarkGray),
+    )));
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+fn render_snippet_popup(frame: &mut Frame, popup: &SnippetPopup) {
+    let area = frame.area();
+    let matches = popup.matches();
+    let width = area.width.saturating_sub(8).clamp(20, 80);
+    // The filter, the list, and a preview of the pick under a blank line
+    let preview: Vec<&str> = matches
+        .get(popup.index)
+        .map(|snippet| snippet.body.lines().take(8).collect())
+        .unwrap_or_default();
+    let wanted = matches.len().max(1) + preview.len() + 6;
+    let height = (wanted as u16).min(area.height.saturating_sub(2));
+    let popup_area = centered(area, width, height);
+    frame.render_widget(Clear, popup_area);
+    let dim = Style::default().fg(Color::DarkGray);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(" Snippets ")
+        .title_bottom(Line::from(Span::styled(
+            " Enter: insert  Tab/\u{2191}\u{2193}: pick  Esc: close ",
+            dim,
+        )));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let mut lines = vec![render_input_line(&popup.filter), Line::from("")];
+    if matches.is_empty() {
+        lines.push(Line::from(Span::styled("No snippet matches", dim)));
+    }
+    let name_width = matches.iter().map(|snippet| snippet.name.width()).max().unwrap_or(0);
+    for (i, snippet) in matches.iter().enumerate() {
+        let selected = i == popup.index;
+        let style = if selected {
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        let origin = if snippet.project { "  (project)" } else { "" };
+        lines.push(Line::from(vec![
+            Span::styled(if selected { "> " } else { "  " }, style),
+            Span::styled(format!("{:<width$}", snippet.name, width = name_width), style),
+            Span::styled(format!("  {}{}", snippet.description, origin), dim),
+        ]));
+    }
+    if !preview.is_empty() {
+        lines.push(Line::from(""));
+        lines.extend(preview.into_iter().map(|line| Line::from(Span::styled(line, dim))));
+    }
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+fn render_codec_popup(frame: &mut Frame, popup: &CodecPopup, theme: Theme) {
+    let area = frame.area();
+    let width = area.width.saturating_sub(8).clamp(20, 90);
+    let height = area.height.saturating_sub(4).clamp(5, 24);
+    let popup_area = centered(area, width, height);
+
+    frame.render_widget(Clear, popup_area);
+    let source = match popup.source {
+        CodecSource::RequestSelection | CodecSource::ResponseSelection => "selection",
+        CodecSource::Clipboard => "clipboard",
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(format!(" Encode / decode ({}) ", source));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let input_preview: String =
+        popup.input.lines().next().unwrap_or("").chars().take(200).collect();
+    let mut lines = vec![
+        Line::from(vec![Span::styled("Input  ", dim), Span::raw(input_preview)]),
+        Line::from(""),
+    ];
+    for (i, transform) in crate::util::codec::Transform::ALL.iter().enumerate() {
+        let style = if i == popup.index {
+            theme.selection()
+        } else {
+            Style::default()
+        };
+        lines.push(Line::from(Span::styled(format!(" {} ", transform.label()), style)));
+    }
+    lines.push(Line::from(""));
+    match popup.result {
+        Ok(ref result) => lines.extend(result.lines().map(|l| Line::from(l.to_string()))),
+        Err(ref err) => lines.push(Line::from(Span::styled(
+            format!("\u{2717} {}", err),
+            Style::default().fg(Color::Red),
+        ))),
+    }
+    lines.push(Line::from(""));
+    let hint = if popup.can_replace() {
+        "y/Enter: copy  r: replace selection  Esc: close"
+    } else {
+        "y/Enter: copy  Esc: close"
+    };
+    lines.push(Line::from(Span::styled(hint, dim)));
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner);
+}
+
+fn render_run_popup(frame: &mut Frame, app: &mut App) {
+    let area = frame.area();
+    let width = area.width.saturating_sub(8).clamp(20, 100);
+    let height = area.height.saturating_sub(4).max(5);
+    let popup_area = centered(area, width, height);
+    frame.render_widget(Clear, popup_area);
+
+    let (Some(run), Some(popup)) = (app.run.as_ref(), app.run_popup.as_mut()) else {
+        return;
+    };
+    let dim = Style::default().fg(Color::DarkGray);
+    let pass = Style::default().fg(Color::Green);
+    let fail = Style::default().fg(Color::Red);
+    let (passed, failed) = run.report.totals();
+
+    let mut lines = Vec::new();
+    let summary = if !run.finished {
+        let done = run.report.results().count();
+        match &run.parallel {
+            Some(sends) => format!(
+                "Running {}/{}, {} at once",
+                done,
+                run.total_steps(),
+                sends.in_flight()
+            ),
+            None => format!("Running {}/{}", done + 1, run.total_steps()),
+        }
+    } else if run.report.cancelled {
+        "Stopped".to_string()
+    } else {
+        "Finished".to_string()
+    };
+    lines.push(Line::from(vec![
+        Span::styled(summary, Style::default().fg(Color::Yellow)),
+        Span::raw("  "),
+        Span::styled(format!("{} passed", passed), pass),
+        Span::raw("  "),
+        Span::styled(format!("{} failed", failed), if failed > 0 { fail } else { dim }),
+    ]));
+    if let Some(file) = &run.report.data_file {
+        lines.push(Line::from(Span::styled(format!("Data: {}", file), dim)));
+    }
+    if let Some(export) = &run.export {
+        let dir = export.dir.display();
+        lines.push(Line::from(Span::styled(format!("Export: {}", dir), dim)));
+    }
+
+    let show_iterations = run.report.iterations.len() > 1 || run.report.data_file.is_some();
+    let sent = run.in_flight();
+    for (index, iteration) in run.report.iterations.iter().enumerate() {
+        let in_flight: Vec<_> = sent.iter().filter(|step| step.iteration == index).collect();
+        if iteration.results.is_empty() && in_flight.is_empty() {
+            continue;
+        }
+        if show_iterations {
+            lines.push(Line::from(""));
+            let variables: Vec<String> =
+                iteration.variables.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("Iteration {}", iteration.iteration),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(format!("  {}", variables.join(" ")), dim),
+            ]));
+        } else if index == 0 {
+            lines.push(Line::from(""));
+        }
+        for (step, result) in iteration.results.iter().enumerate() {
+            // Dependents sit under the requests they reference
+            let indent = "  ".repeat(run.depth(step));
+            let (mark, style) = if result.passed() {
+                ("\u{2713}", pass)
+            } else {
+                ("\u{2717}", fail)
+            };
+            let status = result.status.map(|s| s.to_string()).unwrap_or_else(|| "---".into());
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {}{} {} ", indent, mark, status), style),
+                Span::raw(format!("{:<7} {}", result.method, result.name)),
+                Span::styled(format!("  {} ms", result.duration_ms), dim),
+            ]));
+            if let Some(error) = &result.error {
+                lines.push(Line::from(Span::styled(format!("      {}{}", indent, error), fail)));
+            }
+            for assertion in result.assertions.iter().filter(|a| !a.passed) {
+                let message = assertion.message.as_deref().unwrap_or("failed");
+                lines.push(Line::from(Span::styled(
+                    format!("      {}{}: {}", indent, assertion.name, message),
+                    fail,
+                )));
+            }
+        }
+        for step in in_flight {
+            let name = app
+                .collection
+                .get_item(step.request_id)
+                .map(|item| item.name.clone())
+                .unwrap_or_default();
+            let indent = "  ".repeat(run.depth(iteration.results.len()));
+            lines.push(Line::from(Span::styled(
+                format!("  {}\u{2026} {}", indent, name),
+                Style::default().fg(Color::Yellow),
+            )));
+        }
+    }
+
+    let hint = if run.finished {
+        " j/k: scroll  s: save report  Esc: close "
+    } else {
+        " j/k: scroll  s: save report  x: stop  Esc: hide "
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(format!(" Run: {} ", run.report.target))
+        .title_bottom(Line::from(Span::styled(hint, dim)));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let [list_area, save_area] = Layout::vertical([
+        Constraint::Min(0),
+        Constraint::Length(u16::from(popup.save.is_some())),
+    ])
+    .areas(inner);
+    let total = lines.len() as u16;
+    popup.scroll = popup.scroll.min(total.saturating_sub(list_area.height));
+    frame.render_widget(Paragraph::new(lines).scroll((popup.scroll, 0)), list_area);
+
+    if let Some(input) = &popup.save {
+        let mut spans = vec![Span::styled("Save to: ", Style::default().fg(Color::Yellow))];
+        spans.extend(render_input_line(input).spans);
+        frame.render_widget(Paragraph::new(Line::from(spans)), save_area);
+    }
+}
+
+fn render_url_audit(frame: &mut Frame, popup: &UrlAuditPopup, theme: Theme) {
+    let area = frame.area();
+    let width = area.width.saturating_sub(8).clamp(20, 100);
+    let height = area.height.saturating_sub(4).max(5);
+    let popup_area = centered(area, width, height);
+    frame.render_widget(Clear, popup_area);
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let hint = if popup.fix.is_some() {
+        " Enter: replace  Esc: cancel "
+    } else {
+        " Enter: open  f: replace host with a variable  Esc: close "
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(format!(" URL audit ({} requests) ", popup.entries.len()))
+        .title_bottom(Line::from(Span::styled(hint, dim)));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let fix_rows = if popup.fix.is_some() { 2 } else { 0 };
+    let [list_area, fix_area] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(fix_rows)]).areas(inner);
+
+    // Three rows per entry: path, URL, findings
+    let visible = (list_area.height / 3).max(1) as usize;
+    let first = popup.index.saturating_sub(visible - 1);
+    let mut lines = Vec::new();
+    for (i, entry) in popup.entries.iter().enumerate().skip(first).take(visible) {
+        let selected = i == popup.index;
+        let style = if selected {
+            theme.selection()
+        } else {
+            Style::default().fg(Color::White)
+        };
+        lines.push(Line::from(Span::styled(format!(" {} ", entry.path), style)));
+        lines.push(Line::from(Span::styled(format!("   {}", entry.url), dim)));
+        let findings: Vec<String> = entry.findings.iter().map(|f| f.describe()).collect();
+        lines.push(Line::from(Span::styled(
+            format!("   {}", findings.join("; ")),
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+    frame.render_widget(Paragraph::new(lines), list_area);
+
+    if let (Some(input), Some((base, count))) = (&popup.fix, popup.fix_target()) {
+        let fix = vec![
+            Line::from(format!("Replace {} in {} requests with {{{{variable}}}}:", base, count)),
+            render_input_line(input),
+        ];
+        frame.render_widget(Paragraph::new(fix), fix_area);
+    }
+}
+
+fn render_usages(frame: &mut Frame, popup: &UsagesPopup, theme: Theme) {
+    let area = frame.area();
+    let width = area.width.saturating_sub(8).clamp(20, 80);
+    let height = area.height.saturating_sub(4).clamp(5, 24);
+    let popup_area = centered(area, width, height);
+    frame.render_widget(Clear, popup_area);
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let hint = if popup.preview.is_some() {
+        " Enter: rename  Esc: back "
+    } else if popup.rename.is_some() {
+        " Enter: preview  Esc: cancel "
+    } else {
+        " \u{2191}\u{2193}: move  Enter: open  Ctrl+R: rename  Esc: close "
+    };
+    let title = if popup.preview.is_some() { " Rename variable " } else { " Find usages " };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(title)
+        .title_bottom(Line::from(Span::styled(hint, dim)));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let rename_rows = if popup.rename.is_some() { 2 } else { 0 };
+    let [list_area, rename_area] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(rename_rows)]).areas(inner);
+
+    let mut query = vec![Span::raw(" {{")];
+    query.extend(render_input_line(&popup.name).spans);
+    query.push(Span::raw("}}"));
+    let mut lines = vec![Line::from(query), Line::from("")];
+    let field_label = |field: RequestField| match field {
+        RequestField::Url => "URL",
+        RequestField::Headers => "Headers",
+        RequestField::Auth => "Auth",
+        _ => "Body",
+    };
+    let name = popup.name.value.trim();
+    if let Some(ref preview) = popup.preview {
+        lines.push(Line::from(Span::styled(
+            format!(
+                " {{{{{}}}}} \u{2192} {{{{{}}}}}: {} occurrences will change",
+                preview.from, preview.to, preview.occurrences
+            ),
+            Style::default().fg(Color::Yellow),
+        )));
+        for path in &preview.env_files {
+            lines.push(Line::from(vec![
+                Span::styled("  environment ", dim),
+                Span::raw(path.clone()),
+            ]));
+        }
+        for hit in &popup.hits {
+            lines.push(Line::from(vec![
+                Span::raw(format!("  {} ", hit.path)),
+                Span::styled(format!(" {}:{}", field_label(hit.field), hit.line), dim),
+            ]));
+        }
+    } else if name.is_empty() {
+        lines.push(Line::from(Span::styled(" Type a variable name", dim)));
+    } else if popup.hits.is_empty() {
+        lines.push(Line::from(Span::styled(" No request uses it", dim)));
+    } else {
+        let visible = (list_area.height as usize).saturating_sub(lines.len()).max(1);
+        let first = popup.index.saturating_sub(visible - 1);
+        for (i, hit) in popup.hits.iter().enumerate().skip(first).take(visible) {
+            let style = if i == popup.index {
+                theme.selection()
+            } else {
+                Style::default().fg(Color::White)
+            };
+            lines.push(Line::from(vec![
+                Span::styled(format!(" {} ", hit.path), style),
+                Span::styled(format!(" {}:{}", field_label(hit.field), hit.line), dim),
+            ]));
+        }
+    }
+    frame.render_widget(Paragraph::new(lines), list_area);
+
+    if let Some(ref input) = popup.rename {
+        let rename = vec![
+            Line::from(format!("Rename {{{{{}}}}} everywhere to:", name)),
+            render_input_line(input),
+        ];
+        frame.render_widget(Paragraph::new(rename), rename_area);
+    }
+}
+
+fn render_compare_view(frame: &mut Frame, view: &CompareView) {
+    let area = frame.area();
+    let width = area.width.saturating_sub(4).max(20);
+    let height = area.height.saturating_sub(2).max(5);
+    let popup_area = centered(area, width, height);
+
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(" Compare (p: clear pin, Esc: close) ");
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let removed = Style::default().fg(Color::Red);
+    let added = Style::default().fg(Color::Green);
+    let dim = Style::default().fg(Color::DarkGray);
+    let heading = Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("- pinned   ", removed),
+            Span::raw(view.pinned_summary.clone()),
+        ]),
+        Line::from(vec![
+            Span::styled("+ current  ", added),
+            Span::raw(view.current_summary.clone()),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled("Headers", heading)),
+    ];
+    if view.headers.is_empty() {
+        lines.push(Line::from(Span::styled("  (no differences)", dim)));
+    }
+    for change in &view.headers {
+        match change {
+            diff::HeaderChange::Added { name, value } => {
+                lines.push(Line::from(Span::styled(format!("+ {}: {}", name, value), added)));
+            }
+            diff::HeaderChange::Removed { name, value } => {
+                lines.push(Line::from(Span::styled(format!("- {}: {}", name, value), removed)));
+            }
+            diff::HeaderChange::Changed { name, old, new } => {
+                lines.push(Line::from(Span::styled(format!("- {}: {}", name, old), removed)));
+                lines.push(Line::from(Span::styled(format!("+ {}: {}", name, new), added)));
+            }
+        }
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled("Body", heading)));
+    if view.body.iter().all(|line| matches!(line, diff::DiffLine::Same(_))) {
+        lines.push(Line::from(Span::styled("  (identical)", dim)));
+    } else {
+        for line in &view.body {
+            lines.push(match line {
+                diff::DiffLine::Same(text) => Line::from(format!("  {}", text)),
+                diff::DiffLine::Removed(text) => {
+                    Line::from(Span::styled(format!("- {}", text), removed))
+                }
+                diff::DiffLine::Added(text) => {
+                    Line::from(Span::styled(format!("+ {}", text), added))
+                }
+            });
+        }
+    }
+    frame.render_widget(Paragraph::new(lines).scroll((view.scroll, 0)), inner);
+}
+
+fn render_raw_view(frame: &mut Frame, view: &RawView) {
+    let area = frame.area();
+    let width = area.width.saturating_sub(8).clamp(20, 110);
+    let height = area.height.saturating_sub(4).max(5);
+    let popup_area = centered(area, width, height);
+
+    frame.render_widget(Clear, popup_area);
+    let title = if view.editor.is_some() {
+        " Edit raw request (Ctrl+S: apply, Esc: discard) "
+    } else {
+        " Raw request (e: edit, Esc: close) "
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(title);
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let Some(ref editor) = view.editor else {
+        frame.render_widget(
+            Paragraph::new(view.text.clone())
+                .wrap(Wrap { trim: false })
+                .scroll((view.scroll, 0)),
+            inner,
+        );
+        return;
+    };
+    let [edit_area, footer_area] =
+        Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(inner);
+    frame.render_widget(editor, edit_area);
+    let footer = match view.error {
+        Some(ref err) => Line::from(Span::styled(
+            format!("\u{2717} {}", err),
+            Style::default().fg(Color::Red),
+        )),
+        None => Line::from(Span::styled(
+            "Placeholders stay unresolved here; auth and automatic headers are added on send",
+            Style::default().fg(Color::DarkGray),
+        )),
+    };
+    frame.render_widget(Paragraph::new(footer), footer_area);
+}
+
+fn render_changelog(frame: &mut Frame, popup: &ChangelogPopup) {
+    let area = frame.area();
+    let dim = Style::default().fg(Color::DarkGray);
+    let mut lines: Vec<Line> = Vec::new();
+    for entry in &popup.entries {
+        let when = entry.timestamp.get(..16).unwrap_or(&entry.timestamp).replace('T', " ");
+        let color = match entry.kind {
+            ChangeKind::Added | ChangeKind::Duplicated => Color::Green,
+            ChangeKind::Deleted => Color::Red,
+            ChangeKind::Renamed | ChangeKind::Moved => Color::Yellow,
+            ChangeKind::External => Color::Magenta,
+        };
+        let mut spans = vec![
+            Span::styled(format!(" {}  ", when), dim),
+            Span::styled(format!("{:<10} ", entry.kind.label()), Style::default().fg(color)),
+            Span::raw(entry.path.clone()),
+        ];
+        let detail = entry.detail.as_deref().map(|detail| match entry.kind {
+            ChangeKind::Renamed | ChangeKind::Moved => format!("  (was {})", detail),
+            ChangeKind::Duplicated => format!("  (from {})", detail),
+            _ => detail.to_string(),
+        });
+        if let Some(detail) = detail {
+            spans.push(Span::styled(detail, dim));
+        }
+        lines.push(Line::from(spans));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(" j/k: scroll  Esc: close", dim)));
+
+    let width = area.width.saturating_sub(8).clamp(20, 110);
+    let height = (lines.len() as u16 + 2).clamp(5, area.height.saturating_sub(4).max(5));
+    let popup_area = centered(area, width, height);
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(format!(" Changelog ({} recent, UTC) ", popup.entries.len()));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+    frame.render_widget(
+        Paragraph::new(lines).wrap(Wrap { trim: false }).scroll((popup.scroll, 0)),
+        inner,
+    );
+}
+
+fn render_url_outline(frame: &mut Frame, popup: &UrlOutlinePopup) {
+    let area = frame.area();
+    let outline = http::url_outline(&popup.url);
+    let mut lines: Vec<Line> = outline
+        .iter()
+        .map(|(part, text)| {
+            let lead = match part {
+                http::UrlPart::Host => "",
+                http::UrlPart::Query(0) => "  ? ",
+                http::UrlPart::Query(_) => "  & ",
+                _ => "  ",
+            };
+            Line::from(vec![Span::raw(lead), Span::styled(text.clone(), url_part_style(*part))])
+        })
+        .collect();
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "y: copy  Y: copy decoded  j/k: scroll  Esc: close",
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    let width = area.width.saturating_sub(8).clamp(20, 110);
+    let height = (lines.len() as u16 + 2).clamp(5, area.height.saturating_sub(4).max(5));
+    let popup_area = centered(area, width, height);
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(format!(" URL ({} characters) ", popup.url.chars().count()));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+    frame.render_widget(
+        Paragraph::new(lines).wrap(Wrap { trim: false }).scroll((popup.scroll, 0)),
+        inner,
+    );
+}
+
+fn render_scratch_send(frame: &mut Frame, scratch: &ScratchSend) {
+    let area = frame.area();
+    let width = area.width.saturating_sub(8).clamp(20, 110);
+    let height = area.height.saturating_sub(4).max(5);
+    let popup_area = centered(area, width, height);
+
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Magenta))
+        .title(" Scratch send (Ctrl+S: send, Ctrl+L: refill, Esc: keep for later) ");
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let [edit_area, footer_area] =
+        Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(inner);
+    frame.render_widget(&scratch.editor, edit_area);
+    let footer = match scratch.error {
+        Some(ref err) => Line::from(Span::styled(
+            format!("\u{2717} {}", err),
+            Style::default().fg(Color::Red),
+        )),
+        None => Line::from(Span::styled(
+            "Sent once, never saved to the request; auth and settings are the request's",
+            Style::default().fg(Color::DarkGray),
+        )),
+    };
+    frame.render_widget(Paragraph::new(footer), footer_area);
+}
+
+fn render_notes_popup(frame: &mut Frame, popup: &NotesPopup) {
+    let area = frame.area();
+    let width = area.width.saturating_sub(8).clamp(20, 80);
+    let height = area.height.saturating_sub(4).clamp(5, 16);
+    let popup_area = centered(area, width, height);
+
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(" Notes (Esc: save and close) ");
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let [edit_area, footer_area] =
+        Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(inner);
+    frame.render_widget(&popup.editor, edit_area);
+    let footer = Span::styled(
+        "Kept with your session, not the collection",
+        Style::default().fg(Color::DarkGray),
+    );
+    frame.render_widget(Paragraph::new(Line::from(footer)), footer_area);
+}
+
+fn render_send_preview(frame: &mut Frame, popup: &SendPreviewPopup) {
+    let area = frame.area();
+    let width = area.width.saturating_sub(8).clamp(20, 110);
+    let height = area.height.saturating_sub(4).max(5);
+    let popup_area = centered(area, width, height);
+
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(" Send preview, nothing sent (Esc: close) ");
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let lines = match &popup.preview {
+        Err(err) => vec![Line::from(Span::styled(
+            format!("\u{2717} Can't build the request: {}", err),
+            Style::default().fg(Color::Red),
+        ))],
+        Ok(preview) => {
+            let mut lines = vec![
+                Line::from(vec![
+                    Span::styled(
+                        preview.method.clone(),
+                        Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(" "),
+                    Span::raw(preview.url.clone()),
+                ]),
+                Line::from(""),
+            ];
+            let name_width = preview.headers.iter().map(|(name, ..)| name.width()).max();
+            let name_width = name_width.unwrap_or(0);
+            for (name, value, source) in &preview.headers {
+                let source_style = match source {
+                    http::HeaderSource::Request => Style::default().fg(Color::Green),
+                    http::HeaderSource::Auth => Style::default().fg(Color::Yellow),
+                    http::HeaderSource::Automatic => dim,
+                };
+                lines.push(Line::from(vec![
+                    Span::styled(format!("{:<10}", source.label()), source_style),
+                    Span::styled(format!("{:<width$}", name, width = name_width), dim),
+                    Span::raw(format!(": {}", value)),
+                ]));
+            }
+            lines.push(Line::from(""));
+            if preview.body.is_empty() {
+                lines.push(Line::from(Span::styled("No body", dim)));
+            } else {
+                lines.extend(preview.body.lines().map(|line| Line::from(line.to_string())));
+            }
+            lines
+        }
+    };
+    frame.render_widget(
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .scroll((popup.scroll, 0)),
+        inner,
+    );
+}
+
+fn render_cors_popup(frame: &mut Frame, popup: &CorsPopup) {
+    let area = frame.area();
+    let width = area.width.saturating_sub(8).clamp(20, 90);
+    let height = area.height.saturating_sub(4).clamp(5, 16);
+    let popup_area = centered(area, width, height);
+    frame.render_widget(Clear, popup_area);
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let pass = Style::default().fg(Color::Green);
+    let fail = Style::default().fg(Color::Red);
+    let mut lines = Vec::new();
+    let hint = match &popup.preflight {
+        None => {
+            lines.push(Line::from("Origin the browser page is served from"));
+            lines.push(Line::from(""));
+            lines.push(render_input_line(&popup.origin));
+            " Enter: send OPTIONS  Esc: cancel "
+        }
+        Some(preflight) => {
+            lines.push(Line::from(vec![
+                Span::styled("OPTIONS ", Style::default().fg(Color::Cyan)),
+                Span::raw(format!("from {}", preflight.origin)),
+            ]));
+            for (name, value) in preflight.request_headers().iter().skip(1) {
+                lines.push(Line::from(Span::styled(format!("  {}: {}", name, value), dim)));
+            }
+            lines.push(Line::from(""));
+            match &popup.verdict {
+                None => lines.push(Line::from(Span::styled(
+                    "Waiting for the preflight\u{2026}",
+                    Style::default().fg(Color::Yellow),
+                ))),
+                Some(Err(err)) => lines.push(Line::from(Span::styled(
+                    format!("\u{2717} No answer: {}", err),
+                    fail,
+                ))),
+                Some(Ok(verdict)) => {
+                    for check in &verdict.checks {
+                        let (mark, style) =
+                            if check.passed { ("\u{2713}", pass) } else { ("\u{2717}", fail) };
+                        lines.push(Line::from(vec![
+                            Span::styled(format!("{} {:<8}", mark, check.label), style),
+                            Span::raw(check.detail.clone()),
+                        ]));
+                    }
+                    lines.push(Line::from(vec![
+                        Span::styled(format!("  {:<8}", "Max age"), dim),
+                        Span::raw(verdict.max_age_text()),
+                    ]));
+                    lines.push(Line::from(""));
+                    lines.push(if verdict.allowed() {
+                        Line::from(Span::styled("Allowed: the browser sends the request", pass))
+                    } else {
+                        Line::from(Span::styled("Blocked: the browser stops here", fail))
+                    });
+                }
+            }
+            " Esc: close (the response panel keeps the preflight) "
+        }
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(" CORS preflight ")
+        .title_bottom(Line::from(Span::styled(hint, dim)));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner);
+}
+
+fn render_trust_prompt(frame: &mut Frame, root: &str, trusted: bool) {
+    let area = frame.area();
+    let width = area.width.saturating_sub(8).clamp(20, 72);
+    let height = std::cmp::min(11, area.height);
+    let popup_area = centered(area, width, height);
+
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title(" Trust this project? ");
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let state = if trusted { "trusted" } else { "not trusted" };
+    let lines = vec![
+        Line::from(Span::styled(root.to_string(), Style::default().fg(Color::Cyan))),
+        Line::from(format!("Currently {}.", state)),
+        Line::from(""),
+        Line::from(
+            "Requests in an untrusted project are shown fully resolved before the first \
+             send, and before every send that reads a local file.",
+        ),
+        Line::from(""),
+        Line::from(Span::styled(
+            "y: trust  n: don't trust  Esc: decide later",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner);
+}
+
+fn render_lock_prompt(frame: &mut Frame, owner: Option<LockOwner>) {
+    let area = frame.area();
+    let width = area.width.saturating_sub(8).clamp(20, 72);
+    let height = std::cmp::min(10, area.height);
+    let popup_area = centered(area, width, height);
+
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title(" Project lock left behind ");
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let holder = match owner {
+        Some(owner) => format!("Another perseus ({}) locked this project", owner.describe()),
+        None => "This project's lock file can't be read".to_string(),
+    };
+    let lines = vec![
+        Line::from(holder),
+        Line::from("and is no longer running."),
+        Line::from(""),
+        Line::from(
+            "Take it over if that instance crashed. Read-only still sends requests but \
+             refuses edits.",
+        ),
+        Line::from(""),
+        Line::from(Span::styled(
+            "t: take over  r / Esc: open read-only",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner);
+}
+
+fn render_onboarding(frame: &mut Frame, onboarding: &Onboarding, theme: Theme) {
+    let area = frame.area();
+    let width = area.width.saturating_sub(8).clamp(20, 64);
+    let height = std::cmp::min(12, area.height);
+    let popup_area = centered(area, width, height);
+
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Green))
+        .title(" Welcome to Perseus ");
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let lines = match onboarding {
+        Onboarding::Choose { index } => {
+            let mut lines = vec![Line::from("How would you like to start?"), Line::from("")];
+            for (i, (label, explanation)) in ONBOARDING_CHOICES.iter().enumerate() {
+                let style = if i == *index {
+                    theme.selection()
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                lines.push(Line::from(Span::styled(format!(" {}. {} ", i + 1, label), style)));
+                lines.push(Line::from(Span::styled(format!("    {}", explanation), dim)));
+            }
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "j/k: move  Enter or 1-3: choose  Esc: start empty",
+                dim,
+            )));
+            lines
+        }
+        Onboarding::Import(input) => vec![
+            Line::from("Postman collection, OpenAPI spec (.yaml/.json) or project archive (.zip)"),
+            Line::from(""),
+            render_input_line(input),
+            Line::from(""),
+            Line::from(Span::styled("Enter: import  Esc: back", dim)),
+        ],
+    };
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner);
+}
+
+fn render_send_confirm(frame: &mut Frame, confirm: &SendConfirm) {
+    let area = frame.area();
+    let width = area.width.saturating_sub(8).clamp(20, 100);
+    let height = (confirm.lines.len() as u16 + 6).min(area.height);
+    let popup_area = centered(area, width, height);
+
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title(" Send from an untrusted project? ");
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let mut lines: Vec<Line> = confirm
+        .lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let style = if i == 0 {
+                Style::default().add_modifier(Modifier::BOLD)
+            } else if line.starts_with("Reads file:") {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            };
+            Line::from(Span::styled(line.clone(), style))
+        })
+        .collect();
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "y/Enter: send  t: trust project and send  n/Esc: cancel",
+        Style::default().fg(Color::DarkGray),
+    )));
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner);
+}
+
+fn render_protected_confirm(frame: &mut Frame, confirm: &ProtectedConfirm) {
+    let area = frame.area();
+    let width = area.width.saturating_sub(8).clamp(20, 90);
+    let height = 10.min(area.height);
+    let popup_area = centered(area, width, height);
+
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Red))
+        .title(" Protected host ");
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let environment = confirm.environment.as_deref().unwrap_or("none");
+    let lines = vec![
+        Line::from(vec![
+            Span::styled(format!("{} ", confirm.method), bold.fg(Color::Red)),
+            Span::styled(confirm.url.clone(), bold),
+        ]),
+        Line::from(vec![Span::styled("Environment: ", dim), Span::raw(environment)]),
+        Line::from(Span::styled(confirm.reason.clone(), Style::default().fg(Color::Yellow))),
+        Line::from(""),
+        Line::from(format!("Type {} to send:", confirm.method)),
+        render_input_line(&confirm.input),
+        Line::from(""),
+        Line::from(Span::styled("Enter: send  Esc: cancel", dim)),
+    ];
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner);
+}
+
+fn render_unresolved_confirm(frame: &mut Frame, confirm: &UnresolvedConfirm) {
+    let area = frame.area();
+    let width = area.width.saturating_sub(8).clamp(20, 70);
+    let height = (confirm.names.len() as u16 + 6).min(area.height);
+    let popup_area = centered(area, width, height);
+
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title(" Unresolved variables ");
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let mut lines = vec![Line::from("These would be sent literally:")];
+    for (name, fields) in &confirm.names {
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {{{{{}}}}}", name), Style::default().fg(Color::Yellow)),
+            Span::styled(
+                format!("  in {}", fields.join(", ")),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "y/Enter: send anyway  e: switch environment  n/Esc: cancel",
+        Style::default().fg(Color::DarkGray),
+    )));
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner);
+}
+
+fn render_body_confirm(frame: &mut Frame, confirm: &BodyConfirm) {
+    let area = frame.area();
+    let width = area.width.saturating_sub(8).clamp(20, 70);
+    let popup_area = centered(area, width, 3.min(area.height));
+
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title(format!(" {} ", confirm.describe()));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let keys = match confirm.mismatch {
+        http::BodyMismatch::UnexpectedBody => {
+            "y/Enter: send anyway  p: switch to POST  n/Esc: cancel"
+        }
+        http::BodyMismatch::MissingBody => "y/Enter: send anyway  n/Esc: cancel",
+    };
+    let line = Line::from(Span::styled(keys, Style::default().fg(Color::DarkGray)));
+    frame.render_widget(Paragraph::new(line), inner);
+}
+
+fn render_draft_prompt(frame: &mut Frame, prompt: &DraftPrompt) {
+    let Some(recovered) = prompt.drafts.first() else {
+        return;
+    };
+    let area = frame.area();
+    let width = area.width.saturating_sub(8).clamp(20, 70);
+    let height = (recovered.changes.len() as u16 + 7).min(area.height);
+    let popup_area = centered(area, width, height);
+
+    frame.render_widget(Clear, popup_area);
+    let title = if prompt.drafts.len() > 1 {
+        format!(" Unsaved draft (1 of {}) ", prompt.drafts.len())
+    } else {
+        " Unsaved draft ".to_string()
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title(title);
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let mut lines = vec![
+        Line::from(Span::styled(
+            recovered.path.clone(),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from("has edits that were never saved. The draft changes:"),
+    ];
+    for change in &recovered.changes {
+        lines.push(Line::from(vec![
+            Span::styled("  \u{2022} ", dim),
+            Span::styled(change.clone(), Style::default().fg(Color::Yellow)),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "r/Enter: restore  d: discard  Esc: decide later",
+        dim,
+    )));
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner);
+}
+
+fn render_spec_update(frame: &mut Frame, prompt: &SpecUpdatePrompt) {
+    let area = frame.area();
+    let width = area.width.saturating_sub(8).clamp(20, 70);
+    let height = 8.min(area.height);
+    let popup_area = centered(area, width, height);
+
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow))
+        .title(" Update imported requests? ");
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let lines = vec![
+        Line::from(Span::styled(
+            prompt.spec.archive.project.name.clone(),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(format!(
+            "{} of its {} operations were imported before, by operationId.",
+            prompt.matched, prompt.spec.operations
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            "u/Enter: update them and add the rest  n: import as a new project  Esc: cancel",
+            dim,
+        )),
+    ];
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner);
+}
+
+fn render_error_popup(frame: &mut Frame, popup: &ErrorPopup) {
+    let area = frame.area();
+    let width = area.width.saturating_sub(8).clamp(20, 80);
+    let inner_width = width.saturating_sub(2).max(1) as usize;
+    let body_lines: usize = popup
+        .message
+        .lines()
+        .map(|l| l.chars().count().div_ceil(inner_width).max(1))
+        .sum();
+    let height = (body_lines as u16 + 4).min(area.height);
+    let popup_area = centered(area, width, height);
+
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Red))
+        .title(format!(" {} ", popup.title));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let mut lines: Vec<Line> = popup.message.lines().map(|l| Line::from(l.to_string())).collect();
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Enter/Esc: dismiss",
+        Style::default().fg(Color::DarkGray),
+    )));
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner);
+}
+
+fn is_field_focused(app: &App, field: RequestField) -> bool {
+    app.focus.panel == Panel::Request && app.focus.request_field == field
+}
+
+fn method_color(method: &Method) -> Color {
+    match method {
+        Method::Standard(m) => match m {
+            HttpMethod::Get => Color::Green,
+            HttpMethod::Post => Color::Blue,
+            HttpMethod::Put => Color::Yellow,
+            HttpMethod::Patch => Color::Magenta,
+            HttpMethod::Delete => Color::Red,
+            HttpMethod::Head => Color::Cyan,
+            HttpMethod::Options => Color::White,
+        },
+        Method::Custom(_) => Color::DarkGray,
+    }
+}
+
+fn render_request_input_row(frame: &mut Frame, app: &App, layout: &RequestInputLayout) {
+    // Render Method box with method-specific color
+    let method_focused = is_field_focused(app, RequestField::Method);
+    let method_col = method_color(&app.request.method);
+    let method_block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(app.theme.border(method_focused, Color::DarkGray));
+    // Truncate method display to fit area (inner width minus padding)
+    let display_str = app.request.method.as_str();
+    let max_width = layout.method_area.width.saturating_sub(2) as usize; // account for border
+    let display = if display_str.len() > max_width {
+        format!("{}\u{2026}", &display_str[..max_width.saturating_sub(1)])
+    } else {
+        display_str.to_string()
+    };
+    let method_text = Paragraph::new(Line::from(display))
+        .style(Style::default().fg(method_col))
+        .alignment(Alignment::Center)
+        .block(method_block);
+    frame.render_widget(method_text, layout.method_area);
+
+    // Render URL editor (TextArea handles its own cursor) while editing it,
+    // the URL split into colored parts otherwise. A URL too long for the
+    // field shows the stretch around the cursor, with ⟨ and ⟩ where it's cut.
+    let url = app.request.url_text();
+    let editing_url = app.app_mode == AppMode::Editing && is_field_focused(app, RequestField::Url);
+    if editing_url || url.is_empty() {
+        frame.render_widget(&app.request.url_editor, layout.url_area);
+    } else {
+        let spans: Vec<Span<'static>> = http::url_segments(&url)
+            .into_iter()
+            .map(|(part, text)| Span::styled(text.to_string(), url_part_style(part)))
+            .collect();
+        let block = app.request.url_editor.block().cloned();
+        let inner = block.as_ref().map_or(layout.url_area, |b| b.inner(layout.url_area));
+        let total = width_before(&spans, usize::MAX);
+        let cursor = width_before(&spans, app.request.url_editor.cursor().1);
+        let (start, width) = cursor_window(total, cursor, inner.width as usize);
+        let marker = Style::default().fg(Color::DarkGray);
+        let mut shown = Vec::new();
+        if start > 0 {
+            shown.push(Span::styled("\u{27e8}", marker));
+        }
+        shown.extend(clip_spans(&spans, start, width, None));
+        if start + width < total {
+            shown.push(Span::styled("\u{27e9}", marker));
+        }
+        let mut paragraph = Paragraph::new(Line::from(shown));
+        if let Some(block) = block {
+            paragraph = paragraph.block(block);
+        }
+        frame.render_widget(paragraph, layout.url_area);
+    }
+
+    // Preview of the substituted, normalized URL drawn over the bottom border
+    if let Some(preview) = app.url_preview() {
+        let area = layout.url_area;
+        if area.height >= 3 && area.width > 4 {
+            let preview_area = Rect::new(area.x + 1, area.y + area.height - 1, area.width - 2, 1);
+            let max = preview_area.width.saturating_sub(3) as usize;
+            let text: String = if preview.chars().count() > max {
+                let mut t: String = preview.chars().take(max.saturating_sub(1)).collect();
+                t.push('\u{2026}');
+                t
+            } else {
+                preview
+            };
+            frame.render_widget(
+                Paragraph::new(format!("\u{2192} {}", text))
+                    .style(Style::default().fg(Color::DarkGray)),
+                preview_area,
+            );
+        }
+    }
+
+    // Render Send/Cancel button with focus highlight
+    let send_focused = is_field_focused(app, RequestField::Send);
+    let (btn_label, btn_color) = if app.is_busy() {
+        ("[ Cancel ]", Color::Red)
+    } else {
+        ("[ Send ]", Color::Green)
+    };
+    let send_block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(app.theme.border(send_focused, Color::DarkGray));
+    let send_text = Paragraph::new(Line::from(btn_label))
+        .style(Style::default().fg(btn_color))
+        .block(send_block);
+    frame.render_widget(send_text, layout.send_area);
+}
+
+fn url_part_style(part: http::UrlPart) -> Style {
+    match part {
+        http::UrlPart::Scheme | http::UrlPart::Fragment => Style::default().fg(Color::DarkGray),
+        http::UrlPart::Host => Style::default().fg(Color::Cyan),
+        http::UrlPart::Path => Style::default(),
+        http::UrlPart::Query(index) if index % 2 == 0 => Style::default().fg(Color::Magenta),
+        http::UrlPart::Query(_) => Style::default().fg(Color::LightMagenta),
+        http::UrlPart::Variable => Style::default().fg(Color::Yellow),
+    }
+}
+
+/// Where the open request sits in the collection, fitted into the panel's
+/// top border, with `*` while it has unsaved edits and 📝 when the user has
+/// notes on it.
+fn request_breadcrumb(app: &App, panel_width: u16) -> String {
+    let path = app
+        .current_request_id
+        .map(|id| app.sidebar_tree.path_for(id))
+        .unwrap_or_default();
+    if path.is_empty() {
+        return "Request".to_string();
+    }
+    let dirty = if app.request_dirty { " *" } else { "" };
+    // Two columns wide, after a space
+    let (note, note_width) = match app.current_note() {
+        Some(_) => (" \u{1f4dd}", 3),
+        None => ("", 0),
+    };
+    let focus_mark = if app.theme.high_contrast { " [FOCUS]".len() } else { 0 };
+    let marks = dirty.len() + note_width + focus_mark;
+    let width = (panel_width as usize).saturating_sub(2 + marks);
+    format!("{}{}{}", breadcrumb(&path, width), dirty, note)
+}
+
+fn render_request_panel(frame: &mut Frame, app: &mut App, area: Rect) {
+    let request_panel_focused = app.focus.panel == Panel::Request
+        && matches!(
+            app.focus.request_field,
+            RequestField::Headers | RequestField::Auth | RequestField::Body
+        );
+    let outer_block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(app.theme.border(request_panel_focused, Color::White))
+        .title(app.theme.title(&request_breadcrumb(app, area.width), request_panel_focused));
+
+    let inner_area = outer_block.inner(area);
+    frame.render_widget(outer_block, area);
 
     let layout = RequestLayout::new(inner_area);
 
     // Render Request tabs
-    render_request_tab_bar(frame, app, layout.tab_area);
+    if app.panel_caches.request_tabs.is_stale(app.dirty_panels.request, layout.tab_area) {
+        app.panel_caches.request_tabs = PanelCache {
+            area: layout.tab_area,
+            lines: vec![request_tab_line(app)],
+        };
+    }
+    let tabs_widget = Paragraph::new(app.panel_caches.request_tabs.lines.clone());
+    frame.render_widget(tabs_widget, layout.tab_area);
     frame.render_widget(Paragraph::new(""), layout.spacer_area);
 
+    // The header a send adds, under the headers the user typed
+    let mut content_area = layout.content_area;
+    if app.request_tab == RequestTab::Headers && app.implies_gzip_body() && content_area.height > 1
+    {
+        content_area.height -= 1;
+        let hint = format!(
+            "+ Content-Encoding: gzip (bodies of {} or more)",
+            http::format_bytes(http::COMPRESS_MIN_BYTES)
+        );
+        let hint_area = Rect { y: content_area.bottom(), height: 1, ..content_area };
+        frame.render_widget(
+            Paragraph::new(hint).style(Style::default().fg(Color::DarkGray)),
+            hint_area,
+        );
+    }
+
     // Render active Request editor (TextArea)
     match app.request_tab {
+        RequestTab::Headers if app.headers_table => {
+            let headers_focused = app.focus.panel == Panel::Request
+                && app.focus.request_field == RequestField::Headers;
+            render_kv_table(
+                frame,
+                &app.request.header_rows,
+                &[],
+                false,
+                app.focus.kv_focus,
+                headers_focused,
+                app.app_mode == AppMode::Editing,
+                &app.kv_edit_textarea,
+                content_area,
+            );
+        }
         RequestTab::Headers => {
-            frame.render_widget(&app.request.headers_editor, layout.content_area);
+            frame.render_widget(&app.request.headers_editor, content_area);
         }
         RequestTab::Auth => {
-            render_auth_panel(frame, app, layout.content_area);
+            render_auth_panel(frame, app, content_area);
         }
         RequestTab::Body => {
-            render_body_panel(frame, app, layout.content_area);
+            render_body_panel(frame, app, content_area);
         }
     }
 }
 
-fn render_request_tab_bar(frame: &mut Frame, app: &App, area: Rect) {
+fn request_tab_line(app: &App) -> Line<'static> {
     let request_panel_focused = app.focus.panel == Panel::Request
         && matches!(
             app.focus.request_field,
             RequestField::Headers | RequestField::Auth | RequestField::Body
         );
-    let active_color = if request_panel_focused {
-        Color::Green
-    } else {
-        Color::White
-    };
-    let active_style = Style::default()
-        .fg(active_color)
-        .add_modifier(Modifier::UNDERLINED);
+    let active_style = app.theme.active_tab(request_panel_focused);
     let inactive_style = Style::default().fg(Color::DarkGray);
 
     let auth_label = match app.request.auth_type {
@@ -935,8 +3459,19 @@ fn render_request_tab_bar(frame: &mut Frame, app: &App, area: Rect) {
         BodyMode::Multipart => "Body (Multipart)".to_string(),
         BodyMode::Binary => "Body (Binary)".to_string(),
     };
+    // A section left out of sends shows an empty checkbox and "(off)"
+    let auth_label = if app.request.auth_disabled {
+        "\u{2610} Auth (off)".to_string()
+    } else {
+        auth_label
+    };
+    let body_label = if app.request.body_disabled {
+        "\u{2610} Body (off)".to_string()
+    } else {
+        body_label
+    };
 
-    let tabs_line = Line::from(vec![
+    let mut line = Line::from(vec![
         Span::styled(
             "Headers",
             if app.request_tab == RequestTab::Headers {
@@ -964,9 +3499,11 @@ fn render_request_tab_bar(frame: &mut Frame, app: &App, area: Rect) {
             },
         ),
     ]);
-
-    let tabs_widget = Paragraph::new(tabs_line);
-    frame.render_widget(tabs_widget, area);
+    if app.request_tab == RequestTab::Body && app.body_wrap && app.request.body_mode.is_text_mode()
+    {
+        line.push_span(Span::styled("  wrap", inactive_style));
+    }
+    line
 }
 
 fn render_auth_panel(frame: &mut Frame, app: &App, area: Rect) {
@@ -1058,7 +3595,12 @@ fn render_auth_panel(frame: &mut Frame, app: &App, area: Rect) {
                 Style::default().fg(Color::Cyan)
             };
             frame.render_widget(
-                Paragraph::new("Password:").style(p_style),
+                Paragraph::new(secret_label(
+                    "Password:",
+                    &app.request.auth_password_editor,
+                    password_focused,
+                ))
+                .style(p_style),
                 field_chunks[2],
             );
             frame.render_widget(&app.request.auth_password_editor, field_chunks[3]);
@@ -1098,7 +3640,12 @@ fn render_auth_panel(frame: &mut Frame, app: &App, area: Rect) {
                 Style::default().fg(Color::Cyan)
             };
             frame.render_widget(
-                Paragraph::new("Value:").style(kv_style),
+                Paragraph::new(secret_label(
+                    "Value:",
+                    &app.request.auth_key_value_editor,
+                    kv_focused,
+                ))
+                .style(kv_style),
                 field_chunks[2],
             );
             frame.render_widget(&app.request.auth_key_value_editor, field_chunks[3]);
@@ -1120,17 +3667,26 @@ fn render_auth_panel(frame: &mut Frame, app: &App, area: Rect) {
     }
 }
 
+/// A secret field's label; when focused, followed by the reveal hint.
+fn secret_label(label: &'static str, editor: &TextArea, focused: bool) -> Line<'static> {
+    let mut spans = vec![Span::raw(label)];
+    if focused {
+        let hint = if editor.mask_char().is_some() {
+            "  Ctrl+H: show"
+        } else {
+            "  Ctrl+H: hide"
+        };
+        spans.push(Span::styled(hint, Style::default().fg(Color::DarkGray)));
+    }
+    Line::from(spans)
+}
+
 fn render_response_panel(frame: &mut Frame, app: &mut App, area: Rect) {
-    let border_color = if app.focus.panel == Panel::Response {
-        Color::Green
-    } else {
-        Color::White
-    };
-
+    let focused = app.focus.panel == Panel::Response;
     let outer_block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(border_color))
-        .title("Response");
+        .border_style(app.theme.border(focused, Color::White))
+        .title(app.theme.title("Response", focused));
 
     let inner_area = outer_block.inner(area);
     frame.render_widget(outer_block, area);
@@ -1142,28 +3698,69 @@ fn render_response_panel(frame: &mut Frame, app: &mut App, area: Rect) {
     let editing_response =
         app.app_mode == AppMode::Editing && app.focus.panel == Panel::Response;
     let response_tab = app.response_tab;
-    let response_scroll = app.response_scroll;
+    let scroll = ResponseScroll {
+        column: app.response_hscroll,
+        wrap: app.response_wrap,
+    };
     match &app.response {
         ResponseStatus::Empty => {
-            let hint = Paragraph::new("Press Ctrl+R to send request")
-                .style(Style::default().fg(Color::DarkGray));
+            let mut lines = vec![Line::from("Press Ctrl+R to send request")];
+            if let Some(description) = app.current_request_description() {
+                lines.push(Line::from(""));
+                lines.extend(description.lines().map(|line| Line::from(line.to_string())));
+            }
+            let hint = Paragraph::new(lines)
+                .style(Style::default().fg(Color::DarkGray))
+                .wrap(Wrap { trim: false });
             frame.render_widget(hint, response_layout.content_area);
         }
         ResponseStatus::Loading => {
-            let spinner_frames = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
-            let frame_idx = (app.loading_tick as usize / 4) % spinner_frames.len();
-            let loading = Paragraph::new(format!("{} Sending request...", spinner_frames[frame_idx]))
-                .style(Style::default().fg(Color::Yellow));
+            let elapsed = app
+                .loading_started
+                .map(|start| start.elapsed())
+                .unwrap_or_default();
+            let label = app.theme.loading_label(app.loading_tick, elapsed);
+            let text = match app.loading_progress {
+                Some(progress) => format!(
+                    "{} \u{2014} {} {} \u{2014} {} received",
+                    label,
+                    progress.status,
+                    progress.status_text,
+                    progress_text(&progress)
+                ),
+                // The reduced-motion label already says it's sending
+                None if app.theme.reduced_motion => label,
+                None => format!("{} \u{2014} Sending request...", label),
+            };
+            let loading = Paragraph::new(text).style(Style::default().fg(Color::Yellow));
             frame.render_widget(loading, response_layout.content_area);
         }
-        ResponseStatus::Error(msg) => {
-            let error_lines = vec![Line::from(vec![
-                Span::styled("✗ ", Style::default().fg(Color::Red)),
-                Span::raw(msg.as_str()),
-            ])];
-            let error_text = Paragraph::new(error_lines)
-                .style(Style::default().fg(Color::Red))
-                .wrap(Wrap { trim: true });
+        ResponseStatus::Error(err) => {
+            let dim = Style::default().fg(Color::DarkGray);
+            let mut error_lines = vec![
+                Line::from(vec![
+                    Span::styled("✗ ", Style::default().fg(Color::Red)),
+                    Span::styled(
+                        err.kind.title(),
+                        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                    ),
+                ]),
+                Line::from(Span::styled(err.summary.clone(), Style::default().fg(Color::Red))),
+            ];
+            if let Some(suggestion) = err.kind.suggestion() {
+                error_lines.push(Line::from(Span::styled(
+                    format!("\u{2192} {}", suggestion),
+                    Style::default().fg(Color::Yellow),
+                )));
+            }
+            error_lines.push(Line::from(""));
+            if app.show_error_detail {
+                error_lines.push(Line::from(Span::styled("D: hide details", dim)));
+                error_lines.extend(err.detail.lines().map(|line| Line::from(line.to_string())));
+            } else {
+                error_lines.push(Line::from(Span::styled("D: show details", dim)));
+            }
+            let error_text = Paragraph::new(error_lines).wrap(Wrap { trim: true });
             frame.render_widget(error_text, response_layout.content_area);
         }
         ResponseStatus::Cancelled => {
@@ -1172,6 +3769,30 @@ fn render_response_panel(frame: &mut Frame, app: &mut App, area: Rect) {
             frame.render_widget(hint, response_layout.content_area);
         }
         ResponseStatus::Success(data) => {
+            let mut content_area = response_layout.content_area;
+            if data.partial && content_area.height > 1 {
+                let banner = format!(
+                    "\u{2298} cancelled after {} / {:.1}s \u{2014} partial body shown",
+                    http::format_bytes(data.raw_size),
+                    data.duration_ms as f64 / 1000.0
+                );
+                let banner_area = Rect { height: 1, ..content_area };
+                frame.render_widget(
+                    Paragraph::new(banner).style(Style::default().fg(Color::Yellow)),
+                    banner_area,
+                );
+                content_area.y += 1;
+                content_area.height -= 1;
+            }
+            if response_tab == ResponseTab::Headers && content_area.height > 1 {
+                if let Some(line) = header_filter_line(app, data) {
+                    frame.render_widget(Paragraph::new(line), Rect { height: 1, ..content_area });
+                    content_area.y += 1;
+                    content_area.height -= 1;
+                }
+            }
+            app.response_view_width = content_area.width;
+            app.response_view_height = content_area.height;
             match response_tab {
                 ResponseTab::Body => {
                     let (response_editor, cache) =
@@ -1181,8 +3802,8 @@ fn render_response_panel(frame: &mut Frame, app: &mut App, area: Rect) {
                         response_editor,
                         cache,
                         data,
-                        response_layout.content_area,
-                        response_scroll,
+                        content_area,
+                        scroll,
                         editing_response,
                     );
                 }
@@ -1193,8 +3814,8 @@ fn render_response_panel(frame: &mut Frame, app: &mut App, area: Rect) {
                         frame,
                         response_headers_editor,
                         cache,
-                        response_layout.content_area,
-                        response_scroll,
+                        content_area,
+                        scroll,
                         editing_response,
                     );
                 }
@@ -1203,20 +3824,43 @@ fn render_response_panel(frame: &mut Frame, app: &mut App, area: Rect) {
     }
 }
 
-fn render_response_tab_bar(frame: &mut Frame, app: &App, area: Rect) {
-    let (status_text, status_style) = response_status_text(app);
-    let active_color = if app.focus.panel == Panel::Response {
-        Color::Green
-    } else {
-        Color::White
+/// How far the response content is scrolled sideways, and whether it
+/// wraps. The row each tab is scrolled to lives in its render cache.
+#[derive(Clone, Copy)]
+struct ResponseScroll {
+    column: u16,
+    wrap: bool,
+}
+
+fn render_response_tab_bar(frame: &mut Frame, app: &mut App, area: Rect) {
+    if app.panel_caches.response_tabs.is_stale(app.dirty_panels.response, area) {
+        app.panel_caches.response_tabs = PanelCache {
+            area,
+            lines: response_tab_lines(app),
+        };
+    }
+    let [tabs_line, status_line] = app.panel_caches.response_tabs.lines.as_slice() else {
+        return;
     };
-    let active_style = Style::default()
-        .fg(active_color)
-        .add_modifier(Modifier::UNDERLINED);
+    frame.render_widget(Paragraph::new(tabs_line.clone()), area);
+    frame.render_widget(
+        Paragraph::new(status_line.clone()).alignment(Alignment::Right),
+        area,
+    );
+}
+
+/// The tab labels and the right-aligned status summary.
+fn response_tab_lines(app: &App) -> Vec<Line<'static>> {
+    let (status_text, status_style) = response_status_text(app);
+    let active_style = app.theme.active_tab(app.focus.panel == Panel::Response);
     let inactive_style = Style::default().fg(Color::DarkGray);
-    let tabs_line = Line::from(vec![
+    let body_label = match app.response_body_mode() {
+        Some(mode) => format!("Body [{}]", mode),
+        None => "Body".to_string(),
+    };
+    let mut tabs_line = Line::from(vec![
         Span::styled(
-            "Body",
+            body_label,
             if app.response_tab == ResponseTab::Body {
                 active_style
             } else {
@@ -1233,14 +3877,67 @@ fn render_response_tab_bar(frame: &mut Frame, app: &App, area: Rect) {
             },
         ),
     ]);
+    if !app.response_wrap && matches!(app.response, ResponseStatus::Success(_)) {
+        tabs_line.push_span(Span::styled(
+            format!("  no wrap \u{b7} col {}", app.response_hscroll as usize + 1),
+            inactive_style,
+        ));
+    }
+    if let Some(percent) = app.response_scroll_percent() {
+        tabs_line.push_span(Span::styled(format!("  {}%", percent), inactive_style));
+    }
+    let mut status_line = Line::from(Span::styled(status_text, status_style));
+    if app.tls_verification_disabled() {
+        status_line.spans.insert(
+            0,
+            Span::styled(
+                "\u{26a0} TLS verification disabled  ",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+        );
+    }
+    if let ResponseStatus::Success(data) = &app.response {
+        if let Some(normalized) = data.normalized.describe() {
+            let text = format!("{}  ", normalized);
+            status_line.spans.insert(0, Span::styled(text, Style::default().fg(Color::DarkGray)));
+        }
+        if data.lossy {
+            let text = format!("{} \u{fffd}  ", data.charset.label());
+            status_line.spans.insert(0, Span::styled(text, Style::default().fg(Color::Yellow)));
+        } else if data.charset != Charset::Utf8 {
+            let text = format!("{}  ", data.charset.label());
+            status_line.spans.insert(0, Span::styled(text, Style::default().fg(Color::DarkGray)));
+        }
+    }
+    if let Some(check) = app.current_schema_check() {
+        let (text, color) = match check {
+            SchemaCheck::Passed => ("schema \u{2713}".to_string(), Color::Green),
+            SchemaCheck::Failed(violations) => {
+                (format!("schema \u{2717} {}", violations.len()), Color::Red)
+            }
+            SchemaCheck::SchemaError(_) => ("schema error".to_string(), Color::Yellow),
+        };
+        status_line.spans.insert(0, Span::raw("  "));
+        status_line.spans.insert(0, Span::styled(text, Style::default().fg(color)));
+    }
+    if matches!(app.response, ResponseStatus::Success(_) | ResponseStatus::Error(_)) {
+        if let Some(summary) = send_history_summary(app.send_history()) {
+            status_line.push_span(Span::styled(format!("  {}", summary), inactive_style));
+        }
+    }
+    vec![tabs_line, status_line]
+}
 
-    let tabs_widget = Paragraph::new(tabs_line);
-    frame.render_widget(tabs_widget, area);
-
-    let status_widget =
-        Paragraph::new(Line::from(Span::styled(status_text, status_style)))
-            .alignment(Alignment::Right);
-    frame.render_widget(status_widget, area);
+/// A sparkline of recent durations with their p50 and p95, e.g.
+/// "⣀⣤⣶ p50 120ms p95 340ms".
+fn send_history_summary(sends: &[SendStat]) -> Option<String> {
+    let durations: Vec<u64> = sends.iter().map(|send| send.duration_ms).collect();
+    Some(format!(
+        "{} p50 {}ms p95 {}ms",
+        sparkline(&durations),
+        percentile(&durations, 50)?,
+        percentile(&durations, 95)?
+    ))
 }
 
 fn response_status_text(app: &App) -> (String, Style) {
@@ -1249,24 +3946,94 @@ fn response_status_text(app: &App) -> (String, Style) {
             "Idle".to_string(),
             Style::default().fg(Color::DarkGray),
         ),
-        ResponseStatus::Loading => (
-            "Sending request...".to_string(),
-            Style::default().fg(Color::Yellow),
-        ),
+        ResponseStatus::Loading => match app.loading_progress {
+            Some(progress) => (
+                format!("{} {} \u{b7} receiving...", progress.status, progress.status_text),
+                Style::default().fg(status_color(progress.status)),
+            ),
+            None => (
+                "Sending request...".to_string(),
+                Style::default().fg(Color::Yellow),
+            ),
+        },
         ResponseStatus::Error(_) => ("Error".to_string(), Style::default().fg(Color::Red)),
         ResponseStatus::Cancelled => (
             "Cancelled".to_string(),
             Style::default().fg(Color::Yellow),
         ),
-        ResponseStatus::Success(data) => (
-            format!("{} {} ({}ms)", data.status, data.status_text, data.duration_ms),
-            Style::default().fg(status_color(data.status)),
+        ResponseStatus::Success(data) => {
+            let mut text = format!(
+                "{} {} ({}ms) \u{b7} {}",
+                data.status,
+                data.status_text,
+                data.duration_ms,
+                response_size_text(data)
+            );
+            if let Some(version) = data.version {
+                text.push_str(&format!(" \u{b7} {}", version));
+            }
+            if data.example {
+                text.push_str(" (example)");
+            }
+            if let Some(length) = head_content_length(data) {
+                text.push_str(&format!(" \u{b7} Content-Length {}", length));
+            }
+            if let Some(sent) = data.compression {
+                text.push_str(&format!(
+                    " \u{b7} body {} \u{2192} {}",
+                    http::format_bytes(sent.original),
+                    http::format_bytes(sent.compressed)
+                ));
+            }
+            (text, Style::default().fg(status_color(data.status)))
+        }
+    }
+}
+
+/// The size a HEAD response announces, usually why it was sent:
+/// "5120 (5.0 KB)".
+fn head_content_length(data: &crate::app::ResponseData) -> Option<String> {
+    if data.no_body != Some(http::NoBody::Head) {
+        return None;
+    }
+    let length = http::header_value(&data.headers, "content-length")?.trim();
+    Some(match length.parse::<usize>() {
+        Ok(bytes) => format!("{} ({})", bytes, http::format_bytes(bytes)),
+        Err(_) => length.to_string(),
+    })
+}
+
+/// "2.3 MB of 8.1 MB (28%)" when the length is known, "2.3 MB" otherwise.
+fn progress_text(progress: &http::Progress) -> String {
+    let received = http::format_bytes(progress.received);
+    match (progress.total, progress.percent()) {
+        (Some(total), Some(percent)) => format!(
+            "{} of {} ({}%)",
+            received,
+            http::format_bytes(total as usize),
+            percent
+        ),
+        _ => received,
+    }
+}
+
+/// "14.1 KB (gzip, 92.0 KB decoded)" for compressed bodies, plain size otherwise.
+fn response_size_text(data: &crate::app::ResponseData) -> String {
+    let raw = http::format_bytes(data.raw_size);
+    match data.content_encoding.as_deref() {
+        Some(encoding) if data.decoded_size != data.raw_size => format!(
+            "{} ({}, {} decoded)",
+            raw,
+            encoding,
+            http::format_bytes(data.decoded_size)
         ),
+        Some(encoding) => format!("{} ({}, not decoded)", raw, encoding),
+        None => raw,
     }
 }
 
 fn status_color(status: u16) -> Color {
-    if status >= 200 && status < 300 {
+    if (200..300).contains(&status) {
         Color::Green
     } else if status >= 400 {
         Color::Red
@@ -1281,9 +4048,19 @@ fn render_response_body(
     cache: &mut ResponseBodyRenderCache,
     data: &crate::app::ResponseData,
     area: Rect,
-    scroll_offset: u16,
+    scroll: ResponseScroll,
     editing: bool,
 ) {
+    if let Some(no_body) = data.no_body {
+        let mut lines = vec![Line::from(format!("(no body: {})", no_body.reason()))];
+        if let Some(length) = head_content_length(data) {
+            lines.push(Line::from(""));
+            lines.push(Line::from(format!("Content-Length: {}", length)));
+        }
+        let placeholder = Paragraph::new(lines).style(Style::default().fg(Color::DarkGray));
+        frame.render_widget(placeholder, area);
+        return;
+    }
     if cache.dirty {
         let editor_lines = response_editor.lines();
         cache.body_text = editor_lines.join("\n");
@@ -1296,6 +4073,7 @@ fn render_response_body(
                 .map(|l| Line::from(l.clone()))
                 .collect()
         };
+        cache.max_line_width = cache.lines.iter().map(Line::width).max().unwrap_or(0);
         cache.generation = cache.generation.wrapping_add(1);
         cache.dirty = false;
         cache.wrap_cache.generation = 0;
@@ -1310,17 +4088,22 @@ fn render_response_body(
     } else {
         None
     };
-    render_wrapped_response_cached(
-        frame,
-        area,
-        &cache.lines,
-        &mut cache.wrap_cache,
-        cache.generation,
-        cursor,
-        selection,
-        scroll_offset,
-        editing,
-    );
+    cache.scroll = if scroll.wrap {
+        render_wrapped_response_cached(
+            frame,
+            area,
+            &cache.lines,
+            &mut cache.wrap_cache,
+            cache.generation,
+            cursor,
+            selection,
+            cache.scroll,
+            editing,
+        )
+    } else {
+        let (lines, row) = (&cache.lines, cache.scroll);
+        render_unwrapped_response(frame, area, lines, cursor, selection, row, scroll, editing)
+    };
 }
 
 fn render_response_headers(
@@ -1328,12 +4111,13 @@ fn render_response_headers(
     response_headers_editor: &TextArea<'static>,
     cache: &mut ResponseHeadersRenderCache,
     area: Rect,
-    scroll_offset: u16,
+    scroll: ResponseScroll,
     editing: bool,
 ) {
     if cache.dirty {
         let header_lines = response_headers_editor.lines();
         cache.lines = colorize_headers(header_lines);
+        cache.max_line_width = cache.lines.iter().map(Line::width).max().unwrap_or(0);
         cache.generation = cache.generation.wrapping_add(1);
         cache.dirty = false;
         cache.wrap_cache.generation = 0;
@@ -1348,23 +4132,27 @@ fn render_response_headers(
     } else {
         None
     };
-    render_wrapped_response_cached(
-        frame,
-        area,
-        &cache.lines,
-        &mut cache.wrap_cache,
-        cache.generation,
-        cursor,
-        selection,
-        scroll_offset,
-        editing,
-    );
+    cache.scroll = if scroll.wrap {
+        render_wrapped_response_cached(
+            frame,
+            area,
+            &cache.lines,
+            &mut cache.wrap_cache,
+            cache.generation,
+            cursor,
+            selection,
+            cache.scroll,
+            editing,
+        )
+    } else {
+        let (lines, row) = (&cache.lines, cache.scroll);
+        render_unwrapped_response(frame, area, lines, cursor, selection, row, scroll, editing)
+    };
 }
 
 fn is_json_response(headers: &[(String, String)], body: &str) -> bool {
-    let has_json_content_type = headers.iter().any(|(k, v)| {
-        k.eq_ignore_ascii_case("content-type") && v.contains("application/json")
-    });
+    let has_json_content_type = http::header_value(headers, "content-type")
+        .is_some_and(|v| v.to_ascii_lowercase().contains("application/json"));
     if has_json_content_type {
         return true;
     }
@@ -1377,14 +4165,14 @@ fn colorize_json(json: &str) -> Vec<Line<'static>> {
     let mut lines = Vec::new();
     let mut current_spans: Vec<Span<'static>> = Vec::new();
 
-    let mut chars = json.chars().peekable();
+    let chars = json.chars().peekable();
     let mut in_string = false;
     let mut current_token = String::new();
     let mut stack: Vec<char> = Vec::new();
     let mut expecting_key = false;
     let mut current_string_is_key = false;
 
-    while let Some(c) = chars.next() {
+    for c in chars {
         match c {
             '"' if !in_string => {
                 in_string = true;
@@ -1509,11 +4297,50 @@ fn colorize_token(token: &str) -> Span<'static> {
     }
 }
 
+/// The `/` filter and sort state above the response headers, while either
+/// is in use: "/cache  3 of 14 · sorted".
+fn header_filter_line(app: &App, data: &crate::app::ResponseData) -> Option<Line<'static>> {
+    let dim = Style::default().fg(Color::DarkGray);
+    let filter = match &app.header_filter_input {
+        Some(input) => input.value.as_str(),
+        None => app.response_header_filter.as_str(),
+    };
+    if app.header_filter_input.is_none() && filter.is_empty() && !app.response_header_sort {
+        return None;
+    }
+    let mut spans = Vec::new();
+    match &app.header_filter_input {
+        Some(input) => {
+            spans.push(Span::styled("/", Style::default().fg(Color::Yellow)));
+            spans.extend(render_input_line(input).spans);
+        }
+        None if !filter.is_empty() => {
+            spans.push(Span::styled(format!("/{}", filter), Style::default().fg(Color::Yellow)));
+        }
+        None => {}
+    }
+    let shown = http::arrange_headers(&data.headers, app.response_header_sort, filter).len();
+    let mut summary = Vec::new();
+    if shown < data.headers.len() {
+        summary.push(format!("{} of {}", shown, data.headers.len()));
+    }
+    if app.response_header_sort {
+        summary.push("sorted".to_string());
+    }
+    if !summary.is_empty() {
+        let gap = if spans.is_empty() { "" } else { "  " };
+        spans.push(Span::styled(format!("{}{}", gap, summary.join(" \u{b7} ")), dim));
+    }
+    Some(Line::from(spans))
+}
+
 fn colorize_headers(lines: &[String]) -> Vec<Line<'static>> {
     lines
         .iter()
         .map(|line| {
-            if let Some((key, rest)) = line.split_once(':') {
+            if line.starts_with(HEADER_GROUP_MARK) {
+                Line::from(Span::styled(line.clone(), Style::default().fg(Color::DarkGray)))
+            } else if let Some((key, rest)) = line.split_once(':') {
                 Line::from(vec![
                     Span::styled(format!("{}:", key), Style::default().fg(Color::Cyan)),
                     Span::raw(rest.to_string()),
@@ -1525,6 +4352,10 @@ fn colorize_headers(lines: &[String]) -> Vec<Line<'static>> {
         .collect()
 }
 
+/// Draws `lines` soft-wrapped to the area from display row `scroll_offset`,
+/// moved as little as needed to keep the cursor in view. Returns the row
+/// drawn from.
+#[allow(clippy::too_many_arguments)]
 fn render_wrapped_response_cached(
     frame: &mut Frame,
     area: Rect,
@@ -1533,12 +4364,12 @@ fn render_wrapped_response_cached(
     lines_generation: u64,
     cursor: Option<(usize, usize)>,
     selection: Option<((usize, usize), (usize, usize))>,
-    scroll_offset: u16,
+    scroll_offset: usize,
     show_cursor: bool,
-) {
+) -> usize {
     let _guard = perf::scope("render_wrapped_response_cached");
     if area.height == 0 || area.width == 0 {
-        return;
+        return scroll_offset;
     }
 
     let width = area.width as usize;
@@ -1558,7 +4389,7 @@ fn render_wrapped_response_cached(
     }
 
     let height = area.height as usize;
-    let mut scroll_y = scroll_offset as usize;
+    let mut scroll_y = scroll_offset.min(cache.wrapped_lines.len().saturating_sub(height));
     if show_cursor {
         if let Some((_, cursor_y)) = cache.cursor_pos {
             if cursor_y >= scroll_y + height {
@@ -1594,6 +4425,69 @@ fn render_wrapped_response_cached(
             }
         }
     }
+    scroll_y
+}
+
+/// The no-wrap counterpart of [`render_wrapped_response_cached`]: lines are
+/// clipped to the area from `scroll.column` on, and only the visible rows
+/// are touched, so huge single-line bodies stay cheap. The view follows the
+/// cursor while editing; the row drawn from is returned.
+#[allow(clippy::too_many_arguments)]
+fn render_unwrapped_response(
+    frame: &mut Frame,
+    area: Rect,
+    lines: &[Line<'static>],
+    cursor: Option<(usize, usize)>,
+    selection: Option<((usize, usize), (usize, usize))>,
+    row: usize,
+    scroll: ResponseScroll,
+    show_cursor: bool,
+) -> usize {
+    let _guard = perf::scope("render_unwrapped_response");
+    if area.height == 0 || area.width == 0 {
+        return row;
+    }
+
+    let width = area.width as usize;
+    let height = area.height as usize;
+    let mut scroll_y = row.min(lines.len().saturating_sub(height));
+    let mut scroll_x = scroll.column as usize;
+    // Cursor as (display column, row)
+    let cursor_pos = cursor.filter(|_| show_cursor).map(|(row, col)| {
+        let x = lines.get(row).map_or(0, |line| width_before(&line.spans, col));
+        (x, row)
+    });
+    if let Some((cursor_x, cursor_y)) = cursor_pos {
+        if cursor_y >= scroll_y + height {
+            scroll_y = cursor_y - (height - 1);
+        } else if cursor_y < scroll_y {
+            scroll_y = cursor_y;
+        }
+        if cursor_x >= scroll_x + width {
+            scroll_x = cursor_x - (width - 1);
+        } else if cursor_x < scroll_x {
+            scroll_x = cursor_x;
+        }
+    }
+
+    let visible_lines: Vec<Line<'static>> = lines
+        .iter()
+        .enumerate()
+        .skip(scroll_y)
+        .take(height)
+        .map(|(row, line)| {
+            let selection = selection_range_for_row(selection, row, line_char_len(line));
+            Line::from(clip_spans(&line.spans, scroll_x, width, selection))
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(visible_lines), area);
+
+    if let Some((cursor_x, cursor_y)) = cursor_pos {
+        let x = area.x.saturating_add((cursor_x - scroll_x) as u16);
+        let y = area.y.saturating_add((cursor_y - scroll_y) as u16);
+        frame.set_cursor_position((x, y));
+    }
+    scroll_y
 }
 
 fn wrap_lines_with_cursor(
@@ -1608,7 +4502,7 @@ fn wrap_lines_with_cursor(
     let mut cursor_pos: Option<(usize, usize)> = None;
 
     for (row, line) in lines.iter().enumerate() {
-        let line_len = line_char_len(&line);
+        let line_len = line_char_len(line);
         let selection_range = selection_range_for_row(selection, row, line_len);
         let cursor_col = cursor.and_then(|(r, c)| if r == row { Some(c) } else { None });
         let (parts, line_cursor) =
@@ -1714,7 +4608,149 @@ fn push_span_char(spans: &mut Vec<Span<'static>>, style: Style, ch: char) {
     spans.push(Span::styled(ch.to_string(), style));
 }
 
-fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
+/// Error message plus a window of the URL around the offending character,
+/// with that character highlighted.
+fn url_error_spans(err: &crate::http::UrlError) -> Vec<Span<'static>> {
+    const CONTEXT: usize = 12;
+    let chars: Vec<char> = err.url.chars().collect();
+    let pos = err.position.min(chars.len());
+    let start = pos.saturating_sub(CONTEXT);
+    let end = (pos + 1 + CONTEXT).min(chars.len());
+    let before: String = chars[start..pos].iter().collect();
+    let bad: String = chars.get(pos).map(|c| c.to_string()).unwrap_or_default();
+    let after: String = chars[(pos + 1).min(end)..end].iter().collect();
+    let error_style = Style::default().fg(Color::Red);
+
+    let mut spans = vec![Span::styled(
+        format!("{} (col {}): ", err.message, pos + 1),
+        error_style,
+    )];
+    if start > 0 {
+        spans.push(Span::styled("\u{2026}", error_style));
+    }
+    spans.push(Span::raw(before));
+    spans.push(Span::styled(
+        if bad == " " { "\u{2423}".to_string() } else { bad },
+        Style::default()
+            .fg(Color::White)
+            .bg(Color::Red)
+            .add_modifier(Modifier::BOLD),
+    ));
+    spans.push(Span::raw(after));
+    if end < chars.len() {
+        spans.push(Span::styled("\u{2026}", error_style));
+    }
+    spans
+}
+
+fn render_status_bar(frame: &mut Frame, app: &mut App, area: Rect) {
+    if app.panel_caches.status_bar.is_stale(app.dirty_panels.status, area) {
+        app.panel_caches.status_bar = PanelCache {
+            area,
+            lines: vec![status_line(app, area.width)],
+        };
+    }
+    let status_bar = Paragraph::new(app.panel_caches.status_bar.lines.clone())
+        .style(Style::default().bg(Color::DarkGray).fg(Color::White));
+    frame.render_widget(status_bar, area);
+}
+
+/// The configured segments that fit in `width` columns, left to right.
+fn status_line(app: &App, width: u16) -> Line<'static> {
+    let url_error = app.url_error().is_some();
+    let segments: Vec<(StatusSegment, Vec<Span<'static>>)> = app
+        .config
+        .ui
+        .statusbar
+        .segments
+        .iter()
+        .map(|&segment| (segment, status_segment(app, segment)))
+        .filter(|(_, spans)| !spans.is_empty())
+        .collect();
+    let widths: Vec<(u8, usize)> = segments
+        .iter()
+        .map(|(segment, spans)| {
+            let width = spans.iter().map(Span::width).sum();
+            (statusbar::priority(*segment, url_error), width)
+        })
+        .collect();
+    let keep = statusbar::fit(&widths, width as usize);
+
+    let mut status_spans = Vec::new();
+    let mut previous = None;
+    for ((segment, spans), kept) in segments.into_iter().zip(keep) {
+        if !kept {
+            continue;
+        }
+        match previous {
+            None => {}
+            // The badge is separator enough
+            Some(StatusSegment::Mode) => status_spans.push(Span::raw("  ")),
+            Some(_) => status_spans.push(Span::raw("  │  ")),
+        }
+        status_spans.extend(spans);
+        previous = Some(segment);
+    }
+    Line::from(status_spans)
+}
+
+fn status_segment(app: &App, segment: StatusSegment) -> Vec<Span<'static>> {
+    match segment {
+        // Read-only stays in view as long as the mode badge does
+        StatusSegment::Mode if app.read_only => {
+            let label = match app.lock_owner {
+                Some(owner) => format!(" READ-ONLY: open in pid {} ", owner.pid),
+                None => " READ-ONLY ".to_string(),
+            };
+            let style = Style::default().fg(Color::Black).bg(Color::Yellow);
+            vec![mode_badge(app), Span::raw(" "), Span::styled(label, style)]
+        }
+        StatusSegment::Mode => vec![mode_badge(app)],
+        StatusSegment::Panel => vec![Span::raw(panel_label(app))],
+        StatusSegment::Request => request_segment(app),
+        // A URL validation failure replaces the key hints until the URL is edited
+        StatusSegment::Hints => match app.url_error() {
+            Some(err) => url_error_spans(err),
+            None => vec![Span::styled(key_hints(app), Style::default().fg(Color::DarkGray))],
+        },
+        StatusSegment::Notices => notice_spans(app),
+        StatusSegment::Environment => {
+            let mut spans = Vec::new();
+            if let Some(root) = &app.root_override {
+                spans.push(Span::styled(
+                    format!(" root: {} ", root),
+                    Style::default().fg(Color::Black).bg(Color::Gray),
+                ));
+            }
+            if let Some(env_name) = app.active_environment_name.as_deref() {
+                if !spans.is_empty() {
+                    spans.push(Span::raw(" "));
+                }
+                spans.push(Span::styled(
+                    format!(" {} ", env_name),
+                    Style::default()
+                        .fg(Color::Black)
+                        .bg(Color::Blue)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            }
+            spans
+        }
+        StatusSegment::Notification => app
+            .notifications
+            .visible()
+            .next_back()
+            .map(|note| {
+                vec![Span::styled(
+                    note.message.clone(),
+                    Style::default().fg(level_color(note.level)),
+                )]
+            })
+            .unwrap_or_default(),
+    }
+}
+
+fn mode_badge(app: &App) -> Span<'static> {
     let (mode_text, mode_style) = match app.app_mode {
         AppMode::Navigation => (
             " NAVIGATION ",
@@ -1761,8 +4797,11 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
                 .add_modifier(ratatui::style::Modifier::BOLD),
         ),
     };
+    Span::styled(mode_text, mode_style)
+}
 
-    let panel_info = match app.focus.panel {
+fn panel_label(app: &App) -> String {
+    match app.focus.panel {
         Panel::Sidebar => "Sidebar".to_string(),
         Panel::Request => {
             let field = match app.focus.request_field {
@@ -1776,10 +4815,26 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
             format!("Request > {}", field)
         }
         Panel::Response => format!("Response > {}", app.response_tab.label()),
+    }
+}
+
+/// The open request's name, with `●` while it has unsaved edits.
+fn request_segment(app: &App) -> Vec<Span<'static>> {
+    let Some(item) = app.current_request_id.and_then(|id| app.collection.get_item(id)) else {
+        return Vec::new();
     };
+    let mut spans = vec![Span::styled(item.name.clone(), Style::default().fg(Color::White))];
+    if app.request_dirty {
+        spans.push(Span::styled(" \u{25cf}", Style::default().fg(Color::Yellow)));
+    }
+    spans
+}
 
-    let hints = if app.focus.panel == Panel::Sidebar {
-        if matches!(app.app_mode, AppMode::Sidebar) {
+fn key_hints(app: &App) -> &'static str {
+    if app.focus.panel == Panel::Sidebar {
+        if matches!(app.sidebar.popup, Some(SidebarPopup::RenameInline(_))) {
+            "type name  Ctrl+W:delete word  Enter:rename  Esc:cancel"
+        } else if matches!(app.app_mode, AppMode::Sidebar) {
             "j/k:move  a:add  r:rename  d:del  m:move  /:search  Enter:open  Esc:exit"
         } else {
             "Enter/i:edit  hjkl:nav  Ctrl+p:projects  Ctrl+e:toggle"
@@ -1801,123 +4856,164 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
                 }
                 VimMode::Operator(_) => "motion:complete  Esc:cancel",
             },
-            AppMode::Sidebar => "j/k:move  a:add  r:rename  d:del  m:move  /:search  Enter:open  Esc:exit",
+            AppMode::Sidebar => {
+                "j/k:move  a:add  r:rename  d:del  m:move  /:search  f:filter  Enter:open  Esc:exit"
+            }
         }
-    };
+    }
+}
 
-    let mut status_spans = vec![
-        Span::styled(mode_text, mode_style),
-        Span::raw("  "),
-        Span::raw(panel_info),
-        Span::raw("  │  "),
-        Span::styled(hints, Style::default().fg(Color::DarkGray)),
-    ];
+/// Proxy, polling, run, pin, redirect, host override, offline, privacy
+/// and save notices, whichever apply.
+fn notice_spans(app: &App) -> Vec<Span<'static>> {
+    let mut status_spans = Vec::new();
+    if let Some(notice) = app.proxy_notice() {
+        status_spans.push(Span::raw("  │  "));
+        status_spans.push(Span::styled(notice, Style::default().fg(Color::Magenta)));
+    }
+
+    if let Some(notice) = app.poll_notice() {
+        status_spans.push(Span::raw("  │  "));
+        status_spans.push(Span::styled(notice, Style::default().fg(Color::Cyan)));
+    }
+
+    if let Some(notice) = app.run_notice() {
+        status_spans.push(Span::raw("  │  "));
+        status_spans.push(Span::styled(notice, Style::default().fg(Color::Cyan)));
+    }
+
+    if let Some(notice) = app.pin_notice() {
+        status_spans.push(Span::raw("  │  "));
+        status_spans.push(Span::styled(notice, Style::default().fg(Color::Yellow)));
+    }
 
-    if let Some(env_name) = app.active_environment_name.as_deref() {
+    if let Some(url) = app.redirected_url() {
         status_spans.push(Span::raw("  │  "));
         status_spans.push(Span::styled(
-            format!(" {} ", env_name),
-            Style::default()
-                .fg(Color::Black)
-                .bg(Color::Blue)
-                .add_modifier(Modifier::BOLD),
+            format!("\u{2192} {}", url),
+            Style::default().fg(Color::Cyan),
         ));
     }
 
-    if let Some(msg) = app.clipboard_toast_message() {
+    if let Some((host, target)) = app.current_host_override() {
         status_spans.push(Span::raw("  │  "));
         status_spans.push(Span::styled(
-            format!("Clipboard: {msg}"),
-            Style::default().fg(Color::Yellow),
+            format!("{} \u{2192} {}", host, target),
+            Style::default().fg(Color::Magenta),
         ));
     }
 
-    let status_line = Line::from(status_spans);
+    if app.offline {
+        status_spans.push(Span::raw("  │  "));
+        status_spans.push(Span::styled(
+            " OFFLINE ",
+            Style::default().fg(Color::Black).bg(Color::Yellow),
+        ));
+    }
 
-    let status_bar = Paragraph::new(status_line)
-        .style(Style::default().bg(Color::DarkGray).fg(Color::White));
-    frame.render_widget(status_bar, area);
+    if app.privacy_mode {
+        status_spans.push(Span::raw("  │  "));
+        status_spans.push(Span::styled(
+            " PRIVATE ",
+            Style::default().fg(Color::Black).bg(Color::Magenta),
+        ));
+    }
+
+    match app.save_status {
+        SaveStatus::Saved => {}
+        SaveStatus::Saving { .. } => {
+            status_spans.push(Span::raw("  │  "));
+            status_spans.push(Span::styled("saving\u{2026}", Style::default().fg(Color::Yellow)));
+        }
+        SaveStatus::Failed { attempts, .. } => {
+            status_spans.push(Span::raw("  │  "));
+            status_spans.push(Span::styled(
+                format!("\u{2717} save failed, retrying ({} attempts)", attempts),
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ));
+        }
+    }
+
+    // The first notice's separator goes between segments instead
+    if !status_spans.is_empty() {
+        status_spans.remove(0);
+    }
+    status_spans
 }
 
-fn render_help_overlay(frame: &mut Frame) {
+fn help_context(app: &App) -> HelpContext {
+    match (app.app_mode, app.focus.panel) {
+        (AppMode::Editing, _) => HelpContext::Editing,
+        (AppMode::Sidebar, _) | (_, Panel::Sidebar) => HelpContext::Sidebar,
+        (_, Panel::Response) => HelpContext::Response,
+        (_, Panel::Request) => HelpContext::Request,
+    }
+}
+
+fn render_help_overlay(frame: &mut Frame, app: &mut App) {
     let area = frame.area();
 
     let width = (area.width as f32 * 0.6) as u16;
     let height = (area.height as f32 * 0.7) as u16;
-    let x = (area.width - width) / 2;
-    let y = (area.height - height) / 2;
-    let help_area = Rect::new(x, y, width, height);
+    let help_area = centered(area, width, height);
 
     frame.render_widget(Clear, help_area);
 
-    let help_block = Block::default()
+    let context = help_context(app);
+    let help = &mut app.help;
+    let mut help_text = Vec::new();
+    for (section, bindings) in help::visible_bindings(context, &help.filter.value) {
+        if !help_text.is_empty() {
+            help_text.push(Line::from(""));
+        }
+        help_text.push(Line::from(Span::styled(
+            section.title,
+            Style::default().fg(Color::Yellow),
+        )));
+        for (keys, action) in bindings {
+            help_text.push(Line::from(format!("  {:<11} {}", keys, action)));
+        }
+    }
+    if help_text.is_empty() {
+        help_text.push(Line::from(Span::styled(
+            "  no bindings match",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    let mut help_block = Block::default()
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::Cyan))
-        .title(" Help (press ? to close) ");
-
+        .title(" Help (? to close, / to filter) ");
     let help_inner = help_block.inner(help_area);
+    let show_filter = help.filtering || !help.filter.value.is_empty();
+    let [filter_area, list_area] = Layout::vertical([
+        Constraint::Length(u16::from(show_filter)),
+        Constraint::Min(0),
+    ])
+    .areas(help_inner);
+
+    let total = help_text.len() as u16;
+    help.page = list_area.height.max(1);
+    help.scroll = help.scroll.min(total.saturating_sub(list_area.height));
+    if total > list_area.height {
+        let last = (help.scroll + list_area.height).min(total);
+        help_block = help_block.title_bottom(
+            Line::from(format!(" {}-{} of {} ", help.scroll + 1, last, total)).right_aligned(),
+        );
+    }
     frame.render_widget(help_block, help_area);
 
-    let help_text = vec![
-        Line::from(Span::styled(
-            "Navigation Mode",
-            Style::default().fg(Color::Yellow),
-        )),
-        Line::from("  h/j/k/l     Move focus across UI"),
-        Line::from("  Arrow keys  Same as h/j/k/l"),
-        Line::from("  e           Focus sidebar"),
-        Line::from("  Enter       Activate field (vim normal mode)"),
-        Line::from("  i           Enter field (vim insert mode)"),
-        Line::from("  Ctrl+r      Send request"),
-        Line::from("  Ctrl+e      Toggle sidebar (enter sidebar when opening)"),
-        Line::from("  Ctrl+p      Project switcher"),
-        Line::from("  Ctrl+s      Save request"),
-        Line::from("  Ctrl+n      Switch environment"),
-        Line::from("  q / Esc     Quit"),
-        Line::from(""),
-        Line::from(Span::styled(
-            "Sidebar",
-            Style::default().fg(Color::Yellow),
-        )),
-        Line::from("  Enter / i   Edit sidebar"),
-        Line::from("  Esc         Return to navigation"),
-        Line::from("  j/k or ↑/↓  Move selection"),
-        Line::from("  h           Collapse / parent"),
-        Line::from("  l / Enter   Toggle folder / open request"),
-        Line::from("  a           Add request or folder"),
-        Line::from("  r           Rename"),
-        Line::from("  d           Delete"),
-        Line::from("  D           Duplicate"),
-        Line::from("  m           Move"),
-        Line::from("  c           Copy path"),
-        Line::from("  /           Search"),
-        Line::from("  [ / ]       Outdent / indent"),
-        Line::from("  Shift+h/l   Collapse / expand all"),
-        Line::from(""),
-        Line::from(Span::styled(
-            "Vim Editing Mode",
-            Style::default().fg(Color::Yellow),
-        )),
-        Line::from("  h/j/k/l     Cursor movement"),
-        Line::from("  w/b/e       Word forward/back/end"),
-        Line::from("  0/^/$       Line start/end"),
-        Line::from("  gg/G        Top/bottom"),
-        Line::from("  i/a/I/A     Enter insert mode"),
-        Line::from("  o/O         New line below/above (multiline)"),
-        Line::from("  v/V         Visual / visual line"),
-        Line::from("  d/c/y       Delete/change/yank (+ motion)"),
-        Line::from("  dd/cc/yy    Operate on line"),
-        Line::from("  x/X         Delete char forward/backward"),
-        Line::from("  D/C         Delete/change to end of line"),
-        Line::from("  p           Paste"),
-        Line::from("  clipboard   y/d/c/x/D/C -> system; p from system"),
-        Line::from("  Cmd/Ctrl+C  Copy selection to system clipboard"),
-        Line::from("  Cmd/Ctrl+V  Paste from system clipboard"),
-        Line::from("  u / Ctrl+r  Undo / redo"),
-        Line::from("  Enter       Send request (URL field only)"),
-        Line::from("  Esc         Exit to navigation mode"),
-    ];
+    if show_filter {
+        let mut spans = vec![Span::styled("Filter: ", Style::default().fg(Color::Yellow))];
+        if help.filtering {
+            spans.extend(render_input_line(&help.filter).spans);
+        } else {
+            spans.push(Span::raw(help.filter.value.clone()));
+        }
+        frame.render_widget(Paragraph::new(Line::from(spans)), filter_area);
+    }
 
-    let help_paragraph = Paragraph::new(help_text);
-    frame.render_widget(help_paragraph, help_inner);
+    let help_paragraph = Paragraph::new(help_text).scroll((help.scroll, 0));
+    frame.render_widget(help_paragraph, list_area);
 }
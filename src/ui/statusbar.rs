@@ -0,0 +1,69 @@
+// Which status bar segments fit on a narrow terminal
+
+use crate::config::StatusSegment;
+
+/// Columns between two segments.
+pub const SEPARATOR_WIDTH: usize = 5;
+
+/// How long a segment holds out when the bar is too narrow: the lowest
+/// goes first. A URL error stands in for the hints and is kept longer.
+pub fn priority(segment: StatusSegment, url_error: bool) -> u8 {
+    match segment {
+        StatusSegment::Hints if url_error => 4,
+        StatusSegment::Hints => 0,
+        StatusSegment::Panel => 1,
+        StatusSegment::Notices => 2,
+        StatusSegment::Request => 3,
+        StatusSegment::Environment => 5,
+        StatusSegment::Notification => 6,
+        StatusSegment::Mode => 7,
+    }
+}
+
+/// Which of `segments`, given as `(priority, width)` in display order,
+/// to keep within `available` columns. Segments are dropped lowest
+/// priority first, the rightmost of equals first; the last one left is
+/// always kept.
+pub fn fit(segments: &[(u8, usize)], available: usize) -> Vec<bool> {
+    let mut keep = vec![true; segments.len()];
+    let total = |keep: &[bool]| {
+        let kept: Vec<usize> = segments
+            .iter()
+            .zip(keep)
+            .filter(|(_, kept)| **kept)
+            .map(|((_, width), _)| *width)
+            .collect();
+        kept.iter().sum::<usize>() + kept.len().saturating_sub(1) * SEPARATOR_WIDTH
+    };
+    let mut order: Vec<usize> = (0..segments.len()).collect();
+    order.sort_by_key(|&i| (segments[i].0, std::cmp::Reverse(i)));
+    for i in order.into_iter().take(segments.len().saturating_sub(1)) {
+        if total(&keep) <= available {
+            break;
+        }
+        keep[i] = false;
+    }
+    keep
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_drops_lowest_priority_first() {
+        let segments = [(7, 10), (1, 12), (0, 40), (5, 6)];
+        assert_eq!(fit(&segments, 200), vec![true, true, true, true]);
+        // 10 + 12 + 6 and two separators
+        assert_eq!(fit(&segments, 38), vec![true, true, false, true]);
+        assert_eq!(fit(&segments, 37), vec![true, false, false, true]);
+        assert_eq!(fit(&segments, 5), vec![true, false, false, false]);
+        assert!(fit(&[], 0).is_empty());
+    }
+
+    #[test]
+    fn test_fit_drops_rightmost_of_equal_priority() {
+        assert_eq!(fit(&[(2, 10), (2, 10)], 15), vec![true, false]);
+        assert!(priority(StatusSegment::Hints, true) > priority(StatusSegment::Panel, false));
+    }
+}
@@ -0,0 +1,153 @@
+//! Focus, selection and progress styling. `ui.high_contrast` shows focus
+//! and selection with attributes (bold, reverse video, a `[FOCUS]` title)
+//! instead of color alone; `ui.reduced_motion` replaces the spinner with an
+//! elapsed counter that changes once a second.
+
+use std::time::Duration;
+
+use ratatui::style::{Color, Modifier, Style};
+
+use crate::config::UiConfig;
+
+const SPINNER_FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Theme {
+    pub high_contrast: bool,
+    pub reduced_motion: bool,
+}
+
+impl Theme {
+    pub fn from_config(ui: &UiConfig) -> Self {
+        Self {
+            high_contrast: ui.high_contrast,
+            reduced_motion: ui.reduced_motion,
+        }
+    }
+
+    /// Border of a panel or field; `idle` is its color while unfocused.
+    pub fn border(self, focused: bool, idle: Color) -> Style {
+        match (focused, self.high_contrast) {
+            (true, true) => Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED),
+            (true, false) => Style::default().fg(Color::Green),
+            (false, _) => Style::default().fg(idle),
+        }
+    }
+
+    /// A panel title, marked while the panel has focus in high contrast.
+    pub fn title(self, title: &str, focused: bool) -> String {
+        if focused && self.high_contrast {
+            format!("{} [FOCUS]", title)
+        } else {
+            title.to_string()
+        }
+    }
+
+    /// The active tab of a panel.
+    pub fn active_tab(self, focused: bool) -> Style {
+        let style = Style::default().add_modifier(Modifier::UNDERLINED);
+        match (focused, self.high_contrast) {
+            (true, true) => style.add_modifier(Modifier::BOLD),
+            (true, false) => style.fg(Color::Green),
+            (false, _) => style.fg(Color::White),
+        }
+    }
+
+    /// The selected row of a list.
+    pub fn selection(self) -> Style {
+        if self.high_contrast {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default().bg(Color::DarkGray).fg(Color::White)
+        }
+    }
+
+    /// How often the loading indicator needs redrawing.
+    pub fn loading_interval(self) -> Duration {
+        if self.reduced_motion {
+            Duration::from_secs(1)
+        } else {
+            Duration::from_millis(100)
+        }
+    }
+
+    /// The start of the loading line: a spinner frame and tenths of a
+    /// second, or "Sending… 3s" with reduced motion.
+    pub fn loading_label(self, tick: u8, elapsed: Duration) -> String {
+        if self.reduced_motion {
+            format!("Sending\u{2026} {}s", elapsed.as_secs())
+        } else {
+            let frame = SPINNER_FRAMES[(tick as usize / 4) % SPINNER_FRAMES.len()];
+            format!("{} {:.1}s", frame, elapsed.as_secs_f64())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::widgets::{Block, Borders};
+    use ratatui::Terminal;
+
+    fn render_panel(theme: Theme, focused: bool) -> ratatui::buffer::Buffer {
+        let mut terminal = Terminal::new(TestBackend::new(24, 3)).unwrap();
+        terminal
+            .draw(|frame| {
+                let block = Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(theme.border(focused, Color::White))
+                    .title(theme.title("Request", focused));
+                frame.render_widget(block, frame.area());
+            })
+            .unwrap();
+        terminal.backend().buffer().clone()
+    }
+
+    fn row_text(buffer: &ratatui::buffer::Buffer, y: u16) -> String {
+        (0..buffer.area.width)
+            .map(|x| buffer[(x, y)].symbol())
+            .collect()
+    }
+
+    #[test]
+    fn test_high_contrast_marks_focus_without_color() {
+        let theme = Theme {
+            high_contrast: true,
+            reduced_motion: false,
+        };
+        let focused = render_panel(theme, true);
+        assert!(row_text(&focused, 0).contains("Request [FOCUS]"));
+        let corner = &focused[(0, 0)];
+        assert!(corner.modifier.contains(Modifier::BOLD | Modifier::REVERSED));
+        assert_eq!(corner.fg, Color::Reset);
+
+        let idle = render_panel(theme, false);
+        assert!(!row_text(&idle, 0).contains("[FOCUS]"));
+        assert!(!idle[(0, 0)].modifier.contains(Modifier::REVERSED));
+
+        assert!(theme.selection().add_modifier.contains(Modifier::REVERSED));
+        assert_eq!(theme.selection().bg, None);
+
+        // The default look keeps focus as a green border
+        let default = render_panel(Theme::default(), true);
+        assert!(!row_text(&default, 0).contains("[FOCUS]"));
+        assert_eq!(default[(0, 0)].fg, Color::Green);
+        assert!(default[(0, 0)].modifier.is_empty());
+    }
+
+    #[test]
+    fn test_reduced_motion_counts_whole_seconds() {
+        let theme = Theme {
+            high_contrast: false,
+            reduced_motion: true,
+        };
+        let elapsed = Duration::from_millis(3_400);
+        assert_eq!(theme.loading_label(0, elapsed), "Sending\u{2026} 3s");
+        assert_eq!(theme.loading_label(17, elapsed), "Sending\u{2026} 3s");
+        assert_eq!(theme.loading_interval(), Duration::from_secs(1));
+
+        assert_eq!(Theme::default().loading_label(0, elapsed), "⠋ 3.4s");
+        assert_eq!(Theme::default().loading_label(4, elapsed), "⠙ 3.4s");
+    }
+}
@@ -1 +1,402 @@
 // Widget wrappers for request/response panels
+
+use ratatui::{style::Color, text::Span};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Bytes shown per hex dump row.
+const HEX_ROW_BYTES: usize = 16;
+
+/// Classic hex dump rows: offset, 16 hex bytes split in two groups, and an
+/// ASCII gutter with non-printable bytes shown as `.`. At most `limit` bytes
+/// are dumped; a trailing note reports the remainder.
+pub fn hex_dump_lines(bytes: &[u8], limit: usize) -> Vec<String> {
+    let shown = &bytes[..bytes.len().min(limit)];
+    let mut lines = Vec::with_capacity(shown.len() / HEX_ROW_BYTES + 2);
+    for (row, chunk) in shown.chunks(HEX_ROW_BYTES).enumerate() {
+        let mut line = format!("{:08x}  ", row * HEX_ROW_BYTES);
+        for i in 0..HEX_ROW_BYTES {
+            match chunk.get(i) {
+                Some(b) => line.push_str(&format!("{:02x} ", b)),
+                None => line.push_str("   "),
+            }
+            if i == HEX_ROW_BYTES / 2 - 1 {
+                line.push(' ');
+            }
+        }
+        line.push_str(" |");
+        line.extend(chunk.iter().map(|&b| {
+            if b.is_ascii_graphic() || b == b' ' {
+                b as char
+            } else {
+                '.'
+            }
+        }));
+        line.push('|');
+        lines.push(line);
+    }
+    if bytes.len() > shown.len() {
+        lines.push(format!(
+            "... {} more bytes not shown (save the response to inspect them)",
+            bytes.len() - shown.len()
+        ));
+    }
+    lines
+}
+
+/// The part of a line between display columns `start` and `start + width`,
+/// for drawing it without wrapping. Characters in `selection` (a range of
+/// char indices) are highlighted; a wide character cut by either edge is
+/// left out.
+pub fn clip_spans(
+    spans: &[Span<'static>],
+    start: usize,
+    width: usize,
+    selection: Option<(usize, usize)>,
+) -> Vec<Span<'static>> {
+    let end = start + width;
+    let mut clipped: Vec<Span<'static>> = Vec::new();
+    let mut column = 0;
+    let mut char_index = 0;
+    for span in spans {
+        for ch in span.content.chars() {
+            if column >= end {
+                return clipped;
+            }
+            let ch_width = UnicodeWidthChar::width(ch).unwrap_or(0);
+            if column >= start && column + ch_width <= end {
+                let mut style = span.style;
+                if selection.is_some_and(|(from, to)| char_index >= from && char_index < to) {
+                    style = style.bg(Color::LightBlue);
+                }
+                match clipped.last_mut() {
+                    Some(last) if last.style == style => last.content.to_mut().push(ch),
+                    _ => clipped.push(Span::styled(ch.to_string(), style)),
+                }
+            }
+            column += ch_width;
+            char_index += 1;
+        }
+    }
+    clipped
+}
+
+/// Display width of the first `chars` characters of `spans`.
+pub fn width_before(spans: &[Span<'static>], chars: usize) -> usize {
+    spans
+        .iter()
+        .flat_map(|span| span.content.chars())
+        .take(chars)
+        .map(|ch| UnicodeWidthChar::width(ch).unwrap_or(0))
+        .sum()
+}
+
+/// The columns of a `total`-column line shown in a `width`-column field,
+/// as `(start, width)`: all of it when it fits, otherwise a window
+/// centered on the `cursor` column that leaves a column for the `⟨` or
+/// `⟩` marking each cut side.
+pub fn cursor_window(total: usize, cursor: usize, width: usize) -> (usize, usize) {
+    if total <= width {
+        return (0, total);
+    }
+    let inner = width.saturating_sub(2);
+    let start = cursor.saturating_sub(inner / 2);
+    if start <= 1 {
+        // Only the right side is cut
+        (0, width.saturating_sub(1))
+    } else if start + inner + 1 >= total {
+        let shown = width.saturating_sub(1);
+        (total - shown, shown)
+    } else {
+        (start, inner)
+    }
+}
+
+/// Char index at which each display row of `line` starts when it is
+/// soft-wrapped to `width` columns, breaking like the wrapped response view.
+fn wrap_starts(line: &str, width: usize) -> Vec<usize> {
+    let mut starts = vec![0];
+    let mut row_width = 0;
+    for (index, ch) in line.chars().enumerate() {
+        let ch_width = UnicodeWidthChar::width(ch).unwrap_or(0);
+        if row_width + ch_width > width && row_width > 0 {
+            starts.push(index);
+            row_width = 0;
+        }
+        row_width += ch_width;
+    }
+    starts
+}
+
+/// Where `gj` (`down`) or `gk` moves `cursor` over `lines` soft-wrapped to
+/// `width` columns: one display row on, keeping the display column where
+/// the row is long enough.
+pub fn display_line_move(
+    lines: &[String],
+    (row, col): (usize, usize),
+    width: usize,
+    down: bool,
+) -> (usize, usize) {
+    let width = width.max(1);
+    let Some(line) = lines.get(row) else {
+        return (row, col);
+    };
+    let starts = wrap_starts(line, width);
+    let segment = starts.iter().rposition(|&start| start <= col).unwrap_or(0);
+    let column: usize = line
+        .chars()
+        .skip(starts[segment])
+        .take(col - starts[segment])
+        .map(|ch| UnicodeWidthChar::width(ch).unwrap_or(0))
+        .sum();
+
+    let (target_row, target_starts, target_segment) = if down {
+        if segment + 1 < starts.len() {
+            (row, starts, segment + 1)
+        } else if row + 1 < lines.len() {
+            (row + 1, wrap_starts(&lines[row + 1], width), 0)
+        } else {
+            return (row, col);
+        }
+    } else if segment > 0 {
+        (row, starts, segment - 1)
+    } else if row > 0 {
+        let starts = wrap_starts(&lines[row - 1], width);
+        let last = starts.len() - 1;
+        (row - 1, starts, last)
+    } else {
+        return (row, col);
+    };
+
+    let target = &lines[target_row];
+    let start = target_starts[target_segment];
+    // The last char of a row that continues, or the end of the line
+    let end = match target_starts.get(target_segment + 1) {
+        Some(next) => next - 1,
+        None => target.chars().count(),
+    };
+    let mut index = start;
+    let mut used = 0;
+    for ch in target.chars().skip(start) {
+        let ch_width = UnicodeWidthChar::width(ch).unwrap_or(0);
+        if index >= end || used + ch_width > column {
+            break;
+        }
+        used += ch_width;
+        index += 1;
+    }
+    (target_row, index)
+}
+
+/// `segments` joined with ` / ` in at most `width` columns. When they don't
+/// fit, middle segments nearest the start give way to one `…` first, then
+/// the first segment, and the last is cut short only as a last resort.
+pub fn breadcrumb(segments: &[String], width: usize) -> String {
+    let Some((last, rest)) = segments.split_last() else {
+        return String::new();
+    };
+    let full = segments.join(" / ");
+    if full.width() <= width {
+        return full;
+    }
+    if let Some((first, middle)) = rest.split_first() {
+        for keep in (0..middle.len()).rev() {
+            let mut parts = vec![first.as_str(), "\u{2026}"];
+            parts.extend(middle[middle.len() - keep..].iter().map(String::as_str));
+            parts.push(last);
+            let text = parts.join(" / ");
+            if text.width() <= width {
+                return text;
+            }
+        }
+    }
+    let text = format!("\u{2026} / {}", last);
+    if text.width() <= width {
+        return text;
+    }
+    if last.width() <= width {
+        return last.clone();
+    }
+    let mut cut = String::new();
+    let mut used = 0;
+    for ch in last.chars() {
+        let ch_width = ch.width().unwrap_or(0);
+        if used + ch_width + 1 > width {
+            break;
+        }
+        cut.push(ch);
+        used += ch_width;
+    }
+    if width > 0 {
+        cut.push('\u{2026}');
+    }
+    cut
+}
+
+/// `values` as a braille sparkline two values to a character, scaled so
+/// the smallest is one dot high and the largest four. Values that are all
+/// the same draw a flat line along the bottom.
+pub fn sparkline(values: &[u64]) -> String {
+    // Dots from the bottom up, for the left and right column
+    const LEFT: [u32; 4] = [0x40, 0x04, 0x02, 0x01];
+    const RIGHT: [u32; 4] = [0x80, 0x20, 0x10, 0x08];
+    let (Some(&min), Some(&max)) = (values.iter().min(), values.iter().max()) else {
+        return String::new();
+    };
+    let height = |value: u64| match max - min {
+        0 => 1,
+        spread => 1 + ((value - min) * 3 + spread / 2) / spread,
+    };
+    let dots = |column: &[u32; 4], value: u64| -> u32 {
+        column.iter().take(height(value) as usize).sum()
+    };
+    values
+        .chunks(2)
+        .map(|pair| {
+            let mut bits = dots(&LEFT, pair[0]);
+            if let Some(&right) = pair.get(1) {
+                bits += dots(&RIGHT, right);
+            }
+            char::from_u32(0x2800 + bits).unwrap_or(' ')
+        })
+        .collect()
+}
+
+/// The `percent`th percentile of `values` by nearest rank.
+pub fn percentile(values: &[u64], percent: u32) -> Option<u64> {
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    let rank = (sorted.len() * percent as usize).div_ceil(100).max(1);
+    sorted.get(rank - 1).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Style;
+
+    fn text(spans: &[Span<'static>]) -> String {
+        spans.iter().map(|span| span.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn test_clip_spans_keeps_styles() {
+        let key = Style::default().fg(Color::Cyan);
+        let spans = vec![Span::styled("\"name\"", key), Span::raw(": \"perseus\"")];
+        let clipped = clip_spans(&spans, 3, 6, None);
+        assert_eq!(text(&clipped), "me\": \"");
+        assert_eq!(clipped[0], Span::styled("me\"", key));
+        assert!(clip_spans(&spans, 40, 10, None).is_empty());
+
+        let selected = clip_spans(&spans, 0, 4, Some((1, 3)));
+        assert_eq!(text(&selected), "\"nam");
+        assert_eq!(selected[1].style, key.bg(Color::LightBlue));
+    }
+
+    #[test]
+    fn test_clip_spans_wide_characters() {
+        // Each of these takes two columns
+        let spans = vec![Span::raw("日本語")];
+        assert_eq!(text(&clip_spans(&spans, 1, 4, None)), "本");
+        assert_eq!(text(&clip_spans(&spans, 2, 4, None)), "本語");
+        assert_eq!(width_before(&spans, 2), 4);
+    }
+
+    #[test]
+    fn test_cursor_window_centers_on_the_cursor() {
+        assert_eq!(cursor_window(10, 9, 20), (0, 10));
+        // Cut on the right only, then on both sides, then on the left only
+        assert_eq!(cursor_window(100, 3, 20), (0, 19));
+        assert_eq!(cursor_window(100, 50, 20), (41, 18));
+        assert_eq!(cursor_window(100, 99, 20), (81, 19));
+        assert_eq!(cursor_window(100, 50, 1), (50, 0));
+    }
+
+    #[test]
+    fn test_sparkline_scales_between_min_and_max() {
+        assert_eq!(sparkline(&[]), "");
+        // One point is a one-dot bar in the left column
+        assert_eq!(sparkline(&[120]), "\u{2840}");
+        assert_eq!(sparkline(&[50, 50, 50]), "\u{28c0}\u{2840}");
+        // Lowest one dot, highest four, in between rounded
+        assert_eq!(sparkline(&[100, 400]), "\u{28f8}");
+        assert_eq!(sparkline(&[400, 100, 200, 300]), "\u{28c7}\u{28f4}");
+        assert_eq!(sparkline(&[0; 30]).chars().count(), 15);
+    }
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        assert_eq!(percentile(&[], 50), None);
+        assert_eq!(percentile(&[80], 95), Some(80));
+        let durations = [300, 100, 200, 400, 1000];
+        assert_eq!(percentile(&durations, 50), Some(300));
+        assert_eq!(percentile(&durations, 95), Some(1000));
+        assert_eq!(percentile(&durations, 0), Some(100));
+    }
+
+    #[test]
+    fn test_hex_dump_full_row() {
+        let lines = hex_dump_lines(b"0123456789abcdef", usize::MAX);
+        assert_eq!(
+            lines,
+            vec![
+                "00000000  30 31 32 33 34 35 36 37  38 39 61 62 63 64 65 66  |0123456789abcdef|"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hex_dump_partial_row_and_nonprintable() {
+        let lines = hex_dump_lines(&[0x00, 0x41, 0xff], usize::MAX);
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].starts_with("00000000  00 41 ff "));
+        assert!(lines[0].ends_with("|.A.|"));
+    }
+
+    #[test]
+    fn test_hex_dump_limit_adds_note() {
+        let data = vec![0u8; 40];
+        let lines = hex_dump_lines(&data, 32);
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].starts_with("00000010"));
+        assert!(lines[2].contains("8 more bytes"));
+    }
+
+    #[test]
+    fn test_display_line_move_steps_through_wrapped_rows() {
+        let lines = vec!["abcdefghij".to_string(), "xy".to_string()];
+        // Rows of 4: "abcd", "efgh", "ij", then "xy"
+        assert_eq!(display_line_move(&lines, (0, 1), 4, true), (0, 5));
+        assert_eq!(display_line_move(&lines, (0, 5), 4, true), (0, 9));
+        assert_eq!(display_line_move(&lines, (0, 9), 4, true), (1, 1));
+        assert_eq!(display_line_move(&lines, (1, 1), 4, true), (1, 1));
+        assert_eq!(display_line_move(&lines, (1, 1), 4, false), (0, 9));
+        assert_eq!(display_line_move(&lines, (0, 3), 4, true), (0, 7));
+        // A short row takes the cursor to its end
+        assert_eq!(display_line_move(&lines, (0, 7), 4, true), (0, 10));
+        assert_eq!(display_line_move(&lines, (0, 2), 4, false), (0, 2));
+
+        // Wide characters count by display column
+        let wide = vec!["日本語です".to_string()];
+        assert_eq!(display_line_move(&wide, (0, 1), 4, true), (0, 3));
+    }
+
+    #[test]
+    fn test_breadcrumb_drops_middle_segments_first() {
+        let path: Vec<String> = ["Shop", "Admin", "Users", "Get user"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(breadcrumb(&path, 40), "Shop / Admin / Users / Get user");
+        assert_eq!(breadcrumb(&path, 30), "Shop / \u{2026} / Users / Get user");
+        assert_eq!(breadcrumb(&path, 22), "Shop / \u{2026} / Get user");
+        assert_eq!(breadcrumb(&path, 14), "\u{2026} / Get user");
+        assert_eq!(breadcrumb(&path, 5), "Get \u{2026}");
+        assert_eq!(breadcrumb(&path, 0), "");
+        assert_eq!(breadcrumb(&[], 10), "");
+
+        // Widths count display columns
+        let wide = vec!["日本".to_string(), "語".to_string()];
+        assert_eq!(breadcrumb(&wide, 9), "日本 / 語");
+        assert_eq!(breadcrumb(&wide, 3), "語");
+    }
+}
@@ -0,0 +1,211 @@
+//! Text transforms offered by the codec popup (`Ctrl+U`). All of them are
+//! pure and report bad input as an error message instead of panicking.
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transform {
+    Base64Encode,
+    Base64Decode,
+    UrlEncode,
+    UrlDecode,
+    JwtDecode,
+}
+
+impl Transform {
+    pub const ALL: [Transform; 5] = [
+        Transform::Base64Encode,
+        Transform::Base64Decode,
+        Transform::UrlEncode,
+        Transform::UrlDecode,
+        Transform::JwtDecode,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Transform::Base64Encode => "Base64 encode",
+            Transform::Base64Decode => "Base64 decode",
+            Transform::UrlEncode => "URL encode",
+            Transform::UrlDecode => "URL decode",
+            Transform::JwtDecode => "JWT decode",
+        }
+    }
+
+    pub fn apply(&self, input: &str) -> Result<String, String> {
+        match self {
+            Transform::Base64Encode => Ok(base64_encode(input.as_bytes())),
+            Transform::Base64Decode => String::from_utf8(base64_decode(input)?)
+                .map_err(|_| "decoded bytes are not UTF-8 text".to_string()),
+            Transform::UrlEncode => Ok(url_encode(input)),
+            Transform::UrlDecode => url_decode(input),
+            Transform::JwtDecode => jwt_decode(input),
+        }
+    }
+
+    /// Whether the result can stand in for the input in a request field;
+    /// a decoded JWT is a report, not a value.
+    pub fn replaces_input(&self) -> bool {
+        !matches!(self, Transform::JwtDecode)
+    }
+}
+
+pub fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Decodes standard or URL-safe base64, with or without padding. Whitespace
+/// is ignored.
+pub fn base64_decode(input: &str) -> Result<Vec<u8>, String> {
+    let mut bits: u32 = 0;
+    let mut nbits = 0;
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    let mut padding = 0;
+    for (i, c) in input.chars().filter(|c| !c.is_whitespace()).enumerate() {
+        if c == '=' {
+            padding += 1;
+            continue;
+        }
+        if padding > 0 {
+            return Err("base64 padding in the middle of the input".to_string());
+        }
+        let value = match c {
+            'A'..='Z' => c as u32 - 'A' as u32,
+            'a'..='z' => c as u32 - 'a' as u32 + 26,
+            '0'..='9' => c as u32 - '0' as u32 + 52,
+            '+' | '-' => 62,
+            '/' | '_' => 63,
+            _ => return Err(format!("invalid base64 character {:?} at {}", c, i)),
+        };
+        bits = bits << 6 | value;
+        nbits += 6;
+        if nbits >= 8 {
+            nbits -= 8;
+            out.push((bits >> nbits) as u8);
+            bits &= (1 << nbits) - 1;
+        }
+    }
+    // A lone trailing character carries fewer than 8 bits
+    if nbits >= 6 || padding > 2 {
+        return Err("base64 input has the wrong length".to_string());
+    }
+    Ok(out)
+}
+
+/// Percent-encodes everything except RFC 3986 unreserved characters.
+pub fn url_encode(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    out
+}
+
+/// Decodes `%XX` escapes; `+` is left as is.
+pub fn url_decode(input: &str) -> Result<String, String> {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = input
+                .get(i + 1..i + 3)
+                .and_then(|h| u8::from_str_radix(h, 16).ok())
+                .ok_or_else(|| format!("invalid percent escape at {}", i))?;
+            out.push(hex);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).map_err(|_| "decoded bytes are not UTF-8 text".to_string())
+}
+
+/// Pretty-prints a JWT's header and payload. The signature is not checked.
+pub fn jwt_decode(token: &str) -> Result<String, String> {
+    let token = token.trim();
+    let token = token.strip_prefix("Bearer ").unwrap_or(token).trim();
+    let parts: Vec<&str> = token.split('.').collect();
+    if parts.len() != 3 {
+        return Err(format!("a JWT has 3 dot-separated parts, found {}", parts.len()));
+    }
+    let section = |name: &str, part: &str| -> Result<String, String> {
+        let bytes = base64_decode(part).map_err(|e| format!("{}: {}", name, e))?;
+        let value: serde_json::Value = serde_json::from_slice(&bytes)
+            .map_err(|_| format!("{} is not JSON", name.to_lowercase()))?;
+        Ok(serde_json::to_string_pretty(&value).unwrap_or_default())
+    };
+    Ok(format!(
+        "Header\n{}\n\nPayload\n{}\n\nSignature not verified.",
+        section("Header", parts[0])?,
+        section("Payload", parts[1])?
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_round_trip() {
+        for (plain, encoded) in [("", ""), ("f", "Zg=="), ("fo", "Zm8="), ("foo", "Zm9v")] {
+            assert_eq!(base64_encode(plain.as_bytes()), encoded);
+            assert_eq!(base64_decode(encoded).unwrap(), plain.as_bytes());
+        }
+        assert_eq!(base64_decode("Zm8").unwrap(), b"fo");
+        assert_eq!(base64_decode("-_8").unwrap(), vec![0xfb, 0xff]);
+        assert_eq!(base64_decode("Zm9v\nYmFy").unwrap(), b"foobar");
+    }
+
+    #[test]
+    fn test_base64_decode_rejects_bad_input() {
+        assert!(base64_decode("Zm9v!").unwrap_err().contains("invalid base64 character"));
+        assert!(base64_decode("Z").is_err());
+        assert!(base64_decode("Zg==Zg").is_err());
+        assert_eq!(
+            Transform::Base64Decode.apply("//8=").unwrap_err(),
+            "decoded bytes are not UTF-8 text"
+        );
+    }
+
+    #[test]
+    fn test_url_encode_and_decode() {
+        assert_eq!(url_encode("a b&c=d/é~"), "a%20b%26c%3Dd%2F%C3%A9~");
+        assert_eq!(url_decode("a%20b%26c%3Dd%2F%C3%A9~").unwrap(), "a b&c=d/é~");
+        assert_eq!(url_decode("a+b").unwrap(), "a+b");
+        assert!(url_decode("100%").is_err());
+        assert!(url_decode("%zz").is_err());
+        assert!(url_decode("%FF").is_err());
+    }
+
+    #[test]
+    fn test_jwt_decode() {
+        let header = base64_encode(br#"{"alg":"HS256","typ":"JWT"}"#);
+        let payload = base64_encode(br#"{"sub":"42"}"#);
+        let token = format!("Bearer {}.{}.sig", header.trim_end_matches('='), payload);
+        let decoded = jwt_decode(&token).unwrap();
+        assert!(decoded.starts_with("Header\n{\n  \"alg\": \"HS256\""));
+        assert!(decoded.contains("Payload\n{\n  \"sub\": \"42\"\n}"));
+        assert!(decoded.ends_with("Signature not verified."));
+
+        assert!(jwt_decode("abc").unwrap_err().contains("3 dot-separated parts"));
+        assert_eq!(jwt_decode("e30.bm9wZQ.x").unwrap_err(), "payload is not JSON");
+    }
+}
@@ -0,0 +1,96 @@
+//! Round-tripping text through the user's own editor (`Ctrl+G`). The caller
+//! suspends the TUI around [`edit_text`] and [`edit_file`]; this module only
+//! deals with the temp file and the child process.
+
+use std::path::Path;
+use std::process::Command;
+
+/// Used when neither `$VISUAL` nor `$EDITOR` is set.
+const FALLBACK_EDITOR: &str = "vi";
+
+/// The editor program and its arguments: `$VISUAL`, then `$EDITOR`, then
+/// `vi`. Values like `code --wait` are split on whitespace.
+pub fn editor_command(visual: Option<&str>, editor: Option<&str>) -> Vec<String> {
+    [visual, editor]
+        .into_iter()
+        .flatten()
+        .map(|value| value.split_whitespace().map(str::to_string).collect::<Vec<_>>())
+        .find(|parts| !parts.is_empty())
+        .unwrap_or_else(|| vec![FALLBACK_EDITOR.to_string()])
+}
+
+/// Writes `text` to a temp file named with `extension`, runs the editor on
+/// it and waits, then returns the saved contents. A trailing newline the
+/// editor added is dropped again. Fails, leaving `text` as the caller's to
+/// keep, if the editor can't start or exits unsuccessfully.
+pub fn edit_text(text: &str, extension: &str) -> Result<String, String> {
+    let path = std::env::temp_dir().join(format!(
+        "perseus-{}.{}",
+        uuid::Uuid::new_v4().simple(),
+        extension
+    ));
+    std::fs::write(&path, text).map_err(|e| format!("Could not write temp file: {}", e))?;
+    let result = run_editor(&path, text);
+    let _ = std::fs::remove_file(&path);
+    result
+}
+
+/// Runs the editor on a file in place and waits.
+pub fn edit_file(path: &Path) -> Result<(), String> {
+    spawn_editor(path, "")
+}
+
+fn run_editor(path: &Path, original: &str) -> Result<String, String> {
+    spawn_editor(path, "; changes discarded")?;
+    let edited = std::fs::read_to_string(path)
+        .map_err(|e| format!("Could not read the edited file: {}", e))?;
+    Ok(strip_added_newline(original, edited))
+}
+
+/// Runs the editor on `path`; `on_failure` is added to the error when it
+/// exits unsuccessfully.
+fn spawn_editor(path: &Path, on_failure: &str) -> Result<(), String> {
+    let visual = std::env::var("VISUAL").ok();
+    let editor = std::env::var("EDITOR").ok();
+    let command = editor_command(visual.as_deref(), editor.as_deref());
+    let status = Command::new(&command[0])
+        .args(&command[1..])
+        .arg(path)
+        .status()
+        .map_err(|e| format!("Could not start {}: {}", command[0], e))?;
+    if !status.success() {
+        return Err(format!("{} exited with {}{}", command[0], status, on_failure));
+    }
+    Ok(())
+}
+
+fn strip_added_newline(original: &str, mut edited: String) -> String {
+    if !original.ends_with('\n') && edited.ends_with('\n') {
+        edited.pop();
+        if edited.ends_with('\r') {
+            edited.pop();
+        }
+    }
+    edited
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_editor_command_precedence() {
+        assert_eq!(editor_command(Some("nvim"), Some("nano")), vec!["nvim"]);
+        assert_eq!(editor_command(None, Some("code --wait")), vec!["code", "--wait"]);
+        assert_eq!(editor_command(Some("  "), Some("nano")), vec!["nano"]);
+        assert_eq!(editor_command(None, None), vec!["vi"]);
+    }
+
+    #[test]
+    fn test_strip_added_newline() {
+        assert_eq!(strip_added_newline("a", "a\n".to_string()), "a");
+        assert_eq!(strip_added_newline("a", "b\r\n".to_string()), "b");
+        assert_eq!(strip_added_newline("a\n", "a\n".to_string()), "a\n");
+        assert_eq!(strip_added_newline("a", "a\n\n".to_string()), "a\n");
+    }
+}
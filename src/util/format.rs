@@ -0,0 +1,394 @@
+//! Pretty-printing JSON in the `[json]` style: responses as they are
+//! shown, and a request body re-indented with `F`. The body is laid out
+//! from its text rather than a parsed value, so key order, number spelling
+//! and `{{variable}}` placeholders in value position come through
+//! unchanged; only sorting keys or escaping non-ASCII parses it.
+
+use std::fmt;
+use std::io;
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+use serde_json::ser::{Formatter, PrettyFormatter};
+
+/// How JSON is laid out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonStyle {
+    /// One level of indentation: spaces or a tab.
+    pub indent: String,
+    /// Object keys in sorted order rather than as received.
+    pub sort_keys: bool,
+    /// Characters outside ASCII written as `\uXXXX` escapes.
+    pub ascii_escape: bool,
+}
+
+impl Default for JsonStyle {
+    fn default() -> Self {
+        Self {
+            indent: "  ".to_string(),
+            sort_keys: false,
+            ascii_escape: false,
+        }
+    }
+}
+
+/// A parsed JSON value that keeps its object members in document order,
+/// duplicates included.
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Null,
+    Bool(bool),
+    Number(serde_json::Number),
+    String(String),
+    Array(Vec<Node>),
+    Object(Vec<(String, Node)>),
+}
+
+impl Node {
+    fn sort_keys(&mut self) {
+        match self {
+            Node::Array(items) => items.iter_mut().for_each(Node::sort_keys),
+            Node::Object(members) => {
+                // Stable, so duplicate keys keep their order
+                members.sort_by(|(a, _), (b, _)| a.cmp(b));
+                members.iter_mut().for_each(|(_, value)| value.sort_keys());
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Node {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(NodeVisitor)
+    }
+}
+
+struct NodeVisitor;
+
+impl<'de> Visitor<'de> for NodeVisitor {
+    type Value = Node;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a JSON value")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Node, E> {
+        Ok(Node::Null)
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<Node, E> {
+        Ok(Node::Bool(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Node, E> {
+        Ok(Node::Number(value.into()))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Node, E> {
+        Ok(Node::Number(value.into()))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Node, E> {
+        Ok(serde_json::Number::from_f64(value).map_or(Node::Null, Node::Number))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Node, E> {
+        Ok(Node::String(value.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, value: String) -> Result<Node, E> {
+        Ok(Node::String(value))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Node, A::Error> {
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(Node::Array(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Node, A::Error> {
+        let mut members = Vec::new();
+        while let Some(member) = map.next_entry()? {
+            members.push(member);
+        }
+        Ok(Node::Object(members))
+    }
+}
+
+impl Serialize for Node {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Node::Null => serializer.serialize_unit(),
+            Node::Bool(value) => serializer.serialize_bool(*value),
+            Node::Number(number) => number.serialize(serializer),
+            Node::String(text) => serializer.serialize_str(text),
+            Node::Array(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for item in items {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            }
+            Node::Object(members) => {
+                let mut map = serializer.serialize_map(Some(members.len()))?;
+                for (key, value) in members {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+/// serde_json's pretty printer, escaping non-ASCII when asked to.
+struct StyledFormatter<'a> {
+    pretty: PrettyFormatter<'a>,
+    ascii_escape: bool,
+}
+
+impl Formatter for StyledFormatter<'_> {
+    fn begin_array<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.pretty.begin_array(writer)
+    }
+
+    fn end_array<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.pretty.end_array(writer)
+    }
+
+    fn begin_array_value<W: ?Sized + io::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        self.pretty.begin_array_value(writer, first)
+    }
+
+    fn end_array_value<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.pretty.end_array_value(writer)
+    }
+
+    fn begin_object<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.pretty.begin_object(writer)
+    }
+
+    fn end_object<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.pretty.end_object(writer)
+    }
+
+    fn begin_object_key<W: ?Sized + io::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        self.pretty.begin_object_key(writer, first)
+    }
+
+    fn begin_object_value<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.pretty.begin_object_value(writer)
+    }
+
+    fn end_object_value<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.pretty.end_object_value(writer)
+    }
+
+    fn write_string_fragment<W: ?Sized + io::Write>(
+        &mut self,
+        writer: &mut W,
+        fragment: &str,
+    ) -> io::Result<()> {
+        if !self.ascii_escape || fragment.is_ascii() {
+            return writer.write_all(fragment.as_bytes());
+        }
+        let mut units = [0u16; 2];
+        for c in fragment.chars() {
+            if c.is_ascii() {
+                writer.write_all(&[c as u8])?;
+                continue;
+            }
+            for unit in c.encode_utf16(&mut units) {
+                write!(writer, "\\u{:04x}", unit)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `text` pretty-printed in `style`, or `None` when it isn't JSON.
+pub fn pretty_json(text: &str, style: &JsonStyle) -> Option<String> {
+    let mut node: Node = serde_json::from_str(text).ok()?;
+    if style.sort_keys {
+        node.sort_keys();
+    }
+    let formatter = StyledFormatter {
+        pretty: PrettyFormatter::with_indent(style.indent.as_bytes()),
+        ascii_escape: style.ascii_escape,
+    };
+    let mut out = Vec::with_capacity(text.len() * 2);
+    let mut serializer = serde_json::Serializer::with_formatter(&mut out, formatter);
+    node.serialize(&mut serializer).ok()?;
+    String::from_utf8(out).ok()
+}
+
+/// A request body laid out one member per line with `style`'s indent.
+/// Sorting keys or escaping non-ASCII needs the body to be plain JSON;
+/// one with placeholders is re-indented and left as it is otherwise. Fails
+/// on unbalanced brackets or an unterminated string, leaving the caller's
+/// text alone.
+pub fn format_json(text: &str, style: &JsonStyle) -> Result<String, String> {
+    if style.sort_keys || style.ascii_escape {
+        if let Some(formatted) = pretty_json(text, style) {
+            return Ok(formatted);
+        }
+    }
+    reindent_json(text, &style.indent)
+}
+
+/// `text` laid out one member per line, indented with `indent`.
+fn reindent_json(text: &str, indent: &str) -> Result<String, String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len() * 2);
+    let mut stack: Vec<char> = Vec::new();
+    let mut i = 0;
+    let newline = |out: &mut String, depth: usize| {
+        out.push('\n');
+        for _ in 0..depth {
+            out.push_str(indent);
+        }
+    };
+    while i < chars.len() {
+        let ch = chars[i];
+        match ch {
+            '"' => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && chars[i] != '"' {
+                    i += if chars[i] == '\\' { 2 } else { 1 };
+                }
+                if i >= chars.len() {
+                    return Err("Body has an unterminated string".to_string());
+                }
+                out.extend(&chars[start..=i]);
+            }
+            // A placeholder standing for a whole value
+            '{' if chars.get(i + 1) == Some(&'{') => {
+                let Some(len) = chars[i..].windows(2).position(|pair| pair == ['}', '}']) else {
+                    return Err("Body has an unclosed {{ placeholder".to_string());
+                };
+                out.extend(&chars[i..i + len + 2]);
+                i += len + 1;
+            }
+            '{' | '[' => {
+                out.push(ch);
+                let close = if ch == '{' { '}' } else { ']' };
+                let next = chars[i + 1..].iter().position(|c| !c.is_whitespace());
+                if next.is_some_and(|offset| chars[i + 1 + offset] == close) {
+                    // Empty brackets stay on one line
+                    out.push(close);
+                    i += 1 + next.unwrap_or(0);
+                } else {
+                    stack.push(close);
+                    newline(&mut out, stack.len());
+                }
+            }
+            '}' | ']' => {
+                if stack.pop() != Some(ch) {
+                    return Err(format!("Body has an unexpected '{}'", ch));
+                }
+                newline(&mut out, stack.len());
+                out.push(ch);
+            }
+            ',' => {
+                out.push(',');
+                newline(&mut out, stack.len());
+            }
+            ':' => out.push_str(": "),
+            c if c.is_whitespace() => {}
+            c => out.push(c),
+        }
+        i += 1;
+    }
+    if let Some(close) = stack.last() {
+        return Err(format!("Body is missing a closing '{}'", close));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_json_keeps_order_and_placeholders() {
+        let text =
+            r#"{"zeta":1,"alpha":[1.50, {"s":"a, b: {c}"}],"id":{{userId}},"e":{}, "l":[ ]}"#;
+        assert_eq!(
+            format_json(text, &JsonStyle::default()).unwrap(),
+            r#"{
+  "zeta": 1,
+  "alpha": [
+    1.50,
+    {
+      "s": "a, b: {c}"
+    }
+  ],
+  "id": {{userId}},
+  "e": {},
+  "l": []
+}"#
+        );
+        assert_eq!(
+            format_json(r#"["a\"]", 2]"#, &JsonStyle::default()).unwrap(),
+            "[\n  \"a\\\"]\",\n  2\n]"
+        );
+    }
+
+    #[test]
+    fn test_format_json_refuses_broken_text() {
+        assert!(format_json(r#"{"a": [1}"#, &JsonStyle::default()).is_err());
+        assert!(format_json(r#"{"a": "open}"#, &JsonStyle::default()).is_err());
+        assert!(format_json(r#"{"a": 1"#, &JsonStyle::default())
+            .unwrap_err()
+            .contains("closing '}'"));
+    }
+
+    #[test]
+    fn test_pretty_json_applies_style() {
+        let text = r#"{"b":{"z":1,"y":[true,null]},"a":"caf\u00e9 😀"}"#;
+        assert_eq!(
+            pretty_json(text, &JsonStyle::default()).unwrap(),
+            "{\n  \"b\": {\n    \"z\": 1,\n    \"y\": [\n      true,\n      null\n    ]\n  },\
+             \n  \"a\": \"café 😀\"\n}"
+        );
+        let style = JsonStyle {
+            indent: "\t".to_string(),
+            sort_keys: true,
+            ascii_escape: true,
+        };
+        assert_eq!(
+            pretty_json(text, &style).unwrap(),
+            "{\n\t\"a\": \"caf\\u00e9 \\ud83d\\ude00\",\n\t\"b\": {\n\t\t\"y\": [\n\t\t\ttrue,\
+             \n\t\t\tnull\n\t\t],\n\t\t\"z\": 1\n\t}\n}"
+        );
+        assert!(pretty_json("{{userId}}", &style).is_none());
+    }
+
+    #[test]
+    fn test_format_json_sorts_only_plain_json() {
+        let style = JsonStyle {
+            indent: "    ".to_string(),
+            sort_keys: true,
+            ascii_escape: false,
+        };
+        assert_eq!(
+            format_json(r#"{"b":1,"a":2}"#, &style).unwrap(),
+            "{\n    \"a\": 2,\n    \"b\": 1\n}"
+        );
+        assert_eq!(
+            format_json(r#"{"b":{{x}},"a":2}"#, &style).unwrap(),
+            "{\n    \"b\": {{x}},\n    \"a\": 2\n}"
+        );
+    }
+}
@@ -0,0 +1,4 @@
+pub mod codec;
+pub mod editor;
+pub mod format;
+pub mod pager;
@@ -0,0 +1,155 @@
+//! Handing a body to `$PAGER` (`|`) or through a shell pipeline (`!`). The
+//! caller suspends the TUI around [`page`]; a pipeline has all three of its
+//! streams captured, so it runs with the TUI still up.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Used when `$PAGER` is unset or blank.
+const FALLBACK_PAGER: &str = "less -R";
+
+/// How much of a pipeline's output is kept for the popup.
+pub const MAX_OUTPUT: usize = 64 * 1024;
+
+/// The pager program and its arguments: `$PAGER`, split on whitespace, or
+/// `less -R`.
+pub fn pager_command(pager: Option<&str>) -> Vec<String> {
+    let parts: Vec<String> =
+        pager.unwrap_or_default().split_whitespace().map(str::to_string).collect();
+    if parts.is_empty() {
+        FALLBACK_PAGER.split_whitespace().map(str::to_string).collect()
+    } else {
+        parts
+    }
+}
+
+/// Writes `input` to the pager and waits for it to exit. Quitting the
+/// pager before it has read everything is not an error.
+pub fn page(input: &[u8]) -> Result<(), String> {
+    let pager = std::env::var("PAGER").ok();
+    let command = pager_command(pager.as_deref());
+    let mut child = Command::new(&command[0])
+        .args(&command[1..])
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Could not start {}: {}", command[0], e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(input);
+    }
+    let status = child.wait().map_err(|e| format!("{} failed: {}", command[0], e))?;
+    if !status.success() {
+        return Err(format!("{} exited with {}", command[0], status));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PipeOutput {
+    pub text: String,
+    /// Output past [`MAX_OUTPUT`] was dropped.
+    pub truncated: bool,
+}
+
+/// Runs `command` in the shell with `input` on its stdin and returns what
+/// it printed. A command that exits unsuccessfully fails with its stderr.
+pub fn pipe_through(command: &str, input: &[u8]) -> Result<PipeOutput, String> {
+    let mut child = shell(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Could not start `{}`: {}", command, e))?;
+    // Written from another thread so a command that prints before reading
+    // everything can't deadlock against us
+    let stdin = child.stdin.take();
+    let input = input.to_vec();
+    let writer = std::thread::spawn(move || {
+        if let Some(mut stdin) = stdin {
+            let _ = stdin.write_all(&input);
+        }
+    });
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("`{}` failed: {}", command, e))?;
+    let _ = writer.join();
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(match stderr_summary(&stderr) {
+            Some(summary) => format!("`{}` failed: {}", command, summary),
+            None => format!("`{}` exited with {}", command, output.status),
+        });
+    }
+    Ok(truncate_output(&output.stdout))
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+/// The first [`MAX_OUTPUT`] bytes as text, cut before any character that
+/// doesn't fit whole.
+fn truncate_output(bytes: &[u8]) -> PipeOutput {
+    if bytes.len() <= MAX_OUTPUT {
+        return PipeOutput {
+            text: String::from_utf8_lossy(bytes).into_owned(),
+            truncated: false,
+        };
+    }
+    let mut end = MAX_OUTPUT;
+    while end > 0 && bytes[end] & 0xC0 == 0x80 {
+        end -= 1;
+    }
+    PipeOutput {
+        text: String::from_utf8_lossy(&bytes[..end]).into_owned(),
+        truncated: true,
+    }
+}
+
+/// Stderr on one line, for the notification log.
+fn stderr_summary(stderr: &str) -> Option<String> {
+    let lines: Vec<&str> = stderr.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+    (!lines.is_empty()).then(|| lines.join("; "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pager_command() {
+        assert_eq!(pager_command(Some("most")), vec!["most"]);
+        assert_eq!(pager_command(Some("bat --paging always")), vec!["bat", "--paging", "always"]);
+        assert_eq!(pager_command(Some(" ")), vec!["less", "-R"]);
+        assert_eq!(pager_command(None), vec!["less", "-R"]);
+    }
+
+    #[test]
+    fn test_truncate_output_on_char_boundary() {
+        let mut bytes = vec![b'a'; MAX_OUTPUT - 1];
+        bytes.extend("é and more".as_bytes());
+        let output = truncate_output(&bytes);
+        assert!(output.truncated);
+        assert_eq!(output.text.len(), MAX_OUTPUT - 1);
+        assert!(!truncate_output(b"short").truncated);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_pipe_through() {
+        let output = pipe_through("tr a-z A-Z", b"hello").unwrap();
+        assert_eq!(output.text, "HELLO");
+        let err = pipe_through("echo first >&2; echo second >&2; exit 3", b"").unwrap_err();
+        assert_eq!(err, "`echo first >&2; echo second >&2; exit 3` failed: first; second");
+        assert!(pipe_through("exit 1", b"").unwrap_err().contains("exited with"));
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::VecDeque;
 use std::fmt;
 
 use tui_textarea::{CursorMove, Input, Key, TextArea};
@@ -26,11 +27,68 @@ pub enum Transition {
     Mode(VimMode),
     Pending(Input),
     ExitField,
+    /// Tab in a snippet: on to its next stop.
+    NextStop,
+}
+
+/// The stops of a snippet still to visit, as `(row, col)` in chars. They
+/// last while insert mode does, and move with the text as it is edited.
+#[derive(Debug, Clone, Default)]
+struct TabStops {
+    stops: VecDeque<(usize, usize)>,
+    /// Cursor, line count and char count after the last key, telling
+    /// where the next edit happened.
+    seen: ((usize, usize), usize, usize),
+}
+
+impl TabStops {
+    fn observe(textarea: &TextArea<'_>) -> ((usize, usize), usize, usize) {
+        let lines = textarea.lines();
+        let chars = lines.iter().map(|line| line.chars().count()).sum();
+        (textarea.cursor(), lines.len(), chars)
+    }
+
+    /// Moves the stops after an edit along with the text around them: the
+    /// text after the cursor stays with the cursor.
+    fn follow_edit(&mut self, textarea: &TextArea<'_>) {
+        let now = Self::observe(textarea);
+        let ((row, col), lines, chars) = self.seen;
+        if (now.1, now.2) != (lines, chars) {
+            let (new_row, new_col) = now.0;
+            for stop in self.stops.iter_mut() {
+                if stop.0 > row {
+                    stop.0 = (stop.0 + now.1).saturating_sub(lines);
+                } else if stop.0 == row && stop.1 >= col {
+                    *stop = (new_row, stop.1 - col + new_col);
+                }
+            }
+        }
+        self.seen = now;
+    }
+
+    /// Moves the cursor to the next stop. The snippet is done once the
+    /// cursor reaches the last one.
+    fn jump(mut self, textarea: &mut TextArea<'_>) -> Option<Self> {
+        let (row, col) = self.stops.pop_front()?;
+        textarea.move_cursor(CursorMove::Jump(
+            row.min(u16::MAX as usize) as u16,
+            col.min(u16::MAX as usize) as u16,
+        ));
+        self.seen = Self::observe(textarea);
+        (!self.stops.is_empty()).then_some(self)
+    }
 }
 
 pub struct Vim {
     pub mode: VimMode,
     pending: Input,
+    snippet: Option<TabStops>,
+}
+
+impl Default for Vim {
+    fn default() -> Self {
+        Self::new(VimMode::Normal)
+    }
 }
 
 impl Vim {
@@ -38,13 +96,63 @@ impl Vim {
         Self {
             mode,
             pending: Input::default(),
+            snippet: None,
         }
     }
 
+    /// Whether Tab moves on to a snippet's next stop.
+    pub fn in_snippet(&self) -> bool {
+        self.snippet.is_some()
+    }
+
+    /// Inserts `text` at the cursor, with `stops` as [`crate::snippets::expand`]
+    /// gives them, relative to the start of `text`. Lines after the first
+    /// take the current line's indent. The cursor goes to the first stop,
+    /// or after the text when it has none.
+    pub fn insert_snippet(
+        &mut self,
+        textarea: &mut TextArea<'_>,
+        text: &str,
+        stops: &[(usize, usize)],
+    ) {
+        let (row, col) = textarea.cursor();
+        let indent: String = textarea.lines()[row]
+            .chars()
+            .take_while(|c| c.is_whitespace())
+            .collect();
+        let indent_width = indent.chars().count();
+        let indented = text.replace('\n', &format!("\n{}", indent));
+        textarea.insert_str(&indented);
+        let tab_stops = TabStops {
+            stops: stops
+                .iter()
+                .map(|&(r, c)| match r {
+                    0 => (row, col + c),
+                    r => (row + r, indent_width + c),
+                })
+                .collect(),
+            seen: TabStops::observe(textarea),
+        };
+        self.snippet = tab_stops.jump(textarea);
+    }
+
+    /// A lone `g` is waiting for its second key, as in `gg` or `gj`.
+    pub fn pending_g(&self) -> bool {
+        matches!(
+            self.pending,
+            Input {
+                key: Key::Char('g'),
+                ctrl: false,
+                ..
+            }
+        )
+    }
+
     fn with_pending(self, pending: Input) -> Self {
         Self {
             mode: self.mode,
             pending,
+            snippet: None,
         }
     }
 
@@ -95,6 +203,7 @@ impl Vim {
             Input {
                 key: Key::Enter, ..
             } if single_line => Transition::Nop,
+            Input { key: Key::Tab, .. } if self.in_snippet() => Transition::NextStop,
             input => {
                 textarea.input(input);
                 Transition::Mode(VimMode::Insert)
@@ -700,7 +809,20 @@ impl Vim {
                         }
                     }
                 }
-                Vim::new(new_mode)
+                // Leaving insert mode ends a snippet; the stops are dropped
+                let mut vim = Vim::new(new_mode);
+                if new_mode == VimMode::Insert {
+                    vim.snippet = self.snippet.map(|mut stops| {
+                        stops.follow_edit(textarea);
+                        stops
+                    });
+                }
+                vim
+            }
+            Transition::NextStop => {
+                let mut vim = Vim::new(self.mode);
+                vim.snippet = self.snippet.and_then(|stops| stops.jump(textarea));
+                vim
             }
             Transition::Pending(input) => self.with_pending(input),
             Transition::Nop | Transition::ExitField => Vim::new(self.mode),
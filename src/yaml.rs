@@ -0,0 +1,613 @@
+//! A YAML reader for the subset API descriptions are written in: block
+//! mappings and sequences, plain and quoted scalars, `|`/`>` block scalars
+//! and one-line flow collections. Anchors, aliases and tags are refused.
+//! Documents are read into a JSON `Value`; mapping keys are always strings.
+
+use serde_json::{Map, Number, Value};
+
+/// Parses the first document in `text`.
+pub fn parse(text: &str) -> Result<Value, String> {
+    let mut parser = Parser {
+        lines: text
+            .lines()
+            .enumerate()
+            .map(|(i, raw)| Line {
+                number: i + 1,
+                indent: raw.len() - raw.trim_start_matches(' ').len(),
+                raw,
+            })
+            .collect(),
+        pos: 0,
+    };
+    parser.skip_blank();
+    if parser.peek().is_some_and(|line| line.content() == "---") {
+        parser.pos += 1;
+    }
+    let value = parser.parse_node(0)?;
+    parser.skip_blank();
+    match parser.peek() {
+        Some(line) if !matches!(line.content(), "---" | "...") => {
+            Err(line.error("unexpected indentation"))
+        }
+        _ => Ok(value),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Line<'a> {
+    number: usize,
+    indent: usize,
+    raw: &'a str,
+}
+
+impl<'a> Line<'a> {
+    /// The line without indentation, comment or trailing space.
+    fn content(&self) -> &'a str {
+        strip_comment(&self.raw[self.indent..]).trim_end()
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("YAML line {}: {}", self.number, message)
+    }
+}
+
+struct Parser<'a> {
+    lines: Vec<Line<'a>>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<Line<'a>> {
+        self.lines.get(self.pos).copied()
+    }
+
+    fn skip_blank(&mut self) {
+        while self.peek().is_some_and(|line| line.content().is_empty()) {
+            self.pos += 1;
+        }
+    }
+
+    /// The block starting at the next line, if it is indented at least
+    /// `min_indent`.
+    fn parse_node(&mut self, min_indent: usize) -> Result<Value, String> {
+        self.skip_blank();
+        let Some(line) = self.peek().filter(|line| line.indent >= min_indent) else {
+            return Ok(Value::Null);
+        };
+        let content = line.content();
+        if is_sequence_item(content) {
+            self.parse_sequence(line.indent)
+        } else if key_separator(content).is_some() {
+            self.parse_mapping(line.indent, None)
+        } else {
+            self.pos += 1;
+            self.parse_value(content, line, line.indent.saturating_sub(1))
+        }
+    }
+
+    /// Entries at exactly `indent`. `first` is an entry already taken off
+    /// its line, as after the `- ` of a sequence item.
+    fn parse_mapping(
+        &mut self,
+        indent: usize,
+        first: Option<(&'a str, Line<'a>)>,
+    ) -> Result<Value, String> {
+        let mut map = Map::new();
+        if let Some((content, line)) = first {
+            self.parse_entry(&mut map, content, line, indent)?;
+        }
+        loop {
+            self.skip_blank();
+            let Some(line) = self.peek() else {
+                break;
+            };
+            if line.indent < indent {
+                break;
+            }
+            if line.indent > indent {
+                return Err(line.error("unexpected indentation"));
+            }
+            let content = line.content();
+            if is_sequence_item(content) || matches!(content, "---" | "...") {
+                break;
+            }
+            self.pos += 1;
+            self.parse_entry(&mut map, content, line, indent)?;
+        }
+        Ok(Value::Object(map))
+    }
+
+    fn parse_entry(
+        &mut self,
+        map: &mut Map<String, Value>,
+        content: &'a str,
+        line: Line<'a>,
+        indent: usize,
+    ) -> Result<(), String> {
+        let sep = key_separator(content).ok_or_else(|| line.error("expected \"key: value\""))?;
+        let key = match parse_scalar(content[..sep].trim(), line)? {
+            Value::String(key) => key,
+            Value::Null => String::new(),
+            other => other.to_string(),
+        };
+        let value = self.parse_value(content[sep + 1..].trim(), line, indent)?;
+        map.insert(key, value);
+        Ok(())
+    }
+
+    fn parse_sequence(&mut self, indent: usize) -> Result<Value, String> {
+        let mut items = Vec::new();
+        loop {
+            self.skip_blank();
+            let Some(line) = self.peek().filter(|line| line.indent == indent) else {
+                break;
+            };
+            let content = line.content();
+            if !is_sequence_item(content) {
+                break;
+            }
+            self.pos += 1;
+            let after = content[1..].trim_start();
+            let column = indent + content.len() - after.len();
+            let item = if after.is_empty() {
+                self.parse_value("", line, indent)?
+            } else if is_sequence_item(after) {
+                return Err(line.error("nested \"- - \" sequences aren't supported"));
+            } else if !starts_flow_or_quote(after) && key_separator(after).is_some() {
+                self.parse_mapping(column, Some((after, line)))?
+            } else {
+                self.parse_value(after, line, indent)?
+            };
+            items.push(item);
+        }
+        Ok(Value::Array(items))
+    }
+
+    /// The value after a key or `- ` on a line whose block sits at
+    /// `indent`: inline, or the block on the lines below it.
+    fn parse_value(
+        &mut self,
+        text: &'a str,
+        line: Line<'a>,
+        indent: usize,
+    ) -> Result<Value, String> {
+        if text.is_empty() {
+            self.skip_blank();
+            return match self.peek() {
+                Some(next) if next.indent > indent => self.parse_node(next.indent),
+                // A sequence may sit at its key's indentation
+                Some(next) if next.indent == indent && is_sequence_item(next.content()) => {
+                    self.parse_sequence(indent)
+                }
+                _ => Ok(Value::Null),
+            };
+        }
+        if text.starts_with(['&', '*', '!']) {
+            return Err(line.error("anchors, aliases and tags aren't supported"));
+        }
+        if text.starts_with(['|', '>']) {
+            return Ok(Value::String(self.parse_block_scalar(text, indent)));
+        }
+        if text.starts_with(['[', '{']) {
+            let mut flow = text.to_string();
+            while !flow_balanced(&flow) {
+                let Some(next) = self.peek() else {
+                    return Err(line.error("unterminated flow collection"));
+                };
+                self.pos += 1;
+                flow.push(' ');
+                flow.push_str(next.content().trim_start());
+            }
+            let mut flow_parser = FlowParser {
+                chars: flow.chars().collect(),
+                pos: 0,
+                line,
+            };
+            let value = flow_parser.parse_value()?;
+            flow_parser.skip_spaces();
+            if flow_parser.pos < flow_parser.chars.len() {
+                return Err(line.error("unexpected text after a flow collection"));
+            }
+            return Ok(value);
+        }
+        if text.starts_with(['"', '\'']) {
+            return parse_scalar(text, line);
+        }
+        // Plain scalars continue on more indented lines
+        let mut joined = text.to_string();
+        loop {
+            self.skip_blank();
+            match self.peek() {
+                Some(next) if next.indent > indent => {
+                    joined.push(' ');
+                    joined.push_str(next.content());
+                    self.pos += 1;
+                }
+                _ => break,
+            }
+        }
+        parse_scalar(&joined, line)
+    }
+
+    /// A `|` (literal) or `>` (folded) scalar whose lines are indented more
+    /// than `indent`.
+    fn parse_block_scalar(&mut self, header: &str, indent: usize) -> String {
+        let literal = header.starts_with('|');
+        let chomp = header[1..].chars().find(|c| matches!(c, '-' | '+'));
+        let start = self.pos;
+        let mut block_indent = None;
+        while let Some(line) = self.peek() {
+            if !line.raw.trim().is_empty() {
+                if line.indent <= indent {
+                    break;
+                }
+                block_indent.get_or_insert(line.indent);
+            }
+            self.pos += 1;
+        }
+        let at = block_indent.unwrap_or(indent + 1);
+        let mut lines: Vec<&str> = self.lines[start..self.pos]
+            .iter()
+            .map(|line| {
+                if line.raw.trim().is_empty() {
+                    ""
+                } else {
+                    &line.raw[at.min(line.indent)..]
+                }
+            })
+            .collect();
+        let kept = lines.len();
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        let trailing = kept - lines.len();
+
+        let mut out = String::new();
+        for (i, line) in lines.iter().enumerate() {
+            if i > 0 {
+                let paragraph = line.is_empty() || lines[i - 1].is_empty();
+                out.push(if literal || paragraph { '\n' } else { ' ' });
+            }
+            out.push_str(line);
+        }
+        match chomp {
+            Some('-') => {}
+            Some('+') => out.push_str(&"\n".repeat(trailing + 1)),
+            _ if !lines.is_empty() => out.push('\n'),
+            _ => {}
+        }
+        out
+    }
+}
+
+fn is_sequence_item(content: &str) -> bool {
+    content == "-" || content.starts_with("- ")
+}
+
+fn starts_flow_or_quote(text: &str) -> bool {
+    text.starts_with(['[', '{', '"', '\''])
+}
+
+/// Where the `:` ending a mapping key is: the first one followed by a
+/// space or the end of the line, outside quotes and brackets.
+fn key_separator(content: &str) -> Option<usize> {
+    let bytes = content.as_bytes();
+    if content.starts_with(['[', '{']) {
+        return None;
+    }
+    let mut i = 0;
+    if let Some(quote) = content.chars().next().filter(|c| matches!(c, '"' | '\'')) {
+        i = closing_quote(content, quote)? + 1;
+        let rest = content[i..].trim_start();
+        return rest.starts_with(':').then(|| content.len() - rest.len());
+    }
+    while i < bytes.len() {
+        if bytes[i] == b':' && (i + 1 == bytes.len() || bytes[i + 1] == b' ') {
+            return Some(i);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Byte index of the quote closing the one `text` starts with.
+fn closing_quote(text: &str, quote: char) -> Option<usize> {
+    let mut escaped = false;
+    let mut chars = text.char_indices().skip(1).peekable();
+    while let Some((i, c)) = chars.next() {
+        if quote == '"' {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                return Some(i);
+            }
+        } else if c == '\'' {
+            if chars.peek().is_some_and(|(_, next)| *next == '\'') {
+                chars.next();
+            } else {
+                return Some(i);
+            }
+        }
+    }
+    None
+}
+
+/// `text` up to a `#` that starts a comment: one at the start or after a
+/// space, and not inside a quoted scalar.
+fn strip_comment(text: &str) -> &str {
+    let mut quote: Option<char> = None;
+    let mut prev = ' ';
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match quote {
+            Some('"') if escaped => escaped = false,
+            Some('"') if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '#' && prev.is_whitespace() => return &text[..i],
+            None if matches!(c, '"' | '\'')
+                && matches!(prev, ' ' | '[' | '{' | ',' | ':' | '-') =>
+            {
+                quote = Some(c)
+            }
+            None => {}
+        }
+        prev = c;
+    }
+    text
+}
+
+fn flow_balanced(text: &str) -> bool {
+    let mut depth = 0i32;
+    let mut quote: Option<char> = None;
+    for c in text.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                '[' | '{' => depth += 1,
+                ']' | '}' => depth -= 1,
+                _ => {}
+            },
+        }
+    }
+    depth <= 0
+}
+
+/// A quoted or plain scalar taking up all of `text`.
+fn parse_scalar(text: &str, line: Line) -> Result<Value, String> {
+    if let Some(quote) = text.chars().next().filter(|c| matches!(c, '"' | '\'')) {
+        let close = closing_quote(text, quote).ok_or_else(|| line.error("unterminated string"))?;
+        if !text[close + 1..].trim().is_empty() {
+            return Err(line.error("unexpected text after a quoted string"));
+        }
+        return unquote(&text[1..close], quote, line).map(Value::String);
+    }
+    Ok(plain_scalar(text))
+}
+
+fn unquote(inner: &str, quote: char, line: Line) -> Result<String, String> {
+    if quote == '\'' {
+        return Ok(inner.replace("''", "'"));
+    }
+    let mut out = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some('0') => out.push('\0'),
+            Some('u') => {
+                let hex: String = chars.by_ref().take(4).collect();
+                let ch = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32);
+                out.push(ch.ok_or_else(|| line.error("invalid \\u escape"))?);
+            }
+            Some(other) => out.push(other),
+            None => return Err(line.error("unterminated escape")),
+        }
+    }
+    Ok(out)
+}
+
+/// Plain scalars resolved as in YAML 1.2's core schema.
+fn plain_scalar(text: &str) -> Value {
+    let text = text.trim();
+    match text {
+        "" | "~" | "null" | "Null" | "NULL" => return Value::Null,
+        "true" | "True" | "TRUE" => return Value::Bool(true),
+        "false" | "False" | "FALSE" => return Value::Bool(false),
+        _ => {}
+    }
+    let digits = text.trim_start_matches(['-', '+']);
+    if digits.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        if let Ok(n) = text.parse::<i64>() {
+            return Value::Number(n.into());
+        }
+        if let Some(n) = text.parse::<f64>().ok().and_then(Number::from_f64) {
+            return Value::Number(n);
+        }
+    }
+    Value::String(text.to_string())
+}
+
+/// `[a, b]` and `{k: v}` collections, which may nest.
+struct FlowParser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    line: Line<'a>,
+}
+
+impl FlowParser<'_> {
+    fn skip_spaces(&mut self) {
+        while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Value, String> {
+        self.skip_spaces();
+        match self.chars.get(self.pos) {
+            Some('[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_spaces();
+                    if self.eat(']') {
+                        return Ok(Value::Array(items));
+                    }
+                    items.push(self.parse_value()?);
+                    self.skip_spaces();
+                    if !self.eat(',') && self.chars.get(self.pos) != Some(&']') {
+                        return Err(self.line.error("expected ',' or ']'"));
+                    }
+                }
+            }
+            Some('{') => {
+                self.pos += 1;
+                let mut map = Map::new();
+                loop {
+                    self.skip_spaces();
+                    if self.eat('}') {
+                        return Ok(Value::Object(map));
+                    }
+                    let key = match self.parse_scalar(true)? {
+                        Value::String(key) => key,
+                        other => other.to_string(),
+                    };
+                    self.skip_spaces();
+                    let value = if self.eat(':') {
+                        self.parse_value()?
+                    } else {
+                        Value::Null
+                    };
+                    map.insert(key, value);
+                    self.skip_spaces();
+                    if !self.eat(',') && self.chars.get(self.pos) != Some(&'}') {
+                        return Err(self.line.error("expected ',' or '}'"));
+                    }
+                }
+            }
+            Some(_) => self.parse_scalar(false),
+            None => Err(self.line.error("unterminated flow collection")),
+        }
+    }
+
+    fn parse_scalar(&mut self, key: bool) -> Result<Value, String> {
+        let start = self.pos;
+        if let Some(&quote) = self.chars.get(self.pos).filter(|c| matches!(c, '"' | '\'')) {
+            let rest: String = self.chars[start..].iter().collect();
+            let close = closing_quote(&rest, quote)
+                .ok_or_else(|| self.line.error("unterminated string"))?;
+            self.pos += rest[..=close].chars().count();
+            return unquote(&rest[1..close], quote, self.line).map(Value::String);
+        }
+        while let Some(&c) = self.chars.get(self.pos) {
+            let ends_key = key && c == ':';
+            if matches!(c, ',' | ']' | '}') || ends_key {
+                break;
+            }
+            self.pos += 1;
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        Ok(plain_scalar(&text))
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.chars.get(self.pos) == Some(&c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_block_mappings_and_sequences() {
+        let text = r#"
+openapi: 3.0.3   # version
+info:
+  title: "Pet store"
+  version: '1.0'
+tags:
+- name: pets
+- name: it's
+  description: >
+    Folded
+    text
+
+paths:
+  /pets/{id}:
+    get:
+      parameters:
+        - name: id
+          in: path
+          required: true
+      responses:
+        200:
+          description: |
+            Line one
+            Line two
+"#;
+        let value = parse(text).unwrap();
+        assert_eq!(value["openapi"], json!("3.0.3"));
+        assert_eq!(
+            value["info"],
+            json!({"title": "Pet store", "version": "1.0"})
+        );
+        assert_eq!(
+            value["tags"][1],
+            json!({"name": "it's", "description": "Folded text\n"})
+        );
+        let get = &value["paths"]["/pets/{id}"]["get"];
+        assert_eq!(
+            get["parameters"][0],
+            json!({"name": "id", "in": "path", "required": true})
+        );
+        assert_eq!(
+            get["responses"]["200"]["description"],
+            json!("Line one\nLine two\n")
+        );
+    }
+
+    #[test]
+    fn test_scalars_and_flow_collections() {
+        let text = "a: [1, 2.5, \"x, y\", {k: v}]\nb: {required: [id, name]}\nc: ~\nd: 0.1.0\n\
+                    e: \"tab\\there # not a comment\"\nf: plain text\n  continued\ng: -3\n";
+        let value = parse(text).unwrap();
+        assert_eq!(value["a"], json!([1, 2.5, "x, y", {"k": "v"}]));
+        assert_eq!(value["b"], json!({"required": ["id", "name"]}));
+        assert_eq!(value["c"], Value::Null);
+        assert_eq!(value["d"], json!("0.1.0"));
+        assert_eq!(value["e"], json!("tab\there # not a comment"));
+        assert_eq!(value["f"], json!("plain text continued"));
+        assert_eq!(value["g"], json!(-3));
+    }
+
+    #[test]
+    fn test_unsupported_and_malformed() {
+        assert!(parse("a: &anchor 1\nb: *anchor")
+            .unwrap_err()
+            .contains("line 1"));
+        assert!(parse("a:\n  b: 1\n c: 2")
+            .unwrap_err()
+            .contains("indentation"));
+        assert!(parse("a: [1, 2").unwrap_err().contains("unterminated"));
+        assert_eq!(
+            parse("- a\n- b: 1\n  c: 2").unwrap(),
+            json!(["a", {"b": 1, "c": 2}])
+        );
+    }
+}